@@ -0,0 +1,55 @@
+//! Sign a release binary with the release key, for CI.
+//!
+//! The key is the base64-encoded 32-byte Ed25519 seed in the `RELEASE_SIGNING_KEY` environment variable; generate one with
+//! `head -c32 /dev/urandom | base64`, and pin the public half, printed by `cargo run --example sign-release -- --public`, as
+//! `tweetr::ops::update::RELEASE_KEY`.
+//!
+//! `cargo run --example sign-release -- <binary>` prints the base64-encoded signature `tweetr update` verifies the binary with.
+//! Without the key, it fails, so a release job can't ship an unsigned binary.
+
+
+extern crate rustc_serialize;
+extern crate crypto;
+
+use rustc_serialize::base64::{FromBase64, ToBase64, STANDARD};
+use std::io::{Read, Write, stderr};
+use std::process::exit;
+use crypto::ed25519;
+use std::fs::File;
+use std::env;
+
+
+fn main() {
+    let result = actual_main();
+    exit(result);
+}
+
+fn actual_main() -> i32 {
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.len() != 1 {
+        writeln!(stderr(), "Usage: sign-release (--public | <binary>)").unwrap();
+        return 2;
+    }
+
+    let seed = match env::var("RELEASE_SIGNING_KEY").ok().and_then(|k| k.trim().from_base64().ok()) {
+        Some(ref seed) if seed.len() == 32 => seed.clone(),
+        Some(_) | None => {
+            writeln!(stderr(), "RELEASE_SIGNING_KEY isn't set to a base64-encoded 32-byte seed, refusing to sign").unwrap();
+            return 1;
+        }
+    };
+    let (secret, public) = ed25519::keypair(&seed);
+
+    if args[0] == "--public" {
+        println!("{}", public.to_base64(STANDARD));
+        return 0;
+    }
+
+    let mut data = vec![];
+    if let Err(e) = File::open(&args[0]).and_then(|mut f| f.read_to_end(&mut data)) {
+        writeln!(stderr(), "Couldn't read {}: {}", args[0], e).unwrap();
+        return 1;
+    }
+    println!("{}", ed25519::signature(&data, &secret).to_base64(STANDARD));
+    0
+}
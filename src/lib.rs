@@ -21,22 +21,42 @@
 //! 2 - required data or file needs to be created by running the specified filesysstem
 //! 3 - an error was returned by the Twitter API
 //! 4 - failed to parse the specified file
+//! 5 - a hook rejected a tweet
+//! 6 - the files can't be accessed as needed
+//! 7 - a sink failed to deliver a tweet
+//! 12 - a file couldn't be read or written
 //! ```
 //!
+//! When multiple independent operations fail, all errors are listed and the exit value is the most severe one's,
+//! configuration problems (1, 2, 4, 6, 12) being more severe than delivery failures (3, 7), which are more severe than
+//! rejections (5).
+//!
 //! ## Executable manpage
 //!
 //! [All manpages](https://rawcdn.githack.com/nabijaczleweli/tweetr/man/index.html)
 
 
 extern crate rustc_serialize;
+extern crate crypto;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "network")]
 extern crate egg_mode;
+#[cfg(feature = "network")]
+extern crate hyper;
 extern crate chrono;
 extern crate regex;
+extern crate rand;
 #[macro_use]
 extern crate clap;
 extern crate toml;
+#[cfg(feature = "network")]
+extern crate url;
+extern crate atty;
+#[cfg(unix)]
+extern crate libc;
+#[cfg(feature = "tui")]
+extern crate termion;
 
 mod outcome;
 
@@ -1,10 +1,15 @@
 extern crate tweetr;
+extern crate chrono;
 
-use std::thread;
+use std::cmp;
+use std::fs;
 use std::process::exit;
-use std::path::PathBuf;
-use std::time::Duration;
-use std::io::{stdin, stdout, stderr};
+use std::thread;
+use std::path::{PathBuf, Path};
+use std::time::{Duration, Instant};
+use std::io::{BufRead, Read, Write, Stderr, stdin, stderr};
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::Timelike;
 
 
 fn main() {
@@ -14,99 +19,2369 @@ fn main() {
 
 fn actual_main() -> i32 {
     let opts = tweetr::options::Options::parse();
+    tweetr::ops::diagnostics::use_verbosity(opts.verbosity);
+    tweetr::util::terminal::watch_resize();
+    let (report, json) = (opts.report.clone(), opts.json);
+    let run = if report.is_some() || json {
+        Some(start_run(&opts))
+    } else {
+        None
+    };
 
-    let err = match opts.subsystem.clone() {
-            tweetr::options::Subsystem::Init { force } => init_main(opts, force),
-            tweetr::options::Subsystem::AddUser { verbose } => add_user_main(opts, verbose),
-            tweetr::options::Subsystem::QueueTweet { file_to_load } => queue_tweet_main(opts, file_to_load),
-            tweetr::options::Subsystem::StartDaemon { delay, verbose } => start_daemon_main(opts, delay, verbose),
-        }
+    let err = load_catalog(&opts)
+        .map(|_| use_config(&opts))
+        .and_then(|_| if opts.read_only && opts.subsystem.writes() {
+            Err(tweetr::Outcome::PreflightFailed(vec![format!("the {} subsystem writes to {}, which --read-only forbids",
+                                                              opts.subsystem.name(),
+                                                              opts.config_dir.0)]))
+        } else {
+            Ok(())
+        })
+        .and_then(|_| opts.transcript.as_ref().map(|t| tweetr::ops::transcript::start(t)).unwrap_or(Ok(())))
+        .and_then(|_| match opts.subsystem.clone() {
+            tweetr::options::Subsystem::Init { force, app } => init_main(opts, force, app),
+            tweetr::options::Subsystem::AddUser { app } => add_user_main(opts, app),
+            tweetr::options::Subsystem::RotateToken { account } => rotate_token_main(opts, account),
+            tweetr::options::Subsystem::ListUsers { check } => list_users_main(opts, check),
+            tweetr::options::Subsystem::RemoveUser { account, force, orphan } => remove_user_main(opts, account, force, orphan),
+            tweetr::options::Subsystem::QueueTweet { file_to_load,
+                                                     validate,
+                                                     check_mentions,
+                                                     auto_split,
+                                                     geo,
+                                                     editor,
+                                                     best_time,
+                                                     template,
+                                                     vars,
+                                                     preview,
+                                                     dry_run,
+                                                     encoding } => {
+                queue_tweet_main(opts,
+                                 file_to_load,
+                                 validate,
+                                 check_mentions,
+                                 auto_split,
+                                 geo,
+                                 editor,
+                                 best_time,
+                                 template,
+                                 vars,
+                                 preview,
+                                 dry_run,
+                                 encoding)
+            }
+            tweetr::options::Subsystem::QuickQueue { spec, delimiter } => quick_queue_main(opts, spec, delimiter),
+            tweetr::options::Subsystem::PostNow { account, content } => post_now_main(opts, account, content),
+            tweetr::options::Subsystem::ReleaseAnnounce { source, template, account, version, url, time, highlights, dry_run } => {
+                release_announce_main(opts, source, template, account, version, url, time, highlights, dry_run)
+            }
+            tweetr::options::Subsystem::Setup { test_tweet, dry_run } => setup_main(opts, test_tweet, dry_run),
+            tweetr::options::Subsystem::SuggestTime { author, count } => suggest_time_main(opts, author, count),
+            tweetr::options::Subsystem::Calendar { month, width, colour } => calendar_main(opts, month, width, colour),
+            tweetr::options::Subsystem::Simulate { from, to } => simulate_main(opts, from, to),
+            tweetr::options::Subsystem::Doctor { offline } => doctor_main(opts, offline),
+            tweetr::options::Subsystem::Prune { keep, dry_run } => prune_main(opts, keep, dry_run),
+            tweetr::options::Subsystem::GcMedia { keep, dry_run } => gc_media_main(opts, keep, dry_run),
+            tweetr::options::Subsystem::ShowEffectiveConfig { author } => show_effective_config_main(opts, author),
+            tweetr::options::Subsystem::ExportPosted { format, since } => export_posted_main(opts, format, since),
+            tweetr::options::Subsystem::PrunePosted { older_than, account, metrics, encoding, min_engagement, interval, dry_run } => {
+                prune_posted_main(opts, older_than, account, metrics, encoding, min_engagement, interval, dry_run)
+            }
+            tweetr::options::Subsystem::Reconcile { account } => reconcile_main(opts, account),
+            tweetr::options::Subsystem::FmtQueue => fmt_queue_main(opts),
+            tweetr::options::Subsystem::ShiftQueue { by, from, author, tag } => shift_queue_main(opts, by, from, author, tag),
+            tweetr::options::Subsystem::RequeueThread { thread } => requeue_thread_main(opts, thread),
+            tweetr::options::Subsystem::History { id } => history_main(opts, id),
+            tweetr::options::Subsystem::ListTemplates => list_templates_main(opts),
+            tweetr::options::Subsystem::Schema { format, example } => schema_main(format, example),
+            tweetr::options::Subsystem::Update { check } => update_main(check),
+            tweetr::options::Subsystem::Validate => validate_main(opts),
+            #[cfg(feature = "tui")]
+            tweetr::options::Subsystem::Tui => tui_main(opts),
+            tweetr::options::Subsystem::PostAll { up_to, interval } => post_all_main(opts, up_to, interval),
+            tweetr::options::Subsystem::StartDaemon { delay, events, notify, evergreen } => start_daemon_main(opts, delay, events, notify, evergreen),
+        })
         .err()
         .unwrap_or(tweetr::Outcome::NoError);
-    err.print_error(&mut stderr());
+    err.print_error(&mut tweetr::ops::transcript::Transcribed::new(stderr()));
+    tweetr::ops::transcript::stop();
+
+    let run = run.map(finish_run);
+    let reported = match (run.as_ref(), report) {
+        (Some(run), Some(path)) => Some(write_report(run, &path, &err)),
+        _ => None,
+    };
+    if let (Some(run), true) = (run, json) {
+        print_results(run, &err);
+    }
+    match reported {
+        Some(Err(out)) => {
+            out.print_error(&mut stderr());
+            if err == tweetr::Outcome::NoError {
+                return out.exit_value();
+            }
+        }
+        Some(Ok(())) | None => (),
+    }
     err.exit_value()
 }
 
-fn init_main(opts: tweetr::options::Options, force: bool) -> Result<(), tweetr::Outcome> {
-    let app_path = try!(tweetr::ops::init::verify(&opts.config_dir, force));
+/// The queue before and after the subsystem ran, and what it archived meanwhile, for `--report` and `--json`.
+struct Run {
+    config_dir: PathBuf,
+    subsystem: &'static str,
+    started: chrono::DateTime<chrono::FixedOffset>,
+    start: Instant,
+    duration: Duration,
+    before: Vec<tweetr::ops::QueuedTweet>,
+    after: Vec<tweetr::ops::QueuedTweet>,
+    archived: Vec<tweetr::ops::QueuedTweet>,
+}
+
+fn start_run(opts: &tweetr::options::Options) -> Run {
+    tweetr::ops::report::collect_warnings();
+    if opts.json {
+        tweetr::ops::results::collect();
+    }
+    let now = chrono::Local::now();
+    Run {
+        config_dir: opts.config_dir.1.clone(),
+        subsystem: opts.subsystem.name(),
+        started: now.with_timezone(now.offset()),
+        start: Instant::now(),
+        duration: Duration::from_secs(0),
+        before: tweetr::ops::QueuedTweet::read(&opts.config_dir.1.join("tweets.toml")).unwrap_or_default(),
+        after: vec![],
+        archived: vec![],
+    }
+}
+
+fn finish_run(run: Run) -> Run {
+    Run {
+        duration: run.start.elapsed(),
+        after: tweetr::ops::QueuedTweet::read(&run.config_dir.join("tweets.toml")).unwrap_or_default(),
+        archived: tweetr::ops::archive::read_since(&run.config_dir, run.started).unwrap_or_default(),
+        ..run
+    }
+}
+
+fn write_report(run: &Run, path: &Path, outcome: &tweetr::Outcome) -> Result<(), tweetr::Outcome> {
+    tweetr::ops::report::Report::new(run.subsystem,
+                                     run.started,
+                                     run.duration,
+                                     outcome,
+                                     &run.before,
+                                     &run.after,
+                                     &run.archived,
+                                     tweetr::ops::report::take_warnings())
+        .write(path)
+}
+
+fn print_results(run: Run, outcome: &tweetr::Outcome) {
+    tweetr::ops::results::Results::new(run.subsystem,
+                                       outcome,
+                                       &run.before,
+                                       &run.after,
+                                       &run.archived,
+                                       tweetr::ops::results::take())
+        .print(&mut std::io::stdout());
+}
+
+fn load_catalog(opts: &tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    if let Some(locale) = tweetr::ops::l10n::locale(opts.locale.as_ref().map(String::as_str)) {
+        if let Some(catalog) = try!(tweetr::ops::l10n::read_catalog(&opts.config_dir.1, &locale)) {
+            tweetr::ops::l10n::use_catalog(catalog);
+        }
+    }
+
+    Ok(())
+}
+
+fn use_config(opts: &tweetr::options::Options) {
+    let config_path = tweetr::ops::Config::path(&opts.config_dir.1);
+    let config = match tweetr::ops::Config::read(&config_path) {
+        Ok(config) => config,
+        Err(_) => {
+            // Broken configuration is reported in full by the subsystems that need it
+            warn(&mut stderr(),
+                 &format!("couldn't read {}, so the defaults are used instead of its bind_address, timeouts and formats",
+                          config_path.display()));
+            tweetr::ops::Config::default()
+        }
+    };
+    tweetr::ops::network::use_timeouts(config.timeouts().overridden(opts.connect_timeout, opts.read_timeout, opts.write_timeout));
+    tweetr::ops::network::use_bind_address(config.bind_address());
+    tweetr::ops::retry::use_policy(config.file_retries());
+    tweetr::ops::l10n::use_formats(config.formats());
+    tweetr::ops::schedule::use_horizon(config.horizon());
+    tweetr::ops::queue_tweet::use_confirmation(opts.confirmation);
+    tweetr::ops::http_cache::use_dir(if opts.read_only {
+        None
+    } else {
+        Some(tweetr::ops::http_cache::path(&opts.config_dir.1))
+    });
+}
 
+fn init_main(opts: tweetr::options::Options, force: bool, app: Option<String>) -> Result<(), tweetr::Outcome> {
     let stdin = stdin();
-    let mut lock = stdin.lock();
+    let mut lock = tweetr::ops::transcript::Transcribed::new(stdin.lock());
+
+    match app {
+        Some(name) => {
+            let conflict = tweetr::ops::init::verify_named(&opts.config_dir, &name, force).err();
+            let (app_path, apps) = try!(tweetr::ops::init::verify_named(&opts.config_dir, &name, true));
+            if let Some(tweetr::Outcome::OverrideNoForce(ref fname)) = conflict {
+                try!(refuse_conflict_early(&opts, &mut lock, fname));
+            }
+
+            let data = tweetr::ops::init::get_data(&mut lock, &mut transcribed_stdout());
+            let apps = match conflict {
+                Some(tweetr::Outcome::OverrideNoForce(fname)) => try!(resolve_app_conflict(&opts, &mut lock, fname, &apps, Some(&name), &data)),
+                Some(out) => return Err(out),
+                None => tweetr::ops::init::apply(&apps, Some(&name), &data, tweetr::util::Resolution::Merge).unwrap(),
+            };
+            try!(apps.write(&app_path));
+            tweetr::ops::init::record_created(Some(&name));
+        }
+        None => {
+            let existing = match tweetr::ops::init::verify(&opts.config_dir, force) {
+                Err(tweetr::Outcome::OverrideNoForce(fname)) => {
+                    match tweetr::ops::Apps::read_verbatim(&opts.config_dir.1.join("app.toml")) {
+                        Ok(apps) => {
+                            try!(refuse_conflict_early(&opts, &mut lock, &fname));
+                            Some((fname, apps))
+                        }
+                        // Can't merge into what can't be read, so only offer replacing it
+                        Err(_) => {
+                            if !confirm_override(&opts, &mut lock, Err(tweetr::Outcome::OverrideNoForce(fname.clone()))) {
+                                return Err(tweetr::Outcome::OverrideNoForce(fname));
+                            }
+                            None
+                        }
+                    }
+                }
+                Err(out) => return Err(out),
+                Ok(_) => None,
+            };
+            let app_path = try!(tweetr::ops::init::verify(&opts.config_dir, true));
 
-    let data = tweetr::ops::init::get_data(&mut lock, &mut stdout());
-    data.write(&app_path);
+            let data = tweetr::ops::init::get_data(&mut lock, &mut transcribed_stdout());
+            match existing {
+                Some((fname, apps)) => try!(try!(resolve_app_conflict(&opts, &mut lock, fname, &apps, None, &data)).write(&app_path)),
+                None => try!(data.write(&app_path)),
+            }
+            tweetr::ops::init::record_created(None);
+        }
+    }
 
     Ok(())
 }
 
-fn add_user_main(opts: tweetr::options::Options, verbose: bool) -> Result<(), tweetr::Outcome> {
+/// Abort resolving a conflict before asking for data, if it'd be aborted without asking anyway.
+fn refuse_conflict_early<R: BufRead>(opts: &tweetr::options::Options, input: &mut R, fname: &str) -> Result<(), tweetr::Outcome> {
+    if opts.confirmation.refuses() {
+        tweetr::util::resolve(opts.confirmation, input, &mut transcribed_stdout(), &tweetr::ops::l10n::tr("prompt_resolve", &[]));
+        Err(tweetr::Outcome::OverrideNoForce(fname.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Show what merging and replacing the specified apps would change, and apply the one chosen, failing if aborted.
+fn resolve_app_conflict<R: BufRead>(opts: &tweetr::options::Options, input: &mut R, fname: String, apps: &tweetr::ops::Apps, name: Option<&str>,
+                                    data: &tweetr::ops::AppTokens)
+                                    -> Result<tweetr::ops::Apps, tweetr::Outcome> {
+    tweetr::ops::init::print_changes(&mut transcribed_stdout(), &fname, apps, name, data);
+    let resolution = tweetr::util::resolve(opts.confirmation, input, &mut transcribed_stdout(), &tweetr::ops::l10n::tr("prompt_resolve", &[]));
+    tweetr::ops::init::apply(apps, name, data, resolution).ok_or(tweetr::Outcome::OverrideNoForce(fname))
+}
+
+/// Ask whether to override the file the specified verification refused to, if it did.
+fn confirm_override<R: BufRead>(opts: &tweetr::options::Options, input: &mut R, verified: Result<(), tweetr::Outcome>) -> bool {
+    match verified {
+        Err(tweetr::Outcome::OverrideNoForce(fname)) => {
+            tweetr::util::confirm(opts.confirmation, input, &mut transcribed_stdout(), &tweetr::ops::l10n::tr("prompt_override", &[("fname", &fname)]))
+        }
+        _ => false,
+    }
+}
+
+fn add_user_main(opts: tweetr::options::Options, app: Option<String>) -> Result<(), tweetr::Outcome> {
+    let verbose = tweetr::ops::diagnostics::verbose();
     let (app_path, users_path) = try!(tweetr::ops::add_user::verify(&opts.config_dir));
-    let app = try!(tweetr::ops::AppTokens::read(&app_path).map_err(Option::unwrap));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let app_tokens = try!(apps.get(app.as_ref().map(|a| &a[..]))).clone();
 
     let stdin = stdin();
-    let mut lock = stdin.lock();
+    let mut lock = tweetr::ops::transcript::Transcribed::new(stdin.lock());
 
-    let user = try!(tweetr::ops::add_user::authorise(&mut lock, &mut stdout(), app, verbose));
-    println!("");
-    tweetr::ops::add_user::print_success_message(&mut stdout(), &user, verbose);
+    let mut user = try!(tweetr::ops::add_user::authorise(&mut lock, &mut transcribed_stdout(), app_tokens, verbose));
+    user.app = app;
+    user.added = Some(tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock).to_rfc3339());
+    writeln!(transcribed_stdout(), "").unwrap();
+
+    let existing = if users_path.exists() {
+        try!(tweetr::ops::User::read_verbatim(&users_path).map_err(Option::unwrap)).into_iter().find(|u| u.name == user.name)
+    } else {
+        None
+    };
+    let user = match existing {
+        Some(existing) => {
+            let fname = PathBuf::from(&opts.config_dir.0).join("users.toml").to_str().unwrap().replace("\\", "/");
+            tweetr::ops::add_user::print_changes(&mut transcribed_stdout(), &fname, &existing, &user);
+            let resolution = tweetr::util::resolve(opts.confirmation, &mut lock, &mut transcribed_stdout(), &tweetr::ops::l10n::tr("prompt_resolve", &[]));
+            match tweetr::ops::add_user::apply(&existing, user, resolution) {
+                Some(user) => user,
+                None => return Ok(()),
+            }
+        }
+        None => user,
+    };
+    tweetr::ops::add_user::print_success_message(&mut transcribed_stdout(), &mut diagnostics(), &user, verbose);
 
     Err(tweetr::ops::add_user::append_user(&users_path, user))
 }
 
-fn queue_tweet_main(opts: tweetr::options::Options, file_to_load: Option<PathBuf>) -> Result<(), tweetr::Outcome> {
+fn rotate_token_main(opts: tweetr::options::Options, account: String) -> Result<(), tweetr::Outcome> {
+    let verbose = tweetr::ops::diagnostics::verbose();
+    let (app_path, users_path) = try!(tweetr::ops::rotate_token::verify(&opts.config_dir));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    let app = try!(tweetr::ops::rotate_token::find_user(&users, &account).map(|idx| users[idx].app.clone()));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let app_tokens = try!(apps.get(app.as_ref().map(|a| &a[..]))).clone();
+
+    let stdin = stdin();
+    let mut lock = stdin.lock();
+
+    let authorised = try!(tweetr::ops::add_user::authorise(&mut lock, &mut stdout(), app_tokens, verbose));
+    let user = try!(tweetr::ops::rotate_token::replace_tokens(&users_path, &account, &authorised));
+    writeln!(stdout(), "").unwrap();
+    tweetr::ops::rotate_token::print_success_message(&mut stdout(), &mut diagnostics(), &user, verbose);
+
+    Ok(())
+}
+
+fn list_users_main(opts: tweetr::options::Options, check: bool) -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path) = try!(tweetr::ops::list_users::verify(&opts.config_dir, check));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    let apps = match app_path {
+        Some(app_path) => Some(try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap))),
+        None => None,
+    };
+
+    if users.is_empty() {
+        writeln!(diagnostics(), "No users, add one with add-user.").unwrap();
+        return Ok(());
+    }
+    let users: Vec<_> = users.iter()
+        .map(|user| (user, apps.as_ref().map(|apps| tweetr::ops::doctor::verify_credentials(apps, user))))
+        .collect();
+    tweetr::ops::list_users::print_users(&mut stdout(), &users, tweetr::util::terminal::terminal_width());
+
+    Ok(())
+}
+
+fn remove_user_main(opts: tweetr::options::Options, account: String, force: bool, orphan: bool) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::remove_user::verify(&opts.config_dir));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    let user = users[try!(tweetr::ops::rotate_token::find_user(&users, &account))].clone();
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    let orphaned = tweetr::ops::remove_user::queued_by(&tweets, &account);
+    if !orphan {
+        try!(tweetr::ops::remove_user::check_orphans(&account, &orphaned));
+    }
+
+    if !force {
+        let stdin = stdin();
+        if !tweetr::ops::remove_user::prompt_confirm(opts.confirmation, &mut stdin.lock(), &mut stderr(), &user) {
+            return Ok(());
+        }
+    }
+
+    let user = try!(tweetr::ops::remove_user::remove_user(&users_path, &account));
+    tweetr::ops::remove_user::print_success_message(&mut diagnostics(), &user, orphaned.len());
+
+    Ok(())
+}
+
+fn queue_tweet_main(opts: tweetr::options::Options, file_to_load: Option<PathBuf>, validate: bool, check_mentions: bool, auto_split: bool, geo: bool,
+                    editor: bool, best_time: bool, template: Option<String>, vars: BTreeMap<String, String>, preview: bool, dry_run: bool,
+                    encoding: Option<tweetr::util::encoding::Encoding>)
+                    -> Result<(), tweetr::Outcome> {
     let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let templated = match template {
+        Some(name) => {
+            let templates = match tweetr::ops::Template::read(&tweetr::ops::Template::path(&opts.config_dir.1)) {
+                Ok(templates) => templates,
+                Err(Some(out)) => return Err(out),
+                Err(None) => vec![],
+            };
+            Some(try!(tweetr::ops::queue_tweet::template(&templates, &name, &vars)))
+        }
+        None => None,
+    };
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let hooks_dir = config.hooks_dir(&opts.config_dir.1);
+    let dictionary = match config.dictionary(&opts.config_dir.1) {
+        Some(ref path) if validate => {
+            match tweetr::ops::queue_tweet::load_dictionary(path) {
+                Ok(dictionary) => Some(dictionary),
+                Err(e) => {
+                    warn(&mut stderr(), &format!("couldn't read the dictionary {}, so spelling isn't checked: {}", path.display(), e));
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let accounts = if validate && check_mentions {
+        let (app_path, users_path) = try!(tweetr::ops::list_users::verify(&opts.config_dir, true));
+        Some((try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap)),
+              try!(tweetr::ops::Apps::read(&app_path.unwrap()).map_err(Option::unwrap))))
+    } else {
+        None
+    };
+    let pipeline = if preview {
+        Some(try!(tweetr::ops::transform::pipeline(&config, &opts.config_dir.1)))
+    } else {
+        None
+    };
+
+    // Only for the daily caps and style rules, so nothing to warn about without any users
+    let users = tweetr::ops::User::read(&opts.config_dir.1.join("users.toml")).unwrap_or_default();
+    let settings = config.settings();
+    let collision_window = config.collision_window();
+    let duplicate_window = config.duplicate_window();
+    let archived = recent_archive(&opts.config_dir.1, duplicate_window);
+    let grid = if best_time {
+        Some(try!(tweetr::ops::suggest_time::grid(&config, &opts.config_dir.1)))
+    } else {
+        None
+    };
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+        .map_err(Option::unwrap));
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    let tweets_to_queue = match file_to_load {
+        Some(ref ftl) => {
+            let mut ttq = try!(tweetr::ops::QueuedTweet::read_relative_to(ftl, &tweets, encoding).map_err(Option::unwrap));
+            let mut imports = vec![];
+            if grid.is_none() {
+                let now = chrono::Local::now();
+                let now = now.with_timezone(now.offset());
+                ttq.retain(|t| match tweetr::ops::schedule::within_horizon(t.time, now) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        if dry_run {
+                            imports.push((t.clone(), tweetr::ops::queue_tweet::Import::Invalid(vec![format!("It's {}", e)])));
+                        } else {
+                            writeln!(stderr(), "Can't schedule the tweet \"{}\": it's {}, not queueing it.", t.content, e).unwrap();
+                        }
+                        false
+                    }
+                });
+            }
+            let mut lint_blocked = vec![false; ttq.len()];
+            for i in 0..ttq.len() {
+                let queue: Vec<_> = tweets.iter().chain(&ttq[..i]).cloned().collect();
+                if let Some(ref grid) = grid {
+                    ttq[i].time = best_slot(grid, &ttq[i], &queue, collision_window);
+                    ttq[i].after = None;
+                }
+
+                let tweet = &ttq[i];
+                if let Some(ref pipeline) = pipeline {
+                    tweetr::ops::transform::print_preview(&mut transcribed_stdout(), pipeline, &tweet.content);
+                    tweetr::ops::backend::print_variants(&mut transcribed_stdout(), tweet);
+                }
+
+                if dry_run {
+                    let warnings = if validate {
+                        let mut warnings = validation_warnings(tweet, &dictionary, &accounts, config.content_warning_format());
+                        warnings.extend(daily_cap_warning(tweet, &queue, &users, &settings));
+                        for warning in &warnings {
+                            tweetr::ops::report::warn(warning);
+                        }
+                        warnings
+                    } else {
+                        vec![]
+                    };
+                    let warnings: Vec<_> = warnings.into_iter().chain(lint_violations(tweet, &users).0).collect();
+                    let known: Vec<_> = archived.iter().chain(&queue).cloned().collect();
+                    let duplicates = tweetr::ops::queue_tweet::duplicates(tweet, &known, duplicate_window);
+                    imports.push((tweet.clone(),
+                                  if !warnings.is_empty() {
+                                      tweetr::ops::queue_tweet::Import::Invalid(warnings)
+                                  } else if !duplicates.is_empty() {
+                                      tweetr::ops::queue_tweet::Import::Duplicate(duplicates.into_iter().cloned().collect())
+                                  } else {
+                                      tweetr::ops::queue_tweet::Import::Added
+                                  }));
+                    continue;
+                }
+
+                if validate {
+                    validate_tweet(tweet, &dictionary, &accounts, config.content_warning_format());
+                }
+                if !lint_tweet(&mut stderr(), tweet, &users) {
+                    lint_blocked[i] = true;
+                    continue;
+                }
+                warn_duplicates(&mut stderr(), tweet, &queue, &archived, duplicate_window);
+                if let Some(warning) = daily_cap_warning(tweet, &queue, &users, &settings) {
+                    warn(&mut stderr(), &warning);
+                }
+                let collisions = tweetr::ops::queue_tweet::collisions(tweet, &queue, collision_window);
+                if !collisions.is_empty() {
+                    let free_slot = tweetr::ops::queue_tweet::nearest_free_slot(tweet, &queue, collision_window, &tweetr::ops::clock::SystemClock);
+                    tweetr::ops::queue_tweet::print_collisions(&mut stderr(), tweet, &collisions, collision_window, free_slot);
+                }
+            }
+
+            if dry_run {
+                imports.sort_by_key(|&(ref t, _)| t.time);
+                tweetr::ops::queue_tweet::print_import_preview(&mut transcribed_stdout(), &imports);
+                return Ok(());
+            }
 
-    let mut tweets_to_queue = match file_to_load {
-        Some(ftl) => try!(tweetr::ops::QueuedTweet::read(&ftl).map_err(Option::unwrap)),
+            ttq.into_iter()
+                .zip(lint_blocked)
+                .filter(|&(_, blocked)| !blocked)
+                .filter_map(|(mut t, _)| match tweetr::ops::media::localise(&mut t, &opts.config_dir.1) {
+                    Ok(()) => Some(t),
+                    Err(e) => {
+                        writeln!(stderr(), "Can't download the media of the tweet \"{}\": {}, not queueing it.", t.content, e).unwrap();
+                        None
+                    }
+                })
+                .collect()
+        }
         None => {
             let stdin = stdin();
-            let mut lock = stdin.lock();
+            let mut lock = tweetr::ops::transcript::Transcribed::new(stdin.lock());
+
+            let editor = if editor {
+                Some(tweetr::ops::queue_tweet::editor())
+            } else {
+                None
+            };
+
+            let mut ttq: Vec<tweetr::ops::QueuedTweet> = Vec::new();
+            while let Some(mut tweet) = match (&templated, &editor) {
+                (&Some(ref content), _) => tweetr::ops::queue_tweet::get_templated_tweet(&mut lock, &mut transcribed_stdout(), content, !best_time),
+                (&None, &Some(ref editor)) => tweetr::ops::queue_tweet::get_edited_tweet(&mut lock, &mut transcribed_stdout(), editor, !best_time),
+                (&None, &None) if best_time => tweetr::ops::queue_tweet::get_untimed_tweet(&mut lock, &mut transcribed_stdout()),
+                (&None, &None) => tweetr::ops::queue_tweet::get_tweet(&mut lock, &mut transcribed_stdout()),
+            } {
+                if geo {
+                    let (coordinates, place_id) = tweetr::ops::queue_tweet::get_location(&mut lock, &mut transcribed_stdout());
+                    tweet.coordinates = coordinates;
+                    tweet.place_id = place_id;
+                }
+                if validate {
+                    validate_tweet(&tweet, &dictionary, &accounts, config.content_warning_format());
+                    if !tweetr::ops::queue_tweet::invisible_chars(&tweet.content).is_empty() &&
+                       tweetr::ops::queue_tweet::prompt_strip(&mut lock, &mut transcribed_stdout()) {
+                        tweet.content = tweetr::ops::queue_tweet::strip_invisible(&tweet.content);
+                    }
+                }
+                if let Some(ref pipeline) = pipeline {
+                    tweetr::ops::transform::print_preview(&mut transcribed_stdout(), pipeline, &tweet.content);
+                    tweetr::ops::backend::print_variants(&mut transcribed_stdout(), &tweet);
+                }
+                if !lint_tweet(&mut transcribed_stdout(), &tweet, &users) {
+                    writeln!(transcribed_stdout(), "").unwrap();
+                    continue;
+                }
+
+                let queue: Vec<_> = tweets.iter().chain(&ttq).cloned().collect();
+                if tweet.after.is_some() {
+                    let mut anchored = vec![tweet];
+                    if let Some((_, _, e)) = tweetr::ops::QueuedTweet::schedule(&mut anchored, &queue).into_iter().next() {
+                        writeln!(transcribed_stdout(), "Can't schedule the tweet: {}, not queueing it.\n", e).unwrap();
+                        continue;
+                    }
+                    tweet = anchored.pop().unwrap();
+                    let now = chrono::Local::now();
+                    if let Err(e) = tweetr::ops::schedule::within_horizon(tweet.time, now.with_timezone(now.offset())) {
+                        writeln!(transcribed_stdout(), "Can't schedule the tweet: it's {}, not queueing it.\n", e).unwrap();
+                        continue;
+                    }
+                    writeln!(transcribed_stdout(), "Scheduled for {}.\n", tweetr::ops::l10n::format_datetime(&tweet.time)).unwrap();
+                }
+                if let Some(ref grid) = grid {
+                    tweet.time = best_slot(grid, &tweet, &queue, collision_window);
+                    writeln!(transcribed_stdout(), "Scheduled for {}.\n", tweetr::ops::l10n::format_datetime(&tweet.time)).unwrap();
+                }
+
+                warn_duplicates(&mut transcribed_stdout(), &tweet, &queue, &archived, duplicate_window);
+                if let Some(warning) = daily_cap_warning(&tweet, &queue, &users, &settings) {
+                    warn(&mut transcribed_stdout(), &warning);
+                    writeln!(transcribed_stdout(), "").unwrap();
+                }
+                let free_slot = {
+                    let collisions = tweetr::ops::queue_tweet::collisions(&tweet, &queue, collision_window);
+                    if collisions.is_empty() {
+                        None
+                    } else {
+                        let free_slot = tweetr::ops::queue_tweet::nearest_free_slot(&tweet, &queue, collision_window, &tweetr::ops::clock::SystemClock);
+                        tweetr::ops::queue_tweet::print_collisions(&mut transcribed_stdout(), &tweet, &collisions, collision_window, free_slot);
+                        Some(free_slot)
+                    }
+                };
+                if let Some(free_slot) = free_slot {
+                    if tweetr::ops::queue_tweet::prompt_shift(&mut lock, &mut transcribed_stdout()) {
+                        tweet.time = free_slot;
+                    }
+                }
 
-            let mut ttq = Vec::new();
-            while let Some(tweet) = tweetr::ops::queue_tweet::get_tweet(&mut lock, &mut stdout()) {
                 ttq.push(tweet);
             }
             ttq
         }
     };
 
-    let mut tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
+    let tweets_to_queue = if auto_split {
+        tweets_to_queue.into_iter().flat_map(|t| tweetr::ops::queue_tweet::auto_split(t, config.thread_numbering())).collect()
+    } else {
+        tweets_to_queue
+    };
+    let mut tweets_to_queue = tweets_to_queue.into_iter().flat_map(|t| run_hook(&hooks_dir, tweetr::ops::hooks::Hook::PreQueue, t)).collect();
+
+    let before = tweets.clone();
     tweets.append(&mut tweets_to_queue);
     tweets.sort();
 
-    tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
+    journal_changes(&opts.config_dir.1, "queue-tweet", &before, &tweets, &[]);
+    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    Ok(())
+}
+
+fn quick_queue_main(opts: tweetr::options::Options, spec: String, delimiter: Option<String>) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let now = chrono::Local::now();
+    let tweet = try!(tweetr::ops::quick_queue::parse(&spec,
+                                                     delimiter.as_ref().map(|d| &d[..]).unwrap_or(config.quick_delimiter()),
+                                                     now.with_timezone(now.offset()))
+        .map_err(tweetr::Outcome::PreflightFailed));
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = match tweetr::ops::QueuedTweet::read_recovering(&tweets_path) {
+        Ok(queue) => queue,
+        Err(None) => {
+            tweetr::ops::RecoveredQueue {
+                tweets: vec![],
+                broken: vec![],
+                errors: None,
+            }
+        }
+        Err(Some(out)) => return Err(out),
+    };
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    let duplicate_window = config.duplicate_window();
+    warn_duplicates(&mut stderr(), &tweet, &tweets, &recent_archive(&opts.config_dir.1, duplicate_window), duplicate_window);
+    let collision_window = config.collision_window();
+    let collisions = tweetr::ops::queue_tweet::collisions(&tweet, &tweets, collision_window);
+    if !collisions.is_empty() {
+        let free_slot = tweetr::ops::queue_tweet::nearest_free_slot(&tweet, &tweets, collision_window, &tweetr::ops::clock::SystemClock);
+        tweetr::ops::queue_tweet::print_collisions(&mut stderr(), &tweet, &collisions, collision_window, free_slot);
+    }
+    try!(lint_or_refuse(&tweet, &tweetr::ops::User::read(&opts.config_dir.1.join("users.toml")).unwrap_or_default()));
+
+    if let Some(tweet) = run_hook(&config.hooks_dir(&opts.config_dir.1), tweetr::ops::hooks::Hook::PreQueue, tweet) {
+        tweetr::ops::quick_queue::print_success_message(&mut diagnostics(), &tweet);
+        let before = tweets.clone();
+        tweets.push(tweet);
+        tweets.sort();
+        journal_changes(&opts.config_dir.1, "quick-queue", &before, &tweets, &[]);
+        try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    }
 
     Ok(())
 }
 
-fn start_daemon_main(opts: tweetr::options::Options, delay: Duration, verbose: bool) -> Result<(), tweetr::Outcome> {
-    let (app_path, users_path, tweets_path) = try!(tweetr::ops::start_daemon::verify(&opts.config_dir));
-    let app = try!(tweetr::ops::AppTokens::read(&app_path).map_err(Option::unwrap));
-    let app_tokens = app.raw_token();
+fn post_now_main(opts: tweetr::options::Options, account: String, content: String) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let users = tweetr::ops::User::read(&opts.config_dir.1.join("users.toml")).unwrap_or_default();
+    let sinks = try!(tweetr::ops::backend::sinks(&config, &opts.config_dir.1));
+    if !users.iter().any(|u| u.name == account) && !sinks.iter().any(|s| s.name == account) {
+        return Err(tweetr::Outcome::RequiredDataFromSubsystemNonexistant {
+            subsys: "add-user",
+            desc: format!("add and authorise user with name \"{}\", or configure a sink with that name", account),
+        });
+    }
 
-    loop {
-        match (tweetr::ops::User::read(&users_path), tweetr::ops::QueuedTweet::read(&tweets_path)) {
-            (Ok(users), Ok(mut tweets)) => {
-                let tweets_to_post = tweetr::ops::start_daemon::tweet_indices_to_post(&tweets);
+    let now = chrono::Local::now();
+    let tweet = tweetr::ops::post_now::breaking(&account, &content, now.with_timezone(now.offset()));
 
-                for i in tweets_to_post {
-                    let tweet_to_post = &mut tweets[i];
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = match tweetr::ops::QueuedTweet::read_recovering(&tweets_path) {
+        Ok(queue) => queue,
+        Err(None) => {
+            tweetr::ops::RecoveredQueue {
+                tweets: vec![],
+                broken: vec![],
+                errors: None,
+            }
+        }
+        Err(Some(out)) => return Err(out),
+    };
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
 
-                    match tweetr::ops::start_daemon::find_user_index_for_tweet(tweet_to_post, &users) {
-                        Ok(user_i) => {
-                            tweetr::ops::start_daemon::post_tweet(tweet_to_post, &users[user_i], &app_tokens, verbose, &mut stdout())
-                                .print_error(&mut stderr());
-                        }
-                        Err(out) => out.print_error(&mut stderr()),
-                    }
-                }
+    let duplicate_window = config.duplicate_window();
+    warn_duplicates(&mut stderr(), &tweet, &tweets, &recent_archive(&opts.config_dir.1, duplicate_window), duplicate_window);
+    try!(lint_or_refuse(&tweet, &users));
+
+    if let Some(tweet) = run_hook(&config.hooks_dir(&opts.config_dir.1), tweetr::ops::hooks::Hook::PreQueue, tweet) {
+        let stdin = stdin();
+        if !tweetr::ops::post_now::prompt_confirm(opts.confirmation, &mut stdin.lock(), &mut stderr(), &tweet) {
+            return Ok(());
+        }
+
+        let before = tweets.clone();
+        let shifts = tweetr::ops::post_now::preempt(&mut tweets, &tweet, config.collision_window());
+        tweetr::ops::quick_queue::print_success_message(&mut diagnostics(), &tweet);
+        tweetr::ops::post_now::print_shifts(&mut diagnostics(), &tweets, &shifts);
+
+        tweets.push(tweet);
+        tweets.sort();
+        journal_changes(&opts.config_dir.1, "post-now", &before, &tweets, &[]);
+        try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    }
+
+    Ok(())
+}
+
+fn release_announce_main(opts: tweetr::options::Options, source: PathBuf, template: String, account: String, version: Option<String>,
+                         url: Option<String>, time: Option<chrono::DateTime<chrono::FixedOffset>>, highlights: usize, dry_run: bool)
+                         -> Result<(), tweetr::Outcome> {
+    let templates = match tweetr::ops::Template::read(&tweetr::ops::Template::path(&opts.config_dir.1)) {
+        Ok(templates) => templates,
+        Err(Some(out)) => return Err(out),
+        Err(None) => vec![],
+    };
+    let template = match templates.into_iter().find(|t| t.name == template) {
+        Some(template) => template,
+        None => {
+            return Err(tweetr::Outcome::TemplateError {
+                name: template,
+                errors: vec!["no such template, see list-templates".to_string()],
+            })
+        }
+    };
+
+    let changelog_path = match tweetr::ops::release_announce::changelog_path(&source) {
+        Some(changelog_path) => changelog_path,
+        None => {
+            return Err(tweetr::Outcome::PreflightFailed(vec![format!("no changelog in {}, expected one of {}",
+                                                                     source.display(),
+                                                                     tweetr::ops::release_announce::CHANGELOG_NAMES.join(", "))]))
+        }
+    };
+    let mut changelog = String::new();
+    try!(fs::File::open(&changelog_path)
+        .and_then(|mut f| f.read_to_string(&mut changelog))
+        .map_err(|e| tweetr::Outcome::from_io("read", &changelog_path, e)));
 
-                tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
+    let repo = if source.is_dir() { Some(source.as_path()) } else { None };
+    let version = version.or_else(|| repo.and_then(tweetr::ops::release_announce::latest_tag));
+    let release = match tweetr::ops::release_announce::parse_changelog(&changelog, version.as_ref().map(|v| &v[..])) {
+        Some(release) => release,
+        None => {
+            return Err(tweetr::Outcome::PreflightFailed(vec![match version {
+                                                                 Some(version) => format!("no release {} in {}", version, changelog_path.display()),
+                                                                 None => format!("no releases in {}", changelog_path.display()),
+                                                             }]))
+        }
+    };
+    let url = url.or_else(|| {
+        repo.and_then(tweetr::ops::release_announce::remote_url)
+            .and_then(|remote| tweetr::ops::release_announce::release_url(&remote, &release.version))
+    });
 
-                thread::sleep(delay);
+    let content = try!(template.fill(&tweetr::ops::release_announce::values(&template, &release, url.as_ref().map(|u| &u[..]), highlights))
+        .map_err(|errors| {
+            tweetr::Outcome::TemplateError {
+                name: template.name.clone(),
+                errors: errors,
             }
-            (Err(err), _) => err.unwrap().print_error(&mut stderr()),
-            (_, Err(err)) => err.unwrap().print_error(&mut stderr()),
+        }));
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let now = chrono::Local::now();
+    let announcement = tweetr::ops::release_announce::announcement(&content,
+                                                                   &release.version,
+                                                                   &account,
+                                                                   time.unwrap_or_else(|| now.with_timezone(now.offset())),
+                                                                   config.thread_numbering());
+
+    if dry_run {
+        for tweet in &announcement {
+            writeln!(stdout(), "Would queue \"{}\" by {} for {}.", tweet.content, tweet.author, tweetr::ops::l10n::format_datetime(&tweet.time)).unwrap();
+        }
+        return Ok(());
+    }
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = match tweetr::ops::QueuedTweet::read_recovering(&tweets_path) {
+        Ok(queue) => queue,
+        Err(None) => {
+            tweetr::ops::RecoveredQueue {
+                tweets: vec![],
+                broken: vec![],
+                errors: None,
+            }
+        }
+        Err(Some(out)) => return Err(out),
+    };
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    // Announcing from CI on every tag push mustn't queue the same release twice
+    let name = tweetr::ops::release_announce::name(&release.version);
+    let archived = try!(tweetr::ops::archive::read_all(&opts.config_dir.1));
+    if tweets.iter().chain(&archived).any(|t| t.name.as_ref() == Some(&name)) {
+        writeln!(diagnostics(), "{} is already announced, as {}.", release.version, name).unwrap();
+        return Ok(());
+    }
+
+    let users = tweetr::ops::User::read(&opts.config_dir.1.join("users.toml")).unwrap_or_default();
+    for tweet in &announcement {
+        try!(lint_or_refuse(tweet, &users));
+    }
+
+    let hooks_dir = config.hooks_dir(&opts.config_dir.1);
+    let before = tweets.clone();
+    for tweet in announcement {
+        if let Some(tweet) = run_hook(&hooks_dir, tweetr::ops::hooks::Hook::PreQueue, tweet) {
+            tweetr::ops::quick_queue::print_success_message(&mut diagnostics(), &tweet);
+            tweets.push(tweet);
+        }
+    }
+    tweets.sort();
+    journal_changes(&opts.config_dir.1, "release-announce", &before, &tweets, &[]);
+    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+
+    Ok(())
+}
+
+fn setup_main(opts: tweetr::options::Options, test_tweet: bool, dry_run: bool) -> Result<(), tweetr::Outcome> {
+    let progress = tweetr::ops::setup::progress(&opts.config_dir.1);
+    let app_path = opts.config_dir.1.join("app.toml");
+    let users_path = opts.config_dir.1.join("users.toml");
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+
+    let stdin = stdin();
+    let mut lock = tweetr::ops::transcript::Transcribed::new(stdin.lock());
+
+    tweetr::ops::setup::print_step_header(&mut transcribed_stdout(), tweetr::ops::setup::SetupStep::Init, progress.app);
+    if !progress.app {
+        try!(tweetr::ops::init::get_data(&mut lock, &mut transcribed_stdout()).write(&app_path));
+        tweetr::ops::init::record_created(None);
+        writeln!(transcribed_stdout(), "").unwrap();
+    }
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+
+    tweetr::ops::setup::print_step_header(&mut transcribed_stdout(), tweetr::ops::setup::SetupStep::AddUser, progress.users);
+    if !progress.users {
+        let user = try!(tweetr::ops::add_user::authorise(&mut lock, &mut transcribed_stdout(), apps.default.clone(), false));
+        writeln!(transcribed_stdout(), "").unwrap();
+        tweetr::ops::add_user::print_success_message(&mut transcribed_stdout(), &mut transcribed_stdout(), &user, false);
+        writeln!(transcribed_stdout(), "").unwrap();
+
+        let out = tweetr::ops::add_user::append_user(&users_path, user);
+        if out != tweetr::Outcome::NoError {
+            return Err(out);
+        }
+    }
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+
+    tweetr::ops::setup::print_step_header(&mut transcribed_stdout(), tweetr::ops::setup::SetupStep::QueueTweet, false);
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let hooks_dir = config.hooks_dir(&opts.config_dir.1);
+    let mut tweets = if progress.tweets {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    let before = tweets.clone();
+    while let Some(tweet) = tweetr::ops::queue_tweet::get_tweet(&mut lock, &mut transcribed_stdout()) {
+        tweets.extend(run_hook(&hooks_dir, tweetr::ops::hooks::Hook::PreQueue, tweet));
+    }
+    tweets.sort();
+    journal_changes(&opts.config_dir.1, "setup", &before, &tweets, &[]);
+    try!(tweetr::ops::QueuedTweet::write(tweets.clone(), &tweets_path));
+
+    if test_tweet {
+        writeln!(transcribed_stdout(), "").unwrap();
+        tweetr::ops::setup::print_step_header(&mut transcribed_stdout(), tweetr::ops::setup::SetupStep::TestTweet, false);
+        match users.first() {
+            Some(user) => {
+                let app = try!(apps.get(user.app.as_ref().map(|a| &a[..])));
+                let out = tweetr::ops::setup::test_tweet(user, &app.raw_token(), dry_run, &mut transcribed_stdout());
+                if out != tweetr::Outcome::NoError {
+                    return Err(out);
+                }
+            }
+            None => {
+                return Err(tweetr::Outcome::RequiredDataFromSubsystemNonexistant {
+                    subsys: "add-user",
+                    desc: "add a user to post the test tweet as".to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn suggest_time_main(opts: tweetr::options::Options, author: Option<String>, count: usize) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let grid = try!(tweetr::ops::suggest_time::grid(&config, &opts.config_dir.1));
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+
+    for slot in tweetr::ops::suggest_time::free_slots(&grid,
+                                                      author.as_ref().map(|a| &a[..]),
+                                                      &tweets,
+                                                      config.collision_window(),
+                                                      chrono::Local::now(),
+                                                      count) {
+        writeln!(stdout(), "{}", slot.to_rfc2822()).unwrap();
+    }
+
+    Ok(())
+}
+
+fn calendar_main(opts: tweetr::options::Options, month: bool, width: usize, colour: bool) -> Result<(), tweetr::Outcome> {
+    try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+
+    tweetr::ops::calendar::render(&mut stdout(),
+                                  &tweets,
+                                  if month {
+                                      tweetr::ops::calendar::Span::Month
+                                  } else {
+                                      tweetr::ops::calendar::Span::Week
+                                  },
+                                  chrono::Local::today(),
+                                  width,
+                                  colour);
+
+    Ok(())
+}
+
+fn simulate_main(opts: tweetr::options::Options, from: chrono::DateTime<chrono::FixedOffset>, to: chrono::DateTime<chrono::FixedOffset>)
+                 -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let settings = config.settings();
+    let embargoes = config.embargoes();
+    let embargo_policy = config.embargo_policy();
+    let slots = if !embargoes.is_empty() && embargo_policy == tweetr::ops::embargo::Policy::Slots {
+        try!(tweetr::ops::suggest_time::grid(&config, &opts.config_dir.1))
+    } else {
+        vec![]
+    };
+
+    let users_path = opts.config_dir.1.join("users.toml");
+    let users = if users_path.exists() {
+        try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let mut tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    // The tweets posted the day before count towards the daily caps
+    let history = try!(tweetr::ops::archive::read_since(&opts.config_dir.1, from - chrono::Duration::days(1)));
+
+    let timeline = try!(tweetr::ops::simulate::run(&mut tweets,
+                                                   &history,
+                                                   &tweetr::ops::simulate::Rules {
+                                                       users: &users,
+                                                       settings: &settings,
+                                                       embargoes: &embargoes,
+                                                       embargo_policy: embargo_policy,
+                                                       collision_window: config.collision_window(),
+                                                       slots: &slots,
+                                                   },
+                                                   from,
+                                                   to));
+    tweetr::ops::simulate::print_timeline(&mut stdout(), &timeline, from, to, tweetr::util::terminal::terminal_width());
+
+    Ok(())
+}
+
+fn doctor_main(opts: tweetr::options::Options, offline: bool) -> Result<(), tweetr::Outcome> {
+    let mut checks = vec![tweetr::ops::doctor::check_permissions(&opts.config_dir.1),
+                          tweetr::ops::doctor::check_files(&opts.config_dir.1),
+                          tweetr::ops::doctor::check_versions(&opts.config_dir.1)];
+
+    if !offline {
+        // Invalid or missing files were already reported above
+        if let (Ok(apps), Ok(users)) = (tweetr::ops::Apps::read(&opts.config_dir.1.join("app.toml")),
+                                        tweetr::ops::User::read(&opts.config_dir.1.join("users.toml"))) {
+            checks.extend(tweetr::ops::doctor::check_credentials(&apps, &users));
+        }
+        checks.extend(tweetr::ops::doctor::check_api(&tweetr::ops::clock::SystemClock));
+    }
+
+    tweetr::ops::doctor::print_results(&mut stdout(), &checks);
+    Err(tweetr::ops::doctor::summarise(&checks))
+}
+
+fn prune_main(opts: tweetr::options::Options, keep: Option<u32>, dry_run: bool) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let retention = match keep.or(config.archive_retention()) {
+        Some(retention) => retention,
+        None => {
+            writeln!(diagnostics(), "No retention configured, keeping all archives.").unwrap();
+            return Ok(());
+        }
+    };
+
+    for archive in tweetr::ops::archive::expired(&opts.config_dir.1, retention, chrono::Local::today()) {
+        if dry_run {
+            writeln!(stdout(), "Would remove {}", archive.display()).unwrap();
+        } else {
+            try!(fs::remove_file(&archive).map_err(|e| tweetr::Outcome::from_io("remove", &archive, e)));
+            writeln!(diagnostics(), "Removed {}", archive.display()).unwrap();
+        }
+    }
+
+    Ok(())
+}
+
+fn gc_media_main(opts: tweetr::options::Options, keep: Option<u32>, dry_run: bool) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let mut tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    tweets.extend(try!(tweetr::ops::archive::read_all(&opts.config_dir.1)));
+
+    let now = chrono::Local::now();
+    let unused = tweetr::ops::media::unused(&opts.config_dir.1, &tweets, keep.or(config.media_retention()), now.with_timezone(now.offset()));
+    for &(ref media, _) in &unused {
+        if dry_run {
+            writeln!(stdout(), "Would remove {}", media.display()).unwrap();
+        } else {
+            try!(fs::remove_file(media).map_err(|e| tweetr::Outcome::from_io("remove", media, e)));
+            writeln!(diagnostics(), "Removed {}", media.display()).unwrap();
+        }
+    }
+
+    let reclaimed = tweetr::util::format_size(unused.iter().map(|&(_, size)| size).sum());
+    if dry_run {
+        writeln!(diagnostics(), "Would reclaim {}.", reclaimed).unwrap();
+    } else {
+        writeln!(diagnostics(), "Reclaimed {}.", reclaimed).unwrap();
+    }
+    Ok(())
+}
+
+fn export_posted_main(opts: tweetr::options::Options, format: tweetr::ops::export_posted::Format,
+                      since: Option<chrono::DateTime<chrono::FixedOffset>>)
+                      -> Result<(), tweetr::Outcome> {
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let queue = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    let users_path = opts.config_dir.1.join("users.toml");
+    let users = if users_path.exists() {
+        try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+
+    let posted = try!(tweetr::ops::export_posted::posted(&opts.config_dir.1, &queue, since));
+    match format {
+        tweetr::ops::export_posted::Format::Csv => tweetr::ops::export_posted::write_csv(&mut stdout(), &posted, &users),
+        tweetr::ops::export_posted::Format::Json => tweetr::ops::export_posted::write_json(&mut stdout(), &posted, &users),
+    }
+
+    Ok(())
+}
+
+fn reconcile_main(opts: tweetr::options::Options, account: Option<String>) -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path) = try!(tweetr::ops::prune_posted::verify(&opts.config_dir));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    if let Some(account) = account.as_ref() {
+        if !users.iter().any(|u| u.name == *account) {
+            return Err(tweetr::Outcome::RequiredDataFromSubsystemNonexistant {
+                subsys: "add-user",
+                desc: format!("add and authorise user with name \"{}\"", account),
+            });
+        }
+    }
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let mut recorded = try!(tweetr::ops::archive::read_all(&opts.config_dir.1));
+    if tweets_path.exists() {
+        recorded.extend(try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap)));
+    }
+    let pruned = try!(tweetr::ops::prune_posted::read_pruned(&tweetr::ops::prune_posted::pruned_path(&opts.config_dir.1)));
+
+    let mut errors = vec![];
+    for user in users.iter().filter(|u| account.as_ref().map(|a| u.name == *a).unwrap_or(true)) {
+        if user.test_mode.unwrap_or(false) {
+            writeln!(diagnostics(), "@{}: skipped, in test mode.", user.name).unwrap();
+            continue;
+        }
+
+        let timeline = apps.get(user.app.as_ref().map(|a| &a[..])).and_then(|app| tweetr::ops::reconcile::timeline(user, &app.raw_token()));
+        match timeline {
+            Ok(timeline) => {
+                let discrepancies = tweetr::ops::reconcile::discrepancies(&user.name, &timeline, &recorded, &pruned);
+                tweetr::ops::reconcile::print_discrepancies(&mut stdout(), &user.name, &discrepancies);
+            }
+            Err(out) => errors.push(out),
+        }
+    }
+
+    Err(tweetr::Outcome::from_many(errors))
+}
+
+fn prune_posted_main(opts: tweetr::options::Options, older_than: i64, account: Option<String>, metrics: Option<PathBuf>,
+                     encoding: Option<tweetr::util::encoding::Encoding>, min_engagement: Option<u64>, interval: Duration, dry_run: bool)
+                     -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path) = try!(tweetr::ops::prune_posted::verify(&opts.config_dir));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    if let Some(account) = account.as_ref() {
+        if !users.iter().any(|u| u.name == *account) {
+            return Err(tweetr::Outcome::RequiredDataFromSubsystemNonexistant {
+                subsys: "add-user",
+                desc: format!("add and authorise user with name \"{}\"", account),
+            });
+        }
+    }
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let queue = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+    let posted = try!(tweetr::ops::export_posted::posted(&opts.config_dir.1, &queue, None));
+    let pruned_path = tweetr::ops::prune_posted::pruned_path(&opts.config_dir.1);
+    let mut pruned = try!(tweetr::ops::prune_posted::read_pruned(&pruned_path));
+    let metrics = match metrics {
+        Some(metrics) => Some(try!(tweetr::ops::prune_posted::read_metrics(&metrics, encoding))),
+        None => None,
+    };
+
+    // Whole seconds, for listing
+    let now = chrono::Local::now().with_nanosecond(0).unwrap();
+    let before = match tweetr::ops::prune_posted::cutoff(now.with_timezone(now.offset()), older_than) {
+        Some(before) => before,
+        None => {
+            writeln!(diagnostics(), "No tweets posted that long ago to delete.").unwrap();
+            return Ok(());
+        }
+    };
+    let candidates = tweetr::ops::prune_posted::candidates(&posted,
+                                                           &users,
+                                                           &pruned,
+                                                           before,
+                                                           account.as_ref().map(|a| &a[..]),
+                                                           metrics.as_ref().and_then(|m| min_engagement.map(|e| (m, e))));
+    if candidates.is_empty() {
+        writeln!(diagnostics(), "No tweets posted before {} to delete.", tweetr::ops::l10n::format_datetime(&before)).unwrap();
+        return Ok(());
+    }
+    tweetr::ops::prune_posted::print_plan(&mut stdout(), &posted, &candidates, before, tweetr::util::terminal::terminal_width());
+    if dry_run {
+        return Ok(());
+    }
+    let stdin = stdin();
+    if !tweetr::ops::prune_posted::prompt_confirm(opts.confirmation, &mut stdin.lock(), &mut stderr()) {
+        return Ok(());
+    }
+
+    let mut deleted = 0;
+    let mut first_error = None;
+    let mut rate_limited = false;
+    for (n, &i) in candidates.iter().enumerate() {
+        if n != 0 {
+            thread::sleep(interval);
+        }
+
+        let id = posted[i].id.unwrap();
+        let result = tweetr::ops::start_daemon::find_user_index_for_tweet(&posted[i], &users).and_then(|user_i| {
+            let app = try!(apps.get(users[user_i].app.as_ref().map(|a| &a[..])));
+            tweetr::ops::prune_posted::delete(id, &users[user_i], &app.raw_token())
+        });
+        match result {
+            Ok(existed) => {
+                if existed {
+                    writeln!(diagnostics(), "Deleted tweet {} by {}.", id, posted[i].author).unwrap();
+                } else {
+                    writeln!(diagnostics(), "Tweet {} by {} was already deleted.", id, posted[i].author).unwrap();
+                }
+                deleted += 1;
+                pruned.insert(id);
+                // Written after each tweet, so ones deleted before an interruption aren't deleted again
+                if let Err(out) = tweetr::ops::prune_posted::write_pruned(&pruned, &pruned_path) {
+                    first_error = first_error.or(Some(out));
+                    break;
+                }
+            }
+            Err(out) => {
+                out.print_error(&mut stderr());
+                rate_limited = tweetr::ops::events::rate_limited(&out);
+                first_error = first_error.or(Some(out));
+                if rate_limited {
+                    break;
+                }
+            }
+        }
+    }
+
+    tweetr::ops::prune_posted::print_summary(&mut diagnostics(), deleted, candidates.len(), rate_limited);
+    first_error.map_or(Ok(()), Err)
+}
+
+fn show_effective_config_main(opts: tweetr::options::Options, author: Option<String>) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::show_effective_config::verify(&opts.config_dir));
+    let users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap)).settings();
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let tweets = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+
+    let users: Vec<_> = users.into_iter().filter(|u| author.as_ref().map(|a| *a == u.name).unwrap_or(true)).collect();
+    if let (true, Some(author)) = (users.is_empty(), author) {
+        return Err(tweetr::Outcome::RequiredDataFromSubsystemNonexistant {
+            subsys: "add-user",
+            desc: format!("add and authorise user with name \"{}\"", author),
+        });
+    }
+
+    let mut errors = vec![];
+    for (i, user) in users.iter().enumerate() {
+        if i != 0 {
+            writeln!(stdout(), "").unwrap();
+        }
+
+        let account = user.settings();
+        match tweetr::ops::settings::Effective::resolve(None, Some(&account), &config) {
+            Ok(effective) => tweetr::ops::show_effective_config::print_account(&mut stdout(), &user.name, &effective),
+            Err(out) => {
+                // Errors in the configuration would otherwise be reported once per account
+                if !errors.contains(&out) {
+                    errors.push(out);
+                }
+                continue;
+            }
+        }
+
+        for tweet in tweets.iter().filter(|t| t.author == user.name && t.id.is_none()) {
+            match tweetr::ops::settings::Effective::resolve(Some(&tweet.settings), Some(&account), &config) {
+                Ok(effective) => tweetr::ops::show_effective_config::print_tweet(&mut stdout(), tweet, &effective),
+                Err(out) => errors.push(out),
+            }
+        }
+    }
+
+    Err(tweetr::Outcome::from_many(errors))
+}
+
+fn fmt_queue_main(opts: tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    let tweets_path = try!(tweetr::ops::fmt_queue::verify(&opts.config_dir));
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+        .map_err(Option::unwrap));
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    let before = tweets.clone();
+    let duplicates = tweetr::ops::fmt_queue::dedup(&mut tweets);
+    let remnants = tweetr::ops::fmt_queue::clear_remnants(&mut tweets);
+    let archived = try!(tweetr::ops::archive::rotate(&mut tweets, &opts.config_dir.1, config.archive_size()));
+    tweetr::ops::fmt_queue::sort(&mut tweets);
+
+    writeln!(diagnostics(),
+             "Removed {} duplicate{}, archived {} posted tweet{} and cleared {} stale repl{}; {} tweet{} left.",
+             duplicates,
+             if duplicates == 1 { "" } else { "s" },
+             archived.len(),
+             if archived.len() == 1 { "" } else { "s" },
+             remnants,
+             if remnants == 1 { "y" } else { "ies" },
+             tweets.len(),
+             if tweets.len() == 1 { "" } else { "s" })
+        .unwrap();
+    journal_changes(&opts.config_dir.1, "fmt-queue", &before, &tweets, &archived);
+    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    Ok(())
+}
+
+fn shift_queue_main(opts: tweetr::options::Options, by: i64, from: Option<chrono::DateTime<chrono::FixedOffset>>, author: Option<String>,
+                    tag: Option<String>)
+                    -> Result<(), tweetr::Outcome> {
+    let tweets_path = try!(tweetr::ops::shift_queue::verify(&opts.config_dir));
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+        .map_err(Option::unwrap));
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    let filter = tweetr::ops::shift_queue::Filter {
+        from: from,
+        author: author,
+        tag: tag,
+    };
+    let before = tweets.clone();
+    let (shifted, dependents) = try!(tweetr::ops::shift_queue::shift(&mut tweets, &filter, by).map_err(tweetr::Outcome::PreflightFailed));
+
+    writeln!(diagnostics(),
+             "Moved {} tweet{} {} {}{}.",
+             shifted,
+             if shifted == 1 { "" } else { "s" },
+             tweetr::util::format_amount_of_time(by.abs() as u64),
+             if by < 0 { "earlier" } else { "later" },
+             if dependents == 0 {
+                 String::new()
+             } else {
+                 format!(", and {} tweet{} scheduled after them with them", dependents, if dependents == 1 { "" } else { "s" })
+             })
+        .unwrap();
+    journal_changes(&opts.config_dir.1, "shift-queue", &before, &tweets, &[]);
+    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    Ok(())
+}
+
+fn requeue_thread_main(opts: tweetr::options::Options, thread: String) -> Result<(), tweetr::Outcome> {
+    let tweets_path = try!(tweetr::ops::requeue_thread::verify(&opts.config_dir));
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+        .map_err(Option::unwrap));
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+    }
+
+    let before = tweets.clone();
+    let requeued = tweetr::ops::requeue_thread::requeue(&mut tweets, &thread);
+    if requeued == 0 {
+        writeln!(diagnostics(), "No tweets in thread {} are held back.", thread).unwrap();
+        return Ok(());
+    }
+
+    writeln!(diagnostics(), "Requeued {} tweet{} in thread {}.", requeued, if requeued == 1 { "" } else { "s" }, thread).unwrap();
+    journal_changes(&opts.config_dir.1, "requeue-thread", &before, &tweets, &[]);
+    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path));
+    Ok(())
+}
+
+fn history_main(opts: tweetr::options::Options, id: String) -> Result<(), tweetr::Outcome> {
+    let records = try!(tweetr::ops::journal::read(&tweetr::ops::journal::path(&opts.config_dir.1)));
+    let history = tweetr::ops::journal::history(&records, &id);
+    if history.is_empty() {
+        writeln!(diagnostics(), "No history for {}.", id).unwrap();
+    } else {
+        tweetr::ops::journal::print_history(&mut stdout(), &history);
+    }
+
+    Ok(())
+}
+
+fn list_templates_main(opts: tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    match tweetr::ops::Template::read(&tweetr::ops::Template::path(&opts.config_dir.1)) {
+        Ok(ref templates) if !templates.is_empty() => {
+            for template in templates {
+                tweetr::ops::list_templates::print_template(&mut stdout(), template);
+            }
+        }
+        Ok(_) | Err(None) => writeln!(diagnostics(), "No templates, add them to {}/templates.toml.", opts.config_dir.0).unwrap(),
+        Err(Some(out)) => return Err(out),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+fn tui_main(opts: tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let (tweets, broken) = if tweets_path.exists() {
+        let tweetr::ops::RecoveredQueue { tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+            .map_err(Option::unwrap));
+        if let Some(errors) = errors {
+            errors.print_error(&mut stderr());
+            writeln!(stderr(), "The broken entries were left as-is.").unwrap();
+        }
+        (tweets, broken)
+    } else {
+        (vec![], vec![])
+    };
+
+    let archived = try!(tweetr::ops::archive::read_all(&opts.config_dir.1));
+
+    let before = tweets.clone();
+    let mut state = tweetr::ops::tui::State::new(tweets, archived);
+    if let Err(e) = tweetr::ops::tui::run(&mut state, &config.hooks_dir(&opts.config_dir.1)) {
+        writeln!(stderr(), "Terminal error: {}", e).unwrap();
+    }
+
+    if state.dirty {
+        journal_changes(&opts.config_dir.1, "tui", &before, &state.tweets, &[]);
+        try!(tweetr::ops::QueuedTweet::write_with_broken(state.tweets.clone(), &broken, &tweets_path));
+    }
+    Ok(())
+}
+
+fn schema_main(format: tweetr::ops::schema::Format, example: bool) -> Result<(), tweetr::Outcome> {
+    if example {
+        write!(stdout(), "{}", tweetr::ops::schema::example(format)).unwrap();
+    } else {
+        writeln!(stdout(), "{}", tweetr::ops::schema::json_schema(format).pretty()).unwrap();
+    }
+
+    Ok(())
+}
+
+fn update_main(check: bool) -> Result<(), tweetr::Outcome> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = try!(tweetr::ops::update::latest_release());
+    if !tweetr::ops::update::is_newer(&release.version, current) {
+        writeln!(diagnostics(), "tweetr v{} is up to date.", current).unwrap();
+        return Ok(());
+    }
+    if check {
+        writeln!(stdout(), "tweetr {} is available, this is v{}.", release.version, current).unwrap();
+        return Ok(());
+    }
+
+    let key = try!(tweetr::ops::update::RELEASE_KEY
+        .ok_or_else(|| tweetr::Outcome::UpdateFailed("this build has no release key to verify updates with, update manually".to_string())));
+    let (binary_url, signature_url) = try!(release.binary().map_err(tweetr::Outcome::UpdateFailed));
+    let binary = try!(tweetr::ops::update::download(binary_url));
+    let signature = try!(tweetr::ops::update::download(signature_url));
+    try!(tweetr::ops::update::verify_signature(&binary, &String::from_utf8_lossy(&signature), key)
+        .map_err(tweetr::Outcome::UpdateFailed));
+
+    let exe = try!(std::env::current_exe().map_err(|e| tweetr::Outcome::UpdateFailed(format!("couldn't find the executable: {}", e))));
+    try!(tweetr::ops::update::replace_executable(&exe, &binary).map_err(tweetr::Outcome::UpdateFailed));
+    writeln!(diagnostics(), "Updated tweetr from v{} to {}.", current, release.version).unwrap();
+
+    Ok(())
+}
+
+fn validate_main(opts: tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    let results = tweetr::ops::validate::check_files(&opts.config_dir.1);
+    tweetr::ops::validate::print_results(&mut stdout(), &results);
+
+    Err(tweetr::ops::validate::summarise(&results))
+}
+
+fn start_daemon_main(opts: tweetr::options::Options, delay: Duration, events: Option<PathBuf>, notify: bool, evergreen: bool)
+                     -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path, tweets_path) = try!(tweetr::ops::start_daemon::verify(&opts.config_dir));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let hooks_dir = config.hooks_dir(&opts.config_dir.1);
+    let uploads_path = tweetr::ops::media::uploads_path(&opts.config_dir.1);
+    let sinks = try!(tweetr::ops::backend::sinks(&config, &opts.config_dir.1));
+    let pipeline = try!(tweetr::ops::transform::pipeline(&config, &opts.config_dir.1));
+    let settings = config.settings();
+    // At most a week, as validated
+    let delay = chrono::Duration::from_std(delay).unwrap();
+    let evergreen_grid = if evergreen {
+        Some(try!(tweetr::ops::suggest_time::grid(&config, &opts.config_dir.1)))
+    } else {
+        None
+    };
+    let evergreen_path = tweetr::ops::evergreen::EvergreenTweet::path(&opts.config_dir.1);
+    let embargoes = config.embargoes();
+    let embargo_policy = config.embargo_policy();
+    let embargo_grid = if !embargoes.is_empty() && embargo_policy == tweetr::ops::embargo::Policy::Slots {
+        try!(tweetr::ops::suggest_time::grid(&config, &opts.config_dir.1))
+    } else {
+        vec![]
+    };
+
+    let mut preflight_files = vec![("app.toml", tweetr::ops::preflight::Access::Read),
+                                   ("users.toml", tweetr::ops::preflight::Access::Read),
+                                   ("tweets.toml", tweetr::ops::preflight::Access::ReadWrite),
+                                   ("config.toml", tweetr::ops::preflight::Access::Read)];
+    if evergreen {
+        preflight_files.push(("evergreen.toml", tweetr::ops::preflight::Access::ReadWrite));
+    }
+    for warning in try!(tweetr::ops::preflight::run(&opts.config_dir.1, &preflight_files, config.refuse_insecure_users())) {
+        warn(&mut stderr(), &warning);
+    }
+    if evergreen {
+        try!(tweetr::ops::evergreen::EvergreenTweet::read(&evergreen_path)
+            .map_err(|e| e.unwrap_or_else(|| tweetr::Outcome::PreflightFailed(vec![format!("{} doesn't exist", evergreen_path.display())]))));
+    }
+    let _lock = try!(tweetr::ops::start_daemon::DaemonLock::acquire(&opts.config_dir.1));
+
+    let checkpoint_path = tweetr::ops::recovery::Checkpoint::path(&opts.config_dir.1);
+    recover(&opts.config_dir.1, &checkpoint_path, &tweets_path, &config);
+
+    // Errors reading these are reported by the loop below
+    let users = tweetr::ops::User::read(&users_path).ok();
+    let tweets = tweetr::ops::QueuedTweet::read_recovering(&tweets_path).ok().map(|q| q.tweets);
+    tweetr::ops::start_daemon::print_summary(&mut diagnostics(),
+                                             &tweetr::ops::start_daemon::Summary {
+                                                 config_dir: &opts.config_dir.0,
+                                                 hooks_dir: &hooks_dir,
+                                                 events: events.as_ref().map(|e| e.as_path()),
+                                                 users: users.as_ref().map(|u| &u[..]),
+                                                 sinks: &sinks,
+                                                 tweets: tweets.as_ref().map(|t| &t[..]),
+                                                 delay: delay,
+                                             });
+
+    let mut events = match events {
+        Some(events) => try!(tweetr::ops::events::EventLog::open(&events).map_err(|e| tweetr::Outcome::PreflightFailed(vec![e]))),
+        None => tweetr::ops::events::EventLog::none(),
+    };
+    let mut tracker = tweetr::ops::events::Tracker::default();
+    let mut notifier = tweetr::ops::notify::Notifier::new(notify);
+    let posting = Posting {
+        apps: &apps,
+        sinks: &sinks,
+        pipeline: &pipeline,
+        content_warning_format: config.content_warning_format(),
+        hooks_dir: &hooks_dir,
+        uploads_path: &uploads_path,
+    };
+
+    let mut last_queue_errors = None;
+    let mut last_media_gc: Option<Instant> = None;
+    let mut evergreen_since = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+    let mut users_cache = tweetr::ops::start_daemon::Cached::new(users_path.clone());
+    let mut queue_cache = tweetr::ops::start_daemon::Cached::new(tweets_path.clone());
+    let mut due = tweetr::ops::start_daemon::Due::new(&[]);
+    let mut persisted = vec![];
+    let started = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+    let latency_threshold = config.latency_threshold();
+    let mut latency = tweetr::ops::latency::Latency::default();
+    loop {
+        match (users_cache.get(tweetr::ops::User::read), queue_cache.get(tweetr::ops::QueuedTweet::read_recovering)) {
+            (Ok((users, _)), Ok((&mut tweetr::ops::RecoveredQueue { ref mut tweets, ref broken, ref errors }, reread))) => {
+                // Only report broken entries when they change, not on every iteration
+                if *errors != last_queue_errors {
+                    if let Some(ref errors) = *errors {
+                        errors.print_error(&mut stderr());
+                        writeln!(stderr(), "{}", tweetr::ops::l10n::tr("broken_entries_skipped", &[])).unwrap();
+                    }
+                    last_queue_errors = errors.clone();
+                }
+                if reread {
+                    persisted = tweets.clone();
+                }
+
+                // Set by everything below that changes the queue, so it's only written, and re-sorted, if it was
+                let mut changed = false;
+                let now = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+                let mut checkpoint = tweetr::ops::recovery::Checkpoint::at(now);
+                if let Some(ref grid) = evergreen_grid {
+                    if let Some(slot) = tweetr::ops::evergreen::due_slot(grid, evergreen_since, now) {
+                        changed |= fill_evergreen_slot(&opts.config_dir.1, &evergreen_path, slot, tweets, &config, now);
+                    }
+                    evergreen_since = now;
+                }
+                // The due tweets are only re-sorted when the queue changes
+                if reread || changed {
+                    for i in tracker.queued(tweets) {
+                        emit_event(&mut events, tweetr::ops::events::Event::Queued(&tweets[i]));
+                    }
+                    due = tweetr::ops::start_daemon::Due::new(tweets);
+                }
+
+                let tweets_to_post = due.due(&tweetr::ops::clock::SystemClock);
+                let embargo_lifted = tweetr::ops::embargo::lifted(&embargoes, now);
+                let mut deferred = vec![];
+                let mut embargoed = vec![];
+                let mut archived_today = None;
+
+                for i in tweets_to_post {
+                    if tracker.due(&tweets[i]) {
+                        emit_event(&mut events, tweetr::ops::events::Event::Due(&tweets[i]));
+                    }
+                    let user_i = tweetr::ops::start_daemon::find_user_index_for_tweet(&tweets[i], &users);
+                    let effective = match tweetr::ops::settings::Effective::resolve(Some(&tweets[i].settings),
+                                                                                    user_i.as_ref().ok().map(|&u| users[u].settings()).as_ref(),
+                                                                                    &settings) {
+                        Ok(effective) => effective,
+                        Err(out) => {
+                            out.print_error(&mut stderr());
+                            continue;
+                        }
+                    };
+                    let posting_time = effective.posting_time(&tweets[i]);
+                    if posting_time > now {
+                        deferred.push(posting_time);
+                        continue;
+                    }
+                    if embargo_lifted.is_some() {
+                        embargoed.push(i);
+                        continue;
+                    }
+                    if carry_over_capped(&opts.config_dir.1, &mut archived_today, users, tweets, i, &effective, now) {
+                        changed = true;
+                        continue;
+                    }
+                    // Recorded until the queue is, so if posting it's interrupted, it's known it might've been posted
+                    checkpoint.in_flight.push(tweetr::ops::recovery::InFlight::of(&tweets[i], now));
+                    if let Err(out) = checkpoint.write(&checkpoint_path) {
+                        out.print_error(&mut stderr());
+                    }
+                    let notification = match post_queued(&posting, users, tweets, i, &effective, &mut events) {
+                        Ok(true) => {
+                            changed = true;
+                            // Tweets that came due while the daemon wasn't running are late regardless
+                            if posting_time >= started {
+                                let posted = tweets[i].time_posted.unwrap_or_else(|| tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock));
+                                if latency.record(posting_time, posted, latency_threshold) {
+                                    let late = tweetr::ops::notify::Notification::late(latency.average, latency_threshold.unwrap());
+                                    warn(&mut stderr(), &late.body);
+                                    if let Err(e) = notifier.notify(&late) {
+                                        warn(&mut stderr(), &e);
+                                    }
+                                }
+                            }
+                            tweetr::ops::notify::Notification::posted(&tweets[i])
+                        }
+                        Ok(false) => {
+                            checkpoint.in_flight.pop();
+                            continue;
+                        }
+                        Err(out) => {
+                            changed = true;
+                            tweets[i].retries += 1;
+                            if tweetr::ops::events::suspended(&out) {
+                                suspend_author(&users_path, users, &tweets[i].author, now);
+                            }
+                            block_thread(tweets, i, &out);
+                            if tweetr::ops::recovery::hold_timed_out(&mut tweets[i], &out, config.recovery_policy()) {
+                                warn(&mut stderr(),
+                                     &tweetr::ops::l10n::tr("post_timed_out", &[("content", &tweets[i].content), ("author", &tweets[i].author)]));
+                            }
+                            tweetr::ops::notify::Notification::failed(&tweets[i], &out)
+                        }
+                    };
+                    if let Err(e) = notifier.notify(&notification) {
+                        warn(&mut stderr(), &e);
+                    }
+                }
+
+                if let Some(lifted) = embargo_lifted {
+                    changed |= !embargoed.is_empty();
+                    let scheduled: Vec<_> = embargoed.iter().map(|&i| tweets[i].time).collect();
+                    tweetr::ops::embargo::reschedule(tweets,
+                                                     &embargoed,
+                                                     &embargoes,
+                                                     lifted,
+                                                     embargo_policy,
+                                                     config.collision_window(),
+                                                     &embargo_grid);
+                    for (&i, scheduled) in embargoed.iter().zip(scheduled) {
+                        writeln!(diagnostics(),
+                                 "{}",
+                                 tweetr::ops::l10n::tr("embargo_rescheduled",
+                                                       &[("author", &tweets[i].author),
+                                                         ("time", &tweetr::ops::l10n::format_datetime(&scheduled)),
+                                                         ("new_time", &tweetr::ops::l10n::format_datetime(&tweets[i].time)),
+                                                         ("lifted", &tweetr::ops::l10n::format_datetime(&lifted))]))
+                            .unwrap();
+                    }
+                }
+                let archived = match tweetr::ops::archive::rotate(tweets, &opts.config_dir.1, config.archive_size()) {
+                    Ok(archived) => archived,
+                    Err(out) => {
+                        out.print_error(&mut stderr());
+                        vec![]
+                    }
+                };
+                // Broken entries might have media attached too
+                if config.media_retention().is_some() && broken.is_empty() &&
+                   last_media_gc.map(|l| l.elapsed() >= Duration::from_secs(tweetr::ops::media::GC_INTERVAL)).unwrap_or(true) {
+                    gc_media(&opts.config_dir.1, &tweets, config.media_retention(), now);
+                    last_media_gc = Some(Instant::now());
+                }
+                let next_slot = evergreen_grid.as_ref()
+                    .map(|grid| tweetr::ops::suggest_time::free_slots(grid, None, &[], chrono::Duration::zero(), now.with_timezone(&chrono::Local), 1))
+                    .unwrap_or_default();
+                changed |= !archived.is_empty();
+                // Posting on without recording what was posted would post it again
+                if changed {
+                    journal_changes(&opts.config_dir.1, "start-daemon", &persisted, tweets, &archived);
+                    try!(tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), broken, &tweets_path));
+                    persisted = tweets.clone();
+                    due = tweetr::ops::start_daemon::Due::new(tweets);
+                    queue_cache.written();
+                }
+                checkpoint.in_flight.clear();
+                if let Err(out) = checkpoint.write(&checkpoint_path) {
+                    out.print_error(&mut stderr());
+                }
+                let wake = deferred.into_iter()
+                    .chain(next_slot)
+                    .fold(due.next_wake(&tweetr::ops::clock::SystemClock, delay), cmp::min);
+
+                emit_event(&mut events, tweetr::ops::events::Event::Sleeping(wake));
+                tweetr::ops::clock::sleep_until(tweetr::ops::clock::deadline(&tweetr::ops::clock::SystemClock, wake, Instant::now()));
+            }
+            (Err(err), _) => err.unwrap().print_error(&mut stderr()),
+            (_, Err(err)) => err.unwrap().print_error(&mut stderr()),
+        }
+    }
+}
+
+fn recover(config_dir: &Path, checkpoint_path: &Path, tweets_path: &Path, config: &tweetr::ops::Config) {
+    let now = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+    let checkpoint = match tweetr::ops::recovery::Checkpoint::read(checkpoint_path) {
+        Ok(checkpoint) => checkpoint,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            None
+        }
+    };
+    // Errors reading the queue are reported by the daemon's loop
+    let (mut tweets, broken) = match tweetr::ops::QueuedTweet::read_recovering(tweets_path) {
+        Ok(queue) => (queue.tweets, queue.broken),
+        Err(_) => return,
+    };
+
+    let archived = match tweets.iter().filter(|t| t.id.is_some()).flat_map(|t| t.time_posted).min() {
+        Some(since) => {
+            tweetr::ops::archive::read_since(config_dir, since).unwrap_or_else(|out| {
+                out.print_error(&mut stderr());
+                vec![]
+            })
+        }
+        None => vec![],
+    };
+    let journal = tweetr::ops::journal::read(&tweetr::ops::journal::path(config_dir)).unwrap_or_else(|out| {
+        out.print_error(&mut stderr());
+        vec![]
+    });
+    let recoveries = tweetr::ops::recovery::check(checkpoint.as_ref(),
+                                                  &tweets,
+                                                  &archived,
+                                                  &journal,
+                                                  config.recovery_policy(),
+                                                  config.missed_expiry(),
+                                                  now);
+    tweetr::ops::recovery::print_report(&mut stderr(), checkpoint.as_ref().map(|c| c.last_ran(now.offset())), &recoveries);
+
+    let before = tweets.clone();
+    let dropped = tweetr::ops::recovery::apply(&mut tweets, &recoveries);
+    if tweets != before {
+        journal_changes(config_dir, "start-daemon", &before, &tweets, &dropped);
+        if let Err(out) = tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, tweets_path) {
+            out.print_error(&mut stderr());
+        }
+    }
+}
+
+fn carry_over_capped(config_dir: &Path, archived_today: &mut Option<Vec<tweetr::ops::QueuedTweet>>, users: &[tweetr::ops::User],
+                     tweets: &mut [tweetr::ops::QueuedTweet], i: usize, effective: &tweetr::ops::settings::Effective,
+                     now: chrono::DateTime<chrono::FixedOffset>)
+                     -> bool {
+    let max = match users.iter().find(|u| u.name == tweets[i].author).and_then(|u| u.max_posts_per_day) {
+        Some(max) => max,
+        None => return false,
+    };
+    let tz = effective.timezone.0.unwrap_or_else(|| *now.with_timezone(&chrono::Local).offset());
+
+    // The tweets posted earlier in the day were archived already, but only need reading once each pass
+    if archived_today.is_none() {
+        *archived_today = Some(match tweetr::ops::archive::read_since(config_dir, now - chrono::Duration::days(1)) {
+            Ok(archived) => archived,
+            Err(out) => {
+                out.print_error(&mut stderr());
+                vec![]
+            }
+        });
+    }
+    let posted = tweetr::ops::daily_cap::posted_on(tweets, &tweets[i].author, now, tz) +
+                 tweetr::ops::daily_cap::posted_on(archived_today.as_ref().unwrap(), &tweets[i].author, now, tz);
+    if posted < max as usize {
+        return false;
+    }
+
+    let scheduled = tweets[i].time;
+    tweetr::ops::daily_cap::carry_over(&mut tweets[i], now, tz);
+    writeln!(diagnostics(),
+             "{}",
+             tweetr::ops::l10n::tr("daily_cap_carried_over",
+                                   &[("author", &tweets[i].author),
+                                     ("time", &tweetr::ops::l10n::format_datetime(&scheduled)),
+                                     ("new_time", &tweetr::ops::l10n::format_datetime(&tweets[i].time)),
+                                     ("max", &max)]))
+        .unwrap();
+    true
+}
+
+fn gc_media(config_dir: &Path, queue: &[tweetr::ops::QueuedTweet], retention: Option<u32>, now: chrono::DateTime<chrono::FixedOffset>) {
+    let mut tweets = match tweetr::ops::archive::read_all(config_dir) {
+        Ok(archived) => archived,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            return;
+        }
+    };
+    tweets.extend(queue.iter().cloned());
+
+    for (media, size) in tweetr::ops::media::unused(config_dir, &tweets, retention, now) {
+        match fs::remove_file(&media) {
+            Ok(()) => {
+                writeln!(diagnostics(),
+                         "{}",
+                         tweetr::ops::l10n::tr("media_removed",
+                                               &[("path", &media.display()), ("size", &tweetr::util::format_size(size))]))
+                    .unwrap()
+            }
+            Err(e) => warn(&mut stderr(), &format!("couldn't remove {}: {}", media.display(), e)),
+        }
+    }
+}
+
+fn fill_evergreen_slot(config_dir: &Path, pool_path: &Path, slot: chrono::DateTime<chrono::FixedOffset>, tweets: &mut Vec<tweetr::ops::QueuedTweet>,
+                       config: &tweetr::ops::Config, now: chrono::DateTime<chrono::FixedOffset>)
+                       -> bool {
+    let mut pool = match tweetr::ops::evergreen::EvergreenTweet::read(pool_path) {
+        Ok(pool) => pool,
+        Err(Some(out)) => {
+            out.print_error(&mut stderr());
+            return false;
+        }
+        Err(None) => {
+            warn(&mut stderr(), &format!("couldn't read {}", pool_path.display()));
+            return false;
+        }
+    };
+    let mut groups = match tweetr::ops::evergreen::EvergreenGroup::read(pool_path) {
+        Ok(groups) => groups,
+        Err(Some(out)) => {
+            out.print_error(&mut stderr());
+            return false;
+        }
+        Err(None) => {
+            warn(&mut stderr(), &format!("couldn't read {}", pool_path.display()));
+            return false;
+        }
+    };
+    // Tweets posted in the slot count as filling it
+    let mut known = match tweetr::ops::archive::read_since(config_dir, slot - config.collision_window()) {
+        Ok(archived) => archived,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            return false;
+        }
+    };
+    known.extend(tweets.iter().cloned());
+
+    let queued = tweetr::ops::evergreen::fill(&mut pool, &mut groups, slot, &known, config.collision_window(), config.evergreen_cooldown(), now);
+    if queued.is_empty() {
+        return false;
+    }
+    for tweet in &queued {
+        writeln!(diagnostics(),
+                 "{}",
+                 tweetr::ops::l10n::tr("evergreen_queued",
+                                       &[("author", &tweet.author), ("time", &tweetr::ops::l10n::format_datetime(&tweet.time)), ("content", &tweet.content)]))
+            .unwrap();
+    }
+    tweets.extend(queued);
+    if let Err(out) = tweetr::ops::evergreen::EvergreenTweet::write(pool, groups, pool_path) {
+        out.print_error(&mut stderr());
+    }
+    true
+}
+
+fn post_all_main(opts: tweetr::options::Options, up_to: chrono::DateTime<chrono::FixedOffset>, interval: Duration) -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path, tweets_path) = try!(tweetr::ops::start_daemon::verify(&opts.config_dir));
+    let apps = try!(tweetr::ops::Apps::read(&app_path).map_err(Option::unwrap));
+    let config = try!(tweetr::ops::Config::read(&tweetr::ops::Config::path(&opts.config_dir.1)).map_err(Option::unwrap));
+    let hooks_dir = config.hooks_dir(&opts.config_dir.1);
+    let uploads_path = tweetr::ops::media::uploads_path(&opts.config_dir.1);
+    let sinks = try!(tweetr::ops::backend::sinks(&config, &opts.config_dir.1));
+    let pipeline = try!(tweetr::ops::transform::pipeline(&config, &opts.config_dir.1));
+    let settings = config.settings();
+
+    for warning in try!(tweetr::ops::preflight::run(&opts.config_dir.1,
+                                                    &[("app.toml", tweetr::ops::preflight::Access::Read),
+                                                      ("users.toml", tweetr::ops::preflight::Access::Read),
+                                                      ("tweets.toml", tweetr::ops::preflight::Access::ReadWrite),
+                                                      ("config.toml", tweetr::ops::preflight::Access::Read)],
+                                                    config.refuse_insecure_users())) {
+        warn(&mut stderr(), &warning);
+    }
+    let _lock = try!(tweetr::ops::start_daemon::DaemonLock::acquire(&opts.config_dir.1));
+
+    let mut users = try!(tweetr::ops::User::read(&users_path).map_err(Option::unwrap));
+    let tweetr::ops::RecoveredQueue { mut tweets, broken, errors } = try!(tweetr::ops::QueuedTweet::read_recovering(&tweets_path)
+        .map_err(Option::unwrap));
+    if let Some(errors) = errors {
+        errors.print_error(&mut stderr());
+        writeln!(stderr(), "{}", tweetr::ops::l10n::tr("broken_entries_skipped", &[])).unwrap();
+    }
+
+    let order = tweetr::ops::post_all::order(&tweets, up_to);
+    if order.is_empty() {
+        writeln!(diagnostics(), "No tweets scheduled up to {} to post.", tweetr::ops::l10n::format_datetime(&up_to)).unwrap();
+        return Ok(());
+    }
+    tweetr::ops::post_all::print_plan(&mut stdout(), &tweets, &order, up_to, tweetr::util::terminal::terminal_width());
+    let stdin = stdin();
+    if !tweetr::ops::post_all::prompt_confirm(opts.confirmation, &mut stdin.lock(), &mut stderr()) {
+        return Ok(());
+    }
+
+    let posting = Posting {
+        apps: &apps,
+        sinks: &sinks,
+        pipeline: &pipeline,
+        content_warning_format: config.content_warning_format(),
+        hooks_dir: &hooks_dir,
+        uploads_path: &uploads_path,
+    };
+    let mut events = tweetr::ops::events::EventLog::none();
+    let mut posted = 0;
+    let mut first_error = None;
+    let mut rate_limited = false;
+    let mut persisted = tweets.clone();
+    let mut archived_today = None;
+    for (n, &i) in order.iter().enumerate() {
+        if n != 0 {
+            thread::sleep(interval);
+        }
+
+        let user_i = tweetr::ops::start_daemon::find_user_index_for_tweet(&tweets[i], &users);
+        let result = tweetr::ops::settings::Effective::resolve(Some(&tweets[i].settings),
+                                                               user_i.as_ref().ok().map(|&u| users[u].settings()).as_ref(),
+                                                               &settings)
+            .map_err(|out| {
+                out.print_error(&mut stderr());
+                out
+            })
+            .and_then(|effective| {
+                let now = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+                if carry_over_capped(&opts.config_dir.1, &mut archived_today, &users, &mut tweets, i, &effective, now) {
+                    Ok(false)
+                } else {
+                    post_queued(&posting, &users, &mut tweets, i, &effective, &mut events)
+                }
+            });
+        match result {
+            Ok(true) => {
+                posted += 1;
+                journal_changes(&opts.config_dir.1, "post-all", &persisted, &tweets, &[]);
+                persisted = tweets.clone();
+                // Written after each tweet, so ones posted before an interruption aren't posted again
+                if let Err(out) = tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path) {
+                    first_error = first_error.or(Some(out));
+                    break;
+                }
+            }
+            Ok(false) => (),
+            Err(out) => {
+                tweets[i].retries += 1;
+                if tweetr::ops::events::suspended(&out) {
+                    let now = tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock);
+                    suspend_author(&users_path, &mut users, &tweets[i].author, now);
+                }
+                block_thread(&mut tweets, i, &out);
+                if tweetr::ops::recovery::hold_timed_out(&mut tweets[i], &out, config.recovery_policy()) {
+                    warn(&mut stderr(), &tweetr::ops::l10n::tr("post_timed_out", &[("content", &tweets[i].content), ("author", &tweets[i].author)]));
+                }
+                rate_limited = tweetr::ops::events::rate_limited(&out);
+                first_error = first_error.or(Some(out));
+                if rate_limited {
+                    break;
+                }
+            }
+        }
+    }
+
+    let archived = match tweetr::ops::archive::rotate(&mut tweets, &opts.config_dir.1, config.archive_size()) {
+        Ok(archived) => archived,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            vec![]
+        }
+    };
+    journal_changes(&opts.config_dir.1, "post-all", &persisted, &tweets, &archived);
+    let written = tweetr::ops::QueuedTweet::write_with_broken(tweets.clone(), &broken, &tweets_path);
+    tweetr::ops::post_all::print_summary(&mut diagnostics(), posted, order.len(), rate_limited);
+
+    first_error.or(written.err()).map_or(Ok(()), Err)
+}
+
+
+struct Posting<'a> {
+    apps: &'a tweetr::ops::Apps,
+    sinks: &'a [tweetr::ops::backend::Sink],
+    pipeline: &'a tweetr::ops::transform::Pipeline,
+    content_warning_format: &'a str,
+    hooks_dir: &'a Path,
+    uploads_path: &'a Path,
+}
+
+fn post_queued(posting: &Posting, users: &Vec<tweetr::ops::User>, tweets: &mut Vec<tweetr::ops::QueuedTweet>, i: usize,
+               effective: &tweetr::ops::settings::Effective, events: &mut tweetr::ops::events::EventLog)
+               -> Result<bool, tweetr::Outcome> {
+    if tweets[i].blocked.is_some() || !tweetr::ops::start_daemon::anchor_posted(tweets, i) {
+        return Ok(false);
+    }
+    // Built separately, so a tweet that fails to post is left in the queue as it was, and only replaced with what was posted
+    let mut outgoing = effective.apply(&tweets[i]);
+    match tweetr::ops::start_daemon::thread_predecessor(tweets, i) {
+        Ok(Some(pred_id)) => outgoing.in_reply_to = Some(pred_id),
+        Ok(None) => (),
+        Err(_) => return Ok(false),
+    }
+    outgoing.content = posting.pipeline.apply(&outgoing.content);
+    outgoing.content =
+        tweetr::ops::start_daemon::fold_content_warning(&outgoing.content, outgoing.content_warning.as_ref().map(|w| &w[..]), posting.content_warning_format);
+
+    let user_i = tweetr::ops::start_daemon::find_user_index_for_tweet(&outgoing, users);
+    let sink = posting.sinks.iter().find(|s| s.name == outgoing.author);
+    let posted = match (user_i, sink) {
+        (Ok(user_i), _) => {
+            if users[user_i].suspended.is_some() {
+                return Ok(false);
+            }
+            let user_i = match tweetr::ops::start_daemon::test_mode(users, user_i) {
+                Ok(tweetr::ops::start_daemon::TestMode::Off) => user_i,
+                Ok(tweetr::ops::start_daemon::TestMode::Reroute(test_i)) => {
+                    writeln!(diagnostics(), "Test mode: posting as {} instead of {}.", users[test_i].name, users[user_i].name).unwrap();
+                    test_i
+                }
+                Ok(tweetr::ops::start_daemon::TestMode::Mark) => {
+                    outgoing.content = tweetr::ops::start_daemon::mark_test(&outgoing.content);
+                    user_i
+                }
+                Err(out) => {
+                    out.print_error(&mut stderr());
+                    return Err(out);
+                }
+            };
+            let app = match posting.apps.get(users[user_i].app.as_ref().map(|a| &a[..])) {
+                Ok(app) => app,
+                Err(out) => {
+                    out.print_error(&mut stderr());
+                    return Err(out);
+                }
+            };
+            let backend = tweetr::ops::backend::Twitter {
+                user: &users[user_i],
+                app: &app.raw_token(),
+                uploads: Some(posting.uploads_path),
+            };
+            let telegram = users[user_i].telegram();
+            let discord = users[user_i].discord();
+            let mirrors: Vec<&tweetr::ops::backend::PostingBackend> = telegram.iter()
+                .map(|t| t as &tweetr::ops::backend::PostingBackend)
+                .chain(discord.iter().map(|d| d as &tweetr::ops::backend::PostingBackend))
+                .collect();
+            post_with_hooks(&mut outgoing, &backend, &mirrors, posting.hooks_dir, events)
+        }
+        (Err(_), Some(sink)) => post_with_hooks(&mut outgoing, sink, &[], posting.hooks_dir, events),
+        (Err(out), None) => {
+            out.print_error(&mut stderr());
+            Err(out)
+        }
+    };
+
+    try!(posted);
+    tweets[i] = outgoing;
+    Ok(true)
+}
+
+fn suspend_author(users_path: &Path, users: &mut Vec<tweetr::ops::User>, author: &str, now: chrono::DateTime<chrono::FixedOffset>) {
+    if let Some(user) = users.iter_mut().find(|u| u.name == author && u.suspended.is_none()) {
+        user.suspended = Some(now.to_rfc3339());
+        writeln!(stderr(), "{}", tweetr::ops::l10n::tr("account_suspended", &[("user", &author)])).unwrap();
+        if let Err(out) = tweetr::ops::start_daemon::suspend(users_path, author, now) {
+            out.print_error(&mut stderr());
+        }
+    }
+}
+
+fn block_thread(tweets: &mut Vec<tweetr::ops::QueuedTweet>, i: usize, out: &tweetr::Outcome) {
+    if !tweetr::ops::events::permanent(out) {
+        return;
+    }
+
+    let mut reason = vec![];
+    out.print_error(&mut reason);
+    let blocked = tweetr::ops::start_daemon::block_thread(tweets, i, String::from_utf8_lossy(&reason).trim_right());
+    if !blocked.is_empty() {
+        writeln!(stderr(),
+                 "{}",
+                 tweetr::ops::l10n::tr("thread_blocked",
+                                       &[("content", &tweets[i].content),
+                                         ("count", &blocked.len().to_string()),
+                                         ("thread", tweets[i].thread.as_ref().unwrap())]))
+            .unwrap();
+    }
+}
+
+fn post_with_hooks<B: tweetr::ops::backend::PostingBackend + ?Sized>(tweet: &mut tweetr::ops::QueuedTweet, backend: &B,
+                                                                     mirrors: &[&tweetr::ops::backend::PostingBackend], hooks_dir: &Path,
+                                                                     events: &mut tweetr::ops::events::EventLog)
+                                                                     -> Result<(), tweetr::Outcome> {
+    match tweetr::ops::hooks::run(hooks_dir, tweetr::ops::hooks::Hook::PrePost, tweet.clone()) {
+        Ok(hooked) => *tweet = hooked,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            emit_event(events, tweetr::ops::events::Event::failure(tweet, &out));
+            return Err(out);
+        }
+    }
+
+    emit_event(events, tweetr::ops::events::Event::Posting(tweet));
+    let verbose = tweetr::ops::diagnostics::verbose();
+    let out = tweetr::ops::start_daemon::post_tweet(tweet, backend, &tweetr::ops::clock::SystemClock, verbose, &mut diagnostics());
+    if let Some(correction) = tweetr::ops::oauth::take_clock_correction() {
+        warn(&mut stderr(), &tweetr::ops::oauth::describe_clock_correction(correction));
+    }
+    if out == tweetr::Outcome::NoError {
+        emit_event(events, tweetr::ops::events::Event::Posted(tweet));
+        for mirror in mirrors {
+            tweetr::ops::start_daemon::mirror_tweet(tweet, *mirror, verbose, &mut diagnostics()).print_error(&mut stderr());
+        }
+
+        if let Some(hooked) = run_hook(hooks_dir, tweetr::ops::hooks::Hook::PostPost, tweet.clone()) {
+            *tweet = hooked;
+        }
+        Ok(())
+    } else {
+        out.print_error(&mut stderr());
+        emit_event(events, tweetr::ops::events::Event::failure(tweet, &out));
+        Err(out)
+    }
+}
+
+fn journal_changes(config_dir: &Path, actor: &str, before: &[tweetr::ops::QueuedTweet], after: &[tweetr::ops::QueuedTweet],
+                   archived: &[tweetr::ops::QueuedTweet]) {
+    let now = chrono::Local::now();
+    if let Err(out) = tweetr::ops::journal::record(&tweetr::ops::journal::path(config_dir),
+                                                   actor,
+                                                   &tweetr::ops::journal::diff(before, after, archived),
+                                                   now.with_timezone(now.offset())) {
+        out.print_error(&mut stderr());
+    }
+}
+
+fn emit_event(events: &mut tweetr::ops::events::EventLog, event: tweetr::ops::events::Event) {
+    if let Err(e) = events.emit(event, tweetr::ops::clock::Clock::now(&tweetr::ops::clock::SystemClock)) {
+        warn(&mut stderr(), &e);
+    }
+}
+
+/// The standard output, recorded in the transcript, if any, for the interactive subsystems.
+fn transcribed_stdout() -> tweetr::ops::transcript::Transcribed<tweetr::ops::results::Output> {
+    tweetr::ops::transcript::Transcribed::new(stdout())
+}
+
+/// With `--json`, only the results are printed to the standard output, see `ops::results`.
+fn stdout() -> tweetr::ops::results::Output {
+    tweetr::ops::results::output()
+}
+
+fn diagnostics() -> tweetr::ops::diagnostics::Diagnostics<tweetr::ops::transcript::Transcribed<Stderr>> {
+    tweetr::ops::diagnostics::Diagnostics::new(tweetr::ops::transcript::Transcribed::new(stderr()))
+}
+
+fn warn<W: Write>(output: &mut W, warning: &str) {
+    writeln!(output, "{}", tweetr::ops::l10n::tr("warning", &[("warning", &warning)])).unwrap();
+    tweetr::ops::report::warn(warning);
+}
+
+fn run_hook(hooks_dir: &Path, hook: tweetr::ops::hooks::Hook, tweet: tweetr::ops::QueuedTweet) -> Option<tweetr::ops::QueuedTweet> {
+    match tweetr::ops::hooks::run(hooks_dir, hook, tweet) {
+        Ok(tweet) => Some(tweet),
+        Err(out) => {
+            out.print_error(&mut stderr());
+            None
+        }
+    }
+}
+
+fn best_slot(grid: &[tweetr::ops::suggest_time::Slot], tweet: &tweetr::ops::QueuedTweet, queue: &[tweetr::ops::QueuedTweet], window: chrono::Duration)
+             -> chrono::DateTime<chrono::FixedOffset> {
+    tweetr::ops::suggest_time::free_slots(grid, Some(&tweet.author), queue, window, chrono::Local::now(), 1)[0]
+}
+
+fn recent_archive(config_dir: &Path, duplicate_window: chrono::Duration) -> Vec<tweetr::ops::QueuedTweet> {
+    let now = chrono::Local::now();
+    match tweetr::ops::archive::read_since(config_dir, now.with_timezone(now.offset()) - duplicate_window) {
+        Ok(archived) => archived,
+        Err(out) => {
+            out.print_error(&mut stderr());
+            writeln!(stderr(), "Not checking for duplicates of archived tweets.").unwrap();
+            vec![]
+        }
+    }
+}
+
+fn warn_duplicates<W: Write>(output: &mut W, tweet: &tweetr::ops::QueuedTweet, queue: &[tweetr::ops::QueuedTweet],
+                             archived: &[tweetr::ops::QueuedTweet], window: chrono::Duration) {
+    let known: Vec<_> = archived.iter().chain(queue).cloned().collect();
+    let duplicates = tweetr::ops::queue_tweet::duplicates(tweet, &known, window);
+    if !duplicates.is_empty() {
+        tweetr::ops::queue_tweet::print_duplicates(output, tweet, &duplicates, window);
+    }
+}
+
+fn daily_cap_warning(tweet: &tweetr::ops::QueuedTweet, queue: &[tweetr::ops::QueuedTweet], users: &[tweetr::ops::User],
+                     settings: &tweetr::ops::settings::Settings)
+                     -> Option<String> {
+    let user = match users.iter().find(|u| u.name == tweet.author) {
+        Some(user) => user,
+        None => return None,
+    };
+    let max = match user.max_posts_per_day {
+        Some(max) => max,
+        None => return None,
+    };
+    let tz = tweetr::ops::settings::Effective::resolve(Some(&tweet.settings), Some(&user.settings()), settings)
+        .ok()
+        .and_then(|effective| effective.timezone.0)
+        .unwrap_or_else(|| *tweet.time.with_timezone(&chrono::Local).offset());
+    tweetr::ops::daily_cap::check(tweet, queue, max, tz)
+}
+
+fn lint_violations(tweet: &tweetr::ops::QueuedTweet, users: &[tweetr::ops::User]) -> (Vec<String>, bool) {
+    match users.iter().find(|u| u.name == tweet.author).and_then(|u| u.lint.as_ref()) {
+        Some(lint) => {
+            (lint.check(&tweet.content).into_iter().map(|v| format!("Breaks {}'s style rules: {}", tweet.author, v)).collect(),
+             lint.blocks())
+        }
+        None => (vec![], false),
+    }
+}
+
+/// Warn about the violations of the tweet's author's style rules, returning whether it can be queued anyway.
+fn lint_tweet<W: Write>(output: &mut W, tweet: &tweetr::ops::QueuedTweet, users: &[tweetr::ops::User]) -> bool {
+    let (violations, blocks) = lint_violations(tweet, users);
+    if violations.is_empty() {
+        return true;
+    }
+
+    if blocks {
+        writeln!(output, "Can't queue the tweet \"{}\":", tweet.content).unwrap();
+        for violation in violations {
+            writeln!(output, "  {}", violation).unwrap();
+            tweetr::ops::report::warn(&violation);
+        }
+    } else {
+        for violation in violations {
+            warn(output, &violation);
+        }
+    }
+    !blocks
+}
+
+/// Warn about the violations of the tweet's author's style rules to stderr, or fail with them if they block it.
+fn lint_or_refuse(tweet: &tweetr::ops::QueuedTweet, users: &[tweetr::ops::User]) -> Result<(), tweetr::Outcome> {
+    let (violations, blocks) = lint_violations(tweet, users);
+    if blocks && !violations.is_empty() {
+        return Err(tweetr::Outcome::PreflightFailed(violations));
+    }
+    for violation in violations {
+        warn(&mut stderr(), &violation);
+    }
+    Ok(())
+}
+
+fn validation_warnings(tweet: &tweetr::ops::QueuedTweet, dictionary: &Option<BTreeSet<String>>,
+                       accounts: &Option<(Vec<tweetr::ops::User>, tweetr::ops::Apps)>, content_warning_format: &str)
+                       -> Vec<String> {
+    let mut warnings = tweetr::ops::queue_tweet::check_links(&tweet.content);
+    if let Some(ref dictionary) = *dictionary {
+        let misspelt = tweetr::ops::queue_tweet::misspelt_words(&tweet.content, dictionary);
+        if !misspelt.is_empty() {
+            warnings.push(format!("Possibly misspelt words: {}", misspelt.join(", ")));
+        }
+    }
+    let invisible = tweetr::ops::queue_tweet::invisible_chars(&tweet.content);
+    if !invisible.is_empty() {
+        warnings.push(format!("Invisible characters: {}", invisible.join(", ")));
+    }
+    warnings.extend(tweetr::ops::backend::check_variants(tweet));
+    warnings.extend(tweetr::ops::queue_tweet::check_content_warning(tweet, content_warning_format));
+    if let Some((ref users, ref apps)) = *accounts {
+        // Look the mentions up as the tweet's author if it's authenticated, since they might've blocked other users
+        let user = tweetr::ops::start_daemon::find_user_index_for_tweet(tweet, users).ok().map(|i| &users[i]).or_else(|| users.first());
+        if let Some(user) = user {
+            match apps.get(user.app.as_ref().map(|a| &a[..])) {
+                Ok(app) => warnings.extend(tweetr::ops::queue_tweet::check_mentions(&tweet.content, user, &app.raw_token())),
+                Err(out) => {
+                    let mut error = vec![];
+                    out.print_error(&mut error);
+                    warnings.push(format!("Couldn't look up the mentioned accounts: {}", String::from_utf8_lossy(&error).trim()));
+                }
+            }
+        }
+    }
+    warnings
+}
+
+fn validate_tweet(tweet: &tweetr::ops::QueuedTweet, dictionary: &Option<BTreeSet<String>>,
+                  accounts: &Option<(Vec<tweetr::ops::User>, tweetr::ops::Apps)>, content_warning_format: &str) {
+    let warnings = validation_warnings(tweet, dictionary, accounts, content_warning_format);
+    if !warnings.is_empty() {
+        writeln!(stderr(),
+                 "Warnings for tweet \"{}\" scheduled for {}:",
+                 tweet.content,
+                 tweetr::ops::l10n::format_datetime(&tweet.time))
+            .unwrap();
+        for warning in warnings {
+            writeln!(stderr(), "  {}", warning).unwrap();
+            tweetr::ops::report::warn(&warning);
         }
     }
 }
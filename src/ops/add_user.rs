@@ -10,14 +10,30 @@
 //! |> ops::add_user::append_user()
 //! |> ops::add_user::print_success_message()
 //! ```
+//!
+//! If the user's already been added, what merging and replacing their entry would change is printed before appending, and
+//! the user chooses between them, or aborting:
+//!
+//! ```plaintext
+//! ops::add_user::authorise()
+//! |> ops::add_user::print_changes()
+//! |> util::resolve()
+//! |> ops::add_user::apply()
+//! |> ops::add_user::append_user()
+//! ```
 
 use egg_mode::{Token, request_token, authorize_url, access_token};
-use self::super::super::util::{prompt_exact_len, span_r};
+use self::super::super::util::{Resolution, prompt_exact_len, span_r};
+use self::super::l10n::tr;
 use self::super::{User, verify_file};
+use self::super::{network, oauth};
+use self::super::transcript;
+use self::super::results;
 use self::super::super::Outcome;
 use std::path::{Path, PathBuf};
 use std::io::{BufRead, Write};
 use std::str::FromStr;
+use toml::{self, Value};
 
 
 /// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `add-user`
@@ -48,13 +64,11 @@ use std::str::FromStr;
 /// ```
 /// # use tweetr::ops::add_user;
 /// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
 /// # use tweetr::Outcome;
 /// let tf = temp_dir().join("tweetr-doctest").join("ops-add-user-verify-1");
 /// assert_eq!(add_user::verify(&("$TEMP/ops-add-user-verify-1".to_string(), tf)),
-///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
-///                subsys: "init",
-///                fname: "$TEMP/ops-add-user-verify-1/app.toml".to_string(),
-///            }));
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
 /// ```
 pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
     let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
@@ -84,8 +98,15 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
 
     let req_token = try!(wrap_network_op_in_ellipsis_done(output,
                                                           || {
-                                                              let req_token = request_token(&conn_token, "oob")
-                                                                  .map_err(|e| Outcome::TwitterAPIError(format!("{}", e)));
+                                                              let conn_token = network::owned_token(&conn_token);
+                                                              let req_token = network::request("requesting a request token", move || {
+                                                                  if network::bind_address().is_some() {
+                                                                      oauth::request_token(&conn_token)
+                                                                  } else {
+                                                                      request_token(&conn_token, "oob")
+                                                                          .map_err(|e| network::egg_mode_error("requesting a request token", e))
+                                                                  }
+                                                              });
                                                               (req_token.is_ok(), req_token)
                                                           },
                                                           "request token",
@@ -93,13 +114,24 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
                                                           false,
                                                           true));
 
-    writeln!(output, "Visit this URL: {}", authorize_url(&req_token)).unwrap();
-    let pin = prompt_exact_len(input, output, "Enter the PIN from that page", |s| u32::from_str(s).is_ok(), 7).unwrap();
+    writeln!(output, "{}", tr("authorisation_url", &[("url", &authorize_url(&req_token))])).unwrap();
+    let pin = {
+        let _secret = transcript::secret();
+        prompt_exact_len(input, output, &tr("prompt_pin", &[]), |s| u32::from_str(s).is_ok(), 7).unwrap()
+    };
 
     let access_token_data = try!(wrap_network_op_in_ellipsis_done(output,
                                                                   || {
-                                                                      let access_token_data = access_token(&conn_token, &req_token, pin)
-                                                                          .map_err(|e| Outcome::TwitterAPIError(format!("{}", e)));
+                                                                      let conn_token = network::owned_token(&conn_token);
+                                                                      let req_token = network::owned_token(&req_token);
+                                                                      let access_token_data = network::request("requesting an access token", move || {
+                                                                          if network::bind_address().is_some() {
+                                                                              oauth::access_token(&conn_token, &req_token, &pin)
+                                                                          } else {
+                                                                              access_token(&conn_token, &req_token, pin.clone())
+                                                                                  .map_err(|e| network::egg_mode_error("requesting an access token", e))
+                                                                          }
+                                                                      });
                                                                       (access_token_data.is_ok(), access_token_data)
                                                                   },
                                                                   "access token",
@@ -126,12 +158,27 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
 ///     id: 0x969696969,
 ///     access_token_key: "40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(),
 ///     access_token_secret: "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
 /// });
 /// assert!(tf.exists());
 /// ```
 pub fn append_user(users_path: &Path, user: User) -> Outcome {
     let mut users = if users_path.exists() {
-        match User::read(users_path).map_err(Option::unwrap) {
+        match User::read_verbatim(users_path).map_err(Option::unwrap) {
             Ok(users) => users,
             Err(out) => return out,
         }
@@ -139,35 +186,61 @@ pub fn append_user(users_path: &Path, user: User) -> Outcome {
         vec![]
     };
 
+    let (name, id) = (user.name.clone(), user.id);
     match users.binary_search_by(|u| u.cmp(&user)) {
         Ok(curidx) => users[curidx] = user,
         Err(possidx) => users.insert(possidx, user),
     }
 
-    User::write(users, &users_path);
-    Outcome::NoError
+    match User::write(users, &users_path) {
+        Ok(()) => {
+            results::created("user", &name, Some(id));
+            results::stored_tokens("user", &name, &["access_token_key", "access_token_secret"]);
+            Outcome::NoError
+        }
+        Err(out) => out,
+    }
 }
 
-/// Print the success message mentioning the specified user's name and ID, optionally also mentioning tokens.
+/// Print the success message mentioning the specified user's name and ID as a diagnostic, optionally also printing tokens
+/// as data.
 ///
 /// # Examples
 ///
 /// ```
 /// # use tweetr::ops::{add_user, User};
 /// # use std::iter::FromIterator;
+/// # use std::io::sink;
 /// let mut out = Vec::new();
-/// add_user::print_success_message(&mut out, &User {
+/// add_user::print_success_message(&mut sink(), &mut out, &User {
 ///     name: "random-test-name".to_string(),
 ///     id: 0x42069,
 ///     access_token_key: "270441-N48kdEQFWtj7cUyWomNeE2AsNQw8pnmOaQbcwnV".to_string(),
 ///     access_token_secret: "jCcBthGzve36QMt3RAV6jOEg4qtHt7laMV2YFA3qKCRzw".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
 /// }, false);
 /// assert_eq!(out, Vec::from_iter(b"Successfully authenticated user random-test-name#270441\n".iter().cloned()));
 /// ```
-pub fn print_success_message<W: Write>(output: &mut W, user: &User, verbose: bool) {
-    writeln!(output, "Successfully authenticated user {}#{}", user.name, user.id).unwrap();
+pub fn print_success_message<W: Write, D: Write>(output: &mut W, diagnostics: &mut D, user: &User, verbose: bool) {
+    writeln!(diagnostics, "{}", tr("user_authenticated", &[("name", &user.name), ("id", &user.id)])).unwrap();
     if verbose {
-        writeln!(output, "Access tokens:").unwrap();
+        transcript::hide(&user.access_token_key);
+        transcript::hide(&user.access_token_secret);
+        writeln!(output, "{}", tr("access_tokens", &[])).unwrap();
         writeln!(output, "  Key   : {}", user.access_token_key).unwrap();
         writeln!(output, "  Secret: {}", user.access_token_secret).unwrap();
     }
@@ -190,7 +263,7 @@ fn wrap_network_op_in_ellipsis_done<W, T, F>(output: &mut W, f: F, desc: &str, v
         if succeeded {
             writeln!(output, " {}ms", dur.num_milliseconds()).unwrap();
         } else {
-            writeln!(output, " FAILED").unwrap();
+            writeln!(output, " {}", tr("failed", &[])).unwrap();
         }
         if nl_after {
             writeln!(output, "").unwrap();
@@ -201,3 +274,159 @@ fn wrap_network_op_in_ellipsis_done<W, T, F>(output: &mut W, f: F, desc: &str, v
         f().1
     }
 }
+
+/// Get the entry resulting from resolving the conflict between the existing entry of a user and the one they were just
+/// authorised with as specified, or `None` if aborted.
+///
+/// Merging updates only the ID, tokens and app, keeping the user's settings and when they were added; replacing uses the
+/// new entry as-is.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{add_user, User};
+/// # use tweetr::util::Resolution;
+/// let existing = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 0x81423,
+///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: Some("+02:00".to_string()),
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: Some(vec!["#tweetr".to_string()]),
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: Some("2016-09-09T12:00:00+02:00".to_string()),
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// let authorised = User {
+///     access_token_key: "529443-Fq8oFqjPD5lJcnKUaRs0YW6PhxhHpaM4cMPWE0z".to_string(),
+///     access_token_secret: "Hw1GL9S08Cdo4rm1iwU0PFuWGhEa9JGn4BMfA4y23l6ks".to_string(),
+///     app: Some("client".to_string()),
+///     timezone: None,
+///     tags: None,
+///     added: Some("2016-10-10T12:00:00+02:00".to_string()),
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+///     ..existing.clone()
+/// };
+///
+/// assert_eq!(add_user::apply(&existing, authorised.clone(), Resolution::Merge),
+///            Some(User {
+///                access_token_key: authorised.access_token_key.clone(),
+///                access_token_secret: authorised.access_token_secret.clone(),
+///                app: Some("client".to_string()),
+///                ..existing.clone()
+///            }));
+/// assert_eq!(add_user::apply(&existing, authorised.clone(), Resolution::Replace), Some(authorised.clone()));
+/// assert_eq!(add_user::apply(&existing, authorised, Resolution::Abort), None);
+/// ```
+pub fn apply(existing: &User, authorised: User, resolution: Resolution) -> Option<User> {
+    match resolution {
+        Resolution::Merge => {
+            Some(User {
+                id: authorised.id,
+                access_token_key: authorised.access_token_key,
+                access_token_secret: authorised.access_token_secret,
+                app: authorised.app,
+                ..existing.clone()
+            })
+        }
+        Resolution::Replace => Some(authorised),
+        Resolution::Abort => None,
+    }
+}
+
+/// Print what merging and replacing the existing entry of a user with the one they were just authorised with would
+/// change in the specified file.
+///
+/// Changed keys are listed with `~`, added ones with `+` and removed ones with `-`. Values aren't printed, since most of them
+/// are secret.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{add_user, User};
+/// let existing = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 0x81423,
+///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: Some(vec!["#tweetr".to_string()]),
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+///
+/// let mut out = Vec::new();
+/// add_user::print_changes(&mut out,
+///                         "$HOME/.tweetr/users.toml",
+///                         &existing,
+///                         &User {
+///                             access_token_key: "529443-Fq8oFqjPD5lJcnKUaRs0YW6PhxhHpaM4cMPWE0z".to_string(),
+///                             tags: None,
+///                             ..existing.clone()
+///                         });
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "\"$HOME/.tweetr/users.toml\" already contains the user nabijaczleweli.\n\
+///             Merging would change:\n\
+///             \x20 ~ access_token_key\n\
+///             Replacing would change:\n\
+///             \x20 ~ access_token_key\n\
+///             \x20 - tags\n\
+///             \n");
+/// ```
+pub fn print_changes<W: Write>(output: &mut W, fname: &str, existing: &User, authorised: &User) {
+    writeln!(output, "{}", tr("conflict", &[("fname", &fname), ("entry", &format!("user {}", existing.name))])).unwrap();
+
+    for &(resolution, heading) in &[(Resolution::Merge, "merge_changes"), (Resolution::Replace, "replace_changes")] {
+        writeln!(output, "{}", tr(heading, &[])).unwrap();
+
+        let changes = diff(existing, &apply(existing, authorised.clone(), resolution).unwrap());
+        if changes.is_empty() {
+            writeln!(output, "  {}", tr("no_changes", &[])).unwrap();
+        }
+        for change in changes {
+            writeln!(output, "  {}", change).unwrap();
+        }
+    }
+    writeln!(output, "").unwrap();
+}
+
+
+fn diff(before: &User, after: &User) -> Vec<String> {
+    match (toml::encode(before), toml::encode(after)) {
+        (Value::Table(before), Value::Table(after)) => {
+            before.keys()
+                .chain(after.keys().filter(|key| !before.contains_key(*key)))
+                .filter_map(|key| match (before.get(key), after.get(key)) {
+                    (Some(b), Some(a)) if b != a => Some(format!("~ {}", key)),
+                    (Some(_), None) => Some(format!("- {}", key)),
+                    (None, Some(_)) => Some(format!("+ {}", key)),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
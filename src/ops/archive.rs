@@ -0,0 +1,307 @@
+//! Rotating posted tweets out of the queue into per-month archive files, and pruning them.
+//!
+//! The `start-daemon` subsystem archives the tweets it posts, the flow of the `prune` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::archive::expired()
+//! |> std::fs::remove_file()
+//! ```
+//!
+//! Posted tweets are archived in `archive-YYYY-MM.toml` files, by the month they were posted in, in local time.
+//! If a maximum archive size is configured, a month's tweets past it go to `archive-YYYY-MM.1.toml`,
+//! `archive-YYYY-MM.2.toml` and so on.
+
+
+use chrono::{DateTime, Date, Datelike, FixedOffset, Local, TimeZone};
+use self::super::super::Outcome;
+use self::super::QueuedTweet;
+use std::collections::BTreeMap;
+use std::path::{PathBuf, Path};
+use std::{cmp, fs};
+
+
+/// Get the path to the specified part of the archive of the specified month.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::archive;
+/// # use chrono::{Local, TimeZone};
+/// # use std::env::temp_dir;
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-archive-path-0");
+/// assert_eq!(archive::path(&tf, Local.ymd(2016, 9, 14), 0), tf.join("archive-2016-09.toml"));
+/// assert_eq!(archive::path(&tf, Local.ymd(2016, 9, 14), 2), tf.join("archive-2016-09.2.toml"));
+/// # }
+/// ```
+pub fn path(config_dir: &Path, month: Date<Local>, part: usize) -> PathBuf {
+    config_dir.join(if part == 0 {
+        format!("archive-{}.toml", month.format("%Y-%m"))
+    } else {
+        format!("archive-{}.{}.toml", month.format("%Y-%m"), part)
+    })
+}
+
+/// Find all archive files in the specified directory, with the first days of their months, oldest first.
+pub fn files(config_dir: &Path) -> Vec<(Date<Local>, PathBuf)> {
+    let mut files: Vec<_> = fs::read_dir(config_dir)
+        .map(|rd| {
+            rd.flat_map(Result::ok)
+                .flat_map(|f| {
+                    let fname = f.file_name().to_string_lossy().into_owned();
+                    parse_fname(&fname).map(|(month, part)| (month, part, f.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_else(|_| vec![]);
+    files.sort_by_key(|&(month, part, _)| (month, part));
+
+    files.into_iter().map(|(month, _, path)| (month, path)).collect()
+}
+
+/// Get the indices of the tweets that can be moved out of the queue into the archive.
+///
+/// These are the posted tweets, except for ones in threads still being posted, which need them to reply to, and ones unposted
+/// tweets are scheduled relative to, which need their posting time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{archive, Anchor, QueuedTweet};
+/// # use chrono::Local;
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let posted = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: now,
+///     content: "Abolish".to_string(),
+///     time_posted: Some(now),
+///     id: Some(420),
+///     ..Default::default()
+/// };
+/// let tweets = vec![posted.clone(),
+///                   QueuedTweet { thread: Some("manifesto".to_string()), ..posted.clone() },
+///                   QueuedTweet { thread: Some("manifesto".to_string()), time_posted: None, id: None, ..posted.clone() },
+///                   QueuedTweet { time_posted: None, id: None, ..posted.clone() }];
+///
+/// assert_eq!(archive::archivable(&tweets), vec![0]);
+///
+/// let tweets = vec![QueuedTweet { name: Some("launch".to_string()), ..posted.clone() },
+///                   QueuedTweet {
+///                       after: Some(Anchor {
+///                           name: "launch".to_string(),
+///                           delay: 15 * 60,
+///                       }),
+///                       time_posted: None,
+///                       id: None,
+///                       ..posted.clone()
+///                   }];
+/// assert!(archive::archivable(&tweets).is_empty());
+/// # }
+/// ```
+pub fn archivable(tweets: &[QueuedTweet]) -> Vec<usize> {
+    tweets.iter()
+        .enumerate()
+        .filter(|&(_, t)| {
+            t.id.is_some() &&
+            match t.thread {
+                Some(ref thread) => !tweets.iter().any(|o| o.id.is_none() && o.thread.as_ref() == Some(thread)),
+                None => true,
+            } &&
+            match t.name {
+                Some(ref name) => !tweets.iter().any(|o| o.id.is_none() && o.after.as_ref().map(|a| &a.name) == Some(name)),
+                None => true,
+            }
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Move the archivable tweets (see `archivable()`) out of the specified queue into the archive files in the specified directory,
+/// with at most the specified amount of tweets per file, which must be non-zero, returning the archived tweets.
+///
+/// The queue is left untouched if any of the archive files to add to can't be read.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{archive, QueuedTweet};
+/// # use chrono::{Local, TimeZone};
+/// # use std::env::temp_dir;
+/// # use std::fs;
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-archive-rotate-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// let posted = Local.ymd(2016, 9, 14).and_hms(9, 0, 0);
+/// let posted = posted.with_timezone(posted.offset());
+/// let mut tweets = vec![QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: posted,
+///     content: "Abolish".to_string(),
+///     time_posted: Some(posted),
+///     id: Some(420),
+///     ..Default::default()
+/// }];
+/// let archived = tweets.clone();
+///
+/// assert_eq!(archive::rotate(&mut tweets, &tf, None), Ok(archived.clone()));
+/// assert!(tweets.is_empty());
+/// assert_eq!(QueuedTweet::read(&tf.join("archive-2016-09.toml")), Ok(archived));
+/// # }
+/// ```
+pub fn rotate(tweets: &mut Vec<QueuedTweet>, config_dir: &Path, max_size: Option<usize>) -> Result<Vec<QueuedTweet>, Outcome> {
+    let archivable = archivable(tweets);
+    if archivable.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut by_month = BTreeMap::new();
+    for &i in &archivable {
+        by_month.entry(month_of(tweets[i].time_posted.as_ref().unwrap())).or_insert_with(Vec::new).push(tweets[i].clone());
+    }
+
+    let mut to_write = vec![];
+    for (month, mut new) in by_month {
+        let mut part = 0;
+        while path(config_dir, month, part + 1).exists() {
+            part += 1;
+        }
+
+        let mut archived = try!(read_part(&path(config_dir, month, part)));
+        loop {
+            let room = max_size.map(|s| s.saturating_sub(archived.len())).unwrap_or(new.len());
+            let rest = new.split_off(cmp::min(room, new.len()));
+            archived.append(&mut new);
+            to_write.push((path(config_dir, month, part), archived));
+
+            if rest.is_empty() {
+                break;
+            }
+            new = rest;
+            archived = vec![];
+            part += 1;
+        }
+    }
+
+    for (p, archived) in to_write {
+        try!(QueuedTweet::write(archived, &p));
+    }
+    let mut archived: Vec<_> = archivable.into_iter().rev().map(|i| tweets.remove(i)).collect();
+    archived.reverse();
+    Ok(archived)
+}
+
+/// Read the archived tweets posted since the specified time, from the archive files in the specified directory.
+///
+/// Only the archive files of the months since then are read.
+pub fn read_since(config_dir: &Path, since: DateTime<FixedOffset>) -> Result<Vec<QueuedTweet>, Outcome> {
+    let since_month = month_of(&since);
+
+    let mut tweets = vec![];
+    for (_, p) in files(config_dir).into_iter().filter(|&(month, _)| month >= since_month) {
+        tweets.extend(try!(read_part(&p)).into_iter().filter(|t| t.time_posted.map(|tp| tp >= since).unwrap_or(false)));
+    }
+    Ok(tweets)
+}
+
+/// Read all archived tweets, from the archive files in the specified directory, oldest month first.
+pub fn read_all(config_dir: &Path) -> Result<Vec<QueuedTweet>, Outcome> {
+    let mut tweets = vec![];
+    for (_, p) in files(config_dir) {
+        tweets.extend(try!(read_part(&p)));
+    }
+    Ok(tweets)
+}
+
+/// Find the archive files in the specified directory whose months ended more than the specified amount of months before
+/// the month of the specified day.
+///
+/// A retention reaching back before year 1 keeps everything.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::archive;
+/// # use chrono::{Local, TimeZone};
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-archive-expired-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// for fname in &["archive-2016-06.toml", "archive-2016-07.toml", "archive-2016-07.1.toml", "archive-2016-08.toml"] {
+///     File::create(tf.join(fname)).unwrap();
+/// }
+///
+/// assert_eq!(archive::expired(&tf, 1, Local.ymd(2016, 9, 14)),
+///            vec![tf.join("archive-2016-06.toml"), tf.join("archive-2016-07.toml"), tf.join("archive-2016-07.1.toml")]);
+/// assert!(archive::expired(&tf, 3, Local.ymd(2016, 9, 14)).is_empty());
+/// assert!(archive::expired(&tf, u32::max_value(), Local.ymd(2016, 9, 14)).is_empty());
+/// # }
+/// ```
+pub fn expired(config_dir: &Path, retention: u32, today: Date<Local>) -> Vec<PathBuf> {
+    let months = today.year() as i64 * 12 + today.month0() as i64 - retention as i64;
+    let oldest_kept = match Local.ymd_opt((months / 12) as i32, (months % 12) as u32 + 1, 1).earliest() {
+        Some(oldest_kept) if months >= 12 => oldest_kept,
+        _ => return vec![],
+    };
+
+    files(config_dir).into_iter().filter(|&(month, _)| month < oldest_kept).map(|(_, p)| p).collect()
+}
+
+
+fn month_of(time: &DateTime<FixedOffset>) -> Date<Local> {
+    time.with_timezone(&Local).date().with_day(1).unwrap()
+}
+
+fn read_part(p: &Path) -> Result<Vec<QueuedTweet>, Outcome> {
+    if p.exists() {
+        QueuedTweet::read(p).map_err(|e| {
+            e.unwrap_or_else(|| {
+                Outcome::FileParsingFailed {
+                    desc: "archived tweets",
+                    errors: vec![format!("error: {} can't be read", p.display())],
+                }
+            })
+        })
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn parse_fname(fname: &str) -> Option<(Date<Local>, usize)> {
+    if !fname.starts_with("archive-") || !fname.ends_with(".toml") {
+        return None;
+    }
+    let stem = &fname["archive-".len()..fname.len() - ".toml".len()];
+
+    let mut parts = stem.splitn(2, '.');
+    let month = parts.next().unwrap();
+    let part = match parts.next() {
+        Some(part) => match part.parse() {
+            Ok(part) if part != 0 => part,
+            _ => return None,
+        },
+        None => 0,
+    };
+
+    let mut month = month.splitn(2, '-');
+    match (month.next().and_then(|y| y.parse().ok()), month.next().and_then(|m| m.parse().ok())) {
+        (Some(year), Some(month)) if month >= 1 && month <= 12 => Local.ymd_opt(year, month, 1).single().map(|m| (m, part)),
+        _ => None,
+    }
+}
@@ -0,0 +1,674 @@
+//! Where due tweets are delivered to.
+//!
+//! Tweets are posted to Twitter on behalf of the user named by their `author`, or, if no such user exists, delivered to the
+//! sink with that name, configured in the global configuration (see `Config::sink`).
+//!
+//! A sink either appends the tweet's content to a file, one per line, runs a shell command with the content on its
+//! standard input and the author and scheduled time in the `TWEETR_AUTHOR` and `TWEETR_TIME` (RFC3339) environment
+//! variables, or executes a Discord webhook. File and command sinks report the posting time in milliseconds since the epoch
+//! as the tweet's ID.
+//!
+//! Tweets posted to Twitter are additionally mirrored to the user's Telegram chat and Discord webhook, if configured (see
+//! `User::telegram()` and `User::discord()`), with the content of their `variants` for that backend, if any, cut to fit the
+//! backend's length limit, see `adapt()`.
+
+
+use self::super::super::util::shell;
+use chrono::Local;
+use self::super::{Config, QueuedTweet, key_error, network};
+use std::process::Stdio;
+use std::collections::BTreeMap;
+use self::super::super::Outcome;
+use rustc_serialize::json::Json;
+use std::io::Write;
+use std::fs::OpenOptions;
+use std::path::Path;
+#[cfg(feature = "network")]
+use self::super::{ReplySettings, User, media, oauth};
+#[cfg(feature = "network")]
+#[cfg(feature = "network")]
+use url::form_urlencoded::Serializer;
+#[cfg(feature = "network")]
+use hyper::header::ContentType;
+#[cfg(feature = "network")]
+use hyper::status::StatusCode;
+#[cfg(feature = "network")]
+use std::io::Read;
+#[cfg(feature = "network")]
+use egg_mode::Token;
+
+
+/// The API endpoint tweets are posted to.
+pub static STATUSES_UPDATE_URL: &'static str = "https://api.twitter.com/1.1/statuses/update.json";
+
+/// The v2 API endpoint tweets restricting who can reply are posted to.
+pub static TWEETS_V2_URL: &'static str = "https://api.twitter.com/2/tweets";
+
+/// The Telegram Bot API endpoint, to be suffixed with the bot token and the method name.
+pub static TELEGRAM_API_URL: &'static str = "https://api.telegram.org/bot";
+
+/// The backends tweets can have `variants` for, and the most characters they accept.
+pub static VARIANTS: &'static [(&'static str, usize)] = &[("telegram", 4096), ("discord", 2000)];
+
+
+/// Something due tweets can be delivered to.
+pub trait PostingBackend {
+    /// Get a human-readable description of where tweets are delivered to.
+    fn description(&self) -> String;
+
+    /// Deliver the specified tweet, returning the ID it was posted with.
+    ///
+    /// The time it was posted at is taken from the daemon's clock instead of the backend's, which needn't agree with it,
+    /// nor be as precise.
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome>;
+
+    /// Get the name of the application credential set tweets are posted through, if not the default one.
+    ///
+    /// Recorded in posted tweets' `app`. Twitter attributes tweets to the application's registered name, which can't be
+    /// overridden per tweet, so posting via a different client means adding the user under another application.
+    fn app(&self) -> Option<String> {
+        None
+    }
+
+    /// Get the key of tweets' `variants` for the backend, if it accepts them, see `VARIANTS`.
+    fn variant(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+
+/// Posts tweets to Twitter on behalf of the specified user of the specified application.
+///
+/// Tweets with `extra_params` or a location are sent to `STATUSES_UPDATE_URL` directly, with the extra parameters merged into and
+/// signed along with the ones tweetr sets itself.
+///
+/// Tweets restricting who can reply are sent to `TWEETS_V2_URL` instead, see `tweet_v2_body()`.
+///
+/// The files in tweets' `media` are uploaded first, see `media::upload_file()`, and the tweets sent directly, with the
+/// resulting media IDs.
+#[cfg(feature = "network")]
+#[derive(Clone)]
+pub struct Twitter<'a> {
+    /// The user to post on behalf of.
+    pub user: &'a User,
+    /// The application's tokens.
+    pub app: &'a Token<'a>,
+    /// The file to record the progress of media uploads in segments in, so they're resumed if interrupted, if any.
+    pub uploads: Option<&'a Path>,
+}
+
+/// Delivers tweets to a file, a command or a Discord webhook instead of posting them, see the module-level documentation.
+///
+/// Exactly one of `file`, `command` and `discord_webhook` needs to be set.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Sink {
+    /// The name tweets refer to the sink by in their `author`.
+    pub name: String,
+    /// File to append the tweets to.
+    ///
+    /// Relative paths are resolved against the configuration directory.
+    pub file: Option<String>,
+    /// Shell command to pipe the tweets to.
+    pub command: Option<String>,
+    /// URL of the Discord webhook to execute with the tweets.
+    pub discord_webhook: Option<String>,
+}
+
+/// Sends tweets to a Telegram chat or channel as the specified bot.
+///
+/// Tweets ending with a link to an image are sent as that photo, captioned with the rest of the content,
+/// see `photo_link()`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Telegram {
+    /// The bot's token, as given by @BotFather.
+    pub bot_token: String,
+    /// The chat's ID or the channel's `@username`.
+    pub chat_id: String,
+}
+
+/// Executes a Discord webhook with tweets.
+///
+/// Tweets ending with a link to an image get it attached as an embedded image instead, see `photo_link()`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Discord {
+    /// The webhook's URL, as given by the channel's integration settings.
+    pub webhook_url: String,
+}
+
+
+/// Get the sinks from the specified configuration, with their files resolved against the specified configuration directory.
+///
+/// Fails if a sink doesn't specify exactly one of a file, a command and a Discord webhook.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::backend::{self, Sink};
+/// # use tweetr::ops::Config;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-backend-sinks-0");
+/// let config = Config {
+///     sink: Some(vec![Sink {
+///                         name: "motd".to_string(),
+///                         file: Some("motd".to_string()),
+///                         command: None,
+///                         discord_webhook: None,
+///                     }]),
+///     ..Config::default()
+/// };
+///
+/// assert_eq!(backend::sinks(&config, &tf),
+///            Ok(vec![Sink {
+///                        name: "motd".to_string(),
+///                        file: Some(tf.join("motd").display().to_string()),
+///                        command: None,
+///                        discord_webhook: None,
+///                    }]));
+/// ```
+pub fn sinks(config: &Config, config_dir: &Path) -> Result<Vec<Sink>, Outcome> {
+    config.sinks()
+        .iter()
+        .enumerate()
+        .map(|(i, sink)| match (&sink.file, &sink.command, &sink.discord_webhook) {
+            (&Some(ref file), &None, &None) => Ok(Sink { file: Some(config_dir.join(file).display().to_string()), ..sink.clone() }),
+            (&None, &Some(_), &None) |
+            (&None, &None, &Some(_)) => Ok(sink.clone()),
+            _ => {
+                Err(key_error(&Config::path(config_dir),
+                              "configuration",
+                              &format!("sink.{}", i),
+                              &format!("sink \"{}\" needs exactly one of `file`, `command` and `discord_webhook`", sink.name)))
+            }
+        })
+        .collect()
+}
+
+/// Get the specified tweet as delivered to the backend with the specified `variants` key: with the variant's content, if it
+/// has one, cut to the backend's length limit, if too long, see `VARIANTS`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::backend::adapt;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     variants: vec![("telegram".to_string(), "Abolish the bourgeoisie, read more at https://example.com/manifesto".to_string()),
+///                    ("discord".to_string(), "Abolish ".repeat(300))]
+///         .into_iter()
+///         .collect(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(adapt(&tweet, "telegram").content, "Abolish the bourgeoisie, read more at https://example.com/manifesto");
+/// assert_eq!(adapt(&tweet, "discord").content.chars().count(), 2000);
+/// assert!(adapt(&tweet, "discord").content.ends_with("Abolish…"));
+/// assert_eq!(adapt(&tweet, "irc").content, "Abolish the bourgeoisie");
+/// # }
+/// ```
+pub fn adapt(tweet: &QueuedTweet, variant: &str) -> QueuedTweet {
+    let content = tweet.variants.get(variant).unwrap_or(&tweet.content);
+    let content = match VARIANTS.iter().find(|&&(v, _)| v == variant) {
+        Some(&(_, max_length)) if content.chars().count() > max_length => format!("{}…", content.chars().take(max_length - 1).collect::<String>()),
+        _ => content.clone(),
+    };
+    QueuedTweet { content: content, ..tweet.clone() }
+}
+
+/// Check the specified tweet's `variants` for backends that don't accept them and content that'll be cut to fit.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::backend::check_variants;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     variants: vec![("discord".to_string(), "Abolish ".repeat(300)), ("mastodon".to_string(), "Abolish".to_string())]
+///         .into_iter()
+///         .collect(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(check_variants(&tweet),
+///            vec!["The discord variant is 2400 characters long, it'll be cut to 2000".to_string(),
+///                 "No backend accepts the mastodon variant, expected telegram or discord".to_string()]);
+/// # }
+/// ```
+pub fn check_variants(tweet: &QueuedTweet) -> Vec<String> {
+    tweet.variants
+        .iter()
+        .flat_map(|(variant, content)| match VARIANTS.iter().find(|&&(v, _)| v == variant) {
+            Some(&(_, max_length)) if content.chars().count() > max_length => {
+                Some(format!("The {} variant is {} characters long, it'll be cut to {}", variant, content.chars().count(), max_length))
+            }
+            Some(_) => None,
+            None => {
+                Some(format!("No backend accepts the {} variant, expected {}",
+                             variant,
+                             VARIANTS.iter().map(|&(v, _)| v).collect::<Vec<_>>().join(" or ")))
+            }
+        })
+        .collect()
+}
+
+/// Print the content the specified tweet will be mirrored with to each backend, if it has any `variants`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::backend::print_variants;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// print_variants(&mut out, &tweet);
+/// assert!(out.is_empty());
+///
+/// tweet.variants.insert("telegram".to_string(), "Abolish the bourgeoisie, read more at https://example.com/manifesto".to_string());
+/// let mut out = Vec::new();
+/// print_variants(&mut out, &tweet);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "When mirrored, the tweet will be posted:\n  \
+///               to Twitter as: \"Abolish the bourgeoisie\"\n  \
+///               to telegram as: \"Abolish the bourgeoisie, read more at https://example.com/manifesto\"\n  \
+///               to discord as: \"Abolish the bourgeoisie\"\n");
+/// # }
+/// ```
+pub fn print_variants<W: Write>(output: &mut W, tweet: &QueuedTweet) {
+    if tweet.variants.is_empty() {
+        return;
+    }
+
+    writeln!(output, "When mirrored, the tweet will be posted:").unwrap();
+    writeln!(output, "  to Twitter as: \"{}\"", tweet.content).unwrap();
+    for &(variant, _) in VARIANTS {
+        writeln!(output, "  to {} as: \"{}\"", variant, adapt(tweet, variant).content).unwrap();
+    }
+}
+
+/// Split the specified content into the caption and the link to the image it ends with, if it does.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::backend::photo_link;
+/// assert_eq!(photo_link("Abolish the bourgeoisie https://example.com/manifesto.png"),
+///            Some(("Abolish the bourgeoisie", "https://example.com/manifesto.png")));
+/// assert_eq!(photo_link("http://example.com/cat.JPG?size=large"), Some(("", "http://example.com/cat.JPG?size=large")));
+/// assert_eq!(photo_link("Abolish the bourgeoisie https://example.com/manifesto.html"), None);
+/// assert_eq!(photo_link("manifesto.png"), None);
+/// ```
+pub fn photo_link(content: &str) -> Option<(&str, &str)> {
+    let content = content.trim_right();
+    let link_start = content.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+    let link = &content[link_start..];
+
+    let path = link.split(|c| c == '?' || c == '#').next().unwrap().to_lowercase();
+    if (link.starts_with("http://") || link.starts_with("https://")) &&
+       [".jpg", ".jpeg", ".png", ".gif", ".webp"].iter().any(|ext| path.ends_with(ext)) {
+        Some((content[..link_start].trim_right(), link))
+    } else {
+        None
+    }
+}
+
+/// Get the JSON body posting the specified tweet restricting who can reply to it via the v2 API, or why it can't be.
+///
+/// The v2 API takes neither extra parameters, including `possibly_sensitive` set for `sensitive` tweets, nor coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, ReplySettings};
+/// # use tweetr::ops::backend::tweet_v2_body;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     in_reply_to: Some(774541307568103424),
+///     reply_settings: Some(ReplySettings::MentionedUsers),
+///     ..Default::default()
+/// };
+/// assert_eq!(tweet_v2_body(&tweet).map(|b| b.to_string()),
+///            Ok(r#"{"reply":{"in_reply_to_tweet_id":"774541307568103424"},"reply_settings":"mentionedUsers","text":"Abolish the bourgeoisie"}"#
+///                .to_string()));
+///
+/// let mut sensitive = tweet.clone();
+/// sensitive.extra_params.insert("possibly_sensitive".to_string(), "true".to_string());
+/// assert_eq!(tweet_v2_body(&sensitive),
+///            Err("tweets with reply_settings are posted via the v2 API, which doesn't take possibly_sensitive".to_string()));
+/// # }
+/// ```
+pub fn tweet_v2_body(tweet: &QueuedTweet) -> Result<Json, String> {
+    if !tweet.extra_params.is_empty() {
+        return Err(format!("tweets with reply_settings are posted via the v2 API, which doesn't take {}",
+                           tweet.extra_params.keys().map(|k| &k[..]).collect::<Vec<_>>().join(", ")));
+    }
+    if tweet.coordinates.is_some() {
+        return Err("tweets with reply_settings are posted via the v2 API, which doesn't take lat/long, tag it with a place_id instead".to_string());
+    }
+
+    let mut body = BTreeMap::new();
+    body.insert("text".to_string(), Json::String(tweet.content.clone()));
+    if let Some(reply_settings) = tweet.reply_settings {
+        body.insert("reply_settings".to_string(), Json::String(reply_settings.name().to_string()));
+    }
+    if let Some(in_reply_to) = tweet.in_reply_to {
+        let mut reply = BTreeMap::new();
+        reply.insert("in_reply_to_tweet_id".to_string(), Json::String(in_reply_to.to_string()));
+        body.insert("reply".to_string(), Json::Object(reply));
+    }
+    if let Some(ref place_id) = tweet.place_id {
+        let mut geo = BTreeMap::new();
+        geo.insert("place_id".to_string(), Json::String(place_id.clone()));
+        body.insert("geo".to_string(), Json::Object(geo));
+    }
+    Ok(Json::Object(body))
+}
+
+
+#[cfg(feature = "network")]
+impl<'a> PostingBackend for Twitter<'a> {
+    fn description(&self) -> String {
+        format!("Twitter as @{}", self.user.name)
+    }
+
+    fn app(&self) -> Option<String> {
+        self.user.app.clone()
+    }
+
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let v2_body = match tweet.reply_settings {
+            Some(ReplySettings::Everyone) | None => None,
+            Some(_) => {
+                Some(try!(tweet_v2_body(tweet).map_err(|e| {
+                    Outcome::BackendError {
+                        backend: self.description(),
+                        error: e,
+                    }
+                })))
+            }
+        };
+
+        let media_ids = try!(tweet.media.iter().map(|path| media::upload_file(path, self.uploads, self.user, self.app)).collect::<Result<Vec<_>, _>>());
+
+        network::request_blocking(|| post_tweet(tweet, v2_body.as_ref(), &media_ids, self.app, &self.user.raw_token()))
+    }
+}
+
+#[cfg(feature = "network")]
+fn post_tweet(tweet: &QueuedTweet, v2_body: Option<&Json>, media_ids: &[i64], app: &Token, access: &Token) -> Result<i64, Outcome> {
+    if let Some(body) = v2_body {
+        let mut body = body.clone();
+        if let (false, &mut Json::Object(ref mut obj)) = (media_ids.is_empty(), &mut body) {
+            let mut media = BTreeMap::new();
+            media.insert("media_ids".to_string(), Json::Array(media_ids.iter().map(|id| Json::String(id.to_string())).collect()));
+            obj.insert("media".to_string(), Json::Object(media));
+        }
+
+        return oauth::post_json(TWEETS_V2_URL, &body, app, access).and_then(|resp| {
+            resp.find_path(&["data", "id"])
+                .and_then(Json::as_string)
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| Outcome::TwitterAPIError(format!("invalid response: {}", resp)))
+        });
+    }
+
+    // Rather than with egg_mode, which can't time out, and so can't be stopped from posting after being given up on
+    {
+        let mut params = tweet.extra_params.clone();
+        params.insert("status".to_string(), tweet.content.clone());
+        if let Some(in_reply_to) = tweet.in_reply_to {
+            params.insert("in_reply_to_status_id".to_string(), in_reply_to.to_string());
+        }
+        if let Some(coords) = tweet.coordinates {
+            params.insert("lat".to_string(), coords.lat.to_string());
+            params.insert("long".to_string(), coords.long.to_string());
+            params.insert("display_coordinates".to_string(), "true".to_string());
+        }
+        if let Some(ref place_id) = tweet.place_id {
+            params.insert("place_id".to_string(), place_id.clone());
+        }
+        if !media_ids.is_empty() {
+            params.insert("media_ids".to_string(), media_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(","));
+        }
+
+        oauth::post(STATUSES_UPDATE_URL, &params, app, access)
+            .and_then(|resp| resp.find("id").and_then(Json::as_i64).ok_or_else(|| Outcome::TwitterAPIError(format!("invalid response: {}", resp))))
+    }
+}
+
+impl PostingBackend for Sink {
+    fn description(&self) -> String {
+        format!("sink \"{}\"", self.name)
+    }
+
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let failed = |error: String| {
+            Outcome::BackendError {
+                backend: format!("Sink \"{}\"", self.name),
+                error: error,
+            }
+        };
+
+        if let Some(ref file) = self.file {
+            try!(OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .and_then(|mut f| writeln!(f, "{}", tweet.content))
+                .map_err(|e| failed(format!("failed to append to {}: {}", file, e))));
+        } else if let Some(ref command) = self.command {
+            let mut child = try!(shell(command)
+                .env("TWEETR_AUTHOR", &tweet.author)
+                .env("TWEETR_TIME", tweet.time.to_rfc3339())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| failed(format!("failed to run \"{}\": {}", command, e))));
+            // The command may well not read its input, so a broken pipe isn't an error
+            let _ = child.stdin.take().unwrap().write_all(tweet.content.as_bytes());
+
+            let output = try!(child.wait_with_output().map_err(|e| failed(format!("failed to wait for \"{}\": {}", command, e))));
+            if !output.status.success() {
+                let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(failed(if reason.is_empty() {
+                    format!("\"{}\" exited with {}", command, output.status)
+                } else {
+                    reason
+                }));
+            }
+        } else if let Some(ref webhook_url) = self.discord_webhook {
+            return Discord { webhook_url: webhook_url.clone() }.post(tweet);
+        } else {
+            return Err(failed("neither a file, a command nor a Discord webhook specified".to_string()));
+        }
+
+        let now = Local::now();
+        Ok(now.timestamp() * 1000 + now.timestamp_subsec_millis() as i64)
+    }
+}
+
+impl PostingBackend for Telegram {
+    fn description(&self) -> String {
+        format!("Telegram chat {}", self.chat_id)
+    }
+
+    fn variant(&self) -> Option<&'static str> {
+        Some("telegram")
+    }
+
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let desc = format!("posting to {}", self.description());
+        network::request_blocking(|| self.send(&desc, tweet))
+    }
+}
+
+#[cfg(feature = "network")]
+impl Telegram {
+    fn send(&self, desc: &str, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let failed = |error: String| {
+            Outcome::BackendError {
+                backend: "Telegram".to_string(),
+                error: error,
+            }
+        };
+
+        let mut body = Serializer::new(String::new());
+        body.append_pair("chat_id", &self.chat_id);
+        let method = match photo_link(&tweet.content) {
+            Some((caption, link)) => {
+                body.append_pair("photo", link);
+                if !caption.is_empty() {
+                    body.append_pair("caption", caption);
+                }
+                "sendPhoto"
+            }
+            None => {
+                body.append_pair("text", &tweet.content);
+                "sendMessage"
+            }
+        };
+        let body = body.finish();
+
+        let mut resp = try!(network::client()
+            .post(&format!("{}{}/{}", TELEGRAM_API_URL, self.bot_token, method))
+            .header(ContentType::form_url_encoded())
+            .body(body.as_bytes())
+            .send()
+            .map_err(|e| network::request_error(desc, e, &failed)));
+
+        let mut resp_body = String::new();
+        try!(resp.read_to_string(&mut resp_body).map_err(|e| network::io_error(desc, e, &failed)));
+        let json = try!(Json::from_str(&resp_body).map_err(|e| failed(format!("{} (\"{}\")", e, resp_body))));
+
+        if json.find("error_code").and_then(Json::as_i64) == Some(429) {
+            return Err(Outcome::RateLimited {
+                desc: desc.to_string(),
+                reset: json.find_path(&["parameters", "retry_after"]).and_then(Json::as_i64).map(|secs| Local::now().timestamp() + secs),
+            });
+        }
+        if json.find("ok").and_then(Json::as_boolean) != Some(true) {
+            return Err(failed(json.find("description").and_then(Json::as_string).map(str::to_string).unwrap_or(resp_body)));
+        }
+        json.find_path(&["result", "message_id"]).and_then(Json::as_i64).ok_or_else(|| failed(format!("invalid response: {}", json)))
+    }
+}
+
+impl PostingBackend for Discord {
+    fn description(&self) -> String {
+        "Discord webhook".to_string()
+    }
+
+    fn variant(&self) -> Option<&'static str> {
+        Some("discord")
+    }
+
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let desc = format!("posting to {}", self.description());
+        network::request_blocking(|| self.send(&desc, tweet))
+    }
+}
+
+#[cfg(feature = "network")]
+impl Discord {
+    fn send(&self, desc: &str, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        let failed = |error: String| {
+            Outcome::BackendError {
+                backend: "Discord".to_string(),
+                error: error,
+            }
+        };
+
+        let mut body = BTreeMap::new();
+        match photo_link(&tweet.content) {
+            Some((caption, link)) => {
+                let mut image = BTreeMap::new();
+                image.insert("url".to_string(), Json::String(link.to_string()));
+                let mut embed = BTreeMap::new();
+                embed.insert("image".to_string(), Json::Object(image));
+
+                body.insert("content".to_string(), Json::String(caption.to_string()));
+                body.insert("embeds".to_string(), Json::Array(vec![Json::Object(embed)]));
+            }
+            None => {
+                body.insert("content".to_string(), Json::String(tweet.content.clone()));
+            }
+        }
+        let body = Json::Object(body).to_string();
+
+        // Without wait=true the webhook returns no content instead of the created message
+        let mut resp = try!(network::client()
+            .post(&format!("{}{}wait=true", self.webhook_url, if self.webhook_url.contains('?') { '&' } else { '?' }))
+            .header(ContentType::json())
+            .body(body.as_bytes())
+            .send()
+            .map_err(|e| network::request_error(desc, e, &failed)));
+
+        let mut resp_body = String::new();
+        try!(resp.read_to_string(&mut resp_body).map_err(|e| network::io_error(desc, e, &failed)));
+        if resp.status == StatusCode::TooManyRequests {
+            return Err(Outcome::RateLimited {
+                desc: desc.to_string(),
+                reset: network::rate_limit_reset(&resp.headers, Local::now().timestamp()),
+            });
+        }
+        let json = try!(Json::from_str(&resp_body).map_err(|e| failed(format!("{} (\"{}\")", e, resp_body))));
+
+        if !resp.status.is_success() {
+            return Err(failed(json.find("message").and_then(Json::as_string).map(str::to_string).unwrap_or(resp_body)));
+        }
+        // Discord IDs are 64-bit snowflakes, sent as strings
+        json.find("id").and_then(Json::as_string).and_then(|id| id.parse().ok()).ok_or_else(|| failed(format!("invalid response: {}", json)))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+impl Telegram {
+    fn send(&self, _: &str, _: &QueuedTweet) -> Result<i64, Outcome> {
+        Err(no_network("Telegram"))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+impl Discord {
+    fn send(&self, _: &str, _: &QueuedTweet) -> Result<i64, Outcome> {
+        Err(no_network("Discord"))
+    }
+}
+
+
+#[cfg(not(feature = "network"))]
+fn no_network(backend: &str) -> Outcome {
+    Outcome::BackendError {
+        backend: backend.to_string(),
+        error: "tweetr was built without the network feature".to_string(),
+    }
+}
@@ -0,0 +1,196 @@
+//! This module contains the functions used only by the `calendar` subsystem.
+//!
+//! The flow of the `calendar` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::calendar::render()
+//! ```
+//!
+//! Weeks start on, and dates and times are displayed in, the formats in use, see `ops::l10n::Formats`.
+
+
+use chrono::{Date, Datelike, Duration, Local};
+use self::super::l10n::{format_date, format_time, formats};
+use self::super::QueuedTweet;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::iter;
+
+
+/// The ANSI colours accounts are assigned, in order.
+static ACCOUNT_COLOURS: &'static [u8] = &[32, 33, 34, 35, 36, 31];
+
+
+/// How much time a calendar covers.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Span {
+    /// The week, starting on the first day of the week in use.
+    Week,
+    /// The month, padded to whole weeks.
+    Month,
+}
+
+
+/// Get the days, in order, of the specified span containing the specified day.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::calendar::{self, Span};
+/// # use tweetr::ops::l10n::{self, Formats};
+/// # use chrono::{Local, TimeZone, Weekday};
+/// # fn main() {
+/// // Wednesday
+/// let day = Local.ymd(2016, 9, 14);
+///
+/// let week = calendar::days(Span::Week, day);
+/// assert_eq!(week.len(), 7);
+/// assert_eq!(week[0], Local.ymd(2016, 9, 12));
+/// assert_eq!(week[6], Local.ymd(2016, 9, 18));
+///
+/// let month = calendar::days(Span::Month, day);
+/// assert_eq!(month.len(), 35);
+/// assert_eq!(month[0], Local.ymd(2016, 8, 29));
+/// assert_eq!(month[34], Local.ymd(2016, 10, 2));
+///
+/// l10n::use_formats(Formats { first_day_of_week: Weekday::Sun, ..Formats::default() });
+/// assert_eq!(calendar::days(Span::Week, day)[0], Local.ymd(2016, 9, 11));
+///
+/// let month = calendar::days(Span::Month, day);
+/// assert_eq!(month.len(), 35);
+/// assert_eq!(month[0], Local.ymd(2016, 8, 28));
+/// assert_eq!(month[34], Local.ymd(2016, 10, 1));
+/// # }
+/// ```
+pub fn days(span: Span, day: Date<Local>) -> Vec<Date<Local>> {
+    let first = match span {
+        Span::Week => day,
+        Span::Month => day.with_day(1).unwrap(),
+    };
+    let first_day = formats().first_day_of_week;
+    let start = first - Duration::days((7 + first.weekday().num_days_from_monday() as i64 - first_day.num_days_from_monday() as i64) % 7);
+
+    match span {
+        Span::Week => (0..7).map(|i| start + Duration::days(i)).collect(),
+        Span::Month => {
+            let mut days = vec![];
+            let mut d = start;
+            while !(d > first && d.month() != first.month() && d.weekday() == first_day) {
+                days.push(d);
+                d = d.succ();
+            }
+            days
+        }
+    }
+}
+
+/// Render the specified tweets as a grid of the specified span containing the specified day, one row per week.
+///
+/// Each cell lists the times and the beginnings of the tweets scheduled on that day, truncated to the specified width.
+/// In the month view, days outside the month are left empty.
+///
+/// If `colour` is set, each account's tweets are coloured differently.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::calendar::{self, Span};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{Local, TimeZone};
+/// # use std::iter::FromIterator;
+/// # fn main() {
+/// let time = Local.ymd(2016, 9, 13).and_hms(9, 30, 0);
+/// let tweets = vec![QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time.with_timezone(time.offset()),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// }];
+///
+/// let mut out = Vec::new();
+/// calendar::render(&mut out, &tweets, Span::Week, Local.ymd(2016, 9, 14), 12, false);
+/// assert_eq!(String::from_utf8(out).unwrap().lines().nth(3).unwrap(),
+///            "|              | 09:30 Aboli… |              |              |              |              |              |");
+/// # }
+/// ```
+pub fn render<W: Write>(output: &mut W, tweets: &[QueuedTweet], span: Span, day: Date<Local>, width: usize, colour: bool) {
+    let colours = account_colours(tweets);
+    let separator = format!("+{}", iter::repeat(format!("{}+", iter::repeat('-').take(width + 2).collect::<String>())).take(7).collect::<String>());
+
+    writeln!(output, "{}", separator).unwrap();
+    for week in days(span, day).chunks(7) {
+        let in_span = |d: &Date<Local>| span == Span::Week || d.month() == day.month();
+
+        let headers: Vec<_> = week.iter()
+            .map(|d| if in_span(d) {
+                format_date(d)
+            } else {
+                String::new()
+            })
+            .collect();
+        write_row(output, &headers.iter().map(|h| (h.clone(), None)).collect::<Vec<_>>(), width);
+        writeln!(output, "{}", separator).unwrap();
+
+        let entries: Vec<Vec<_>> = week.iter()
+            .map(|d| if in_span(d) {
+                let mut on_day: Vec<_> = tweets.iter().filter(|t| t.time.with_timezone(&Local).date() == *d).collect();
+                on_day.sort_by_key(|t| t.time);
+                on_day.into_iter()
+                    .map(|t| {
+                        (format!("{} {}", format_time(&t.time.with_timezone(&Local)), t.content.replace('\n', " ")),
+                         if colour { colours.get(&t.author[..]).cloned() } else { None })
+                    })
+                    .collect()
+            } else {
+                vec![]
+            })
+            .collect();
+
+        let height = entries.iter().map(Vec::len).max().unwrap_or(0);
+        for i in 0..height {
+            write_row(output,
+                      &entries.iter().map(|e| e.get(i).cloned().unwrap_or((String::new(), None))).collect::<Vec<_>>(),
+                      width);
+        }
+        if height != 0 {
+            writeln!(output, "{}", separator).unwrap();
+        }
+    }
+}
+
+
+fn account_colours(tweets: &[QueuedTweet]) -> BTreeMap<&str, u8> {
+    let mut authors: Vec<_> = tweets.iter().map(|t| &t.author[..]).collect();
+    authors.sort();
+    authors.dedup();
+
+    authors.into_iter().enumerate().map(|(i, a)| (a, ACCOUNT_COLOURS[i % ACCOUNT_COLOURS.len()])).collect()
+}
+
+fn write_row<W: Write>(output: &mut W, cells: &[(String, Option<u8>)], width: usize) {
+    write!(output, "|").unwrap();
+    for &(ref text, colour) in cells {
+        let text = fit(text, width);
+        match colour {
+            Some(c) => write!(output, " \x1b[{}m{}\x1b[0m |", c, text).unwrap(),
+            None => write!(output, " {} |", text).unwrap(),
+        }
+    }
+    writeln!(output, "").unwrap();
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len > width {
+        text.chars().take(width - 1).chain(iter::once('…')).collect()
+    } else {
+        text.chars().chain(iter::repeat(' ').take(width - len)).collect()
+    }
+}
@@ -0,0 +1,145 @@
+//! Sources of the current time for the daemon and scheduling code, so that they can be tested against a clock that moves
+//! arbitrarily.
+//!
+//! All times are compared as instants, so tweets are posted at the right moment regardless of DST transitions, and posted
+//! tweets are marked as such, so they aren't posted again if the clock jumps backwards.
+//!
+//! The daemon waits for the wall-clock times tweets are scheduled for on the monotonic clock (see `deadline()`), so adjusting
+//! the wall clock during a wait neither cuts it short nor drags it out.
+
+
+use chrono::{DateTime, Duration, FixedOffset, Local};
+use std::time::Instant;
+use std::cell::Cell;
+use std::thread;
+
+
+/// A source of the current time.
+pub trait Clock {
+    /// Get the current time.
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+
+/// The system clock, in local time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::clock::{Clock, SystemClock};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// assert!(SystemClock.now() - now.with_timezone(now.offset()) < Duration::seconds(1));
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct SystemClock;
+
+/// A clock only moved explicitly, in either direction.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::clock::{Clock, ManualClock};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let clock = ManualClock::new(DateTime::parse_from_rfc3339("2016-10-30T02:59:00+02:00").unwrap());
+///
+/// clock.advance(Duration::minutes(2));
+/// assert_eq!(clock.now(), DateTime::parse_from_rfc3339("2016-10-30T02:01:00+01:00").unwrap());
+///
+/// clock.set(DateTime::parse_from_rfc3339("2016-10-30T02:30:00+02:00").unwrap());
+/// assert_eq!(clock.now(), DateTime::parse_from_rfc3339("2016-10-30T00:30:00Z").unwrap());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManualClock {
+    now: Cell<DateTime<FixedOffset>>,
+}
+
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        let now = Local::now();
+        now.with_timezone(now.offset())
+    }
+}
+
+impl ManualClock {
+    /// Create a clock showing the specified time.
+    pub fn new(now: DateTime<FixedOffset>) -> ManualClock {
+        ManualClock { now: Cell::new(now) }
+    }
+
+    /// Set the clock to the specified time.
+    pub fn set(&self, now: DateTime<FixedOffset>) {
+        self.now.set(now)
+    }
+
+    /// Move the clock by the specified amount of time, backwards if negative.
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.now.get()
+    }
+}
+
+
+/// Map the specified time on the specified clock to the monotonic clock, relative to the specified instant, taken to be now.
+///
+/// Times already past map to the specified instant.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::clock::{self, ManualClock};
+/// # use chrono::DateTime;
+/// # use std::time::{Duration, Instant};
+/// # fn main() {
+/// let clock = ManualClock::new(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+/// let now = Instant::now();
+///
+/// assert_eq!(clock::deadline(&clock, DateTime::parse_from_rfc3339("2016-09-10T12:00:01.250+02:00").unwrap(), now),
+///            now + Duration::from_millis(1250));
+/// assert_eq!(clock::deadline(&clock, DateTime::parse_from_rfc3339("2016-09-10T11:00:00+02:00").unwrap(), now), now);
+/// # }
+/// ```
+pub fn deadline<C: Clock + ?Sized>(clock: &C, target: DateTime<FixedOffset>, now: Instant) -> Instant {
+    match (target - clock.now()).to_std() {
+        Ok(left) => now + left,
+        Err(_) => now,
+    }
+}
+
+/// Sleep until the specified instant, as measured by the monotonic clock.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::clock;
+/// # use std::time::{Duration, Instant};
+/// let deadline = Instant::now() + Duration::from_millis(50);
+/// clock::sleep_until(deadline);
+/// assert!(Instant::now() >= deadline);
+/// ```
+pub fn sleep_until(deadline: Instant) {
+    // Sleeps can end early, so keep sleeping for what's left until there's nothing left
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        thread::sleep(deadline - now);
+    }
+}
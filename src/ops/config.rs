@@ -0,0 +1,756 @@
+use self::super::super::Outcome;
+use self::super::settings::{Settings, parse_timezone};
+use self::super::embargo::{Embargo, Policy, Window};
+use self::super::recovery::Policy as RecoveryPolicy;
+use self::super::network::{Timeouts, resolve_bind_address};
+use self::super::retry::RetryPolicy;
+use self::super::backend::Sink;
+use self::super::transform::Transforms;
+use self::super::suggest_time::parse_weekday;
+use self::super::l10n::{Formats, valid_format};
+use self::super::schedule::DEFAULT_HORIZON_DAYS;
+use self::super::quick_queue::check_delimiter;
+use std::path::{PathBuf, Path};
+use std::net::IpAddr;
+use chrono::Duration;
+use self::super::{key_error, read_interpolated_toml_file, write_file};
+use toml::encode_str;
+
+
+/// Global configuration, affecting all subsystems.
+///
+/// All values are optional, and an nonexistant configuration file is equivalent to an empty one.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Config {
+    /// Directory containing the hook executables. Default: `"$CONFIG_DIR/hooks"`
+    ///
+    /// Relative paths are resolved against the configuration directory.
+    pub hooks_dir: Option<String>,
+    /// File containing the dictionary to spellcheck queued tweets against, one word per line. Default: `None`
+    ///
+    /// Relative paths are resolved against the configuration directory. Spellchecking is disabled if unset.
+    pub dictionary: Option<String>,
+    /// Template for numbering automatically split threads. Default: `"{n}/{total} {content}"`
+    ///
+    /// See `ops::queue_tweet::split_thread()` for the format.
+    pub thread_numbering: Option<String>,
+    /// Template for folding tweets' content warnings into their content. Default: `"CW: {warning}\n\n{content}"`
+    ///
+    /// See `ops::start_daemon::fold_content_warning()` for the format.
+    pub content_warning_format: Option<String>,
+    /// What separates the content, time and account of tweets queued with the `quick-queue` subsystem. Default: `"|"`
+    ///
+    /// See `ops::quick_queue` for the format.
+    pub quick_delimiter: Option<String>,
+    /// How close, in minutes, tweets by the same account can be scheduled before being warned about when queueing. Default: `10`
+    ///
+    /// `0` disables the warnings.
+    pub collision_window: Option<i64>,
+    /// Weekly grid of preferred posting times, in local time. Default: `[]`
+    ///
+    /// See `ops::suggest_time::parse_slots()` for the format.
+    pub posting_slots: Option<Vec<String>>,
+    /// Whether to refuse to start the daemon if the users file is readable by everyone, instead of only warning. Default: `false`
+    pub refuse_insecure_users: Option<bool>,
+    /// How many tweets an archive file can hold before the month's archive continues in the next one. Default: unlimited
+    ///
+    /// `0` is treated as unlimited. See `ops::archive` for details.
+    pub archive_size: Option<usize>,
+    /// How many months before the current one to keep the archive files of when pruning. Default: `None`
+    ///
+    /// Nothing is pruned if unset.
+    pub archive_retention: Option<u32>,
+    /// How many days after the last tweet attached to them was posted to keep downloaded media for. Default: `None`
+    ///
+    /// If unset, they're only removed by `gc-media` once no tweet is attached to them, otherwise also by the daemon.
+    pub media_retention: Option<u32>,
+    /// How close, in days, tweets by the same account with the same content can be before being warned about when queueing.
+    /// Default: `30`
+    ///
+    /// `0` disables the warnings.
+    pub duplicate_window: Option<i64>,
+    /// How many days the daemon doesn't queue the same evergreen tweet again for. Default: `30`
+    ///
+    /// See `ops::evergreen` for details.
+    pub evergreen_cooldown: Option<u32>,
+    /// How far in the future, in days, tweets can be queued or moved to. Default: `3650`
+    ///
+    /// `0` allows any time. See `ops::schedule` for details.
+    pub horizon: Option<u32>,
+    /// Spans of time the daemon doesn't post anything in. Default: `[]`
+    ///
+    /// The times are in the UTC offset of `timezone`. See `ops::embargo` for details.
+    pub embargo: Option<Vec<Embargo>>,
+    /// Where to reschedule tweets coming due under an embargo to, one of `"end"`, `"spread"` or `"slots"`. Default: `"end"`
+    ///
+    /// See `ops::embargo::Policy` for details.
+    pub embargo_policy: Option<String>,
+    /// What to do with tweets the daemon was posting when it stopped, if it's not recorded whether they were posted, one of
+    /// `"hold"` or `"retry"`. Default: `"hold"`
+    ///
+    /// See `ops::recovery` for details.
+    pub recovery_policy: Option<String>,
+    /// How long, in minutes, after their scheduled time tweets that came due while the daemon wasn't running are still posted
+    /// when it starts. Default: unlimited
+    ///
+    /// Later ones are paused instead. See `ops::recovery` for details.
+    pub missed_expiry: Option<u64>,
+    /// How late, in seconds, the daemon can post tweets on average before warning about it. Default: `300`
+    ///
+    /// `0` never warns. See `ops::latency` for details.
+    pub latency_threshold: Option<u64>,
+    /// Files and commands to deliver tweets to instead of posting them. Default: `[]`
+    ///
+    /// See `ops::backend` for details.
+    pub sink: Option<Vec<Sink>>,
+    /// How long, in seconds, to wait for connecting to a server. Default: `10`
+    ///
+    /// `0` waits indefinitely. This and the following timeouts apply to all network requests, see `ops::network`.
+    pub connect_timeout: Option<u64>,
+    /// How long, in seconds, to wait for a server to respond. Default: `30`
+    pub read_timeout: Option<u64>,
+    /// How long, in seconds, to wait for a server to accept a request. Default: `30`
+    pub write_timeout: Option<u64>,
+    /// The local IP address, or, on Unix, network interface, to connect to servers from. Default: the system's choice
+    ///
+    /// See `ops::network` for details.
+    pub bind_address: Option<String>,
+    /// How many times to retry reading or writing a file in the configuration directory failing transiently. Default: `3`
+    ///
+    /// See `ops::retry` for details.
+    pub file_retries: Option<u32>,
+    /// How long, in milliseconds, to wait before retrying a file access. Default: `1000`
+    pub file_retry_delay: Option<u64>,
+    /// How to display dates with times, as a `strftime` pattern. Default: RFC 3339
+    ///
+    /// This and the following formats only affect what's displayed, see `ops::l10n::Formats`.
+    pub datetime_format: Option<String>,
+    /// How to display dates, as a `strftime` pattern. Default: `"%a %d %b"`
+    pub date_format: Option<String>,
+    /// How to display times of day, as a `strftime` pattern. Default: `"%H:%M"`
+    pub time_format: Option<String>,
+    /// The abbreviated English name of the day weeks start on. Default: `"Mon"`
+    pub first_day_of_week: Option<String>,
+    /// Which transformations to apply to tweets' content before posting them. Default: none
+    ///
+    /// See `ops::transform` for details.
+    pub transform: Option<Transforms>,
+    /// The UTC offset quiet hours are in. Default: local time
+    ///
+    /// This and the following settings are inherited by all tweets, unless their author's account or they themselves override
+    /// them, see `ops::settings` for details.
+    pub timezone: Option<String>,
+    /// The daily span of time no tweets are posted in. Default: `None`
+    pub quiet_hours: Option<String>,
+    /// Up to how many minutes after their scheduled time tweets are posted. Default: `0`
+    pub jitter: Option<u32>,
+    /// Hashtags appended to tweets. Default: `[]`
+    pub tags: Option<Vec<String>>,
+    /// Whether to mark tweets as possibly sensitive. Default: `false`
+    pub sensitive: Option<bool>,
+}
+
+
+impl Config {
+    /// Get the path to the file containing the global configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-config-path-0");
+    /// assert_eq!(Config::path(&tf), tf.join("config.toml"));
+    /// ```
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join("config.toml")
+    }
+
+    /// Read the global configuration from the specified file, defaulting if it doesn't exist.
+    ///
+    /// The display formats are validated, see `formats()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// # use tweetr::Outcome;
+    /// # use std::env::temp_dir;
+    /// # use std::fs::{self, File};
+    /// # use std::io::Write;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-config-read-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// let tf = tf.join("config.toml");
+    ///
+    /// File::create(&tf).unwrap().write_all(b"date_format = \"%d.%m\"\nfirst_day_of_week = \"Sun\"\n").unwrap();
+    /// assert_eq!(Config::read(&tf).unwrap().date_format, Some("%d.%m".to_string()));
+    ///
+    /// File::create(&tf).unwrap().write_all(b"date_format = \"%d.%\"\n").unwrap();
+    /// assert_eq!(Config::read(&tf),
+    ///            Err(Some(Outcome::FileParsingFailed {
+    ///                desc: "configuration",
+    ///                errors: vec!["error: 1:1: invalid strftime pattern \"%d.%\" for the key `date_format`".to_string()],
+    ///            })));
+    /// ```
+    pub fn read(p: &Path) -> Result<Config, Option<Outcome>> {
+        if p.exists() {
+            let config: Config = try!(read_interpolated_toml_file(p, "configuration"));
+
+            for &(key, pattern) in &[("datetime_format", &config.datetime_format),
+                                     ("date_format", &config.date_format),
+                                     ("time_format", &config.time_format)] {
+                if let Some(ref pattern) = *pattern {
+                    if !valid_format(pattern) {
+                        return Err(Some(key_error(p, "configuration", key, &format!("invalid strftime pattern \"{}\"", pattern))));
+                    }
+                }
+            }
+            for &(key, window, max, unit) in &[("collision_window", config.collision_window, 365 * 24 * 60, "minutes"),
+                                               ("duplicate_window", config.duplicate_window, 365, "days")] {
+                if let Some(window) = window {
+                    if window < 0 || window > max {
+                        return Err(Some(key_error(p, "configuration", key, &format!("{} {} is not between 0 and {} (a year)", window, unit, max))));
+                    }
+                }
+            }
+            if let Some(ref delimiter) = config.quick_delimiter {
+                if let Err(e) = check_delimiter(delimiter) {
+                    return Err(Some(key_error(p, "configuration", "quick_delimiter", &e)));
+                }
+            }
+            if let Some(ref day) = config.first_day_of_week {
+                if let Err(e) = parse_weekday(day) {
+                    return Err(Some(key_error(p, "configuration", "first_day_of_week", &e)));
+                }
+            }
+            for e in config.embargo.iter().flat_map(|e| e) {
+                if let Err(e) = e.window(config.timezone.as_ref().and_then(|tz| parse_timezone(tz))) {
+                    return Err(Some(key_error(p, "configuration", "embargo", &format!("invalid embargo: {}", e))));
+                }
+            }
+            if let Some(ref policy) = config.embargo_policy {
+                if let Err(e) = Policy::parse(policy) {
+                    return Err(Some(key_error(p, "configuration", "embargo_policy", &e)));
+                }
+            }
+            if let Some(ref policy) = config.recovery_policy {
+                if let Err(e) = RecoveryPolicy::parse(policy) {
+                    return Err(Some(key_error(p, "configuration", "recovery_policy", &e)));
+                }
+            }
+            if let Some(ref addr) = config.bind_address {
+                if let Err(e) = resolve_bind_address(addr) {
+                    return Err(Some(key_error(p, "configuration", "bind_address", &e)));
+                }
+            }
+
+            Ok(config)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    /// Save the global configuration to the specified file.
+    pub fn write(&self, p: &Path) -> Result<(), Outcome> {
+        write_file(p, &encode_str(&self))
+    }
+
+    /// Get the directory containing the hook executables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-config-hooks_dir-0");
+    /// assert_eq!(Config::default().hooks_dir(&tf), tf.join("hooks"));
+    /// assert_eq!(Config { hooks_dir: Some("plugins".to_string()), ..Config::default() }.hooks_dir(&tf), tf.join("plugins"));
+    /// ```
+    pub fn hooks_dir(&self, config_dir: &Path) -> PathBuf {
+        config_dir.join(self.hooks_dir.as_ref().map(|s| &s[..]).unwrap_or("hooks"))
+    }
+
+    /// Get the file containing the spellchecking dictionary, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-config-dictionary-0");
+    /// assert_eq!(Config::default().dictionary(&tf), None);
+    /// assert_eq!(Config { dictionary: Some("/usr/share/dict/words".to_string()), ..Config::default() }.dictionary(&tf),
+    ///            Some(tf.join("/usr/share/dict/words")));
+    /// ```
+    pub fn dictionary(&self, config_dir: &Path) -> Option<PathBuf> {
+        self.dictionary.as_ref().map(|d| config_dir.join(d))
+    }
+
+    /// Get the template for numbering automatically split threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().thread_numbering(), "{n}/{total} {content}");
+    /// assert_eq!(Config { thread_numbering: Some("{content} ({n}/{total})".to_string()), ..Config::default() }.thread_numbering(),
+    ///            "{content} ({n}/{total})");
+    /// ```
+    pub fn thread_numbering(&self) -> &str {
+        self.thread_numbering.as_ref().map(|s| &s[..]).unwrap_or("{n}/{total} {content}")
+    }
+
+    /// Get the template for folding tweets' content warnings into their content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().content_warning_format(), "CW: {warning}\n\n{content}");
+    /// assert_eq!(Config { content_warning_format: Some("[{warning}] {content}".to_string()), ..Config::default() }.content_warning_format(),
+    ///            "[{warning}] {content}");
+    /// ```
+    pub fn content_warning_format(&self) -> &str {
+        self.content_warning_format.as_ref().map(|s| &s[..]).unwrap_or("CW: {warning}\n\n{content}")
+    }
+
+    /// Get what separates the content, time and account of tweets queued with the `quick-queue` subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().quick_delimiter(), "|");
+    /// assert_eq!(Config { quick_delimiter: Some(";;".to_string()), ..Config::default() }.quick_delimiter(), ";;");
+    /// ```
+    pub fn quick_delimiter(&self) -> &str {
+        self.quick_delimiter.as_ref().map(|s| &s[..]).unwrap_or("|")
+    }
+
+    /// Get how close tweets by the same account can be scheduled before being warned about.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().collision_window(), Duration::minutes(10));
+    /// assert_eq!(Config { collision_window: Some(0), ..Config::default() }.collision_window(), Duration::zero());
+    /// # }
+    /// ```
+    pub fn collision_window(&self) -> Duration {
+        Duration::minutes(self.collision_window.unwrap_or(10))
+    }
+
+    /// Get the specifications of the weekly grid of preferred posting times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert!(Config::default().posting_slots().is_empty());
+    /// assert_eq!(Config { posting_slots: Some(vec!["Mon/Wed/Fri 09:00 17:00".to_string()]), ..Config::default() }.posting_slots(),
+    ///            &["Mon/Wed/Fri 09:00 17:00".to_string()]);
+    /// ```
+    pub fn posting_slots(&self) -> &[String] {
+        self.posting_slots.as_ref().map(|s| &s[..]).unwrap_or(&[])
+    }
+
+    /// Check whether to refuse to start the daemon if the users file is readable by everyone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert!(!Config::default().refuse_insecure_users());
+    /// assert!(Config { refuse_insecure_users: Some(true), ..Config::default() }.refuse_insecure_users());
+    /// ```
+    pub fn refuse_insecure_users(&self) -> bool {
+        self.refuse_insecure_users.unwrap_or(false)
+    }
+
+    /// Get how many tweets an archive file can hold, if limited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().archive_size(), None);
+    /// assert_eq!(Config { archive_size: Some(0), ..Config::default() }.archive_size(), None);
+    /// assert_eq!(Config { archive_size: Some(1000), ..Config::default() }.archive_size(), Some(1000));
+    /// ```
+    pub fn archive_size(&self) -> Option<usize> {
+        self.archive_size.and_then(|s| if s == 0 { None } else { Some(s) })
+    }
+
+    /// Get how many months before the current one to keep the archive files of, if they're to be pruned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().archive_retention(), None);
+    /// assert_eq!(Config { archive_retention: Some(6), ..Config::default() }.archive_retention(), Some(6));
+    /// ```
+    pub fn archive_retention(&self) -> Option<u32> {
+        self.archive_retention
+    }
+
+    /// Get how many days after the last tweet attached to them was posted to keep downloaded media for, if they're to be
+    /// removed before no tweet is attached to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().media_retention(), None);
+    /// assert_eq!(Config { media_retention: Some(90), ..Config::default() }.media_retention(), Some(90));
+    /// ```
+    pub fn media_retention(&self) -> Option<u32> {
+        self.media_retention
+    }
+
+    /// Get how close tweets by the same account with the same content can be before being warned about.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().duplicate_window(), Duration::days(30));
+    /// assert_eq!(Config { duplicate_window: Some(0), ..Config::default() }.duplicate_window(), Duration::zero());
+    /// # }
+    /// ```
+    pub fn duplicate_window(&self) -> Duration {
+        Duration::days(self.duplicate_window.unwrap_or(30))
+    }
+
+    /// Get how long the daemon doesn't queue the same evergreen tweet again for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().evergreen_cooldown(), Duration::days(30));
+    /// assert_eq!(Config { evergreen_cooldown: Some(7), ..Config::default() }.evergreen_cooldown(), Duration::days(7));
+    /// # }
+    /// ```
+    pub fn evergreen_cooldown(&self) -> Duration {
+        Duration::days(self.evergreen_cooldown.unwrap_or(30) as i64)
+    }
+
+    /// Get how far in the future tweets can be queued or moved to, if limited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().horizon(), Some(Duration::days(3650)));
+    /// assert_eq!(Config { horizon: Some(365), ..Config::default() }.horizon(), Some(Duration::days(365)));
+    /// assert_eq!(Config { horizon: Some(0), ..Config::default() }.horizon(), None);
+    /// # }
+    /// ```
+    pub fn horizon(&self) -> Option<Duration> {
+        match self.horizon.map(|h| h as i64).unwrap_or(DEFAULT_HORIZON_DAYS) {
+            0 => None,
+            days => Some(Duration::days(days)),
+        }
+    }
+
+    /// Get the spans of time the daemon doesn't post anything in.
+    ///
+    /// Invalid ones, which `read()` rejects, are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::embargo::{Embargo, Window};
+    /// # use tweetr::ops::Config;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// assert!(Config::default().embargoes().is_empty());
+    /// assert_eq!(Config {
+    ///                    embargo: Some(vec![Embargo {
+    ///                                           start: "2016-09-10 00:00".to_string(),
+    ///                                           end: "2016-09-12 09:00".to_string(),
+    ///                                       }]),
+    ///                    timezone: Some("+02:00".to_string()),
+    ///                    ..Config::default()
+    ///                }
+    ///                .embargoes(),
+    ///            vec![Window {
+    ///                     start: DateTime::parse_from_rfc3339("2016-09-10T00:00:00+02:00").unwrap(),
+    ///                     end: DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap(),
+    ///                 }]);
+    /// # }
+    /// ```
+    pub fn embargoes(&self) -> Vec<Window> {
+        let tz = self.timezone.as_ref().and_then(|tz| parse_timezone(tz));
+        self.embargo.iter().flat_map(|e| e).flat_map(|e| e.window(tz).ok()).collect()
+    }
+
+    /// Get where to reschedule tweets coming due under an embargo to.
+    ///
+    /// An invalid policy, which `read()` rejects, is defaulted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::embargo::Policy;
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().embargo_policy(), Policy::End);
+    /// assert_eq!(Config { embargo_policy: Some("slots".to_string()), ..Config::default() }.embargo_policy(), Policy::Slots);
+    /// ```
+    pub fn embargo_policy(&self) -> Policy {
+        self.embargo_policy.as_ref().and_then(|p| Policy::parse(p).ok()).unwrap_or_default()
+    }
+
+    /// Get what to do with tweets the daemon was posting when it stopped, if it's not recorded whether they were posted.
+    ///
+    /// An invalid policy, which `read()` rejects, is defaulted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::recovery::Policy;
+    /// # use tweetr::ops::Config;
+    /// assert_eq!(Config::default().recovery_policy(), Policy::Hold);
+    /// assert_eq!(Config { recovery_policy: Some("retry".to_string()), ..Config::default() }.recovery_policy(), Policy::Retry);
+    /// ```
+    pub fn recovery_policy(&self) -> RecoveryPolicy {
+        self.recovery_policy.as_ref().and_then(|p| RecoveryPolicy::parse(p).ok()).unwrap_or_default()
+    }
+
+    /// Get how long after their scheduled time tweets that came due while the daemon wasn't running are still posted, if
+    /// limited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().missed_expiry(), None);
+    /// assert_eq!(Config { missed_expiry: Some(90), ..Config::default() }.missed_expiry(), Some(Duration::minutes(90)));
+    /// # }
+    /// ```
+    pub fn missed_expiry(&self) -> Option<Duration> {
+        self.missed_expiry.map(|e| Duration::minutes(e as i64))
+    }
+
+    /// Get how late the daemon can post tweets on average before warning about it, if it ever warns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Config::default().latency_threshold(), Some(Duration::minutes(5)));
+    /// assert_eq!(Config { latency_threshold: Some(90), ..Config::default() }.latency_threshold(), Some(Duration::seconds(90)));
+    /// assert_eq!(Config { latency_threshold: Some(0), ..Config::default() }.latency_threshold(), None);
+    /// # }
+    /// ```
+    pub fn latency_threshold(&self) -> Option<Duration> {
+        match self.latency_threshold.unwrap_or(300) {
+            0 => None,
+            secs => Some(Duration::seconds(secs as i64)),
+        }
+    }
+
+    /// Get the sinks to deliver tweets to instead of posting them, as specified.
+    ///
+    /// See `ops::backend::sinks()` for the validated ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::backend::Sink;
+    /// # use tweetr::ops::Config;
+    /// let motd = Sink {
+    ///     name: "motd".to_string(),
+    ///     file: Some("/etc/motd".to_string()),
+    ///     command: None,
+    ///     discord_webhook: None,
+    /// };
+    ///
+    /// assert!(Config::default().sinks().is_empty());
+    /// assert_eq!(Config { sink: Some(vec![motd.clone()]), ..Config::default() }.sinks(), &[motd]);
+    /// ```
+    pub fn sinks(&self) -> &[Sink] {
+        self.sink.as_ref().map(|s| &s[..]).unwrap_or(&[])
+    }
+
+    /// Get the timeouts for network requests, as specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::network::Timeouts;
+    /// # use tweetr::ops::Config;
+    /// # use std::time::Duration;
+    /// assert_eq!(Config::default().timeouts(), Timeouts::default());
+    /// assert_eq!(Config { read_timeout: Some(0), ..Config::default() }.timeouts(), Timeouts { read: None, ..Timeouts::default() });
+    /// assert_eq!(Config { connect_timeout: Some(3), ..Config::default() }.timeouts().connect, Some(Duration::from_secs(3)));
+    /// ```
+    pub fn timeouts(&self) -> Timeouts {
+        Timeouts::default().overridden(self.connect_timeout, self.read_timeout, self.write_timeout)
+    }
+
+    /// Get the local address to connect to servers from, if not the system's choice, as specified.
+    ///
+    /// An address that can't be found, which `read()` rejects, is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Config;
+    /// # use std::net::IpAddr;
+    /// # use std::str::FromStr;
+    /// assert_eq!(Config::default().bind_address(), None);
+    /// assert_eq!(Config { bind_address: Some("192.0.2.7".to_string()), ..Config::default() }.bind_address(),
+    ///            Some(IpAddr::from_str("192.0.2.7").unwrap()));
+    /// ```
+    pub fn bind_address(&self) -> Option<IpAddr> {
+        self.bind_address.as_ref().and_then(|addr| resolve_bind_address(addr).ok())
+    }
+
+    /// Get how to retry file accesses failing transiently, as specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::retry::RetryPolicy;
+    /// # use tweetr::ops::Config;
+    /// # use std::time::Duration;
+    /// assert_eq!(Config::default().file_retries(), RetryPolicy::default());
+    /// assert_eq!(Config { file_retries: Some(0), ..Config::default() }.file_retries().retries, 0);
+    /// assert_eq!(Config { file_retry_delay: Some(100), ..Config::default() }.file_retries().delay, Duration::from_millis(100));
+    /// ```
+    pub fn file_retries(&self) -> RetryPolicy {
+        RetryPolicy::default().overridden(self.file_retries, self.file_retry_delay)
+    }
+
+    /// Get the formats to display dates and times in, as specified.
+    ///
+    /// Invalid formats, which `read()` rejects, are defaulted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::l10n::Formats;
+    /// # use tweetr::ops::Config;
+    /// # use chrono::Weekday;
+    /// # fn main() {
+    /// assert_eq!(Config::default().formats(), Formats::default());
+    /// assert_eq!(Config {
+    ///                    datetime_format: Some("%d.%m.%Y %H:%M".to_string()),
+    ///                    first_day_of_week: Some("sun".to_string()),
+    ///                    ..Config::default()
+    ///                }
+    ///                .formats(),
+    ///            Formats {
+    ///                datetime: Some("%d.%m.%Y %H:%M".to_string()),
+    ///                first_day_of_week: Weekday::Sun,
+    ///                ..Formats::default()
+    ///            });
+    /// assert_eq!(Config { time_format: Some("%".to_string()), ..Config::default() }.formats(), Formats::default());
+    /// # }
+    /// ```
+    pub fn formats(&self) -> Formats {
+        let default = Formats::default();
+        let pattern = |p: &Option<String>| p.as_ref().and_then(|p| if valid_format(p) { Some(p.clone()) } else { None });
+
+        Formats {
+            datetime: pattern(&self.datetime_format),
+            date: pattern(&self.date_format).unwrap_or(default.date),
+            time: pattern(&self.time_format).unwrap_or(default.time),
+            first_day_of_week: self.first_day_of_week.as_ref().and_then(|d| parse_weekday(d).ok()).unwrap_or(default.first_day_of_week),
+        }
+    }
+
+    /// Get the settings all tweets inherit, see `ops::settings`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::settings::Settings;
+    /// # use tweetr::ops::Config;
+    /// assert!(Config::default().settings().is_empty());
+    /// assert_eq!(Config { jitter: Some(5), ..Config::default() }.settings(), Settings { jitter: Some(5), ..Settings::default() });
+    /// ```
+    pub fn settings(&self) -> Settings {
+        Settings {
+            timezone: self.timezone.clone(),
+            quiet_hours: self.quiet_hours.clone(),
+            jitter: self.jitter,
+            tags: self.tags.clone(),
+            sensitive: self.sensitive,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            hooks_dir: None,
+            dictionary: None,
+            thread_numbering: None,
+            content_warning_format: None,
+            quick_delimiter: None,
+            collision_window: None,
+            posting_slots: None,
+            refuse_insecure_users: None,
+            archive_size: None,
+            archive_retention: None,
+            media_retention: None,
+            duplicate_window: None,
+            evergreen_cooldown: None,
+            horizon: None,
+            embargo: None,
+            embargo_policy: None,
+            recovery_policy: None,
+            missed_expiry: None,
+            latency_threshold: None,
+            sink: None,
+            connect_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            bind_address: None,
+            file_retries: None,
+            file_retry_delay: None,
+            datetime_format: None,
+            date_format: None,
+            time_format: None,
+            first_day_of_week: None,
+            transform: None,
+            timezone: None,
+            quiet_hours: None,
+            jitter: None,
+            tags: None,
+            sensitive: None,
+        }
+    }
+}
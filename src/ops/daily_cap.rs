@@ -0,0 +1,124 @@
+//! Daily caps, the most tweets an account has posted a day, to keep a bulk import gone wrong from flooding its timeline.
+//!
+//! Caps are specified per account (see `User::max_posts_per_day`), and count the tweets posted on each day in the account's
+//! timezone (see `Settings::timezone`) or local time. Tweets coming due after their author's already posted that many that
+//! day aren't posted, but carried over to the next day, keeping their time of day, so they're still posted in the order
+//! they were scheduled in, and carried over again if that day fills up too.
+//!
+//! The flow of the daemon carrying tweets over is as follows:
+//!
+//! ```plaintext
+//! ops::daily_cap::posted_on(), with the queue and the tweets archived today, if the tweet's author has a cap
+//! |> ops::daily_cap::carry_over(), if it's reached
+//! ```
+
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+use self::super::l10n::format_date;
+use self::super::QueuedTweet;
+use std::cmp;
+
+
+/// Count the tweets by the specified author posted on the day of the specified time, in the specified timezone.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, daily_cap};
+/// # use chrono::{DateTime, FixedOffset};
+/// # fn main() {
+/// let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+/// let tweet = |posted: &str| QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time("2016-09-10T12:00:00+02:00"),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     time_posted: Some(time(posted)),
+///     id: Some(774560457755590656),
+///     ..Default::default()
+/// };
+/// let tweets = [tweet("2016-09-10T12:00:00+02:00"), tweet("2016-09-10T23:30:00+02:00"), tweet("2016-09-11T01:30:00+02:00")];
+///
+/// assert_eq!(daily_cap::posted_on(&tweets, "nabijaczleweli", time("2016-09-10T08:00:00+02:00"), FixedOffset::east(2 * 60 * 60)), 2);
+/// assert_eq!(daily_cap::posted_on(&tweets, "nabijaczleweli", time("2016-09-10T08:00:00+02:00"), FixedOffset::east(0)), 3);
+/// assert_eq!(daily_cap::posted_on(&tweets, "tweetr_test", time("2016-09-10T08:00:00+02:00"), FixedOffset::east(2 * 60 * 60)), 0);
+/// # }
+/// ```
+pub fn posted_on(tweets: &[QueuedTweet], author: &str, time: DateTime<FixedOffset>, tz: FixedOffset) -> usize {
+    let day = day_of(time, tz);
+    tweets.iter().filter(|t| t.author == author && t.time_posted.map(|tp| day_of(tp, tz) == day).unwrap_or(false)).count()
+}
+
+/// Carry the specified tweet over to the day after the one of the specified time, in the specified timezone, keeping its
+/// time of day.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, daily_cap};
+/// # use chrono::{DateTime, FixedOffset};
+/// # fn main() {
+/// let time = |s| DateTime::parse_from_rfc3339(s).unwrap();
+/// let mut tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time("2016-09-08T12:00:00+02:00"),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// daily_cap::carry_over(&mut tweet, time("2016-09-10T15:00:00+02:00"), FixedOffset::east(2 * 60 * 60));
+/// assert_eq!(tweet.time, time("2016-09-11T12:00:00+02:00"));
+/// # }
+/// ```
+pub fn carry_over(tweet: &mut QueuedTweet, time: DateTime<FixedOffset>, tz: FixedOffset) {
+    let days = (day_of(time, tz) - day_of(tweet.time, tz)).num_days() + 1;
+    tweet.time = tweet.time + Duration::days(cmp::max(days, 1));
+}
+
+/// Check whether queueing the specified tweet would schedule more tweets by its author on its day than the specified cap,
+/// in the specified timezone, along with the specified queue.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, daily_cap};
+/// # use chrono::{DateTime, FixedOffset};
+/// # fn main() {
+/// let tweet = |t: &str| QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339(t).unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let queue = [tweet("2016-09-10T09:00:00+02:00"), tweet("2016-09-10T12:00:00+02:00"), tweet("2016-09-11T12:00:00+02:00")];
+/// let tz = FixedOffset::east(2 * 60 * 60);
+///
+/// assert_eq!(daily_cap::check(&tweet("2016-09-10T18:00:00+02:00"), &queue, 3, tz), None);
+/// assert_eq!(daily_cap::check(&tweet("2016-09-10T18:00:00+02:00"), &queue, 2, tz),
+///            Some("That'd be 3 tweets by nabijaczleweli on Sat 10 Sep, over the limit of 2 a day; \
+///                  the ones over it will be carried over to the next day".to_string()));
+/// # }
+/// ```
+pub fn check(tweet: &QueuedTweet, queue: &[QueuedTweet], max: u32, tz: FixedOffset) -> Option<String> {
+    let day = day_of(tweet.time, tz);
+    let scheduled = queue.iter().filter(|t| t.author == tweet.author && day_of(t.time_posted.unwrap_or(t.time), tz) == day).count() + 1;
+    if scheduled > max as usize {
+        Some(format!("That'd be {} tweets by {} on {}, over the limit of {} a day; the ones over it will be carried over to the next day",
+                     scheduled,
+                     tweet.author,
+                     format_date(&tweet.time.with_timezone(&tz).date()),
+                     max))
+    } else {
+        None
+    }
+}
+
+
+fn day_of(time: DateTime<FixedOffset>, tz: FixedOffset) -> NaiveDate {
+    time.with_timezone(&tz).naive_local().date()
+}
@@ -0,0 +1,131 @@
+//! Diagnostics, the messages about what a subsystem is doing, kept apart from the data it outputs.
+//!
+//! Data, like lists, exports, plans and JSON, is written to the standard output, so it can be piped safely, and
+//! diagnostics to the standard error, how many of them depending on the verbosity (see `Verbosity`): warnings and errors
+//! are always written, what's being done only unless `--quiet`, and the network requests and access tokens only with
+//! `--verbose`.
+//!
+//! Subsystems printing both take separate writers for each, and write diagnostics through `Diagnostics`, which drops them
+//! if quiet.
+//!
+//! The verbosity in use is set per thread, with `Verbosity::Normal` being the default.
+
+
+use std::io::{self, Stderr, Write};
+use std::cell::Cell;
+
+
+thread_local! {
+    static VERBOSITY: Cell<Verbosity> = Cell::new(Verbosity::Normal);
+}
+
+
+/// How many diagnostics to write.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only warnings and errors, with `--quiet`.
+    Quiet,
+    /// Also what's being done.
+    Normal,
+    /// Also the network requests made and the access tokens received, with `--verbose`.
+    Verbose,
+}
+
+/// A writer of diagnostics, which drops them if quiet.
+#[derive(Debug)]
+pub struct Diagnostics<W>(W);
+
+
+impl Verbosity {
+    /// Get the verbosity given whether `--quiet` and `--verbose` were specified.
+    ///
+    /// `--quiet` wins over `--verbose`, so output meant to be piped stays clean.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::diagnostics::Verbosity;
+    /// assert_eq!(Verbosity::new(false, false), Verbosity::Normal);
+    /// assert_eq!(Verbosity::new(false, true), Verbosity::Verbose);
+    /// assert_eq!(Verbosity::new(true, true), Verbosity::Quiet);
+    /// ```
+    pub fn new(quiet: bool, verbose: bool) -> Verbosity {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+impl Default for Verbosity {
+    fn default() -> Verbosity {
+        Verbosity::Normal
+    }
+}
+
+impl<W: Write> Diagnostics<W> {
+    /// Write the diagnostics to the specified writer, unless quiet.
+    pub fn new(inner: W) -> Diagnostics<W> {
+        Diagnostics(inner)
+    }
+}
+
+impl<W: Write> Write for Diagnostics<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if verbosity() == Verbosity::Quiet {
+            Ok(buf.len())
+        } else {
+            self.0.write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+
+/// Use the specified verbosity for all diagnostics subsequently written on the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::diagnostics::{self, Diagnostics, Verbosity};
+/// # use std::io::Write;
+/// let mut out = Vec::new();
+/// diagnostics::use_verbosity(Verbosity::Quiet);
+/// writeln!(Diagnostics::new(&mut out), "Removed 3 duplicates.").unwrap();
+/// assert!(out.is_empty());
+///
+/// diagnostics::use_verbosity(Verbosity::Normal);
+/// writeln!(Diagnostics::new(&mut out), "Removed 3 duplicates.").unwrap();
+/// assert_eq!(out, b"Removed 3 duplicates.\n");
+/// ```
+pub fn use_verbosity(verbosity: Verbosity) {
+    VERBOSITY.with(|v| v.set(verbosity));
+}
+
+/// Get the verbosity in use on the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::diagnostics::{self, Verbosity};
+/// assert_eq!(diagnostics::verbosity(), Verbosity::Normal);
+/// ```
+pub fn verbosity() -> Verbosity {
+    VERBOSITY.with(|v| v.get())
+}
+
+/// Check whether the verbosity in use on the current thread is `Verbosity::Verbose`.
+pub fn verbose() -> bool {
+    verbosity() == Verbosity::Verbose
+}
+
+/// Get a writer of diagnostics to the standard error.
+pub fn stderr() -> Diagnostics<Stderr> {
+    Diagnostics::new(io::stderr())
+}
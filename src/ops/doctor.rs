@@ -0,0 +1,470 @@
+//! This module contains the functions used only by the `doctor` subsystem.
+//!
+//! The flow of the `doctor` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::doctor::check_permissions()
+//! |> ops::doctor::check_files()
+//! |> ops::doctor::check_versions()
+//! |> ops::doctor::check_credentials(), unless offline
+//! |> ops::doctor::check_api(), unless offline
+//! |> ops::doctor::print_results()
+//! |> ops::doctor::summarise()
+//! ```
+
+
+use self::super::preflight::{self, Access};
+use self::super::schema::{Format, Kind};
+use self::super::{Apps, User, parse_toml_file, validate};
+use self::super::super::util::span_r;
+use self::super::{network, oauth};
+use std::collections::BTreeMap;
+use self::super::super::Outcome;
+use self::super::clock::Clock;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::io::Write;
+use std::path::Path;
+use toml::Value;
+
+
+/// The URL requested to check whether the Twitter API is reachable, and what time it thinks it is.
+pub static API_URL: &'static str = "https://api.twitter.com/1.1/help/configuration.json";
+
+/// The account/verify_credentials API endpoint.
+pub static VERIFY_CREDENTIALS_URL: &'static str = "https://api.twitter.com/1.1/account/verify_credentials.json";
+
+/// How far the system clock can be from the Twitter API's before being reported.
+pub static MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// The keys known to this version of the files not described by `ops::schema`, per file, as the top-level ones and, if any,
+/// the array of tables and its entries' keys.
+static OTHER_KNOWN_KEYS: &'static [(&'static str, &'static [&'static str], Option<(&'static str, &'static [&'static str])>)] =
+    &[("app.toml", &["key", "secret", "app"], Some(("app", &["name", "key", "secret"]))),
+      ("templates.toml", &["template"], Some(("template", &["name", "content"]))),
+      ("evergreen.toml", &["tweet"], Some(("tweet", &["author", "content", "weight", "last_queued"]))),
+      ("pruned.toml", &["ids"], None),
+      ("uploads.toml", &["upload"], Some(("upload", &["path", "user_id", "size", "media_id", "segments", "expires", "finalised"])))];
+
+
+/// The result of a single check.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked.
+    pub name: String,
+    /// `NoError` if the check passed, why it failed otherwise.
+    pub outcome: Outcome,
+    /// How to fix the problem, if the check failed.
+    pub hint: &'static str,
+}
+
+
+/// Check whether all files in the specified configuration directory can be accessed by the subsystems using them, and whether
+/// the users file is readable by everyone.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor;
+/// # use tweetr::Outcome;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-doctor-check_permissions-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("tweets.toml")).unwrap();
+///
+/// assert_eq!(doctor::check_permissions(&tf).outcome, Outcome::NoError);
+/// ```
+pub fn check_permissions(config_dir: &Path) -> Check {
+    let mut problems = preflight::check_access(config_dir,
+                                               &[("app.toml", Access::Read),
+                                                 ("users.toml", Access::ReadWrite),
+                                                 ("tweets.toml", Access::ReadWrite),
+                                                 ("config.toml", Access::Read),
+                                                 ("evergreen.toml", Access::ReadWrite),
+                                                 ("pruned.toml", Access::ReadWrite),
+                                                 ("uploads.toml", Access::ReadWrite)]);
+    problems.extend(preflight::insecure_users_file(&config_dir.join("users.toml")));
+
+    Check {
+        name: "file permissions".to_string(),
+        outcome: if problems.is_empty() {
+            Outcome::NoError
+        } else {
+            Outcome::PreflightFailed(problems)
+        },
+        hint: "Make the files readable and writable by the user running tweetr, and users.toml readable only by them (chmod 600).",
+    }
+}
+
+/// Check whether all files in the specified configuration directory are valid, like the `validate` subsystem.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor;
+/// # use tweetr::Outcome;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-doctor-check_files-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("config.toml")).unwrap().write_all(b"collision_window = \"10\"\n").unwrap();
+///
+/// assert_eq!(doctor::check_files(&tf).outcome,
+///            Outcome::FileParsingFailed {
+///                desc: "configuration directory",
+///                errors: vec!["config.toml: error: 1:1: expected a value of type `integer`, but found a value of type \
+///                              `string` for the key `collision_window`".to_string()],
+///            });
+/// ```
+pub fn check_files(config_dir: &Path) -> Check {
+    Check {
+        name: "configuration files".to_string(),
+        outcome: validate::summarise(&validate::check_files(config_dir)),
+        hint: "Fix the listed entries by hand, see tweetr(1) for the format of each file.",
+    }
+}
+
+/// Check whether all files in the specified configuration directory only have keys known to this version of tweetr.
+///
+/// Unknown keys are ignored, so they most likely come from a newer version, or are misspelt.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor;
+/// # use tweetr::Outcome;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-doctor-check_versions-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("config.toml")).unwrap().write_all(b"colision_window = 10\n").unwrap();
+/// File::create(tf.join("tweets.toml")).unwrap()
+///     .write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nretweet = true\n")
+///     .unwrap();
+///
+/// assert_eq!(doctor::check_versions(&tf).outcome,
+///            Outcome::FileParsingFailed {
+///                desc: "configuration directory",
+///                errors: vec!["tweets.toml: unknown key `tweet.0.retweet`".to_string(),
+///                             "config.toml: unknown key `colision_window`".to_string()],
+///            });
+/// ```
+pub fn check_versions(config_dir: &Path) -> Check {
+    let errors: Vec<_> = known_keys()
+        .into_iter()
+        .flat_map(|(fname, top_keys, arrays)| {
+            parse_toml_file(&config_dir.join(fname), "")
+                .map(|(_, table)| {
+                    let mut unknown: Vec<_> = table.keys().filter(|k| !top_keys.contains(&&k[..])).cloned().collect();
+                    for (array_key, entry_keys) in arrays {
+                        if let Some(&Value::Array(ref entries)) = table.get(array_key) {
+                            for (i, entry) in entries.iter().enumerate() {
+                                if let Value::Table(ref entry) = *entry {
+                                    unknown.extend(entry.keys()
+                                        .filter(|k| !entry_keys.contains(&&k[..]))
+                                        .map(|k| format!("{}.{}.{}", array_key, i, k)));
+                                }
+                            }
+                        }
+                    }
+
+                    unknown.into_iter().map(|k| format!("{}: unknown key `{}`", fname, k)).collect::<Vec<_>>()
+                })
+                .unwrap_or_else(|_| vec![])
+        })
+        .collect();
+
+    Check {
+        name: "file versions".to_string(),
+        outcome: if errors.is_empty() {
+            Outcome::NoError
+        } else {
+            Outcome::FileParsingFailed {
+                desc: "configuration directory",
+                errors: errors,
+            }
+        },
+        hint: "The files were likely written by a newer version of tweetr, which should be used instead; otherwise fix the misspelt keys.",
+    }
+}
+
+/// Check whether each of the specified users' credentials are valid for the application they're bound to.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{doctor, AppTokens, Apps, User};
+/// # use std::collections::BTreeMap;
+/// # use tweetr::Outcome;
+/// let checks = doctor::check_credentials(&Apps {
+///                                            default: AppTokens {
+///                                                key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///                                                secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///                                            },
+///                                            named: BTreeMap::new(),
+///                                        },
+///                                        &[User {
+///                                              name: "nabijaczleweli".to_string(),
+///                                              id: 0x81423,
+///                                              access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///                                              access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///                                              app: None,
+///                                              telegram_bot_token: None,
+///                                              telegram_chat_id: None,
+///                                              discord_webhook: None,
+///                                              timezone: None,
+///                                              quiet_hours: None,
+///                                              jitter: None,
+///                                              tags: None,
+///                                              sensitive: None,
+///                                              test_mode: None,
+///                                              test_account: None,
+///                                              added: None,
+///                                              suspended: None,
+///                                              max_posts_per_day: None,
+///                                              lint: None,
+///                                          }]);
+/// assert_eq!(checks[0].outcome, Outcome::NoError);
+/// ```
+pub fn check_credentials(apps: &Apps, users: &[User]) -> Vec<Check> {
+    users.iter()
+        .map(|user| {
+            Check {
+                name: format!("credentials for {}", user.name),
+                outcome: verify_credentials(apps, user),
+                hint: "Re-authorise the user with tweetr-add-user(1), and check the application tokens with tweetr-init(1).",
+            }
+        })
+        .collect()
+}
+
+/// Verify the specified user's access tokens, with the app they were issued for, against the Twitter API.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{doctor, AppTokens, Apps, User};
+/// # use std::collections::BTreeMap;
+/// # use tweetr::Outcome;
+/// let apps = Apps {
+///     default: AppTokens {
+///         key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///         secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     },
+///     named: BTreeMap::new(),
+/// };
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 0x81423,
+///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// assert_eq!(doctor::verify_credentials(&apps, &user), Outcome::NoError);
+/// ```
+pub fn verify_credentials(apps: &Apps, user: &User) -> Outcome {
+    match apps.get(user.app.as_ref().map(|a| &a[..])) {
+        Ok(app) => {
+            let desc = format!("verifying the credentials for {}", user.name);
+            let mut params = BTreeMap::new();
+            params.insert("skip_status".to_string(), "true".to_string());
+
+            let app = network::owned_token(&app.raw_token());
+            let user = user.clone();
+            // Via oauth::get(), so rechecking unchanged credentials is answered from the HTTP cache
+            match network::request(&desc, move || oauth::get(VERIFY_CREDENTIALS_URL, &params, &app, &user.raw_token())) {
+                Ok(_) => Outcome::NoError,
+                Err(out) => out,
+            }
+        }
+        Err(out) => out,
+    }
+}
+
+/// Check whether the Twitter API is reachable and, if it is, whether the specified clock agrees with it (see `check_clock()`).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::clock::SystemClock;
+/// # use tweetr::ops::doctor;
+/// # use tweetr::Outcome;
+/// let checks = doctor::check_api(&SystemClock);
+/// assert!(checks.iter().all(|c| c.outcome == Outcome::NoError));
+/// ```
+pub fn check_api<C: Clock + ?Sized>(clock: &C) -> Vec<Check> {
+    let (dur, resp) = span_r(|| network::client().head(API_URL).send());
+    let reachability = |outcome| {
+        Check {
+            name: "Twitter API reachability".to_string(),
+            outcome: outcome,
+            hint: "Check the network connection and proxy settings.",
+        }
+    };
+
+    match resp {
+        Ok(resp) => {
+            let server_time = resp.headers
+                .get_raw("Date")
+                .and_then(|d| d.get(0))
+                .and_then(|d| String::from_utf8(d.clone()).ok())
+                .and_then(|d| DateTime::parse_from_rfc2822(&d).ok());
+            // Halfway through the request is the best estimate of when the server's time was taken
+            let now = clock.now() - dur / 2;
+
+            vec![reachability(Outcome::NoError),
+                 match server_time {
+                     Some(server_time) => check_clock(server_time, now),
+                     None => {
+                         Check {
+                             name: "system clock".to_string(),
+                             outcome: Outcome::TwitterAPIError("no valid Date header in the response".to_string()),
+                             hint: "Check whether something between tweetr and the Twitter API is altering the responses.",
+                         }
+                     }
+                 }]
+        }
+        Err(e) => {
+            vec![reachability(network::request_error(&format!("reaching {}", API_URL),
+                                                     e,
+                                                     |e| Outcome::TwitterAPIError(format!("couldn't reach {}: {}", API_URL, e))))]
+        }
+    }
+}
+
+/// Check whether the system clock, showing the specified time, is within `MAX_CLOCK_SKEW_SECONDS` of the specified time of
+/// the Twitter API.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::doctor;
+/// # use tweetr::Outcome;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let server_time = DateTime::parse_from_rfc2822("Fri, 09 Sep 2016 12:00:00 GMT").unwrap();
+///
+/// assert_eq!(doctor::check_clock(server_time, server_time + Duration::seconds(20)).outcome, Outcome::NoError);
+/// assert_eq!(doctor::check_clock(server_time, server_time - Duration::minutes(5)).outcome,
+///            Outcome::PreflightFailed(vec!["the system clock is 300 seconds behind the Twitter API's".to_string()]));
+/// # }
+/// ```
+pub fn check_clock(server_time: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> Check {
+    let skew = now - server_time;
+
+    Check {
+        name: "system clock".to_string(),
+        outcome: if skew.num_seconds().abs() <= MAX_CLOCK_SKEW_SECONDS {
+            Outcome::NoError
+        } else {
+            Outcome::PreflightFailed(vec![format!("the system clock is {} seconds {} the Twitter API's",
+                                                  skew.num_seconds().abs(),
+                                                  if skew > Duration::zero() { "ahead of" } else { "behind" })])
+        },
+        hint: "Synchronise the system clock, e.g. with NTP; tweets are posted by it, and the Twitter API rejects requests \
+               from clocks too far off.",
+    }
+}
+
+/// Print whether each check passed and, for the failed ones, why and how to fix it.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor::{self, Check};
+/// # use tweetr::Outcome;
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// doctor::print_results(&mut out,
+///                       &[Check {
+///                             name: "file permissions".to_string(),
+///                             outcome: Outcome::NoError,
+///                             hint: "chmod",
+///                         },
+///                         Check {
+///                             name: "credentials for nabijaczleweli".to_string(),
+///                             outcome: Outcome::TwitterAPIError("Invalid or expired token.".to_string()),
+///                             hint: "Re-authorise the user.",
+///                         }]);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "[PASS] file permissions\n\
+///             [FAIL] credentials for nabijaczleweli\n  \
+///                    Twitter API error: Invalid or expired token.\n  \
+///                    Hint: Re-authorise the user.\n".to_string());
+/// ```
+pub fn print_results<W: Write>(output: &mut W, checks: &[Check]) {
+    for check in checks {
+        if check.outcome == Outcome::NoError {
+            writeln!(output, "[PASS] {}", check.name).unwrap();
+        } else {
+            writeln!(output, "[FAIL] {}", check.name).unwrap();
+
+            let mut err = Vec::new();
+            check.outcome.print_error(&mut err);
+            for line in String::from_utf8_lossy(&err).lines() {
+                writeln!(output, "  {}", line).unwrap();
+            }
+            writeln!(output, "  Hint: {}", check.hint).unwrap();
+        }
+    }
+}
+
+/// Combine the results of all checks into one `Outcome`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor::{self, Check};
+/// # use tweetr::Outcome;
+/// assert_eq!(doctor::summarise(&[Check {
+///                                    name: "file permissions".to_string(),
+///                                    outcome: Outcome::NoError,
+///                                    hint: "chmod",
+///                                }]),
+///            Outcome::NoError);
+/// ```
+pub fn summarise(checks: &[Check]) -> Outcome {
+    Outcome::from_many(checks.iter().map(|c| c.outcome.clone()).collect())
+}
+
+
+/// The keys known to this version, per file, as the top-level ones and those of the entries of each array of tables, taken
+/// from the formats' descriptions in `ops::schema` where there are any.
+fn known_keys() -> Vec<(&'static str, Vec<&'static str>, Vec<(&'static str, Vec<&'static str>)>)> {
+    let described = [Format::Users, Format::Queue, Format::Config].iter().map(|format| {
+        (format.file(),
+         format.fields().iter().map(|f| f.key).collect(),
+         format.fields()
+             .iter()
+             .filter_map(|f| match f.kind {
+                 Kind::Tables(entry_fields) => Some((f.key, entry_fields.iter().map(|e| e.key).collect())),
+                 _ => None,
+             })
+             .collect())
+    });
+    let other = OTHER_KNOWN_KEYS.iter().map(|&(fname, top_keys, array)| (fname, top_keys.to_vec(), array.iter().map(|&(k, e)| (k, e.to_vec())).collect()));
+
+    described.chain(other).collect()
+}
@@ -0,0 +1,309 @@
+//! Embargoes, spans of time the daemon doesn't post anything in, like around earnings releases.
+//!
+//! Embargoes are specified in the configuration (see `Config::embargo`), each with its start and end, in the configuration's
+//! timezone or local time. Tweets coming due while one is in effect aren't posted, but rescheduled to after it's lifted,
+//! according to the embargo policy (see `Policy`), and then posted as usual. Overlapping and back-to-back embargoes are
+//! lifted together.
+//!
+//! The flow of the daemon deferring tweets is as follows:
+//!
+//! ```plaintext
+//! ops::embargo::lifted(), with the current time
+//! |> ops::embargo::reschedule(), with the tweets due if it's in an embargo
+//! ```
+
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDateTime, TimeZone};
+use self::super::suggest_time::{Slot, free_slots};
+use self::super::super::util::parse_unix_time;
+use self::super::QueuedTweet;
+use std::collections::BTreeMap;
+
+
+/// An embargo, as specified in the configuration.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Embargo {
+    /// When the embargo starts, inclusive.
+    ///
+    /// See `parse_time()` for the format.
+    pub start: String,
+    /// When the embargo ends, exclusive.
+    pub end: String,
+}
+
+/// An embargo's span of time.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Window {
+    /// When the embargo starts, inclusive.
+    pub start: DateTime<FixedOffset>,
+    /// When the embargo ends, exclusive.
+    pub end: DateTime<FixedOffset>,
+}
+
+/// Where to reschedule the tweets coming due under an embargo to.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Policy {
+    /// When the embargo's lifted, all at once.
+    End,
+    /// After the embargo's lifted, each account's tweets the collision window apart, in the order they're queued in.
+    Spread,
+    /// The free posting slots after the embargo's lifted, see `ops::suggest_time::free_slots()`.
+    ///
+    /// Same as `End` if there are no posting slots.
+    Slots,
+}
+
+
+/// Parse a date and time in the format `YYYY-MM-DD HH:MM`, in the specified UTC offset or local time, RFC 3339, or as a Unix
+/// time (see `util::parse_unix_time()`).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::embargo;
+/// # use chrono::{DateTime, FixedOffset};
+/// # fn main() {
+/// assert_eq!(embargo::parse_time("2016-09-10 12:00", Some(FixedOffset::east(2 * 60 * 60))),
+///            Ok(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()));
+/// assert_eq!(embargo::parse_time("2016-09-10T12:00:00-05:00", Some(FixedOffset::east(2 * 60 * 60))),
+///            Ok(DateTime::parse_from_rfc3339("2016-09-10T12:00:00-05:00").unwrap()));
+/// assert_eq!(embargo::parse_time("@1473501600", Some(FixedOffset::east(2 * 60 * 60))),
+///            Ok(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()));
+/// assert_eq!(embargo::parse_time("2016-09-10", None), Err("\"2016-09-10\" is not a date and time".to_string()));
+/// # }
+/// ```
+pub fn parse_time(s: &str, tz: Option<FixedOffset>) -> Result<DateTime<FixedOffset>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s.trim()) {
+        return Ok(time);
+    }
+    if let Some(secs) = parse_unix_time(s) {
+        let naive = try!(secs.ok()
+            .and_then(|secs| NaiveDateTime::from_timestamp_opt(secs, 0))
+            .ok_or_else(|| format!("\"{}\" is not a valid Unix time", s)));
+        return Ok(match tz {
+            Some(tz) => tz.from_utc_datetime(&naive),
+            None => {
+                let local = Local.from_utc_datetime(&naive);
+                local.with_timezone(local.offset())
+            }
+        });
+    }
+
+    let naive = try!(NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M").map_err(|_| format!("\"{}\" is not a date and time", s)));
+    match tz {
+        Some(tz) => tz.from_local_datetime(&naive).single(),
+        // Times skipped by DST changes don't exist
+        None => Local.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(t.offset())),
+    }
+    .ok_or_else(|| format!("\"{}\" doesn't exist in local time", s))
+}
+
+/// Lift all the embargoes the specified time falls in, getting when it's lifted, if it falls in any.
+///
+/// Embargoes starting before or when the ones the time falls in end are lifted with them.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::embargo::{self, Window};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = |s| DateTime::parse_from_rfc3339(s).unwrap();
+/// let embargoes = [Window {
+///                      start: time("2016-09-10T00:00:00+02:00"),
+///                      end: time("2016-09-12T09:00:00+02:00"),
+///                  },
+///                  Window {
+///                      start: time("2016-09-12T09:00:00+02:00"),
+///                      end: time("2016-09-12T12:00:00+02:00"),
+///                  }];
+///
+/// assert_eq!(embargo::lifted(&embargoes, time("2016-09-11T15:00:00+02:00")), Some(time("2016-09-12T12:00:00+02:00")));
+/// assert_eq!(embargo::lifted(&embargoes, time("2016-09-10T00:00:00+02:00")), Some(time("2016-09-12T12:00:00+02:00")));
+/// assert_eq!(embargo::lifted(&embargoes, time("2016-09-12T12:00:00+02:00")), None);
+/// assert_eq!(embargo::lifted(&embargoes, time("2016-09-09T23:59:00+02:00")), None);
+/// # }
+/// ```
+pub fn lifted(embargoes: &[Window], time: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+    let mut end = match embargoes.iter().filter(|w| w.contains(time)).map(|w| w.end).max() {
+        Some(end) => end,
+        None => return None,
+    };
+    while let Some(later) = embargoes.iter().filter(|w| w.start <= end && w.end > end).map(|w| w.end).max() {
+        end = later;
+    }
+    Some(end.with_timezone(time.offset()))
+}
+
+/// Reschedule the tweets at the specified indices, which came due under an embargo lifted at `end`, according to the
+/// specified policy.
+///
+/// `window` is the collision window, see `Config::collision_window()`, and `grid` the weekly grid of posting slots, see
+/// `ops::suggest_time::grid()`. Tweets keep their UTC offsets, and are moved out of any further embargoes they'd land in.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::embargo::{self, Policy, Window};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = |s| DateTime::parse_from_rfc3339(s).unwrap();
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time("2016-09-11T12:00:00+02:00"),
+///     content: "Q3 results are in".to_string(),
+///     ..Default::default()
+/// };
+/// let embargoes = [Window {
+///                      start: time("2016-09-10T00:00:00+02:00"),
+///                      end: time("2016-09-12T09:00:00+02:00"),
+///                  }];
+///
+/// let mut tweets = vec![tweet.clone(), tweet.clone()];
+/// embargo::reschedule(&mut tweets, &[0, 1], &embargoes, embargoes[0].end, Policy::Spread, Duration::minutes(10), &[]);
+/// assert_eq!(tweets[0].time, time("2016-09-12T09:00:00+02:00"));
+/// assert_eq!(tweets[1].time, time("2016-09-12T09:10:00+02:00"));
+///
+/// let mut tweets = vec![tweet.clone(), tweet.clone()];
+/// embargo::reschedule(&mut tweets, &[0, 1], &embargoes, embargoes[0].end, Policy::End, Duration::minutes(10), &[]);
+/// assert_eq!(tweets[0].time, time("2016-09-12T09:00:00+02:00"));
+/// assert_eq!(tweets[1].time, time("2016-09-12T09:00:00+02:00"));
+/// # }
+/// ```
+pub fn reschedule(tweets: &mut [QueuedTweet], indices: &[usize], embargoes: &[Window], end: DateTime<FixedOffset>, policy: Policy,
+                  window: Duration, grid: &[Slot]) {
+    let mut per_author = BTreeMap::new();
+    for &i in indices {
+        let mut time = match policy {
+            Policy::End => end,
+            Policy::Spread => {
+                let n = per_author.entry(tweets[i].author.clone()).or_insert(0);
+                *n += 1;
+                end + window * (*n - 1)
+            }
+            Policy::Slots => {
+                // Slots right when it's lifted are free too
+                free_slots(grid, Some(&tweets[i].author), tweets, window, (end - Duration::seconds(1)).with_timezone(&Local), 1)
+                    .pop()
+                    .unwrap_or(end)
+            }
+        };
+        while let Some(later) = lifted(embargoes, time) {
+            time = match policy {
+                Policy::Slots => {
+                    free_slots(grid, Some(&tweets[i].author), tweets, window, (later - Duration::seconds(1)).with_timezone(&Local), 1)
+                        .pop()
+                        .unwrap_or(later)
+                }
+                _ => later,
+            };
+        }
+
+        tweets[i].time = time.with_timezone(tweets[i].time.offset());
+    }
+}
+
+
+impl Embargo {
+    /// Get the span of time the embargo covers, with the times in the specified UTC offset, or local time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::embargo::{Embargo, Window};
+    /// # use chrono::{DateTime, FixedOffset};
+    /// # fn main() {
+    /// let utc = Some(FixedOffset::east(0));
+    /// assert_eq!(Embargo {
+    ///                    start: "2016-09-10 00:00".to_string(),
+    ///                    end: "2016-09-12 09:00".to_string(),
+    ///                }
+    ///                .window(utc),
+    ///            Ok(Window {
+    ///                start: DateTime::parse_from_rfc3339("2016-09-10T00:00:00Z").unwrap(),
+    ///                end: DateTime::parse_from_rfc3339("2016-09-12T09:00:00Z").unwrap(),
+    ///            }));
+    /// assert_eq!(Embargo {
+    ///                    start: "2016-09-12 09:00".to_string(),
+    ///                    end: "2016-09-10 00:00".to_string(),
+    ///                }
+    ///                .window(utc),
+    ///            Err("2016-09-12 09:00-2016-09-10 00:00 ends before it starts".to_string()));
+    /// # }
+    /// ```
+    pub fn window(&self, tz: Option<FixedOffset>) -> Result<Window, String> {
+        let start = try!(parse_time(&self.start, tz));
+        let end = try!(parse_time(&self.end, tz));
+        if end <= start {
+            return Err(format!("{}-{} ends before it starts", self.start, self.end));
+        }
+
+        Ok(Window {
+            start: start,
+            end: end,
+        })
+    }
+}
+
+impl Window {
+    /// Check whether the specified time falls in the span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::embargo::Window;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let time = |s| DateTime::parse_from_rfc3339(s).unwrap();
+    /// let embargo = Window {
+    ///     start: time("2016-09-10T00:00:00+02:00"),
+    ///     end: time("2016-09-12T09:00:00+02:00"),
+    /// };
+    /// assert!(embargo.contains(time("2016-09-10T00:00:00+02:00")));
+    /// assert!(embargo.contains(time("2016-09-12T06:59:00Z")));
+    /// assert!(!embargo.contains(time("2016-09-12T09:00:00+02:00")));
+    /// # }
+    /// ```
+    pub fn contains(&self, time: DateTime<FixedOffset>) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+impl Policy {
+    /// Parse a policy from its name, in any case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::embargo::Policy;
+    /// assert_eq!(Policy::parse("end"), Ok(Policy::End));
+    /// assert_eq!(Policy::parse("Spread"), Ok(Policy::Spread));
+    /// assert_eq!(Policy::parse("SLOTS"), Ok(Policy::Slots));
+    /// assert_eq!(Policy::parse("later"), Err("\"later\" is not one of end, spread or slots".to_string()));
+    /// ```
+    pub fn parse(s: &str) -> Result<Policy, String> {
+        match &s.to_lowercase()[..] {
+            "end" => Ok(Policy::End),
+            "spread" => Ok(Policy::Spread),
+            "slots" => Ok(Policy::Slots),
+            _ => Err(format!("\"{}\" is not one of end, spread or slots", s)),
+        }
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy::End
+    }
+}
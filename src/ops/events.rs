@@ -0,0 +1,421 @@
+//! The daemon's structured event stream, for external dashboards to tail.
+//!
+//! Each event is written as a single line of JSON, an object with the `event` name, the `time` it happened at, and, depending
+//! on the event, the `tweet` it's about, the `error` it failed with, or the time the daemon sleeps `until`.
+//!
+//! The events are, in the order they happen to a tweet:
+//!
+//!   * `queued` - the tweet was first seen in the queue, also emitted for the whole queue on startup, and again for tweets
+//!                changed in the queue,
+//!   * `due` - the time the tweet's scheduled for has come, though posting it may still be delayed, e.g. by quiet hours,
+//!   * `posting` - the tweet is being posted,
+//!   * `posted` - the tweet was posted, its `tweet` has the `id` and `time_posted`,
+//!   * `failed` - posting the tweet failed, it'll be tried again,
+//!   * `rate_limited` - posting the tweet failed because of the Twitter API's rate limits, it'll be tried again,
+//!   * `suspended` - posting the tweet failed because its author's account is suspended or locked, none of the account's
+//!                   tweets will be tried again until posting's resumed for it, see `ops::start_daemon::suspend()`,
+//!   * `sleeping` - the daemon is waiting for the next tweet to be due.
+
+
+use self::super::super::Outcome;
+use self::super::QueuedTweet;
+use rustc_serialize::json::Json;
+use chrono::{DateTime, FixedOffset};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::io::Write;
+
+
+/// Something that happened in the daemon.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'t> {
+    /// The specified tweet was first seen in the queue.
+    Queued(&'t QueuedTweet),
+    /// The time the specified tweet's scheduled for has come.
+    Due(&'t QueuedTweet),
+    /// The specified tweet is being posted.
+    Posting(&'t QueuedTweet),
+    /// The specified tweet was posted.
+    Posted(&'t QueuedTweet),
+    /// Posting the specified tweet failed with the specified error.
+    Failed(&'t QueuedTweet, String),
+    /// Posting the specified tweet hit the rate limits, with the specified error.
+    RateLimited(&'t QueuedTweet, String),
+    /// Posting the specified tweet failed because its author's account is suspended or locked, with the specified error.
+    Suspended(&'t QueuedTweet, String),
+    /// The daemon is waiting until the specified time.
+    Sleeping(DateTime<FixedOffset>),
+}
+
+impl<'t> Event<'t> {
+    /// Get the event for posting the specified tweet failing with the specified outcome, either `Failed`, `RateLimited` or
+    /// `Suspended`.
+    ///
+    /// See `rate_limited()` and `suspended()` for which failures are rate limits and suspensions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::Event;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use tweetr::Outcome;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(Event::failure(&tweet, &Outcome::TwitterAPIError("Rate limit exceeded (code 88)".to_string())),
+    ///            Event::RateLimited(&tweet, "Twitter API error: Rate limit exceeded (code 88)".to_string()));
+    /// assert_eq!(Event::failure(&tweet, &Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string())),
+    ///            Event::Failed(&tweet, "Twitter API error: Status is a duplicate. (code 187)".to_string()));
+    /// assert_eq!(Event::failure(&tweet, &Outcome::TwitterAPIError("Your account is suspended. (code 64)".to_string())),
+    ///            Event::Suspended(&tweet, "Twitter API error: Your account is suspended. (code 64)".to_string()));
+    /// # }
+    /// ```
+    pub fn failure(tweet: &'t QueuedTweet, out: &Outcome) -> Event<'t> {
+        let mut error = vec![];
+        out.print_error(&mut error);
+        let error = String::from_utf8_lossy(&error).trim().to_string();
+
+        if rate_limited(out) {
+            Event::RateLimited(tweet, error)
+        } else if suspended(out) {
+            Event::Suspended(tweet, error)
+        } else {
+            Event::Failed(tweet, error)
+        }
+    }
+
+    /// Get the event's name, as written in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::Event;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// assert_eq!(Event::Sleeping(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()).name(), "sleeping");
+    /// # }
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Event::Queued(_) => "queued",
+            Event::Due(_) => "due",
+            Event::Posting(_) => "posting",
+            Event::Posted(_) => "posted",
+            Event::Failed(..) => "failed",
+            Event::RateLimited(..) => "rate_limited",
+            Event::Suspended(..) => "suspended",
+            Event::Sleeping(_) => "sleeping",
+        }
+    }
+
+    /// Get the event as it's written in the stream, as happening at the specified time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::Event;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let now = DateTime::parse_from_rfc3339("2016-09-10T11:59:00+02:00").unwrap();
+    /// assert_eq!(Event::Sleeping(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()).to_json(now).to_string(),
+    ///            r#"{"event":"sleeping","time":"2016-09-10T11:59:00+02:00","until":"2016-09-10T12:00:00+02:00"}"#);
+    /// # }
+    /// ```
+    pub fn to_json(&self, now: DateTime<FixedOffset>) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("event".to_string(), Json::String(self.name().to_string()));
+        obj.insert("time".to_string(), Json::String(now.to_rfc3339()));
+
+        match *self {
+            Event::Queued(tweet) |
+            Event::Due(tweet) |
+            Event::Posting(tweet) |
+            Event::Posted(tweet) => {
+                obj.insert("tweet".to_string(), tweet_json(tweet));
+            }
+            Event::Failed(tweet, ref error) |
+            Event::RateLimited(tweet, ref error) |
+            Event::Suspended(tweet, ref error) => {
+                obj.insert("tweet".to_string(), tweet_json(tweet));
+                obj.insert("error".to_string(), Json::String(error.clone()));
+            }
+            Event::Sleeping(until) => {
+                obj.insert("until".to_string(), Json::String(until.to_rfc3339()));
+            }
+        }
+
+        Json::Object(obj)
+    }
+}
+
+
+/// Where the events are written to, if anywhere.
+pub struct EventLog {
+    output: Option<Box<Write>>,
+}
+
+impl EventLog {
+    /// Get a log not writing the events anywhere.
+    pub fn none() -> EventLog {
+        EventLog { output: None }
+    }
+
+    /// Get a log writing the events to the specified output.
+    pub fn new<W: Write + 'static>(output: W) -> EventLog {
+        EventLog { output: Some(Box::new(output)) }
+    }
+
+    /// Get a log appending the events to the specified file, or why it couldn't be opened.
+    ///
+    /// On Unix-likes, file descriptors can be written to by specifying `/dev/fd/<fd>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::events::EventLog;
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// let td = temp_dir().join("tweetr-doctest").join("ops-events-EventLog-open");
+    /// fs::create_dir_all(&td).unwrap();
+    ///
+    /// assert!(EventLog::open(&td.join("events.ndjson")).is_ok());
+    /// assert!(EventLog::open(&td).is_err());
+    /// ```
+    pub fn open(p: &Path) -> Result<EventLog, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(p)
+            .map(EventLog::new)
+            .map_err(|e| format!("couldn't open {} for writing events: {}", p.display(), e))
+    }
+
+    /// Write the specified event, as happening at the specified time.
+    ///
+    /// If it can't be written, for example because the dashboard reading it went away, the error is returned and no further
+    /// events are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::{Event, EventLog};
+    /// # use std::io::{Read, Seek, SeekFrom};
+    /// # use std::fs::OpenOptions;
+    /// # use std::env::temp_dir;
+    /// # use chrono::DateTime;
+    /// # use std::fs;
+    /// # fn main() {
+    /// let td = temp_dir().join("tweetr-doctest").join("ops-events-EventLog-emit");
+    /// fs::create_dir_all(&td).unwrap();
+    /// let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(td.join("events.ndjson")).unwrap();
+    ///
+    /// let mut log = EventLog::new(file.try_clone().unwrap());
+    /// let now = DateTime::parse_from_rfc3339("2016-09-10T11:59:00+02:00").unwrap();
+    /// log.emit(Event::Sleeping(now), now).unwrap();
+    /// log.emit(Event::Sleeping(now), now).unwrap();
+    ///
+    /// let mut written = String::new();
+    /// file.seek(SeekFrom::Start(0)).unwrap();
+    /// file.read_to_string(&mut written).unwrap();
+    /// assert_eq!(written.lines().count(), 2);
+    /// assert!(written.ends_with("\"until\":\"2016-09-10T11:59:00+02:00\"}\n"));
+    /// # }
+    /// ```
+    pub fn emit(&mut self, event: Event, now: DateTime<FixedOffset>) -> Result<(), String> {
+        let result = match self.output {
+            Some(ref mut output) => writeln!(output, "{}", event.to_json(now)).and_then(|_| output.flush()),
+            None => return Ok(()),
+        };
+
+        result.map_err(|e| {
+            self.output = None;
+            format!("couldn't write events: {}, not writing any more", e)
+        })
+    }
+}
+
+
+/// Which tweets events were already emitted for.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Tracker {
+    queued: HashSet<(String, String, DateTime<FixedOffset>)>,
+    due: HashSet<(String, String, DateTime<FixedOffset>)>,
+}
+
+impl Tracker {
+    /// Get the indices of the unposted tweets in the specified queue not seen in the previous one, and forget the ones no
+    /// longer in it.
+    ///
+    /// Tweets are told apart by their author, content and scheduled time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::Tracker;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let other = QueuedTweet { content: "Workers of the world, unite!".to_string(), ..tweet.clone() };
+    ///
+    /// let mut tracker = Tracker::default();
+    /// assert_eq!(tracker.queued(&[tweet.clone()]), vec![0]);
+    /// assert_eq!(tracker.queued(&[tweet.clone(), other.clone()]), vec![1]);
+    /// assert_eq!(tracker.queued(&[other.clone(), tweet.clone()]), Vec::<usize>::new());
+    /// # }
+    /// ```
+    pub fn queued(&mut self, tweets: &[QueuedTweet]) -> Vec<usize> {
+        let current: HashSet<_> = tweets.iter().filter(|t| t.id.is_none()).map(key).collect();
+        let new = tweets.iter().enumerate().filter(|&(_, t)| t.id.is_none() && !self.queued.contains(&key(t))).map(|(i, _)| i).collect();
+
+        self.due = self.due.intersection(&current).cloned().collect();
+        self.queued = current;
+        new
+    }
+
+    /// Check whether the specified tweet is due for the first time, remembering it if so.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::events::Tracker;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut tracker = Tracker::default();
+    /// tracker.queued(&[tweet.clone()]);
+    /// assert!(tracker.due(&tweet));
+    /// assert!(!tracker.due(&tweet));
+    /// # }
+    /// ```
+    pub fn due(&mut self, tweet: &QueuedTweet) -> bool {
+        self.due.insert(key(tweet))
+    }
+}
+
+
+/// Check whether the specified outcome of posting a tweet is from hitting the rate limits.
+///
+/// Besides `Outcome::RateLimited`, Twitter API errors for exceeding the rate limits (code 88) or the daily tweet limit
+/// (code 185) are rate limits.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::events;
+/// # use tweetr::Outcome;
+/// assert!(events::rate_limited(&Outcome::RateLimited { desc: "posting to Discord webhook".to_string(), reset: None }));
+/// assert!(events::rate_limited(&Outcome::TwitterAPIError("Rate limit reached, hold until 1473505200".to_string())));
+/// assert!(events::rate_limited(&Outcome::TwitterAPIError("User is over daily status update limit. (code 185)".to_string())));
+/// assert!(!events::rate_limited(&Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string())));
+/// ```
+pub fn rate_limited(out: &Outcome) -> bool {
+    match *out {
+        // The first is egg_mode's, the rest Twitter's error codes as formatted by ops::oauth::post()
+        Outcome::RateLimited { .. } => true,
+        Outcome::TwitterAPIError(ref e) => e.starts_with("Rate limit reached") || e.contains("(code 88)") || e.contains("(code 185)"),
+        _ => false,
+    }
+}
+
+/// Check whether the specified failure to post means the author's account is suspended or locked, so no tweets can be posted
+/// by it until that's resolved.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::events;
+/// # use tweetr::Outcome;
+/// assert!(events::suspended(&Outcome::TwitterAPIError("Your account is suspended and is not permitted to access this feature. \
+///                                                       (code 64)".to_string())));
+/// assert!(events::suspended(&Outcome::TwitterAPIError("To protect our users from spam and other malicious activity, this account is \
+///                                                       temporarily locked. (code 326)".to_string())));
+/// assert!(!events::suspended(&Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string())));
+/// assert!(!events::suspended(&Outcome::RateLimited { desc: "posting tweet".to_string(), reset: None }));
+/// ```
+pub fn suspended(out: &Outcome) -> bool {
+    match *out {
+        // Twitter's error codes as formatted by ops::oauth::post(), the v2 API only describes it
+        Outcome::TwitterAPIError(ref e) => {
+            e.contains("(code 64)") || e.contains("(code 326)") || e.contains("account is suspended") || e.contains("account is temporarily locked")
+        }
+        _ => false,
+    }
+}
+
+/// Check whether the specified failure to post won't go away by retrying, so the tweet needs to be changed first.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::events;
+/// # use tweetr::Outcome;
+/// assert!(events::permanent(&Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string())));
+/// assert!(events::permanent(&Outcome::TwitterAPIError("No status found with that ID. (code 144)".to_string())));
+/// assert!(!events::permanent(&Outcome::TwitterAPIError("Over capacity (code 130)".to_string())));
+/// assert!(!events::permanent(&Outcome::TwitterAPIError("User is over daily status update limit. (code 185)".to_string())));
+/// assert!(!events::permanent(&Outcome::RateLimited { desc: "posting tweet".to_string(), reset: None }));
+/// ```
+pub fn permanent(out: &Outcome) -> bool {
+    match *out {
+        // Twitter's error codes as formatted by ops::oauth::post(): the replied-to tweet is gone, the tweet is too long,
+        // a duplicate, or otherwise rejected
+        Outcome::TwitterAPIError(ref e) => {
+            !rate_limited(out) && !suspended(out) &&
+            ["(code 144)", "(code 170)", "(code 186)", "(code 187)", "(code 324)", "(code 385)", "(code 433)"].iter().any(|c| e.contains(c))
+        }
+        _ => false,
+    }
+}
+
+fn tweet_json(tweet: &QueuedTweet) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("author".to_string(), Json::String(tweet.author.clone()));
+    obj.insert("content".to_string(), Json::String(tweet.content.clone()));
+    obj.insert("scheduled".to_string(), Json::String(tweet.time.to_rfc3339()));
+    if let Some(ref thread) = tweet.thread {
+        obj.insert("thread".to_string(), Json::String(thread.clone()));
+    }
+    if let Some(id) = tweet.id {
+        obj.insert("id".to_string(), Json::I64(id));
+    }
+    if let Some(time_posted) = tweet.time_posted {
+        obj.insert("time_posted".to_string(), Json::String(time_posted.to_rfc3339()));
+    }
+    Json::Object(obj)
+}
+
+fn key(tweet: &QueuedTweet) -> (String, String, DateTime<FixedOffset>) {
+    (tweet.author.clone(), tweet.content.clone(), tweet.time)
+}
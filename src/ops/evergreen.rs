@@ -0,0 +1,573 @@
+//! Evergreen tweets, posted by the daemon in posting slots nothing else is scheduled in, to keep accounts active between
+//! fresh content.
+//!
+//! The pool of evergreen tweets is kept in `evergreen.toml` in the configuration directory. When the daemon is started with
+//! `--evergreen` and a slot from the weekly grid of posting slots (see `ops::suggest_time`) passes with no tweet by an
+//! author with evergreen tweets scheduled less than the collision window away from it, one of that author's evergreen
+//! tweets is picked at random, in proportion to their weights, and queued for the slot. Tweets queued less than the
+//! cooldown ago (see `Config::evergreen_cooldown()`) aren't picked again.
+//!
+//! Accounts can also be pooled into groups, which fill their slots together: only one of the group's accounts is picked
+//! for each slot nothing by any of them is scheduled around, in proportion to their weights, so a brand's main account can
+//! get most of the filler posts and its regional accounts the rest. How many tweets the daemon queued for each account is
+//! saved with the group, and the account furthest behind its share is picked, so the realised ratios converge to the
+//! weights instead of drifting with chance.
+//!
+//! The flow of the daemon filling a slot is as follows:
+//!
+//! ```plaintext
+//! ops::evergreen::due_slot()
+//! |> ops::evergreen::EvergreenTweet::read()
+//! |> ops::evergreen::EvergreenGroup::read()
+//! |> ops::archive::read_since()
+//! |> ops::evergreen::fill()
+//!    |> ops::evergreen::authors()
+//!    |> ops::evergreen::slot_empty(), for each author
+//!    |> ops::evergreen::pick(), for each author whose slot is empty
+//!    |> ops::evergreen::EvergreenGroup::choose(), for each group
+//!    |> ops::evergreen::EvergreenTweet::queued()
+//! |> ops::evergreen::EvergreenTweet::write()
+//! ```
+
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local};
+use self::super::super::Outcome;
+use self::super::suggest_time::Slot;
+use self::super::settings::Settings;
+use self::super::{QueuedTweet, key_error, read_toml_file, replace_file};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::encode_str;
+use rand;
+
+
+/// A tweet from the evergreen pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvergreenTweet {
+    /// The account to post the tweet as.
+    pub author: String,
+    /// The tweet content.
+    pub content: String,
+    /// How likely the tweet is to be picked, relative to the author's other evergreen tweets.
+    ///
+    /// `0` means it's never picked.
+    pub weight: u32,
+    /// When the tweet was last queued by the daemon, if ever.
+    pub last_queued: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+struct EvergreenTweetForSerialisation {
+    author: String,
+    content: String,
+    weight: Option<u32>,
+    last_queued: Option<String>,
+}
+
+/// A group of accounts filling their posting slots together.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EvergreenGroup {
+    /// How large a share of the group's evergreen tweets each account is to get, relative to the other accounts.
+    ///
+    /// `0` means it's never picked.
+    pub weights: BTreeMap<String, u32>,
+    /// How many evergreen tweets the daemon queued for each account in the group so far.
+    pub queued: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+struct EvergreenGroupForSerialisation {
+    weights: BTreeMap<String, u32>,
+    queued: Option<BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+struct EvergreenTweets {
+    tweet: Vec<EvergreenTweetForSerialisation>,
+    group: Option<Vec<EvergreenGroupForSerialisation>>,
+}
+
+
+impl EvergreenTweet {
+    /// Get the path to the evergreen pool file in the specified configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::evergreen::EvergreenTweet;
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-evergreen-path-0");
+    /// assert_eq!(EvergreenTweet::path(&tf), tf.join("evergreen.toml"));
+    /// ```
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join("evergreen.toml")
+    }
+
+    /// Read the evergreen pool from the specified file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::evergreen::EvergreenTweet;
+    /// # use std::fs::{self, File};
+    /// # use std::env::temp_dir;
+    /// # use std::io::Write;
+    /// # use tweetr::Outcome;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-evergreen-read-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// let tf = tf.join("evergreen.toml");
+    ///
+    /// File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish\"\n").unwrap();
+    /// let pool = EvergreenTweet::read(&tf).unwrap();
+    /// assert_eq!(pool[0].weight, 1);
+    /// assert_eq!(pool[0].last_queued, None);
+    ///
+    /// File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish\"\n\
+    ///                                        last_queued = \"yesterday\"\n").unwrap();
+    /// assert_eq!(EvergreenTweet::read(&tf),
+    ///            Err(Some(Outcome::FileParsingFailed {
+    ///                desc: "evergreen tweets",
+    ///                errors: vec!["error: 4:1: invalid RFC3339 datetime (input contains invalid characters) for the key \
+    ///                              `tweet.0.last_queued`".to_string()],
+    ///            })));
+    /// ```
+    pub fn read(p: &Path) -> Result<Vec<EvergreenTweet>, Option<Outcome>> {
+        let pool: EvergreenTweets = try!(read_toml_file(p, "evergreen tweets"));
+
+        pool.tweet
+            .into_iter()
+            .enumerate()
+            .map(|(i, ets)| {
+                let last_queued = match ets.last_queued {
+                    Some(ref lq) => {
+                        Some(try!(DateTime::parse_from_rfc3339(lq).map_err(|e| {
+                            Some(key_error(p,
+                                           "evergreen tweets",
+                                           &format!("tweet.{}.last_queued", i),
+                                           &format!("invalid RFC3339 datetime ({})", e)))
+                        })))
+                    }
+                    None => None,
+                };
+
+                Ok(EvergreenTweet {
+                    author: ets.author,
+                    content: ets.content,
+                    weight: ets.weight.unwrap_or(1),
+                    last_queued: last_queued,
+                })
+            })
+            .collect()
+    }
+
+    /// Save the evergreen pool and the specified groups to the specified file.
+    ///
+    /// The file is replaced at once, so it's never seen partially written.
+    pub fn write(pool: Vec<EvergreenTweet>, groups: Vec<EvergreenGroup>, p: &Path) -> Result<(), Outcome> {
+        let pool = EvergreenTweets {
+            tweet: pool.into_iter()
+                .map(|et| {
+                    EvergreenTweetForSerialisation {
+                        author: et.author,
+                        content: et.content,
+                        weight: if et.weight == 1 { None } else { Some(et.weight) },
+                        last_queued: et.last_queued.map(|lq| lq.to_rfc3339()),
+                    }
+                })
+                .collect(),
+            group: if groups.is_empty() {
+                None
+            } else {
+                Some(groups.into_iter()
+                    .map(|eg| {
+                        EvergreenGroupForSerialisation {
+                            weights: eg.weights,
+                            queued: if eg.queued.is_empty() { None } else { Some(eg.queued) },
+                        }
+                    })
+                    .collect())
+            },
+        };
+
+        replace_file(p, &encode_str(&pool))
+    }
+
+    /// Get the tweet to queue for the specified slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::evergreen::EvergreenTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = EvergreenTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     weight: 1,
+    ///     last_queued: None,
+    /// };
+    /// let slot = DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap();
+    ///
+    /// let queued = tweet.queued(slot);
+    /// assert_eq!(queued.author, "nabijaczleweli");
+    /// assert_eq!(queued.content, "Abolish the bourgeoisie");
+    /// assert_eq!(queued.time, slot);
+    /// # }
+    /// ```
+    pub fn queued(&self, slot: DateTime<FixedOffset>) -> QueuedTweet {
+        QueuedTweet {
+            author: self.author.clone(),
+            time: slot,
+            content: self.content.clone(),
+            media: vec![],
+            thread: None,
+            in_reply_to: None,
+            extra_params: BTreeMap::new(),
+            variants: BTreeMap::new(),
+            content_warning: None,
+            coordinates: None,
+            place_id: None,
+            reply_settings: None,
+            name: None,
+            after: None,
+            paused: false,
+            blocked: None,
+            settings: Settings::default(),
+            time_posted: None,
+            id: None,
+            app: None,
+            retries: 0,
+        }
+    }
+}
+
+impl EvergreenGroup {
+    /// Read the groups of accounts from the specified evergreen pool file.
+    ///
+    /// Accounts can only be in one group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::evergreen::EvergreenGroup;
+    /// # use std::fs::{self, File};
+    /// # use std::env::temp_dir;
+    /// # use std::io::Write;
+    /// # use tweetr::Outcome;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-evergreen-group-read-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// let tf = tf.join("evergreen.toml");
+    ///
+    /// File::create(&tf).unwrap().write_all(b"tweet = []\n\n[[group]]\n[group.weights]\ntweetr = 7\ntweetr_pl = 3\n").unwrap();
+    /// let groups = EvergreenGroup::read(&tf).unwrap();
+    /// assert_eq!(groups[0].weights["tweetr"], 7);
+    /// assert!(groups[0].queued.is_empty());
+    ///
+    /// File::create(&tf).unwrap().write_all(b"tweet = []\n\n[[group]]\n[group.weights]\ntweetr = 7\ntweetr_pl = 3\n\n\
+    ///                                        [[group]]\n[group.weights]\ntweetr_pl = 1\n").unwrap();
+    /// assert_eq!(EvergreenGroup::read(&tf),
+    ///            Err(Some(Outcome::FileParsingFailed {
+    ///                desc: "evergreen tweets",
+    ///                errors: vec!["error: tweetr_pl is already in group 0 for the key `group.1.weights.tweetr_pl`".to_string()],
+    ///            })));
+    /// ```
+    pub fn read(p: &Path) -> Result<Vec<EvergreenGroup>, Option<Outcome>> {
+        let pool: EvergreenTweets = try!(read_toml_file(p, "evergreen tweets"));
+
+        let mut groups: Vec<EvergreenGroup> = vec![];
+        for (i, egs) in pool.group.unwrap_or_default().into_iter().enumerate() {
+            for account in egs.weights.keys() {
+                if let Some(j) = groups.iter().position(|g| g.weights.contains_key(account)) {
+                    return Err(Some(key_error(p,
+                                              "evergreen tweets",
+                                              &format!("group.{}.weights.{}", i, account),
+                                              &format!("{} is already in group {}", account, j))));
+                }
+            }
+
+            groups.push(EvergreenGroup {
+                weights: egs.weights,
+                queued: egs.queued.unwrap_or_default(),
+            });
+        }
+        Ok(groups)
+    }
+
+    /// Choose which of the specified accounts from the group to queue an evergreen tweet for next.
+    ///
+    /// The account whose share of the tweets queued so far is furthest behind its weight is chosen, the first one in
+    /// alphabetical order on ties. Accounts not in the group or with a weight of `0` are never chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::evergreen::EvergreenGroup;
+    /// let mut group = EvergreenGroup {
+    ///     weights: vec![("tweetr".to_string(), 7), ("tweetr_de".to_string(), 1), ("tweetr_pl".to_string(), 2)].into_iter().collect(),
+    ///     queued: Default::default(),
+    /// };
+    /// assert_eq!(group.choose(&["tweetr", "tweetr_de", "tweetr_pl"]), Some("tweetr"));
+    /// assert_eq!(group.choose(&["tweetr_de", "tweetr_pl"]), Some("tweetr_pl"));
+    /// assert_eq!(group.choose(&["nabijaczleweli"]), None);
+    ///
+    /// group.queued.insert("tweetr".to_string(), 7);
+    /// group.queued.insert("tweetr_pl".to_string(), 1);
+    /// assert_eq!(group.choose(&["tweetr", "tweetr_de", "tweetr_pl"]), Some("tweetr_de"));
+    /// ```
+    pub fn choose<'a>(&self, candidates: &[&'a str]) -> Option<&'a str> {
+        let total_weight: u64 = self.weights.values().map(|&w| w as u64).sum();
+        let total_queued: u64 = self.queued.values().sum();
+
+        // Deficits are scaled by the total weight to stay integral: the share of the tweets queued after this one the
+        // account should have got, less what it did get
+        candidates.iter()
+            .filter_map(|&c| match self.weights.get(c) {
+                Some(&w) if w != 0 => {
+                    Some((c, (w as u64 * (total_queued + 1)) as i64 - (self.queued.get(c).cloned().unwrap_or(0) * total_weight) as i64))
+                }
+                _ => None,
+            })
+            .fold(None, |best: Option<(&str, i64)>, (c, deficit)| match best {
+                Some((b, best_deficit)) if best_deficit > deficit || (best_deficit == deficit && b < c) => Some((b, best_deficit)),
+                _ => Some((c, deficit)),
+            })
+            .map(|(c, _)| c)
+    }
+}
+
+
+/// Get the latest slot from the specified grid after the specified time and at or before the specified current time, if any.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{evergreen, suggest_time};
+/// # use chrono::{Local, TimeZone};
+/// # fn main() {
+/// let grid = suggest_time::parse_slots("Mon/Wed 09:00 17:00").unwrap();
+/// // Monday
+/// let since = Local.ymd(2016, 9, 12).and_hms(8, 0, 0);
+/// let since = since.with_timezone(since.offset());
+///
+/// assert_eq!(evergreen::due_slot(&grid, since, since + chrono::Duration::minutes(30)), None);
+/// assert_eq!(evergreen::due_slot(&grid, since, since + chrono::Duration::hours(1)),
+///            Some(since + chrono::Duration::hours(1)));
+/// assert_eq!(evergreen::due_slot(&grid, since, since + chrono::Duration::days(2)),
+///            Some(since + chrono::Duration::hours(9)));
+/// # }
+/// ```
+pub fn due_slot(grid: &[Slot], since: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+    let since_local = since.with_timezone(&Local);
+    let now_local = now.with_timezone(&Local);
+
+    let mut due = None;
+    let mut date = since_local.date();
+    while date <= now_local.date() {
+        for slot in grid.iter().filter(|s| s.weekday == date.weekday()) {
+            // Times skipped by DST changes don't exist
+            if let Some(time) = date.and_hms_opt(slot.hour, slot.minute, 0) {
+                let time = time.with_timezone(time.offset());
+                if time > since && time <= now && due.map(|d| time > d).unwrap_or(true) {
+                    due = Some(time);
+                }
+            }
+        }
+        date = date.succ();
+    }
+    due
+}
+
+/// Get the authors of the tweets in the specified evergreen pool, each once, in order.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::evergreen::{self, EvergreenTweet};
+/// let tweet = EvergreenTweet {
+///     author: "nabijaczleweli".to_string(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     weight: 1,
+///     last_queued: None,
+/// };
+/// let pool = vec![tweet.clone(), EvergreenTweet { author: "tweetr_test".to_string(), ..tweet.clone() }, tweet.clone()];
+///
+/// assert_eq!(evergreen::authors(&pool), vec!["nabijaczleweli", "tweetr_test"]);
+/// ```
+pub fn authors(pool: &[EvergreenTweet]) -> Vec<&str> {
+    let mut authors: Vec<&str> = vec![];
+    for tweet in pool {
+        if !authors.contains(&&tweet.author[..]) {
+            authors.push(&tweet.author);
+        }
+    }
+    authors
+}
+
+/// Check whether no tweet by the specified author among the specified queued and recently archived ones is scheduled less than
+/// the specified window away from the specified slot.
+///
+/// Unlike when suggesting times, posted tweets count, since they were scheduled in the slot.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{evergreen, QueuedTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let slot = DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap();
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: slot + Duration::minutes(5),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     time_posted: Some(slot + Duration::minutes(5)),
+///     id: Some(774541307568103424),
+///     ..Default::default()
+/// };
+///
+/// assert!(!evergreen::slot_empty(slot, "nabijaczleweli", &[tweet.clone()], Duration::minutes(10)));
+/// assert!(evergreen::slot_empty(slot, "nabijaczleweli", &[tweet.clone()], Duration::minutes(5)));
+/// assert!(evergreen::slot_empty(slot, "tweetr_test", &[tweet.clone()], Duration::minutes(10)));
+/// # }
+/// ```
+pub fn slot_empty(slot: DateTime<FixedOffset>, author: &str, tweets: &[QueuedTweet], window: Duration) -> bool {
+    !tweets.iter().any(|t| t.author == author && (t.time == slot || (t.time - slot).num_seconds().abs() < window.num_seconds()))
+}
+
+/// Pick one of the specified author's evergreen tweets not queued within the specified cooldown before the specified time,
+/// by the specified random number, in proportion to their weights.
+///
+/// Returns the index of the picked tweet, or `None` if none can be picked.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::evergreen::{self, EvergreenTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap();
+/// let tweet = EvergreenTweet {
+///     author: "nabijaczleweli".to_string(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     weight: 1,
+///     last_queued: None,
+/// };
+/// let pool = vec![EvergreenTweet { last_queued: Some(now - Duration::days(3)), ..tweet.clone() },
+///                 EvergreenTweet { weight: 3, ..tweet.clone() },
+///                 EvergreenTweet { author: "tweetr_test".to_string(), ..tweet.clone() },
+///                 tweet.clone()];
+///
+/// assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(7), 0), Some(1));
+/// assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(7), 2), Some(1));
+/// assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(7), 3), Some(3));
+/// assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(1), 0), Some(0));
+/// assert_eq!(evergreen::pick(&pool, "danerangLP", now, Duration::days(7), 0), None);
+/// # }
+/// ```
+pub fn pick(pool: &[EvergreenTweet], author: &str, now: DateTime<FixedOffset>, cooldown: Duration, random: u64) -> Option<usize> {
+    let candidates: Vec<_> = pool.iter()
+        .enumerate()
+        .filter(|&(_, t)| t.author == author && t.weight != 0 && t.last_queued.map(|lq| now - lq >= cooldown).unwrap_or(true))
+        .collect();
+    let total: u64 = candidates.iter().map(|&(_, t)| t.weight as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut left = random % total;
+    for (i, tweet) in candidates {
+        if left < tweet.weight as u64 {
+            return Some(i);
+        }
+        left -= tweet.weight as u64;
+    }
+    None
+}
+
+/// Pick an evergreen tweet from the specified pool for each author with no tweet among the specified ones scheduled less than
+/// the specified window away from the specified slot, marking the picked ones as queued at the specified time.
+///
+/// Authors in one of the specified groups are picked from together instead: if none of the group's accounts has a tweet
+/// scheduled around the slot, a tweet is picked for the one account chosen by `EvergreenGroup::choose()` out of the ones with
+/// tweets that can be picked, and counted as queued for it in the group.
+///
+/// Returns the tweets to queue.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::evergreen::{self, EvergreenGroup, EvergreenTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let slot = DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap();
+/// let tweet = EvergreenTweet {
+///     author: "nabijaczleweli".to_string(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     weight: 1,
+///     last_queued: None,
+/// };
+/// let mut pool = vec![tweet.clone(), EvergreenTweet { author: "tweetr_test".to_string(), ..tweet.clone() }];
+///
+/// let now = slot + Duration::seconds(30);
+/// let queued = evergreen::fill(&mut pool, &mut [], slot, &[], Duration::minutes(10), Duration::days(30), now);
+/// assert_eq!(queued, vec![tweet.queued(slot), pool[1].queued(slot)]);
+/// assert_eq!(pool[0].last_queued, Some(now));
+/// assert_eq!(pool[1].last_queued, Some(now));
+///
+/// assert_eq!(evergreen::fill(&mut pool, &mut [], slot, &[], Duration::minutes(10), Duration::days(30), now), vec![]);
+/// assert_eq!(evergreen::fill(&mut pool, &mut [], slot, &queued[..1], Duration::minutes(10), Duration::days(0), now),
+///            vec![pool[1].queued(slot)]);
+///
+/// let mut groups = vec![EvergreenGroup {
+///     weights: vec![("nabijaczleweli".to_string(), 1), ("tweetr_test".to_string(), 1)].into_iter().collect(),
+///     queued: Default::default(),
+/// }];
+/// assert_eq!(evergreen::fill(&mut pool, &mut groups, slot, &[], Duration::minutes(10), Duration::days(0), now),
+///            vec![pool[0].queued(slot)]);
+/// assert_eq!(evergreen::fill(&mut pool, &mut groups, slot, &[], Duration::minutes(10), Duration::days(0), now),
+///            vec![pool[1].queued(slot)]);
+/// assert_eq!(groups[0].queued, vec![("nabijaczleweli".to_string(), 1), ("tweetr_test".to_string(), 1)].into_iter().collect());
+/// # }
+/// ```
+pub fn fill(pool: &mut [EvergreenTweet], groups: &mut [EvergreenGroup], slot: DateTime<FixedOffset>, tweets: &[QueuedTweet], window: Duration,
+            cooldown: Duration, now: DateTime<FixedOffset>)
+            -> Vec<QueuedTweet> {
+    let authors: Vec<_> = authors(pool).into_iter().map(str::to_string).collect();
+
+    let mut queued = vec![];
+    for author in authors.iter().filter(|a| !groups.iter().any(|g| g.weights.contains_key(&a[..]))) {
+        if !slot_empty(slot, author, tweets, window) {
+            continue;
+        }
+
+        if let Some(i) = pick(pool, author, now, cooldown, rand::random()) {
+            queued.push(pool[i].queued(slot));
+            pool[i].last_queued = Some(now);
+        }
+    }
+
+    for group in groups {
+        if !group.weights.keys().all(|a| slot_empty(slot, a, tweets, window)) {
+            continue;
+        }
+
+        let candidates: Vec<_> = authors.iter().map(|a| &a[..]).filter(|a| pick(pool, a, now, cooldown, 0).is_some()).collect();
+        if let Some(author) = group.choose(&candidates) {
+            if let Some(i) = pick(pool, author, now, cooldown, rand::random()) {
+                queued.push(pool[i].queued(slot));
+                pool[i].last_queued = Some(now);
+                *group.queued.entry(author.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    queued
+}
@@ -0,0 +1,151 @@
+//! This module contains the functions used only by the `export-posted` subsystem.
+//!
+//! The flow of the `export-posted` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::QueuedTweet::read()
+//! |> ops::User::read()
+//! |> ops::export_posted::posted()
+//! |> ops::export_posted::write_csv() or ops::export_posted::write_json()
+//! ```
+//!
+//! Each posted tweet is exported with its ID, author, content, the time it was scheduled for and the time it was actually
+//! posted at, how many times posting it was retried, and, if it was posted to Twitter, its permalink.
+
+
+use self::super::{QueuedTweet, User, archive};
+use self::super::super::Outcome;
+use rustc_serialize::json::{self, Json};
+use chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::io::Write;
+
+
+/// What to export the posted tweets as.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Format {
+    /// RFC 4180 CSV, with a header row.
+    Csv,
+    /// A JSON array of objects.
+    Json,
+}
+
+
+/// Get the tweets posted since the specified time, if any, from the archive in the specified directory and the specified
+/// queue, in the order they were posted in.
+pub fn posted(config_dir: &Path, queue: &[QueuedTweet], since: Option<DateTime<FixedOffset>>) -> Result<Vec<QueuedTweet>, Outcome> {
+    let archived = try!(match since {
+        Some(since) => archive::read_since(config_dir, since),
+        None => archive::read_all(config_dir),
+    });
+
+    let mut tweets: Vec<_> = archived.into_iter()
+        .chain(queue.iter().cloned())
+        .filter(|t| match (t.time_posted, since) {
+            (Some(time_posted), Some(since)) => time_posted >= since,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+        .collect();
+    tweets.sort_by_key(|t| t.time_posted);
+    Ok(tweets)
+}
+
+/// Get the link to the specified tweet on Twitter, if it was posted there by one of the specified users.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{export_posted, QueuedTweet, User};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:05+02:00").unwrap()),
+///     id: Some(774541307568103424),
+///     ..Default::default()
+/// };
+/// let users = vec![User {
+///                       name: "nabijaczleweli".to_string(),
+///                       id: 481,
+///                       access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///                       access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///                       app: None,
+///                       telegram_bot_token: None,
+///                       telegram_chat_id: None,
+///                       discord_webhook: None,
+///                       timezone: None,
+///                       quiet_hours: None,
+///                       jitter: None,
+///                       tags: None,
+///                       sensitive: None,
+///                       test_mode: None,
+///                       test_account: None,
+///                       added: None,
+///                       suspended: None,
+///                       max_posts_per_day: None,
+///                       lint: None,
+///                   }];
+///
+/// assert_eq!(export_posted::permalink(&tweet, &users),
+///            Some("https://twitter.com/nabijaczleweli/status/774541307568103424".to_string()));
+/// assert_eq!(export_posted::permalink(&QueuedTweet { author: "motd".to_string(), ..tweet.clone() }, &users), None);
+/// assert_eq!(export_posted::permalink(&QueuedTweet { id: None, ..tweet.clone() }, &users), None);
+/// # }
+/// ```
+pub fn permalink(tweet: &QueuedTweet, users: &[User]) -> Option<String> {
+    match tweet.id {
+        Some(id) if users.iter().any(|u| u.name == tweet.author) => Some(format!("https://twitter.com/{}/status/{}", tweet.author, id)),
+        _ => None,
+    }
+}
+
+/// Write the specified posted tweets as CSV to the specified output, linking to the ones posted by the specified users.
+pub fn write_csv<W: Write>(output: &mut W, tweets: &[QueuedTweet], users: &[User]) {
+    writeln!(output, "id,account,text,scheduled,posted,retries,permalink").unwrap();
+    for tweet in tweets {
+        let fields = [tweet.id.map(|id| id.to_string()).unwrap_or_default(),
+                      tweet.author.clone(),
+                      tweet.content.clone(),
+                      tweet.time.to_rfc3339(),
+                      tweet.time_posted.map(|tp| tp.to_rfc3339()).unwrap_or_default(),
+                      tweet.retries.to_string(),
+                      permalink(tweet, users).unwrap_or_default()];
+        writeln!(output, "{}", fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",")).unwrap();
+    }
+}
+
+/// Write the specified posted tweets as JSON to the specified output, linking to the ones posted by the specified users.
+///
+/// The IDs are strings, since they're too big for JavaScript's numbers, like Twitter's own `id_str`.
+pub fn write_json<W: Write>(output: &mut W, tweets: &[QueuedTweet], users: &[User]) {
+    let tweets = tweets.iter()
+        .map(|tweet| {
+            let mut obj = BTreeMap::new();
+            obj.insert("id".to_string(), tweet.id.map(|id| Json::String(id.to_string())).unwrap_or(Json::Null));
+            obj.insert("account".to_string(), Json::String(tweet.author.clone()));
+            obj.insert("text".to_string(), Json::String(tweet.content.clone()));
+            obj.insert("scheduled".to_string(), Json::String(tweet.time.to_rfc3339()));
+            obj.insert("posted".to_string(), tweet.time_posted.map(|tp| Json::String(tp.to_rfc3339())).unwrap_or(Json::Null));
+            obj.insert("retries".to_string(), Json::U64(tweet.retries as u64));
+            obj.insert("permalink".to_string(), permalink(tweet, users).map(Json::String).unwrap_or(Json::Null));
+            Json::Object(obj)
+        })
+        .collect();
+    writeln!(output, "{}", json::as_pretty_json(&Json::Array(tweets))).unwrap();
+}
+
+
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
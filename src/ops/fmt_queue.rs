@@ -0,0 +1,177 @@
+//! This module contains the functions used only by the `fmt-queue` subsystem.
+//!
+//! The flow of the `fmt-queue` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::fmt_queue::verify()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::fmt_queue::dedup()
+//! |> ops::fmt_queue::clear_remnants()
+//! |> ops::archive::rotate()
+//! |> ops::fmt_queue::sort()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+//!
+//! Datetimes are normalised to RFC3339 by reading and writing the queue back, and the broken entries are kept as-is at the end of
+//! the queue.
+
+
+use std::collections::{BTreeMap, VecDeque};
+use self::super::super::Outcome;
+use self::super::{QueuedTweet, verify_file};
+use std::path::PathBuf;
+
+
+/// Verify if specified configuration directory contains the tweet queue.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::{self, File};
+/// # use tweetr::ops::fmt_queue;
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-fmt-queue-verify-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(fmt_queue::verify(&("$TEMP/ops-fmt-queue-verify-0".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("tweets.toml")).unwrap();
+/// assert_eq!(fmt_queue::verify(&("$TEMP/ops-fmt-queue-verify-0".to_string(), tf.clone())),
+///            Ok(tf.join("tweets.toml")));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("tweets.toml", true, config_dir, false, "queue-tweet")
+}
+
+/// Remove all but the first of each set of identical tweets, returning how many were removed.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, fmt_queue};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let other = QueuedTweet { content: "Workers of the world, unite!".to_string(), ..tweet.clone() };
+///
+/// let mut tweets = vec![tweet.clone(), other.clone(), tweet.clone(), tweet.clone()];
+/// assert_eq!(fmt_queue::dedup(&mut tweets), 2);
+/// assert_eq!(tweets, vec![tweet, other]);
+/// # }
+/// ```
+pub fn dedup(tweets: &mut Vec<QueuedTweet>) -> usize {
+    let before = tweets.len();
+
+    let mut i = 0;
+    while i < tweets.len() {
+        if tweets[..i].contains(&tweets[i]) {
+            tweets.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    before - tweets.len()
+}
+
+/// Clear the reply IDs of unposted tweets that aren't the first in their threads, returning how many were cleared.
+///
+/// The daemon fills them in right before posting each such tweet, so they're only left behind by failed posting attempts,
+/// and will be filled in again.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, fmt_queue};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let first = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish".to_string(),
+///     thread: Some("manifesto".to_string()),
+///     in_reply_to: Some(420),
+///     ..Default::default()
+/// };
+/// let second = QueuedTweet { content: "the bourgeoisie".to_string(), in_reply_to: Some(421), ..first.clone() };
+///
+/// let mut tweets = vec![first.clone(), second.clone()];
+/// assert_eq!(fmt_queue::clear_remnants(&mut tweets), 1);
+/// assert_eq!(tweets, vec![first, QueuedTweet { in_reply_to: None, ..second }]);
+/// # }
+/// ```
+pub fn clear_remnants(tweets: &mut [QueuedTweet]) -> usize {
+    let mut cleared = 0;
+    for i in 0..tweets.len() {
+        let stale = tweets[i].id.is_none() && tweets[i].in_reply_to.is_some() &&
+                    tweets[i].thread.as_ref().map(|thread| tweets[..i].iter().any(|t| t.thread.as_ref() == Some(thread))).unwrap_or(false);
+
+        if stale {
+            tweets[i].in_reply_to = None;
+            cleared += 1;
+        }
+    }
+    cleared
+}
+
+/// Sort the tweets by the time they're scheduled for, stably, keeping the tweets in each thread in their order.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, fmt_queue};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = |content: &str, time: &str, thread: Option<&str>| {
+///     QueuedTweet {
+///         author: "nabijaczleweli".to_string(),
+///         time: DateTime::parse_from_rfc3339(time).unwrap(),
+///         content: content.to_string(),
+///         thread: thread.map(str::to_string),
+///         ..Default::default()
+///     }
+/// };
+///
+/// let mut tweets = vec![tweet("Abolish", "2016-09-10T11:30:00+00:00", Some("manifesto")),
+///                       tweet("the bourgeoisie", "2016-09-10T11:00:00+00:00", Some("manifesto")),
+///                       tweet("Workers", "2016-09-10T13:15:00+02:00", None)];
+/// fmt_queue::sort(&mut tweets);
+/// assert_eq!(tweets.iter().map(|t| &t.content[..]).collect::<Vec<_>>(), vec!["Abolish", "Workers", "the bourgeoisie"]);
+/// # }
+/// ```
+pub fn sort(tweets: &mut Vec<QueuedTweet>) {
+    let mut order: Vec<_> = (0..tweets.len()).collect();
+    order.sort_by_key(|&i| tweets[i].time);
+
+    // Each slot the sort gave a thread's tweet is filled with the thread's next tweet in the original order
+    let mut threads = BTreeMap::new();
+    for (i, t) in tweets.iter().enumerate() {
+        if let Some(ref thread) = t.thread {
+            threads.entry(thread.clone()).or_insert_with(VecDeque::new).push_back(i);
+        }
+    }
+    for slot in &mut order {
+        if let Some(ref thread) = tweets[*slot].thread {
+            *slot = threads.get_mut(thread).unwrap().pop_front().unwrap();
+        }
+    }
+
+    let mut unsorted: Vec<_> = tweets.drain(..).map(Some).collect();
+    tweets.extend(order.into_iter().map(|i| unsorted[i].take().unwrap()));
+}
@@ -0,0 +1,122 @@
+//! External executables run at specific points of a tweet's lifetime.
+//!
+//! A hook is an executable named after the point it's run at, placed in the hooks directory (see `Config::hooks_dir()`).
+//!
+//! The hook receives the tweet as a JSON object on its standard input, in the same format as in the queue file
+//! (all datetimes in RFC3339), and the name of the hook in the `TWEETR_HOOK` environment variable.
+//!
+//! If the hook exits with 0, it accepts the tweet; if it additionally prints a JSON object to its standard output,
+//! the tweet is replaced with that. Any other exit value vetoes the tweet, with the hook's standard error used as the reason.
+//!
+//! Nonexistant hooks accept everything unchanged.
+
+
+use self::super::queued_tweet::QueuedTweetForSerialisation;
+use std::process::{Command, Stdio};
+use self::super::super::Outcome;
+use rustc_serialize::json;
+use self::super::QueuedTweet;
+use std::path::Path;
+use std::io::Write;
+
+
+/// All the points of a tweet's lifetime hooks can be run at.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Hook {
+    /// Before the tweet is added to the queue, vetoing drops it.
+    PreQueue,
+    /// Before the tweet is posted, vetoing leaves it in the queue for the next try.
+    PrePost,
+    /// After the tweet was posted, vetoing only reports an error.
+    PostPost,
+}
+
+impl Hook {
+    /// Get the name of the hook executable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::hooks::Hook;
+    /// assert_eq!(Hook::PreQueue.name(), "pre-queue");
+    /// assert_eq!(Hook::PrePost.name(), "pre-post");
+    /// assert_eq!(Hook::PostPost.name(), "post-post");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Hook::PreQueue => "pre-queue",
+            Hook::PrePost => "pre-post",
+            Hook::PostPost => "post-post",
+        }
+    }
+}
+
+
+/// Run the specified hook from the specified directory on the specified tweet.
+///
+/// Returns the possibly-changed tweet or why the hook rejected it.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::hooks::{self, Hook};
+/// # use tweetr::ops::QueuedTweet;
+/// # use std::env::temp_dir;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+///     content: "No hook to change this".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let td = temp_dir().join("tweetr-doctest").join("ops-hooks-run-0");
+/// assert_eq!(hooks::run(&td, Hook::PreQueue, tweet.clone()), Ok(tweet));
+/// # }
+/// ```
+pub fn run(hooks_dir: &Path, hook: Hook, tweet: QueuedTweet) -> Result<QueuedTweet, Outcome> {
+    let hook_path = hooks_dir.join(hook.name());
+    if !hook_path.is_file() {
+        return Ok(tweet);
+    }
+
+    let input = json::encode(&QueuedTweetForSerialisation::from(tweet.clone())).unwrap();
+    let vetoed = |reason: String| {
+        Outcome::HookVetoed {
+            hook: hook.name(),
+            reason: reason,
+        }
+    };
+
+    let mut child = try!(Command::new(&hook_path)
+        .env("TWEETR_HOOK", hook.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| vetoed(format!("failed to run {}: {}", hook_path.display(), e))));
+    // The hook may well not read its input, so a broken pipe isn't an error
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+
+    let output = try!(child.wait_with_output().map_err(|e| vetoed(format!("failed to wait for {}: {}", hook_path.display(), e))));
+    if !output.status.success() {
+        let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(vetoed(if reason.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            reason
+        }));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        Ok(tweet)
+    } else {
+        let qts: QueuedTweetForSerialisation = try!(json::decode(stdout.trim()).map_err(|e| vetoed(format!("returned an invalid tweet: {}", e))));
+        let qt: Result<QueuedTweet, _> = qts.into();
+        qt.map_err(|(key, e)| vetoed(format!("returned an invalid tweet: {} for the key `{}`", e, key)))
+    }
+}
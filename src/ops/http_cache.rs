@@ -0,0 +1,245 @@
+//! The on-disk cache of API responses, so checking the same thing again doesn't count against the rate limits.
+//!
+//! GET requests sent with `oauth::get()`, like verifying credentials or fetching the timelines to reconcile against,
+//! remember the last response with an `ETag` or `Last-Modified` header in `http-cache/` in the configuration directory,
+//! and send the next request for the same URL as the same user conditionally, with `If-None-Match` and
+//! `If-Modified-Since`; a `304 Not Modified` is then answered from the cache.
+//!
+//! The flow of a cached request is as follows:
+//!
+//! ```plaintext
+//! ops::http_cache::key()
+//! |> ops::http_cache::read()
+//! |> ops::http_cache::Entry::conditional_headers()
+//! |> the request
+//! |> ops::http_cache::hit() and the cached body, if not modified,
+//!    or ops::http_cache::miss() and ops::http_cache::write()
+//! ```
+//!
+//! The cache is used once its directory is set with `use_dir()`, which it isn't with `--read-only`, and how many requests
+//! it answered is counted for `--report` and `--json`, see `stats()`.
+
+
+use rustc_serialize::json::{self, Json};
+use self::super::super::Outcome;
+use crypto::digest::Digest;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use self::super::write_file;
+use crypto::sha1::Sha1;
+use std::sync::Mutex;
+use std::fs::{self, File};
+use std::io::Read;
+
+
+lazy_static! {
+    static ref CACHE: Mutex<(Option<PathBuf>, Stats)> = Mutex::new((None, Stats::default()));
+}
+
+
+/// A cached response.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Entry {
+    /// The response's `ETag`, if any.
+    pub etag: Option<String>,
+    /// The response's `Last-Modified`, if any.
+    pub last_modified: Option<String>,
+    /// The response body.
+    pub body: String,
+}
+
+/// How many conditional requests were answered from the cache and how many weren't.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// Requests answered with `304 Not Modified`, from the cache.
+    pub hits: usize,
+    /// Requests answered with a new response.
+    pub misses: usize,
+}
+
+
+impl Stats {
+    /// Get the statistics as reported, as an object with the `hits` and `misses`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rustc_serialize;
+    /// # extern crate tweetr;
+    /// # use tweetr::ops::http_cache::Stats;
+    /// # use rustc_serialize::json::Json;
+    /// # fn main() {
+    /// let json = Stats { hits: 1, misses: 2 }.to_json();
+    /// assert_eq!(json.find("hits"), Some(&Json::U64(1)));
+    /// assert_eq!(json.find("misses"), Some(&Json::U64(2)));
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("hits".to_string(), Json::U64(self.hits as u64));
+        obj.insert("misses".to_string(), Json::U64(self.misses as u64));
+        Json::Object(obj)
+    }
+}
+
+impl Entry {
+    /// Remember a response with the specified `ETag` and `Last-Modified` headers and body, if it can be requested
+    /// conditionally, i.e. has either header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::http_cache::Entry;
+    /// assert_eq!(Entry::new(None, None, "{}"), None);
+    /// assert_eq!(Entry::new(Some("\"6908265\""), None, "{}"),
+    ///            Some(Entry {
+    ///                etag: Some("\"6908265\"".to_string()),
+    ///                last_modified: None,
+    ///                body: "{}".to_string(),
+    ///            }));
+    /// ```
+    pub fn new(etag: Option<&str>, last_modified: Option<&str>, body: &str) -> Option<Entry> {
+        if etag.is_none() && last_modified.is_none() {
+            None
+        } else {
+            Some(Entry {
+                etag: etag.map(str::to_string),
+                last_modified: last_modified.map(str::to_string),
+                body: body.to_string(),
+            })
+        }
+    }
+
+    /// Get the headers to request the same resource again with, so it's only sent if it changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::http_cache::Entry;
+    /// let entry = Entry::new(Some("\"6908265\""), Some("Sat, 10 Sep 2016 10:00:00 GMT"), "{}").unwrap();
+    /// assert_eq!(entry.conditional_headers(),
+    ///            vec![("If-None-Match", "\"6908265\"".to_string()),
+    ///                 ("If-Modified-Since", "Sat, 10 Sep 2016 10:00:00 GMT".to_string())]);
+    /// ```
+    pub fn conditional_headers(&self) -> Vec<(&'static str, String)> {
+        self.etag
+            .iter()
+            .map(|e| ("If-None-Match", e.clone()))
+            .chain(self.last_modified.iter().map(|l| ("If-Modified-Since", l.clone())))
+            .collect()
+    }
+
+    /// Get the entry as written to the cache.
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("etag".to_string(), self.etag.clone().map(Json::String).unwrap_or(Json::Null));
+        obj.insert("last_modified".to_string(), self.last_modified.clone().map(Json::String).unwrap_or(Json::Null));
+        obj.insert("body".to_string(), Json::String(self.body.clone()));
+        Json::Object(obj)
+    }
+
+    /// Read an entry as written to the cache, if it is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::http_cache::Entry;
+    /// let entry = Entry::new(None, Some("Sat, 10 Sep 2016 10:00:00 GMT"), "{\"id\": 6908265}").unwrap();
+    /// assert_eq!(Entry::from_json(&entry.to_json()), Some(entry));
+    /// ```
+    pub fn from_json(json: &Json) -> Option<Entry> {
+        let string = |key| json.find(key).and_then(Json::as_string);
+        string("body").and_then(|body| Entry::new(string("etag"), string("last_modified"), body))
+    }
+}
+
+
+/// Get the path to the cache in the specified configuration directory.
+pub fn path(config_dir: &Path) -> PathBuf {
+    config_dir.join("http-cache")
+}
+
+/// Get the key of the response to the specified URL, with the specified query string, for the user with the specified
+/// access token key, as responses differ between users.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::http_cache;
+/// let key = http_cache::key("https://api.twitter.com/1.1/account/verify_credentials.json", "skip_status=true", "6908265-abcd");
+/// assert_eq!(key.len(), 40);
+/// assert!(key.chars().all(|c| c.is_digit(16)));
+/// assert!(key != http_cache::key("https://api.twitter.com/1.1/account/verify_credentials.json", "skip_status=true", "1234-efgh"));
+/// ```
+pub fn key(uri: &str, query: &str, access_key: &str) -> String {
+    let mut sha = Sha1::new();
+    sha.input_str(&format!("{}?{}\n{}", uri, query, access_key));
+    sha.result_str()
+}
+
+/// Read the entry with the specified key from the cache in the specified directory, if there is one.
+pub fn read(dir: &Path, key: &str) -> Option<Entry> {
+    let mut buf = String::new();
+    File::open(dir.join(key))
+        .and_then(|mut f| f.read_to_string(&mut buf))
+        .ok()
+        .and_then(|_| Json::from_str(&buf).ok())
+        .and_then(|j| Entry::from_json(&j))
+}
+
+/// Write the specified entry with the specified key to the cache in the specified directory, replacing the previous one.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::http_cache::{self, Entry};
+/// # use std::env::temp_dir;
+/// let dir = temp_dir().join("tweetr-doctest").join("ops-http-cache-write-0");
+/// let entry = Entry::new(Some("\"6908265\""), None, "{}").unwrap();
+///
+/// http_cache::write(&dir, "6908265", &entry).unwrap();
+/// assert_eq!(http_cache::read(&dir, "6908265"), Some(entry));
+/// assert_eq!(http_cache::read(&dir, "1234"), None);
+/// ```
+pub fn write(dir: &Path, key: &str, entry: &Entry) -> Result<(), Outcome> {
+    try!(fs::create_dir_all(dir).map_err(|e| Outcome::from_io("create", dir, e)));
+    write_file(&dir.join(key), &json::encode(&entry.to_json()).unwrap())
+}
+
+/// Cache the responses in the specified directory from now on, or, if `None`, stop caching them.
+pub fn use_dir(dir: Option<PathBuf>) {
+    CACHE.lock().unwrap().0 = dir;
+}
+
+/// Get the directory the responses are cached in, if they are.
+pub fn dir() -> Option<PathBuf> {
+    CACHE.lock().unwrap().0.clone()
+}
+
+/// Count a request answered from the cache.
+pub fn hit() {
+    CACHE.lock().unwrap().1.hits += 1;
+}
+
+/// Count a request not answered from the cache.
+pub fn miss() {
+    CACHE.lock().unwrap().1.misses += 1;
+}
+
+/// Get how many requests were answered from the cache, and how many weren't, so far.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::http_cache;
+/// let before = http_cache::stats();
+/// http_cache::hit();
+/// http_cache::miss();
+/// http_cache::miss();
+///
+/// let after = http_cache::stats();
+/// assert_eq!((after.hits - before.hits, after.misses - before.misses), (1, 2));
+/// ```
+pub fn stats() -> Stats {
+    CACHE.lock().unwrap().1
+}
@@ -8,11 +8,34 @@
 //! |> ops::init::get_data()
 //! |> ops::AppTokens::write()
 //! ```
+//!
+//! Or, when adding a named app:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::init::verify_named()
+//! |> ops::init::get_data()
+//! |> ops::Apps::write()
+//! ```
+//!
+//! If the app configuration already contains the app being initialised, what merging and replacing would change is printed,
+//! and the user chooses between them, or aborting:
+//!
+//! ```plaintext
+//! ops::init::get_data()
+//! |> ops::init::print_changes()
+//! |> util::resolve()
+//! |> ops::init::apply()
+//! |> ops::Apps::write()
+//! ```
 
 
-use self::super::super::util::prompt_exact_len;
-use self::super::{AppTokens, verify_file};
+use self::super::super::util::{Resolution, prompt_exact_len};
+use self::super::l10n::tr;
+use self::super::{AppTokens, Apps, results, verify_file};
+use self::super::transcript;
 use self::super::super::Outcome;
+use std::collections::BTreeMap;
 use std::io::{BufRead, Write};
 use std::path::PathBuf;
 
@@ -52,6 +75,55 @@ pub fn verify(config_dir: &(String, PathBuf), force: bool) -> Result<PathBuf, Ou
     verify_file("app.toml", false, config_dir, force, "")
 }
 
+/// Verify if, given the current configuration, it's permitted to add the specified named app.
+///
+/// Named apps are added to the existing global app configuration, which mustn't already contain an app with the same name,
+/// unless forced.
+///
+/// The return value contains either the path to the file containing the global app configuration and the apps currently in it
+/// or why getting them failed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::init;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-init-verify_named-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(init::verify_named(&("$TEMP/ops-init-verify_named-0".to_string(), tf.clone()), "client", false),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("app.toml"))
+///     .unwrap()
+///     .write_all(b"key = \"qzuqpwr101q4RtK9mDorI9ndm\"\n\
+///                  secret = \"HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5\"\n\
+///                  [[app]]\n\
+///                  name = \"client\"\n\
+///                  key = \"GeVFiYk7q8DhUmgMXE0iODrFa\"\n\
+///                  secret = \"bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe\"\n")
+///     .unwrap();
+/// assert_eq!(init::verify_named(&("$TEMP/ops-init-verify_named-0".to_string(), tf.clone()), "client", false),
+///            Err(Outcome::OverrideNoForce("$TEMP/ops-init-verify_named-0/app.toml".to_string())));
+/// assert!(init::verify_named(&("$TEMP/ops-init-verify_named-0".to_string(), tf.clone()), "client", true).is_ok());
+/// assert!(init::verify_named(&("$TEMP/ops-init-verify_named-0".to_string(), tf.clone()), "agency", false).is_ok());
+/// ```
+pub fn verify_named(config_dir: &(String, PathBuf), name: &str, force: bool) -> Result<(PathBuf, Apps), Outcome> {
+    let app_path = try!(verify_file("app.toml", true, config_dir, false, "init"));
+    let apps = try!(Apps::read_verbatim(&app_path).map_err(Option::unwrap));
+
+    if !force && apps.named.contains_key(name) {
+        Err(Outcome::OverrideNoForce(PathBuf::from(&config_dir.0).join("app.toml").to_str().unwrap().replace("\\", "/")))
+    } else {
+        Ok((app_path, apps))
+    }
+}
+
 /// Prompt the user for application data.
 ///
 /// # Examples
@@ -69,8 +141,197 @@ pub fn verify(config_dir: &(String, PathBuf), force: bool) -> Result<PathBuf, Ou
 ///            });
 /// ```
 pub fn get_data<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> AppTokens {
+    let _secret = transcript::secret();
     AppTokens {
-        key: prompt_exact_len(input, output, "App key", |_| true, 25).unwrap(),
-        secret: prompt_exact_len(input, output, "App secret", |_| true, 50).unwrap(),
+        key: prompt_exact_len(input, output, &tr("prompt_app_key", &[]), |_| true, 25).unwrap(),
+        secret: prompt_exact_len(input, output, &tr("prompt_app_secret", &[]), |_| true, 50).unwrap(),
+    }
+}
+
+/// Get the app configuration resulting from resolving the conflict between the existing apps and the specified app data
+/// as specified, or `None` if aborted.
+///
+/// The specified name is the one of the app being initialised, or `None` for the default app.
+///
+/// Merging changes only that app, keeping all the others; replacing leaves only the default app and the one being
+/// initialised.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{init, AppTokens, Apps};
+/// # use tweetr::util::Resolution;
+/// # use std::iter::FromIterator;
+/// # use std::collections::BTreeMap;
+/// let default = AppTokens {
+///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+/// };
+/// let client = AppTokens {
+///     key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+///     secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+/// };
+/// let data = AppTokens {
+///     key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+///     secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+/// };
+/// let existing = Apps {
+///     default: default.clone(),
+///     named: BTreeMap::from_iter(vec![("client".to_string(), client.clone())]),
+/// };
+///
+/// assert_eq!(init::apply(&existing, None, &data, Resolution::Merge),
+///            Some(Apps {
+///                default: data.clone(),
+///                named: BTreeMap::from_iter(vec![("client".to_string(), client.clone())]),
+///            }));
+/// assert_eq!(init::apply(&existing, None, &data, Resolution::Replace),
+///            Some(Apps {
+///                default: data.clone(),
+///                named: BTreeMap::new(),
+///            }));
+/// assert_eq!(init::apply(&existing, Some("agency"), &data, Resolution::Replace),
+///            Some(Apps {
+///                default: default.clone(),
+///                named: BTreeMap::from_iter(vec![("agency".to_string(), data.clone())]),
+///            }));
+/// assert_eq!(init::apply(&existing, None, &data, Resolution::Abort), None);
+/// ```
+pub fn apply(existing: &Apps, name: Option<&str>, data: &AppTokens, resolution: Resolution) -> Option<Apps> {
+    let mut apps = match resolution {
+        Resolution::Merge => existing.clone(),
+        Resolution::Replace => {
+            Apps {
+                default: existing.default.clone(),
+                named: BTreeMap::new(),
+            }
+        }
+        Resolution::Abort => return None,
+    };
+
+    match name {
+        Some(name) => {
+            apps.named.insert(name.to_string(), data.clone());
+        }
+        None => apps.default = data.clone(),
+    }
+    Some(apps)
+}
+
+/// Note the app with the specified name, or the default one, as created with its tokens stored, see `ops::results`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{init, results};
+/// results::collect();
+/// init::record_created(Some("agency"));
+/// let recorded = results::take();
+/// assert_eq!(recorded.created[0].name, "agency");
+/// assert_eq!(recorded.tokens[0].names, vec!["key", "secret"]);
+/// ```
+pub fn record_created(name: Option<&str>) {
+    let name = name.unwrap_or("default");
+    results::created("app", name, None);
+    results::stored_tokens("app", name, &["key", "secret"]);
+}
+
+/// Print what merging and replacing the existing apps with the specified app data would change in the specified file.
+///
+/// Changed apps are listed with `~` and which of their tokens changed, added ones with `+` and removed ones with `-`.
+/// Secrets aren't printed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{init, AppTokens, Apps};
+/// # use std::iter::FromIterator;
+/// # use std::collections::BTreeMap;
+/// let mut out = Vec::new();
+/// init::print_changes(&mut out,
+///                     "$HOME/.tweetr/app.toml",
+///                     &Apps {
+///                         default: AppTokens {
+///                             key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///                             secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///                         },
+///                         named: BTreeMap::from_iter(vec![("client".to_string(), AppTokens {
+///                             key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+///                             secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+///                         })]),
+///                     },
+///                     None,
+///                     &AppTokens {
+///                         key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+///                         secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+///                     });
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "\"$HOME/.tweetr/app.toml\" already contains the default app.\n\
+///             Merging would change:\n\
+///             \x20 ~ default app (key qzuqpwr101q4RtK9mDorI9ndm -> qdPD7N8CcPYDKiNv81QWNWaHK, secret)\n\
+///             Replacing would change:\n\
+///             \x20 ~ default app (key qzuqpwr101q4RtK9mDorI9ndm -> qdPD7N8CcPYDKiNv81QWNWaHK, secret)\n\
+///             \x20 - app \"client\"\n\
+///             \n");
+/// ```
+pub fn print_changes<W: Write>(output: &mut W, fname: &str, existing: &Apps, name: Option<&str>, data: &AppTokens) {
+    let app = match name {
+        Some(name) => format!("app \"{}\"", name),
+        None => "default app".to_string(),
+    };
+    for key in Some(&existing.default.key).into_iter().chain(existing.named.values().map(|tokens| &tokens.key)).chain(Some(&data.key)) {
+        transcript::hide(key);
+    }
+    writeln!(output, "{}", tr("conflict", &[("fname", &fname), ("entry", &app)])).unwrap();
+
+    for &(resolution, heading) in &[(Resolution::Merge, "merge_changes"), (Resolution::Replace, "replace_changes")] {
+        writeln!(output, "{}", tr(heading, &[])).unwrap();
+
+        let changes = diff(existing, &apply(existing, name, data, resolution).unwrap());
+        if changes.is_empty() {
+            writeln!(output, "  {}", tr("no_changes", &[])).unwrap();
+        }
+        for change in changes {
+            writeln!(output, "  {}", change).unwrap();
+        }
+    }
+    writeln!(output, "").unwrap();
+}
+
+
+fn diff(before: &Apps, after: &Apps) -> Vec<String> {
+    let mut changes = vec![];
+    if let Some(change) = diff_tokens(&before.default, &after.default) {
+        changes.push(format!("~ default app ({})", change));
+    }
+    for (name, tokens) in &before.named {
+        match after.named.get(name) {
+            Some(after_tokens) => {
+                if let Some(change) = diff_tokens(tokens, after_tokens) {
+                    changes.push(format!("~ app \"{}\" ({})", name, change));
+                }
+            }
+            None => changes.push(format!("- app \"{}\"", name)),
+        }
+    }
+    for name in after.named.keys().filter(|name| !before.named.contains_key(*name)) {
+        changes.push(format!("+ app \"{}\"", name));
+    }
+    changes
+}
+
+fn diff_tokens(before: &AppTokens, after: &AppTokens) -> Option<String> {
+    let mut changed = vec![];
+    if before.key != after.key {
+        changed.push(format!("key {} -> {}", before.key, after.key));
+    }
+    if before.secret != after.secret {
+        changed.push("secret".to_string());
+    }
+
+    if changed.is_empty() {
+        None
+    } else {
+        Some(changed.join(", "))
     }
 }
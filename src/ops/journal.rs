@@ -0,0 +1,528 @@
+//! The append-only journal of changes to the queue, shown by the `history` subsystem.
+//!
+//! Each subsystem changing the queue records what it changed, by comparing the queue before and after, in `journal.jsonl` in
+//! the configuration directory, as a line of JSON per changed tweet: an object with the `time` the change was made at, the
+//! `actor` that made it (the subsystem, like `queue-tweet` or `start-daemon`), the `user` running it, if known, the
+//! `action`, the `entry` it's about, the `changes`, for edits, and the `tweet` as changed. The changes are journalled before
+//! the queue is written, so ones interrupted while writing it, like posted tweets, are still recorded.
+//!
+//! The actions are:
+//!
+//!   * `queued` - the tweet was added to the queue,
+//!   * `edited` - the tweet was changed, `changes` lists what, like `"time"` when rescheduled,
+//!   * `posted` - the tweet was posted, its `tweet` has the `id`,
+//!   * `deleted` - the tweet was removed from the queue without being archived,
+//!   * `archived` - the posted tweet was moved to the archive.
+//!
+//! Entries are identified by a short hash of the author, content and scheduled time they were first journalled with, and
+//! keep their identifier through edits, since they're followed by the author, content and scheduled time they were last
+//! journalled with.
+//!
+//! The flow of the `history` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::journal::read()
+//! |> ops::journal::history()
+//! |> ops::journal::print_history()
+//! ```
+
+
+use self::super::l10n::format_datetime;
+use self::super::super::Outcome;
+use self::super::{QueuedTweet, results};
+use rustc_serialize::json::Json;
+use chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::env;
+
+
+/// What happened to a tweet in the queue.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Action {
+    /// The tweet was added to the queue.
+    Queued,
+    /// The specified fields of the tweet changed.
+    Edited(Vec<String>),
+    /// The tweet was posted.
+    Posted,
+    /// The tweet was removed from the queue without being archived.
+    Deleted,
+    /// The posted tweet was moved to the archive.
+    Archived,
+}
+
+/// The parts of a tweet identifying it in the journal.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The author to post on behalf of.
+    pub author: String,
+    /// The tweet's content.
+    pub content: String,
+    /// The time the tweet's scheduled for.
+    pub scheduled: DateTime<FixedOffset>,
+    /// The ID of the posted tweet, if posted.
+    pub id: Option<i64>,
+}
+
+/// A change to the queue, not yet journalled.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Change {
+    /// What happened.
+    pub action: Action,
+    /// The tweet before the change, if it was in the queue.
+    pub before: Option<Snapshot>,
+    /// The tweet after the change, or as it was removed.
+    pub tweet: Snapshot,
+}
+
+/// A journalled change.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Record {
+    /// When the change was made.
+    pub time: DateTime<FixedOffset>,
+    /// The subsystem that made the change.
+    pub actor: String,
+    /// The user running the subsystem, if known.
+    pub user: Option<String>,
+    /// What happened.
+    pub action: Action,
+    /// The identifier of the entry the change is about.
+    pub entry: String,
+    /// The tweet after the change, or as it was removed.
+    pub tweet: Snapshot,
+}
+
+
+/// Get the path to the journal in the specified configuration directory.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::journal;
+/// # use std::path::Path;
+/// assert_eq!(journal::path(Path::new("$HOME/.tweetr")), Path::new("$HOME/.tweetr/journal.jsonl"));
+/// ```
+pub fn path(config_dir: &Path) -> PathBuf {
+    config_dir.join("journal.jsonl")
+}
+
+/// Get the changes between the specified queues, the specified tweets having been moved to the archive in between.
+///
+/// Unchanged tweets are matched first, then changed ones by their author and either their content or their scheduled time,
+/// so rescheduling or rewording a tweet is an edit, not a deletion and a new tweet.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::journal::{self, Action};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let rescheduled = QueuedTweet { time: DateTime::parse_from_rfc3339("2016-09-10T15:00:00+02:00").unwrap(), ..tweet.clone() };
+/// let other = QueuedTweet { content: "Workers of the world, unite!".to_string(), ..tweet.clone() };
+///
+/// let changes = journal::diff(&[tweet.clone()], &[rescheduled.clone(), other.clone()], &[]);
+/// assert_eq!(changes.iter().map(|c| c.action.clone()).collect::<Vec<_>>(),
+///            vec![Action::Edited(vec!["time".to_string()]), Action::Queued]);
+/// assert_eq!(changes[0].before, Some(journal::Snapshot::of(&tweet)));
+///
+/// assert_eq!(journal::diff(&[tweet.clone(), other.clone()], &[tweet.clone(), other.clone()], &[]), vec![]);
+/// assert_eq!(journal::diff(&[tweet.clone(), other.clone()], &[other.clone()], &[])[0].action, Action::Deleted);
+/// # }
+/// ```
+pub fn diff(before: &[QueuedTweet], after: &[QueuedTweet], archived: &[QueuedTweet]) -> Vec<Change> {
+    let mut unmatched: Vec<_> = before.iter().map(Some).collect();
+    let mut pairs: Vec<(Option<&QueuedTweet>, &QueuedTweet)> = after.iter().map(|t| (None, t)).collect();
+
+    let matchers: [&Fn(&QueuedTweet, &QueuedTweet) -> bool; 4] = [&|b, a| b == a,
+                                                                  &|b, a| b.author == a.author && b.content == a.content && b.time == a.time,
+                                                                  &|b, a| b.author == a.author && b.content == a.content,
+                                                                  &|b, a| b.author == a.author && b.time == a.time];
+    for matcher in &matchers {
+        for pair in pairs.iter_mut().filter(|p| p.0.is_none()) {
+            if let Some(b) = unmatched.iter_mut().find(|b| b.map(|b| matcher(b, pair.1)).unwrap_or(false)) {
+                pair.0 = b.take();
+            }
+        }
+    }
+
+    let mut changes = vec![];
+    for (before, after) in pairs {
+        match before {
+            Some(before) if before == after => (),
+            Some(before) => {
+                if before.id.is_none() && after.id.is_some() {
+                    changes.push(change(Action::Posted, Some(before), after));
+                }
+                let edited = edited_fields(before, after);
+                if !edited.is_empty() {
+                    changes.push(change(Action::Edited(edited), Some(before), after));
+                }
+            }
+            None => changes.push(change(Action::Queued, None, after)),
+        }
+    }
+    for before in unmatched.into_iter().flat_map(|b| b) {
+        match archived.iter().find(|a| a.author == before.author && a.content == before.content && a.time == before.time) {
+            Some(archived) => {
+                if before.id.is_none() {
+                    changes.push(change(Action::Posted, Some(before), archived));
+                }
+                changes.push(change(Action::Archived, Some(before), archived));
+            }
+            None => changes.push(change(Action::Deleted, Some(before), before)),
+        }
+    }
+    changes
+}
+
+/// Journal the specified changes made by the specified actor at the specified time in the journal at the specified path.
+///
+/// The entries the changed tweets belong to are looked up in the journal.
+pub fn record(p: &Path, actor: &str, changes: &[Change], now: DateTime<FixedOffset>) -> Result<(), Outcome> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut entries = BTreeMap::new();
+    for record in try!(read(p)) {
+        entries.insert(record.tweet.key(), record.entry);
+    }
+
+    let user = env::var("USER").or_else(|_| env::var("USERNAME")).ok();
+    let mut lines = String::new();
+    for change in changes {
+        let entry = change.before
+            .as_ref()
+            .and_then(|b| entries.get(&b.key()).cloned())
+            .unwrap_or_else(|| entry_id(change.before.as_ref().unwrap_or(&change.tweet)));
+        entries.insert(change.tweet.key(), entry.clone());
+
+        let record = Record {
+            time: now,
+            actor: actor.to_string(),
+            user: user.clone(),
+            action: change.action.clone(),
+            entry: entry,
+            tweet: change.tweet.clone(),
+        };
+        lines.push_str(&format!("{}\n", record.to_json()));
+    }
+
+    try!(OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(p)
+        .and_then(|mut f| f.write_all(lines.as_bytes()))
+        .map_err(|e| Outcome::from_io("append to", p, e)));
+    results::wrote(p);
+    Ok(())
+}
+
+/// Read all the records in the journal at the specified path, oldest first.
+///
+/// A nonexistant journal is empty.
+pub fn read(p: &Path) -> Result<Vec<Record>, Outcome> {
+    let file = match File::open(p) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(Outcome::from_io("open", p, e)),
+    };
+
+    let mut records = vec![];
+    let mut errors = vec![];
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = try!(line.map_err(|e| Outcome::from_io("read", p, e)));
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Json::from_str(&line).ok().as_ref().and_then(Record::from_json) {
+            Some(record) => records.push(record),
+            None => errors.push(format!("error: {}:1: invalid journal record", i + 1)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(Outcome::FileParsingFailed {
+            desc: "journal",
+            errors: errors,
+        })
+    }
+}
+
+/// Get the records of the entry with the specified identifier, or of the entry posted with the specified tweet ID.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::journal::{self, Action, Record, Snapshot};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let record = |action: Action, entry: &str, id: Option<i64>| Record {
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     actor: "queue-tweet".to_string(),
+///     user: None,
+///     action: action,
+///     entry: entry.to_string(),
+///     tweet: Snapshot {
+///         author: "nabijaczleweli".to_string(),
+///         content: "Abolish the bourgeoisie".to_string(),
+///         scheduled: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///         id: id,
+///     },
+/// };
+/// let records = vec![record(Action::Queued, "4b6e3a1f", None),
+///                    record(Action::Queued, "0c1d2e3f", None),
+///                    record(Action::Posted, "4b6e3a1f", Some(774541307568103424))];
+///
+/// assert_eq!(journal::history(&records, "4b6e3a1f"), vec![&records[0], &records[2]]);
+/// assert_eq!(journal::history(&records, "774541307568103424"), vec![&records[0], &records[2]]);
+/// assert!(journal::history(&records, "ffffffff").is_empty());
+/// # }
+/// ```
+pub fn history<'r>(records: &'r [Record], id: &str) -> Vec<&'r Record> {
+    let entry = id.parse::<i64>()
+        .ok()
+        .and_then(|id| records.iter().find(|r| r.tweet.id == Some(id)))
+        .map(|r| &r.entry[..])
+        .unwrap_or(id);
+
+    records.iter().filter(|r| r.entry == entry).collect()
+}
+
+/// Print the specified records of an entry's lifecycle, one per line.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::journal::{self, Action, Record, Snapshot};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = Snapshot {
+///     author: "nabijaczleweli".to_string(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     scheduled: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     id: None,
+/// };
+/// let mut out = Vec::new();
+/// journal::print_history(&mut out, &[&Record {
+///                                        time: DateTime::parse_from_rfc3339("2016-09-09T10:00:00+02:00").unwrap(),
+///                                        actor: "queue-tweet".to_string(),
+///                                        user: Some("nabijaczleweli".to_string()),
+///                                        action: Action::Queued,
+///                                        entry: "4b6e3a1f".to_string(),
+///                                        tweet: tweet.clone(),
+///                                    },
+///                                    &Record {
+///                                        time: DateTime::parse_from_rfc3339("2016-09-09T11:00:00+02:00").unwrap(),
+///                                        actor: "shift-queue".to_string(),
+///                                        user: None,
+///                                        action: Action::Edited(vec!["time".to_string()]),
+///                                        entry: "4b6e3a1f".to_string(),
+///                                        tweet: tweet,
+///                                    }]);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Entry 4b6e3a1f:\n\
+///             \x20 2016-09-09T10:00:00+02:00: queued by queue-tweet (nabijaczleweli): \
+///                  \"Abolish the bourgeoisie\" by nabijaczleweli, scheduled for 2016-09-10T12:00:00+02:00\n\
+///             \x20 2016-09-09T11:00:00+02:00: edited (time) by shift-queue: \
+///                  \"Abolish the bourgeoisie\" by nabijaczleweli, scheduled for 2016-09-10T12:00:00+02:00\n");
+/// # }
+/// ```
+pub fn print_history<W: Write>(output: &mut W, records: &[&Record]) {
+    let mut entry = None;
+    for record in records {
+        if entry != Some(&record.entry) {
+            writeln!(output, "Entry {}:", record.entry).unwrap();
+            entry = Some(&record.entry);
+        }
+
+        write!(output, "  {}: {}", format_datetime(&record.time), record.action.name()).unwrap();
+        if let Action::Edited(ref fields) = record.action {
+            write!(output, " ({})", fields.join(", ")).unwrap();
+        }
+        write!(output, " by {}", record.actor).unwrap();
+        if let Some(ref user) = record.user {
+            write!(output, " ({})", user).unwrap();
+        }
+        write!(output,
+               ": \"{}\" by {}, scheduled for {}",
+               record.tweet.content,
+               record.tweet.author,
+               format_datetime(&record.tweet.scheduled))
+            .unwrap();
+        if let Some(id) = record.tweet.id {
+            write!(output, ", ID {}", id).unwrap();
+        }
+        writeln!(output, "").unwrap();
+    }
+}
+
+
+impl Action {
+    /// Get the action's name, as written in the journal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::journal::Action;
+    /// assert_eq!(Action::Edited(vec!["time".to_string()]).name(), "edited");
+    /// assert_eq!(Action::Archived.name(), "archived");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Action::Queued => "queued",
+            Action::Edited(_) => "edited",
+            Action::Posted => "posted",
+            Action::Deleted => "deleted",
+            Action::Archived => "archived",
+        }
+    }
+}
+
+impl Snapshot {
+    /// Take the parts of the specified tweet identifying it in the journal.
+    pub fn of(tweet: &QueuedTweet) -> Snapshot {
+        Snapshot {
+            author: tweet.author.clone(),
+            content: tweet.content.clone(),
+            scheduled: tweet.time,
+            id: tweet.id,
+        }
+    }
+
+    /// Get the snapshot as written in the journal.
+    pub fn to_json(&self) -> Json {
+        let mut tweet = BTreeMap::new();
+        tweet.insert("author".to_string(), Json::String(self.author.clone()));
+        tweet.insert("content".to_string(), Json::String(self.content.clone()));
+        tweet.insert("scheduled".to_string(), Json::String(self.scheduled.to_rfc3339()));
+        if let Some(id) = self.id {
+            tweet.insert("id".to_string(), Json::I64(id));
+        }
+        Json::Object(tweet)
+    }
+
+    fn key(&self) -> (String, String, DateTime<FixedOffset>) {
+        (self.author.clone(), self.content.clone(), self.scheduled)
+    }
+}
+
+impl Record {
+    /// Get the record as written in the journal.
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("time".to_string(), Json::String(self.time.to_rfc3339()));
+        obj.insert("actor".to_string(), Json::String(self.actor.clone()));
+        if let Some(ref user) = self.user {
+            obj.insert("user".to_string(), Json::String(user.clone()));
+        }
+        obj.insert("action".to_string(), Json::String(self.action.name().to_string()));
+        if let Action::Edited(ref fields) = self.action {
+            obj.insert("changes".to_string(), Json::Array(fields.iter().cloned().map(Json::String).collect()));
+        }
+        obj.insert("entry".to_string(), Json::String(self.entry.clone()));
+        obj.insert("tweet".to_string(), self.tweet.to_json());
+        Json::Object(obj)
+    }
+
+    /// Parse a record as written in the journal.
+    pub fn from_json(json: &Json) -> Option<Record> {
+        let datetime = |key: &str, obj: &Json| obj.find(key).and_then(Json::as_string).and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+        let string = |key: &str, obj: &Json| obj.find(key).and_then(Json::as_string).map(str::to_string);
+
+        let tweet = match json.find("tweet") {
+            Some(tweet) => tweet,
+            None => return None,
+        };
+        let action = match json.find("action").and_then(Json::as_string) {
+            Some("queued") => Action::Queued,
+            Some("edited") => {
+                Action::Edited(json.find("changes")
+                    .and_then(Json::as_array)
+                    .map(|cs| cs.iter().flat_map(Json::as_string).map(str::to_string).collect())
+                    .unwrap_or_default())
+            }
+            Some("posted") => Action::Posted,
+            Some("deleted") => Action::Deleted,
+            Some("archived") => Action::Archived,
+            _ => return None,
+        };
+
+        match (datetime("time", json), string("actor", json), string("entry", json), string("author", tweet), string("content", tweet),
+               datetime("scheduled", tweet)) {
+            (Some(time), Some(actor), Some(entry), Some(author), Some(content), Some(scheduled)) => {
+                Some(Record {
+                    time: time,
+                    actor: actor,
+                    user: string("user", json),
+                    action: action,
+                    entry: entry,
+                    tweet: Snapshot {
+                        author: author,
+                        content: content,
+                        scheduled: scheduled,
+                        id: tweet.find("id").and_then(Json::as_i64),
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+
+fn change(action: Action, before: Option<&QueuedTweet>, after: &QueuedTweet) -> Change {
+    Change {
+        action: action,
+        before: before.map(Snapshot::of),
+        tweet: Snapshot::of(after),
+    }
+}
+
+/// The fields users edit, not the ones filled in when posting.
+fn edited_fields(before: &QueuedTweet, after: &QueuedTweet) -> Vec<String> {
+    let fields = [("time", before.time != after.time || before.after != after.after),
+                  ("content", before.content != after.content),
+                  ("media", before.media != after.media),
+                  ("thread", before.thread != after.thread),
+                  ("extra_params", before.extra_params != after.extra_params),
+                  ("variants", before.variants != after.variants),
+                  ("coordinates", before.coordinates != after.coordinates),
+                  ("place_id", before.place_id != after.place_id),
+                  ("reply_settings", before.reply_settings != after.reply_settings),
+                  ("name", before.name != after.name),
+                  ("paused", before.paused != after.paused),
+                  ("blocked", before.blocked != after.blocked),
+                  ("settings", before.settings != after.settings)];
+    fields.iter().filter(|&&(_, changed)| changed).map(|&(field, _)| field.to_string()).collect()
+}
+
+/// The first 8 hex digits of the 64-bit FNV-1a hash of the tweet's author, content and scheduled time, which, unlike
+/// `DefaultHasher`'s, is stable across builds.
+fn entry_id(tweet: &Snapshot) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in format!("{}\n{}\n{}", tweet.author, tweet.content, tweet.scheduled.to_rfc3339()).bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:08x}", hash >> 32)
+}
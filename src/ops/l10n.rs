@@ -0,0 +1,402 @@
+//! Translations of the prompts, error messages and daemon logs.
+//!
+//! Each message has an ID and an English text, embedded in the executable and used whenever there's no translation.
+//! Translations are read from message catalogs, TOML files mapping message IDs to the translated texts, found as
+//! `locale/<locale>.toml` in the configuration directory, or, failing that, among the ones built in.
+//!
+//! The values filled into a message are referred to in its text by name, as `{name}`.
+//!
+//! The catalog in use is set per thread, with English being the default.
+//!
+//! Dates and times are displayed in the formats in use, also set per thread, see `Formats`.
+
+
+use self::super::{key_error, parse_toml_file};
+use self::super::super::Outcome;
+use chrono::{Date, DateTime, TimeZone, Weekday};
+use chrono::format::{Item, StrftimeItems};
+use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::path::Path;
+use toml::{Parser, Table, Value};
+use std::env;
+use std::fmt;
+
+
+/// All messages, by ID, in English.
+pub static ENGLISH: &'static [(&'static str, &'static str)] =
+    &[("override_no_force", "File \"{fname}\" was not overriden to prevent data loss.\nPass --force to override it."),
+      ("required_file", "Run the {subsys} subsystem first to produce \"{fname}\"."),
+      ("required_data", "Run the {subsys} subsystem first to {desc}."),
+      ("setup_first_run",
+       "Nothing's configured yet.\nRun the setup subsystem to configure the app, authorise a user and queue tweets step by step, \
+        or start with the init subsystem."),
+      ("setup_no_app", "The app isn't configured yet.\nNext, run the init subsystem to enter its key and secret, the users and tweets already there are kept."),
+      ("setup_no_users", "The app is configured, but no users are authorised yet.\nNext, run the add-user subsystem to authorise an account to post as."),
+      ("setup_no_queue", "The app is configured and users are authorised, but no tweets are queued yet.\nNext, run the queue-tweet subsystem to queue some."),
+      ("setup_complete", "Everything's configured."),
+      ("twitter_api_error", "Twitter API error: {error}"),
+      ("file_parsing_failed", "Failed to parse {desc}."),
+      ("file_parsing_failed_with_errors", "Failed to parse {desc}:"),
+      ("hook_vetoed", "The {hook} hook rejected the tweet: {reason}"),
+      ("preflight_failed", "Can't start:"),
+      ("backend_error", "{backend} error: {error}"),
+      ("network_timeout", "Timed out {desc}."),
+      ("rate_limited", "Rate limited {desc}, try again after {time}."),
+      ("rate_limited_later", "Rate limited {desc}, try again later."),
+      ("template_error", "Can't use the template \"{name}\":"),
+      ("update_failed", "Couldn't update: {error}"),
+      ("io_error", "Couldn't {op} \"{path}\": {cause}"),
+      ("multiple_errors", "{count} errors occured:"),
+      ("prompt_app_key", "App key"),
+      ("prompt_app_secret", "App secret"),
+      ("authorisation_url", "Visit this URL: {url}"),
+      ("prompt_pin", "Enter the PIN from that page"),
+      ("user_authenticated", "Successfully authenticated user {name}#{id}"),
+      ("access_tokens", "Access tokens:"),
+      ("tokens_rotated", "Rotated the access tokens of user {name}#{id}"),
+      ("user_removed", "Removed user {name}#{id}"),
+      ("prompt_author", "Author (or empty to finish)"),
+      ("prompt_content", "Tweet content"),
+      ("prompt_time", "Time to post the tweet (RFC2822, RFC3339 or relative)"),
+      ("prompt_latitude", "Latitude (or empty for none)"),
+      ("prompt_longitude", "Longitude"),
+      ("prompt_place_id", "Place ID (or empty for none)"),
+      ("prompt_move", "Move it there? [y/N]"),
+      ("prompt_reedit", "Edit it again? [Y/n]"),
+      ("prompt_strip", "Strip them? [y/N]"),
+      ("prompt_queue_past", "Queue it anyway? [y/N]"),
+      ("prompt_post_all", "Post them now? [y/N]"),
+      ("prompt_post_now", "Post \"{content}\" as {author} now? [y/N]"),
+      ("prompt_prune_posted", "Delete them from Twitter? [y/N]"),
+      ("prompt_remove_user", "Remove user {name}#{id}? [y/N]"),
+      ("prompt_override", "Override \"{fname}\"? [y/N]"),
+      ("conflict", "\"{fname}\" already contains the {entry}."),
+      ("merge_changes", "Merging would change:"),
+      ("replace_changes", "Replacing would change:"),
+      ("no_changes", "nothing"),
+      ("prompt_resolve", "Merge, replace or abort? [m/r/A]"),
+      ("posting", "Posting tweet scheduled for {time}..."),
+      ("posted", "Posted tweet \"{content}\" scheduled for {time} by {author} at {time_posted} with ID {id}"),
+      ("mirroring", "Mirroring tweet to {backend}..."),
+      ("mirrored", "Mirrored tweet \"{content}\" to {backend} with ID {id}"),
+      ("failed", "FAILED"),
+      ("warning", "Warning: {warning}"),
+      ("broken_entries_skipped", "Posting the remaining tweets, the broken entries were left as-is."),
+      ("account_suspended",
+       "The account {user} is suspended or locked, its tweets won't be posted until \"suspended\" is removed from it in users.toml."),
+      ("thread_blocked",
+       "\"{content}\" failed permanently, holding back {count} tweet(s) of thread {thread} until requeued with `tweetr requeue-thread {thread}`."),
+      ("media_removed", "Removed unused media {path} ({size})"),
+      ("evergreen_queued", "Queued evergreen tweet by {author} for {time}: {content}"),
+      ("embargo_rescheduled", "Rescheduled tweet by {author} from {time} to {new_time}, the embargo is lifted at {lifted}"),
+      ("daily_cap_carried_over", "Carried tweet by {author} over from {time} to {new_time}, {author} already posted {max} tweets that day"),
+      ("latency_exceeded", "Tweets are being posted {latency} late on average, over the threshold of {threshold}, the machine might be overloaded"),
+      ("post_timed_out",
+       "Posting \"{content}\" by {author} timed out, it might or might not have been posted, paused it, check whether it was and unpause it")];
+
+/// The catalogs built into the executable, by locale.
+pub static BUILT_IN: &'static [(&'static str, &'static str)] = &[("pl", include_str!("../../assets/locale/pl.toml"))];
+
+thread_local! {
+    static CATALOG: RefCell<BTreeMap<String, String>> = RefCell::new(BTreeMap::new());
+    static FORMATS: RefCell<Formats> = RefCell::new(Formats::default());
+}
+
+
+/// How to display dates and times, as `strftime` patterns, see `chrono::format::strftime`.
+///
+/// Only what's displayed is affected, the times read and written, like in the queue, are always RFC 3339.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formats {
+    /// The pattern for dates with times, or `None` for RFC 3339. Default: `None`
+    pub datetime: Option<String>,
+    /// The pattern for dates, like in the calendar's headers. Default: `"%a %d %b"`
+    pub date: String,
+    /// The pattern for times of day, like in the calendar's cells. Default: `"%H:%M"`
+    pub time: String,
+    /// The day weeks start on, like in the calendar. Default: `Weekday::Mon`
+    pub first_day_of_week: Weekday,
+}
+
+
+impl Default for Formats {
+    fn default() -> Formats {
+        Formats {
+            datetime: None,
+            date: "%a %d %b".to_string(),
+            time: "%H:%M".to_string(),
+            first_day_of_week: Weekday::Mon,
+        }
+    }
+}
+
+
+/// Get the message with the specified ID in the current thread's catalog, or in English if it has none,
+/// with the specified values filled in.
+///
+/// The placeholders are filled in in one pass over the message, so values containing placeholders are left as they are.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::l10n;
+/// assert_eq!(l10n::tr("hook_vetoed", &[("hook", &"pre-post"), ("reason", &"too spicy")]),
+///            "The pre-post hook rejected the tweet: too spicy");
+/// assert_eq!(l10n::tr("hook_vetoed", &[("hook", &"{reason}"), ("reason", &"{hook}")]),
+///            "The {reason} hook rejected the tweet: {hook}");
+/// assert_eq!(l10n::tr("prompt_pin", &[]), "Enter the PIN from that page");
+/// ```
+pub fn tr(id: &str, values: &[(&str, &fmt::Display)]) -> String {
+    let text = CATALOG.with(|c| c.borrow().get(id).cloned())
+        .or_else(|| ENGLISH.iter().find(|&&(eid, _)| eid == id).map(|&(_, text)| text.to_string()))
+        .unwrap_or_else(|| id.to_string());
+
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = &text[..];
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| values.iter().find(|&&(name, _)| name == &rest[1..end]).map(|&(_, value)| (end, value)));
+        match value {
+            Some((end, value)) => {
+                filled.push_str(&value.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Get the locale to use: the specified one, or, if none, the one set in the environment, `LC_ALL`, `LC_MESSAGES` or `LANG`,
+/// in that order.
+///
+/// Returns `None` for the default locale.
+pub fn locale(specified: Option<&str>) -> Option<String> {
+    match specified {
+        Some(l) => normalise_locale(l),
+        None => {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .flat_map(|var| env::var(var).ok())
+                .find(|l| !l.is_empty())
+                .and_then(|l| normalise_locale(&l))
+        }
+    }
+}
+
+/// Strip the encoding and modifier off the specified locale, returning `None` for the default one.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::l10n;
+/// assert_eq!(l10n::normalise_locale("pl_PL.UTF-8"), Some("pl_PL".to_string()));
+/// assert_eq!(l10n::normalise_locale("sr_RS@latin"), Some("sr_RS".to_string()));
+/// assert_eq!(l10n::normalise_locale("pl"), Some("pl".to_string()));
+/// assert_eq!(l10n::normalise_locale("C.UTF-8"), None);
+/// assert_eq!(l10n::normalise_locale("POSIX"), None);
+/// ```
+pub fn normalise_locale(locale: &str) -> Option<String> {
+    let locale = locale.split(|c| c == '.' || c == '@').next().unwrap().trim();
+
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        None
+    } else {
+        Some(locale.to_string())
+    }
+}
+
+/// Read the catalog for the specified locale, from the specified configuration directory or built in, trying just the language
+/// (e.g. `pl` for `pl_PL`) if there's none for the whole locale.
+///
+/// Returns `Ok(None)` if there's no catalog for the locale at all.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::l10n;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-l10n-read_catalog-0");
+/// let catalog = l10n::read_catalog(&tf, "pl_PL").unwrap().unwrap();
+/// assert_eq!(catalog["prompt_app_key"], "Klucz aplikacji");
+///
+/// assert_eq!(l10n::read_catalog(&tf, "tlh"), Ok(None));
+/// ```
+pub fn read_catalog(config_dir: &Path, locale: &str) -> Result<Option<BTreeMap<String, String>>, Outcome> {
+    let mut candidates = vec![locale];
+    if let Some(underscore) = locale.find('_') {
+        candidates.push(&locale[..underscore]);
+    }
+
+    for candidate in candidates {
+        let path = config_dir.join("locale").join(format!("{}.toml", candidate));
+        if path.exists() {
+            let (_, table) = try!(parse_toml_file(&path, "message catalog").map_err(Option::unwrap));
+            return catalog_from_table(table, |key, message| key_error(&path, "message catalog", key, message)).map(Some);
+        }
+
+        if let Some(&(_, built_in)) = BUILT_IN.iter().find(|&&(l, _)| l == candidate) {
+            return Ok(Some(catalog_from_table(Parser::new(built_in).parse().unwrap(), |key, message| panic!("{} for the key `{}`", message, key))
+                .unwrap()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Use the specified catalog for all messages subsequently produced on the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::l10n;
+/// # use std::collections::BTreeMap;
+/// let mut catalog = BTreeMap::new();
+/// catalog.insert("prompt_pin".to_string(), "Wpisz PIN z tej strony".to_string());
+/// l10n::use_catalog(catalog);
+///
+/// assert_eq!(l10n::tr("prompt_pin", &[]), "Wpisz PIN z tej strony");
+/// assert_eq!(l10n::tr("prompt_app_key", &[]), "App key");
+/// ```
+pub fn use_catalog(catalog: BTreeMap<String, String>) {
+    CATALOG.with(|c| *c.borrow_mut() = catalog);
+}
+
+/// Use the specified formats for all dates and times subsequently displayed on the current thread.
+///
+/// The patterns must be valid, see `valid_format()`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::l10n::{self, Formats};
+/// # use chrono::Weekday;
+/// # fn main() {
+/// assert_eq!(l10n::formats(), Formats::default());
+///
+/// l10n::use_formats(Formats { first_day_of_week: Weekday::Sun, ..Formats::default() });
+/// assert_eq!(l10n::formats().first_day_of_week, Weekday::Sun);
+/// # }
+/// ```
+pub fn use_formats(formats: Formats) {
+    FORMATS.with(|f| *f.borrow_mut() = formats);
+}
+
+/// Get the formats in use on the current thread.
+pub fn formats() -> Formats {
+    FORMATS.with(|f| f.borrow().clone())
+}
+
+/// Display the specified date with time in the format in use.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::l10n::{self, Formats};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(l10n::format_datetime(&time), "2016-09-10T12:00:00+02:00");
+///
+/// l10n::use_formats(Formats { datetime: Some("%d.%m.%Y %H:%M".to_string()), ..Formats::default() });
+/// assert_eq!(l10n::format_datetime(&time), "10.09.2016 12:00");
+/// # }
+/// ```
+pub fn format_datetime<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+    where Tz::Offset: fmt::Display
+{
+    FORMATS.with(|f| match f.borrow().datetime {
+        Some(ref pattern) => time.format(pattern).to_string(),
+        None => time.to_rfc3339(),
+    })
+}
+
+/// Display the specified date in the format in use.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::l10n::{self, Formats};
+/// # use chrono::{FixedOffset, TimeZone};
+/// # fn main() {
+/// let date = FixedOffset::east(2 * 60 * 60).ymd(2016, 9, 10);
+/// assert_eq!(l10n::format_date(&date), "Sat 10 Sep");
+///
+/// l10n::use_formats(Formats { date: "%d.%m".to_string(), ..Formats::default() });
+/// assert_eq!(l10n::format_date(&date), "10.09");
+/// # }
+/// ```
+pub fn format_date<Tz: TimeZone>(date: &Date<Tz>) -> String
+    where Tz::Offset: fmt::Display
+{
+    FORMATS.with(|f| date.format(&f.borrow().date).to_string())
+}
+
+/// Display the time of day of the specified date with time in the format in use.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::l10n::{self, Formats};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-10T21:30:00+02:00").unwrap();
+/// assert_eq!(l10n::format_time(&time), "21:30");
+///
+/// l10n::use_formats(Formats { time: "%I:%M %p".to_string(), ..Formats::default() });
+/// assert_eq!(l10n::format_time(&time), "09:30 PM");
+/// # }
+/// ```
+pub fn format_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+    where Tz::Offset: fmt::Display
+{
+    FORMATS.with(|f| time.format(&f.borrow().time).to_string())
+}
+
+/// Check whether the specified `strftime` pattern is valid, i.e. has no unknown or unfinished specifiers.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::l10n;
+/// assert!(l10n::valid_format("%d.%m.%Y %H:%M"));
+/// assert!(l10n::valid_format("at %%H"));
+/// assert!(!l10n::valid_format("%d.%m.%"));
+/// assert!(!l10n::valid_format("%Q"));
+/// ```
+pub fn valid_format(pattern: &str) -> bool {
+    StrftimeItems::new(pattern).all(|i| i != Item::Error)
+}
+
+
+fn catalog_from_table<F: Fn(&str, &str) -> Outcome>(table: Table, error: F) -> Result<BTreeMap<String, String>, Outcome> {
+    let mut catalog = BTreeMap::new();
+    for (key, value) in table {
+        if !ENGLISH.iter().any(|&(id, _)| id == key) {
+            return Err(error(&key, "unknown message"));
+        }
+
+        match value {
+            Value::String(text) => {
+                catalog.insert(key, text);
+            }
+            _ => return Err(error(&key, "expected a value of type `string`")),
+        }
+    }
+    Ok(catalog)
+}
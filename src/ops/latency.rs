@@ -0,0 +1,70 @@
+//! Posting latency, how late after coming due the daemon posts tweets, to notice the machine it runs on being overloaded.
+//!
+//! The latency is tracked as an exponential moving average, each tweet posted moving it `SMOOTHING` of the way towards how
+//! late that one was, so it follows the daemon falling behind, but not the odd slow request. Only tweets coming due while
+//! the daemon's running count, the ones that came due before it started are late regardless (see `ops::recovery`).
+//!
+//! Once the average exceeds the threshold (see `Config::latency_threshold()`), the daemon warns and shows a notification,
+//! and only does so again after it's dropped back below.
+//!
+//! The flow of the daemon tracking the latency is as follows:
+//!
+//! ```plaintext
+//! ops::latency::Latency::record(), for each tweet posted
+//! |> ops::notify::Notification::late(), if it just exceeded the threshold
+//! ```
+
+
+use chrono::{DateTime, Duration, FixedOffset};
+use std::cmp;
+
+
+/// How far towards the latency of each tweet posted the average moves.
+pub static SMOOTHING: f64 = 0.2;
+
+
+/// The average posting latency so far.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Latency {
+    /// The average latency, in seconds, starting at 0.
+    pub average: f64,
+    /// Whether the average exceeds the threshold, and so was already alerted about.
+    pub exceeded: bool,
+}
+
+
+impl Latency {
+    /// Record a tweet due at the specified time having been posted at the other specified time, returning whether the average
+    /// just exceeded the specified threshold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::latency::Latency;
+    /// # use chrono::{DateTime, Duration};
+    /// # fn main() {
+    /// let due = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+    /// let threshold = Some(Duration::minutes(1));
+    ///
+    /// let mut latency = Latency::default();
+    /// assert!(!latency.record(due, due + Duration::seconds(5), threshold));
+    /// assert_eq!(latency.average.round(), 1.0);
+    ///
+    /// assert!(latency.record(due, due + Duration::minutes(10), threshold));
+    /// assert_eq!(latency.average.round(), 121.0);
+    /// assert!(!latency.record(due, due + Duration::minutes(10), threshold));
+    /// assert!(latency.exceeded);
+    /// # }
+    /// ```
+    pub fn record(&mut self, due: DateTime<FixedOffset>, posted: DateTime<FixedOffset>, threshold: Option<Duration>) -> bool {
+        let latency = cmp::max(posted - due, Duration::zero()).num_milliseconds() as f64 / 1000.0;
+        self.average += SMOOTHING * (latency - self.average);
+
+        let exceeded = threshold.map(|t| self.average > t.num_seconds() as f64).unwrap_or(false);
+        let alert = exceeded && !self.exceeded;
+        self.exceeded = exceeded;
+        alert
+    }
+}
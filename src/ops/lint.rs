@@ -0,0 +1,141 @@
+//! Style rules, checked when queueing an account's tweets, to keep them in line with its style guide.
+//!
+//! Rules are specified per account, in its `[user.lint]` table (see `User::lint`), and apply to the tweets' content as
+//! queued: hashtags appended when posting (see `Settings::tags`) aren't counted. Breaking them is warned about, or, with
+//! `block = true`, the tweet isn't queued at all.
+//!
+//! The flow of checking a tweet is as follows:
+//!
+//! ```plaintext
+//! ops::User::read()
+//! |> ops::lint::Lint::check(), with the tweet's author's rules, if any
+//! |> ops::lint::Lint::blocks()
+//! ```
+
+
+use self::super::queue_tweet::find_urls;
+
+
+/// The `[user.lint]` table in the users file, specifying an account's style rules.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable, Default)]
+pub struct Lint {
+    /// The most hashtags a tweet can contain, if limited. Default: `None`
+    pub max_hashtags: Option<u32>,
+    /// The most accounts a tweet can mention, if limited. Default: `None`
+    pub max_mentions: Option<u32>,
+    /// The most letters a word in ALL CAPS can have, if limited. Default: `None`
+    ///
+    /// Hashtags, mentions and links aren't words for this purpose.
+    pub max_caps: Option<u32>,
+    /// Hashtags tweets containing which must also contain a link, with or without the leading `#`. Default: `[]`
+    ///
+    /// Matched case-insensitively, with `*` standing for any amount of any characters, like `"release*"`.
+    pub link_required_for: Option<Vec<String>>,
+    /// Whether to refuse to queue tweets breaking the rules, instead of warning about them. Default: `false`
+    pub block: Option<bool>,
+}
+
+impl Lint {
+    /// Check the specified tweet content against the rules.
+    ///
+    /// Returns a description of each broken rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::lint::Lint;
+    /// let lint = Lint {
+    ///     max_hashtags: Some(1),
+    ///     max_mentions: Some(1),
+    ///     max_caps: Some(3),
+    ///     link_required_for: Some(vec!["#release*".to_string()]),
+    ///     block: None,
+    /// };
+    ///
+    /// assert!(lint.check("tweetr v1.4.0 is out! #ReleaseDay https://github.com/nabijaczleweli/tweetr").is_empty());
+    /// assert_eq!(lint.check("FREE #tweetr for @nabijaczleweli and @brandacct, NOW! #ReleaseDay"),
+    ///            vec!["2 hashtags, more than the 1 allowed".to_string(),
+    ///                 "2 mentions, more than the 1 allowed".to_string(),
+    ///                 "\"FREE\" is in capitals, longer than the 3 letters allowed".to_string(),
+    ///                 "#ReleaseDay without a link".to_string()]);
+    /// ```
+    pub fn check(&self, content: &str) -> Vec<String> {
+        let words: Vec<_> = content.split_whitespace()
+            .map(|w| {
+                w.trim_left_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '#' && c != '@')
+                    .trim_right_matches(|c: char| !c.is_alphanumeric() && c != '_')
+            })
+            .collect();
+        let hashtags: Vec<_> = words.iter().filter(|w| w.starts_with('#') && w.len() > 1).collect();
+        let mentions = words.iter().filter(|w| w.starts_with('@') && w.len() > 1).count();
+        let mut errors = vec![];
+
+        if let Some(max) = self.max_hashtags {
+            if hashtags.len() > max as usize {
+                errors.push(format!("{} hashtag{}, more than the {} allowed", hashtags.len(), if hashtags.len() == 1 { "" } else { "s" }, max));
+            }
+        }
+        if let Some(max) = self.max_mentions {
+            if mentions > max as usize {
+                errors.push(format!("{} mention{}, more than the {} allowed", mentions, if mentions == 1 { "" } else { "s" }, max));
+            }
+        }
+        if let Some(max) = self.max_caps {
+            for word in words.iter().filter(|w| !w.starts_with('#') && !w.starts_with('@') && !w.contains("://")) {
+                for caps in word.split(|c: char| !c.is_alphabetic()) {
+                    if caps.chars().count() > max as usize && caps.chars().all(char::is_uppercase) {
+                        errors.push(format!("\"{}\" is in capitals, longer than the {} letter{} allowed", caps, max, if max == 1 { "" } else { "s" }));
+                    }
+                }
+            }
+        }
+        if let Some(ref patterns) = self.link_required_for {
+            if find_urls(content).is_empty() {
+                for hashtag in hashtags.iter().filter(|h| patterns.iter().any(|p| matches(p.trim_left_matches('#'), &h[1..]))) {
+                    errors.push(format!("{} without a link", hashtag));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Check whether tweets breaking the rules aren't queued.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::lint::Lint;
+    /// assert!(!Lint::default().blocks());
+    /// assert!(Lint { block: Some(true), ..Lint::default() }.blocks());
+    /// ```
+    pub fn blocks(&self) -> bool {
+        self.block.unwrap_or(false)
+    }
+}
+
+
+fn matches(pattern: &str, hashtag: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let hashtag = hashtag.to_lowercase();
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap();
+    if !hashtag.starts_with(first) {
+        return false;
+    }
+
+    let mut rest = &hashtag[first.len()..];
+    let mut parts: Vec<_> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
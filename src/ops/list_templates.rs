@@ -0,0 +1,57 @@
+//! This module contains the functions used only by the `list-templates` subsystem.
+//!
+//! The flow of the `list-templates` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Template::path()
+//! |> ops::Template::read()
+//! |> ops::list_templates::print_template(), for each template
+//! ```
+
+
+use self::super::Template;
+use std::io::Write;
+
+
+/// Print the specified template's name and placeholders, followed by its content, indented.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{Template, list_templates};
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// list_templates::print_template(&mut out,
+///                                &Template {
+///                                    name: "release".to_string(),
+///                                    content: "tweetr {version} is out!\n{notes}".to_string(),
+///                                });
+/// list_templates::print_template(&mut out,
+///                                &Template {
+///                                    name: "hello".to_string(),
+///                                    content: "Hello, world!".to_string(),
+///                                });
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "release: {version} {notes}\n  \
+///               tweetr {version} is out!\n  \
+///               {notes}\n\
+///             hello: no placeholders\n  \
+///               Hello, world!\n".to_string());
+/// ```
+pub fn print_template<W: Write>(output: &mut W, template: &Template) {
+    let placeholders = template.placeholders().unwrap();
+    if placeholders.is_empty() {
+        writeln!(output, "{}: no placeholders", template.name).unwrap();
+    } else {
+        writeln!(output,
+                 "{}: {}",
+                 template.name,
+                 placeholders.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(" "))
+            .unwrap();
+    }
+
+    for line in template.content.lines() {
+        writeln!(output, "  {}", line).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! This module contains the functions used only by the `list-users` subsystem.
+//!
+//! The flow of the `list-users` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::list_users::verify()
+//! |> ops::User::read()
+//! |> ops::Apps::read(), if checking
+//! |> ops::doctor::verify_credentials(), for each user, if checking
+//! |> ops::list_users::print_users()
+//! ```
+//!
+//! No secrets are printed.
+
+
+use self::super::super::Outcome;
+use self::super::backend::PostingBackend;
+use self::super::super::util::table::{Column, Table};
+use self::super::l10n::format_datetime;
+use self::super::{User, verify_file};
+use std::path::PathBuf;
+use chrono::DateTime;
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `list-users`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global app configuration, if the users' access
+/// tokens are to be checked, and the path to the file containing the global users data or why getting them failed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::list_users;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-list-users-verify");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("users.toml")).unwrap();
+///
+/// assert_eq!(list_users::verify(&("$TEMP/ops-list-users-verify".to_string(), tf.clone()), false),
+///            Ok((None, tf.join("users.toml"))));
+/// assert_eq!(list_users::verify(&("$TEMP/ops-list-users-verify".to_string(), tf.clone()), true),
+///            Err(Outcome::SetupIncomplete(SetupState::NoApp)));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf), check: bool) -> Result<(Option<PathBuf>, PathBuf), Outcome> {
+    let users = try!(verify_file("users.toml", true, config_dir, false, "add-user"));
+    let app = if check {
+        Some(try!(verify_file("app.toml", true, config_dir, false, "init")))
+    } else {
+        None
+    };
+    Ok((app, users))
+}
+
+/// Print a table of the specified users' names and IDs, the apps they're authorised for, where their tweets go, when they
+/// were added, since when posting their tweets is paused, if for any of them, and, if checked, whether their access tokens
+/// work, fit in the specified width if possible.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{list_users, User};
+/// # use tweetr::Outcome;
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: Some("agency".to_string()),
+///     telegram_bot_token: Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string()),
+///     telegram_chat_id: Some("@tweetr_mirror".to_string()),
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: Some("2016-09-10T12:00:00+02:00".to_string()),
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+///
+/// let mut out = Vec::new();
+/// list_users::print_users(&mut out, &[(&user, None)], None);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "User                App     Backends                               Added                      Tokens\n\
+///             nabijaczleweli#481  agency  Twitter, Telegram chat @tweetr_mirror  2016-09-10T12:00:00+02:00  not checked\n");
+///
+/// let mut out = Vec::new();
+/// list_users::print_users(&mut out, &[(&User { app: None, added: None, ..user.clone() }, Some(Outcome::NoError))], Some(70));
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "User                App      Backends                  Added    Tokens\n\
+///             nabijaczleweli#481  default  Twitter, Telegram chat …  unknown  valid\n");
+///
+/// let mut out = Vec::new();
+/// list_users::print_users(&mut out,
+///                         &[(&User { telegram_bot_token: None, suspended: Some("2016-09-11T12:00:00+02:00".to_string()), ..user }, None)],
+///                         None);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "User                App     Backends  Added                      Posting                                                      Tokens\n\
+///             nabijaczleweli#481  agency  Twitter   2016-09-10T12:00:00+02:00  paused since 2016-09-11T12:00:00+02:00, suspended or locked  \
+///                                                                                                                                not checked\n");
+/// ```
+pub fn print_users<W: Write>(output: &mut W, users: &[(&User, Option<Outcome>)], max_width: Option<usize>) {
+    // Only shown when there's something to show, since it's rare
+    let posting = users.iter().any(|&(u, _)| u.suspended.is_some());
+    let mut columns = vec![Column::new("User"), Column::new("App"), Column::new("Backends").shrinkable(), Column::new("Added")];
+    if posting {
+        columns.push(Column::new("Posting").shrinkable());
+    }
+    columns.push(Column::new("Tokens").shrinkable());
+
+    let mut table = Table::new(columns);
+    table.max_width = max_width;
+    for &(user, ref health) in users {
+        let mut row = user_row(user, health.as_ref());
+        if posting {
+            let paused = match user.suspended {
+                Some(ref suspended) => {
+                    format!("paused since {}, suspended or locked",
+                            DateTime::parse_from_rfc3339(suspended).map(|s| format_datetime(&s)).unwrap_or_else(|_| suspended.clone()))
+                }
+                None => "active".to_string(),
+            };
+            row.insert(4, paused);
+        }
+        table.row(row);
+    }
+    table.write(output);
+}
+
+
+fn user_row(user: &User, health: Option<&Outcome>) -> Vec<String> {
+    let backends: Vec<_> = Some("Twitter".to_string())
+        .into_iter()
+        .chain(user.telegram().map(|t| t.description()))
+        .chain(user.discord().map(|d| d.description()))
+        .collect();
+    let added = match user.added {
+        Some(ref added) => DateTime::parse_from_rfc3339(added).map(|a| format_datetime(&a)).unwrap_or_else(|_| added.clone()),
+        None => "unknown".to_string(),
+    };
+    let tokens = match health {
+        Some(&Outcome::NoError) => "valid".to_string(),
+        Some(out) => {
+            let mut error = vec![];
+            out.print_error(&mut error);
+            format!("invalid: {}", String::from_utf8_lossy(&error).trim().lines().map(str::trim).collect::<Vec<_>>().join(" "))
+        }
+        None => "not checked".to_string(),
+    };
+
+    vec![format!("{}#{}", user.name, user.id),
+         user.app.clone().unwrap_or_else(|| "default".to_string()),
+         backends.join(", "),
+         added,
+         tokens]
+}
@@ -0,0 +1,603 @@
+//! Files attached to tweets.
+//!
+//! Tweets loaded from a file can list URLs in their `media`, for example of images generated by a pipeline and hosted
+//! elsewhere; those are downloaded when queueing, into the `media` subdirectory of the configuration directory, named after
+//! the SHA-256 of their contents, so the same file is only stored once, and replaced with the path to the downloaded file.
+//!
+//! When posting, the files are uploaded to Twitter's media endpoint, and the tweet posted with the resulting media IDs.
+//!
+//! Videos and files larger than `MAX_SIMPLE_UPLOAD_SIZE` are uploaded in segments, with the progress recorded in
+//! `uploads.toml` in the configuration directory after each one, so an upload interrupted by, say, restarting the daemon is
+//! resumed where it stopped instead of started over, as long as Twitter still remembers it; then Twitter's processing of
+//! them is waited for, for at most `PROCESSING_TIMEOUT` seconds.
+//!
+//! The flow of the `gc-media` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::archive::read_all()
+//! |> ops::media::unused()
+//! |> std::fs::remove_file()
+//! ```
+//!
+//! The daemon does the same after posting, at most once every `GC_INTERVAL`, if a `media_retention` is configured.
+
+
+use self::super::{QueuedTweet, read_toml_file, replace_file};
+use self::super::super::Outcome;
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, UTC};
+use std::path::{Path, PathBuf};
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use std::fs::{self, Metadata};
+use std::time::UNIX_EPOCH;
+use toml::encode_str;
+#[cfg(feature = "network")]
+use self::super::{User, network, oauth, results};
+#[cfg(feature = "network")]
+use std::io::{Seek, SeekFrom};
+#[cfg(feature = "network")]
+use std::thread;
+#[cfg(feature = "network")]
+use std::time::Duration as StdDuration;
+#[cfg(feature = "network")]
+use rustc_serialize::base64::{self, ToBase64};
+#[cfg(feature = "network")]
+use rustc_serialize::json::Json;
+#[cfg(feature = "network")]
+use hyper::header::ContentType;
+#[cfg(feature = "network")]
+use std::collections::BTreeMap;
+#[cfg(feature = "network")]
+use std::io::{Read, Write};
+#[cfg(feature = "network")]
+use std::fs::File;
+#[cfg(feature = "network")]
+use egg_mode::Token;
+
+
+/// The API endpoint media are uploaded to.
+pub static MEDIA_UPLOAD_URL: &'static str = "https://upload.twitter.com/1.1/media/upload.json";
+
+/// The largest file to download, in bytes, the most Twitter accepts for an animated GIF.
+pub const MAX_MEDIA_SIZE: u64 = 15 * 1024 * 1024;
+
+/// How long, in seconds, downloaded media are kept for regardless of the tweets attached to them, so ones downloaded for
+/// tweets still being queued aren't removed.
+pub const GRACE_PERIOD: i64 = 60 * 60;
+
+/// How often, in seconds, the daemon removes unused media.
+pub const GC_INTERVAL: u64 = 60 * 60 * 24;
+
+/// The largest file to upload in one request, in bytes, the most Twitter accepts for an image; larger ones, and all videos,
+/// are uploaded in segments.
+pub const MAX_SIMPLE_UPLOAD_SIZE: u64 = 5 * 1024 * 1024;
+
+/// The size of each segment of an upload, in bytes.
+pub const SEGMENT_SIZE: u64 = 1024 * 1024;
+
+/// How long to wait for Twitter to finish processing an uploaded file, in seconds, at most.
+pub const PROCESSING_TIMEOUT: i64 = 10 * 60;
+
+
+/// An upload in segments, recorded so it can be resumed if interrupted.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Upload {
+    /// The file being uploaded.
+    pub path: String,
+    /// ID of the user the file's uploaded as, the only one who can resume it.
+    pub user_id: i64,
+    /// The file's size, in bytes, to notice it changing.
+    pub size: u64,
+    /// The media ID Twitter assigned to the file.
+    pub media_id: i64,
+    /// How many segments of `SEGMENT_SIZE` bytes were uploaded.
+    pub segments: u64,
+    /// When Twitter forgets the upload, in seconds since the epoch.
+    pub expires: i64,
+    /// Whether the whole file was uploaded, with only Twitter's processing of it remaining.
+    pub finalised: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+struct Uploads {
+    upload: Vec<Upload>,
+}
+
+
+/// Get the path to the directory downloaded media are stored in.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-media-dir-0");
+/// assert_eq!(media::dir(&tf), tf.join("media"));
+/// ```
+pub fn dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("media")
+}
+
+/// Check whether the specified media entry is a URL to download rather than a path.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// assert!(media::is_url("https://example.com/chart.png"));
+/// assert!(!media::is_url("/home/nabijaczleweli/chart.png"));
+/// ```
+pub fn is_url(entry: &str) -> bool {
+    entry.starts_with("http://") || entry.starts_with("https://")
+}
+
+/// Get the extension to store the file downloaded from the specified URL with, from the URL or, failing that, from the
+/// specified `Content-Type`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// assert_eq!(media::extension("https://example.com/chart.PNG?size=large", None), Some("png".to_string()));
+/// assert_eq!(media::extension("https://example.com/render?chart=3", Some("image/jpeg")), Some("jpg".to_string()));
+/// assert_eq!(media::extension("https://example.com/render?chart=3", Some("text/html")), None);
+/// ```
+pub fn extension(url: &str, content_type: Option<&str>) -> Option<String> {
+    let path = url.split(|c| c == '?' || c == '#').next().unwrap();
+    let fname = &path[path.rfind('/').map(|i| i + 1).unwrap_or(0)..];
+    if let Some(ext) = fname.rfind('.').map(|i| fname[i + 1..].to_lowercase()) {
+        if ["jpg", "jpeg", "png", "gif", "webp", "mp4"].contains(&&ext[..]) {
+            return Some(ext);
+        }
+    }
+
+    match content_type.map(|ct| ct.split(';').next().unwrap().trim()) {
+        Some("image/jpeg") => Some("jpg".to_string()),
+        Some("image/png") => Some("png".to_string()),
+        Some("image/gif") => Some("gif".to_string()),
+        Some("image/webp") => Some("webp".to_string()),
+        Some("video/mp4") => Some("mp4".to_string()),
+        _ => None,
+    }
+}
+
+/// Get the name to store the specified file with the specified extension as, the SHA-256 of its contents.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// assert_eq!(media::file_name(b"", "png"), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855.png");
+/// ```
+pub fn file_name(data: &[u8], extension: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(data);
+    format!("{}.{}", hasher.result_str(), extension)
+}
+
+/// Download the file at the specified URL into the media directory in the specified configuration directory, returning the
+/// path to it.
+///
+/// Files larger than `MAX_MEDIA_SIZE`, and ones that don't look like images or videos, aren't downloaded.
+#[cfg(feature = "network")]
+pub fn download(config_dir: &Path, url: &str) -> Result<PathBuf, String> {
+    let mut resp = try!(network::client().get(url).send().map_err(|e| format!("couldn't download {}: {}", url, e)));
+    if !resp.status.is_success() {
+        return Err(format!("downloading {} returned {}", url, resp.status));
+    }
+    let ext = try!(extension(url, resp.headers.get::<ContentType>().map(|ct| ct.to_string()).as_ref().map(|ct| &ct[..]))
+        .ok_or_else(|| format!("{} isn't an image or a video", url)));
+
+    let mut data = vec![];
+    try!(resp.by_ref().take(MAX_MEDIA_SIZE + 1).read_to_end(&mut data).map_err(|e| format!("couldn't download {}: {}", url, e)));
+    if data.len() as u64 > MAX_MEDIA_SIZE {
+        return Err(format!("{} is larger than {} MiB", url, MAX_MEDIA_SIZE / 1024 / 1024));
+    }
+
+    let media_dir = dir(config_dir);
+    try!(fs::create_dir_all(&media_dir).map_err(|e| format!("couldn't create {}: {}", media_dir.display(), e)));
+    let path = media_dir.join(file_name(&data, &ext));
+    // Rewritten even if already there, to restart the grace period
+    try!(File::create(&path).and_then(|mut f| f.write_all(&data)).map_err(|e| format!("couldn't write {}: {}", path.display(), e)));
+    results::wrote(&path);
+    Ok(path)
+}
+
+/// Download the media the specified tweet links to into the media directory in the specified configuration directory,
+/// replacing the links with the paths to the downloaded files.
+///
+/// Paths are left as-is.
+#[cfg(feature = "network")]
+pub fn localise(tweet: &mut QueuedTweet, config_dir: &Path) -> Result<(), String> {
+    for entry in &mut tweet.media {
+        if is_url(entry) {
+            *entry = try!(download(config_dir, entry)).display().to_string();
+        }
+    }
+    Ok(())
+}
+
+/// Get the downloaded media in the specified configuration directory no longer needed by the specified queued and archived
+/// tweets as of the specified time, and their sizes, in bytes.
+///
+/// A file is needed if it's attached to an unposted tweet or, with a retention, in days, to one posted within it, or,
+/// without one, to any tweet, or if it was downloaded within the last `GRACE_PERIOD`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{media, QueuedTweet};
+/// # use chrono::{DateTime, Duration, Local};
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// # use std::io::Write;
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-media-unused");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(media::dir(&tf)).unwrap();
+/// for fname in &["chart.png", "legend.png", "logo.png"] {
+///     File::create(media::dir(&tf).join(fname)).unwrap().write_all(b"PNG").unwrap();
+/// }
+///
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "This week's numbers".to_string(),
+///     media: vec![media::dir(&tf).join("chart.png").display().to_string()],
+///     ..Default::default()
+/// };
+/// let posted = QueuedTweet {
+///     media: vec![media::dir(&tf).join("legend.png").display().to_string()],
+///     time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()),
+///     id: Some(774541307568103424),
+///     ..tweet.clone()
+/// };
+///
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+/// assert!(media::unused(&tf, &[tweet.clone(), posted.clone()], None, now).is_empty());
+/// assert_eq!(media::unused(&tf, &[tweet.clone(), posted.clone()], None, now + Duration::days(1)),
+///            vec![(media::dir(&tf).join("logo.png"), 3)]);
+/// assert_eq!(media::unused(&tf, &[tweet.clone(), posted.clone()], Some(30), now + Duration::days(1)),
+///            vec![(media::dir(&tf).join("legend.png"), 3), (media::dir(&tf).join("logo.png"), 3)]);
+/// # }
+/// ```
+pub fn unused(config_dir: &Path, tweets: &[QueuedTweet], retention: Option<u32>, now: DateTime<FixedOffset>) -> Vec<(PathBuf, u64)> {
+    let mut files: Vec<_> = match fs::read_dir(dir(config_dir)) {
+        Ok(entries) => {
+            entries.filter_map(Result::ok)
+                .filter_map(|e| e.metadata().ok().map(|m| (e.path(), m)))
+                .filter(|&(_, ref m)| m.is_file())
+                .collect()
+        }
+        Err(_) => return vec![],
+    };
+    files.sort_by(|&(ref lhs, _), &(ref rhs, _)| lhs.cmp(rhs));
+
+    files.into_iter()
+        .filter(|&(ref path, ref meta)| {
+            let downloaded_recently = downloaded(meta).map(|d| now - d < Duration::seconds(GRACE_PERIOD)).unwrap_or(true);
+            let needed = tweets.iter()
+                .filter(|t| t.media.iter().any(|m| Path::new(m).file_name() == path.file_name()))
+                .any(|t| match (t.time_posted, retention) {
+                    (None, _) |
+                    (Some(_), None) => true,
+                    (Some(posted), Some(days)) => now - posted < Duration::days(days as i64),
+                });
+            !downloaded_recently && !needed
+        })
+        .map(|(path, meta)| (path, meta.len()))
+        .collect()
+}
+
+/// Upload the specified file to Twitter as the specified user of the specified app, returning its media ID.
+#[cfg(feature = "network")]
+pub fn upload(data: &[u8], app: &Token, access: &Token) -> Result<i64, Outcome> {
+    let mut params = BTreeMap::new();
+    params.insert("media_data".to_string(), data.to_base64(base64::STANDARD));
+
+    oauth::post(MEDIA_UPLOAD_URL, &params, app, access).and_then(|resp| media_id(&resp))
+}
+
+/// Get the MIME type of the media file at the specified path, by its extension.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// assert_eq!(media::media_type("/srv/charts/weekly.PNG"), Some("image/png"));
+/// assert_eq!(media::media_type("clip.mp4"), Some("video/mp4"));
+/// assert_eq!(media::media_type("notes.txt"), None);
+/// ```
+pub fn media_type(path: &str) -> Option<&'static str> {
+    match &Path::new(path).extension().and_then(|e| e.to_str()).map(str::to_lowercase).unwrap_or_default()[..] {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "mp4" => Some("video/mp4"),
+        "mov" => Some("video/quicktime"),
+        _ => None,
+    }
+}
+
+/// Check whether the media file at the specified path, of the specified size, needs to be uploaded in segments.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// assert!(!media::chunked("chart.png", 1024));
+/// assert!(media::chunked("chart.png", media::MAX_SIMPLE_UPLOAD_SIZE + 1));
+/// assert!(media::chunked("clip.mp4", 1024));
+/// ```
+pub fn chunked(path: &str, size: u64) -> bool {
+    size > MAX_SIMPLE_UPLOAD_SIZE || media_type(path).map(|t| t.starts_with("video/")).unwrap_or(false)
+}
+
+/// Get the path to the file recording the uploads in progress in the specified configuration directory.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-media-uploads_path");
+/// assert_eq!(media::uploads_path(&tf), tf.join("uploads.toml"));
+/// ```
+pub fn uploads_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("uploads.toml")
+}
+
+/// Read the uploads in progress from the specified file, none if it doesn't exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media::{self, Upload};
+/// # use std::env::temp_dir;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-media-read_uploads");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = media::uploads_path(&tf);
+///
+/// assert_eq!(media::read_uploads(&tf), Ok(vec![]));
+///
+/// let uploads = vec![Upload {
+///                        path: "/srv/videos/weekly.mp4".to_string(),
+///                        user_id: 481,
+///                        size: 52428800,
+///                        media_id: 710511363345354753,
+///                        segments: 17,
+///                        expires: 1473591600,
+///                        finalised: false,
+///                    }];
+/// media::write_uploads(&uploads, &tf).unwrap();
+/// assert_eq!(media::read_uploads(&tf), Ok(uploads));
+/// ```
+pub fn read_uploads(p: &Path) -> Result<Vec<Upload>, Outcome> {
+    if !p.exists() {
+        return Ok(vec![]);
+    }
+
+    match read_toml_file::<Uploads>(p, "media uploads") {
+        Ok(uploads) => Ok(uploads.upload),
+        Err(Some(out)) => Err(out),
+        Err(None) => {
+            Err(Outcome::FileParsingFailed {
+                desc: "media uploads",
+                errors: vec!["error: couldn't read the file".to_string()],
+            })
+        }
+    }
+}
+
+/// Save the uploads in progress to the specified file.
+///
+/// The file is replaced at once, so it's never seen partially written.
+pub fn write_uploads(uploads: &[Upload], p: &Path) -> Result<(), Outcome> {
+    replace_file(p, &encode_str(&Uploads { upload: uploads.to_vec() }))
+}
+
+/// Find the upload of the specified file of the specified size as the specified user that can be resumed at the specified
+/// time, in seconds since the epoch.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::media::{self, Upload};
+/// let uploads = vec![Upload {
+///                        path: "/srv/videos/weekly.mp4".to_string(),
+///                        user_id: 481,
+///                        size: 52428800,
+///                        media_id: 710511363345354753,
+///                        segments: 17,
+///                        expires: 1473591600,
+///                        finalised: false,
+///                    }];
+///
+/// assert_eq!(media::resumable(&uploads, "/srv/videos/weekly.mp4", 481, 52428800, 1473505200), Some(0));
+/// // Expired
+/// assert_eq!(media::resumable(&uploads, "/srv/videos/weekly.mp4", 481, 52428800, 1473591600), None);
+/// // Changed since
+/// assert_eq!(media::resumable(&uploads, "/srv/videos/weekly.mp4", 481, 41943040, 1473505200), None);
+/// // Someone else's
+/// assert_eq!(media::resumable(&uploads, "/srv/videos/weekly.mp4", 482, 52428800, 1473505200), None);
+/// ```
+pub fn resumable(uploads: &[Upload], path: &str, user_id: i64, size: u64, now: i64) -> Option<usize> {
+    uploads.iter().position(|u| u.path == path && u.user_id == user_id && u.size == size && u.expires > now)
+}
+
+/// Upload the file at the specified path to Twitter as the specified user of the specified app, returning its media ID.
+///
+/// Files that need to be uploaded in segments (see `chunked()`) have their progress recorded in the specified file, if any,
+/// and an upload recorded there is resumed, see `resumable()`; segments are sent, and Twitter's processing polled for,
+/// each in its own request, see `network::request()`.
+///
+/// Fails with `Outcome::NetworkTimeout` if Twitter doesn't finish processing the file in `PROCESSING_TIMEOUT` seconds, in
+/// which case uploading it again only waits for the processing.
+#[cfg(feature = "network")]
+pub fn upload_file(path: &str, progress: Option<&Path>, user: &User, app: &Token) -> Result<i64, Outcome> {
+    let size = try!(fs::metadata(path).map_err(|e| Outcome::from_io("read", Path::new(path), e))).len();
+    let app = network::owned_token(app);
+    let desc = format!("uploading {} as @{}", path, user.name);
+
+    if !chunked(path, size) {
+        let mut data = vec![];
+        try!(File::open(path).and_then(|mut f| f.read_to_end(&mut data)).map_err(|e| Outcome::from_io("read", Path::new(path), e)));
+        let user = user.clone();
+        return network::request(&desc, move || upload(&data, &app, &user.raw_token()));
+    }
+
+    let mut uploads = match progress {
+        Some(p) => try!(read_uploads(p)),
+        None => vec![],
+    };
+    let mut state = match resumable(&uploads, path, user.id, size, UTC::now().timestamp()) {
+        Some(i) => uploads.remove(i),
+        None => {
+            let media_type = media_type(path).unwrap_or("application/octet-stream");
+            let resp = try!(command(&desc,
+                                    false,
+                                    vec![("command", "INIT".to_string()),
+                                         ("total_bytes", size.to_string()),
+                                         ("media_type", media_type.to_string()),
+                                         ("media_category", media_category(media_type).to_string())],
+                                    user,
+                                    &app));
+            Upload {
+                path: path.to_string(),
+                user_id: user.id,
+                size: size,
+                media_id: try!(media_id(&resp)),
+                segments: 0,
+                expires: UTC::now().timestamp() + resp.find("expires_after_secs").and_then(Json::as_i64).unwrap_or(24 * 60 * 60),
+                finalised: false,
+            }
+        }
+    };
+    uploads.retain(|u| !(u.path == path && u.user_id == user.id));
+    try!(record(progress, &uploads, Some(&state)));
+
+    let mut processing = if state.finalised {
+        try!(command(&desc,
+                     true,
+                     vec![("command", "STATUS".to_string()), ("media_id", state.media_id.to_string())],
+                     user,
+                     &app))
+            .find("processing_info")
+            .cloned()
+    } else {
+        let mut file = try!(File::open(path).map_err(|e| Outcome::from_io("read", Path::new(path), e)));
+        try!(file.seek(SeekFrom::Start(state.segments * SEGMENT_SIZE)).map_err(|e| Outcome::from_io("read", Path::new(path), e)));
+        loop {
+            let mut segment = vec![];
+            try!((&mut file).take(SEGMENT_SIZE).read_to_end(&mut segment).map_err(|e| Outcome::from_io("read", Path::new(path), e)));
+            if segment.is_empty() {
+                break;
+            }
+
+            try!(command(&desc,
+                         false,
+                         vec![("command", "APPEND".to_string()),
+                              ("media_id", state.media_id.to_string()),
+                              ("segment_index", state.segments.to_string()),
+                              ("media_data", segment.to_base64(base64::STANDARD))],
+                         user,
+                         &app));
+            state.segments += 1;
+            try!(record(progress, &uploads, Some(&state)));
+        }
+
+        let resp = try!(command(&desc,
+                                false,
+                                vec![("command", "FINALIZE".to_string()), ("media_id", state.media_id.to_string())],
+                                user,
+                                &app));
+        state.finalised = true;
+        try!(record(progress, &uploads, Some(&state)));
+        resp.find("processing_info").cloned()
+    };
+
+    let deadline = UTC::now().timestamp() + PROCESSING_TIMEOUT;
+    while let Some(info) = processing {
+        match info.find("state").and_then(Json::as_string) {
+            Some("failed") => {
+                // The media ID is unusable, so it's uploaded anew the next time
+                try!(record(progress, &uploads, None));
+                return Err(Outcome::TwitterAPIError(format!("processing {} failed: {}",
+                                                            path,
+                                                            info.find_path(&["error", "message"]).and_then(Json::as_string).unwrap_or("unknown error"))));
+            }
+            Some("pending") | Some("in_progress") => {
+                let wait = info.find("check_after_secs").and_then(Json::as_i64).unwrap_or(5);
+                if UTC::now().timestamp() + wait > deadline {
+                    return Err(Outcome::NetworkTimeout { desc: format!("waiting for Twitter to process {}", path) });
+                }
+                thread::sleep(StdDuration::from_secs(wait as u64));
+
+                processing = try!(command(&desc,
+                                          true,
+                                          vec![("command", "STATUS".to_string()), ("media_id", state.media_id.to_string())],
+                                          user,
+                                          &app))
+                    .find("processing_info")
+                    .cloned();
+            }
+            _ => processing = None,
+        }
+    }
+
+    try!(record(progress, &uploads, None));
+    Ok(state.media_id)
+}
+
+
+#[cfg(feature = "network")]
+fn media_id(resp: &Json) -> Result<i64, Outcome> {
+    resp.find("media_id_string")
+        .and_then(Json::as_string)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| Outcome::TwitterAPIError(format!("invalid response: {}", resp)))
+}
+
+#[cfg(feature = "network")]
+fn media_category(media_type: &str) -> &'static str {
+    match media_type {
+        "image/gif" => "tweet_gif",
+        t if t.starts_with("video/") => "tweet_video",
+        _ => "tweet_image",
+    }
+}
+
+/// Send the specified command of an upload in segments, in a request of its own, `STATUS` ones with `GET`.
+#[cfg(feature = "network")]
+fn command(desc: &str, get: bool, params: Vec<(&str, String)>, user: &User, app: &Token) -> Result<Json, Outcome> {
+    let params: BTreeMap<_, _> = params.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+    let user = user.clone();
+    let app = network::owned_token(app);
+    network::request(desc, move || if get {
+        oauth::get(MEDIA_UPLOAD_URL, &params, &app, &user.raw_token())
+    } else {
+        oauth::post(MEDIA_UPLOAD_URL, &params, &app, &user.raw_token())
+    })
+}
+
+/// Save the other uploads along with the specified one, if any, to the specified file, if any.
+#[cfg(feature = "network")]
+fn record(progress: Option<&Path>, uploads: &[Upload], state: Option<&Upload>) -> Result<(), Outcome> {
+    match progress {
+        Some(p) => write_uploads(&uploads.iter().chain(state).cloned().collect::<Vec<_>>(), p),
+        None => Ok(()),
+    }
+}
+
+fn downloaded(meta: &Metadata) -> Option<DateTime<UTC>> {
+    meta.modified().ok().and_then(|m| m.duration_since(UNIX_EPOCH).ok()).map(|d| UTC.timestamp(d.as_secs() as i64, d.subsec_nanos()))
+}
@@ -3,25 +3,92 @@
 //! Each module contains the functions for their respective subsystems.
 
 
-use toml::{Parser, Value, decode};
+use toml::{DecodeErrorKind, DecodeError, Decoder, Parser, Table, Value};
+use std::collections::BTreeMap;
 use rustc_serialize::Decodable;
 use std::path::{PathBuf, Path};
+use self::super::util::encoding::{self, Encoding};
+use self::super::util::interpolate_env;
+use self::setup_state::SetupState;
 use self::super::Outcome;
-use std::fs::File;
-use std::io::Read;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::fs::{self, File};
 
 mod user;
 mod token;
+mod config;
 mod queued_tweet;
+mod template;
 
 pub mod init;
+pub mod hooks;
+pub mod archive;
+pub mod clock;
+#[cfg(feature = "network")]
+pub mod oauth;
+pub mod backend;
+pub mod media;
+pub mod settings;
+pub mod schedule;
+pub mod transform;
+pub mod calendar;
+pub mod fmt_queue;
+pub mod list_templates;
+#[cfg(feature = "network")]
+pub mod setup;
+pub mod tui;
+pub mod preflight;
+#[cfg(feature = "network")]
+pub mod doctor;
+pub mod validate;
+#[cfg(feature = "network")]
 pub mod add_user;
+pub mod rotate_token;
+pub mod list_users;
+pub mod remove_user;
 pub mod queue_tweet;
+pub mod quick_queue;
+pub mod post_now;
+pub mod release_announce;
 pub mod start_daemon;
+pub mod post_all;
+pub mod prune_posted;
+pub mod reconcile;
+pub mod events;
+pub mod notify;
+pub mod suggest_time;
+pub mod evergreen;
+pub mod embargo;
+pub mod daily_cap;
+pub mod lint;
+pub mod show_effective_config;
+pub mod export_posted;
+pub mod schema;
+pub mod shift_queue;
+pub mod requeue_thread;
+pub mod journal;
+pub mod report;
+pub mod results;
+pub mod transcript;
+pub mod diagnostics;
+pub mod setup_state;
+pub mod simulate;
+pub mod recovery;
+pub mod latency;
+#[cfg(feature = "network")]
+pub mod update;
+pub mod l10n;
+pub mod network;
+pub mod http_cache;
+pub mod retry;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use self::user::User;
-pub use self::token::AppTokens;
-pub use self::queued_tweet::QueuedTweet;
+pub use self::token::{AppTokens, Apps};
+pub use self::config::Config;
+pub use self::template::Template;
+pub use self::queued_tweet::{QueuedTweet, Anchor, Coordinates, RecoveredQueue, ReplySettings};
 
 
 fn verify_file(fname: &str, should_exist: bool, config_dir: &(String, PathBuf), force: bool, producing_subsystem: &'static str) -> Result<PathBuf, Outcome> {
@@ -33,9 +100,14 @@ fn verify_file(fname: &str, should_exist: bool, config_dir: &(String, PathBuf),
         let filename = PathBuf::from(&config_dir.0).join(fname).to_str().unwrap().replace("\\", "/");
 
         if should_exist {
-            Err(Outcome::RequiredFileFromSubsystemNonexistant {
-                subsys: producing_subsystem,
-                fname: filename,
+            Err(match SetupState::detect(&config_dir.1) {
+                SetupState::Complete => {
+                    Outcome::RequiredFileFromSubsystemNonexistant {
+                        subsys: producing_subsystem,
+                        fname: filename,
+                    }
+                }
+                state => Outcome::SetupIncomplete(state),
             })
         } else {
             Err(Outcome::OverrideNoForce(filename))
@@ -44,21 +116,301 @@ fn verify_file(fname: &str, should_exist: bool, config_dir: &(String, PathBuf),
 }
 
 fn read_toml_file<T: Decodable>(p: &Path, desc: &'static str) -> Result<T, Option<Outcome>> {
-    let mut buf = String::new();
-    try!(try!(File::open(p).map_err(|_| None)).read_to_string(&mut buf).map_err(|_| None));
+    let (buf, table) = try!(parse_toml_file(p, desc));
 
-    let mut parser = Parser::new(&buf);
-    let parsed = parser.parse().and_then(|t| decode(Value::Table(t)));
-    parsed.ok_or_else(move || {
+    decode_toml(&buf, &table, "").map_err(|e| {
         Some(Outcome::FileParsingFailed {
             desc: desc,
-            errors: parser.errors
+            errors: vec![e],
+        })
+    })
+}
+
+/// Read the specified file like `read_toml_file()`, but with the environment variable references in its strings replaced
+/// first, see `util::interpolate_env()`, so secrets needn't be stored in it.
+///
+/// Files that get written back need to be read with `read_toml_file()` instead, so the references aren't replaced with the
+/// secrets on disk.
+fn read_interpolated_toml_file<T: Decodable>(p: &Path, desc: &'static str) -> Result<T, Option<Outcome>> {
+    let (buf, mut table) = try!(parse_toml_file(p, desc));
+
+    let errors = interpolate_table(&buf, &mut table, "");
+    if !errors.is_empty() {
+        return Err(Some(Outcome::FileParsingFailed {
+            desc: desc,
+            errors: errors,
+        }));
+    }
+
+    decode_toml(&buf, &table, "").map_err(|e| {
+        Some(Outcome::FileParsingFailed {
+            desc: desc,
+            errors: vec![e],
+        })
+    })
+}
+
+/// Replace the environment variable references in all strings in the specified table, found under the specified key
+/// prefix in the specified file contents, returning the error messages pointing at the keys they couldn't be replaced in.
+fn interpolate_table(buf: &str, table: &mut Table, prefix: &str) -> Vec<String> {
+    let mut errors = vec![];
+    for (key, value) in table.iter_mut() {
+        interpolate_value(buf, value, &format!("{}{}", prefix, key), &mut errors);
+    }
+    errors
+}
+
+fn interpolate_value(buf: &str, value: &mut Value, key: &str, errors: &mut Vec<String>) {
+    match *value {
+        Value::String(ref mut s) => {
+            match interpolate_env(s) {
+                Ok(interpolated) => *s = interpolated,
+                Err(e) => errors.push(key_error_message(buf, Some(key), &format!("{} for the key `{}`", e, key))),
+            }
+        }
+        Value::Array(ref mut values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                // Only the elements of arrays of tables are located by index
+                let key = match *value {
+                    Value::Table(_) => format!("{}.{}", key, i),
+                    _ => key.to_string(),
+                };
+                interpolate_value(buf, value, &key, errors);
+            }
+        }
+        Value::Table(ref mut table) => errors.extend(interpolate_table(buf, table, &format!("{}.", key))),
+        _ => (),
+    }
+}
+
+/// Read the specified file into a TOML table, also returning its contents to locate errors in.
+///
+/// Fails with `None` if the file doesn't exist.
+fn parse_toml_file(p: &Path, desc: &'static str) -> Result<(String, Table), Option<Outcome>> {
+    let mut file = try!(retry::run(|| File::open(p)).map_err(|e| if e.kind() == ErrorKind::NotFound {
+        None
+    } else {
+        Some(Outcome::from_io("open", p, e))
+    }));
+    let buf = try!(retry::run(|| {
+            let mut buf = String::new();
+            try!(file.seek(SeekFrom::Start(0)));
+            file.read_to_string(&mut buf).map(|_| buf)
+        })
+        .map_err(|e| Some(Outcome::from_io("read", p, e))));
+
+    parse_toml(buf, desc).map_err(Some)
+}
+
+/// Read the specified imported file, converting it from the specified encoding, or the detected one, if none.
+///
+/// Fails with `None` if the file doesn't exist,
+/// or with an `Outcome::FileParsingFailed` naming the byte offset of each invalid sequence in it.
+fn read_imported_file(p: &Path, desc: &'static str, encoding: Option<Encoding>) -> Result<String, Option<Outcome>> {
+    let mut file = try!(retry::run(|| File::open(p)).map_err(|e| if e.kind() == ErrorKind::NotFound {
+        None
+    } else {
+        Some(Outcome::from_io("open", p, e))
+    }));
+    let bytes = try!(retry::run(|| {
+            let mut bytes = vec![];
+            try!(file.seek(SeekFrom::Start(0)));
+            file.read_to_end(&mut bytes).map(|_| bytes)
+        })
+        .map_err(|e| Some(Outcome::from_io("read", p, e))));
+
+    encoding::decode(&bytes, encoding).map(|(buf, _)| buf).map_err(|invalid| {
+        Some(Outcome::FileParsingFailed {
+            desc: desc,
+            errors: invalid.iter().map(|i| i.to_string()).collect(),
+        })
+    })
+}
+
+/// Parse the specified file contents into a TOML table, also returning them to locate errors in.
+fn parse_toml(buf: String, desc: &'static str) -> Result<(String, Table), Outcome> {
+    let parsed = {
+        let mut parser = Parser::new(&buf);
+        parser.parse().ok_or_else(|| {
+            parser.errors
                 .iter()
                 .map(|e| {
                     let (line, col) = parser.to_linecol(e.lo);
-                    format!("error: {}:{}: {}", line, col, e.desc)
+                    format!("error: {}:{}: {}", line + 1, col + 1, e.desc)
                 })
-                .collect(),
+                .collect()
         })
+    };
+
+    match parsed {
+        Ok(table) => Ok((buf, table)),
+        Err(errors) => {
+            Err(Outcome::FileParsingFailed {
+                desc: desc,
+                errors: errors,
+            })
+        }
+    }
+}
+
+/// Write the specified contents to the specified file, replacing it.
+fn write_file(p: &Path, contents: &str) -> Result<(), Outcome> {
+    try!(write_contents(p, contents));
+    results::wrote(p);
+    Ok(())
+}
+
+/// Write the specified contents to the specified file, without noting it for the results, see `results::wrote()`.
+fn write_contents(p: &Path, contents: &str) -> Result<(), Outcome> {
+    let mut file = try!(retry::run(|| File::create(p)).map_err(|e| Outcome::from_io("create", p, e)));
+    retry::run(|| file.seek(SeekFrom::Start(0)).and_then(|_| file.set_len(0)).and_then(|_| file.write_all(contents.as_bytes())))
+        .map_err(|e| Outcome::from_io("write", p, e))
+}
+
+/// Write the specified contents next to the specified file first and then move them over it, so it's never read partially
+/// written.
+fn replace_file(p: &Path, contents: &str) -> Result<(), Outcome> {
+    let new = p.with_file_name(format!(".{}.new", p.file_name().unwrap().to_string_lossy()));
+    try!(write_contents(&new, contents));
+    try!(retry::run(|| fs::rename(&new, p)).map_err(|e| Outcome::from_io("replace", p, e)));
+    results::wrote(p);
+    Ok(())
+}
+
+/// Decode the specified table, found under the specified key prefix (like `"tweet.3."`) in the specified file contents,
+/// into the error message pointing at the key, if it fails.
+fn decode_toml<T: Decodable>(buf: &str, table: &Table, prefix: &str) -> Result<T, String> {
+    T::decode(&mut Decoder::new(Value::Table(table.clone()))).map_err(|e| {
+        let key = e.field
+            .as_ref()
+            .and_then(|f| index_key(table, &f.split('.').collect::<Vec<_>>(), &e.kind))
+            .or(e.field)
+            .map(|k| format!("{}{}", prefix, k))
+            .or_else(|| if prefix.is_empty() {
+                None
+            } else {
+                Some(prefix.trim_right_matches('.').to_string())
+            });
+        let message = DecodeError {
+                field: key.clone(),
+                kind: e.kind,
+            }
+            .to_string();
+
+        key_error_message(buf, key.as_ref().map(|k| &k[..]), &message)
+    })
+}
+
+/// Produce an `Outcome::FileParsingFailed` for the value under the specified key in the specified file,
+/// for errors found after the file was successfully decoded.
+fn key_error(p: &Path, desc: &'static str, key: &str, message: &str) -> Outcome {
+    // Imported files needn't be UTF-8, so locate the key in them as they were decoded
+    let mut bytes = vec![];
+    let _ = File::open(p).and_then(|mut f| f.read_to_end(&mut bytes));
+    let buf = encoding::decode_lossy(&bytes, None).0;
+
+    Outcome::FileParsingFailed {
+        desc: desc,
+        errors: vec![key_error_message(&buf, Some(key), &format!("{} for the key `{}`", message, key))],
+    }
+}
+
+fn key_error_message(buf: &str, key: Option<&str>, message: &str) -> String {
+    match key.and_then(|k| key_linecol(buf, k)) {
+        Some((line, col)) => format!("error: {}:{}: {}", line + 1, col + 1, message),
+        None => format!("error: {}", message),
+    }
+}
+
+/// The decoder doesn't include array indices in the key it reports, so find the first array element the error applies to,
+/// the same way the decoder would, and add them.
+fn index_key(table: &Table, path: &[&str], kind: &DecodeErrorKind) -> Option<String> {
+    let val = table.get(path[0]);
+    if path.len() == 1 {
+        let faulty = match *kind {
+            DecodeErrorKind::ExpectedField(_) => val.is_none(),
+            DecodeErrorKind::ExpectedType(expected, _) => val.map(|v| v.type_str() != expected).unwrap_or(false),
+            _ => true,
+        };
+
+        if faulty { Some(path[0].to_string()) } else { None }
+    } else {
+        match val {
+            Some(&Value::Table(ref t)) => index_key(t, &path[1..], kind).map(|k| format!("{}.{}", path[0], k)),
+            Some(&Value::Array(ref a)) => {
+                a.iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| match *v {
+                        Value::Table(ref t) => index_key(t, &path[1..], kind).map(|k| format!("{}.{}.{}", path[0], i, k)),
+                        _ => None,
+                    })
+                    .next()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Find the 0-based line and column of the specified (indexed) key, or of its section if the key itself is missing.
+///
+/// The decoder doesn't say where values came from, so this scans the lines of the file, and only finds bare keys
+/// (`name = ...` lines) in sections with bare names (`[section]` and `[[section]]` lines), skipping multi-line strings.
+/// Quoted keys, keys in inline tables and keys in sections nested in arrays of tables aren't found, and errors for them
+/// have no position, rather than a wrong one.
+fn key_linecol(buf: &str, key: &str) -> Option<(usize, usize)> {
+    let (section, name) = match key.rfind('.') {
+        Some(i) => (&key[..i], &key[i + 1..]),
+        None => ("", key),
+    };
+
+    let mut array_counts = BTreeMap::new();
+    let mut cur_section = Some(String::new());
+    let mut section_pos = None;
+    let mut multiline = None;
+    for (i, line) in buf.lines().enumerate() {
+        if let Some(delim) = multiline {
+            if line.contains(delim) {
+                multiline = None;
+            }
+            continue;
+        }
+
+        let col = line.len() - line.trim_left().len();
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            let header = line.trim_left_matches('[').split(']').next().unwrap().trim();
+            let parts: Vec<_> = header.split('.').map(str::trim).collect();
+            let nested = (1..parts.len()).any(|n| array_counts.contains_key(&parts[..n].join(".")));
+            cur_section = if !parts.iter().all(|p| is_bare_key(p)) || nested {
+                None
+            } else if line.starts_with("[[") {
+                let count = array_counts.entry(parts.join(".")).or_insert(0);
+                *count += 1;
+                Some(format!("{}.{}", parts.join("."), *count - 1))
+            } else {
+                Some(parts.join("."))
+            };
+
+            if cur_section.as_ref().map(|s| &s[..]) == Some(section) {
+                section_pos = Some((i, col));
+            }
+        } else if let Some(eq) = line.find('=') {
+            if cur_section.as_ref().map(|s| &s[..]) == Some(section) && line[..eq].trim() == name && is_bare_key(name) {
+                return Some((i, col));
+            }
+
+            let value = line[eq + 1..].trim_left();
+            multiline = ["\"\"\"", "'''"].iter().find(|&&d| value.starts_with(d) && !value[3..].contains(d)).cloned();
+        }
+    }
+    section_pos
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() &&
+    key.chars().all(|c| match c {
+        'A'...'Z' | 'a'...'z' | '0'...'9' | '_' | '-' => true,
+        _ => false,
     })
 }
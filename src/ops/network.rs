@@ -0,0 +1,733 @@
+//! Timeouts for all network requests.
+//!
+//! Requests made directly give up connecting, reading or writing after the respective timeouts, and report it as
+//! `Outcome::NetworkTimeout`. The ones made through `egg_mode` can't time out by themselves, so requests that could stall
+//! the caller are also run with `run()`, which gives up waiting for them after all three timeouts have passed.
+//!
+//! A request given up on that way is still carried on in the background, though, so requests that mustn't be sent twice,
+//! like posting, are made directly, and run with `request_blocking()` instead, on the current thread. One that times out
+//! might still have been carried out by the server, so a tweet whose posting timed out is treated like one that was being
+//! posted when the daemon stopped, see `ops::recovery::hold_timed_out()`.
+//!
+//! The timeouts in use are set per thread, and carried over to the threads `run()` starts.
+//!
+//! So is the local address to connect from, if any (see `use_bind_address()`), for multi-homed hosts where only one of
+//! the addresses is allowed out. `egg_mode` always connects from the default one, so the requests it'd make are sent with
+//! `ops::oauth` instead while one is in use.
+//!
+//! API requests hitting the rate limits fail with `Outcome::RateLimited`, and are run with `request()`, which waits for the
+//! limits to reset and retries them, if that's soon enough.
+
+
+use self::super::super::Outcome;
+#[cfg(feature = "network")]
+use self::super::oauth;
+use std::sync::mpsc::{RecvTimeoutError, channel};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::net::IpAddr;
+#[cfg(feature = "network")]
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(feature = "network")]
+use egg_mode::error::Error as EggModeError;
+#[cfg(feature = "network")]
+use egg_mode::Token;
+#[cfg(feature = "network")]
+use hyper::status::StatusCode;
+#[cfg(feature = "network")]
+use hyper::header::Headers;
+#[cfg(feature = "network")]
+use chrono::DateTime;
+#[cfg(feature = "network")]
+use hyper::Client;
+#[cfg(feature = "network")]
+use hyper::net::{HttpStream, HttpsConnector, NetworkConnector, OpensslClient};
+use std::{cmp, str};
+use std::thread;
+#[cfg(feature = "network")]
+use hyper;
+use std::io;
+
+
+/// How many times `request()` retries a rate limited request, at most.
+pub static RATE_LIMIT_RETRIES: u32 = 3;
+
+/// How long `request()` waits for the rate limits to reset, at most, in seconds.
+///
+/// This is the length of the Twitter API's rate limit windows, so the limits reset within it unless the daily ones were hit.
+pub static MAX_RATE_LIMIT_WAIT: i64 = 15 * 60;
+
+
+/// How long to wait for each stage of a request, or `None` to wait indefinitely.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Timeouts {
+    /// How long to wait for connecting to a server. Default: 10s
+    pub connect: Option<Duration>,
+    /// How long to wait for a server to respond. Default: 30s
+    pub read: Option<Duration>,
+    /// How long to wait for a server to accept a request. Default: 30s
+    pub write: Option<Duration>,
+}
+
+thread_local! {
+    static TIMEOUTS: Cell<Timeouts> = Cell::new(Timeouts::default());
+    static BIND_ADDRESS: Cell<Option<IpAddr>> = Cell::new(None);
+}
+
+
+impl Timeouts {
+    /// Get how long a whole request can take, or `None` if indefinitely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::network::Timeouts;
+    /// # use std::time::Duration;
+    /// assert_eq!(Timeouts::default().total(), Some(Duration::from_secs(70)));
+    /// assert_eq!(Timeouts { read: None, ..Timeouts::default() }.total(), None);
+    /// ```
+    pub fn total(&self) -> Option<Duration> {
+        match (self.connect, self.read, self.write) {
+            (Some(connect), Some(read), Some(write)) => Some(connect + read + write),
+            _ => None,
+        }
+    }
+
+    /// Override the timeouts with the specified ones, in seconds, if any, `0` meaning waiting indefinitely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::network::Timeouts;
+    /// # use std::time::Duration;
+    /// assert_eq!(Timeouts::default().overridden(Some(5), None, Some(0)),
+    ///            Timeouts {
+    ///                connect: Some(Duration::from_secs(5)),
+    ///                read: Some(Duration::from_secs(30)),
+    ///                write: None,
+    ///            });
+    /// ```
+    pub fn overridden(&self, connect: Option<u64>, read: Option<u64>, write: Option<u64>) -> Timeouts {
+        Timeouts {
+            connect: connect.map(seconds).unwrap_or(self.connect),
+            read: read.map(seconds).unwrap_or(self.read),
+            write: write.map(seconds).unwrap_or(self.write),
+        }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Timeouts {
+        Timeouts {
+            connect: Some(Duration::from_secs(10)),
+            read: Some(Duration::from_secs(30)),
+            write: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+
+/// Use the specified timeouts for all requests subsequently made on the current thread.
+pub fn use_timeouts(timeouts: Timeouts) {
+    TIMEOUTS.with(|t| t.set(timeouts));
+}
+
+/// Get the timeouts in use on the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network::{self, Timeouts};
+/// assert_eq!(network::timeouts(), Timeouts::default());
+///
+/// network::use_timeouts(Timeouts { connect: None, ..Timeouts::default() });
+/// assert_eq!(network::timeouts().connect, None);
+/// ```
+pub fn timeouts() -> Timeouts {
+    TIMEOUTS.with(|t| t.get())
+}
+
+/// Connect all requests subsequently made on the current thread from the specified local address, or the default one.
+pub fn use_bind_address(addr: Option<IpAddr>) {
+    BIND_ADDRESS.with(|a| a.set(addr));
+}
+
+/// Get the local address requests on the current thread connect from, if not the default one.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network;
+/// # use std::net::IpAddr;
+/// # use std::str::FromStr;
+/// assert_eq!(network::bind_address(), None);
+///
+/// network::use_bind_address(Some(IpAddr::from_str("192.0.2.7").unwrap()));
+/// assert_eq!(network::bind_address(), Some(IpAddr::from_str("192.0.2.7").unwrap()));
+/// ```
+pub fn bind_address() -> Option<IpAddr> {
+    BIND_ADDRESS.with(|a| a.get())
+}
+
+/// Find the local address specified as either an IP address or, on Unix, the name of a network interface.
+///
+/// An interface's IPv4 address is preferred to its IPv6 ones.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network;
+/// # use std::net::IpAddr;
+/// # use std::str::FromStr;
+/// assert_eq!(network::resolve_bind_address("192.0.2.7"), Ok(IpAddr::from_str("192.0.2.7").unwrap()));
+/// assert_eq!(network::resolve_bind_address("2001:db8::7"), Ok(IpAddr::from_str("2001:db8::7").unwrap()));
+/// assert_eq!(network::resolve_bind_address("not an interface"),
+///            Err("no such IP address or network interface \"not an interface\"".to_string()));
+/// ```
+pub fn resolve_bind_address(spec: &str) -> Result<IpAddr, String> {
+    spec.parse()
+        .ok()
+        .or_else(|| interface_address(spec))
+        .ok_or_else(|| format!("no such IP address or network interface \"{}\"", spec))
+}
+
+/// Make an HTTP client with the timeouts and the local address in use.
+#[cfg(feature = "network")]
+pub fn client() -> Client {
+    let timeouts = timeouts();
+
+    let connector = Connector {
+        bind: bind_address(),
+        timeout: timeouts.connect,
+    };
+    let mut client = Client::with_connector(HttpsConnector::with_connector(OpensslClient::default(), connector));
+    client.set_read_timeout(timeouts.read);
+    client.set_write_timeout(timeouts.write);
+    client
+}
+
+/// Check whether the specified I/O error is a read or write timing out.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network;
+/// # use std::io::{Error, ErrorKind};
+/// assert!(network::timed_out(&Error::new(ErrorKind::TimedOut, "")));
+/// assert!(!network::timed_out(&Error::new(ErrorKind::ConnectionRefused, "")));
+/// ```
+pub fn timed_out(e: &io::Error) -> bool {
+    // Timed out socket operations fail with WouldBlock on some platforms
+    e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock
+}
+
+/// Turn the specified error in the specified request into `Outcome::NetworkTimeout` if it's a timeout, or with the specified
+/// function otherwise.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate hyper;
+/// # use tweetr::ops::network;
+/// # use std::io::{Error, ErrorKind};
+/// # use tweetr::Outcome;
+/// # fn main() {
+/// assert_eq!(network::request_error("sending to Telegram",
+///                                   hyper::Error::Io(Error::new(ErrorKind::TimedOut, "")),
+///                                   |e| Outcome::BackendError { backend: "Telegram".to_string(), error: e }),
+///            Outcome::NetworkTimeout { desc: "sending to Telegram".to_string() });
+/// # }
+/// ```
+#[cfg(feature = "network")]
+pub fn request_error<F: FnOnce(String) -> Outcome>(desc: &str, e: hyper::Error, other: F) -> Outcome {
+    match e {
+        hyper::Error::Io(e) => io_error(desc, e, other),
+        e => other(e.to_string()),
+    }
+}
+
+/// Turn the specified error reading a response to the specified request into `Outcome::NetworkTimeout` if it's a timeout,
+/// or with the specified function otherwise.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network;
+/// # use std::io::{Error, ErrorKind};
+/// # use tweetr::Outcome;
+/// assert_eq!(network::io_error("sending to Discord",
+///                              Error::new(ErrorKind::UnexpectedEof, "early EOF"),
+///                              |e| Outcome::BackendError { backend: "Discord".to_string(), error: e }),
+///            Outcome::BackendError { backend: "Discord".to_string(), error: "early EOF".to_string() });
+/// ```
+pub fn io_error<F: FnOnce(String) -> Outcome>(desc: &str, e: io::Error, other: F) -> Outcome {
+    if timed_out(&e) {
+        Outcome::NetworkTimeout { desc: desc.to_string() }
+    } else {
+        other(e.to_string())
+    }
+}
+
+/// Turn the specified `egg_mode` error in the specified request into `Outcome::NetworkTimeout` if it's a timeout,
+/// `Outcome::RateLimited` if it hit the rate limits, or `Outcome::TwitterAPIError` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate egg_mode;
+/// # extern crate tweetr;
+/// # use egg_mode::error::Error;
+/// # use tweetr::ops::network;
+/// # use std::io::{self, ErrorKind};
+/// # use tweetr::Outcome;
+/// # fn main() {
+/// assert_eq!(network::egg_mode_error("verifying tokens", Error::IOError(io::Error::new(ErrorKind::WouldBlock, ""))),
+///            Outcome::NetworkTimeout { desc: "verifying tokens".to_string() });
+/// assert_eq!(network::egg_mode_error("verifying tokens", Error::RateLimit(1473505200)),
+///            Outcome::RateLimited {
+///                desc: "verifying tokens".to_string(),
+///                reset: Some(1473505200),
+///            });
+/// assert_eq!(network::egg_mode_error("verifying tokens", Error::MissingValue("id")),
+///            Outcome::TwitterAPIError(Error::MissingValue("id").to_string()));
+/// # }
+/// ```
+#[cfg(feature = "network")]
+pub fn egg_mode_error(desc: &str, e: EggModeError) -> Outcome {
+    match e {
+        EggModeError::NetError(e) => request_error(desc, e, Outcome::TwitterAPIError),
+        EggModeError::IOError(e) => io_error(desc, e, Outcome::TwitterAPIError),
+        EggModeError::RateLimit(reset) => {
+            Outcome::RateLimited {
+                desc: desc.to_string(),
+                reset: Some(reset as i64),
+            }
+        }
+        // egg_mode only reads the reset time from errors with Twitter's rate limit error code
+        EggModeError::BadStatus(StatusCode::TooManyRequests) => {
+            Outcome::RateLimited {
+                desc: desc.to_string(),
+                reset: None,
+            }
+        }
+        e => Outcome::TwitterAPIError(e.to_string()),
+    }
+}
+
+/// Check whether the specified `egg_mode` error could be Twitter rejecting the request's `oauth_timestamp`, i.e. whether it's
+/// worth checking the clock with `oauth::sync_clock()`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate egg_mode;
+/// # extern crate hyper;
+/// # extern crate tweetr;
+/// # use egg_mode::error::{Error, TwitterErrorCode, TwitterErrors};
+/// # use hyper::status::StatusCode;
+/// # use tweetr::ops::network;
+/// # fn main() {
+/// assert!(network::timestamp_rejected(&Error::TwitterError(TwitterErrors {
+///     errors: vec![TwitterErrorCode {
+///                      message: "Timestamp out of bounds.".to_string(),
+///                      code: 135,
+///                  }],
+/// })));
+/// assert!(network::timestamp_rejected(&Error::BadStatus(StatusCode::Unauthorized)));
+/// assert!(!network::timestamp_rejected(&Error::TwitterError(TwitterErrors {
+///     errors: vec![TwitterErrorCode {
+///                      message: "Status is a duplicate.".to_string(),
+///                      code: 187,
+///                  }],
+/// })));
+/// # }
+/// ```
+#[cfg(feature = "network")]
+pub fn timestamp_rejected(e: &EggModeError) -> bool {
+    match *e {
+        // Twitter reports skewed timestamps either as such, or as failing to authenticate
+        EggModeError::TwitterError(ref errors) => errors.errors.iter().any(|e| e.code as i64 == oauth::TIMESTAMP_OUT_OF_BOUNDS || e.code == 32),
+        EggModeError::BadStatus(StatusCode::Unauthorized) => true,
+        _ => false,
+    }
+}
+
+/// Copy the specified token, so it can be carried into a request run with `run()`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate egg_mode;
+/// # extern crate tweetr;
+/// # use tweetr::ops::network;
+/// # use egg_mode::Token;
+/// # fn main() {
+/// let token = network::owned_token(&Token::new("key", "secret"));
+/// assert_eq!(token.key, "key");
+/// assert_eq!(token.secret, "secret");
+/// # }
+/// ```
+#[cfg(feature = "network")]
+pub fn owned_token(token: &Token) -> Token<'static> {
+    Token::new(token.key.to_string(), token.secret.to_string())
+}
+
+/// Run the specified request on another thread, giving up waiting for it after the total timeout.
+///
+/// A request given up on is left running, and anything it'd've done is still done.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network::{self, Timeouts};
+/// # use std::time::Duration;
+/// # use tweetr::Outcome;
+/// # use std::thread;
+/// assert_eq!(network::run("counting", || Ok(2 + 2)), Ok(4));
+///
+/// network::use_timeouts(Timeouts {
+///     connect: None,
+///     read: None,
+///     write: None,
+/// });
+/// assert_eq!(network::run("sleeping", || Ok(thread::sleep(Duration::from_millis(10)))), Ok(()));
+///
+/// network::use_timeouts(Timeouts {
+///     connect: Some(Duration::from_millis(10)),
+///     read: Some(Duration::from_millis(10)),
+///     write: Some(Duration::from_millis(10)),
+/// });
+/// assert_eq!(network::run("sleeping", || Ok(thread::sleep(Duration::from_secs(1)))),
+///            Err(Outcome::NetworkTimeout { desc: "sleeping".to_string() }));
+/// ```
+pub fn run<T, F>(desc: &str, request: F) -> Result<T, Outcome>
+    where T: Send + 'static,
+          F: FnOnce() -> Result<T, Outcome> + Send + 'static
+{
+    let timeouts = timeouts();
+    let bind_address = bind_address();
+    let (send, recv) = channel();
+    thread::spawn(move || {
+        use_timeouts(timeouts);
+        use_bind_address(bind_address);
+        // Nobody's waiting for the result if the request was given up on
+        let _ = send.send(request());
+    });
+
+    match timeouts.total() {
+        Some(total) => {
+            match recv.recv_timeout(total) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => Err(Outcome::NetworkTimeout { desc: desc.to_string() }),
+                Err(RecvTimeoutError::Disconnected) => panic!("request thread for {} panicked", desc),
+            }
+        }
+        None => recv.recv().unwrap_or_else(|_| panic!("request thread for {} panicked", desc)),
+    }
+}
+
+/// Run the specified API request with `run()`, retrying it after the rate limits reset if it hits them.
+///
+/// The request is retried up to `RATE_LIMIT_RETRIES` times, and only if the limits reset within `MAX_RATE_LIMIT_WAIT`
+/// seconds, otherwise its `Outcome::RateLimited` is returned as-is.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network::{self, Timeouts};
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use std::time::{SystemTime, UNIX_EPOCH};
+/// # use std::sync::Arc;
+/// # use tweetr::Outcome;
+/// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+///
+/// let attempts = Arc::new(AtomicUsize::new(0));
+/// let counter = attempts.clone();
+/// assert_eq!(network::request("posting", move || if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+///                Err(Outcome::RateLimited {
+///                    desc: "posting".to_string(),
+///                    reset: Some(now),
+///                })
+///            } else {
+///                Ok(481)
+///            }),
+///            Ok(481));
+/// assert_eq!(attempts.load(Ordering::SeqCst), 2);
+///
+/// assert_eq!(network::request("posting", move || Err::<(), _>(Outcome::RateLimited {
+///                desc: "posting".to_string(),
+///                reset: Some(now + 24 * 60 * 60),
+///            })),
+///            Err(Outcome::RateLimited {
+///                desc: "posting".to_string(),
+///                reset: Some(now + 24 * 60 * 60),
+///            }));
+/// ```
+pub fn request<T, F>(desc: &str, request: F) -> Result<T, Outcome>
+    where T: Send + 'static,
+          F: Fn() -> Result<T, Outcome> + Send + Sync + 'static
+{
+    let request = Arc::new(request);
+    retry_rate_limited(|| {
+        let attempt = request.clone();
+        run(desc, move || attempt())
+    })
+}
+
+/// Run the specified request that mustn't be sent twice on the current thread, retrying it after the rate limits reset if
+/// it hits them, like `request()`.
+///
+/// It's given up on only by the timeouts of the connection it's made over (see `client()`), so it can't be carried on
+/// after returning.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::network;
+/// # use tweetr::Outcome;
+/// assert_eq!(network::request_blocking(|| Ok(481)), Ok(481));
+/// assert_eq!(network::request_blocking(|| Err::<(), _>(Outcome::TwitterAPIError("Status is a duplicate.".to_string()))),
+///            Err(Outcome::TwitterAPIError("Status is a duplicate.".to_string())));
+/// ```
+pub fn request_blocking<T, F: Fn() -> Result<T, Outcome>>(request: F) -> Result<T, Outcome> {
+    retry_rate_limited(request)
+}
+
+fn retry_rate_limited<T, F: Fn() -> Result<T, Outcome>>(request: F) -> Result<T, Outcome> {
+    let mut retries = 0;
+    loop {
+        match request() {
+            Err(Outcome::RateLimited { reset: Some(reset), .. }) if retries < RATE_LIMIT_RETRIES && reset - now() <= MAX_RATE_LIMIT_WAIT => {
+                retries += 1;
+                // The reset time is rounded down to a second, so wait out the rest of it
+                thread::sleep(Duration::from_secs(cmp::max(reset - now(), 0) as u64 + 1));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Get when the rate limits reset according to the specified response headers, in seconds since the epoch, if they say.
+///
+/// `Retry-After`, either in seconds from the specified current time or an HTTP date, takes precedence over
+/// `X-Rate-Limit-Reset`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate hyper;
+/// # use tweetr::ops::network;
+/// # use hyper::header::Headers;
+/// # fn main() {
+/// let mut headers = Headers::new();
+/// assert_eq!(network::rate_limit_reset(&headers, 1473505200), None);
+///
+/// headers.set_raw("X-Rate-Limit-Reset", vec![b"1473506100".to_vec()]);
+/// assert_eq!(network::rate_limit_reset(&headers, 1473505200), Some(1473506100));
+///
+/// headers.set_raw("Retry-After", vec![b"120".to_vec()]);
+/// assert_eq!(network::rate_limit_reset(&headers, 1473505200), Some(1473505320));
+///
+/// headers.set_raw("Retry-After", vec![b"Sat, 10 Sep 2016 11:05:00 GMT".to_vec()]);
+/// assert_eq!(network::rate_limit_reset(&headers, 1473505200), Some(1473505500));
+/// # }
+/// ```
+#[cfg(feature = "network")]
+pub fn rate_limit_reset(headers: &Headers, now: i64) -> Option<i64> {
+    let header = |name| headers.get_raw(name).and_then(|h| h.first()).and_then(|h| str::from_utf8(h).ok()).map(str::trim);
+
+    match header("Retry-After") {
+        Some(retry_after) => {
+            retry_after.parse::<i64>()
+                .ok()
+                .map(|secs| now + secs)
+                .or_else(|| DateTime::parse_from_rfc2822(retry_after).ok().map(|t| t.timestamp()))
+        }
+        None => header("X-Rate-Limit-Reset").and_then(|reset| reset.parse().ok()),
+    }
+}
+
+
+fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(dur) => dur.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        }
+}
+
+fn seconds(secs: u64) -> Option<Duration> {
+    if secs == 0 {
+        None
+    } else {
+        Some(Duration::from_secs(secs))
+    }
+}
+
+
+/// Connects to servers giving up after the connect timeout, and from the specified local address, if any, neither of which
+/// hyper's own connector does.
+#[cfg(feature = "network")]
+#[derive(Debug, Copy, Clone)]
+struct Connector {
+    bind: Option<IpAddr>,
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "network")]
+impl NetworkConnector for Connector {
+    type Stream = HttpStream;
+
+    fn connect(&self, host: &str, port: u16, _: &str) -> hyper::Result<HttpStream> {
+        let mut last_err = match self.bind {
+            Some(local) => io::Error::new(io::ErrorKind::AddrNotAvailable, format!("{} has no address reachable from {}", host, local)),
+            None => io::Error::new(io::ErrorKind::AddrNotAvailable, format!("{} has no addresses", host)),
+        };
+        for remote in try!((host, port).to_socket_addrs()).filter(|remote| self.bind.map(|local| remote.is_ipv4() == local.is_ipv4()).unwrap_or(true)) {
+            let stream = match (self.bind, self.timeout) {
+                (Some(local), timeout) => connect_from(local, &remote, timeout),
+                (None, Some(timeout)) => TcpStream::connect_timeout(&remote, timeout),
+                (None, None) => TcpStream::connect(remote),
+            };
+            match stream {
+                Ok(stream) => return Ok(HttpStream(stream)),
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err.into())
+    }
+}
+
+#[cfg(all(unix, feature = "network"))]
+fn connect_from(local: IpAddr, remote: &SocketAddr, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    use std::os::unix::io::FromRawFd;
+    use libc;
+
+    unsafe {
+        let fd = libc::socket(if remote.is_ipv4() { libc::AF_INET } else { libc::AF_INET6 }, libc::SOCK_STREAM, 0);
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // Closes the socket if binding or connecting fails
+        let stream = TcpStream::from_raw_fd(fd);
+        // Like std's own sockets, so they aren't leaked into hooks and sink commands
+        libc::ioctl(fd, libc::FIOCLEX);
+
+        try!(with_sockaddr(&SocketAddr::new(local, 0), |addr, len| libc::bind(fd, addr, len)));
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => {
+                try!(with_sockaddr(remote, |addr, len| libc::connect(fd, addr, len)));
+                return Ok(stream);
+            }
+        };
+
+        // Connecting in the background and waiting for it as long as the timeout, like TcpStream::connect_timeout()
+        try!(stream.set_nonblocking(true));
+        match with_sockaddr(remote, |addr, len| libc::connect(fd, addr, len)) {
+            Ok(()) => (),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {
+                let mut pollfd = libc::pollfd {
+                    fd: fd,
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                let millis = timeout.as_secs().saturating_mul(1000).saturating_add(timeout.subsec_nanos() as u64 / 1_000_000);
+                match libc::poll(&mut pollfd, 1, cmp::min(cmp::max(millis, 1), libc::c_int::max_value() as u64) as libc::c_int) {
+                    -1 => return Err(io::Error::last_os_error()),
+                    0 => return Err(io::Error::new(io::ErrorKind::TimedOut, "connection timed out")),
+                    _ => {
+                        if let Some(e) = try!(stream.take_error()) {
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        try!(stream.set_nonblocking(false));
+        Ok(stream)
+    }
+}
+
+#[cfg(all(not(unix), feature = "network"))]
+fn connect_from(_: IpAddr, _: &SocketAddr, _: Option<Duration>) -> io::Result<TcpStream> {
+    Err(io::Error::new(io::ErrorKind::Other, "connecting from a specific local address isn't supported on this platform"))
+}
+
+#[cfg(all(unix, feature = "network"))]
+unsafe fn with_sockaddr<F: FnOnce(*const ::libc::sockaddr, ::libc::socklen_t) -> ::libc::c_int>(addr: &SocketAddr, f: F) -> io::Result<()> {
+    use std::mem;
+    use libc;
+
+    let ret = match *addr {
+        SocketAddr::V4(ref addr) => {
+            let mut sin: libc::sockaddr_in = mem::zeroed();
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = addr.port().to_be();
+            sin.sin_addr.s_addr = u32::from(*addr.ip()).to_be();
+            f(&sin as *const _ as *const libc::sockaddr, mem::size_of_val(&sin) as libc::socklen_t)
+        }
+        SocketAddr::V6(ref addr) => {
+            let mut sin6: libc::sockaddr_in6 = mem::zeroed();
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = addr.port().to_be();
+            sin6.sin6_flowinfo = addr.flowinfo();
+            sin6.sin6_addr.s6_addr = addr.ip().octets();
+            sin6.sin6_scope_id = addr.scope_id();
+            f(&sin6 as *const _ as *const libc::sockaddr, mem::size_of_val(&sin6) as libc::socklen_t)
+        }
+    };
+
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(unix)]
+fn interface_address(name: &str) -> Option<IpAddr> {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::ffi::CStr;
+    use std::ptr;
+    use libc;
+
+    unsafe {
+        let mut addrs = ptr::null_mut();
+        if libc::getifaddrs(&mut addrs) != 0 {
+            return None;
+        }
+
+        let mut found = None;
+        let mut cur = addrs;
+        while !cur.is_null() {
+            let ifa = &*cur;
+            if !ifa.ifa_addr.is_null() && CStr::from_ptr(ifa.ifa_name).to_bytes() == name.as_bytes() {
+                match (*ifa.ifa_addr).sa_family as libc::c_int {
+                    libc::AF_INET => {
+                        let sin = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                        found = Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))));
+                        break;
+                    }
+                    libc::AF_INET6 if found.is_none() => {
+                        let sin6 = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                        found = Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)));
+                    }
+                    _ => {}
+                }
+            }
+            cur = ifa.ifa_next;
+        }
+
+        libc::freeifaddrs(addrs);
+        found
+    }
+}
+
+#[cfg(not(unix))]
+fn interface_address(_: &str) -> Option<IpAddr> {
+    None
+}
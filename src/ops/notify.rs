@@ -0,0 +1,231 @@
+//! Desktop notifications about the daemon posting tweets, failing to, or falling behind, for running it on a workstation
+//! instead of a server.
+//!
+//! Notifications are shown with `notify-send` on Linux and other Unix-likes, `osascript` on macOS, and as a toast via
+//! PowerShell on Windows.
+//!
+//! Showing them is only available with the `notify` feature, but what they say isn't, to keep it testable.
+
+
+use self::super::super::Outcome;
+use self::super::super::util::format_approximate_time;
+use self::super::events::{rate_limited, suspended};
+use self::super::l10n::tr;
+use chrono::Duration;
+use self::super::QueuedTweet;
+#[cfg(feature = "notify")]
+use std::process::Command;
+
+
+/// What a notification says.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Notification {
+    /// The notification's summary.
+    pub title: String,
+    /// The notification's text.
+    pub body: String,
+}
+
+/// Shows notifications, if enabled.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Notifier {
+    enabled: bool,
+}
+
+
+impl Notification {
+    /// Get the notification about the specified tweet having been posted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::notify::Notification;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(Notification::posted(&tweet),
+    ///            Notification {
+    ///                title: "Posted tweet by nabijaczleweli".to_string(),
+    ///                body: "Abolish the bourgeoisie".to_string(),
+    ///            });
+    /// # }
+    /// ```
+    pub fn posted(tweet: &QueuedTweet) -> Notification {
+        Notification {
+            title: format!("Posted tweet by {}", tweet.author),
+            body: tweet.content.clone(),
+        }
+    }
+
+    /// Get the notification about posting the specified tweet having failed with the specified outcome.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::notify::Notification;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use tweetr::Outcome;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(Notification::failed(&tweet, &Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string())),
+    ///            Notification {
+    ///                title: "Failed to post tweet by nabijaczleweli".to_string(),
+    ///                body: "\"Abolish the bourgeoisie\": Twitter API error: Status is a duplicate. (code 187)".to_string(),
+    ///            });
+    /// assert_eq!(Notification::failed(&tweet, &Outcome::TwitterAPIError("Your account is suspended. (code 64)".to_string())).title,
+    ///            "Paused posting tweets by nabijaczleweli, the account is suspended or locked");
+    /// # }
+    /// ```
+    pub fn failed(tweet: &QueuedTweet, out: &Outcome) -> Notification {
+        let mut error = vec![];
+        out.print_error(&mut error);
+
+        Notification {
+            title: if suspended(out) {
+                format!("Paused posting tweets by {}, the account is suspended or locked", tweet.author)
+            } else {
+                format!("{} tweet by {}",
+                        if rate_limited(out) {
+                            "Rate limited posting"
+                        } else {
+                            "Failed to post"
+                        },
+                        tweet.author)
+            },
+            body: format!("\"{}\": {}", tweet.content, String::from_utf8_lossy(&error).trim()),
+        }
+    }
+
+    /// Get the notification about the average posting latency, in seconds, having exceeded the specified threshold
+    /// (see `ops::latency`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::notify::Notification;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(Notification::late(312.5, Duration::minutes(5)),
+    ///            Notification {
+    ///                title: "Posting tweets late".to_string(),
+    ///                body: "Tweets are being posted 5 minutes late on average, over the threshold of 5 minutes, \
+    ///                       the machine might be overloaded".to_string(),
+    ///            });
+    /// # }
+    /// ```
+    pub fn late(average: f64, threshold: Duration) -> Notification {
+        Notification {
+            title: "Posting tweets late".to_string(),
+            body: tr("latency_exceeded",
+                     &[("latency", &format_approximate_time(average as u64)),
+                       ("threshold", &format_approximate_time(threshold.num_seconds() as u64))]),
+        }
+    }
+}
+
+impl Notifier {
+    /// Get a notifier showing notifications if enabled and built with the `notify` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::notify::{Notification, Notifier};
+    /// let mut notifier = Notifier::new(false);
+    /// assert_eq!(notifier.notify(&Notification {
+    ///                title: "Posted tweet by nabijaczleweli".to_string(),
+    ///                body: "Abolish the bourgeoisie".to_string(),
+    ///            }),
+    ///            Ok(()));
+    /// ```
+    pub fn new(enabled: bool) -> Notifier {
+        Notifier { enabled: enabled && cfg!(feature = "notify") }
+    }
+
+    /// Show the specified notification, if enabled.
+    ///
+    /// If it can't be shown, for example because there's no notification daemon running, the error is returned and no further
+    /// notifications are shown.
+    pub fn notify(&mut self, notification: &Notification) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        show(notification).map_err(|e| {
+            self.enabled = false;
+            format!("{}, not showing any more desktop notifications", e)
+        })
+    }
+}
+
+
+#[cfg(feature = "notify")]
+fn show(notification: &Notification) -> Result<(), String> {
+    let (program, mut cmd) = command(notification);
+    match cmd.status() {
+        Ok(ref status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{} failed with {}", program, status)),
+        Err(e) => Err(format!("couldn't run {}: {}", program, e)),
+    }
+}
+
+#[cfg(not(feature = "notify"))]
+fn show(_: &Notification) -> Result<(), String> {
+    Err("tweetr was built without the notify feature".to_string())
+}
+
+#[cfg(all(feature = "notify", unix, not(target_os = "macos")))]
+fn command(notification: &Notification) -> (&'static str, Command) {
+    let mut cmd = Command::new("notify-send");
+    cmd.arg("--app-name=tweetr").arg("--").arg(&notification.title).arg(&notification.body);
+    ("notify-send", cmd)
+}
+
+#[cfg(all(feature = "notify", target_os = "macos"))]
+fn command(notification: &Notification) -> (&'static str, Command) {
+    // Passed as arguments, so they needn't be quoted for AppleScript
+    let mut cmd = Command::new("osascript");
+    cmd.args(&["-e", "on run argv", "-e", "display notification (item 2 of argv) with title (item 1 of argv)", "-e", "end run"])
+        .arg(&notification.title)
+        .arg(&notification.body);
+    ("osascript", cmd)
+}
+
+#[cfg(all(feature = "notify", windows))]
+fn command(notification: &Notification) -> (&'static str, Command) {
+    // Passed in the environment, so they needn't be quoted for PowerShell
+    let mut cmd = Command::new("powershell");
+    cmd.args(&["-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+                 $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent(\
+                     [Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+                 $text = $xml.GetElementsByTagName('text'); \
+                 $text.Item(0).AppendChild($xml.CreateTextNode($env:TWEETR_NOTIFY_TITLE)) > $null; \
+                 $text.Item(1).AppendChild($xml.CreateTextNode($env:TWEETR_NOTIFY_BODY)) > $null; \
+                 [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('tweetr').Show(\
+                     [Windows.UI.Notifications.ToastNotification]::new($xml))"])
+        .env("TWEETR_NOTIFY_TITLE", &notification.title)
+        .env("TWEETR_NOTIFY_BODY", &notification.body);
+    ("powershell", cmd)
+}
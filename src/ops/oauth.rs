@@ -0,0 +1,592 @@
+//! Signing and sending Twitter API requests `egg_mode` has no first-class support for.
+//!
+//! Based on the OAuth 1.0a implementation in `egg_mode`, which doesn't expose it.
+//!
+//! The signing is done in steps, each exposed, so requests to arbitrary endpoints can be signed with the tokens from the
+//! configuration directory (see `AppTokens::raw_token()` and `User::raw_token()`) and debugged:
+//!
+//! ```plaintext
+//! ops::oauth::timestamp(), ops::oauth::nonce()
+//! |> ops::oauth::protocol_params()
+//! |> ops::oauth::signature_base_string()
+//! |> ops::oauth::hmac_sha1(ops::oauth::signing_key())
+//! |> ops::oauth::authorization_header()
+//! ```
+//!
+//! `ops::oauth::authorization()` does all of the above.
+//!
+//! Twitter rejects requests whose `oauth_timestamp` is too far off its own clock, so the `Date` header of every response is
+//! compared against the system clock, and, if they're more than `MAX_CLOCK_SKEW` seconds apart, the timestamps are corrected
+//! by the difference from then on, see `correct_clock()`. Requests signed by `egg_mode` use the system clock regardless, so
+//! are sent with `post()` instead once it's known to be off.
+//!
+//! GET requests are sent conditionally when an earlier response to them is cached, see `ops::http_cache`.
+//!
+//! So are the requests for authorising users while connecting from a specific local address, see `request_token()` and
+//! `access_token()`.
+
+
+use url::percent_encoding::{EncodeSet, utf8_percent_encode};
+use rustc_serialize::base64::{self, ToBase64};
+use hyper::header::{Authorization, ContentType, Headers};
+use hyper::status::StatusCode;
+use std::time::{UNIX_EPOCH, SystemTime};
+use std::collections::BTreeMap;
+use chrono::{DateTime, UTC};
+use std::sync::Mutex;
+use rustc_serialize::json::Json;
+use url::form_urlencoded;
+use crypto::hmac::Hmac;
+use rand::{self, Rng};
+use crypto::sha1::Sha1;
+use crypto::mac::Mac;
+use self::super::doctor::API_URL;
+use self::super::http_cache::{self, Entry};
+use self::super::network;
+use egg_mode::Token;
+use self::super::super::Outcome;
+use std::io::Read;
+use std::str;
+
+
+/// How far off Twitter's clock, in seconds, the system clock can be before the timestamps are corrected.
+///
+/// This is well within what Twitter accepts, but above the imprecision of measuring it with the whole seconds in `Date`.
+pub static MAX_CLOCK_SKEW: i64 = 30;
+
+/// Twitter's error code for a request whose `oauth_timestamp` is too far off.
+pub static TIMESTAMP_OUT_OF_BOUNDS: i64 = 135;
+
+/// The oauth/request_token endpoint.
+pub static REQUEST_TOKEN_URL: &'static str = "https://api.twitter.com/oauth/request_token";
+
+/// The oauth/access_token endpoint.
+pub static ACCESS_TOKEN_URL: &'static str = "https://api.twitter.com/oauth/access_token";
+
+lazy_static! {
+    // The correction in seconds, and whether it changed since last reported by take_clock_correction()
+    static ref CLOCK_CORRECTION: Mutex<(i64, bool)> = Mutex::new((0, false));
+}
+
+
+/// The percent-encoding Twitter expects, i.e. everything but unreserved characters.
+#[derive(Copy, Clone)]
+struct TwitterEncodeSet;
+
+impl EncodeSet for TwitterEncodeSet {
+    fn contains(&self, byte: u8) -> bool {
+        match byte {
+            b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' => false,
+            _ => true,
+        }
+    }
+}
+
+
+/// POST the specified parameters to the specified API endpoint as the specified user of the specified app.
+///
+/// All parameters are included in the signature.
+///
+/// Returns the parsed response body, or `Outcome::TwitterAPIError` with the error messages returned by the API,
+/// `Outcome::RateLimited`, or `Outcome::NetworkTimeout`.
+///
+/// If the request was rejected as unauthorised and the response shows the clock's off, it's corrected and the request is
+/// retried, once.
+pub fn post(uri: &str, params: &BTreeMap<String, String>, app: &Token, access: &Token) -> Result<Json, Outcome> {
+    let body = params.iter()
+        .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let pairs: Vec<_> = params.iter().map(|(k, v)| (&k[..], &v[..])).collect();
+    attempt("POST", uri, &pairs, &body, Some(ContentType::form_url_encoded()), app, access, true)
+}
+
+/// GET the specified API endpoint with the specified parameters in the query string as the specified user of the specified
+/// app.
+///
+/// As with `post()`, but the response is cached, and answered from the cache if it didn't change, see `ops::http_cache`.
+pub fn get(uri: &str, params: &BTreeMap<String, String>, app: &Token, access: &Token) -> Result<Json, Outcome> {
+    let query = params.iter()
+        .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let pairs: Vec<_> = params.iter().map(|(k, v)| (&k[..], &v[..])).collect();
+    attempt("GET", uri, &pairs, &query, None, app, access, true)
+}
+
+/// POST the specified JSON body to the specified v2 API endpoint as the specified user of the specified app.
+///
+/// As with `post()`, but the body isn't included in the signature, and the v2 API's error responses are understood, too.
+pub fn post_json(uri: &str, body: &Json, app: &Token, access: &Token) -> Result<Json, Outcome> {
+    attempt("POST", uri, &[], &body.to_string(), Some(ContentType::json()), app, access, true)
+}
+
+/// Get a request token for the specified app to authorise a user with a PIN, like `egg_mode::request_token()` with `"oob"`.
+pub fn request_token(app: &Token) -> Result<Token<'static>, Outcome> {
+    let resp = try!(post_form(REQUEST_TOKEN_URL, &[("oauth_callback", "oob")], app, &Token::new("", "")));
+    match (resp.get("oauth_token"), resp.get("oauth_token_secret")) {
+        (Some(key), Some(secret)) => Ok(Token::new(key.clone(), secret.clone())),
+        _ => Err(Outcome::TwitterAPIError(format!("invalid response: {:?}", resp))),
+    }
+}
+
+/// Exchange the specified request token and the PIN the user authorised it with for their access token, ID, and screen name,
+/// like `egg_mode::access_token()`.
+pub fn access_token(app: &Token, request: &Token, verifier: &str) -> Result<(Token<'static>, i64, String), Outcome> {
+    let resp = try!(post_form(ACCESS_TOKEN_URL, &[("oauth_verifier", verifier)], app, request));
+    match (resp.get("oauth_token"), resp.get("oauth_token_secret"), resp.get("user_id").and_then(|id| id.parse().ok()), resp.get("screen_name")) {
+        (Some(key), Some(secret), Some(id), Some(name)) => Ok((Token::new(key.clone(), secret.clone()), id, name.clone())),
+        _ => Err(Outcome::TwitterAPIError(format!("invalid response: {:?}", resp))),
+    }
+}
+
+/// Get the value of the `Authorization` header signing a request with the specified method to the specified URI with the
+/// specified parameters as the specified user of the specified app, with a fresh nonce and the current timestamp.
+///
+/// The parameters are the ones in the query string and, for `application/x-www-form-urlencoded` bodies, in the body, not
+/// percent-encoded. The URI mustn't contain the query string.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate egg_mode;
+/// # use tweetr::ops::oauth;
+/// # use egg_mode::Token;
+/// # fn main() {
+/// let app = Token::new("xvz1evFS4wEEPTGEFPHBog", "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw");
+/// let access = Token::new("370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb", "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE");
+///
+/// let header = oauth::authorization("GET", "https://api.twitter.com/1.1/statuses/home_timeline.json", &[("count", "5")], &app, &access);
+/// assert!(header.starts_with("OAuth oauth_consumer_key=\"xvz1evFS4wEEPTGEFPHBog\", oauth_nonce=\""));
+/// assert!(header.contains(", oauth_signature=\""));
+/// # }
+/// ```
+pub fn authorization(method: &str, uri: &str, params: &[(&str, &str)], app: &Token, access: &Token) -> String {
+    let mut oauth_params = protocol_params(app, access, &timestamp(), &nonce());
+    let signature = sign(method, uri, params, &oauth_params, app, access);
+    oauth_params.insert("oauth_signature", signature);
+
+    authorization_header(&oauth_params)
+}
+
+/// Get the current time in seconds since the epoch, as used for `oauth_timestamp`, corrected by `clock_correction()`.
+pub fn timestamp() -> String {
+    (match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(dur) => dur,
+                Err(err) => err.duration(),
+            }
+            .as_secs() as i64 + clock_correction())
+        .to_string()
+}
+
+/// Get how far behind Twitter's clock the system clock is, in seconds, according to the `Date` header of the specified
+/// response received at the specified time, in seconds since the epoch, if it has one.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate hyper;
+/// # use tweetr::ops::oauth;
+/// # use hyper::header::Headers;
+/// # fn main() {
+/// let mut headers = Headers::new();
+/// assert_eq!(oauth::clock_skew(&headers, 1473505200), None);
+///
+/// headers.set_raw("Date", vec![b"Sat, 10 Sep 2016 11:05:00 GMT".to_vec()]);
+/// assert_eq!(oauth::clock_skew(&headers, 1473505200), Some(300));
+/// assert_eq!(oauth::clock_skew(&headers, 1473505800), Some(-300));
+/// # }
+/// ```
+pub fn clock_skew(headers: &Headers, now: i64) -> Option<i64> {
+    raw_header(headers, "Date")
+        .and_then(|date| DateTime::parse_from_rfc2822(date.trim()).ok())
+        .map(|date| date.timestamp() - now)
+}
+
+/// Correct the timestamps by the specified clock skew, as measured by `clock_skew()`, if it's more than `MAX_CLOCK_SKEW`
+/// seconds off the current correction, returning whether it was.
+///
+/// The correction is shared by all threads, and reported once by `take_clock_correction()`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert!(!oauth::correct_clock(10));
+/// assert_eq!(oauth::clock_correction(), 0);
+///
+/// assert!(oauth::correct_clock(300));
+/// assert!(!oauth::correct_clock(290));
+/// assert_eq!(oauth::clock_correction(), 300);
+///
+/// assert!(oauth::correct_clock(0));
+/// assert_eq!(oauth::clock_correction(), 0);
+/// ```
+pub fn correct_clock(skew: i64) -> bool {
+    let mut correction = CLOCK_CORRECTION.lock().unwrap();
+    if (skew - correction.0).abs() > MAX_CLOCK_SKEW {
+        *correction = (skew, true);
+        true
+    } else {
+        false
+    }
+}
+
+/// Get the current correction to the timestamps, in seconds.
+pub fn clock_correction() -> i64 {
+    CLOCK_CORRECTION.lock().unwrap().0
+}
+
+/// Get the correction to the timestamps, in seconds, if it changed since this was last called.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert_eq!(oauth::take_clock_correction(), None);
+///
+/// oauth::correct_clock(-300);
+/// assert_eq!(oauth::take_clock_correction(), Some(-300));
+/// assert_eq!(oauth::take_clock_correction(), None);
+/// ```
+pub fn take_clock_correction() -> Option<i64> {
+    let mut correction = CLOCK_CORRECTION.lock().unwrap();
+    if correction.1 {
+        correction.1 = false;
+        Some(correction.0)
+    } else {
+        None
+    }
+}
+
+/// Describe the specified correction to the timestamps, as returned by `take_clock_correction()`, for warning the user.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert_eq!(oauth::describe_clock_correction(-300),
+///            "the system clock is 300 seconds ahead of the Twitter API's, correcting the OAuth timestamps by it; \
+///             set the clock right to stop seeing this");
+/// assert_eq!(oauth::describe_clock_correction(3),
+///            "the system clock is in sync with the Twitter API's again, no longer correcting the OAuth timestamps");
+/// ```
+pub fn describe_clock_correction(correction: i64) -> String {
+    if correction.abs() <= MAX_CLOCK_SKEW {
+        "the system clock is in sync with the Twitter API's again, no longer correcting the OAuth timestamps".to_string()
+    } else {
+        format!("the system clock is {} seconds {} the Twitter API's, correcting the OAuth timestamps by it; set the clock right to stop \
+                 seeing this",
+                correction.abs(),
+                if correction < 0 { "ahead of" } else { "behind" })
+    }
+}
+
+/// Measure the clock skew against Twitter's API with an unauthenticated request to `doctor::API_URL`, and correct the
+/// timestamps by it, see `correct_clock()`, returning whether they were.
+///
+/// Used when a request signed by `egg_mode` was rejected for its timestamp, as its response isn't available to measure
+/// the skew from.
+pub fn sync_clock() -> Result<bool, Outcome> {
+    let desc = format!("checking the time at {}", API_URL);
+    let resp = try!(network::client().head(API_URL).send().map_err(|e| network::request_error(&desc, e, Outcome::TwitterAPIError)));
+    match clock_skew(&resp.headers, UTC::now().timestamp()) {
+        Some(skew) => Ok(correct_clock(skew)),
+        None => Err(Outcome::TwitterAPIError(format!("no Date header in the response to {}", desc))),
+    }
+}
+
+/// Generate a random nonce, as used for `oauth_nonce`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// let nonce = oauth::nonce();
+/// assert_eq!(nonce.len(), 32);
+/// assert!(nonce.chars().all(|c| c.is_alphanumeric()));
+/// assert!(nonce != oauth::nonce());
+/// ```
+pub fn nonce() -> String {
+    rand::thread_rng().gen_ascii_chars().take(32).collect()
+}
+
+/// Get the protocol parameters, except for the signature, for a request as the specified user of the specified app with the
+/// specified timestamp and nonce.
+///
+/// `oauth_token` is left out for an empty access token, as when getting a request token.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate egg_mode;
+/// # use tweetr::ops::oauth;
+/// # use egg_mode::Token;
+/// # fn main() {
+/// let params = oauth::protocol_params(&Token::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44"),
+///                                     &Token::new("nnch734d00sl2jdk", "pfkkdhi9sl3r4s00"),
+///                                     "137131202",
+///                                     "chapoH");
+/// assert_eq!(params.keys().cloned().collect::<Vec<_>>(),
+///            vec!["oauth_consumer_key", "oauth_nonce", "oauth_signature_method", "oauth_timestamp", "oauth_token",
+///                 "oauth_version"]);
+/// assert_eq!(params["oauth_signature_method"], "HMAC-SHA1");
+///
+/// let params = oauth::protocol_params(&Token::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44"), &Token::new("", ""), "137131202", "chapoH");
+/// assert!(!params.contains_key("oauth_token"));
+/// # }
+/// ```
+pub fn protocol_params(app: &Token, access: &Token, timestamp: &str, nonce: &str) -> BTreeMap<&'static str, String> {
+    let mut oauth_params = BTreeMap::new();
+    oauth_params.insert("oauth_consumer_key", app.key.to_string());
+    oauth_params.insert("oauth_nonce", nonce.to_string());
+    oauth_params.insert("oauth_signature_method", "HMAC-SHA1".to_string());
+    oauth_params.insert("oauth_timestamp", timestamp.to_string());
+    if !access.key.is_empty() {
+        oauth_params.insert("oauth_token", access.key.to_string());
+    }
+    oauth_params.insert("oauth_version", "1.0".to_string());
+    oauth_params
+}
+
+/// Sign a request with the specified method to the specified URI with the specified parameters and protocol parameters as the
+/// specified user of the specified app, returning the value of `oauth_signature`.
+///
+/// See `authorization()` for the parameters.
+pub fn sign(method: &str, uri: &str, params: &[(&str, &str)], oauth_params: &BTreeMap<&str, String>, app: &Token, access: &Token) -> String {
+    hmac_sha1(&signing_key(&app.secret, &access.secret),
+              &signature_base_string(method, uri, params, oauth_params))
+}
+
+/// Construct the signature base string of a request, as specified in RFC 5849, section 3.4.1.
+///
+/// See `authorization()` for the parameters.
+///
+/// # Examples
+///
+/// The example from RFC 5849, section 3.4.1.1:
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// # use std::collections::BTreeMap;
+/// let mut oauth_params = BTreeMap::new();
+/// oauth_params.insert("oauth_consumer_key", "9djdj82h48djs9d2".to_string());
+/// oauth_params.insert("oauth_token", "kkk9d7dh3k39sjv7".to_string());
+/// oauth_params.insert("oauth_signature_method", "HMAC-SHA1".to_string());
+/// oauth_params.insert("oauth_timestamp", "137131201".to_string());
+/// oauth_params.insert("oauth_nonce", "7d8f3e4a".to_string());
+///
+/// assert_eq!(oauth::signature_base_string("POST",
+///                                         "http://example.com/request",
+///                                         &[("b5", "=%3D"), ("a3", "a"), ("c@", ""), ("a2", "r b"), ("c2", ""), ("a3", "2 q")],
+///                                         &oauth_params),
+///            "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q%26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D%26c2%3D%26\
+///             oauth_consumer_key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_method%3DHMAC-SHA1%26\
+///             oauth_timestamp%3D137131201%26oauth_token%3Dkkk9d7dh3k39sjv7");
+/// ```
+pub fn signature_base_string(method: &str, uri: &str, params: &[(&str, &str)], oauth_params: &BTreeMap<&str, String>) -> String {
+    let mut sig_params: Vec<_> = params.iter()
+        .map(|&(k, v)| (percent_encode(k), percent_encode(v)))
+        .chain(oauth_params.iter().map(|(k, v)| (percent_encode(k), percent_encode(v))))
+        .collect();
+    sig_params.sort();
+    let query = sig_params.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+    format!("{}&{}&{}", method.to_uppercase(), percent_encode(uri), percent_encode(&query))
+}
+
+/// Get the key to sign requests with from the consumer (app) and token (user) secrets.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert_eq!(oauth::signing_key("kd94hf93k423kf44", "pfkkdhi9sl3r4s00"), "kd94hf93k423kf44&pfkkdhi9sl3r4s00");
+/// assert_eq!(oauth::signing_key("kd94hf93k423kf44", ""), "kd94hf93k423kf44&");
+/// ```
+pub fn signing_key(consumer_secret: &str, token_secret: &str) -> String {
+    format!("{}&{}", percent_encode(consumer_secret), percent_encode(token_secret))
+}
+
+/// Calculate the base64-encoded HMAC-SHA1 signature of the specified signature base string with the specified key.
+///
+/// # Examples
+///
+/// The example from RFC 5849, section 1.2:
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert_eq!(oauth::hmac_sha1("kd94hf93k423kf44&pfkkdhi9sl3r4s00",
+///                             "GET&http%3A%2F%2Fphotos.example.net%2Fphotos&file%3Dvacation.jpg%26oauth_consumer_key%3Ddpf43f3p2l4k3l03%26\
+///                              oauth_nonce%3DchapoH%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D137131202%26\
+///                              oauth_token%3Dnnch734d00sl2jdk%26size%3Doriginal"),
+///            "MdpQcU8iPSUjWoN/UDMsK2sui9I=");
+/// ```
+pub fn hmac_sha1(key: &str, base_string: &str) -> String {
+    let mut hmac = Hmac::new(Sha1::new(), key.as_bytes());
+    hmac.input(base_string.as_bytes());
+    hmac.result().code().to_base64(base64::STANDARD)
+}
+
+/// Format the specified protocol parameters, including the signature, into the value of the `Authorization` header.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// # use std::collections::BTreeMap;
+/// let mut oauth_params = BTreeMap::new();
+/// oauth_params.insert("oauth_consumer_key", "dpf43f3p2l4k3l03".to_string());
+/// oauth_params.insert("oauth_signature", "MdpQcU8iPSUjWoN/UDMsK2sui9I=".to_string());
+/// assert_eq!(oauth::authorization_header(&oauth_params),
+///            "OAuth oauth_consumer_key=\"dpf43f3p2l4k3l03\", oauth_signature=\"MdpQcU8iPSUjWoN%2FUDMsK2sui9I%3D\"");
+/// ```
+pub fn authorization_header(oauth_params: &BTreeMap<&str, String>) -> String {
+    format!("OAuth {}",
+            oauth_params.iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, percent_encode(v)))
+                .collect::<Vec<_>>()
+                .join(", "))
+}
+
+/// Percent-encode the specified string as specified in RFC 5849, section 3.6, i.e. everything but unreserved characters.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::oauth;
+/// assert_eq!(oauth::percent_encode("Ladies + Gentlemen"), "Ladies%20%2B%20Gentlemen");
+/// assert_eq!(oauth::percent_encode("An encoded string!"), "An%20encoded%20string%21");
+/// assert_eq!(oauth::percent_encode("Dogs, Cats & Mice"), "Dogs%2C%20Cats%20%26%20Mice");
+/// assert_eq!(oauth::percent_encode("☃"), "%E2%98%83");
+/// ```
+pub fn percent_encode(s: &str) -> String {
+    utf8_percent_encode(s, TwitterEncodeSet).collect()
+}
+
+
+/// Send the request, with the body, or, without a `Content-Type`, the query string.
+fn attempt(method: &str, uri: &str, params: &[(&str, &str)], body: &str, content_type: Option<ContentType>, app: &Token, access: &Token, retry: bool)
+           -> Result<Json, Outcome> {
+    let header = authorization(method, uri, params, app, access);
+    let cache = match content_type {
+        Some(_) => None,
+        None => http_cache::dir().map(|dir| (dir, http_cache::key(uri, body, &access.key))),
+    };
+    let cached = cache.as_ref().and_then(|&(ref dir, ref key)| http_cache::read(dir, key));
+
+    let desc = format!("sending a request to {}", uri);
+    let client = network::client();
+    let url = format!("{}?{}", uri, body);
+    let req = match content_type {
+        Some(ref content_type) => client.post(uri).header(content_type.clone()).body(body.as_bytes()),
+        None if body.is_empty() => client.get(uri),
+        None => client.get(&url),
+    };
+    let mut conditional = Headers::new();
+    for (name, value) in cached.as_ref().map(Entry::conditional_headers).unwrap_or_default() {
+        conditional.set_raw(name, vec![value.into_bytes()]);
+    }
+    let mut resp = try!(req.header(Authorization(header))
+        .headers(conditional)
+        .send()
+        .map_err(|e| network::request_error(&desc, e, Outcome::TwitterAPIError)));
+
+    let mut resp_body = String::new();
+    try!(resp.read_to_string(&mut resp_body).map_err(|e| network::io_error(&desc, e, Outcome::TwitterAPIError)));
+    let corrected = clock_skew(&resp.headers, UTC::now().timestamp()).map(correct_clock).unwrap_or(false);
+    if resp.status == StatusCode::Unauthorized && corrected && retry {
+        return attempt(method, uri, params, body, content_type, app, access, false);
+    }
+
+    let mut success = resp.status.is_success();
+    if let Some((dir, key)) = cache {
+        match cached {
+            Some(cached) if resp.status == StatusCode::NotModified => {
+                http_cache::hit();
+                resp_body = cached.body;
+                success = true;
+            }
+            _ => {
+                http_cache::miss();
+                if let Some(entry) = Entry::new(raw_header(&resp.headers, "ETag"), raw_header(&resp.headers, "Last-Modified"), &resp_body) {
+                    if success {
+                        // The cache only saves requests, so failing to write to it isn't worth failing the request over
+                        let _ = http_cache::write(&dir, &key, &entry);
+                    }
+                }
+            }
+        }
+    }
+    // Like appending to chunked media uploads
+    if success && resp_body.trim().is_empty() {
+        return Ok(Json::Null);
+    }
+
+    let json = try!(Json::from_str(&resp_body).map_err(|e| Outcome::TwitterAPIError(format!("{} (\"{}\")", e, resp_body))));
+
+    let errors = json.find("errors").and_then(Json::as_array);
+    if success {
+        Ok(json)
+    } else if resp.status == StatusCode::TooManyRequests ||
+              errors.map(|errors| errors.iter().any(|e| e.find("code").and_then(Json::as_i64) == Some(88))).unwrap_or(false) {
+        Err(Outcome::RateLimited {
+            desc: desc,
+            reset: network::rate_limit_reset(&resp.headers, UTC::now().timestamp()),
+        })
+    } else {
+        Err(Outcome::TwitterAPIError(match errors {
+            Some(errors) => {
+                errors.iter()
+                    .map(|e| {
+                        let message = e.find("message").and_then(Json::as_string).unwrap_or("unknown error");
+                        match e.find("code").and_then(Json::as_i64) {
+                            Some(code) => format!("{} (code {})", message, code),
+                            None => message.to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+            // The v2 API's problem details
+            None if json.find("detail").is_some() => json.find("detail").and_then(Json::as_string).unwrap_or("unknown error").to_string(),
+            None => format!("{} (\"{}\")", resp.status, resp_body),
+        }))
+    }
+}
+
+/// Send the request to an OAuth endpoint, whose responses are form-encoded instead.
+fn post_form(uri: &str, params: &[(&str, &str)], app: &Token, access: &Token) -> Result<BTreeMap<String, String>, Outcome> {
+    let body = params.iter()
+        .map(|&(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let desc = format!("sending a request to {}", uri);
+    let mut resp = try!(network::client()
+        .post(uri)
+        .header(ContentType::form_url_encoded())
+        .header(Authorization(authorization("POST", uri, params, app, access)))
+        .body(body.as_bytes())
+        .send()
+        .map_err(|e| network::request_error(&desc, e, Outcome::TwitterAPIError)));
+
+    let mut resp_body = String::new();
+    try!(resp.read_to_string(&mut resp_body).map_err(|e| network::io_error(&desc, e, Outcome::TwitterAPIError)));
+    if let Some(skew) = clock_skew(&resp.headers, UTC::now().timestamp()) {
+        correct_clock(skew);
+    }
+
+    if resp.status.is_success() {
+        Ok(form_urlencoded::parse(resp_body.as_bytes()).into_owned().collect())
+    } else {
+        Err(Outcome::TwitterAPIError(format!("{} (\"{}\")", resp.status, resp_body.trim())))
+    }
+}
+
+fn raw_header<'h>(headers: &'h Headers, name: &str) -> Option<&'h str> {
+    headers.get_raw(name).and_then(|h| h.first()).and_then(|h| str::from_utf8(h).ok())
+}
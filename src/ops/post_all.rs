@@ -0,0 +1,188 @@
+//! This module contains the functions used only by the `post-all` subsystem.
+//!
+//! The flow of the `post-all` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::start_daemon::verify()
+//! |> ops::Apps::read()
+//! |> ops::Config::read()
+//! |> ops::preflight::run()
+//! |> ops::backend::sinks()
+//! |> ops::transform::pipeline()
+//! |> ops::User::read()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::post_all::order()
+//! |> ops::post_all::print_plan()
+//! |> ops::post_all::prompt_confirm()
+//! ```
+//!
+//! Then, for each tweet in order, as by the `start-daemon` subsystem:
+//!
+//! ```plaintext
+//! ops::start_daemon::anchor_posted()
+//! |> ops::start_daemon::thread_predecessor()
+//! |> ops::settings::Effective::apply()
+//! |> ops::transform::Pipeline::apply()
+//! |> ops::start_daemon::find_user_index_for_tweet() or ops::Sink
+//! |> ops::start_daemon::post_tweet()
+//! |> ops::start_daemon::mirror_tweet()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+//!
+//! Then:
+//!
+//! ```plaintext
+//! ops::archive::rotate()
+//! |> ops::QueuedTweet::write_with_broken()
+//! |> ops::post_all::print_summary()
+//! ```
+
+
+use self::super::super::util::table::{Column, Table};
+use self::super::super::util::{Confirmation, confirm};
+use chrono::{DateTime, FixedOffset};
+use std::io::{BufRead, Write};
+use self::super::QueuedTweet;
+use self::super::l10n::{format_datetime, tr};
+
+
+/// Get the indices of the unposted, unpaused tweets scheduled up to the specified time, in the order to post them in.
+///
+/// Tweets are posted in the order they're scheduled in, but never before the tweet before them in their thread, or the tweet
+/// they're scheduled after. Tweets waiting for a tweet that isn't going to be posted are left out.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{post_all, QueuedTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let tweets = vec![QueuedTweet { time: tweet.time + Duration::days(2), ..tweet.clone() },
+///                   QueuedTweet { time: tweet.time + Duration::days(1), ..tweet.clone() },
+///                   tweet.clone()];
+///
+/// assert_eq!(post_all::order(&tweets, tweet.time + Duration::days(1)), vec![2, 1]);
+/// # }
+/// ```
+pub fn order(tweets: &[QueuedTweet], up_to: DateTime<FixedOffset>) -> Vec<usize> {
+    let mut pending: Vec<_> = (0..tweets.len())
+        .filter(|&i| tweets[i].id.is_none() && !tweets[i].paused && tweets[i].blocked.is_none() && tweets[i].time <= up_to)
+        .collect();
+    pending.sort_by_key(|&i| (tweets[i].time, i));
+
+    let mut posted: Vec<_> = tweets.iter().map(|t| t.id.is_some()).collect();
+    let mut ordered = vec![];
+    while let Some(p) = pending.iter().position(|&i| ready(tweets, i, &posted)) {
+        let i = pending.remove(p);
+        posted[i] = true;
+        ordered.push(i);
+    }
+    ordered
+}
+
+/// Print a table of the tweets at the specified indices, in order, that are going to be posted, fit in the specified width if
+/// possible.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{post_all, QueuedTweet};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// post_all::print_plan(&mut out, &[tweet], &[0], DateTime::parse_from_rfc3339("2016-09-11T00:00:00+02:00").unwrap(), Some(60));
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "1 tweet scheduled up to 2016-09-11T00:00:00+02:00 is going to be posted now:\n\
+///             Scheduled                  Author          Content\n\
+///             2016-09-10T12:00:00+02:00  nabijaczleweli  Abolish the bour…\n");
+/// # }
+/// ```
+pub fn print_plan<W: Write>(output: &mut W, tweets: &[QueuedTweet], order: &[usize], up_to: DateTime<FixedOffset>, max_width: Option<usize>) {
+    writeln!(output,
+             "{} tweet{} scheduled up to {} {} going to be posted now:",
+             order.len(),
+             if order.len() == 1 { "" } else { "s" },
+             format_datetime(&up_to),
+             if order.len() == 1 { "is" } else { "are" })
+        .unwrap();
+
+    let mut table = Table::new(vec![Column::new("Scheduled"), Column::new("Author"), Column::new("Content").shrinkable()]);
+    table.max_width = max_width;
+    for &i in order {
+        table.row(vec![format_datetime(&tweets[i].time), tweets[i].author.clone(), tweets[i].content.clone()]);
+    }
+    table.write(output);
+}
+
+/// Ask the user whether to post the tweets, unless told how to answer.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::post_all;
+/// # use tweetr::util::Confirmation;
+/// # use std::io::BufReader;
+/// assert!(post_all::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new()));
+/// assert!(!post_all::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()));
+/// assert!(post_all::prompt_confirm(Confirmation::Yes, &mut BufReader::new(b"" as &[u8]), &mut Vec::new()));
+/// ```
+pub fn prompt_confirm<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W) -> bool {
+    confirm(confirmation, input, output, &tr("prompt_post_all", &[]))
+}
+
+/// Print how many of the specified amount of tweets were posted, and whether posting stopped at the rate limits.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::post_all;
+/// let mut out = Vec::new();
+/// post_all::print_summary(&mut out, 3, 40, true);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Posted 3 of 40 tweets, stopped at the rate limits, post the rest later.\n");
+/// ```
+pub fn print_summary<W: Write>(output: &mut W, posted: usize, total: usize, rate_limited: bool) {
+    writeln!(output,
+             "Posted {} of {} tweet{}{}.",
+             posted,
+             total,
+             if total == 1 { "" } else { "s" },
+             if rate_limited {
+                 ", stopped at the rate limits, post the rest later"
+             } else {
+                 ""
+             })
+        .unwrap();
+}
+
+
+fn ready(tweets: &[QueuedTweet], i: usize, posted: &[bool]) -> bool {
+    let anchor = match tweets[i].after {
+        Some(ref anchor) => tweets.iter().position(|t| t.name.as_ref() == Some(&anchor.name)).map(|j| posted[j]).unwrap_or(true),
+        None => true,
+    };
+    let thread = match tweets[i].thread {
+        Some(ref thread) => tweets[..i].iter().rposition(|t| t.thread.as_ref() == Some(thread)).map(|j| posted[j]).unwrap_or(true),
+        None => true,
+    };
+
+    anchor && thread
+}
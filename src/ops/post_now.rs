@@ -0,0 +1,192 @@
+//! This module contains the functions used only by the `post-now` subsystem.
+//!
+//! The flow of the `post-now` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::post_now::breaking()
+//! |> ops::queue_tweet::tweets_path()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::post_now::prompt_confirm()
+//! |> ops::post_now::preempt()
+//! |> ops::post_now::print_shifts()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+//!
+//! A breaking tweet is queued for right now, without jitter, and goes before everything else its account has scheduled:
+//! the account's unposted tweets due before it, or less than the minimum interval (the `collision_window`, see
+//! `Config::collision_window()`) after it, are pushed back, each to the minimum interval after the one before it,
+//! until one is already far enough away. Tweets scheduled after others move along with them, and paused tweets and tweets
+//! held back after a failure aren't moved.
+//!
+//! Every moved tweet is journalled, as with any other change to the queue.
+
+
+use chrono::{DateTime, Duration, FixedOffset};
+use self::super::super::util::{Confirmation, confirm};
+use self::super::l10n::{format_datetime, tr};
+use self::super::settings::Settings;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use self::super::QueuedTweet;
+use std::cmp;
+
+
+/// Make a breaking tweet with the specified content, to be posted as the specified account at the specified time, without
+/// jitter.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::post_now;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// let tweet = post_now::breaking("brandacct", "We're down, we're on it", now);
+///
+/// assert_eq!(tweet.author, "brandacct");
+/// assert_eq!(tweet.content, "We're down, we're on it");
+/// assert_eq!(tweet.time, now);
+/// assert_eq!(tweet.settings.jitter, Some(0));
+/// # }
+/// ```
+pub fn breaking(author: &str, content: &str, now: DateTime<FixedOffset>) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: now,
+        content: content.to_string(),
+        media: vec![],
+        thread: None,
+        in_reply_to: None,
+        extra_params: BTreeMap::new(),
+        variants: BTreeMap::new(),
+        content_warning: None,
+        coordinates: None,
+        place_id: None,
+        reply_settings: None,
+        name: None,
+        after: None,
+        paused: false,
+        blocked: None,
+        settings: Settings { jitter: Some(0), ..Settings::default() },
+        time_posted: None,
+        id: None,
+        app: None,
+        retries: 0,
+    }
+}
+
+/// Ask the user whether to post the specified breaking tweet now, unless told how to answer.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::post_now;
+/// # use tweetr::util::Confirmation;
+/// # use std::io::BufReader;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// let tweet = post_now::breaking("brandacct", "We're down, we're on it", now);
+///
+/// let mut out = Vec::new();
+/// assert!(post_now::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"y\n" as &[u8]), &mut out, &tweet));
+/// assert_eq!(String::from_utf8(out).unwrap(), "Post \"We're down, we're on it\" as brandacct now? [y/N]: \n");
+/// assert!(!post_now::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"\n" as &[u8]), &mut Vec::new(), &tweet));
+/// assert!(post_now::prompt_confirm(Confirmation::Yes, &mut BufReader::new(b"" as &[u8]), &mut Vec::new(), &tweet));
+/// # }
+/// ```
+pub fn prompt_confirm<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W, tweet: &QueuedTweet) -> bool {
+    confirm(confirmation, input, output, &tr("prompt_post_now", &[("content", &tweet.content), ("author", &tweet.author)]))
+}
+
+/// Push the tweets by the specified breaking tweet's author out of its way, so that it's posted first and the rest
+/// follow at least the specified interval (but no less than a second) apart.
+///
+/// Returns the indices of the moved tweets, along with their previous times.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::post_now;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// let scheduled = |author: &str, minutes: i64| {
+///     let mut tweet = post_now::breaking(author, "Scheduled", now + Duration::minutes(minutes));
+///     tweet.settings.jitter = None;
+///     tweet
+/// };
+/// let mut tweets = vec![scheduled("brandacct", 2), scheduled("brandacct", 8), scheduled("brandacct", 60), scheduled("other", 1)];
+///
+/// assert_eq!(post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10)),
+///            vec![(0, now + Duration::minutes(2)), (1, now + Duration::minutes(8))]);
+/// assert_eq!(tweets[0].time, now + Duration::minutes(10));
+/// assert_eq!(tweets[1].time, now + Duration::minutes(20));
+/// assert_eq!(tweets[2].time, now + Duration::minutes(60));
+/// assert_eq!(tweets[3].time, now + Duration::minutes(1));
+/// # }
+/// ```
+pub fn preempt(tweets: &mut [QueuedTweet], breaking: &QueuedTweet, interval: Duration) -> Vec<(usize, DateTime<FixedOffset>)> {
+    let interval = cmp::max(interval, Duration::seconds(1));
+    let before = tweets.to_vec();
+
+    let mut in_the_way: Vec<_> = tweets.iter()
+        .enumerate()
+        .filter(|&(_, t)| t.author == breaking.author && t.id.is_none() && t.after.is_none() && !t.paused && t.blocked.is_none())
+        .map(|(i, _)| i)
+        .collect();
+    in_the_way.sort_by_key(|&i| tweets[i].time);
+
+    let mut previous = breaking.time;
+    for i in in_the_way {
+        if tweets[i].time >= previous + interval {
+            break;
+        }
+        previous = previous + interval;
+        tweets[i].time = previous;
+    }
+    // Errors in relative scheduling were already there, and are reported elsewhere
+    QueuedTweet::schedule(tweets, &[]);
+
+    before.iter().zip(tweets.iter()).enumerate().filter(|&(_, (old, new))| old.time != new.time).map(|(i, (old, _))| (i, old.time)).collect()
+}
+
+/// Print which of the specified tweets were moved out of the way, and from when to when.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::post_now;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// let mut tweets = vec![post_now::breaking("brandacct", "Scheduled", now + Duration::minutes(5))];
+/// let shifts = post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10));
+///
+/// let mut out = Vec::new();
+/// post_now::print_shifts(&mut out, &tweets, &shifts);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Moved \"Scheduled\" by brandacct from 2016-09-10T12:05:00+02:00 to 2016-09-10T12:10:00+02:00.\n");
+/// # }
+/// ```
+pub fn print_shifts<W: Write>(output: &mut W, tweets: &[QueuedTweet], shifts: &[(usize, DateTime<FixedOffset>)]) {
+    for &(i, from) in shifts {
+        writeln!(output,
+                 "Moved \"{}\" by {} from {} to {}.",
+                 tweets[i].content,
+                 tweets[i].author,
+                 format_datetime(&from),
+                 format_datetime(&tweets[i].time))
+            .unwrap();
+    }
+}
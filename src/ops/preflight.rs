@@ -0,0 +1,116 @@
+//! Checks run before starting subsystems, so that they fail right away instead of midway through.
+//!
+//! The `start-daemon` subsystem runs them during its initialisation, after reading the configuration.
+
+
+use std::fs::{File, OpenOptions};
+use self::super::super::Outcome;
+use std::path::Path;
+
+
+/// How a subsystem is going to access a file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Access {
+    /// The file is only read.
+    Read,
+    /// The file is read and written back.
+    ReadWrite,
+}
+
+
+/// Check whether the specified files in the specified directory can be accessed as specified.
+///
+/// Returns a description of each problem. Nonexistant files are skipped, as each subsystem checks for them itself.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::preflight::{self, Access};
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-preflight-check_access-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("tweets.toml")).unwrap();
+/// let _ = fs::remove_file(tf.join("config.toml"));
+///
+/// assert!(preflight::check_access(&tf, &[("tweets.toml", Access::ReadWrite), ("config.toml", Access::Read)]).is_empty());
+/// ```
+pub fn check_access(config_dir: &Path, files: &[(&str, Access)]) -> Vec<String> {
+    files.iter()
+        .flat_map(|&(fname, access)| {
+            let path = config_dir.join(fname);
+            if !path.exists() {
+                return None;
+            }
+
+            if let Err(e) = File::open(&path) {
+                return Some(format!("{} can't be read: {}", path.display(), e));
+            }
+            if access == Access::ReadWrite {
+                // Appending nothing doesn't change the file, but fails the same way writing would
+                if let Err(e) = OpenOptions::new().append(true).open(&path) {
+                    return Some(format!("{} can't be written: {}", path.display(), e));
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Check whether the specified file, containing the users' access tokens, is readable by everyone.
+///
+/// Returns a description of the problem if it is. Never fails on platforms without Unix permissions.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::preflight;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-preflight-insecure_users_file-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// let _ = fs::remove_file(tf.join("users.toml"));
+///
+/// assert_eq!(preflight::insecure_users_file(&tf.join("users.toml")), None);
+/// ```
+pub fn insecure_users_file(p: &Path) -> Option<String> {
+    world_readable(p).map(|mode| format!("{} is readable by everyone (mode {:o}), exposing the users' access tokens", p.display(), mode))
+}
+
+/// Run all checks on the specified files in the specified directory.
+///
+/// Returns the warnings, or fails with all problems found. The users file being readable by everyone is a warning, unless
+/// `refuse_insecure` is set.
+pub fn run(config_dir: &Path, files: &[(&str, Access)], refuse_insecure: bool) -> Result<Vec<String>, Outcome> {
+    let mut problems = check_access(config_dir, files);
+    let mut warnings = vec![];
+
+    if files.iter().any(|&(fname, _)| fname == "users.toml") {
+        if let Some(insecure) = insecure_users_file(&config_dir.join("users.toml")) {
+            if refuse_insecure {
+                problems.push(insecure);
+            } else {
+                warnings.push(insecure);
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(Outcome::PreflightFailed(problems))
+    }
+}
+
+
+#[cfg(unix)]
+fn world_readable(p: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    p.metadata().ok().map(|m| m.permissions().mode() & 0o777).and_then(|mode| if mode & 0o004 != 0 { Some(mode) } else { None })
+}
+
+#[cfg(not(unix))]
+fn world_readable(_: &Path) -> Option<u32> {
+    None
+}
@@ -0,0 +1,487 @@
+//! This module contains the functions used only by the `prune-posted` subsystem.
+//!
+//! The flow of the `prune-posted` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::prune_posted::verify()
+//! |> ops::Apps::read()
+//! |> ops::User::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::export_posted::posted()
+//! |> ops::prune_posted::read_pruned()
+//! |> ops::prune_posted::read_metrics(), with --metrics
+//! |> ops::prune_posted::candidates()
+//! |> ops::prune_posted::print_plan()
+//! |> ops::prune_posted::prompt_confirm(), unless --dry-run
+//! ```
+//!
+//! Then, for each tweet:
+//!
+//! ```plaintext
+//! ops::start_daemon::find_user_index_for_tweet()
+//! |> ops::prune_posted::delete()
+//! |> ops::prune_posted::write_pruned()
+//! ```
+//!
+//! Then:
+//!
+//! ```plaintext
+//! ops::prune_posted::print_summary()
+//! ```
+//!
+//! Deleted tweets stay in the archive, for exporting, and their IDs are recorded in `pruned.toml` in the configuration
+//! directory, so they're not deleted again.
+
+
+use self::super::super::util::table::{Column, Table};
+use self::super::super::util::encoding::{self, Encoding};
+use self::super::super::util::{Confirmation, confirm};
+use self::super::{QueuedTweet, User, read_toml_file, replace_file, verify_file};
+use self::super::l10n::{format_datetime, tr};
+use self::super::super::Outcome;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, Read, Write};
+use std::str::FromStr;
+use std::fs::File;
+use toml::encode_str;
+#[cfg(feature = "network")]
+use self::super::{network, oauth};
+#[cfg(feature = "network")]
+use egg_mode::Token;
+
+
+/// The API endpoint tweets are deleted at, to be suffixed with the tweet's ID and `.json`.
+#[cfg(feature = "network")]
+pub static STATUSES_DESTROY_URL: &'static str = "https://api.twitter.com/1.1/statuses/destroy/";
+
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+struct Pruned {
+    ids: Vec<i64>,
+}
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `prune-posted`
+/// subsystem.
+///
+/// The return value contains either the paths to the files containing the global app configuration and the users
+/// or why getting them failed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-prune-posted-verify");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap();
+///
+/// assert_eq!(prune_posted::verify(&("$TEMP/ops-prune-posted-verify".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::NoUsers)));
+///
+/// File::create(tf.join("users.toml")).unwrap();
+/// assert_eq!(prune_posted::verify(&("$TEMP/ops-prune-posted-verify".to_string(), tf.clone())),
+///            Ok((tf.join("app.toml"), tf.join("users.toml"))));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
+    let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
+    let users = try!(verify_file("users.toml", true, config_dir, false, "add-user"));
+
+    Ok((app, users))
+}
+
+/// Get the path to the file recording the IDs of the deleted tweets in the specified configuration directory.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-prune-posted-pruned_path");
+/// assert_eq!(prune_posted::pruned_path(&tf), tf.join("pruned.toml"));
+/// ```
+pub fn pruned_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("pruned.toml")
+}
+
+/// Read the IDs of the deleted tweets from the specified file, none if it doesn't exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// # use std::collections::BTreeSet;
+/// # use std::env::temp_dir;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-prune-posted-read_pruned");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = prune_posted::pruned_path(&tf);
+///
+/// assert_eq!(prune_posted::read_pruned(&tf), Ok(BTreeSet::new()));
+///
+/// prune_posted::write_pruned(&vec![774560457755590656].into_iter().collect(), &tf).unwrap();
+/// assert_eq!(prune_posted::read_pruned(&tf), Ok(vec![774560457755590656].into_iter().collect()));
+/// ```
+pub fn read_pruned(p: &Path) -> Result<BTreeSet<i64>, Outcome> {
+    if !p.exists() {
+        return Ok(BTreeSet::new());
+    }
+
+    match read_toml_file::<Pruned>(p, "deleted tweets") {
+        Ok(pruned) => Ok(pruned.ids.into_iter().collect()),
+        Err(Some(out)) => Err(out),
+        Err(None) => {
+            Err(Outcome::FileParsingFailed {
+                desc: "deleted tweets",
+                errors: vec!["error: couldn't read the file".to_string()],
+            })
+        }
+    }
+}
+
+/// Save the IDs of the deleted tweets to the specified file.
+///
+/// The file is replaced at once, so it's never seen partially written.
+pub fn write_pruned(ids: &BTreeSet<i64>, p: &Path) -> Result<(), Outcome> {
+    replace_file(p, &encode_str(&Pruned { ids: ids.iter().cloned().collect() }))
+}
+
+/// Read how many engagements each tweet got from the specified CSV file.
+///
+/// See `parse_metrics()` for the format.
+///
+/// The file is converted from the specified encoding, or the detected one, if none, see `util::encoding::decode()`;
+/// the rows with invalid sequences in it are all reported at once.
+pub fn read_metrics(p: &Path, encoding: Option<Encoding>) -> Result<BTreeMap<i64, u64>, Outcome> {
+    let mut bytes = vec![];
+    try!(File::open(p).and_then(|mut f| f.read_to_end(&mut bytes)).map_err(|e| Outcome::from_io("read", p, e)));
+
+    let (buf, _, invalid) = encoding::decode_lossy(&bytes, encoding);
+    if !invalid.is_empty() {
+        return Err(Outcome::FileParsingFailed {
+            desc: "metrics",
+            errors: invalid.iter().map(|i| format!("error: row {}: {}", csv_row_of_line(&buf, i.line), i.description())).collect(),
+        });
+    }
+
+    parse_metrics(&buf).map_err(|e| {
+        Outcome::FileParsingFailed {
+            desc: "metrics",
+            errors: vec![format!("error: {}", e)],
+        }
+    })
+}
+
+/// Get the 1-based row of the specified CSV the specified 1-based line is in, with quoted cells spanning lines.
+fn csv_row_of_line(csv: &str, line: usize) -> usize {
+    let (mut row, mut cur_line, mut quoted) = (1, 1, false);
+    for c in csv.chars() {
+        if cur_line == line {
+            break;
+        }
+        match c {
+            '"' => quoted = !quoted,
+            '\n' => {
+                cur_line += 1;
+                if !quoted {
+                    row += 1;
+                }
+            }
+            _ => (),
+        }
+    }
+    row
+}
+
+/// Parse how many engagements each tweet got from the specified RFC 4180 CSV.
+///
+/// The first row names the columns: the tweet's ID is taken from the `id` or `Tweet id` one, as exported by
+/// `export-posted` and by Twitter's analytics, respectively, and the amount of engagements from the `engagements` or
+/// `engagement` one, in any case. Other columns are ignored.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// let metrics = prune_posted::parse_metrics("\"Tweet id\",\"Tweet text\",\"impressions\",\"engagements\"\n\
+///                                            \"774560457755590656\",\"Capitalism,\nbad\",\"1200.0\",\"31.0\"\n\
+///                                            \"774560460511248384\",\"Abolish\",\"8\",\"0\"\n")
+///     .unwrap();
+/// assert_eq!(metrics.get(&774560457755590656), Some(&31));
+/// assert_eq!(metrics.get(&774560460511248384), Some(&0));
+///
+/// assert_eq!(prune_posted::parse_metrics("id,likes\n774560457755590656,3\n"),
+///            Err("no \"engagements\" column".to_string()));
+/// assert_eq!(prune_posted::parse_metrics("id,engagements\n774560457755590656,many\n"),
+///            Err("row 2: \"many\" is not a valid amount of engagements".to_string()));
+/// ```
+pub fn parse_metrics(csv: &str) -> Result<BTreeMap<i64, u64>, String> {
+    let mut rows = csv_rows(csv).into_iter();
+    let header: Vec<_> = rows.next().unwrap_or_default().into_iter().map(|c| c.trim().to_lowercase()).collect();
+    let column = |names: &[&str], desc: &str| header.iter().position(|c| names.contains(&&c[..])).ok_or_else(|| format!("no \"{}\" column", desc));
+    let id_col = try!(column(&["id", "tweet id"], "id"));
+    let engagement_col = try!(column(&["engagements", "engagement"], "engagements"));
+
+    let mut metrics = BTreeMap::new();
+    for (i, row) in rows.enumerate().filter(|&(_, ref r)| r.iter().any(|c| !c.is_empty())) {
+        let cell = |col: usize| row.get(col).map(|c| c.trim()).unwrap_or("");
+        let id = try!(i64::from_str(cell(id_col)).map_err(|_| format!("row {}: \"{}\" is not a valid tweet ID", i + 2, cell(id_col))));
+        // Twitter's analytics exports whole numbers with a fraction
+        let engagements = try!(f64::from_str(cell(engagement_col))
+            .ok()
+            .and_then(|e| if e >= 0.0 && e.fract() == 0.0 { Some(e as u64) } else { None })
+            .ok_or_else(|| format!("row {}: \"{}\" is not a valid amount of engagements", i + 2, cell(engagement_col))));
+        metrics.insert(id, engagements);
+    }
+    Ok(metrics)
+}
+
+/// Get the time the specified amount of seconds before the specified one, before which posted tweets are deleted.
+///
+/// Returns `None` if that'd be before the earliest representable time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::prune_posted;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(prune_posted::cutoff(now, 60 * 60 * 24),
+///            Some(DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap()));
+/// assert_eq!(prune_posted::cutoff(now, 1000000000000 * 60 * 60 * 24 * 7), None);
+/// # }
+/// ```
+pub fn cutoff(now: DateTime<FixedOffset>, older_than: i64) -> Option<DateTime<FixedOffset>> {
+    older_than.checked_mul(1000).and_then(|ms| now.checked_sub(Duration::milliseconds(ms)))
+}
+
+/// Get the indices of the specified posted tweets to delete, oldest first.
+///
+/// Those are the tweets posted to Twitter by one of the specified users, or only the specified one, before the specified
+/// time, that weren't already deleted. If a minimum amount of engagements is specified, tweets that got at least that many
+/// according to the specified metrics, or aren't in them, are kept.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{prune_posted, QueuedTweet, User};
+/// # use std::collections::{BTreeMap, BTreeSet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:01+02:00").unwrap()),
+///     id: Some(774560457755590656),
+///     ..Default::default()
+/// };
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "key".to_string(),
+///     access_token_secret: "secret".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// let later = tweet.time_posted.unwrap() + Duration::days(1);
+///
+/// assert_eq!(prune_posted::candidates(&[tweet.clone()], &[user.clone()], &BTreeSet::new(), later, None, None), vec![0]);
+/// assert_eq!(prune_posted::candidates(&[tweet.clone()], &[user.clone()], &BTreeSet::new(), tweet.time, None, None),
+///            Vec::<usize>::new());
+/// assert_eq!(prune_posted::candidates(&[tweet.clone()], &[user.clone()], &vec![774560457755590656].into_iter().collect(), later,
+///                                     None, None),
+///            Vec::<usize>::new());
+///
+/// let metrics = vec![(774560457755590656, 10)].into_iter().collect();
+/// assert_eq!(prune_posted::candidates(&[tweet.clone()], &[user.clone()], &BTreeSet::new(), later, None, Some((&metrics, 11))),
+///            vec![0]);
+/// assert_eq!(prune_posted::candidates(&[tweet.clone()], &[user.clone()], &BTreeSet::new(), later, None, Some((&metrics, 10))),
+///            Vec::<usize>::new());
+/// # }
+/// ```
+pub fn candidates(tweets: &[QueuedTweet], users: &[User], pruned: &BTreeSet<i64>, before: DateTime<FixedOffset>, account: Option<&str>,
+                  min_engagement: Option<(&BTreeMap<i64, u64>, u64)>)
+                  -> Vec<usize> {
+    let mut indices: Vec<_> = tweets.iter()
+        .enumerate()
+        .filter(|&(_, t)| {
+            let (id, time_posted) = match (t.id, t.time_posted) {
+                (Some(id), Some(time_posted)) => (id, time_posted),
+                _ => return false,
+            };
+
+            time_posted < before && !pruned.contains(&id) && users.iter().any(|u| u.name == t.author) &&
+            account.map(|a| a == t.author).unwrap_or(true) &&
+            min_engagement.map(|(metrics, min)| metrics.get(&id).map(|&e| e < min).unwrap_or(false)).unwrap_or(true)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    indices.sort_by_key(|&i| tweets[i].time_posted);
+    indices
+}
+
+/// Print a table of the specified tweets about to be deleted, fit in the specified width if possible.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{prune_posted, QueuedTweet};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:01+02:00").unwrap()),
+///     id: Some(774560457755590656),
+///     ..Default::default()
+/// };
+///
+/// let mut out = Vec::new();
+/// prune_posted::print_plan(&mut out, &[tweet], &[0], DateTime::parse_from_rfc3339("2016-12-09T00:00:00+01:00").unwrap(), None);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "1 tweet posted before 2016-12-09T00:00:00+01:00 is going to be deleted from Twitter:\n\
+///             Posted                     Author                          ID  Content\n\
+///             2016-09-10T12:00:01+02:00  nabijaczleweli  774560457755590656  Abolish the bourgeoisie\n");
+/// # }
+/// ```
+pub fn print_plan<W: Write>(output: &mut W, tweets: &[QueuedTweet], indices: &[usize], before: DateTime<FixedOffset>, max_width: Option<usize>) {
+    writeln!(output,
+             "{} tweet{} posted before {} {} going to be deleted from Twitter:",
+             indices.len(),
+             if indices.len() == 1 { "" } else { "s" },
+             format_datetime(&before),
+             if indices.len() == 1 { "is" } else { "are" })
+        .unwrap();
+
+    let mut table = Table::new(vec![Column::new("Posted"), Column::new("Author"), Column::new("ID").right(), Column::new("Content").shrinkable()]);
+    table.max_width = max_width;
+    for &i in indices {
+        table.row(vec![format_datetime(&tweets[i].time_posted.unwrap()),
+                       tweets[i].author.clone(),
+                       tweets[i].id.unwrap().to_string(),
+                       tweets[i].content.clone()]);
+    }
+    table.write(output);
+}
+
+/// Ask the user whether to delete the tweets, unless told how to answer.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// # use tweetr::util::Confirmation;
+/// # use std::io::BufReader;
+/// assert!(prune_posted::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new()));
+/// assert!(!prune_posted::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()));
+/// assert!(!prune_posted::prompt_confirm(Confirmation::Refuse, &mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new()));
+/// ```
+pub fn prompt_confirm<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W) -> bool {
+    confirm(confirmation, input, output, &tr("prompt_prune_posted", &[]))
+}
+
+/// Delete the tweet with the specified ID from Twitter on behalf of the specified user of the specified application.
+///
+/// Waits for the rate limits to reset if they're hit, see `network::request()`.
+///
+/// Returns whether the tweet was deleted, or had already been, e.g. by hand.
+#[cfg(feature = "network")]
+pub fn delete(id: i64, user: &User, app: &Token) -> Result<bool, Outcome> {
+    let desc = format!("deleting tweet {} as @{}", id, user.name);
+    let url = format!("{}{}.json", STATUSES_DESTROY_URL, id);
+    let user = user.clone();
+    let app = network::owned_token(app);
+    network::request(&desc, move || match oauth::post(&url, &BTreeMap::new(), &app, &user.raw_token()) {
+        Ok(_) => Ok(true),
+        // No status found with that ID
+        Err(Outcome::TwitterAPIError(ref e)) if e.contains("(code 144)") => Ok(false),
+        Err(out) => Err(out),
+    })
+}
+
+/// Print how many of the specified amount of tweets were deleted, and whether deleting stopped at the rate limits.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::prune_posted;
+/// let mut out = Vec::new();
+/// prune_posted::print_summary(&mut out, 3, 40, true);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Deleted 3 of 40 tweets, stopped at the rate limits, delete the rest later.\n");
+/// ```
+pub fn print_summary<W: Write>(output: &mut W, deleted: usize, total: usize, rate_limited: bool) {
+    writeln!(output,
+             "Deleted {} of {} tweet{}{}.",
+             deleted,
+             total,
+             if total == 1 { "" } else { "s" },
+             if rate_limited {
+                 ", stopped at the rate limits, delete the rest later"
+             } else {
+                 ""
+             })
+        .unwrap();
+}
+
+
+/// Split the specified RFC 4180 CSV into rows of unquoted cells.
+fn csv_rows(csv: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => row.push(cell.split_off(0)),
+            ('\r', false) => (),
+            ('\n', false) => {
+                row.push(cell.split_off(0));
+                rows.push(row.split_off(0));
+            }
+            (c, _) => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    rows
+}
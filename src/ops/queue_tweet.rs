@@ -10,6 +10,18 @@
 //! |> ops::QueuedTweet::write()
 //! ```
 //!
+//! When filling in a template:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::queue_tweet::tweets_path()
+//! |> ops::Template::read()
+//! |> ops::queue_tweet::template()
+//! |> ops::queue_tweet::get_templated_tweet()
+//! |> ops::QueuedTweet::read()
+//! |> ops::QueuedTweet::write()
+//! ```
+//!
 //! When queueing from file:
 //!
 //! ```plaintext
@@ -19,13 +31,157 @@
 //! |> ops::QueuedTweet::read()
 //! |> ops::QueuedTweet::write()
 //! ```
+//!
+//! Unless disabled, each tweet is validated before being queued:
+//!
+//! ```plaintext
+//! ops::queue_tweet::get_tweet()
+//! |> ops::queue_tweet::check_links()
+//! |> ops::queue_tweet::misspelt_words()
+//! |> ops::queue_tweet::invisible_chars()
+//! ```
+//!
+//! When checking mentions, the @mentioned accounts are then looked up, too:
+//!
+//! ```plaintext
+//! ops::queue_tweet::find_mentions()
+//! |> ops::queue_tweet::check_mentions()
+//! ```
+//!
+//! When queueing interactively, invisible characters can then be stripped:
+//!
+//! ```plaintext
+//! ops::queue_tweet::invisible_chars()
+//! |> ops::queue_tweet::prompt_strip()
+//! |> ops::queue_tweet::strip_invisible()
+//! ```
+//!
+//! When automatically splitting, each tweet is also split before being queued:
+//!
+//! ```plaintext
+//! ops::queue_tweet::get_tweet()
+//! |> ops::queue_tweet::auto_split()
+//! ```
+//!
+//! When geo-tagging, the location is prompted for right after each tweet:
+//!
+//! ```plaintext
+//! ops::queue_tweet::get_tweet()
+//! |> ops::queue_tweet::get_location()
+//! ```
+//!
+//! Each tweet is then checked against the queue for tweets by the same account scheduled too close to it:
+//!
+//! ```plaintext
+//! ops::queue_tweet::collisions()
+//! |> ops::queue_tweet::nearest_free_slot()
+//! |> ops::queue_tweet::print_collisions()
+//! |> ops::queue_tweet::prompt_shift()
+//! ```
+//!
+//! And against the queue and the archive for duplicates of recent tweets:
+//!
+//! ```plaintext
+//! ops::archive::read_since()
+//! |> ops::queue_tweet::duplicates()
+//! |> ops::queue_tweet::print_duplicates()
+//! ```
+//!
+//! When only showing what loading tweets from a file would queue, all of the above are gathered instead of printed:
+//!
+//! ```plaintext
+//! ops::QueuedTweet::read()
+//! |> ops::schedule::within_horizon()
+//! |> ops::queue_tweet::check_links()
+//! |> ops::queue_tweet::misspelt_words()
+//! |> ops::queue_tweet::invisible_chars()
+//! |> ops::queue_tweet::duplicates()
+//! |> ops::queue_tweet::print_import_preview()
+//! ```
 
-use self::super::super::util::{prompt_any_len, prompt_nonzero_len, prompt_multiline, parse_relative_time};
-use chrono::{ParseResult, FixedOffset, DateTime, Duration, Local};
+use self::super::super::util::{TWEET_MAX_LENGTH, RelativeTimeError, prompt_any_len, prompt_nonzero_len, prompt_multiline, parse_relative_time,
+                               parse_unix_time, MultilineOptions, Confirmation, format_approximate_time, tweet_length, confirm_or, mul_str,
+                               shell};
+use chrono::{FixedOffset, DateTime, Duration, Local, TimeZone};
+#[cfg(feature = "network")]
+use hyper::client::RedirectPolicy;
+use std::io::{BufRead, BufReader, Read, Write, Result as IoResult, Error as IoError, ErrorKind};
+use std::collections::{BTreeSet, BTreeMap};
 use std::path::{PathBuf, Path};
-use std::io::{BufRead, Write};
-use self::super::QueuedTweet;
+#[cfg(feature = "network")]
+use hyper::header::Location;
+use self::super::{QueuedTweet, Anchor, Coordinates, Template};
+use self::super::super::Outcome;
+use self::super::l10n::{format_datetime, tr};
+use self::super::start_daemon::fold_content_warning;
+use self::super::clock::Clock;
+#[cfg(feature = "network")]
+use self::super::network;
+use self::super::schedule;
+use self::super::settings::Settings;
+use std::str::FromStr;
+use regex::Regex;
+use std::fs::{self, File, OpenOptions};
+use std::{cmp, env, iter};
+use std::cell::Cell;
+use rand;
+#[cfg(feature = "network")]
+use hyper::Url;
+#[cfg(feature = "network")]
+use self::super::{User, oauth};
+#[cfg(feature = "network")]
+use rustc_serialize::json::Json;
+#[cfg(feature = "network")]
+use egg_mode::Token;
+
+
+/// How many redirects to follow when checking a link before giving up.
+pub const MAX_LINK_REDIRECTS: usize = 5;
 
+/// The users/lookup API endpoint, looking up up to 100 accounts at once.
+pub static USERS_LOOKUP_URL: &'static str = "https://api.twitter.com/1.1/users/lookup.json";
+
+/// The users/show API endpoint, looking up a single account, saying why it can't be if it can't be.
+pub static USERS_SHOW_URL: &'static str = "https://api.twitter.com/1.1/users/show.json";
+
+thread_local! {
+    static CONFIRMATION: Cell<Confirmation> = Cell::new(Confirmation::Ask);
+}
+
+
+/// What queueing a tweet loaded from a file would do, as shown by `print_import_preview()`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Import {
+    /// The tweet would be queued.
+    Added,
+    /// The tweet would be queued, but duplicates the specified queued or archived tweets.
+    Duplicate(Vec<QueuedTweet>),
+    /// The tweet fails validation for the specified reasons.
+    Invalid(Vec<String>),
+}
+
+
+/// Use the specified way of answering whether to queue tweets for times in the past, for all tweets subsequently prompted
+/// for on the current thread.
+///
+/// If the standard input isn't a terminal, they're queued without asking, like before it was asked.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use tweetr::util::Confirmation;
+/// # use std::io::BufReader;
+/// queue_tweet::use_confirmation(Confirmation::NotTerminal);
+/// assert!(queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
+///                                                      Test tweet\n\
+///                                                      2016-09-09T00:33:30+02:00\n" as &[u8]),
+///                                &mut Vec::new())
+///     .is_some());
+/// ```
+pub fn use_confirmation(confirmation: Confirmation) {
+    CONFIRMATION.with(|c| c.set(confirmation));
+}
 
 /// Get the path to the file containing the global tweet queue.
 ///
@@ -56,14 +212,14 @@ pub fn tweets_path(config_dir: &Path) -> PathBuf {
 /// # fn main() {
 /// assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
 ///                                   Test tweet\n\
-///                                   2016-09-09T00:33:30+02:00\n" as &[u8]),
+///                                   2016-09-09T00:33:30+02:00\n\
+///                                   y\n" as &[u8]),
 ///                                   &mut Vec::new()),
 ///            Some(QueuedTweet {
 ///                author: "tweetr_test".to_string(),
 ///                time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
 ///                content: "Test tweet".to_string(),
-///                time_posted: None,
-///                id: None,
+///                ..Default::default()
 ///            }));
 /// # }
 /// ```
@@ -76,29 +232,1292 @@ pub fn tweets_path(config_dir: &Path) -> PathBuf {
 /// assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()), None);
 /// ```
 pub fn get_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Option<QueuedTweet> {
-    prompt_any_len(input, output, "Author (or empty to finish)", |_| true).unwrap().map(|uname| {
-        let content = prompt_multiline(input, output, "Tweet content", |s| !s.trim().is_empty()).unwrap();
-        let time = prompt_nonzero_len(input,
-                                      output,
-                                      "Time to post the tweet (RFC2822, RFC3339 or relative)",
-                                      |s| DateTime::parse_from_rfc2822(s).is_ok() || DateTime::parse_from_rfc3339(s).is_ok() || parse_relative_time(s).is_ok())
-            .unwrap();
+    get_tweet_impl(input, output, true, ContentSource::Prompt)
+}
+
+/// Prompt the user for tweet data, but not the time to post it at, which is left at the current time to be filled in later.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// let tweet = queue_tweet::get_untimed_tweet(&mut BufReader::new(b"tweetr_test\n\
+///                                                                  Test tweet\n" as &[u8]),
+///                                            &mut Vec::new())
+///     .unwrap();
+/// assert_eq!(tweet.author, "tweetr_test");
+/// assert_eq!(tweet.content, "Test tweet");
+/// ```
+pub fn get_untimed_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Option<QueuedTweet> {
+    get_tweet_impl(input, output, false, ContentSource::Prompt)
+}
+
+/// Parse the time to post a tweet at, in RFC2822, RFC3339, as a Unix time (see `util::parse_unix_time()`), in the specified
+/// time's offset, or relative to the specified time (see `util::parse_relative_time()`).
+///
+/// Times that look relative or Unix, i.e. start with "in", "+" or "@", fail with what exactly is wrong with them.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::queue_tweet;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+/// assert_eq!(queue_tweet::parse_time("Fri, 09 Sep 2016 00:33:30 +0200", now), Ok(now));
+/// assert_eq!(queue_tweet::parse_time("2016-09-09T00:33:30+02:00", now), Ok(now));
+/// assert_eq!(queue_tweet::parse_time("in 2 hours", now), Ok(now + Duration::hours(2)));
+/// assert_eq!(queue_tweet::parse_time("+7200", now), Ok(now + Duration::hours(2)));
+/// assert_eq!(queue_tweet::parse_time("@1473374010", now), Ok(now));
+/// assert_eq!(queue_tweet::parse_time("tomorrow-ish", now),
+///            Err("\"tomorrow-ish\" is not an RFC2822, RFC3339, Unix or relative time".to_string()));
+/// assert_eq!(queue_tweet::parse_time("@tomorrow", now),
+///            Err("\"@tomorrow\" is not a valid Unix time: expected a whole number of seconds after \"@\"".to_string()));
+/// assert_eq!(queue_tweet::parse_time("in 5 fortnights", now),
+///            Err("\"in 5 fortnights\" is not a valid relative time: \
+///                 unknown unit \"fortnights\", expected one of seconds, minutes, hours, days or weeks".to_string()));
+/// # }
+/// ```
+pub fn parse_time(s: &str, now: DateTime<FixedOffset>) -> Result<DateTime<FixedOffset>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc2822(s).or_else(|_| DateTime::parse_from_rfc3339(s)) {
+        return Ok(time);
+    }
+    if let Some(secs) = parse_unix_time(s) {
+        return secs.and_then(|secs| now.offset().timestamp_opt(secs, 0).single().ok_or(RelativeTimeError::NumberOverflow))
+            .map_err(|e| format!("\"{}\" is not a valid Unix time: {}", s, e));
+    }
+
+    match parse_relative_time(s).and_then(|rel| schedule::later(now, rel.as_secs()).map_err(|_| RelativeTimeError::NumberOverflow)) {
+        Ok(time) => Ok(time),
+        Err(e) => {
+            if s.split_whitespace().next().map(|w| w.to_lowercase() == "in" || w.starts_with('+')).unwrap_or(false) {
+                Err(format!("\"{}\" is not a valid relative time: {}", s, e))
+            } else {
+                Err(format!("\"{}\" is not an RFC2822, RFC3339, Unix or relative time", s))
+            }
+        }
+    }
+}
+
+/// Prompt the user for the location to tag a tweet with.
+///
+/// Returns the coordinates and place ID, either of which can be left empty.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{queue_tweet, Coordinates};
+/// # use std::io::BufReader;
+/// assert_eq!(queue_tweet::get_location(&mut BufReader::new(b"52.2297\n\
+///                                      21.0122\n\
+///                                      \n" as &[u8]),
+///                                      &mut Vec::new()),
+///            (Some(Coordinates { lat: 52.2297, long: 21.0122 }), None));
+/// assert_eq!(queue_tweet::get_location(&mut BufReader::new(b"\n\
+///                                      5a110d312052166f\n" as &[u8]),
+///                                      &mut Vec::new()),
+///            (None, Some("5a110d312052166f".to_string())));
+/// ```
+pub fn get_location<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> (Option<Coordinates>, Option<String>) {
+    let coordinates = prompt_any_len(input,
+                                     output,
+                                     &tr("prompt_latitude", &[]),
+                                     |s| f64::from_str(s).ok().map(Coordinates::valid_lat).unwrap_or(false))
+        .unwrap()
+        .map(|lat| {
+            let long = prompt_nonzero_len(input,
+                                          output,
+                                          &tr("prompt_longitude", &[]),
+                                          |s| f64::from_str(s).ok().map(Coordinates::valid_long).unwrap_or(false))
+                .unwrap();
+            Coordinates::new(f64::from_str(&lat).unwrap(), f64::from_str(&long).unwrap()).unwrap()
+        });
+    let place_id = prompt_any_len(input, output, &tr("prompt_place_id", &[]), |_| true).unwrap();
+
+    writeln!(output, "").unwrap();
+    (coordinates, place_id)
+}
+
+/// Find all http(s) URLs in the specified tweet content.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::find_urls("Read https://github.com/nabijaczleweli/tweetr and http://example.com/a?b=c!"),
+///            vec!["https://github.com/nabijaczleweli/tweetr", "http://example.com/a?b=c"]);
+/// assert!(queue_tweet::find_urls("No links here, example.com doesn't count").is_empty());
+/// ```
+pub fn find_urls(content: &str) -> Vec<&str> {
+    lazy_static! {
+        static ref URL_REGEX: Regex = Regex::new(r#"(?i)https?://[^\s]*[^\s.,:;!?)'"]"#).unwrap();
+    }
+
+    URL_REGEX.find_iter(content).map(|(start, end)| &content[start..end]).collect()
+}
+
+/// Check whether all links in the specified tweet content are alive by `HEAD`ing them.
+///
+/// Returns a warning for each link that couldn't be reached, returned a non-success status, or redirected more than once.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::queue_tweet;
+/// assert!(queue_tweet::check_links("Source at https://github.com/nabijaczleweli/tweetr").is_empty());
+/// assert_eq!(queue_tweet::check_links("Dead link: https://github.com/nabijaczleweli/nonexistant").len(), 1);
+/// ```
+#[cfg(feature = "network")]
+pub fn check_links(content: &str) -> Vec<String> {
+    let mut client = network::client();
+    client.set_redirect_policy(RedirectPolicy::FollowNone);
+
+    find_urls(content)
+        .into_iter()
+        .flat_map(|url| {
+            let mut cur_url = match Url::parse(url) {
+                Ok(u) => u,
+                Err(e) => return Some(format!("Link {} is invalid: {}", url, e)),
+            };
+
+            for redirects in 0..MAX_LINK_REDIRECTS + 1 {
+                let resp = match client.head(cur_url.clone()).send() {
+                    Ok(resp) => resp,
+                    Err(e) => return Some(format!("Link {} couldn't be reached: {}", url, e)),
+                };
+
+                if resp.status.is_redirection() {
+                    match resp.headers.get::<Location>().and_then(|l| cur_url.join(l).ok()) {
+                        Some(next) => cur_url = next,
+                        None => return Some(format!("Link {} redirects to nowhere", url)),
+                    }
+                } else if !resp.status.is_success() {
+                    return Some(format!("Link {} returned {}", url, resp.status));
+                } else if redirects > 1 {
+                    return Some(format!("Link {} redirects {} times before reaching {}", url, redirects, cur_url));
+                } else {
+                    return None;
+                }
+            }
+
+            Some(format!("Link {} redirects more than {} times", url, MAX_LINK_REDIRECTS))
+        })
+        .collect()
+}
+
+/// Find the accounts @mentioned in the specified tweet content, each once, as first spelled.
+///
+/// As with Twitter, handles are up to 15 letters, digits and underscores, and `@`s right after letters, digits and some
+/// symbols, like in e-mail addresses, or right before other `@`s aren't mentions.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::find_mentions("Thanks @nabijaczleweli and @tweetr_test, cc @NabijaczLeweli!"),
+///            vec!["nabijaczleweli", "tweetr_test"]);
+/// assert_eq!(queue_tweet::find_mentions(".@tweetr_test: mail me at nabijaczleweli@gmail.com"), vec!["tweetr_test"]);
+/// assert!(queue_tweet::find_mentions("No mentions here, @ and @@ and @a_handle_way_too_long don't count").is_empty());
+/// ```
+pub fn find_mentions(content: &str) -> Vec<&str> {
+    let mut mentions: Vec<&str> = vec![];
+    let mut prev = None;
+    for (i, c) in content.char_indices() {
+        if c == '@' && prev.map(|p: char| !p.is_alphanumeric() && !"_!#$%&*@".contains(p)).unwrap_or(true) {
+            let handle = &content[i + 1..];
+            let len = handle.find(|c: char| !(c.is_ascii() && (c.is_alphanumeric() || c == '_'))).unwrap_or(handle.len());
+            let handle = &handle[..len];
+            if !handle.is_empty() && len <= 15 && !content[i + 1 + len..].starts_with('@') &&
+               !mentions.iter().any(|m| m.to_lowercase() == handle.to_lowercase()) {
+                mentions.push(handle);
+            }
+        }
+        prev = Some(c);
+    }
+    mentions
+}
+
+/// Check whether all accounts @mentioned in the specified tweet content exist by looking them up on behalf of the specified
+/// user of the specified application.
+///
+/// Returns a warning for each account that doesn't exist, which might be because it was renamed, or is suspended, or for the
+/// lookup itself if it failed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{queue_tweet, Apps, User};
+/// # use std::path::Path;
+/// let user = User::read(Path::new("users.toml")).unwrap().remove(0);
+/// let apps = Apps::read(Path::new("app.toml")).unwrap();
+/// let app = apps.get(None).unwrap().raw_token();
+/// assert!(queue_tweet::check_mentions("Thanks @nabijaczleweli!", &user, &app).is_empty());
+/// assert_eq!(queue_tweet::check_mentions("Thanks @nabijaczlewelj!", &user, &app),
+///            vec!["Mentioned account @nabijaczlewelj doesn't exist, it might've been renamed".to_string()]);
+/// ```
+#[cfg(feature = "network")]
+pub fn check_mentions(content: &str, user: &User, app: &Token) -> Vec<String> {
+    let mentions = find_mentions(content);
+    if mentions.is_empty() {
+        return vec![];
+    }
+
+    let looked_up = {
+        let mut params = BTreeMap::new();
+        params.insert("screen_name".to_string(), mentions.join(","));
+        lookup(&format!("looking up the accounts mentioned as @{}", user.name), USERS_LOOKUP_URL, params, user, app)
+    };
+    let found: Vec<_> = match looked_up {
+        Ok(Json::Array(accounts)) => {
+            accounts.iter().flat_map(|a| a.find("screen_name").and_then(Json::as_string)).map(str::to_lowercase).collect()
+        }
+        Ok(_) => return vec!["Couldn't look up the mentioned accounts: unexpected response".to_string()],
+        // No user matches for specified terms
+        Err(Outcome::TwitterAPIError(ref e)) if e.contains("(code 17)") => vec![],
+        Err(out) => return vec![format!("Couldn't look up the mentioned accounts: {}", error_text(&out))],
+    };
+
+    mentions.into_iter()
+        .filter(|m| !found.contains(&m.to_lowercase()))
+        .flat_map(|m| {
+            let mut params = BTreeMap::new();
+            params.insert("screen_name".to_string(), m.to_string());
+            match lookup(&format!("looking up @{} as @{}", m, user.name), USERS_SHOW_URL, params, user, app) {
+                Ok(_) => None,
+                Err(Outcome::TwitterAPIError(ref e)) if e.contains("(code 63)") => Some(format!("Mentioned account @{} is suspended", m)),
+                Err(Outcome::TwitterAPIError(ref e)) if e.contains("(code 50)") => {
+                    Some(format!("Mentioned account @{} doesn't exist, it might've been renamed", m))
+                }
+                Err(out) => Some(format!("Couldn't look up the mentioned account @{}: {}", m, error_text(&out))),
+            }
+        })
+        .collect()
+}
+
+/// Read a dictionary consisting of one word per line from the specified file.
+///
+/// Words are lowercased, empty lines are ignored.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-queue_tweet-load_dictionary-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("words");
+/// File::create(&tf).unwrap().write_all(b"Abolish\nthe\n\nbourgeoisie\n").unwrap();
+///
+/// let dict = queue_tweet::load_dictionary(&tf).unwrap();
+/// assert_eq!(dict.len(), 3);
+/// assert!(dict.contains("abolish"));
+/// ```
+pub fn load_dictionary(p: &Path) -> IoResult<BTreeSet<String>> {
+    File::open(p)
+        .map(|f| BufReader::new(f).lines().flat_map(Result::ok).map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty()).collect())
+}
+
+/// Find all words in the specified tweet content that aren't in the specified dictionary.
+///
+/// Links, mentions, hashtags and words containing digits are skipped.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::collections::BTreeSet;
+/// let dict: BTreeSet<_> = vec!["abolish", "the", "bourgeoisie", "don't"].into_iter().map(str::to_string).collect();
+/// assert_eq!(queue_tweet::misspelt_words("Abolish teh burgeoisie, don't wait for 2017! #revolution @marx https://marxists.org",
+///                                        &dict),
+///            vec!["teh", "burgeoisie", "wait", "for"]);
+/// ```
+pub fn misspelt_words<'c>(content: &'c str, dictionary: &BTreeSet<String>) -> Vec<&'c str> {
+    content.split_whitespace()
+        .filter(|w| !w.starts_with('@') && !w.starts_with('#') && !w.contains("://"))
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty() && !w.chars().any(|c| c.is_numeric()))
+        .filter(|w| !dictionary.contains(&w.to_lowercase()))
+        .collect()
+}
+
+/// Find all invisible characters in the specified tweet content, usually left over from copying it from elsewhere:
+/// soft hyphens, zero-width spaces and joiners, byte order marks, bidirectional text controls and control characters.
+///
+/// Zero-width joiners between two non-ASCII characters are part of emoji sequences (like 👩‍💻), and so are left alone,
+/// as are newlines, tabs and carriage returns.
+///
+/// Each is described by its code point and name, with how many times it occurs, in the order of its first occurrence.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::invisible_chars("Abol\u{ad}ish the\u{200b}bourgeoisie\u{200b}!"),
+///            vec!["U+00AD SOFT HYPHEN", "U+200B ZERO WIDTH SPACE (2 times)"]);
+/// assert!(queue_tweet::invisible_chars("Abolish the bourgeoisie!\n👩\u{200d}💻").is_empty());
+/// ```
+pub fn invisible_chars(content: &str) -> Vec<String> {
+    let mut found: Vec<(char, usize)> = vec![];
+    for c in invisible_positions(content).into_iter().map(|(_, c)| c) {
+        match found.iter().position(|&(f, _)| f == c) {
+            Some(i) => found[i].1 += 1,
+            None => found.push((c, 1)),
+        }
+    }
+
+    found.into_iter()
+        .map(|(c, n)| {
+            let desc = format!("U+{:04X} {}", c as u32, invisible_char_name(c));
+            if n == 1 {
+                desc
+            } else {
+                format!("{} ({} times)", desc, n)
+            }
+        })
+        .collect()
+}
+
+/// Check whether the specified tweet still fits with its content warning folded in with the specified template, see
+/// `start_daemon::fold_content_warning()`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, queue_tweet};
+/// # use tweetr::util::mul_str;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: mul_str("Abolish ", 33),
+///     content_warning: Some("politics".to_string()),
+///     ..Default::default()
+/// };
+/// assert_eq!(queue_tweet::check_content_warning(&tweet, "CW: {warning}\n\n{content}"), None);
+/// assert_eq!(queue_tweet::check_content_warning(&tweet, "Content warning: {warning}\n\n{content}"),
+///            Some("With the content warning, the tweet is 291 characters long, only 280 fit".to_string()));
+///
+/// tweet.content_warning = None;
+/// assert_eq!(queue_tweet::check_content_warning(&tweet, "CW: {warning}\n\n{content}"), None);
+/// # }
+/// ```
+pub fn check_content_warning(tweet: &QueuedTweet, format: &str) -> Option<String> {
+    tweet.content_warning.as_ref().and_then(|warning| {
+        let len = tweet_length(&fold_content_warning(&tweet.content, Some(warning), format));
+        if len > TWEET_MAX_LENGTH {
+            Some(format!("With the content warning, the tweet is {} characters long, only {} fit", len, TWEET_MAX_LENGTH))
+        } else {
+            None
+        }
+    })
+}
+
+/// Remove all invisible characters found by `invisible_chars()` from the specified tweet content.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::strip_invisible("\u{feff}Abol\u{ad}ish the\u{200b} bourgeoisie\u{202c}!\n👩\u{200d}💻"),
+///            "Abolish the bourgeoisie!\n👩\u{200d}💻");
+/// ```
+pub fn strip_invisible(content: &str) -> String {
+    let positions = invisible_positions(content);
+    content.char_indices().filter(|&(i, _)| !positions.iter().any(|&(p, _)| p == i)).map(|(_, c)| c).collect()
+}
+
+/// Ask the user whether to strip the invisible characters from a tweet.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// assert!(queue_tweet::prompt_strip(&mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new()));
+/// assert!(!queue_tweet::prompt_strip(&mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()));
+/// ```
+pub fn prompt_strip<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> bool {
+    let answer = prompt_any_len(input, output, &tr("prompt_strip", &[]), |s| ["y", "n", "Y", "N"].contains(&&s[..])).unwrap();
+    writeln!(output, "").unwrap();
+
+    answer.map(|a| a.to_lowercase() == "y").unwrap_or(false)
+}
+
+/// Split the specified text into parts fitting in a tweet each, numbering them according to the specified template.
+///
+/// In the template, `{n}` is replaced with the part number, `{total}` with the amount of parts, and `{content}` with the part's
+/// content (if `{content}` is missing, the content is appended to the template).
+///
+/// Text that fits in a single tweet is returned unchanged. Otherwise, it's split at sentence boundaries where possible and at
+/// word boundaries where not.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use tweetr::util::{TWEET_MAX_LENGTH, tweet_length, mul_str};
+/// assert_eq!(queue_tweet::split_thread("Abolish the bourgeoisie!", "{n}/{total} {content}"),
+///            vec!["Abolish the bourgeoisie!".to_string()]);
+///
+/// let long = mul_str("Capitalism is bad. ", 20);
+/// let parts = queue_tweet::split_thread(long.trim(), "{n}/{total} {content}");
+/// assert_eq!(parts.len(), 2);
+/// assert!(parts[0].starts_with("1/2 Capitalism is bad."));
+/// assert!(parts[0].ends_with("bad."));
+/// assert!(parts[1].starts_with("2/2 Capitalism is bad."));
+/// assert!(parts.iter().all(|p| tweet_length(p) <= TWEET_MAX_LENGTH));
+/// ```
+pub fn split_thread(content: &str, numbering: &str) -> Vec<String> {
+    if tweet_length(content) <= TWEET_MAX_LENGTH {
+        return vec![content.to_string()];
+    }
+
+    let template = if numbering.contains("{content}") {
+        numbering.to_string()
+    } else {
+        format!("{}{{content}}", numbering)
+    };
+    let words: Vec<&str> = content.split_whitespace().collect();
+
+    // The numbering takes up the most space when both numbers have the maximal amount of digits,
+    // so find the smallest amount of digits that all parts can be numbered with.
+    let mut digits = 1;
+    loop {
+        let widest = mul_str("9", digits);
+        let widest_template = template.replace("{n}", &widest).replace("{total}", &widest);
+        let parts = pack_words(&words, &widest_template);
+
+        if parts.len() < 10usize.pow(digits as u32) {
+            let total = parts.len();
+            return parts.into_iter()
+                .enumerate()
+                .map(|(i, p)| template.replace("{n}", &(i + 1).to_string()).replace("{total}", &total.to_string()).replace("{content}", &p))
+                .collect();
+        }
+        digits += 1;
+    }
+}
+
+/// Split the specified tweet into a thread if it's too long to fit in a single tweet, numbering the parts according to the
+/// specified template (see `split_thread()`).
+///
+/// All parts are scheduled for the same time and are in a thread with the same name as the original tweet,
+/// or a new name made from its author and time. The media are attached to the first part.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use tweetr::util::mul_str;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+///     content: mul_str("Capitalism is bad. ", 20),
+///     media: vec!["chart.png".to_string()],
+///     ..Default::default()
+/// };
+///
+/// let thread = queue_tweet::auto_split(tweet.clone(), "{n}/{total} {content}");
+/// assert_eq!(thread.len(), 2);
+/// assert!(thread.iter().all(|t| t.time == tweet.time));
+/// assert!(thread.iter().all(|t| t.thread == Some("nabijaczleweli@2016-09-09T00:33:30+02:00".to_string())));
+/// assert_eq!(thread[0].media, vec!["chart.png".to_string()]);
+/// assert!(thread[1].media.is_empty());
+/// # }
+/// ```
+pub fn auto_split(tweet: QueuedTweet, numbering: &str) -> Vec<QueuedTweet> {
+    let parts = split_thread(&tweet.content, numbering);
+    if parts.len() == 1 {
+        return vec![tweet];
+    }
+
+    let thread = tweet.thread.clone().unwrap_or_else(|| format!("{}@{}", tweet.author, tweet.time.to_rfc3339()));
+    parts.into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            QueuedTweet {
+                content: p,
+                // Attached to the first part only
+                media: if i == 0 { tweet.media.clone() } else { vec![] },
+                thread: Some(thread.clone()),
+                ..tweet.clone()
+            }
+        })
+        .collect()
+}
+
+/// Find the unposted tweets in the queue by the same author as the specified tweet scheduled less than the specified window
+/// apart from it.
+///
+/// Tweets in the same thread as the specified tweet don't collide with it.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+///     content: "Abolish".to_string(),
+///     ..Default::default()
+/// };
+/// let queue = vec![QueuedTweet {
+///                      time: DateTime::parse_from_rfc3339("2016-09-09T12:05:00+02:00").unwrap(),
+///                      content: "the bourgeoisie".to_string(),
+///                      ..tweet.clone()
+///                  },
+///                  QueuedTweet {
+///                      author: "danerangLP".to_string(),
+///                      content: "Different account".to_string(),
+///                      ..tweet.clone()
+///                  },
+///                  QueuedTweet {
+///                      time: DateTime::parse_from_rfc3339("2016-09-09T12:30:00+02:00").unwrap(),
+///                      content: "Far enough".to_string(),
+///                      ..tweet.clone()
+///                  }];
+///
+/// assert_eq!(queue_tweet::collisions(&tweet, &queue, Duration::minutes(10)), vec![&queue[0]]);
+/// assert!(queue_tweet::collisions(&tweet, &queue, Duration::minutes(1)).is_empty());
+/// # }
+/// ```
+pub fn collisions<'q>(tweet: &QueuedTweet, queue: &'q [QueuedTweet], window: Duration) -> Vec<&'q QueuedTweet> {
+    queue.iter().filter(|q| collides(tweet, tweet.time, q, window)).collect()
+}
+
+/// Find the time closest to the specified tweet's at which it wouldn't collide with any tweet in the queue (see `collisions()`).
+///
+/// The returned time is never earlier than both the specified clock's current time and the tweet's time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::clock::ManualClock;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap();
+/// let clock = ManualClock::new(now);
+///
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: now + Duration::hours(1),
+///     content: "Abolish".to_string(),
+///     ..Default::default()
+/// };
+/// let queue = vec![QueuedTweet { time: now + Duration::hours(1) + Duration::minutes(2), ..tweet.clone() },
+///                  QueuedTweet { time: now + Duration::hours(1) + Duration::minutes(12), ..tweet.clone() }];
+///
+/// assert_eq!(queue_tweet::nearest_free_slot(&tweet, &queue, Duration::minutes(10), &clock),
+///            now + Duration::hours(1) - Duration::minutes(8));
+/// assert_eq!(queue_tweet::nearest_free_slot(&tweet, &[], Duration::minutes(10), &clock), tweet.time);
+/// # }
+/// ```
+pub fn nearest_free_slot<C: Clock + ?Sized>(tweet: &QueuedTweet, queue: &[QueuedTweet], window: Duration, clock: &C) -> DateTime<FixedOffset> {
+    let earliest = cmp::min(tweet.time, clock.now());
+
+    // A free slot, if any, is either the tweet's own time or right at the edge of another tweet's window,
+    // and the one after the latest tweet is always free
+    iter::once(tweet.time)
+        .chain(queue.iter().filter(|q| competes(tweet, q)).flat_map(|q| vec![q.time - window, q.time + window]))
+        .filter(|&t| t >= earliest && !queue.iter().any(|q| collides(tweet, t, q, window)))
+        .min_by_key(|&t| ((t - tweet.time).num_seconds().abs(), t))
+        .unwrap()
+}
+
+/// Print a warning about the specified tweet colliding with the specified tweets and the nearest free slot to move it to.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use std::iter::FromIterator;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+///     content: "Abolish".to_string(),
+///     ..Default::default()
+/// };
+/// let other = QueuedTweet {
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:05:00+02:00").unwrap(),
+///     content: "the bourgeoisie".to_string(),
+///     ..tweet.clone()
+/// };
+///
+/// let mut out = Vec::new();
+/// queue_tweet::print_collisions(&mut out, &tweet, &[&other], Duration::minutes(10),
+///                               DateTime::parse_from_rfc3339("2016-09-09T11:55:00+02:00").unwrap());
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "Tweet \"Abolish\" scheduled for 2016-09-09T12:00:00+02:00 is less than 10 minutes apart from \
+///             1 other tweet by nabijaczleweli:\n  \
+///               \"the bourgeoisie\" scheduled for 2016-09-09T12:05:00+02:00\n\
+///             The nearest free slot is 2016-09-09T11:55:00+02:00.\n".to_string());
+/// # }
+/// ```
+pub fn print_collisions<W: Write>(output: &mut W, tweet: &QueuedTweet, collisions: &[&QueuedTweet], window: Duration, free_slot: DateTime<FixedOffset>) {
+    writeln!(output,
+             "Tweet \"{}\" scheduled for {} is less than {} minutes apart from {} other tweet{} by {}:",
+             tweet.content,
+             format_datetime(&tweet.time),
+             window.num_minutes(),
+             collisions.len(),
+             if collisions.len() == 1 { "" } else { "s" },
+             tweet.author)
+        .unwrap();
+    for collision in collisions {
+        writeln!(output, "  \"{}\" scheduled for {}", collision.content, format_datetime(&collision.time)).unwrap();
+    }
+    writeln!(output, "The nearest free slot is {}.", format_datetime(&free_slot)).unwrap();
+}
+
+/// Find the tweets by the same author with the same content as the specified one, scheduled or posted less than the specified
+/// time apart from it.
+///
+/// Twitter refuses to post duplicates of recent tweets, so the tweets should include the archived ones (see
+/// `ops::archive::read_since()`).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let posted = DateTime::parse_from_rfc3339("2016-09-01T12:00:00+02:00").unwrap();
+/// let tweets = vec![QueuedTweet { time: posted, time_posted: Some(posted), id: Some(420), ..tweet.clone() },
+///                   QueuedTweet { author: "danerangLP".to_string(), ..tweet.clone() },
+///                   QueuedTweet { content: "Abolish the monarchy".to_string(), ..tweet.clone() }];
+///
+/// assert_eq!(queue_tweet::duplicates(&tweet, &tweets, Duration::days(30)), vec![&tweets[0]]);
+/// assert!(queue_tweet::duplicates(&tweet, &tweets, Duration::days(7)).is_empty());
+/// # }
+/// ```
+pub fn duplicates<'q>(tweet: &QueuedTweet, tweets: &'q [QueuedTweet], window: Duration) -> Vec<&'q QueuedTweet> {
+    tweets.iter()
+        .filter(|t| {
+            t.author == tweet.author && t.content.trim() == tweet.content.trim() &&
+            (t.time_posted.unwrap_or(t.time) - tweet.time).num_seconds().abs() < window.num_seconds()
+        })
+        .collect()
+}
+
+/// Print a warning about the specified tweet duplicating the specified tweets.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use std::iter::FromIterator;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+///     content: "Abolish".to_string(),
+///     ..Default::default()
+/// };
+/// let posted = DateTime::parse_from_rfc3339("2016-09-01T12:00:00+02:00").unwrap();
+/// let other = QueuedTweet { time: posted, time_posted: Some(posted), id: Some(420), ..tweet.clone() };
+///
+/// let mut out = Vec::new();
+/// queue_tweet::print_duplicates(&mut out, &tweet, &[&other], Duration::days(30));
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "Tweet \"Abolish\" scheduled for 2016-09-09T12:00:00+02:00 duplicates 1 tweet by nabijaczleweli \
+///             less than 30 days apart:\n  \
+///               posted at 2016-09-01T12:00:00+02:00\n".to_string());
+/// # }
+/// ```
+pub fn print_duplicates<W: Write>(output: &mut W, tweet: &QueuedTweet, duplicates: &[&QueuedTweet], window: Duration) {
+    writeln!(output,
+             "Tweet \"{}\" scheduled for {} duplicates {} tweet{} by {} less than {} days apart:",
+             tweet.content,
+             format_datetime(&tweet.time),
+             duplicates.len(),
+             if duplicates.len() == 1 { "" } else { "s" },
+             tweet.author,
+             window.num_days())
+        .unwrap();
+    for duplicate in duplicates {
+        match duplicate.time_posted {
+            Some(time_posted) => writeln!(output, "  posted at {}", format_datetime(&time_posted)).unwrap(),
+            None => writeln!(output, "  scheduled for {}", format_datetime(&duplicate.time)).unwrap(),
+        }
+    }
+}
+
+/// Print a diff-like preview of what queueing the specified tweets loaded from a file would do, followed by the counts of
+/// each.
+///
+/// Tweets to be added are prefixed with `+`, duplicates with `=`, and ones failing validation with `!`, followed by why.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::queue_tweet::{self, Import};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let posted = DateTime::parse_from_rfc3339("2016-09-01T12:00:00+02:00").unwrap();
+///
+/// let mut out = Vec::new();
+/// queue_tweet::print_import_preview(&mut out,
+///                                   &[(tweet.clone(), Import::Added),
+///                                     (QueuedTweet { time: tweet.time + Duration::days(1), ..tweet.clone() },
+///                                      Import::Duplicate(vec![QueuedTweet { time_posted: Some(posted), ..tweet.clone() }])),
+///                                     (QueuedTweet { content: "Abolish teh bourgeoisie".to_string(), ..tweet.clone() },
+///                                      Import::Invalid(vec!["Possibly misspelt words: teh".to_string()]))]);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "+ 2016-09-09T12:00:00+02:00 by nabijaczleweli: \"Abolish the bourgeoisie\"\n\
+///             = 2016-09-10T12:00:00+02:00 by nabijaczleweli: \"Abolish the bourgeoisie\"\n    \
+///                 duplicates the tweet posted at 2016-09-01T12:00:00+02:00\n\
+///             ! 2016-09-09T12:00:00+02:00 by nabijaczleweli: \"Abolish teh bourgeoisie\"\n    \
+///                 Possibly misspelt words: teh\n\
+///             1 to add, 1 duplicate, 1 failing validation; nothing was queued.\n");
+/// # }
+/// ```
+pub fn print_import_preview<W: Write>(output: &mut W, imports: &[(QueuedTweet, Import)]) {
+    let (mut added, mut duplicate, mut invalid) = (0, 0, 0);
+    for &(ref tweet, ref import) in imports {
+        let prefix = match *import {
+            Import::Added => {
+                added += 1;
+                '+'
+            }
+            Import::Duplicate(_) => {
+                duplicate += 1;
+                '='
+            }
+            Import::Invalid(_) => {
+                invalid += 1;
+                '!'
+            }
+        };
+        writeln!(output, "{} {} by {}: \"{}\"", prefix, format_datetime(&tweet.time), tweet.author, tweet.content).unwrap();
+
+        match *import {
+            Import::Added => (),
+            Import::Duplicate(ref duplicates) => {
+                for duplicate in duplicates {
+                    match duplicate.time_posted {
+                        Some(time_posted) => writeln!(output, "    duplicates the tweet posted at {}", format_datetime(&time_posted)).unwrap(),
+                        None => writeln!(output, "    duplicates the tweet scheduled for {}", format_datetime(&duplicate.time)).unwrap(),
+                    }
+                }
+            }
+            Import::Invalid(ref reasons) => {
+                for reason in reasons {
+                    writeln!(output, "    {}", reason).unwrap();
+                }
+            }
+        }
+    }
+
+    writeln!(output,
+             "{} to add, {} duplicate{}, {} failing validation; nothing was queued.",
+             added,
+             duplicate,
+             if duplicate == 1 { "" } else { "s" },
+             invalid)
+        .unwrap();
+}
+
+/// Ask the user whether to move a colliding tweet to the nearest free slot.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// assert!(queue_tweet::prompt_shift(&mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new()));
+/// assert!(!queue_tweet::prompt_shift(&mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()));
+/// ```
+pub fn prompt_shift<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> bool {
+    let answer = prompt_any_len(input, output, &tr("prompt_move", &[]), |s| ["y", "n", "Y", "N"].contains(&&s[..])).unwrap();
+    writeln!(output, "").unwrap();
+
+    answer.map(|a| a.to_lowercase() == "y").unwrap_or(false)
+}
+
+/// Get the editor to compose tweets in, as specified by the `VISUAL` or `EDITOR` environment variables, falling back to
+/// `vi` (`notepad` on Windows).
+pub fn editor() -> String {
+    env::var("VISUAL")
+        .ok()
+        .into_iter()
+        .chain(env::var("EDITOR").ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Make the contents of the file to edit the specified tweet content in, followed by comments with its length and instructions.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::editor_template("Abolish the bourgeoisie"),
+///            "Abolish the bourgeoisie\n\n\
+///             \x23 23/280 characters.\n\
+///             \x23 Write the tweet above, lines starting with '#' are ignored. An empty tweet finishes queueing.\n");
+/// ```
+pub fn editor_template(content: &str) -> String {
+    format!("{}\n\n# {}/{} characters{}.\n# Write the tweet above, lines starting with '#' are ignored. An empty tweet finishes queueing.\n",
+            content,
+            tweet_length(content),
+            TWEET_MAX_LENGTH,
+            if tweet_length(content) > TWEET_MAX_LENGTH {
+                ", too long"
+            } else {
+                ""
+            })
+}
+
+/// Remove the comment lines, starting with `#`, and the surrounding whitespace from the specified edited tweet content.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// assert_eq!(queue_tweet::strip_comments("Abolish\n# 7/280 characters.\nthe bourgeoisie\n\n# Write the tweet above\n"),
+///            "Abolish\nthe bourgeoisie");
+/// ```
+pub fn strip_comments(content: &str) -> String {
+    content.lines().filter(|l| !l.starts_with('#')).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+/// Edit the specified tweet content in the specified editor, returning the new content, without comments.
+///
+/// The editor is run in the system shell with the path to a temporary file containing the `editor_template()` appended.
+/// The file's only readable by the user, and a new one's always made, never one already there opened.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # #[cfg(not(windows))]
+/// assert_eq!(queue_tweet::edit_content("true", "Abolish the bourgeoisie").unwrap(), "Abolish the bourgeoisie");
+/// ```
+pub fn edit_content(editor: &str, content: &str) -> IoResult<String> {
+    let (path, mut file) = try!(create_temp_file());
+    let written = file.write_all(editor_template(content).as_bytes());
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+
+    let status = shell(&format!("{} \"{}\"", editor, path.display())).status();
+    let mut edited = String::new();
+    let read = File::open(&path).and_then(|mut f| f.read_to_string(&mut edited));
+    let _ = fs::remove_file(&path);
+
+    let status = try!(status);
+    if !status.success() {
+        return Err(IoError::new(ErrorKind::Other, format!("{} exited with {}", editor, status)));
+    }
+    try!(read);
+    Ok(strip_comments(&edited))
+}
+
+/// Ask the user to input a tweet, composing its content in the specified editor (see `edit_content()`) and, if specified,
+/// prompting for the time to post it at.
+///
+/// Returns `None` if the author or content is empty, or the editor fails.
+///
+/// Tweets too long to fit are reopened in the editor, unless the user declines.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// # #[cfg(not(windows))]
+/// # {
+/// assert_eq!(queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\n" as &[u8]), &mut Vec::new(), "true", false),
+///            None);
+/// # }
+/// ```
+pub fn get_edited_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W, editor: &str, prompt_time: bool) -> Option<QueuedTweet> {
+    get_tweet_impl(input, output, prompt_time, ContentSource::Editor(editor))
+}
+
+/// Ask the user to input a tweet with the specified content, only prompting for its author and, if specified, the time to post
+/// it at.
+///
+/// Returns `None` if the author is empty.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// let tweet = queue_tweet::get_templated_tweet(&mut BufReader::new(b"tweetr_test\n\
+///                                                                    2016-09-10T12:00:00+02:00\n\
+///                                                                    y\n" as &[u8]),
+///                                              &mut Vec::new(),
+///                                              "tweetr v1.4.0 is out!",
+///                                              true)
+///     .unwrap();
+/// assert_eq!(tweet.author, "tweetr_test");
+/// assert_eq!(tweet.content, "tweetr v1.4.0 is out!");
+/// assert_eq!(tweet.time.to_rfc3339(), "2016-09-10T12:00:00+02:00");
+/// ```
+pub fn get_templated_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W, content: &str, prompt_time: bool) -> Option<QueuedTweet> {
+    get_tweet_impl(input, output, prompt_time, ContentSource::Fixed(content))
+}
+
+/// Fill in the template with the specified name from the specified templates with the specified values.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{Template, queue_tweet};
+/// # use std::collections::BTreeMap;
+/// # use tweetr::Outcome;
+/// let templates = vec![Template {
+///                          name: "release".to_string(),
+///                          content: "tweetr {version} is out!".to_string(),
+///                      }];
+/// let mut values = BTreeMap::new();
+/// values.insert("version".to_string(), "v1.4.0".to_string());
+///
+/// assert_eq!(queue_tweet::template(&templates, "release", &values), Ok("tweetr v1.4.0 is out!".to_string()));
+/// assert_eq!(queue_tweet::template(&templates, "relaese", &values),
+///            Err(Outcome::TemplateError {
+///                name: "relaese".to_string(),
+///                errors: vec!["no such template, see list-templates".to_string()],
+///            }));
+/// ```
+pub fn template(templates: &[Template], name: &str, values: &BTreeMap<String, String>) -> Result<String, Outcome> {
+    match templates.iter().find(|t| t.name == name) {
+        Some(template) => {
+            template.fill(values).map_err(|errors| {
+                Outcome::TemplateError {
+                    name: name.to_string(),
+                    errors: errors,
+                }
+            })
+        }
+        None => {
+            Err(Outcome::TemplateError {
+                name: name.to_string(),
+                errors: vec!["no such template, see list-templates".to_string()],
+            })
+        }
+    }
+}
+
+
+enum ContentSource<'a> {
+    Prompt,
+    Editor(&'a str),
+    Fixed(&'a str),
+}
+
+fn get_tweet_impl<R: BufRead, W: Write>(input: &mut R, output: &mut W, prompt_time: bool, source: ContentSource) -> Option<QueuedTweet> {
+    prompt_any_len(input, output, &tr("prompt_author", &[]), |_| true).unwrap().and_then(|uname| {
+        let content = match source {
+            ContentSource::Editor(editor) => {
+                let mut content = String::new();
+                loop {
+                    content = match edit_content(editor, &content) {
+                        Ok(content) => content,
+                        Err(e) => {
+                            writeln!(output, "Couldn't edit the tweet: {}", e).unwrap();
+                            return None;
+                        }
+                    };
+                    if content.is_empty() {
+                        return None;
+                    }
+
+                    let len = tweet_length(&content);
+                    if len <= TWEET_MAX_LENGTH || !prompt_reedit(input, output, len) {
+                        break;
+                    }
+                }
+                writeln!(output, "{}", content).unwrap();
+                content
+            }
+            ContentSource::Fixed(content) => {
+                writeln!(output, "{}", content).unwrap();
+                content.to_string()
+            }
+            ContentSource::Prompt => {
+                prompt_multiline(input,
+                                 output,
+                                 &tr("prompt_content", &[]),
+                                 |s| !s.trim().is_empty(),
+                                 MultilineOptions {
+                                     line_numbers: true,
+                                     char_count: true,
+                                 })
+                    .unwrap()
+            }
+        };
+        let now = Local::now();
+        let now = now.with_timezone(now.offset());
+        let (time, after) = if prompt_time {
+            loop {
+                let time = prompt_nonzero_len(input, output, &tr("prompt_time", &[]), |_| true).unwrap();
+
+                // The time of tweets scheduled relative to others is filled in when queueing them
+                match Anchor::parse(&time) {
+                    Some(Ok(anchor)) => break (now, Some(anchor)),
+                    Some(Err(e)) => writeln!(output, "\"{}\" is not a valid relative time: {}", time, e).unwrap(),
+                    None => {
+                        match parse_time(&time, now)
+                            .and_then(|t| schedule::within_horizon(t, now).map_err(|e| format!("\"{}\" is {}", time, e))) {
+                            // Tweets in the past would be posted as soon as the daemon sees them
+                            Ok(time) => {
+                                if time >= now || prompt_past(input, output, time, now) {
+                                    break (time, None);
+                                }
+                            }
+                            Err(e) => writeln!(output, "{}", e).unwrap(),
+                        }
+                    }
+                }
+            }
+        } else {
+            (now, None)
+        };
 
         writeln!(output, "").unwrap();
-        QueuedTweet {
+        Some(QueuedTweet {
             author: uname,
-            time: DateTime::parse_from_rfc2822(&time)
-                .or_else(|_| DateTime::parse_from_rfc3339(&time))
-                .or_else(|_| {
-                    let now = Local::now();
-                    let now = now.with_timezone(now.offset());
-
-                    Ok(now + Duration::from_std(parse_relative_time(&time).unwrap()).unwrap()) as ParseResult<DateTime<FixedOffset>>
-                })
-                .unwrap(),
+            time: time,
             content: content,
+            media: vec![],
+            thread: None,
+            in_reply_to: None,
+            extra_params: BTreeMap::new(),
+            variants: BTreeMap::new(),
+            content_warning: None,
+            coordinates: None,
+            place_id: None,
+            reply_settings: None,
+            name: None,
+            after: after,
+            paused: false,
+            blocked: None,
+            settings: Settings::default(),
             time_posted: None,
             id: None,
-        }
+            app: None,
+            retries: 0,
+        })
     })
 }
+
+fn prompt_past<R: BufRead, W: Write>(input: &mut R, output: &mut W, time: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> bool {
+    writeln!(output,
+             "{} is in the past, that was {} ago.",
+             format_datetime(&time),
+             format_approximate_time((now - time).num_seconds() as u64))
+        .unwrap();
+
+    // Piped tweets were queued for past times before this was asked, so they still are
+    let confirmation = CONFIRMATION.with(|c| c.get());
+    if confirmation != Confirmation::Ask {
+        return confirm_or(confirmation, true, input, output, &tr("prompt_queue_past", &[]));
+    }
+    let answer = prompt_any_len(input, output, &tr("prompt_queue_past", &[]), |s| ["y", "n", "Y", "N"].contains(&&s[..])).unwrap();
+
+    answer.map(|a| a.to_lowercase() == "y").unwrap_or(false)
+}
+
+fn create_temp_file() -> IoResult<(PathBuf, File)> {
+    loop {
+        let path = env::temp_dir().join(format!("tweetr-{:08x}.txt", rand::random::<u32>()));
+        match temp_file_options().open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(ref e) if e.kind() == ErrorKind::AlreadyExists => (),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn temp_file_options() -> OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true).mode(0o600);
+    options
+}
+
+#[cfg(not(unix))]
+fn temp_file_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    options
+}
+
+fn prompt_reedit<R: BufRead, W: Write>(input: &mut R, output: &mut W, len: usize) -> bool {
+    writeln!(output, "The tweet is {} characters long, only {} fit.", len, TWEET_MAX_LENGTH).unwrap();
+    let answer = prompt_any_len(input, output, &tr("prompt_reedit", &[]), |s| ["y", "n", "Y", "N"].contains(&&s[..])).unwrap();
+
+    answer.map(|a| a.to_lowercase() == "y").unwrap_or(true)
+}
+
+/// Find the byte positions of the invisible characters in the specified content, skipping zero width joiners in emoji sequences.
+fn invisible_positions(content: &str) -> Vec<(usize, char)> {
+    let chars: Vec<_> = content.char_indices().collect();
+    let non_ascii = |i: Option<usize>| i.and_then(|i| chars.get(i)).map(|&(_, c)| c as u32 >= 0x80).unwrap_or(false);
+
+    chars.iter()
+        .enumerate()
+        .filter(|&(i, &(_, c))| match c {
+            // Part of an emoji sequence
+            '\u{200D}' => !(non_ascii(i.checked_sub(1)) && non_ascii(Some(i + 1))),
+            c => !invisible_char_name(c).is_empty(),
+        })
+        .map(|(_, &ic)| ic)
+        .collect()
+}
+
+fn invisible_char_name(c: char) -> &'static str {
+    match c {
+        '\u{AD}' => "SOFT HYPHEN",
+        '\u{61C}' => "ARABIC LETTER MARK",
+        '\u{180E}' => "MONGOLIAN VOWEL SEPARATOR",
+        '\u{200B}' => "ZERO WIDTH SPACE",
+        '\u{200C}' => "ZERO WIDTH NON-JOINER",
+        '\u{200D}' => "ZERO WIDTH JOINER",
+        '\u{200E}' => "LEFT-TO-RIGHT MARK",
+        '\u{200F}' => "RIGHT-TO-LEFT MARK",
+        '\u{202A}' => "LEFT-TO-RIGHT EMBEDDING",
+        '\u{202B}' => "RIGHT-TO-LEFT EMBEDDING",
+        '\u{202C}' => "POP DIRECTIONAL FORMATTING",
+        '\u{202D}' => "LEFT-TO-RIGHT OVERRIDE",
+        '\u{202E}' => "RIGHT-TO-LEFT OVERRIDE",
+        '\u{2060}' => "WORD JOINER",
+        '\u{2061}'...'\u{2064}' => "INVISIBLE OPERATOR",
+        '\u{2066}' => "LEFT-TO-RIGHT ISOLATE",
+        '\u{2067}' => "RIGHT-TO-LEFT ISOLATE",
+        '\u{2068}' => "FIRST STRONG ISOLATE",
+        '\u{2069}' => "POP DIRECTIONAL ISOLATE",
+        '\u{FEFF}' => "ZERO WIDTH NO-BREAK SPACE",
+        '\u{FFF9}'...'\u{FFFB}' => "INTERLINEAR ANNOTATION",
+        '\u{FFFC}' => "OBJECT REPLACEMENT CHARACTER",
+        '\t' | '\n' | '\r' => "",
+        c if c.is_control() => "CONTROL CHARACTER",
+        _ => "",
+    }
+}
+
+#[cfg(feature = "network")]
+fn lookup(desc: &str, url: &'static str, params: BTreeMap<String, String>, user: &User, app: &Token) -> Result<Json, Outcome> {
+    let user = user.clone();
+    let app = network::owned_token(app);
+    network::request(desc, move || oauth::get(url, &params, &app, &user.raw_token()))
+}
+
+#[cfg(feature = "network")]
+fn error_text(out: &Outcome) -> String {
+    let mut error = vec![];
+    out.print_error(&mut error);
+    String::from_utf8_lossy(&error).trim().lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+/// Whether the specified tweets can collide at all, i.e. the other one is unposted, by the same author and not in the same thread.
+fn competes(tweet: &QueuedTweet, other: &QueuedTweet) -> bool {
+    other.id.is_none() && other.author == tweet.author && !(tweet.thread.is_some() && other.thread == tweet.thread)
+}
+
+fn collides(tweet: &QueuedTweet, time: DateTime<FixedOffset>, other: &QueuedTweet, window: Duration) -> bool {
+    competes(tweet, other) && (other.time - time).num_seconds().abs() < window.num_seconds()
+}
+
+fn pack_words(words: &[&str], template: &str) -> Vec<String> {
+    let fits = |part: &str| tweet_length(&template.replace("{content}", part)) <= TWEET_MAX_LENGTH;
+
+    // Words too long to fit in a tweet on their own are broken up as late as possible
+    let mut pieces = vec![];
+    for &word in words {
+        let mut word = word;
+        while !fits(word) {
+            let split_at = word.char_indices()
+                .map(|(idx, _)| idx)
+                .skip(1)
+                .take_while(|&idx| fits(&word[..idx]))
+                .last()
+                .unwrap_or_else(|| word.chars().next().unwrap().len_utf8());
+            pieces.push(&word[..split_at]);
+            word = &word[split_at..];
+        }
+        pieces.push(word);
+    }
+
+    let mut parts = vec![];
+    let mut cur: Vec<&str> = vec![];
+    for piece in pieces {
+        if !cur.is_empty() && !fits(&format!("{} {}", cur.join(" "), piece)) {
+            // Prefer ending the part at the last sentence end, as long as that doesn't leave it less than half-full
+            let sentence_end = (0..cur.len() - 1)
+                .rev()
+                .find(|&j| cur[j].ends_with(|c| c == '.' || c == '!' || c == '?') && tweet_length(&cur[..j + 1].join(" ")) * 2 >= TWEET_MAX_LENGTH);
+            match sentence_end {
+                Some(j) => {
+                    parts.push(cur[..j + 1].join(" "));
+                    cur = cur[j + 1..].to_vec();
+
+                    if !fits(&format!("{} {}", cur.join(" "), piece)) {
+                        parts.push(cur.join(" "));
+                        cur.clear();
+                    }
+                }
+                None => {
+                    parts.push(cur.join(" "));
+                    cur.clear();
+                }
+            }
+        }
+        cur.push(piece);
+    }
+    if !cur.is_empty() {
+        parts.push(cur.join(" "));
+    }
+
+    parts
+}
@@ -6,19 +6,26 @@
 //! Instead, we convert it to a `QueuedTweetForSerialisation`, which has `DateTime`s converted to a `String` in RFC-3339
 //! format and then serialise the `Vec` of them via `QueuedTweets` (classic trick).
 //!
-//! We do the inverse for deserialisation and silently return `None` if the `DateTime` strings have invalid format (same thing
-//! we do with a `ParserError`, TODO: don't silent-ignore parser errors (applies to all serialisables))
+//! We do the inverse for deserialisation, and report the key of the first `DateTime` string with an invalid format (or the first
+//! invalid coordinate, timezone or quiet hours) as a parsing error.
+//!
+//! Tweets scheduled relative to others have their `time` serialised as the `Anchor` instead, and computed from the other tweets'
+//! times once they're all deserialised (see `QueuedTweet::schedule()`).
 
 
-use chrono::{DateTime, FixedOffset, ParseError};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use std::hash::{Hash, Hasher};
+use self::super::settings::{QuietHours, Settings, parse_timezone};
+use self::super::schedule;
+use self::super::super::util::encoding::Encoding;
+use self::super::super::util::{RelativeTimeError, format_amount_of_time, parse_anchored_time};
 use self::super::super::Outcome;
-use self::super::read_toml_file;
-use std::iter::FromIterator;
+use self::super::{read_toml_file, read_imported_file, parse_toml, parse_toml_file, decode_toml, key_error, key_error_message, write_file};
+use toml::{self, Value, encode_str};
+use std::collections::BTreeMap;
 use std::cmp::Ordering;
-use toml::encode_str;
 use std::path::Path;
-use std::io::Write;
-use std::fs::File;
+use std::fmt;
 
 
 /// The struct representing a queued tweet to post, posted or not.
@@ -33,6 +40,63 @@ pub struct QueuedTweet {
 
     /// The string content of the tweet.
     pub content: String,
+    /// The paths to the files to attach to the tweet.
+    ///
+    /// URLs in tweets loaded from a file are downloaded into the media directory when queueing, see `ops::media`.
+    pub media: Vec<String>,
+    /// The name of the thread this tweet is part of, if any.
+    ///
+    /// Tweets in the same thread are posted in queue order, each as a reply to the one before it.
+    pub thread: Option<String>,
+    /// The numeric ID of the tweet this tweet replies to, if any.
+    ///
+    /// Filled in by the daemon for tweets in a thread.
+    pub in_reply_to: Option<i64>,
+    /// Additional parameters to send to the `statuses/update` API endpoint, for parameters tweetr has no first-class support
+    /// for.
+    ///
+    /// The parameters set by tweetr itself take precedence.
+    pub extra_params: BTreeMap<String, String>,
+    /// The content to mirror the tweet with instead, by backend, `"telegram"` or `"discord"`.
+    ///
+    /// Content too long for a backend is cut to fit, see `backend::adapt()`.
+    pub variants: BTreeMap<String, String>,
+    /// What to warn about the content with, if anything, like Mastodon's spoiler text.
+    ///
+    /// Twitter has no content warnings, so it's folded into the content when posting, see
+    /// `start_daemon::fold_content_warning()`.
+    pub content_warning: Option<String>,
+
+    /// The location to tag the tweet with, if any.
+    ///
+    /// Displayed as the exact location the tweet was sent from.
+    pub coordinates: Option<Coordinates>,
+    /// The ID of the Twitter place to tag the tweet with, if any.
+    pub place_id: Option<String>,
+    /// Who can reply to the tweet, if restricted.
+    ///
+    /// Restricted tweets are posted via the v2 API, see `backend::tweet_v2_body()`.
+    pub reply_settings: Option<ReplySettings>,
+
+    /// The name other tweets can be scheduled relative to, if any.
+    ///
+    /// Unique in the queue.
+    pub name: Option<String>,
+    /// The tweet this one is scheduled relative to, if any.
+    ///
+    /// The `time` is then computed from the other tweet's, see `QueuedTweet::schedule()`.
+    pub after: Option<Anchor>,
+
+    /// Whether the tweet is held back from posting until unpaused.
+    pub paused: bool,
+    /// Why the tweet is held back from posting, if a tweet before it in its thread failed permanently.
+    ///
+    /// Set by the daemon, see `start_daemon::block_thread()`, and cleared by the `requeue-thread` subsystem.
+    pub blocked: Option<String>,
+    /// The settings overriding the ones inherited from the author's account and the global configuration.
+    ///
+    /// See `ops::settings` for details.
+    pub settings: Settings,
 
     /// The time this tweet was posted.
     ///
@@ -44,38 +108,393 @@ pub struct QueuedTweet {
     ///
     /// Becomes non-empty when posted.
     pub id: Option<i64>,
+    /// The name of the application credential set the tweet was posted through, if not the default one.
+    ///
+    /// Twitter displays the application's registered name as the client the tweet was sent "via", so this records which
+    /// integration posted what.
+    ///
+    /// Filled in when posted, see `backend::PostingBackend::app()`.
+    pub app: Option<String>,
+    /// How many times posting the tweet failed and it was retried.
+    pub retries: u32,
 }
 
+/// The tweet, by name, another one is scheduled relative to, and how long after it.
+///
+/// Displayed and parsed as `15 minutes after launch`, see `util::parse_anchored_time()`.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
-struct QueuedTweetForSerialisation {
+pub struct Anchor {
+    /// The name of the tweet to schedule relative to.
+    pub name: String,
+    /// How many seconds after it to schedule.
+    pub delay: u64,
+}
+
+/// Who can reply to a tweet.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum ReplySettings {
+    /// Anyone, as when unrestricted.
+    Everyone,
+    /// Only the accounts the author follows, and the ones mentioned.
+    Following,
+    /// Only the accounts mentioned in the tweet.
+    MentionedUsers,
+}
+
+/// A point on Earth, in degrees.
+///
+/// Only ever constructed from valid coordinates, hence never `NaN` and `Eq`.
+#[derive(Debug, Copy, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct Coordinates {
+    /// The latitude, in range `[-90, 90]`.
+    pub lat: f64,
+    /// The longitude, in range `[-180, 180]`.
+    pub long: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct QueuedTweetForSerialisation {
     pub author: String,
     pub time: String,
 
     pub content: String,
+    pub media: Option<Vec<String>>,
+    pub thread: Option<String>,
+    pub in_reply_to: Option<i64>,
+    pub extra_params: Option<BTreeMap<String, String>>,
+    pub variants: Option<BTreeMap<String, String>>,
+    pub content_warning: Option<String>,
+
+    pub lat: Option<f64>,
+    pub long: Option<f64>,
+    pub place_id: Option<String>,
+    pub reply_settings: Option<String>,
+
+    pub name: Option<String>,
+
+    pub paused: Option<bool>,
+    pub blocked: Option<String>,
+
+    pub timezone: Option<String>,
+    pub quiet_hours: Option<String>,
+    pub jitter: Option<u32>,
+    pub tags: Option<Vec<String>>,
+    pub sensitive: Option<bool>,
 
     pub time_posted: Option<String>,
     pub id: Option<i64>,
+    pub app: Option<String>,
+    pub retries: Option<u32>,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+/// The contents of a queue file with its broken entries set aside, see `QueuedTweet::read_recovering()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredQueue {
+    /// The valid queued tweets.
+    pub tweets: Vec<QueuedTweet>,
+    /// The raw broken entries, to be written back verbatim with `QueuedTweet::write_with_broken()`.
+    pub broken: Vec<Value>,
+    /// Why the broken entries are broken, if there are any.
+    pub errors: Option<Outcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, RustcEncodable, RustcDecodable)]
 struct QueuedTweets {
     tweet: Vec<QueuedTweetForSerialisation>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+enum Scheduling {
+    Pending,
+    InProgress,
+    Done,
+    Failed(String),
+}
+
 
 impl QueuedTweet {
     /// Read all queued tweets from the specified file.
     pub fn read(p: &Path) -> Result<Vec<QueuedTweet>, Option<Outcome>> {
-        let queued_tweets: QueuedTweets = try!(read_toml_file(p, "queued tweets"));
-        Result::from_iter(queued_tweets.tweet.into_iter().map(|qts| qts.into()).collect::<Vec<_>>()).map_err(|_| None)
+        QueuedTweet::decode_relative_to(p, try!(read_toml_file(p, "queued tweets")), &[])
+    }
+
+    /// Read all queued tweets from the specified imported file, to be added to the specified queue, so they can be scheduled
+    /// relative to the tweets in it, too.
+    ///
+    /// The file is converted from the specified encoding, or the detected one, if none, see `util::encoding::decode()`.
+    pub fn read_relative_to(p: &Path, queue: &[QueuedTweet], encoding: Option<Encoding>) -> Result<Vec<QueuedTweet>, Option<Outcome>> {
+        let (buf, table) = try!(read_imported_file(p, "queued tweets", encoding)
+            .and_then(|buf| parse_toml(buf, "queued tweets").map_err(Some)));
+        let queued_tweets = try!(decode_toml(&buf, &table, "").map_err(|e| {
+            Some(Outcome::FileParsingFailed {
+                desc: "queued tweets",
+                errors: vec![e],
+            })
+        }));
+
+        QueuedTweet::decode_relative_to(p, queued_tweets, queue)
+    }
+
+    fn decode_relative_to(p: &Path, queued_tweets: QueuedTweets, queue: &[QueuedTweet]) -> Result<Vec<QueuedTweet>, Option<Outcome>> {
+
+        let mut tweets = Vec::with_capacity(queued_tweets.tweet.len());
+        for (i, qts) in queued_tweets.tweet.into_iter().enumerate() {
+            let qt: Result<QueuedTweet, (&'static str, String)> = qts.into();
+            tweets.push(try!(qt.map_err(|(key, e)| Some(key_error(p, "queued tweets", &format!("tweet.{}.{}", i, key), &e)))));
+        }
+
+        if let Some((i, key, e)) = QueuedTweet::schedule(&mut tweets, queue).into_iter().next() {
+            return Err(Some(key_error(p, "queued tweets", &format!("tweet.{}.{}", i, key), &e)));
+        }
+        Ok(tweets)
+    }
+
+    /// Read all queued tweets from the specified file, setting aside the entries that fail to parse instead of failing entirely.
+    ///
+    /// Still fails if the file isn't valid TOML at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use tweetr::Outcome;
+    /// # use std::fs::{self, File};
+    /// # use std::env::temp_dir;
+    /// # use std::io::Write;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-queued_tweet-read_recovering-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// File::create(tf.join("tweets.toml")).unwrap().write_all(b"[[tweet]]\n\
+    ///                                                           author = \"nabijaczleweli\"\n\
+    ///                                                           time = \"2016-09-09T00:33:30+02:00\"\n\
+    ///                                                           content = \"Abolish\"\n\n\
+    ///                                                           [[tweet]]\n\
+    ///                                                           author = \"nabijaczleweli\"\n\
+    ///                                                           time = \"tomorrow\"\n\
+    ///                                                           content = \"the bourgeoisie\"\n").unwrap();
+    ///
+    /// let queue = QueuedTweet::read_recovering(&tf.join("tweets.toml")).unwrap();
+    /// assert_eq!(queue.tweets.len(), 1);
+    /// assert_eq!(queue.tweets[0].content, "Abolish");
+    /// assert_eq!(queue.broken.len(), 1);
+    /// assert_eq!(queue.errors,
+    ///            Some(Outcome::FileParsingFailed {
+    ///                desc: "queued tweets",
+    ///                errors: vec!["error: 8:1: invalid RFC3339 datetime (input contains invalid characters) \
+    ///                              for the key `tweet.1.time`".to_string()],
+    ///            }));
+    /// ```
+    pub fn read_recovering(p: &Path) -> Result<RecoveredQueue, Option<Outcome>> {
+        let (buf, table) = try!(parse_toml_file(p, "queued tweets"));
+        let entries = match table.get("tweet") {
+            Some(&Value::Array(ref entries)) => entries.clone(),
+            None => vec![],
+            Some(_) => {
+                return Err(Some(Outcome::FileParsingFailed {
+                    desc: "queued tweets",
+                    errors: vec![decode_toml::<QueuedTweets>(&buf, &table, "").unwrap_err()],
+                }))
+            }
+        };
+
+        let mut queue = RecoveredQueue {
+            tweets: Vec::with_capacity(entries.len()),
+            broken: vec![],
+            errors: None,
+        };
+        let mut errors = vec![];
+        let mut parsed = vec![];
+        for (i, entry) in entries.into_iter().enumerate() {
+            let prefix = format!("tweet.{}.", i);
+            let tweet = match entry {
+                Value::Table(ref t) => {
+                    decode_toml(&buf, t, &prefix).and_then(|qts: QueuedTweetForSerialisation| {
+                        let qt: Result<QueuedTweet, (&'static str, String)> = qts.into();
+                        qt.map_err(|(key, e)| {
+                            let key = format!("{}{}", prefix, key);
+                            key_error_message(&buf, Some(&key), &format!("{} for the key `{}`", e, key))
+                        })
+                    })
+                }
+                _ => Err(format!("error: expected a section for the key `tweet.{}`", i)),
+            };
+
+            match tweet {
+                Ok(tweet) => {
+                    queue.tweets.push(tweet);
+                    parsed.push((i, entry));
+                }
+                Err(e) => {
+                    queue.broken.push(entry);
+                    errors.push(e);
+                }
+            }
+        }
+
+        for (i, key, e) in QueuedTweet::schedule(&mut queue.tweets, &[]).into_iter().rev() {
+            let (entry_i, entry) = parsed.remove(i);
+            queue.tweets.remove(i);
+
+            let key = format!("tweet.{}.{}", entry_i, key);
+            queue.broken.push(entry);
+            errors.push(key_error_message(&buf, Some(&key), &format!("{} for the key `{}`", e, key)));
+        }
+
+        if !errors.is_empty() {
+            queue.errors = Some(Outcome::FileParsingFailed {
+                desc: "queued tweets",
+                errors: errors,
+            });
+        }
+        Ok(queue)
+    }
+
+    /// Compute the times of the specified tweets scheduled relative to others, from the times of the ones in the specified tweets
+    /// or in the specified queue they're to be added to.
+    ///
+    /// Tweets are scheduled relative to the time the other tweet was posted at or, if it wasn't yet, the time it's scheduled for.
+    /// Posted tweets whose other tweet is gone, like when it's in an earlier archive, are taken to be scheduled for when they were
+    /// posted.
+    ///
+    /// Returns the index of each tweet that can't be scheduled or has a duplicate name, the key at fault, and why, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::{Anchor, QueuedTweet};
+    /// # use chrono::{DateTime, Duration};
+    /// # fn main() {
+    /// let launch = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "tweetr v0.3.0 is out!".to_string(),
+    ///     name: Some("launch".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let follow_up = QueuedTweet {
+    ///     content: "Now with tweets scheduled relative to others.".to_string(),
+    ///     name: None,
+    ///     after: Some(Anchor {
+    ///         name: "launch".to_string(),
+    ///         delay: 15 * 60,
+    ///     }),
+    ///     ..launch.clone()
+    /// };
+    ///
+    /// let mut tweets = vec![follow_up.clone(), launch.clone()];
+    /// assert!(QueuedTweet::schedule(&mut tweets, &[]).is_empty());
+    /// assert_eq!(tweets[0].time, launch.time + Duration::minutes(15));
+    ///
+    /// let mut tweets = vec![follow_up.clone()];
+    /// assert_eq!(QueuedTweet::schedule(&mut tweets, &[]),
+    ///            vec![(0, "time", "no tweet named \"launch\"".to_string())]);
+    /// assert!(QueuedTweet::schedule(&mut tweets, &[launch.clone()]).is_empty());
+    ///
+    /// let mut tweets = vec![QueuedTweet {
+    ///                           after: Some(Anchor {
+    ///                               name: "launch".to_string(),
+    ///                               delay: 0,
+    ///                           }),
+    ///                           ..launch.clone()
+    ///                       }];
+    /// assert_eq!(QueuedTweet::schedule(&mut tweets, &[]),
+    ///            vec![(0, "time", "scheduled relative to itself through \"launch\"".to_string())]);
+    /// # }
+    /// ```
+    pub fn schedule(tweets: &mut [QueuedTweet], queue: &[QueuedTweet]) -> Vec<(usize, &'static str, String)> {
+        let mut errors = vec![];
+        for (i, tweet) in tweets.iter().enumerate() {
+            if let Some(ref name) = tweet.name {
+                if tweets[..i].iter().chain(queue).any(|t| t.name.as_ref() == Some(name)) {
+                    errors.push((i, "name", format!("duplicate name \"{}\"", name)));
+                }
+            }
+        }
+
+        let mut states = vec![Scheduling::Pending; tweets.len()];
+        for i in 0..tweets.len() {
+            if let Err(e) = QueuedTweet::schedule_one(tweets, queue, i, &mut states) {
+                errors.push((i, "time", e));
+            }
+        }
+
+        errors.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        errors
+    }
+
+    fn schedule_one(tweets: &mut [QueuedTweet], queue: &[QueuedTweet], i: usize, states: &mut [Scheduling]) -> Result<(), String> {
+        match states[i] {
+            Scheduling::Done => return Ok(()),
+            Scheduling::Failed(ref e) => return Err(e.clone()),
+            Scheduling::Pending | Scheduling::InProgress => (),
+        }
+        let anchor = match tweets[i].after.clone() {
+            Some(anchor) => anchor,
+            None => {
+                states[i] = Scheduling::Done;
+                return Ok(());
+            }
+        };
+
+        states[i] = Scheduling::InProgress;
+        let base = match tweets.iter().position(|t| t.name.as_ref() == Some(&anchor.name)) {
+            Some(j) if states[j] == Scheduling::InProgress => Err(format!("scheduled relative to itself through \"{}\"", anchor.name)),
+            Some(j) => {
+                QueuedTweet::schedule_one(tweets, queue, j, states)
+                    .map(|_| tweets[j].time_posted.unwrap_or(tweets[j].time))
+                    .map_err(|_| format!("scheduled relative to \"{}\", which can't be scheduled", anchor.name))
+            }
+            None => {
+                match (queue.iter().find(|t| t.name.as_ref() == Some(&anchor.name)), tweets[i].time_posted) {
+                    (Some(t), _) => Ok(t.time_posted.unwrap_or(t.time)),
+                    (None, Some(time_posted)) => {
+                        tweets[i].time = time_posted;
+                        states[i] = Scheduling::Done;
+                        return Ok(());
+                    }
+                    (None, None) => Err(format!("no tweet named \"{}\"", anchor.name)),
+                }
+            }
+        };
+
+        let time = base.and_then(|base| {
+            schedule::later(base, anchor.delay).map_err(|_| format!("{} after \"{}\" is too late", format_amount_of_time(anchor.delay), anchor.name))
+        });
+        match time {
+            Ok(time) => {
+                tweets[i].time = time;
+                states[i] = Scheduling::Done;
+                Ok(())
+            }
+            Err(e) => {
+                states[i] = Scheduling::Failed(e.clone());
+                Err(e)
+            }
+        }
     }
 
     /// Save all queued tweets to the specified file.
-    pub fn write(queued_tweets: Vec<QueuedTweet>, p: &Path) {
-        File::create(p)
-            .unwrap()
-            .write_all(encode_str(&QueuedTweets { tweet: queued_tweets.into_iter().map(QueuedTweetForSerialisation::from).collect() }).as_bytes())
-            .unwrap();
+    pub fn write(queued_tweets: Vec<QueuedTweet>, p: &Path) -> Result<(), Outcome> {
+        let toml = encode_str(&QueuedTweets { tweet: queued_tweets.into_iter().map(QueuedTweetForSerialisation::from).collect() });
+        write_file(p, &escape_control_characters(toml))
+    }
+
+    /// Save all queued tweets to the specified file, followed by the specified raw broken entries
+    /// (see `QueuedTweet::read_recovering()`).
+    pub fn write_with_broken(queued_tweets: Vec<QueuedTweet>, broken: &[Value], p: &Path) -> Result<(), Outcome> {
+        if broken.is_empty() {
+            return QueuedTweet::write(queued_tweets, p);
+        }
+
+        let entries = queued_tweets.into_iter()
+            .map(|qt| toml::encode(&QueuedTweetForSerialisation::from(qt)))
+            .chain(broken.iter().cloned())
+            .collect();
+
+        let mut table = toml::Table::new();
+        table.insert("tweet".to_string(), Value::Array(entries));
+        write_file(p, &escape_control_characters(Value::Table(table).to_string()))
     }
 }
 
@@ -91,30 +510,279 @@ impl PartialOrd for QueuedTweet {
     }
 }
 
+impl Default for QueuedTweet {
+    fn default() -> QueuedTweet {
+        QueuedTweet {
+            author: String::new(),
+            // There's no sensible default time, so the epoch, which every real tweet is after
+            time: FixedOffset::east(0).timestamp(0, 0),
+            content: String::new(),
+            media: vec![],
+            thread: None,
+            in_reply_to: None,
+            extra_params: BTreeMap::new(),
+            variants: BTreeMap::new(),
+            content_warning: None,
+            coordinates: None,
+            place_id: None,
+            reply_settings: None,
+            name: None,
+            after: None,
+            paused: false,
+            blocked: None,
+            settings: Settings::default(),
+            time_posted: None,
+            id: None,
+            app: None,
+            retries: 0,
+        }
+    }
+}
+
+impl Anchor {
+    /// Parse the time another tweet is scheduled relative to this one, if it looks like one, see
+    /// `util::parse_anchored_time()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Anchor;
+    /// assert_eq!(Anchor::parse("15 minutes after launch"),
+    ///            Some(Ok(Anchor {
+    ///                name: "launch".to_string(),
+    ///                delay: 15 * 60,
+    ///            })));
+    /// assert_eq!(Anchor::parse("2016-09-10T12:00:00+02:00"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<Result<Anchor, RelativeTimeError>> {
+        parse_anchored_time(s).map(|r| {
+            r.map(|(delay, name)| {
+                Anchor {
+                    name: name.to_string(),
+                    delay: delay,
+                }
+            })
+        })
+    }
+}
+
+impl fmt::Display for Anchor {
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Anchor;
+    /// assert_eq!(Anchor { name: "launch".to_string(), delay: 2 * 60 * 60 }.to_string(), "2 hours after launch");
+    /// assert_eq!(Anchor { name: "launch".to_string(), delay: 0 }.to_string(), "after launch");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.delay == 0 {
+            write!(f, "after {}", self.name)
+        } else {
+            write!(f, "{} after {}", format_amount_of_time(self.delay), self.name)
+        }
+    }
+}
+
+impl ReplySettings {
+    /// Parse reply settings from their name in the API.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::ReplySettings;
+    /// assert_eq!(ReplySettings::parse("mentionedUsers"), Some(ReplySettings::MentionedUsers));
+    /// assert_eq!(ReplySettings::parse("following"), Some(ReplySettings::Following));
+    /// assert_eq!(ReplySettings::parse("mentioned_users"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<ReplySettings> {
+        match s {
+            "everyone" => Some(ReplySettings::Everyone),
+            "following" => Some(ReplySettings::Following),
+            "mentionedUsers" => Some(ReplySettings::MentionedUsers),
+            _ => None,
+        }
+    }
+
+    /// Get the reply settings' name in the API.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::ReplySettings;
+    /// assert_eq!(ReplySettings::MentionedUsers.name(), "mentionedUsers");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ReplySettings::Everyone => "everyone",
+            ReplySettings::Following => "following",
+            ReplySettings::MentionedUsers => "mentionedUsers",
+        }
+    }
+}
+
+impl Coordinates {
+    /// Create coordinates from the specified latitude and longitude, if they're valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Coordinates;
+    /// assert_eq!(Coordinates::new(52.2297, 21.0122), Some(Coordinates { lat: 52.2297, long: 21.0122 }));
+    /// assert_eq!(Coordinates::new(91.0, 21.0122), None);
+    /// assert_eq!(Coordinates::new(52.2297, -181.0), None);
+    /// ```
+    pub fn new(lat: f64, long: f64) -> Option<Coordinates> {
+        if Coordinates::valid_lat(lat) && Coordinates::valid_long(long) {
+            Some(Coordinates {
+                lat: lat,
+                long: long,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check whether the specified latitude is in range.
+    pub fn valid_lat(lat: f64) -> bool {
+        -90.0 <= lat && lat <= 90.0
+    }
+
+    /// Check whether the specified longitude is in range.
+    pub fn valid_long(long: f64) -> bool {
+        -180.0 <= long && long <= 180.0
+    }
+}
+
+impl Eq for Coordinates {}
+
+impl Hash for Coordinates {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.lat.to_string().hash(state);
+        self.long.to_string().hash(state);
+    }
+}
+
 
 impl From<QueuedTweet> for QueuedTweetForSerialisation {
     fn from(qt: QueuedTweet) -> QueuedTweetForSerialisation {
         QueuedTweetForSerialisation {
             author: qt.author,
-            time: qt.time.to_rfc3339(),
+            time: match qt.after {
+                Some(ref anchor) => anchor.to_string(),
+                None => qt.time.to_rfc3339(),
+            },
             content: qt.content,
+            media: if qt.media.is_empty() { None } else { Some(qt.media) },
+            thread: qt.thread,
+            in_reply_to: qt.in_reply_to,
+            extra_params: if qt.extra_params.is_empty() {
+                None
+            } else {
+                Some(qt.extra_params)
+            },
+            variants: if qt.variants.is_empty() { None } else { Some(qt.variants) },
+            content_warning: qt.content_warning,
+            lat: qt.coordinates.map(|c| c.lat),
+            long: qt.coordinates.map(|c| c.long),
+            place_id: qt.place_id,
+            reply_settings: qt.reply_settings.map(|rs| rs.name().to_string()),
+            name: qt.name,
+            paused: if qt.paused { Some(true) } else { None },
+            blocked: qt.blocked,
+            timezone: qt.settings.timezone,
+            quiet_hours: qt.settings.quiet_hours,
+            jitter: qt.settings.jitter,
+            tags: qt.settings.tags,
+            sensitive: qt.settings.sensitive,
             time_posted: qt.time_posted.map(|dt| dt.to_rfc3339()),
             id: qt.id,
+            app: qt.app,
+            retries: if qt.retries == 0 { None } else { Some(qt.retries) },
         }
     }
 }
 
-impl Into<Result<QueuedTweet, ParseError>> for QueuedTweetForSerialisation {
-    fn into(self) -> Result<QueuedTweet, ParseError> {
+/// The error is the key of the invalid value and what's wrong with it.
+impl Into<Result<QueuedTweet, (&'static str, String)>> for QueuedTweetForSerialisation {
+    fn into(self) -> Result<QueuedTweet, (&'static str, String)> {
+        fn datetime(key: &'static str, dts: &str) -> Result<DateTime<FixedOffset>, (&'static str, String)> {
+            DateTime::parse_from_rfc3339(dts).map_err(|e| (key, format!("invalid RFC3339 datetime ({})", e)))
+        }
+
+        let (time, after) = match (datetime("time", &self.time), Anchor::parse(&self.time)) {
+            (Ok(time), _) => (time, None),
+            // Computed from the anchor's time by QueuedTweet::schedule()
+            (_, Some(Ok(anchor))) => (FixedOffset::east(0).timestamp(0, 0), Some(anchor)),
+            (_, Some(Err(e))) => return Err(("time", format!("invalid relative time ({})", e))),
+            (Err(e), None) => return Err(e),
+        };
+
         Ok(QueuedTweet {
             author: self.author,
-            time: try!(DateTime::parse_from_rfc3339(&self.time)),
+            time: time,
             content: self.content,
+            media: self.media.unwrap_or_default(),
+            thread: self.thread,
+            in_reply_to: self.in_reply_to,
+            extra_params: self.extra_params.unwrap_or_default(),
+            variants: self.variants.unwrap_or_default(),
+            content_warning: self.content_warning,
+            coordinates: match (self.lat, self.long) {
+                (Some(lat), _) if !Coordinates::valid_lat(lat) => return Err(("lat", "latitude out of range [-90, 90]".to_string())),
+                (_, Some(long)) if !Coordinates::valid_long(long) => return Err(("long", "longitude out of range [-180, 180]".to_string())),
+                (Some(lat), Some(long)) => Coordinates::new(lat, long),
+                (Some(_), None) => return Err(("lat", "latitude without longitude".to_string())),
+                (None, Some(_)) => return Err(("long", "longitude without latitude".to_string())),
+                (None, None) => None,
+            },
+            place_id: self.place_id,
+            reply_settings: match self.reply_settings {
+                Some(ref rs) => {
+                    Some(try!(ReplySettings::parse(rs)
+                        .ok_or_else(|| ("reply_settings", "invalid reply settings, expected \"everyone\", \"following\" or \"mentionedUsers\"".to_string()))))
+                }
+                None => None,
+            },
+            name: self.name,
+            after: after,
+            paused: self.paused.unwrap_or(false),
+            blocked: self.blocked,
+            settings: Settings {
+                timezone: match self.timezone {
+                    Some(ref tz) if parse_timezone(tz).is_none() => return Err(("timezone", "invalid UTC offset".to_string())),
+                    tz => tz,
+                },
+                quiet_hours: match self.quiet_hours {
+                    Some(ref qh) if QuietHours::parse(qh).is_none() => return Err(("quiet_hours", "invalid span of time".to_string())),
+                    qh => qh,
+                },
+                jitter: self.jitter,
+                tags: self.tags,
+                sensitive: self.sensitive,
+            },
             time_posted: match self.time_posted {
-                Some(dts) => Some(try!(DateTime::parse_from_rfc3339(&dts))),
+                Some(dts) => Some(try!(datetime("time_posted", &dts))),
                 None => None,
             },
             id: self.id,
+            app: self.app,
+            retries: self.retries.unwrap_or(0),
         })
     }
 }
+
+
+/// The encoder leaves control characters other than whitespace unescaped, but the parser rejects them, so escape them, too.
+///
+/// Line breaks are the only ones the encoder writes outside of strings.
+fn escape_control_characters(toml: String) -> String {
+    let mut escaped = String::with_capacity(toml.len());
+    for c in toml.chars() {
+        if c < ' ' && c != '\n' {
+            escaped.push_str(&format!("\\u{:04X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
@@ -0,0 +1,218 @@
+//! This module contains the functions used only by the `quick-queue` subsystem.
+//!
+//! The flow of the `quick-queue` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::quick_queue::parse()
+//! |> ops::queue_tweet::tweets_path()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::queue_tweet::duplicates()
+//! |> ops::queue_tweet::collisions()
+//! |> ops::QueuedTweet::write_with_broken()
+//! |> ops::quick_queue::print_success_message()
+//! ```
+//!
+//! The tweet is specified in a single argument as its content, the time to post it at and the account to post it as,
+//! separated by the delimiter, `|` by default (see `Config::quick_delimiter`), like `Ship day! | in 2 hours | @brandacct`.
+//!
+//! Whitespace around each segment is removed, and the account's leading `@` is optional. In any segment, `\` followed by the
+//! delimiter stands for the delimiter itself, `\\` for a backslash, and `\n` for a newline; anything else after a `\` is an
+//! error.
+
+
+use self::super::queue_tweet::parse_time;
+use self::super::l10n::format_datetime;
+use chrono::{DateTime, FixedOffset};
+use self::super::settings::Settings;
+use std::collections::BTreeMap;
+use self::super::QueuedTweet;
+use self::super::schedule;
+use std::io::Write;
+
+
+/// The names of the segments, in order, as used in error messages.
+pub static SEGMENTS: &'static [&'static str] = &["content", "time", "account"];
+
+
+/// Parse the specified single-argument tweet, with its segments separated by the specified delimiter, scheduling relative
+/// times after the specified time.
+///
+/// Returns the tweet, or what's wrong with each segment that's wrong, named.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::quick_queue;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+///
+/// let tweet = quick_queue::parse("Ship day! | in 2 hours | @brandacct", "|", now).unwrap();
+/// assert_eq!(tweet.content, "Ship day!");
+/// assert_eq!(tweet.time, now + Duration::hours(2));
+/// assert_eq!(tweet.author, "brandacct");
+///
+/// let tweet = quick_queue::parse(r"Cats | dogs\nvote now ;; 2016-09-10T12:00:00+02:00 ;; brandacct", ";;", now).unwrap();
+/// assert_eq!(tweet.content, "Cats | dogs\nvote now");
+///
+/// assert_eq!(quick_queue::parse("Ship day! | in 2 fortnights | ", "|", now),
+///            Err(vec!["time segment: \"in 2 fortnights\" is not a valid relative time: unknown unit \"fortnights\", \
+///                      expected one of seconds, minutes, hours, days or weeks".to_string(),
+///                     "account segment: empty".to_string()]));
+/// assert_eq!(quick_queue::parse("Ship day! | in 2 hours", "|", now),
+///            Err(vec!["expected the content, time and account separated by \"|\", got 2 segments".to_string()]));
+/// # }
+/// ```
+pub fn parse(spec: &str, delimiter: &str, now: DateTime<FixedOffset>) -> Result<QueuedTweet, Vec<String>> {
+    let raw = raw_segments(spec, delimiter);
+    if raw.len() != SEGMENTS.len() {
+        return Err(vec![format!("expected the content, time and account separated by \"{}\", got {} segment{}",
+                                delimiter,
+                                raw.len(),
+                                if raw.len() == 1 { "" } else { "s" })]);
+    }
+
+    let segment = |i: usize| match unescape(raw[i].trim(), delimiter) {
+        Ok(ref segment) if segment.is_empty() => Err(format!("{} segment: empty", SEGMENTS[i])),
+        Ok(segment) => Ok(segment),
+        Err(e) => Err(format!("{} segment: {}", SEGMENTS[i], e)),
+    };
+    let content = segment(0);
+    let time = segment(1).and_then(|time| {
+        parse_time(&time, now)
+            .and_then(|t| schedule::within_horizon(t, now).map_err(|e| format!("\"{}\" is {}", time, e)))
+            .map_err(|e| format!("time segment: {}", e))
+    });
+    let author = segment(2).and_then(|author| if author.trim_left_matches('@').is_empty() || author.contains(char::is_whitespace) {
+        Err(format!("account segment: \"{}\" isn't an account name", author))
+    } else {
+        Ok(author.trim_left_matches('@').to_string())
+    });
+
+    match (content, time, author) {
+        (Ok(content), Ok(time), Ok(author)) => {
+            Ok(QueuedTweet {
+                author: author,
+                time: time,
+                content: content,
+                media: vec![],
+                thread: None,
+                in_reply_to: None,
+                extra_params: BTreeMap::new(),
+                variants: BTreeMap::new(),
+                content_warning: None,
+                coordinates: None,
+                place_id: None,
+                reply_settings: None,
+                name: None,
+                after: None,
+                paused: false,
+                blocked: None,
+                settings: Settings::default(),
+                time_posted: None,
+                id: None,
+                app: None,
+                retries: 0,
+            })
+        }
+        (content, time, author) => Err(vec![content.err(), time.err(), author.err()].into_iter().filter_map(|e| e).collect()),
+    }
+}
+
+/// Check whether the specified delimiter can separate segments: it has to be non-empty and not contain `\`, which starts
+/// escapes.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::quick_queue;
+/// assert_eq!(quick_queue::check_delimiter(";;"), Ok(()));
+/// assert_eq!(quick_queue::check_delimiter(""), Err("the delimiter can't be empty".to_string()));
+/// assert_eq!(quick_queue::check_delimiter("\\|"), Err("the delimiter \"\\|\" can't contain \"\\\"".to_string()));
+/// ```
+pub fn check_delimiter(delimiter: &str) -> Result<(), String> {
+    if delimiter.is_empty() {
+        Err("the delimiter can't be empty".to_string())
+    } else if delimiter.contains('\\') {
+        Err(format!("the delimiter \"{}\" can't contain \"\\\"", delimiter))
+    } else {
+        Ok(())
+    }
+}
+
+/// Print the message confirming the specified tweet was queued.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::quick_queue;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+/// let tweet = quick_queue::parse("Ship day! | in 2 hours | @brandacct", "|", now).unwrap();
+///
+/// let mut out = Vec::new();
+/// quick_queue::print_success_message(&mut out, &tweet);
+/// assert_eq!(String::from_utf8(out).unwrap(), "Queued \"Ship day!\" by brandacct for 2016-09-09T02:33:30+02:00.\n");
+/// # }
+/// ```
+pub fn print_success_message<W: Write>(output: &mut W, tweet: &QueuedTweet) {
+    writeln!(output, "Queued \"{}\" by {} for {}.", tweet.content, tweet.author, format_datetime(&tweet.time)).unwrap();
+}
+
+
+fn raw_segments<'s>(spec: &'s str, delimiter: &str) -> Vec<&'s str> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut i = 0;
+    while i < spec.len() {
+        let rest = &spec[i..];
+        if rest.starts_with('\\') {
+            // Skip whatever's escaped, so an escaped delimiter doesn't end the segment
+            let escaped = &rest[1..];
+            i += 1 + if escaped.starts_with(delimiter) {
+                delimiter.len()
+            } else {
+                escaped.chars().next().map(char::len_utf8).unwrap_or(0)
+            };
+        } else if rest.starts_with(delimiter) {
+            segments.push(&spec[start..i]);
+            i += delimiter.len();
+            start = i;
+        } else {
+            i += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+    segments.push(&spec[start..]);
+    segments
+}
+
+fn unescape(segment: &str, delimiter: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(idx) = rest.find('\\') {
+        result.push_str(&rest[..idx]);
+        rest = &rest[idx + 1..];
+
+        if rest.starts_with(delimiter) {
+            result.push_str(delimiter);
+            rest = &rest[delimiter.len()..];
+        } else {
+            match rest.chars().next() {
+                Some('\\') => result.push('\\'),
+                Some('n') => result.push('\n'),
+                Some(c) => return Err(format!("unknown escape \"\\{}\", expected \"\\{}\", \"\\\\\" or \"\\n\"", c, delimiter)),
+                None => return Err("unfinished escape at the end".to_string()),
+            }
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
@@ -0,0 +1,289 @@
+//! This module contains the functions used only by the `reconcile` subsystem.
+//!
+//! The flow of the `reconcile` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::prune_posted::verify()
+//! |> ops::Apps::read()
+//! |> ops::User::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::archive::read_all()
+//! |> ops::prune_posted::read_pruned()
+//! ```
+//!
+//! Then, for each user:
+//!
+//! ```plaintext
+//! ops::reconcile::timeline()
+//! |> ops::reconcile::discrepancies()
+//! |> ops::reconcile::print_discrepancies()
+//! ```
+//!
+//! Only the most recent page of the timeline is fetched, so only tweets posted since its oldest tweet are checked for having
+//! been deleted; tweets deleted with `prune-posted` are expected to be missing, and aren't reported.
+
+
+use self::super::queue_tweet::find_urls;
+use self::super::l10n::format_datetime;
+use self::super::QueuedTweet;
+use chrono::{DateTime, Duration, FixedOffset};
+use rustc_serialize::json::Json;
+use std::collections::BTreeSet;
+use std::io::Write;
+#[cfg(feature = "network")]
+use self::super::{User, network, oauth};
+#[cfg(feature = "network")]
+use self::super::super::Outcome;
+#[cfg(feature = "network")]
+use std::collections::BTreeMap;
+#[cfg(feature = "network")]
+use egg_mode::Token;
+
+
+/// The statuses/user_timeline API endpoint.
+pub static USER_TIMELINE_URL: &'static str = "https://api.twitter.com/1.1/statuses/user_timeline.json";
+
+/// How many of the most recent tweets, including retweets, to fetch from each timeline, the most the API allows at once.
+pub const TIMELINE_PAGE_SIZE: usize = 200;
+
+/// How long before its scheduled time a tweet can be posted, allowing for clocks being a bit off.
+pub const MAX_EARLINESS_SECONDS: i64 = 60;
+
+
+/// A tweet on an account's timeline.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Posted {
+    /// The tweet's ID.
+    pub id: i64,
+    /// When the tweet was posted.
+    pub time: DateTime<FixedOffset>,
+    /// The tweet's text, as returned by the API, with links shortened and `&<>` escaped.
+    pub text: String,
+}
+
+/// A difference between what was recorded and what's on the timeline.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Discrepancy<'t> {
+    /// The queued tweet was posted as the specified one, but not recorded as posted, as if tweetr stopped right after posting it.
+    Unrecorded(&'t QueuedTweet, &'t Posted),
+    /// The tweet recorded as posted isn't on the timeline, as if deleted by hand.
+    Deleted(&'t QueuedTweet),
+}
+
+
+/// Get the specified user's most recent tweets, newest first, on behalf of the specified application.
+///
+/// Retweets are skipped. Waits for the rate limits to reset if they're hit, see `network::request()`.
+#[cfg(feature = "network")]
+pub fn timeline(user: &User, app: &Token) -> Result<Vec<Posted>, Outcome> {
+    let desc = format!("fetching the timeline of @{}", user.name);
+    let mut params = BTreeMap::new();
+    params.insert("user_id".to_string(), user.id.to_string());
+    params.insert("count".to_string(), TIMELINE_PAGE_SIZE.to_string());
+    params.insert("trim_user".to_string(), "true".to_string());
+    params.insert("tweet_mode".to_string(), "extended".to_string());
+
+    let user = user.clone();
+    let app = network::owned_token(app);
+    let resp = try!(network::request(&desc, move || oauth::get(USER_TIMELINE_URL, &params, &app, &user.raw_token())));
+    parse_timeline(&resp).ok_or_else(|| Outcome::TwitterAPIError(format!("{}: unexpected response", desc)))
+}
+
+/// Parse the tweets in the specified statuses/user_timeline response, skipping retweets.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate rustc_serialize;
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::reconcile::{self, Posted};
+/// # use rustc_serialize::json::Json;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let resp = Json::from_str(r#"[{"id": 774541307568103424, "created_at": "Sat Sep 10 10:00:05 +0000 2016",
+///                                "full_text": "Abolish the bourgeoisie"},
+///                               {"id": 774541307568103423, "created_at": "Sat Sep 10 09:00:00 +0000 2016",
+///                                "full_text": "RT @tweetr_test: Hello", "retweeted_status": {}}]"#).unwrap();
+/// assert_eq!(reconcile::parse_timeline(&resp),
+///            Some(vec![Posted {
+///                          id: 774541307568103424,
+///                          time: DateTime::parse_from_rfc3339("2016-09-10T10:00:05+00:00").unwrap(),
+///                          text: "Abolish the bourgeoisie".to_string(),
+///                      }]));
+/// assert_eq!(reconcile::parse_timeline(&Json::from_str("{}").unwrap()), None);
+/// # }
+/// ```
+pub fn parse_timeline(resp: &Json) -> Option<Vec<Posted>> {
+    resp.as_array().and_then(|tweets| {
+        tweets.iter()
+            .filter(|tweet| tweet.find("retweeted_status").is_none())
+            .map(|tweet| {
+                match (tweet.find("id").and_then(Json::as_i64),
+                       tweet.find("created_at").and_then(Json::as_string).and_then(|ca| DateTime::parse_from_str(ca, "%a %b %d %H:%M:%S %z %Y").ok()),
+                       tweet.find("full_text").or_else(|| tweet.find("text")).and_then(Json::as_string)) {
+                    (Some(id), Some(time), Some(text)) => {
+                        Some(Posted {
+                            id: id,
+                            time: time,
+                            text: text.to_string(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    })
+}
+
+/// Check whether the specified tweet content was posted as the specified text.
+///
+/// Links, which Twitter shortens, aren't compared, and neither are case, whitespace and quotes, which can be changed by the
+/// configured transformations, see `ops::transform`; the text can also have more after the content, like appended hashtags.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::reconcile;
+/// assert!(reconcile::matches("Abolish   the bourgeoisie: https://example.com",
+///                            "abolish the bourgeoisie: https://t.co/abcdefghij #TweetrRelease"));
+/// assert!(reconcile::matches("“Workers” & <owners>", "\"Workers\" &amp; &lt;owners&gt;"));
+/// assert!(!reconcile::matches("Abolish the bourgeoisie", "Abolish the proletariat"));
+/// ```
+pub fn matches(content: &str, text: &str) -> bool {
+    let content = normalise(content);
+    !content.is_empty() && normalise(&text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")).starts_with(&content)
+}
+
+/// Find the discrepancies between the specified author's recorded tweets and timeline.
+///
+/// Each unposted tweet is matched to at most one unrecorded tweet on the timeline, posted at or after its scheduled time, with
+/// its content; recorded tweets posted since the oldest one on the timeline, but not on it, and not pruned, were deleted.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::reconcile::{self, Discrepancy, Posted};
+/// # use tweetr::ops::QueuedTweet;
+/// # use std::collections::{BTreeMap, BTreeSet};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = |content: &str, time: &str, id: Option<i64>| QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339(time).unwrap(),
+///     content: content.to_string(),
+///     time_posted: id.map(|_| DateTime::parse_from_rfc3339(time).unwrap()),
+///     id: id,
+///     ..Default::default()
+/// };
+/// let posted = |id: i64, time: &str, text: &str| Posted {
+///     id: id,
+///     time: DateTime::parse_from_rfc3339(time).unwrap(),
+///     text: text.to_string(),
+/// };
+///
+/// let recorded = vec![tweet("Abolish", "2016-09-10T12:00:00+02:00", Some(1)),
+///                     tweet("the", "2016-09-11T12:00:00+02:00", Some(2)),
+///                     tweet("bourgeoisie", "2016-09-12T12:00:00+02:00", None)];
+/// let timeline = vec![posted(3, "2016-09-12T12:00:05+02:00", "bourgeoisie"), posted(1, "2016-09-10T12:00:05+02:00", "Abolish")];
+/// assert_eq!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()),
+///            vec![Discrepancy::Unrecorded(&recorded[2], &timeline[0]), Discrepancy::Deleted(&recorded[1])]);
+/// assert_eq!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &vec![2].into_iter().collect()),
+///            vec![Discrepancy::Unrecorded(&recorded[2], &timeline[0])]);
+/// # }
+/// ```
+pub fn discrepancies<'t>(author: &str, timeline: &'t [Posted], recorded: &'t [QueuedTweet], pruned: &BTreeSet<i64>) -> Vec<Discrepancy<'t>> {
+    let ids: BTreeSet<_> = recorded.iter().flat_map(|t| t.id).collect();
+    let mut matched = BTreeSet::new();
+
+    let mut discrepancies: Vec<_> = recorded.iter()
+        .filter(|t| t.author == author && t.id.is_none())
+        .flat_map(|t| {
+            let posted = timeline.iter().rev().find(|p| {
+                !ids.contains(&p.id) && !matched.contains(&p.id) && p.time >= t.time - Duration::seconds(MAX_EARLINESS_SECONDS) &&
+                matches(&t.content, &p.text)
+            });
+            if let Some(p) = posted {
+                matched.insert(p.id);
+            }
+            posted.map(|p| Discrepancy::Unrecorded(t, p))
+        })
+        .collect();
+
+    if let Some(oldest) = timeline.iter().map(|p| p.time).min() {
+        let on_timeline: BTreeSet<_> = timeline.iter().map(|p| p.id).collect();
+        discrepancies.extend(recorded.iter()
+            .filter(|t| t.author == author && t.time_posted.map(|tp| tp >= oldest).unwrap_or(false))
+            .filter(|t| t.id.map(|id| !on_timeline.contains(&id) && !pruned.contains(&id)).unwrap_or(false))
+            .map(Discrepancy::Deleted));
+    }
+
+    discrepancies
+}
+
+/// Print the specified discrepancies found for the specified author, or that there are none.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::reconcile;
+/// let mut out = Vec::new();
+/// reconcile::print_discrepancies(&mut out, "nabijaczleweli", &[]);
+/// assert_eq!(String::from_utf8(out).unwrap(), "@nabijaczleweli: no discrepancies.\n");
+/// ```
+pub fn print_discrepancies<W: Write>(output: &mut W, author: &str, discrepancies: &[Discrepancy]) {
+    if discrepancies.is_empty() {
+        writeln!(output, "@{}: no discrepancies.", author).unwrap();
+        return;
+    }
+
+    writeln!(output,
+             "@{}: {} discrepanc{}:",
+             author,
+             discrepancies.len(),
+             if discrepancies.len() == 1 { "y" } else { "ies" })
+        .unwrap();
+    for discrepancy in discrepancies {
+        match *discrepancy {
+            Discrepancy::Unrecorded(tweet, posted) => {
+                writeln!(output,
+                         "  Tweet \"{}\" scheduled for {} was posted at {} with ID {}, but isn't recorded as posted",
+                         tweet.content,
+                         format_datetime(&tweet.time),
+                         format_datetime(&posted.time),
+                         posted.id)
+                    .unwrap()
+            }
+            Discrepancy::Deleted(tweet) => {
+                writeln!(output,
+                         "  Tweet \"{}\" recorded as posted at {} with ID {} isn't on the timeline, it might've been deleted",
+                         tweet.content,
+                         format_datetime(&tweet.time_posted.unwrap()),
+                         tweet.id.unwrap())
+                    .unwrap()
+            }
+        }
+    }
+}
+
+
+fn normalise(s: &str) -> String {
+    let mut s = s.to_string();
+    for url in find_urls(&s.clone()) {
+        s = s.replace(url, " ");
+    }
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '“' | '”' | '„' => '"',
+            '‘' | '’' => '\'',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,531 @@
+//! Checkpointing the daemon, and reporting what it recovers when it's started again after stopping.
+//!
+//! While running, the daemon records in `checkpoint.toml` in the configuration directory when it last went over the queue,
+//! and, while posting, which tweets it started posting without having recorded them as posted in the queue yet. When it
+//! starts, the checkpoint is compared with the queue, the archive and the journal, and each of the following is reported,
+//! along with what's done about it:
+//!
+//!   * posted tweets that weren't archived are archived, or only removed from the queue if the archive or the journal has
+//!     them archived already; posted tweets kept in the queue for the rest of their thread or for the tweets scheduled after
+//!     them aren't reported,
+//!   * tweets that were being posted when the daemon stopped are left as they are if the queue or the journal records them
+//!     as posted; otherwise they might or might not have been, so, according to the `recovery_policy`, they're paused, to be
+//!     checked and unpaused by hand, or posted again,
+//!   * tweets that came due while the daemon wasn't running are posted late, or paused, if they're later than the
+//!     `missed_expiry`.
+//!
+//! The changes are journalled as made by `start-daemon`, like all others it makes.
+//!
+//! The flow of recovering is as follows:
+//!
+//! ```plaintext
+//! ops::recovery::Checkpoint::read()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::archive::read_since()
+//! |> ops::journal::read()
+//! |> ops::recovery::check()
+//! |> ops::recovery::apply()
+//! |> ops::recovery::print_report()
+//! ```
+
+
+use self::super::journal::{Action, Record, Snapshot};
+use self::super::l10n::format_datetime;
+use self::super::{QueuedTweet, archive, read_toml_file, replace_file};
+use self::super::super::Outcome;
+use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use toml::encode_str;
+
+
+/// What the daemon was doing when it last went over the queue.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Checkpoint {
+    /// When the daemon last went over the queue, in seconds since the epoch.
+    pub time: i64,
+    /// The tweets the daemon started posting, but didn't record as posted in the queue yet.
+    pub in_flight: Vec<InFlight>,
+}
+
+/// A tweet the daemon started posting.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct InFlight {
+    /// The author it's posted on behalf of.
+    pub author: String,
+    /// Its content, as queued.
+    pub content: String,
+    /// The time it's scheduled for, in seconds since the epoch.
+    pub time: i64,
+    /// When the daemon started posting it, in seconds since the epoch.
+    pub started: i64,
+}
+
+/// What to do with the tweets that might or might not have been posted when the daemon stopped.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Policy {
+    /// Pause them, to be checked and unpaused by hand.
+    Hold,
+    /// Post them again, at the risk of posting them twice.
+    Retry,
+}
+
+/// What was found when starting the daemon.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Kind {
+    /// A posted tweet that wasn't archived.
+    Unarchived,
+    /// A tweet that was being posted when the daemon stopped.
+    InFlight,
+    /// A tweet that came due while the daemon wasn't running.
+    Missed,
+}
+
+/// What's done about what was found.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Resolution {
+    /// The tweet's archived.
+    Archive,
+    /// The tweet's archived already, so only removed from the queue.
+    Drop,
+    /// The tweet's recorded as posted already, so left as it is.
+    Leave,
+    /// The tweet's paused.
+    Pause,
+    /// The tweet's posted.
+    Post,
+}
+
+/// Something found when starting the daemon, and what's done about it.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Recovery {
+    /// What was found.
+    pub kind: Kind,
+    /// The index of the tweet in the queue, if it's still there.
+    pub index: Option<usize>,
+    /// The tweet.
+    pub tweet: Snapshot,
+    /// What's done about it.
+    pub resolution: Resolution,
+}
+
+
+/// Check the specified queue, with the specified archived tweets and journal records, for what the daemon has to recover
+/// from when starting at the specified time after stopping at the specified checkpoint, if any.
+///
+/// The tweets that came due while the daemon wasn't running more than the specified time ago, if any, are paused.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::recovery::{self, Checkpoint, InFlight, Kind, Policy, Resolution};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time("2016-09-10T12:00:00+02:00"),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let tweets = vec![tweet.clone(), QueuedTweet { time: time("2016-09-10T15:00:00+02:00"), ..tweet.clone() }];
+/// let checkpoint = Checkpoint {
+///     time: time("2016-09-10T11:59:00+02:00").timestamp(),
+///     in_flight: vec![InFlight::of(&tweet, time("2016-09-10T12:00:00+02:00"))],
+/// };
+///
+/// let recoveries = recovery::check(Some(&checkpoint), &tweets, &[], &[], Policy::Hold, Some(Duration::hours(1)),
+///                                  time("2016-09-10T15:30:00+02:00"));
+/// assert_eq!(recoveries.iter().map(|r| (r.kind, r.index, r.resolution)).collect::<Vec<_>>(),
+///            vec![(Kind::InFlight, Some(0), Resolution::Pause), (Kind::Missed, Some(1), Resolution::Post)]);
+///
+/// assert!(recovery::check(None, &tweets, &[], &[], Policy::Hold, None, time("2016-09-10T15:30:00+02:00")).is_empty());
+/// # }
+/// ```
+pub fn check(checkpoint: Option<&Checkpoint>, tweets: &[QueuedTweet], archived: &[QueuedTweet], journal: &[Record], policy: Policy,
+             missed_expiry: Option<Duration>, now: DateTime<FixedOffset>)
+             -> Vec<Recovery> {
+    let mut recoveries = vec![];
+
+    for i in archive::archivable(tweets) {
+        let tweet = &tweets[i];
+        let already_archived = archived.iter().any(|a| a.author == tweet.author && a.content == tweet.content && a.id == tweet.id) ||
+                               journal.iter().any(|r| r.action == Action::Archived && r.tweet.author == tweet.author && r.tweet.id == tweet.id);
+        recoveries.push(Recovery {
+            kind: Kind::Unarchived,
+            index: Some(i),
+            tweet: Snapshot::of(tweet),
+            resolution: if already_archived {
+                Resolution::Drop
+            } else {
+                Resolution::Archive
+            },
+        });
+    }
+
+    let checkpoint = match checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return recoveries,
+    };
+    for in_flight in &checkpoint.in_flight {
+        let index = tweets.iter().position(|t| in_flight.is(t));
+        let posted = index.map(|i| tweets[i].id.is_some()).unwrap_or(true) ||
+                     journal.iter().any(|r| {
+            r.action == Action::Posted && r.time.timestamp() >= in_flight.started && r.tweet.author == in_flight.author &&
+            r.tweet.content == in_flight.content && r.tweet.scheduled.timestamp() == in_flight.time
+        });
+        recoveries.push(Recovery {
+            kind: Kind::InFlight,
+            index: index,
+            tweet: index.map(|i| Snapshot::of(&tweets[i])).unwrap_or_else(|| in_flight.snapshot(now.offset())),
+            resolution: match (posted, policy) {
+                (true, _) => Resolution::Leave,
+                (false, Policy::Hold) => Resolution::Pause,
+                (false, Policy::Retry) => Resolution::Post,
+            },
+        });
+    }
+
+    for (i, tweet) in tweets.iter().enumerate() {
+        if tweet.id.is_some() || tweet.paused || tweet.blocked.is_some() || tweet.time.timestamp() <= checkpoint.time || tweet.time > now ||
+           checkpoint.in_flight.iter().any(|f| f.is(tweet)) {
+            continue;
+        }
+
+        recoveries.push(Recovery {
+            kind: Kind::Missed,
+            index: Some(i),
+            tweet: Snapshot::of(tweet),
+            resolution: if missed_expiry.map(|e| now - tweet.time > e).unwrap_or(false) {
+                Resolution::Pause
+            } else {
+                Resolution::Post
+            },
+        });
+    }
+
+    recoveries
+}
+
+/// Make the specified changes to the specified queue, returning the tweets removed from it for being archived already.
+///
+/// Tweets to archive are left for `archive::rotate()`, and tweets to post for the daemon.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::recovery::{self, Kind, Recovery, Resolution};
+/// # use tweetr::ops::journal::Snapshot;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let posted = QueuedTweet { time_posted: Some(tweet.time), id: Some(774560457755590656), ..tweet.clone() };
+/// let mut tweets = vec![posted.clone(), tweet.clone()];
+///
+/// let dropped = recovery::apply(&mut tweets,
+///                               &[Recovery {
+///                                     kind: Kind::Unarchived,
+///                                     index: Some(0),
+///                                     tweet: Snapshot::of(&posted),
+///                                     resolution: Resolution::Drop,
+///                                 },
+///                                 Recovery {
+///                                     kind: Kind::InFlight,
+///                                     index: Some(1),
+///                                     tweet: Snapshot::of(&tweet),
+///                                     resolution: Resolution::Pause,
+///                                 }]);
+/// assert_eq!(dropped, vec![posted]);
+/// assert_eq!(tweets, vec![QueuedTweet { paused: true, ..tweet }]);
+/// # }
+/// ```
+pub fn apply(tweets: &mut Vec<QueuedTweet>, recoveries: &[Recovery]) -> Vec<QueuedTweet> {
+    let mut to_drop = vec![];
+    for recovery in recoveries {
+        match (recovery.index, recovery.resolution) {
+            (Some(i), Resolution::Pause) => tweets[i].paused = true,
+            (Some(i), Resolution::Drop) => to_drop.push(i),
+            _ => (),
+        }
+    }
+
+    to_drop.sort();
+    to_drop.dedup();
+    let mut dropped: Vec<_> = to_drop.into_iter().rev().map(|i| tweets.remove(i)).collect();
+    dropped.reverse();
+    dropped
+}
+
+/// Pause the specified tweet, if posting it failed with the specified outcome after possibly reaching the backend, and the
+/// specified policy is to hold such tweets, returning whether it was paused.
+///
+/// A post that timed out might've been posted all the same, only without the response making it back, so it's treated like
+/// one that was being posted when the daemon stopped.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::recovery::{self, Policy};
+/// # use tweetr::ops::QueuedTweet;
+/// # use tweetr::Outcome;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let timed_out = Outcome::NetworkTimeout { desc: "posting to Twitter as @nabijaczleweli".to_string() };
+///
+/// assert!(!recovery::hold_timed_out(&mut tweet, &timed_out, Policy::Retry));
+/// assert!(!recovery::hold_timed_out(&mut tweet, &Outcome::TwitterAPIError("Status is a duplicate.".to_string()), Policy::Hold));
+/// assert!(!tweet.paused);
+///
+/// assert!(recovery::hold_timed_out(&mut tweet, &timed_out, Policy::Hold));
+/// assert!(tweet.paused);
+/// # }
+/// ```
+pub fn hold_timed_out(tweet: &mut QueuedTweet, out: &Outcome, policy: Policy) -> bool {
+    match (out, policy) {
+        (&Outcome::NetworkTimeout { .. }, Policy::Hold) => {
+            tweet.paused = true;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Print what was recovered from the daemon stopping at the specified time, if known, if anything.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::recovery::{self, Kind, Recovery, Resolution};
+/// # use tweetr::ops::journal::Snapshot;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+/// let tweet = Snapshot {
+///     author: "nabijaczleweli".to_string(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     scheduled: time("2016-09-10T12:00:00+02:00"),
+///     id: None,
+/// };
+///
+/// let mut out = Vec::new();
+/// recovery::print_report(&mut out,
+///                        Some(time("2016-09-10T11:59:00+02:00")),
+///                        &[Recovery {
+///                              kind: Kind::InFlight,
+///                              index: Some(0),
+///                              tweet: tweet.clone(),
+///                              resolution: Resolution::Pause,
+///                          },
+///                          Recovery {
+///                              kind: Kind::Missed,
+///                              index: Some(1),
+///                              tweet: tweet,
+///                              resolution: Resolution::Post,
+///                          }]);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Recovering from the daemon stopping at 2016-09-10T11:59:00+02:00:\n\
+///             \x20 being posted: \"Abolish the bourgeoisie\" by nabijaczleweli, scheduled for 2016-09-10T12:00:00+02:00: \
+///                  paused, check whether it was posted\n\
+///             \x20 came due while stopped: \"Abolish the bourgeoisie\" by nabijaczleweli, scheduled for 2016-09-10T12:00:00+02:00: \
+///                  posting\n");
+///
+/// let mut out = Vec::new();
+/// recovery::print_report(&mut out, None, &[]);
+/// assert!(out.is_empty());
+/// # }
+/// ```
+pub fn print_report<W: Write>(output: &mut W, stopped: Option<DateTime<FixedOffset>>, recoveries: &[Recovery]) {
+    if recoveries.is_empty() {
+        return;
+    }
+
+    match stopped {
+        Some(stopped) => writeln!(output, "Recovering from the daemon stopping at {}:", format_datetime(&stopped)).unwrap(),
+        None => writeln!(output, "Recovering from the daemon stopping:").unwrap(),
+    }
+    for recovery in recoveries {
+        writeln!(output,
+                 "  {}: \"{}\" by {}, scheduled for {}: {}",
+                 recovery.kind.description(),
+                 recovery.tweet.content,
+                 recovery.tweet.author,
+                 format_datetime(&recovery.tweet.scheduled),
+                 match (recovery.kind, recovery.resolution) {
+                     (_, Resolution::Archive) => "archived",
+                     (_, Resolution::Drop) => "archived already, removed from the queue",
+                     (_, Resolution::Leave) => "recorded as posted",
+                     (Kind::InFlight, Resolution::Pause) => "paused, check whether it was posted",
+                     (_, Resolution::Pause) => "paused, too late to post",
+                     (Kind::InFlight, Resolution::Post) => "posting again",
+                     (_, Resolution::Post) => "posting",
+                 })
+            .unwrap();
+    }
+}
+
+
+impl Checkpoint {
+    /// Get the path to the daemon's checkpoint in the specified configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::recovery::Checkpoint;
+    /// # use std::path::Path;
+    /// assert_eq!(Checkpoint::path(Path::new("$HOME/.tweetr")), Path::new("$HOME/.tweetr/checkpoint.toml"));
+    /// ```
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join("checkpoint.toml")
+    }
+
+    /// Get a checkpoint at the specified time, with nothing being posted.
+    pub fn at(now: DateTime<FixedOffset>) -> Checkpoint {
+        Checkpoint {
+            time: now.timestamp(),
+            in_flight: vec![],
+        }
+    }
+
+    /// Read the daemon's checkpoint from the specified file, `None` if it doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::recovery::Checkpoint;
+    /// # use chrono::DateTime;
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// # fn main() {
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-recovery-checkpoint-read-0");
+    /// let _ = fs::remove_dir_all(&tf);
+    /// fs::create_dir_all(&tf).unwrap();
+    /// let tf = Checkpoint::path(&tf);
+    ///
+    /// assert_eq!(Checkpoint::read(&tf), Ok(None));
+    ///
+    /// let checkpoint = Checkpoint::at(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+    /// checkpoint.write(&tf).unwrap();
+    /// assert_eq!(Checkpoint::read(&tf), Ok(Some(checkpoint)));
+    /// # }
+    /// ```
+    pub fn read(p: &Path) -> Result<Option<Checkpoint>, Outcome> {
+        if !p.exists() {
+            return Ok(None);
+        }
+
+        match read_toml_file(p, "daemon checkpoint") {
+            Ok(checkpoint) => Ok(Some(checkpoint)),
+            Err(Some(out)) => Err(out),
+            Err(None) => {
+                Err(Outcome::FileParsingFailed {
+                    desc: "daemon checkpoint",
+                    errors: vec!["error: couldn't read the file".to_string()],
+                })
+            }
+        }
+    }
+
+    /// Save the daemon's checkpoint to the specified file.
+    pub fn write(&self, p: &Path) -> Result<(), Outcome> {
+        replace_file(p, &encode_str(self))
+    }
+
+    /// Get when the daemon last went over the queue, in the specified UTC offset.
+    pub fn last_ran(&self, tz: &FixedOffset) -> DateTime<FixedOffset> {
+        tz.timestamp(self.time, 0)
+    }
+}
+
+impl InFlight {
+    /// Record the specified tweet as started being posted at the specified time.
+    pub fn of(tweet: &QueuedTweet, now: DateTime<FixedOffset>) -> InFlight {
+        InFlight {
+            author: tweet.author.clone(),
+            content: tweet.content.clone(),
+            time: tweet.time.timestamp(),
+            started: now.timestamp(),
+        }
+    }
+
+    /// Check whether the specified queued tweet is this one.
+    pub fn is(&self, tweet: &QueuedTweet) -> bool {
+        tweet.author == self.author && tweet.content == self.content && tweet.time.timestamp() == self.time
+    }
+
+    fn snapshot(&self, tz: &FixedOffset) -> Snapshot {
+        Snapshot {
+            author: self.author.clone(),
+            content: self.content.clone(),
+            scheduled: tz.timestamp(self.time, 0),
+            id: None,
+        }
+    }
+}
+
+impl Policy {
+    /// Parse a policy from its name, in any case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::recovery::Policy;
+    /// assert_eq!(Policy::parse("hold"), Ok(Policy::Hold));
+    /// assert_eq!(Policy::parse("Retry"), Ok(Policy::Retry));
+    /// assert_eq!(Policy::parse("ignore"), Err("\"ignore\" is not one of hold or retry".to_string()));
+    /// ```
+    pub fn parse(s: &str) -> Result<Policy, String> {
+        match &s.to_lowercase()[..] {
+            "hold" => Ok(Policy::Hold),
+            "retry" => Ok(Policy::Retry),
+            _ => Err(format!("\"{}\" is not one of hold or retry", s)),
+        }
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy::Hold
+    }
+}
+
+impl Kind {
+    /// Get a description of what was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::recovery::Kind;
+    /// assert_eq!(Kind::Unarchived.description(), "posted, not archived");
+    /// assert_eq!(Kind::Missed.description(), "came due while stopped");
+    /// ```
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Kind::Unarchived => "posted, not archived",
+            Kind::InFlight => "being posted",
+            Kind::Missed => "came due while stopped",
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! This module contains the functions used only by the `release-announce` subsystem.
+//!
+//! The flow of the `release-announce` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Template::read()
+//! |> ops::release_announce::changelog_path()
+//! |> ops::release_announce::latest_tag(), unless the version is specified
+//! |> ops::release_announce::parse_changelog()
+//! |> ops::release_announce::remote_url() and ops::release_announce::release_url(), unless the link is specified
+//! |> ops::release_announce::values()
+//! |> ops::Template::fill()
+//! |> ops::release_announce::announcement()
+//! |> ops::QueuedTweet::write_with_broken()
+//! |> ops::quick_queue::print_success_message()
+//! ```
+//!
+//! The announcement is filled in from a template (see `ops::Template`), with the `{version}`, `{highlights}` and `{url}`
+//! placeholders, and split into a thread if it's too long. Its first tweet is named `release-<version>`, so other tweets
+//! can be scheduled after it, and so announcing the same release again doesn't queue it twice.
+
+
+use self::super::queue_tweet::auto_split;
+use self::super::settings::Settings;
+use chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use self::super::{QueuedTweet, Template};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+
+/// The names of the changelog files looked for in a repository, in order.
+pub static CHANGELOG_NAMES: &'static [&'static str] = &["CHANGELOG.md", "CHANGELOG", "CHANGES.md", "CHANGES", "NEWS.md", "NEWS"];
+
+
+/// A release, as described in a changelog.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Release {
+    /// The version, like `"v1.4.0"`.
+    pub version: String,
+    /// The changelog entries' first lines, in order.
+    pub highlights: Vec<String>,
+}
+
+
+/// Find the changelog in the specified file or repository.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::release_announce;
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// let td = temp_dir().join("tweetr-doctest").join("ops-release-announce-changelog_path-0");
+/// fs::create_dir_all(&td).unwrap();
+/// let _ = fs::remove_file(td.join("CHANGELOG.md"));
+/// assert_eq!(release_announce::changelog_path(&td), None);
+///
+/// File::create(td.join("CHANGELOG.md")).unwrap();
+/// assert_eq!(release_announce::changelog_path(&td), Some(td.join("CHANGELOG.md")));
+/// assert_eq!(release_announce::changelog_path(&td.join("CHANGELOG.md")), Some(td.join("CHANGELOG.md")));
+/// ```
+pub fn changelog_path(source: &Path) -> Option<PathBuf> {
+    if source.is_file() {
+        Some(source.to_path_buf())
+    } else {
+        CHANGELOG_NAMES.iter().map(|n| source.join(n)).find(|p| p.is_file())
+    }
+}
+
+/// Find the release with the specified version, or the latest one, in the specified changelog.
+///
+/// Releases are headings, like `## [1.4.0] - 2017-01-29` or `# v1.4.0`, starting with the version, and
+/// headings without a digit, like `## Unreleased` or `### Fixed`, are skipped. Versions match with or without the leading
+/// `v`. The highlights are the list items under the release's heading, up to the next release's.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::release_announce::{self, Release};
+/// let changelog = "# Changelog\n\
+///                  \n\
+///                  ## Unreleased\n\
+///                  - Nothing yet\n\
+///                  \n\
+///                  ## [1.4.0] - 2017-01-29\n\
+///                  ### Added\n\
+///                  - The `release-announce` subsystem\n\
+///                  - `--report`\n\
+///                  ### Fixed\n\
+///                  * Threads posted out of order\n\
+///                  \n\
+///                  ## [1.3.0] - 2016-12-24\n\
+///                  - Content warnings\n";
+///
+/// assert_eq!(release_announce::parse_changelog(changelog, None),
+///            Some(Release {
+///                version: "1.4.0".to_string(),
+///                highlights: vec!["The `release-announce` subsystem".to_string(),
+///                                 "`--report`".to_string(),
+///                                 "Threads posted out of order".to_string()],
+///            }));
+/// assert_eq!(release_announce::parse_changelog(changelog, Some("v1.3.0")),
+///            Some(Release {
+///                version: "v1.3.0".to_string(),
+///                highlights: vec!["Content warnings".to_string()],
+///            }));
+/// assert_eq!(release_announce::parse_changelog(changelog, Some("v1.2.0")), None);
+/// ```
+pub fn parse_changelog(changelog: &str, version: Option<&str>) -> Option<Release> {
+    let mut release: Option<Release> = None;
+    for line in changelog.lines().map(str::trim) {
+        if line.starts_with('#') {
+            let heading_version = line.trim_left_matches('#')
+                .split_whitespace()
+                .next()
+                .map(|v| v.trim_matches(|c| c == '[' || c == ']'))
+                .and_then(|v| if v.contains(|c: char| c.is_digit(10)) { Some(v) } else { None });
+            if let Some(heading_version) = heading_version {
+                if release.is_some() {
+                    break;
+                }
+
+                match version {
+                    Some(version) if bare_version(version) == bare_version(heading_version) => {
+                        release = Some(Release {
+                            version: version.to_string(),
+                            highlights: vec![],
+                        })
+                    }
+                    Some(_) => (),
+                    None => {
+                        release = Some(Release {
+                            version: heading_version.to_string(),
+                            highlights: vec![],
+                        })
+                    }
+                }
+            }
+        } else if let Some(ref mut release) = release {
+            if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+                release.highlights.push(line[2..].trim().to_string());
+            }
+        }
+    }
+    release
+}
+
+/// Get the latest tag in the specified git repository, if it is one and there is one.
+pub fn latest_tag(repo: &Path) -> Option<String> {
+    git(repo, &["describe", "--tags", "--abbrev=0"])
+}
+
+/// Get the URL of the `origin` remote of the specified git repository, if it is one and it has one.
+pub fn remote_url(repo: &Path) -> Option<String> {
+    git(repo, &["config", "--get", "remote.origin.url"])
+}
+
+/// Get the link to the release page of the specified tag in the repository with the specified remote URL, if it's on
+/// GitHub or GitLab.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::release_announce;
+/// assert_eq!(release_announce::release_url("https://github.com/nabijaczleweli/tweetr.git", "v1.4.0"),
+///            Some("https://github.com/nabijaczleweli/tweetr/releases/tag/v1.4.0".to_string()));
+/// assert_eq!(release_announce::release_url("git@github.com:nabijaczleweli/tweetr", "v1.4.0"),
+///            Some("https://github.com/nabijaczleweli/tweetr/releases/tag/v1.4.0".to_string()));
+/// assert_eq!(release_announce::release_url("https://gitlab.com/nabijaczleweli/tweetr", "v1.4.0"),
+///            Some("https://gitlab.com/nabijaczleweli/tweetr/-/releases/v1.4.0".to_string()));
+/// assert_eq!(release_announce::release_url("/srv/git/tweetr.git", "v1.4.0"), None);
+/// ```
+pub fn release_url(remote: &str, tag: &str) -> Option<String> {
+    let remote = remote.trim_right_matches('/');
+    let remote = remote.trim_right_matches(".git");
+    let path = if remote.starts_with("https://") {
+        &remote["https://".len()..]
+    } else if remote.starts_with("git@") {
+        &remote["git@".len()..]
+    } else {
+        return None;
+    };
+
+    let mut parts = path.splitn(2, |c| c == '/' || c == ':');
+    match (parts.next(), parts.next()) {
+        (Some("github.com"), Some(repo)) => Some(format!("https://github.com/{}/releases/tag/{}", repo, tag)),
+        (Some("gitlab.com"), Some(repo)) => Some(format!("https://gitlab.com/{}/-/releases/{}", repo, tag)),
+        _ => None,
+    }
+}
+
+/// Get the values to fill the placeholders the specified template has with, for the specified release, linked to at the
+/// specified URL, if any, with at most the specified amount of highlights, listed one per line.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::release_announce::{self, Release};
+/// # use tweetr::ops::Template;
+/// let template = Template {
+///     name: "release".to_string(),
+///     content: "tweetr {version} is out!\n{highlights}".to_string(),
+/// };
+/// let release = Release {
+///     version: "v1.4.0".to_string(),
+///     highlights: vec!["release-announce".to_string(), "--report".to_string(), "Threads posted in order".to_string()],
+/// };
+///
+/// let values = release_announce::values(&template, &release, Some("https://github.com/nabijaczleweli/tweetr"), 2);
+/// assert_eq!(template.fill(&values), Ok("tweetr v1.4.0 is out!\n- release-announce\n- --report".to_string()));
+/// ```
+pub fn values(template: &Template, release: &Release, url: Option<&str>, highlights: usize) -> BTreeMap<String, String> {
+    let placeholders = template.placeholders().unwrap_or_default();
+
+    let mut values = BTreeMap::new();
+    values.insert("version".to_string(), release.version.clone());
+    values.insert("highlights".to_string(),
+                  release.highlights.iter().take(highlights).map(|h| format!("- {}", h)).collect::<Vec<_>>().join("\n"));
+    if let Some(url) = url {
+        values.insert("url".to_string(), url.to_string());
+    }
+    values.into_iter().filter(|&(ref k, _)| placeholders.contains(k)).collect()
+}
+
+/// Get the name of the first tweet announcing the specified version.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::release_announce;
+/// assert_eq!(release_announce::name("v1.4.0"), "release-v1.4.0");
+/// ```
+pub fn name(version: &str) -> String {
+    format!("release-{}", version)
+}
+
+/// Make the tweets announcing the specified version with the specified content, by the specified author at the specified
+/// time, split into a thread numbered as specified, if too long.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::release_announce;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2017-01-29T12:00:00+01:00").unwrap();
+///
+/// let tweets = release_announce::announcement("tweetr v1.4.0 is out!", "v1.4.0", "nabijaczleweli", time, "{n}/{total} {content}");
+/// assert_eq!(tweets.len(), 1);
+/// assert_eq!(tweets[0].name, Some("release-v1.4.0".to_string()));
+/// assert_eq!(tweets[0].time, time);
+///
+/// let content = format!("tweetr v1.4.0 is out! {}", vec!["Abolish the bourgeoisie."; 15].join(" "));
+/// let tweets = release_announce::announcement(&content, "v1.4.0", "nabijaczleweli", time, "{n}/{total} {content}");
+/// assert_eq!(tweets.len(), 2);
+/// assert_eq!(tweets[0].name, Some("release-v1.4.0".to_string()));
+/// assert_eq!(tweets[1].name, None);
+/// assert_eq!(tweets[1].thread, tweets[0].thread);
+/// # }
+/// ```
+pub fn announcement(content: &str, version: &str, author: &str, time: DateTime<FixedOffset>, numbering: &str) -> Vec<QueuedTweet> {
+    let tweet = QueuedTweet {
+        author: author.to_string(),
+        time: time,
+        content: content.to_string(),
+        media: vec![],
+        thread: None,
+        in_reply_to: None,
+        extra_params: BTreeMap::new(),
+        variants: BTreeMap::new(),
+        content_warning: None,
+        coordinates: None,
+        place_id: None,
+        reply_settings: None,
+        name: None,
+        after: None,
+        paused: false,
+        blocked: None,
+        settings: Settings::default(),
+        time_posted: None,
+        id: None,
+        app: None,
+        retries: 0,
+    };
+
+    let mut tweets = auto_split(tweet, numbering);
+    tweets[0].name = Some(name(version));
+    tweets
+}
+
+
+fn bare_version(version: &str) -> &str {
+    version.trim_left_matches(|c| c == 'v' || c == 'V')
+}
+
+fn git(repo: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .ok()
+        .and_then(|o| if o.status.success() {
+            String::from_utf8(o.stdout).ok()
+        } else {
+            None
+        })
+        .map(|s| s.trim().to_string())
+        .and_then(|s| if s.is_empty() { None } else { Some(s) })
+}
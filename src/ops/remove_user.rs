@@ -0,0 +1,272 @@
+//! This module contains the functions used only by the `remove-user` subsystem.
+//!
+//! The flow of the `remove-user` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::remove_user::verify()
+//! |> ops::User::read()
+//! |> ops::rotate_token::find_user()
+//! |> ops::QueuedTweet::read()
+//! |> ops::remove_user::queued_by()
+//! |> ops::remove_user::check_orphans(), unless orphaning
+//! |> ops::remove_user::prompt_confirm(), unless forced
+//! |> ops::remove_user::remove_user()
+//! |> ops::remove_user::print_success_message()
+//! ```
+
+
+use self::super::super::util::{Confirmation, confirm};
+use self::super::{QueuedTweet, User, verify_file};
+use self::super::rotate_token::find_user;
+use self::super::l10n::{format_datetime, tr};
+use self::super::super::Outcome;
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, Write};
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `remove-user`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it failed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::remove_user;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-remove-user-verify");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(remove_user::verify(&("$TEMP/ops-remove-user-verify".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("users.toml")).unwrap();
+/// assert_eq!(remove_user::verify(&("$TEMP/ops-remove-user-verify".to_string(), tf.clone())), Ok(tf.join("users.toml")));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Get the unposted tweets by the user with the specified name, which can't be posted once it's removed.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{remove_user, QueuedTweet};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let tweets = vec![tweet.clone(),
+///                   QueuedTweet { author: "tweetr_test".to_string(), ..tweet.clone() },
+///                   QueuedTweet { id: Some(481), ..tweet.clone() }];
+///
+/// assert_eq!(remove_user::queued_by(&tweets, "nabijaczleweli"), vec![&tweets[0]]);
+/// assert!(remove_user::queued_by(&tweets, "brandacct").is_empty());
+/// # }
+/// ```
+pub fn queued_by<'t>(tweets: &'t [QueuedTweet], name: &str) -> Vec<&'t QueuedTweet> {
+    tweets.iter().filter(|t| t.id.is_none() && t.author == name).collect()
+}
+
+/// Check whether the user with the specified name can be removed without orphaning the specified queued tweets by it.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{remove_user, QueuedTweet};
+/// # use chrono::DateTime;
+/// # use tweetr::Outcome;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(remove_user::check_orphans("nabijaczleweli", &[]), Ok(()));
+/// assert_eq!(remove_user::check_orphans("nabijaczleweli", &[&tweet]),
+///            Err(Outcome::PreflightFailed(vec!["\"Abolish the bourgeoisie\" scheduled for 2016-09-10T12:00:00+02:00 is by \
+///                                               nabijaczleweli"
+///                                                  .to_string(),
+///                                              "pass --orphan to remove nabijaczleweli anyway, leaving 1 tweet unpostable"
+///                                                  .to_string()])));
+/// # }
+/// ```
+pub fn check_orphans(name: &str, queued: &[&QueuedTweet]) -> Result<(), Outcome> {
+    if queued.is_empty() {
+        Ok(())
+    } else {
+        Err(Outcome::PreflightFailed(queued.iter()
+            .map(|t| format!("\"{}\" scheduled for {} is by {}", t.content, format_datetime(&t.time), name))
+            .chain(Some(format!("pass --orphan to remove {} anyway, leaving {} tweet{} unpostable",
+                                name,
+                                queued.len(),
+                                if queued.len() == 1 { "" } else { "s" })))
+            .collect()))
+    }
+}
+
+/// Ask the user whether to remove the specified user.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{remove_user, User};
+/// # use tweetr::util::Confirmation;
+/// # use std::io::BufReader;
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+///
+/// assert!(remove_user::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new(), &user));
+/// assert!(!remove_user::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"\n" as &[u8]), &mut Vec::new(), &user));
+/// assert!(!remove_user::prompt_confirm(Confirmation::Refuse, &mut BufReader::new(b"y\n" as &[u8]), &mut Vec::new(), &user));
+/// ```
+pub fn prompt_confirm<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W, user: &User) -> bool {
+    confirm(confirmation, input, output, &tr("prompt_remove_user", &[("name", &user.name), ("id", &user.id)]))
+}
+
+/// Remove the user with the specified name from the authenticated users list at the specified path, returning the removed
+/// user.
+///
+/// The list is reread, so changes made to it while confirming aren't lost, and replaced at once, so it's never seen
+/// partially written.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{remove_user, User};
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-remove-user-remove_user");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("users.toml");
+///
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// let other = User { name: "tweetr_test".to_string(), id: 482, ..user.clone() };
+/// User::write(vec![user.clone(), other.clone()], &tf).unwrap();
+///
+/// assert_eq!(remove_user::remove_user(&tf, "nabijaczleweli"), Ok(user));
+/// assert_eq!(User::read(&tf), Ok(vec![other]));
+/// assert_eq!(remove_user::remove_user(&tf, "nabijaczleweli"),
+///            Err(Outcome::RequiredDataFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                desc: "add and authorise user with name \"nabijaczleweli\"".to_string(),
+///            }));
+/// ```
+pub fn remove_user(users_path: &Path, name: &str) -> Result<User, Outcome> {
+    let mut users = try!(User::read_verbatim(users_path).map_err(Option::unwrap));
+    let idx = try!(find_user(&users, name));
+
+    let user = users.remove(idx);
+    try!(User::write(users, users_path));
+    Ok(user)
+}
+
+/// Print the success message mentioning the specified user's name and ID, and how many queued tweets by it were left
+/// unpostable.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{remove_user, User};
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+///
+/// let mut out = Vec::new();
+/// remove_user::print_success_message(&mut out, &user, 0);
+/// assert_eq!(String::from_utf8(out).unwrap(), "Removed user nabijaczleweli#481\n");
+///
+/// let mut out = Vec::new();
+/// remove_user::print_success_message(&mut out, &user, 2);
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Removed user nabijaczleweli#481\n\
+///             2 queued tweets by nabijaczleweli were left in the queue, change their author or remove them.\n");
+/// ```
+pub fn print_success_message<W: Write>(output: &mut W, user: &User, orphaned: usize) {
+    writeln!(output, "{}", tr("user_removed", &[("name", &user.name), ("id", &user.id)])).unwrap();
+    if orphaned != 0 {
+        writeln!(output,
+                 "{} queued tweet{} by {} {} left in the queue, change {} author or remove {}.",
+                 orphaned,
+                 if orphaned == 1 { "" } else { "s" },
+                 user.name,
+                 if orphaned == 1 { "was" } else { "were" },
+                 if orphaned == 1 { "its" } else { "their" },
+                 if orphaned == 1 { "it" } else { "them" })
+            .unwrap();
+    }
+}
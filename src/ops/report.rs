@@ -0,0 +1,242 @@
+//! Run reports, machine-readable summaries of what a subsystem did, for scripts driving tweetr.
+//!
+//! With `--report`, the queue is compared before and after the subsystem runs, the same way as for the journal (see
+//! `journal::diff()`), and a JSON object is written to the specified file when it finishes, successfully or not:
+//!
+//! ```json
+//! {
+//!   "added": 0,
+//!   "archived": 2,
+//!   "duration": 1.5,
+//!   "edited": 0,
+//!   "error": null,
+//!   "exit_code": 0,
+//!   "http_cache": {
+//!     "hits": 0,
+//!     "misses": 0
+//!   },
+//!   "posted": 2,
+//!   "removed": 0,
+//!   "started": "2016-09-10T12:00:00+02:00",
+//!   "subsystem": "post-all",
+//!   "warnings": []
+//! }
+//! ```
+//!
+//! `duration` is in seconds, `error` is the error message the subsystem failed with, and `warnings` are the warnings it
+//! printed along the way, collected with `warn()`. `http_cache` is how many API requests were answered from the HTTP cache,
+//! and how many weren't, see `ops::http_cache`.
+//!
+//! The flow of reporting is as follows:
+//!
+//! ```plaintext
+//! ops::report::collect_warnings()
+//! |> the subsystem
+//! |> ops::report::Report::new(), with the queue before and after, and the tweets archived meanwhile
+//! |> ops::report::Report::write()
+//! ```
+
+
+use self::super::journal::{self, Action};
+use self::super::super::Outcome;
+use self::super::{QueuedTweet, write_file};
+use self::super::http_cache::{self, Stats};
+use rustc_serialize::json::{self, Json};
+use chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::time::Duration;
+use std::path::Path;
+
+
+/// What a subsystem did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// The subsystem, like `"queue-tweet"`.
+    pub subsystem: String,
+    /// When it started.
+    pub started: DateTime<FixedOffset>,
+    /// How long it ran.
+    pub duration: Duration,
+    /// The exit code it finished with.
+    pub exit_code: i32,
+    /// The error message it failed with, if it did.
+    pub error: Option<String>,
+    /// How many tweets it queued.
+    pub added: usize,
+    /// How many queued tweets it changed.
+    pub edited: usize,
+    /// How many tweets it removed from the queue without posting them.
+    pub removed: usize,
+    /// How many tweets it posted.
+    pub posted: usize,
+    /// How many posted tweets it moved to the archive.
+    pub archived: usize,
+    /// The warnings it printed.
+    pub warnings: Vec<String>,
+    /// How many API requests it made were answered from the HTTP cache, and how many weren't.
+    pub http_cache: Stats,
+}
+
+thread_local! {
+    static WARNINGS: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+
+impl Report {
+    /// Summarise the run of the specified subsystem, started at the specified time and finished after the specified time
+    /// with the specified outcome, which changed the queue from `before` to `after`, archiving the specified tweets, and
+    /// printed the specified warnings, with the HTTP cache statistics so far, see `http_cache::stats()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::report::Report;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use tweetr::Outcome;
+    /// # use std::time::Duration;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let started = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: started,
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let other = QueuedTweet { content: "Workers of the world, unite!".to_string(), ..tweet.clone() };
+    ///
+    /// let report = Report::new("queue-tweet",
+    ///                          started,
+    ///                          Duration::from_millis(1500),
+    ///                          &Outcome::NoError,
+    ///                          &[tweet.clone()],
+    ///                          &[tweet.clone(), other.clone()],
+    ///                          &[],
+    ///                          vec!["Possibly misspelt words: Wrokers".to_string()]);
+    /// assert_eq!(report.exit_code, 0);
+    /// assert_eq!(report.error, None);
+    /// assert_eq!((report.added, report.edited, report.removed, report.posted, report.archived), (1, 0, 0, 0, 0));
+    /// assert_eq!(report.warnings, vec!["Possibly misspelt words: Wrokers".to_string()]);
+    /// # }
+    /// ```
+    pub fn new(subsystem: &str, started: DateTime<FixedOffset>, duration: Duration, outcome: &Outcome, before: &[QueuedTweet],
+               after: &[QueuedTweet], archived: &[QueuedTweet], warnings: Vec<String>)
+               -> Report {
+        let mut error = vec![];
+        outcome.print_error(&mut error);
+        let error = String::from_utf8_lossy(&error).trim_right().to_string();
+
+        let changes = journal::diff(before, after, archived);
+        let count = |f: &Fn(&Action) -> bool| changes.iter().filter(|c| f(&c.action)).count();
+
+        Report {
+            subsystem: subsystem.to_string(),
+            started: started,
+            duration: duration,
+            exit_code: outcome.exit_value(),
+            error: if error.is_empty() { None } else { Some(error) },
+            added: count(&|a| *a == Action::Queued),
+            edited: count(&|a| if let Action::Edited(_) = *a { true } else { false }),
+            removed: count(&|a| *a == Action::Deleted),
+            posted: count(&|a| *a == Action::Posted),
+            archived: count(&|a| *a == Action::Archived),
+            warnings: warnings,
+            http_cache: http_cache::stats(),
+        }
+    }
+
+    /// Get the report as written to the report file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rustc_serialize;
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use rustc_serialize::json::Json;
+    /// # use tweetr::ops::http_cache::Stats;
+    /// # use tweetr::ops::report::Report;
+    /// # use std::time::Duration;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let report = Report {
+    ///     subsystem: "post-all".to_string(),
+    ///     started: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     duration: Duration::from_millis(1500),
+    ///     exit_code: 0,
+    ///     error: None,
+    ///     added: 0,
+    ///     edited: 0,
+    ///     removed: 0,
+    ///     posted: 2,
+    ///     archived: 2,
+    ///     warnings: vec![],
+    ///     http_cache: Stats { hits: 1, misses: 2 },
+    /// };
+    ///
+    /// let json = report.to_json();
+    /// assert_eq!(json.find("subsystem"), Some(&Json::String("post-all".to_string())));
+    /// assert_eq!(json.find("started"), Some(&Json::String("2016-09-10T12:00:00+02:00".to_string())));
+    /// assert_eq!(json.find("duration"), Some(&Json::F64(1.5)));
+    /// assert_eq!(json.find("error"), Some(&Json::Null));
+    /// assert_eq!(json.find("posted"), Some(&Json::U64(2)));
+    /// assert_eq!(json.find("warnings"), Some(&Json::Array(vec![])));
+    /// assert_eq!(json.find_path(&["http_cache", "misses"]), Some(&Json::U64(2)));
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("subsystem".to_string(), Json::String(self.subsystem.clone()));
+        obj.insert("started".to_string(), Json::String(self.started.to_rfc3339()));
+        obj.insert("duration".to_string(),
+                   Json::F64(self.duration.as_secs() as f64 + self.duration.subsec_nanos() as f64 / 1_000_000_000f64));
+        obj.insert("exit_code".to_string(), Json::I64(self.exit_code as i64));
+        obj.insert("error".to_string(), self.error.clone().map(Json::String).unwrap_or(Json::Null));
+        obj.insert("added".to_string(), Json::U64(self.added as u64));
+        obj.insert("edited".to_string(), Json::U64(self.edited as u64));
+        obj.insert("removed".to_string(), Json::U64(self.removed as u64));
+        obj.insert("posted".to_string(), Json::U64(self.posted as u64));
+        obj.insert("archived".to_string(), Json::U64(self.archived as u64));
+        obj.insert("warnings".to_string(), Json::Array(self.warnings.iter().cloned().map(Json::String).collect()));
+        obj.insert("http_cache".to_string(), self.http_cache.to_json());
+        Json::Object(obj)
+    }
+
+    /// Write the report to the specified file, replacing it.
+    pub fn write(&self, p: &Path) -> Result<(), Outcome> {
+        write_file(p, &format!("{}\n", json::as_pretty_json(&self.to_json())))
+    }
+}
+
+
+/// Start collecting the warnings subsequently passed to `warn()` on the current thread.
+pub fn collect_warnings() {
+    WARNINGS.with(|w| *w.borrow_mut() = Some(vec![]));
+}
+
+/// Note the specified warning, if collecting them.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::report;
+/// report::warn("Not collected");
+/// assert_eq!(report::take_warnings(), vec![] as Vec<String>);
+///
+/// report::collect_warnings();
+/// report::warn("Couldn't read hooks/pre-post");
+/// assert_eq!(report::take_warnings(), vec!["Couldn't read hooks/pre-post".to_string()]);
+/// ```
+pub fn warn(warning: &str) {
+    WARNINGS.with(|w| if let Some(ref mut warnings) = *w.borrow_mut() {
+        warnings.push(warning.to_string());
+    });
+}
+
+/// Stop collecting warnings on the current thread, returning the ones collected.
+pub fn take_warnings() -> Vec<String> {
+    WARNINGS.with(|w| w.borrow_mut().take().unwrap_or_default())
+}
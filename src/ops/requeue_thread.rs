@@ -0,0 +1,90 @@
+//! This module contains the functions used only by the `requeue-thread` subsystem.
+//!
+//! The flow of the `requeue-thread` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::requeue_thread::verify()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::requeue_thread::requeue()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+//!
+//! When a tweet in a thread fails permanently, the daemon holds it and the rest of its thread back, see
+//! `ops::start_daemon::block_thread()`, so no orphaned replies are posted; once the failed tweet is fixed, this lets the
+//! thread be posted again.
+
+
+use self::super::{QueuedTweet, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `requeue-thread`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the queued tweets or why getting it failed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::{self, File};
+/// # use tweetr::ops::requeue_thread;
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-requeue-thread-verify-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(requeue_thread::verify(&("$TEMP/ops-requeue-thread-verify-0".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("tweets.toml")).unwrap();
+/// assert_eq!(requeue_thread::verify(&("$TEMP/ops-requeue-thread-verify-0".to_string(), tf.clone())),
+///            Ok(tf.join("tweets.toml")));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("tweets.toml", true, config_dir, false, "queue-tweet")
+}
+
+/// Let the held back tweets in the specified thread be posted again, resetting how many times they were retried.
+///
+/// Returns how many tweets were requeued.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, requeue_thread};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = |content: &str, blocked: Option<&str>| QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: content.to_string(),
+///     thread: Some("manifesto".to_string()),
+///     blocked: blocked.map(str::to_string),
+///     retries: if blocked.is_some() { 1 } else { 0 },
+///     ..Default::default()
+/// };
+/// let reason = "Twitter API error: Status is a duplicate. (code 187)";
+/// let mut tweets = vec![tweet("1/2 Abolish", Some(reason)), tweet("2/2 the bourgeoisie", Some(reason))];
+///
+/// assert_eq!(requeue_thread::requeue(&mut tweets, "other"), 0);
+/// assert_eq!(requeue_thread::requeue(&mut tweets, "manifesto"), 2);
+/// assert_eq!(tweets[0].blocked, None);
+/// assert_eq!(tweets[0].retries, 0);
+/// assert_eq!(tweets[1].blocked, None);
+/// # }
+/// ```
+pub fn requeue(tweets: &mut [QueuedTweet], thread: &str) -> usize {
+    let mut requeued = 0;
+    for tweet in tweets.iter_mut().filter(|t| t.thread.as_ref().map(|t| &t[..]) == Some(thread) && t.blocked.is_some()) {
+        tweet.blocked = None;
+        tweet.retries = 0;
+        requeued += 1;
+    }
+    requeued
+}
@@ -0,0 +1,361 @@
+//! Results, machine-readable descriptions of what a subsystem did, printed with `--json` for wrappers orchestrating tweetr.
+//!
+//! With `--json`, the files written, the apps and users created and the tokens stored are recorded as the subsystem runs,
+//! the queue is compared before and after it, the same way as for the journal (see `journal::diff()`), and a single JSON
+//! object is printed to the standard output when it finishes, successfully or not:
+//!
+//! ```json
+//! {
+//!   "changes": [],
+//!   "created": [
+//!     {
+//!       "id": 2355389910,
+//!       "kind": "user",
+//!       "name": "nabijaczleweli"
+//!     }
+//!   ],
+//!   "error": null,
+//!   "exit_code": 0,
+//!   "http_cache": {
+//!     "hits": 0,
+//!     "misses": 0
+//!   },
+//!   "subsystem": "add-user",
+//!   "tokens": [
+//!     {
+//!       "access_token_key": "[redacted]",
+//!       "access_token_secret": "[redacted]",
+//!       "kind": "user",
+//!       "owner": "nabijaczleweli"
+//!     }
+//!   ],
+//!   "written": [
+//!     "/home/nabijaczleweli/.tweetr/users.toml"
+//!   ]
+//! }
+//! ```
+//!
+//! `changes` are the journal's changes, like `{"action": "queued", "tweet": {...}}`, with the IDs of the tweets posted.
+//! Tokens are only ever named, never included. `http_cache` is how many API requests were answered from the HTTP cache, and
+//! how many weren't, see `ops::http_cache`.
+//!
+//! Everything the subsystem would otherwise write to the standard output, like prompts and lists, is written to the
+//! standard error instead, see `output()`, so the object can be parsed as-is.
+//!
+//! The flow of printing results is as follows:
+//!
+//! ```plaintext
+//! ops::results::collect()
+//! |> the subsystem, calling ops::results::{wrote,created,stored_tokens}()
+//! |> ops::results::Results::new(), with the queue before and after, and the tweets archived meanwhile
+//! |> ops::results::Results::print()
+//! ```
+
+
+use self::super::journal::{self, Change};
+use self::super::http_cache::{self, Stats};
+use self::super::super::Outcome;
+use self::super::QueuedTweet;
+use rustc_serialize::json::{self, Json};
+use std::io::{self, Stderr, Stdout, Write};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::cell::RefCell;
+
+
+/// What tokens are replaced with in the results.
+pub static REDACTED: &'static str = "[redacted]";
+
+thread_local! {
+    static RECORDED: RefCell<Option<Recorded>> = RefCell::new(None);
+}
+
+
+/// What a subsystem did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Results {
+    /// The subsystem, like `"add-user"`.
+    pub subsystem: String,
+    /// The exit code it finished with.
+    pub exit_code: i32,
+    /// The error message it failed with, if it did.
+    pub error: Option<String>,
+    /// The changes it made to the queue.
+    pub changes: Vec<Change>,
+    /// What it recorded doing.
+    pub recorded: Recorded,
+    /// How many API requests it had answered from the HTTP cache, and how many not.
+    pub http_cache: Stats,
+}
+
+/// What a subsystem recorded doing, as it ran.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Recorded {
+    /// The files it wrote, in the order first written.
+    pub written: Vec<PathBuf>,
+    /// The apps and users it created.
+    pub created: Vec<Created>,
+    /// The tokens it stored.
+    pub tokens: Vec<Tokens>,
+}
+
+/// An app or user created by a subsystem.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Created {
+    /// What was created, like `"app"` or `"user"`.
+    pub kind: &'static str,
+    /// Its name.
+    pub name: String,
+    /// Its ID on Twitter, if it has one.
+    pub id: Option<i64>,
+}
+
+/// Tokens stored by a subsystem, without their values.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Tokens {
+    /// What they belong to, like `"app"` or `"user"`.
+    pub kind: &'static str,
+    /// The name of what they belong to.
+    pub owner: String,
+    /// The tokens' names, like `"access_token_key"`.
+    pub names: Vec<&'static str>,
+}
+
+/// The standard output, or, if collecting results, the standard error.
+#[derive(Debug)]
+pub enum Output {
+    /// Not collecting results.
+    Stdout(Stdout),
+    /// Collecting results.
+    Stderr(Stderr),
+}
+
+
+impl Results {
+    /// Describe the run of the specified subsystem, finished with the specified outcome, which changed the queue from
+    /// `before` to `after`, archiving the specified tweets, and recorded doing the specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::results::{Recorded, Results};
+    /// # use tweetr::Outcome;
+    /// let results = Results::new("init", &Outcome::OverrideNoForce("app.toml".to_string()), &[], &[], &[], Recorded::default());
+    /// assert_eq!(results.exit_code, 1);
+    /// assert!(results.error.unwrap().starts_with("File \"app.toml\" was not overriden"));
+    /// assert!(results.changes.is_empty());
+    /// ```
+    pub fn new(subsystem: &str, outcome: &Outcome, before: &[QueuedTweet], after: &[QueuedTweet], archived: &[QueuedTweet], recorded: Recorded)
+               -> Results {
+        let mut error = vec![];
+        outcome.print_error(&mut error);
+        let error = String::from_utf8_lossy(&error).trim_right().to_string();
+
+        Results {
+            subsystem: subsystem.to_string(),
+            exit_code: outcome.exit_value(),
+            error: if error.is_empty() { None } else { Some(error) },
+            changes: journal::diff(before, after, archived),
+            recorded: recorded,
+            http_cache: http_cache::stats(),
+        }
+    }
+
+    /// Get the results as printed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rustc_serialize;
+    /// # extern crate tweetr;
+    /// # use rustc_serialize::json::Json;
+    /// # use tweetr::ops::results::{Created, Recorded, Results, Tokens};
+    /// # use tweetr::ops::http_cache::Stats;
+    /// # use std::path::PathBuf;
+    /// # fn main() {
+    /// let results = Results {
+    ///     subsystem: "add-user".to_string(),
+    ///     exit_code: 0,
+    ///     error: None,
+    ///     changes: vec![],
+    ///     recorded: Recorded {
+    ///         written: vec![PathBuf::from("users.toml")],
+    ///         created: vec![Created {
+    ///                           kind: "user",
+    ///                           name: "nabijaczleweli".to_string(),
+    ///                           id: Some(2355389910),
+    ///                       }],
+    ///         tokens: vec![Tokens {
+    ///                          kind: "user",
+    ///                          owner: "nabijaczleweli".to_string(),
+    ///                          names: vec!["access_token_key", "access_token_secret"],
+    ///                      }],
+    ///     },
+    ///     http_cache: Stats { hits: 1, misses: 0 },
+    /// };
+    ///
+    /// let json = results.to_json();
+    /// assert_eq!(json.find("subsystem"), Some(&Json::String("add-user".to_string())));
+    /// assert_eq!(json.find("error"), Some(&Json::Null));
+    /// assert_eq!(json.find("written"), Some(&Json::Array(vec![Json::String("users.toml".to_string())])));
+    /// assert_eq!(json["created"][0].find("id"), Some(&Json::I64(2355389910)));
+    /// assert_eq!(json["tokens"][0].find("access_token_secret"), Some(&Json::String("[redacted]".to_string())));
+    /// assert_eq!(json.find("changes"), Some(&Json::Array(vec![])));
+    /// assert_eq!(json.find_path(&["http_cache", "hits"]), Some(&Json::U64(1)));
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("subsystem".to_string(), Json::String(self.subsystem.clone()));
+        obj.insert("exit_code".to_string(), Json::I64(self.exit_code as i64));
+        obj.insert("error".to_string(), self.error.clone().map(Json::String).unwrap_or(Json::Null));
+        obj.insert("written".to_string(),
+                   Json::Array(self.recorded.written.iter().map(|p| Json::String(p.display().to_string())).collect()));
+        obj.insert("created".to_string(), Json::Array(self.recorded.created.iter().map(Created::to_json).collect()));
+        obj.insert("tokens".to_string(), Json::Array(self.recorded.tokens.iter().map(Tokens::to_json).collect()));
+        obj.insert("changes".to_string(),
+                   Json::Array(self.changes
+                       .iter()
+                       .map(|c| {
+                           let mut change = BTreeMap::new();
+                           change.insert("action".to_string(), Json::String(c.action.name().to_string()));
+                           change.insert("tweet".to_string(), c.tweet.to_json());
+                           Json::Object(change)
+                       })
+                       .collect()));
+        obj.insert("http_cache".to_string(), self.http_cache.to_json());
+        Json::Object(obj)
+    }
+
+    /// Print the results to the specified output.
+    pub fn print<W: Write>(&self, output: &mut W) {
+        writeln!(output, "{}", json::as_pretty_json(&self.to_json())).unwrap();
+    }
+}
+
+impl Created {
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("kind".to_string(), Json::String(self.kind.to_string()));
+        obj.insert("name".to_string(), Json::String(self.name.clone()));
+        if let Some(id) = self.id {
+            obj.insert("id".to_string(), Json::I64(id));
+        }
+        Json::Object(obj)
+    }
+}
+
+impl Tokens {
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("kind".to_string(), Json::String(self.kind.to_string()));
+        obj.insert("owner".to_string(), Json::String(self.owner.clone()));
+        for name in &self.names {
+            obj.insert(name.to_string(), Json::String(REDACTED.to_string()));
+        }
+        Json::Object(obj)
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Output::Stdout(ref mut out) => out.write(buf),
+            Output::Stderr(ref mut out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Output::Stdout(ref mut out) => out.flush(),
+            Output::Stderr(ref mut out) => out.flush(),
+        }
+    }
+}
+
+
+/// Start recording what's subsequently passed to `wrote()`, `created()` and `stored_tokens()` on the current thread.
+pub fn collect() {
+    RECORDED.with(|r| *r.borrow_mut() = Some(Recorded::default()));
+}
+
+/// Check whether results are being collected on the current thread.
+pub fn collecting() -> bool {
+    RECORDED.with(|r| r.borrow().is_some())
+}
+
+/// Get where to write what'd go to the standard output: there, or, if collecting results, to the standard error,
+/// so only they are printed there.
+pub fn output() -> Output {
+    if collecting() {
+        Output::Stderr(io::stderr())
+    } else {
+        Output::Stdout(io::stdout())
+    }
+}
+
+/// Note that the specified file was written, if collecting results.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::results;
+/// # use std::path::{Path, PathBuf};
+/// results::wrote(Path::new("not-collected.toml"));
+/// assert_eq!(results::take().written, vec![] as Vec<PathBuf>);
+///
+/// results::collect();
+/// results::wrote(Path::new("tweets.toml"));
+/// results::wrote(Path::new("journal.jsonl"));
+/// results::wrote(Path::new("tweets.toml"));
+/// assert_eq!(results::take().written, vec![PathBuf::from("tweets.toml"), PathBuf::from("journal.jsonl")]);
+/// ```
+pub fn wrote(p: &Path) {
+    RECORDED.with(|r| if let Some(ref mut recorded) = *r.borrow_mut() {
+        if !recorded.written.iter().any(|w| w == p) {
+            recorded.written.push(p.to_path_buf());
+        }
+    });
+}
+
+/// Note that the specified kind of thing was created with the specified name and Twitter ID, if collecting results.
+pub fn created(kind: &'static str, name: &str, id: Option<i64>) {
+    RECORDED.with(|r| if let Some(ref mut recorded) = *r.borrow_mut() {
+        recorded.created.push(Created {
+            kind: kind,
+            name: name.to_string(),
+            id: id,
+        });
+    });
+}
+
+/// Note that the tokens with the specified names were stored for the specified kind of thing with the specified name,
+/// if collecting results.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::results::{self, Tokens};
+/// results::collect();
+/// results::stored_tokens("app", "default", &["consumer_key", "consumer_secret"]);
+/// assert_eq!(results::take().tokens,
+///            vec![Tokens {
+///                     kind: "app",
+///                     owner: "default".to_string(),
+///                     names: vec!["consumer_key", "consumer_secret"],
+///                 }]);
+/// ```
+pub fn stored_tokens(kind: &'static str, owner: &str, names: &[&'static str]) {
+    RECORDED.with(|r| if let Some(ref mut recorded) = *r.borrow_mut() {
+        recorded.tokens.push(Tokens {
+            kind: kind,
+            owner: owner.to_string(),
+            names: names.to_vec(),
+        });
+    });
+}
+
+/// Stop collecting results on the current thread, returning what was recorded.
+pub fn take() -> Recorded {
+    RECORDED.with(|r| r.borrow_mut().take().unwrap_or_default())
+}
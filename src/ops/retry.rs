@@ -0,0 +1,163 @@
+//! Retrying accesses to the files in the configuration directory that fail transiently.
+//!
+//! On networked filesystems, like NFS, reading or writing a file occasionally fails with an error that goes away by itself
+//! a moment later: a stale file handle, a lock that's briefly unavailable, or an interrupted or timed out operation. Such
+//! accesses are retried after a delay, up to the configured amount of times, see `Config::file_retries`; other errors,
+//! and transient ones that persist past the retries, fail right away, the latter noting how many attempts were made.
+
+
+use std::io::{Error, Result as IoResult};
+use std::time::Duration;
+use std::cell::Cell;
+use std::io::ErrorKind;
+use std::thread;
+#[cfg(unix)]
+use libc;
+
+
+/// How to retry file accesses failing transiently.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times to retry a failed access, at most. Default: 3
+    pub retries: u32,
+    /// How long to wait before each retry. Default: 1s
+    pub delay: Duration,
+}
+
+thread_local! {
+    static POLICY: Cell<RetryPolicy> = Cell::new(RetryPolicy::default());
+}
+
+
+impl RetryPolicy {
+    /// Override the policy with the specified amount of retries and delay, in milliseconds, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::retry::RetryPolicy;
+    /// # use std::time::Duration;
+    /// assert_eq!(RetryPolicy::default().overridden(Some(0), None),
+    ///            RetryPolicy {
+    ///                retries: 0,
+    ///                delay: Duration::from_secs(1),
+    ///            });
+    /// assert_eq!(RetryPolicy::default().overridden(None, Some(250)).delay, Duration::from_millis(250));
+    /// ```
+    pub fn overridden(&self, retries: Option<u32>, delay: Option<u64>) -> RetryPolicy {
+        RetryPolicy {
+            retries: retries.unwrap_or(self.retries),
+            delay: delay.map(Duration::from_millis).unwrap_or(self.delay),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            retries: 3,
+            delay: Duration::from_secs(1),
+        }
+    }
+}
+
+
+/// Use the specified policy for all file accesses subsequently made on the current thread.
+pub fn use_policy(policy: RetryPolicy) {
+    POLICY.with(|p| p.set(policy));
+}
+
+/// Get the policy in use on the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::retry::{self, RetryPolicy};
+/// assert_eq!(retry::policy(), RetryPolicy::default());
+/// ```
+pub fn policy() -> RetryPolicy {
+    POLICY.with(|p| p.get())
+}
+
+/// Check whether the specified I/O error is expected to go away by itself.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::retry;
+/// # use std::io::{Error, ErrorKind};
+/// assert!(retry::transient(&Error::new(ErrorKind::Interrupted, "")));
+/// assert!(retry::transient(&Error::new(ErrorKind::WouldBlock, "")));
+/// assert!(!retry::transient(&Error::new(ErrorKind::NotFound, "")));
+/// assert!(!retry::transient(&Error::new(ErrorKind::PermissionDenied, "")));
+/// # #[cfg(target_os = "linux")]
+/// assert!(retry::transient(&Error::from_raw_os_error(116 /* ESTALE */)));
+/// ```
+pub fn transient(e: &Error) -> bool {
+    match e.kind() {
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut => true,
+        _ => e.raw_os_error().map(|code| TRANSIENT_OS_ERRORS.contains(&code)).unwrap_or(false),
+    }
+}
+
+/// Run the specified file access, retrying it according to the current thread's policy if it fails transiently.
+///
+/// If it still fails after all the retries, the error says how many attempts were made.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::retry::{self, RetryPolicy};
+/// # use std::io::{Error, ErrorKind};
+/// # use std::time::Duration;
+/// retry::use_policy(RetryPolicy {
+///     retries: 2,
+///     delay: Duration::from_millis(1),
+/// });
+///
+/// let mut attempts = 0;
+/// assert_eq!(retry::run(|| {
+///                attempts += 1;
+///                if attempts < 3 {
+///                    Err(Error::new(ErrorKind::Interrupted, "interrupted"))
+///                } else {
+///                    Ok(attempts)
+///                }
+///            }).unwrap(),
+///            3);
+///
+/// let error = retry::run(|| Err::<(), _>(Error::new(ErrorKind::Interrupted, "interrupted"))).unwrap_err();
+/// assert_eq!(error.to_string(), "interrupted, still after 3 attempts");
+///
+/// attempts = 0;
+/// retry::run(|| {
+///         attempts += 1;
+///         Err::<(), _>(Error::new(ErrorKind::NotFound, "not found"))
+///     })
+///     .unwrap_err();
+/// assert_eq!(attempts, 1);
+/// ```
+pub fn run<T, F: FnMut() -> IoResult<T>>(mut access: F) -> IoResult<T> {
+    let policy = policy();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match access() {
+            Err(ref e) if transient(e) && attempts <= policy.retries => thread::sleep(policy.delay),
+            Err(ref e) if transient(e) && attempts > 1 => return Err(Error::new(e.kind(), format!("{}, still after {} attempts", e, attempts))),
+            result => return result,
+        }
+    }
+}
+
+
+/// Stale NFS file handle, locks unavailable, and resource busy.
+#[cfg(unix)]
+static TRANSIENT_OS_ERRORS: &'static [i32] = &[libc::ESTALE, libc::ENOLCK, libc::EBUSY];
+
+/// Sharing and lock violations, `ERROR_SHARING_VIOLATION` and `ERROR_LOCK_VIOLATION`.
+#[cfg(windows)]
+static TRANSIENT_OS_ERRORS: &'static [i32] = &[32, 33];
+
+#[cfg(not(any(unix, windows)))]
+static TRANSIENT_OS_ERRORS: &'static [i32] = &[];
@@ -0,0 +1,225 @@
+//! This module contains the functions used only by the `rotate-token` subsystem.
+//!
+//! The flow of the `rotate-token` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::rotate_token::verify()
+//! |> ops::User::read()
+//! |> ops::rotate_token::find_user()
+//! |> ops::Apps::read()
+//! |> ops::add_user::authorise()
+//! |> ops::rotate_token::replace_tokens()
+//! |> ops::rotate_token::print_success_message()
+//! ```
+//!
+//! Everything but the account's access tokens, like the app it's bound to, its backends and its settings, is kept.
+
+
+use self::super::super::Outcome;
+use self::super::{User, results, verify_file};
+use self::super::l10n::tr;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `rotate-token`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global app configuration and the path to the file
+/// containing the global users data or why getting them failed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::rotate_token;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-rotate-token-verify");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap();
+///
+/// assert_eq!(rotate_token::verify(&("$TEMP/ops-rotate-token-verify".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::NoUsers)));
+///
+/// File::create(tf.join("users.toml")).unwrap();
+/// assert_eq!(rotate_token::verify(&("$TEMP/ops-rotate-token-verify".to_string(), tf.clone())),
+///            Ok((tf.join("app.toml"), tf.join("users.toml"))));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
+    let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
+    let users = try!(verify_file("users.toml", true, config_dir, false, "add-user"));
+    Ok((app, users))
+}
+
+/// Find the index of the user with the specified name.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{rotate_token, User};
+/// # use tweetr::Outcome;
+/// let users = vec![User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// }];
+///
+/// assert_eq!(rotate_token::find_user(&users, "nabijaczleweli"), Ok(0));
+/// assert_eq!(rotate_token::find_user(&users, "not-nabijaczleweli"),
+///            Err(Outcome::RequiredDataFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                desc: "add and authorise user with name \"not-nabijaczleweli\"".to_string(),
+///            }));
+/// ```
+pub fn find_user(users: &[User], name: &str) -> Result<usize, Outcome> {
+    users.iter().position(|u| u.name == name).ok_or_else(|| {
+        Outcome::RequiredDataFromSubsystemNonexistant {
+            subsys: "add-user",
+            desc: format!("add and authorise user with name \"{}\"", name),
+        }
+    })
+}
+
+/// Replace the access tokens of the user with the specified name in the authenticated users list at the specified path with
+/// the ones of the specified newly authorised user, returning the updated user.
+///
+/// The list is reread, so changes made to it while authorising aren't lost, and replaced at once, so it's never seen
+/// partially written. The newly authorised user must be the same account, so tweets aren't posted by the wrong one.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{rotate_token, User};
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-rotate-token-replace_tokens");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("users.toml");
+///
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: Some("agency".to_string()),
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: Some("23:00-07:00".to_string()),
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// User::write(vec![user.clone()], &tf).unwrap();
+///
+/// let authorised = User {
+///     access_token_key: "481-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(),
+///     access_token_secret: "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx".to_string(),
+///     app: None,
+///     quiet_hours: None,
+///     ..user.clone()
+/// };
+/// let rotated = User {
+///     access_token_key: authorised.access_token_key.clone(),
+///     access_token_secret: authorised.access_token_secret.clone(),
+///     ..user.clone()
+/// };
+/// assert_eq!(rotate_token::replace_tokens(&tf, "nabijaczleweli", &authorised), Ok(rotated.clone()));
+/// assert_eq!(User::read(&tf), Ok(vec![rotated]));
+///
+/// assert_eq!(rotate_token::replace_tokens(&tf, "nabijaczleweli", &User { id: 482, ..authorised }),
+///            Err(Outcome::PreflightFailed(vec!["the PIN authorises user nabijaczleweli#482 instead of nabijaczleweli#481, \
+///                                               sign into nabijaczleweli on Twitter before authorising".to_string()])));
+/// ```
+pub fn replace_tokens(users_path: &Path, name: &str, authorised: &User) -> Result<User, Outcome> {
+    let mut users = try!(User::read_verbatim(users_path).map_err(Option::unwrap));
+    let idx = try!(find_user(&users, name));
+
+    if users[idx].id != authorised.id {
+        return Err(Outcome::PreflightFailed(vec![format!("the PIN authorises user {}#{} instead of {}#{}, sign into {} on Twitter before \
+                                                          authorising",
+                                                         authorised.name,
+                                                         authorised.id,
+                                                         users[idx].name,
+                                                         users[idx].id,
+                                                         users[idx].name)]));
+    }
+
+    users[idx].access_token_key = authorised.access_token_key.clone();
+    users[idx].access_token_secret = authorised.access_token_secret.clone();
+
+    let user = users[idx].clone();
+    try!(User::write(users, users_path));
+    results::stored_tokens("user", &user.name, &["access_token_key", "access_token_secret"]);
+    Ok(user)
+}
+
+/// Print the success message mentioning the specified user's name and ID as a diagnostic, optionally also printing the new tokens
+/// as data.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{rotate_token, User};
+/// # use std::iter::FromIterator;
+/// # use std::io::sink;
+/// let mut out = Vec::new();
+/// rotate_token::print_success_message(&mut sink(), &mut out, &User {
+///     name: "random-test-name".to_string(),
+///     id: 0x42069,
+///     access_token_key: "270441-N48kdEQFWtj7cUyWomNeE2AsNQw8pnmOaQbcwnV".to_string(),
+///     access_token_secret: "jCcBthGzve36QMt3RAV6jOEg4qtHt7laMV2YFA3qKCRzw".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// }, false);
+/// assert_eq!(out, Vec::from_iter(b"Rotated the access tokens of user random-test-name#270441\n".iter().cloned()));
+/// ```
+pub fn print_success_message<W: Write, D: Write>(output: &mut W, diagnostics: &mut D, user: &User, verbose: bool) {
+    writeln!(diagnostics, "{}", tr("tokens_rotated", &[("name", &user.name), ("id", &user.id)])).unwrap();
+    if verbose {
+        writeln!(output, "{}", tr("access_tokens", &[])).unwrap();
+        writeln!(output, "  Key   : {}", user.access_token_key).unwrap();
+        writeln!(output, "  Secret: {}", user.access_token_secret).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! Arithmetic on the times tweets are scheduled at.
+//!
+//! All of it is checked: times too far away to represent are reported as `ScheduleError::Overflow`, instead of panicking or
+//! wrapping around.
+//!
+//! Tweets can't be queued or moved further in the future than the horizon, see `Config::horizon()`, since that's more
+//! likely a typo, like `in 2000 days` for `in 200 days`, than intended. The horizon in use is set per thread, with the
+//! default being 10 years.
+
+
+use self::super::super::util::format_amount_of_time;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::time::Duration as StdDuration;
+use std::cell::Cell;
+use std::fmt;
+
+
+/// The default horizon, in days.
+pub static DEFAULT_HORIZON_DAYS: i64 = 3650;
+
+thread_local! {
+    static HORIZON: Cell<Option<Duration>> = Cell::new(Some(Duration::days(DEFAULT_HORIZON_DAYS)));
+}
+
+
+/// Why a time couldn't be scheduled.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The time is too far away to represent.
+    Overflow,
+    /// The time is further in the future than the horizon.
+    PastHorizon {
+        /// The horizon in use.
+        horizon: Duration,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScheduleError::Overflow => write!(f, "too far away"),
+            ScheduleError::PastHorizon { horizon } => {
+                write!(f, "more than {} in the future, past the horizon", format_amount_of_time(horizon.num_seconds() as u64))
+            }
+        }
+    }
+}
+
+
+/// Get the specified amount of seconds as a `Duration`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::schedule::{self, ScheduleError};
+/// # use chrono::Duration;
+/// # fn main() {
+/// assert_eq!(schedule::seconds(15 * 60), Ok(Duration::minutes(15)));
+/// assert_eq!(schedule::seconds(u64::max_value()), Err(ScheduleError::Overflow));
+/// # }
+/// ```
+pub fn seconds(secs: u64) -> Result<Duration, ScheduleError> {
+    Duration::from_std(StdDuration::from_secs(secs)).map_err(|_| ScheduleError::Overflow)
+}
+
+/// Get the time the specified amount of seconds after the specified one.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::schedule::{self, ScheduleError};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(schedule::later(time, 2 * 60 * 60), Ok(time + Duration::hours(2)));
+/// assert_eq!(schedule::later(time, 9999999999999 * 7 * 24 * 60 * 60), Err(ScheduleError::Overflow));
+/// # }
+/// ```
+pub fn later(time: DateTime<FixedOffset>, secs: u64) -> Result<DateTime<FixedOffset>, ScheduleError> {
+    seconds(secs).and_then(|delta| time.checked_add(delta).ok_or(ScheduleError::Overflow))
+}
+
+/// Get the time the specified amount of seconds after the specified one, or before it if negative.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::schedule::{self, ScheduleError};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(schedule::shifted(time, 24 * 60 * 60), Ok(time + Duration::days(1)));
+/// assert_eq!(schedule::shifted(time, -24 * 60 * 60), Ok(time - Duration::days(1)));
+/// assert_eq!(schedule::shifted(time, i64::min_value()), Err(ScheduleError::Overflow));
+/// # }
+/// ```
+pub fn shifted(time: DateTime<FixedOffset>, secs: i64) -> Result<DateTime<FixedOffset>, ScheduleError> {
+    if secs >= 0 {
+        later(time, secs as u64)
+    } else {
+        let delta = try!(secs.checked_neg().ok_or(ScheduleError::Overflow).and_then(|secs| seconds(secs as u64)));
+        time.checked_sub(delta).ok_or(ScheduleError::Overflow)
+    }
+}
+
+/// Check whether the specified time is within the horizon in use from the specified current time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::schedule::{self, ScheduleError};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(schedule::within_horizon(now + Duration::weeks(100), now), Ok(now + Duration::weeks(100)));
+/// assert_eq!(schedule::within_horizon(now - Duration::weeks(1000), now), Ok(now - Duration::weeks(1000)));
+/// assert_eq!(schedule::within_horizon(now + Duration::weeks(1000), now),
+///            Err(ScheduleError::PastHorizon { horizon: Duration::days(3650) }));
+///
+/// schedule::use_horizon(None);
+/// assert_eq!(schedule::within_horizon(now + Duration::weeks(1000), now), Ok(now + Duration::weeks(1000)));
+/// # }
+/// ```
+pub fn within_horizon(time: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> Result<DateTime<FixedOffset>, ScheduleError> {
+    match horizon() {
+        Some(horizon) if now.checked_add(horizon).map(|limit| time > limit).unwrap_or(false) => {
+            Err(ScheduleError::PastHorizon { horizon: horizon })
+        }
+        _ => Ok(time),
+    }
+}
+
+/// Use the specified horizon for all times subsequently scheduled on the current thread, or none to allow any time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::schedule;
+/// # use chrono::Duration;
+/// # fn main() {
+/// assert_eq!(schedule::horizon(), Some(Duration::days(3650)));
+///
+/// schedule::use_horizon(Some(Duration::days(365)));
+/// assert_eq!(schedule::horizon(), Some(Duration::days(365)));
+/// # }
+/// ```
+pub fn use_horizon(horizon: Option<Duration>) {
+    HORIZON.with(|h| h.set(horizon));
+}
+
+/// Get the horizon in use on the current thread, if any.
+pub fn horizon() -> Option<Duration> {
+    HORIZON.with(|h| h.get())
+}
@@ -0,0 +1,1038 @@
+//! This module contains the functions used only by the `schema` subsystem.
+//!
+//! The flow of the `schema` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::schema::json_schema()
+//! ```
+//!
+//! Or, for a commented example file:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::schema::example()
+//! ```
+//!
+//! The formats are described by the keys of the files as written, like `QueuedTweet`'s, whose time is either RFC 3339 or
+//! anchored to another tweet, and whose coordinates are split into `lat` and `long`, rather than by the structs they're read
+//! into, so the descriptions are kept here, next to each other, and need updating with the structs. `ops::doctor` checks the
+//! files' keys against them, too.
+
+
+use rustc_serialize::json::Json;
+use std::collections::BTreeMap;
+
+
+/// An on-disk format.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Format {
+    /// The global configuration, see `Config`.
+    Config,
+    /// The authenticated users, see `User`.
+    Users,
+    /// The queued tweets, see `QueuedTweet`.
+    Queue,
+    /// The posted tweets, archived by month, see `ops::archive`.
+    Archive,
+}
+
+/// Whether a key needs to be specified.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Presence {
+    /// Always.
+    Required,
+    /// Never.
+    Optional,
+    /// In the archive, since tweets are only archived once posted, but never in the queue.
+    Posted,
+}
+
+/// What a key's value is.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Kind {
+    /// Any string.
+    String,
+    /// An RFC 3339 date and time, as a string.
+    DateTime,
+    /// One of the specified strings.
+    OneOf(&'static [&'static str]),
+    /// Any integer.
+    Integer,
+    /// A non-negative integer.
+    Unsigned,
+    /// A number in the specified range, inclusive.
+    Number(i64, i64),
+    /// `true` or `false`.
+    Boolean,
+    /// An array of strings.
+    Strings,
+    /// A table of strings.
+    StringMap,
+    /// A table with the specified keys.
+    Table(&'static [Field]),
+    /// An array of tables with the specified keys.
+    Tables(&'static [Field]),
+}
+
+/// A key in a format.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Field {
+    /// The key's name.
+    pub key: &'static str,
+    /// What the key's value is.
+    pub kind: Kind,
+    /// Whether the key needs to be specified.
+    pub presence: Presence,
+    /// What the key means, in one or more sentences.
+    pub description: &'static str,
+    /// An example value, in TOML, empty for tables.
+    pub example: &'static str,
+}
+
+
+impl Format {
+    /// All formats, in the order they're listed in.
+    pub fn all() -> &'static [Format] {
+        static ALL: &'static [Format] = &[Format::Config, Format::Users, Format::Queue, Format::Archive];
+        ALL
+    }
+
+    /// Parse a format from its name, as specified on the commandline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::schema::Format;
+    /// assert_eq!(Format::parse("queue"), Some(Format::Queue));
+    /// assert_eq!(Format::parse("tweets"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<Format> {
+        Format::all().iter().find(|f| f.name() == s).cloned()
+    }
+
+    /// Get the format's name, as specified on the commandline.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Format::Config => "config",
+            Format::Users => "users",
+            Format::Queue => "queue",
+            Format::Archive => "archive",
+        }
+    }
+
+    /// Get the name of the file in the configuration directory in this format.
+    pub fn file(&self) -> &'static str {
+        match *self {
+            Format::Config => "config.toml",
+            Format::Users => "users.toml",
+            Format::Queue => "tweets.toml",
+            Format::Archive => "archive-YYYY-MM.toml",
+        }
+    }
+
+    /// Get the format's top-level keys.
+    pub fn fields(&self) -> &'static [Field] {
+        match *self {
+            Format::Config => CONFIG_FIELDS,
+            Format::Users => USERS_FIELDS,
+            Format::Queue | Format::Archive => TWEETS_FIELDS,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match *self {
+            Format::Config => "tweetr global configuration",
+            Format::Users => "tweetr authenticated users",
+            Format::Queue => "tweetr tweet queue",
+            Format::Archive => "tweetr archive of posted tweets",
+        }
+    }
+
+    fn requires(&self, presence: Presence) -> bool {
+        match (presence, *self) {
+            (Presence::Required, _) |
+            (Presence::Posted, Format::Archive) => true,
+            _ => false,
+        }
+    }
+}
+
+
+/// Get the JSON Schema (draft 7) of the specified format, as parsed from TOML.
+///
+/// Keys not in the format are rejected, though tweetr itself ignores them, so that typos are caught.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::schema::{self, Format};
+/// let schema = schema::json_schema(Format::Archive);
+/// assert_eq!(schema.find("title").and_then(|t| t.as_string()), Some("tweetr archive of posted tweets (archive-YYYY-MM.toml)"));
+///
+/// let tweet = schema.find_path(&["properties", "tweet", "items"]).unwrap();
+/// assert_eq!(tweet.find_path(&["properties", "time_posted", "format"]).and_then(|f| f.as_string()), Some("date-time"));
+/// assert!(tweet.find("required").unwrap().as_array().unwrap().iter().any(|k| k.as_string() == Some("time_posted")));
+/// ```
+pub fn json_schema(format: Format) -> Json {
+    let mut schema = table_schema(format, format.fields());
+    schema.insert("$schema".to_string(), Json::String("http://json-schema.org/draft-07/schema#".to_string()));
+    schema.insert("title".to_string(), Json::String(format!("{} ({})", format.title(), format.file())));
+    Json::Object(schema)
+}
+
+/// Get an example file in the specified format, each key preceded by its description.
+///
+/// Optional keys are commented out, so the example only specifies the required ones as-is; posted tweets' `time_posted` and
+/// `id` are only required in the archive.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::schema::{self, Format};
+/// let example = schema::example(Format::Queue);
+/// assert!(example.starts_with("# tweetr tweet queue (tweets.toml)\n"));
+/// assert!(example.contains("\n[[tweet]]\n"));
+/// assert!(example.contains("\nauthor = \"nabijaczleweli\"\n"));
+/// assert!(example.contains("\n# sensitive = false\n"));
+/// assert!(example.contains("\n# time_posted = \"2016-09-10T12:00:05+02:00\"\n"));
+/// assert!(schema::example(Format::Archive).contains("\ntime_posted = \"2016-09-10T12:00:05+02:00\"\n"));
+/// ```
+pub fn example(format: Format) -> String {
+    let mut out = format!("# {} ({})\n#\n# Commented-out keys are optional.\n", format.title(), format.file());
+    example_table(&mut out, format, "", format.fields(), false);
+    out
+}
+
+
+fn table_schema(format: Format, fields: &[Field]) -> BTreeMap<String, Json> {
+    let mut schema = BTreeMap::new();
+    schema.insert("type".to_string(), Json::String("object".to_string()));
+    schema.insert("properties".to_string(),
+                  Json::Object(fields.iter().map(|f| (f.key.to_string(), Json::Object(field_schema(format, f)))).collect()));
+    let required: Vec<_> = fields.iter().filter(|f| format.requires(f.presence)).map(|f| Json::String(f.key.to_string())).collect();
+    if !required.is_empty() {
+        schema.insert("required".to_string(), Json::Array(required));
+    }
+    schema.insert("additionalProperties".to_string(), Json::Boolean(false));
+    schema
+}
+
+fn field_schema(format: Format, field: &Field) -> BTreeMap<String, Json> {
+    let mut schema = BTreeMap::new();
+    {
+        let mut set = |k: &str, v: Json| {
+            schema.insert(k.to_string(), v);
+        };
+        let string = |s: &str| Json::String(s.to_string());
+        match field.kind {
+            Kind::String => set("type", string("string")),
+            Kind::DateTime => {
+                set("type", string("string"));
+                set("format", string("date-time"));
+            }
+            Kind::OneOf(values) => {
+                set("type", string("string"));
+                set("enum", Json::Array(values.iter().map(|v| string(v)).collect()));
+            }
+            Kind::Integer => set("type", string("integer")),
+            Kind::Unsigned => {
+                set("type", string("integer"));
+                set("minimum", Json::U64(0));
+            }
+            Kind::Number(min, max) => {
+                set("type", string("number"));
+                set("minimum", Json::I64(min));
+                set("maximum", Json::I64(max));
+            }
+            Kind::Boolean => set("type", string("boolean")),
+            Kind::Strings => {
+                set("type", string("array"));
+                set("items", Json::Object(vec![("type".to_string(), string("string"))].into_iter().collect()));
+            }
+            Kind::StringMap => {
+                set("type", string("object"));
+                set("additionalProperties",
+                    Json::Object(vec![("type".to_string(), string("string"))].into_iter().collect()));
+            }
+            Kind::Table(fields) => {
+                for (k, v) in table_schema(format, fields) {
+                    set(&k, v);
+                }
+            }
+            Kind::Tables(fields) => {
+                set("type", string("array"));
+                set("items", Json::Object(table_schema(format, fields)));
+            }
+        }
+    }
+    schema.insert("description".to_string(), Json::String(field.description.to_string()));
+    schema
+}
+
+fn example_table(out: &mut String, format: Format, path: &str, fields: &[Field], commented: bool) {
+    let (scalars, tables): (Vec<_>, Vec<_>) = fields.iter().partition(|f| match f.kind {
+        Kind::Table(_) | Kind::Tables(_) => false,
+        _ => true,
+    });
+    for field in scalars {
+        example_field(out, format, field, commented);
+    }
+    // Tables nested in others, like [user.lint], go after the rest of the enclosing table's keys
+    for field in tables {
+        let commented = commented || !format.requires(field.presence);
+        let path = format!("{}{}", path, field.key);
+        out.push('\n');
+        comment(out, field.description);
+        let (header, fields) = match field.kind {
+            Kind::Table(fields) => (format!("[{}]", path), fields),
+            Kind::Tables(fields) => (format!("[[{}]]", path), fields),
+            _ => unreachable!(),
+        };
+        out.push_str(&format!("{}{}\n", if commented { "# " } else { "" }, header));
+        example_table(out, format, &format!("{}.", path), fields, commented);
+    }
+}
+
+fn example_field(out: &mut String, format: Format, field: &Field, commented: bool) {
+    out.push('\n');
+    comment(out, field.description);
+    out.push_str(&format!("{}{} = {}\n",
+                          if commented || !format.requires(field.presence) {
+                              "# "
+                          } else {
+                              ""
+                          },
+                          field.key,
+                          field.example));
+}
+
+/// Append the specified text as comment lines, wrapped at 80 characters.
+fn comment(out: &mut String, text: &str) {
+    let mut line = String::from("#");
+    for word in text.split_whitespace() {
+        if line.len() > 1 && line.chars().count() + 1 + word.chars().count() > 80 {
+            out.push_str(&line);
+            out.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+
+static WEEKDAYS: &'static [&'static str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+static CONFIG_FIELDS: &'static [Field] =
+    &[Field {
+          key: "hooks_dir",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Directory containing the hook executables, relative to the configuration directory. Default: \"hooks\"",
+          example: "\"hooks\"",
+      },
+      Field {
+          key: "dictionary",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "File to spellcheck queued tweets against, one word per line, relative to the configuration directory. Default: \
+                        none, tweets aren't spellchecked",
+          example: "\"/usr/share/dict/words\"",
+      },
+      Field {
+          key: "thread_numbering",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Template for numbering automatically split threads. Default: \"{n}/{total} {content}\"",
+          example: "\"{n}/{total} {content}\"",
+      },
+      Field {
+          key: "content_warning_format",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Template for folding tweets' content warnings into their content. Default: \"CW: {warning}\\n\\n{content}\"",
+          example: "\"[{warning}] {content}\"",
+      },
+      Field {
+          key: "quick_delimiter",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "What separates the content, time and account of tweets queued with quick-queue. Default: \"|\"",
+          example: "\"|\"",
+      },
+      Field {
+          key: "collision_window",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How close, in minutes, tweets by the same account can be scheduled before being warned about when queueing, \
+                        0 disables the warnings, at most a year. Default: 10",
+          example: "10",
+      },
+      Field {
+          key: "posting_slots",
+          kind: Kind::Strings,
+          presence: Presence::Optional,
+          description: "Weekly grid of preferred posting times, in local time, each entry being /-separated weekdays followed by \
+                        HH:MM times. Default: none",
+          example: "[\"Mon/Wed/Fri 09:00 17:00\", \"Sat 12:00\"]",
+      },
+      Field {
+          key: "refuse_insecure_users",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to refuse to start the daemon if the users file is readable by everyone, instead of only warning. \
+                        Default: false",
+          example: "false",
+      },
+      Field {
+          key: "archive_size",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many tweets an archive file can hold before the month's archive continues in the next one, 0 meaning \
+                        unlimited. Default: unlimited",
+          example: "1000",
+      },
+      Field {
+          key: "archive_retention",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many months before the current one to keep the archive files of when pruning. Default: none, archives \
+                        are kept forever",
+          example: "12",
+      },
+      Field {
+          key: "media_retention",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many days after the last tweet attached to them was posted to keep downloaded media for. Default: none, \
+                        media are kept until no tweet is attached to them",
+          example: "30",
+      },
+      Field {
+          key: "duplicate_window",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How close, in days, tweets by the same account with the same content can be before being warned about when \
+                        queueing, 0 disables the warnings, at most a year. Default: 30",
+          example: "30",
+      },
+      Field {
+          key: "evergreen_cooldown",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many days the daemon doesn't queue the same evergreen tweet again for. Default: 30",
+          example: "30",
+      },
+      Field {
+          key: "horizon",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How far in the future, in days, tweets can be queued or moved to, 0 allowing any time. Default: 3650",
+          example: "3650",
+      },
+      Field {
+          key: "embargo_policy",
+          kind: Kind::OneOf(&["end", "spread", "slots"]),
+          presence: Presence::Optional,
+          description: "Where to reschedule tweets coming due under an embargo to: right when it ends, spread out after it ends, or \
+                        to the free posting slots after it ends. Default: \"end\"",
+          example: "\"end\"",
+      },
+      Field {
+          key: "recovery_policy",
+          kind: Kind::OneOf(&["hold", "retry"]),
+          presence: Presence::Optional,
+          description: "What to do with tweets the daemon was posting when it stopped, if it's not recorded whether they were \
+                        posted: pause them or post them again. Default: \"hold\"",
+          example: "\"hold\"",
+      },
+      Field {
+          key: "missed_expiry",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How long, in minutes, after their scheduled time tweets that came due while the daemon wasn't running \
+                        are still posted when it starts, later ones being paused. Default: unlimited",
+          example: "60",
+      },
+      Field {
+          key: "latency_threshold",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How late, in seconds, the daemon can post tweets on average before warning about it, 0 never warning. \
+                        Default: 300",
+          example: "300",
+      },
+      Field {
+          key: "connect_timeout",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How long, in seconds, to wait for connecting to a server, 0 waiting indefinitely. Default: 10",
+          example: "10",
+      },
+      Field {
+          key: "read_timeout",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How long, in seconds, to wait for a server to respond, 0 waiting indefinitely. Default: 30",
+          example: "30",
+      },
+      Field {
+          key: "write_timeout",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How long, in seconds, to wait for a server to accept a request, 0 waiting indefinitely. Default: 30",
+          example: "30",
+      },
+      Field {
+          key: "bind_address",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The local IP address, or, on Unix, network interface, to connect to servers from. Default: the system's choice",
+          example: "\"192.0.2.7\"",
+      },
+      Field {
+          key: "file_retries",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many times to retry reading or writing a file in the configuration directory failing transiently. \
+                        Default: 3",
+          example: "3",
+      },
+      Field {
+          key: "file_retry_delay",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How long, in milliseconds, to wait before retrying a file access. Default: 1000",
+          example: "1000",
+      },
+      Field {
+          key: "datetime_format",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "How to display dates with times, as a strftime pattern. Default: RFC 3339",
+          example: "\"%d.%m.%Y %H:%M\"",
+      },
+      Field {
+          key: "date_format",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "How to display dates, as a strftime pattern. Default: \"%a %d %b\"",
+          example: "\"%a %d %b\"",
+      },
+      Field {
+          key: "time_format",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "How to display times of day, as a strftime pattern. Default: \"%H:%M\"",
+          example: "\"%H:%M\"",
+      },
+      Field {
+          key: "first_day_of_week",
+          kind: Kind::OneOf(WEEKDAYS),
+          presence: Presence::Optional,
+          description: "The abbreviated English name of the day weeks start on. Default: \"Mon\"",
+          example: "\"Mon\"",
+      },
+      Field {
+          key: "timezone",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The UTC offset quiet hours and embargoes are in, as +HH:MM, -HH:MM or UTC. Default: local time",
+          example: "\"+02:00\"",
+      },
+      Field {
+          key: "quiet_hours",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The daily span of time no tweets are posted in, as HH:MM-HH:MM. Default: none",
+          example: "\"23:00-07:00\"",
+      },
+      Field {
+          key: "jitter",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "Up to how many minutes after their scheduled time tweets are posted. Default: 0",
+          example: "5",
+      },
+      Field {
+          key: "tags",
+          kind: Kind::Strings,
+          presence: Presence::Optional,
+          description: "Hashtags appended to tweets, with or without the leading #. Default: none",
+          example: "[\"#tweetr\"]",
+      },
+      Field {
+          key: "sensitive",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to mark tweets as possibly sensitive. Default: false",
+          example: "false",
+      },
+      Field {
+          key: "embargo",
+          kind: Kind::Tables(EMBARGO_FIELDS),
+          presence: Presence::Optional,
+          description: "Spans of time the daemon doesn't post anything in. Default: none",
+          example: "",
+      },
+      Field {
+          key: "sink",
+          kind: Kind::Tables(SINK_FIELDS),
+          presence: Presence::Optional,
+          description: "Files, commands and Discord webhooks to deliver the tweets of the same-named authors to instead of posting \
+                        them, each with exactly one of file, command and discord_webhook. Default: none",
+          example: "",
+      },
+      Field {
+          key: "transform",
+          kind: Kind::Table(TRANSFORM_FIELDS),
+          presence: Presence::Optional,
+          description: "Which transformations to apply to tweets' content before posting them. Default: none",
+          example: "",
+      }];
+
+static EMBARGO_FIELDS: &'static [Field] = &[Field {
+                                                  key: "start",
+                                                  kind: Kind::String,
+                                                  presence: Presence::Required,
+                                                  description: "When the embargo starts, inclusive, as YYYY-MM-DD HH:MM in the UTC \
+                                                                offset of timezone, or RFC 3339",
+                                                  example: "\"2016-09-10 18:00\"",
+                                              },
+                                              Field {
+                                                  key: "end",
+                                                  kind: Kind::String,
+                                                  presence: Presence::Required,
+                                                  description: "When the embargo ends, exclusive, in the same format",
+                                                  example: "\"2016-09-11 09:00\"",
+                                              }];
+
+static SINK_FIELDS: &'static [Field] = &[Field {
+                                               key: "name",
+                                               kind: Kind::String,
+                                               presence: Presence::Required,
+                                               description: "The name tweets refer to the sink by in their author",
+                                               example: "\"motd\"",
+                                           },
+                                           Field {
+                                               key: "file",
+                                               kind: Kind::String,
+                                               presence: Presence::Optional,
+                                               description: "File to append the tweets to, relative to the configuration directory",
+                                               example: "\"motd.txt\"",
+                                           },
+                                           Field {
+                                               key: "command",
+                                               kind: Kind::String,
+                                               presence: Presence::Optional,
+                                               description: "Shell command to pipe the tweets to",
+                                               example: "\"wall\"",
+                                           },
+                                           Field {
+                                               key: "discord_webhook",
+                                               kind: Kind::String,
+                                               presence: Presence::Optional,
+                                               description: "URL of the Discord webhook to execute with the tweets",
+                                               example: "\"https://discord.com/api/webhooks/481/token\"",
+                                           }];
+
+static TRANSFORM_FIELDS: &'static [Field] = &[Field {
+                                                    key: "collapse_spaces",
+                                                    kind: Kind::Boolean,
+                                                    presence: Presence::Optional,
+                                                    description: "Whether to collapse runs of whitespace. Default: false",
+                                                    example: "true",
+                                                },
+                                                Field {
+                                                    key: "smart_quotes",
+                                                    kind: Kind::Boolean,
+                                                    presence: Presence::Optional,
+                                                    description: "Whether to replace typographic quotes with plain ones. Default: false",
+                                                    example: "true",
+                                                },
+                                                Field {
+                                                    key: "hashtags",
+                                                    kind: Kind::String,
+                                                    presence: Presence::Optional,
+                                                    description: "File containing the hashtags to change the case of hashtags to, one \
+                                                                  per line, relative to the configuration directory. Default: none",
+                                                    example: "\"hashtags.txt\"",
+                                                },
+                                                Field {
+                                                    key: "campaign_tag",
+                                                    kind: Kind::String,
+                                                    presence: Presence::Optional,
+                                                    description: "Hashtag to append to all tweets, with or without the leading #. \
+                                                                  Default: none",
+                                                    example: "\"#TweetrRelease\"",
+                                                }];
+
+static USERS_FIELDS: &'static [Field] = &[Field {
+                                                key: "user",
+                                                kind: Kind::Tables(USER_FIELDS),
+                                                presence: Presence::Required,
+                                                description: "The authenticated users, as added by add-user",
+                                                example: "",
+                                            }];
+
+static USER_FIELDS: &'static [Field] =
+    &[Field {
+          key: "name",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "The user's screen name, without the @",
+          example: "\"nabijaczleweli\"",
+      },
+      Field {
+          key: "id",
+          kind: Kind::Integer,
+          presence: Presence::Required,
+          description: "The user's numeric ID",
+          example: "2863178003",
+      },
+      Field {
+          key: "access_token_key",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "The key part of the user's access token",
+          example: "\"2863178003-Fq8oFqjPD5lJcnKUaRs0YW6PhxhHpaM4cMPWE0z\"",
+      },
+      Field {
+          key: "access_token_secret",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "The secret part of the user's access token",
+          example: "\"Hw1GL9S08Cdo4rm1iwU0PFuWGhEa9JGn4BMfA4y23l6ks\"",
+      },
+      Field {
+          key: "app",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Name of the app the access token was issued for. Default: the default app",
+          example: "\"work\"",
+      },
+      Field {
+          key: "telegram_bot_token",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Token of the Telegram bot to mirror the user's tweets with. Default: none",
+          example: "\"110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw\"",
+      },
+      Field {
+          key: "telegram_chat_id",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "ID or @username of the Telegram chat or channel to mirror the user's tweets to. Default: none",
+          example: "\"@tweetr_news\"",
+      },
+      Field {
+          key: "discord_webhook",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "URL of the Discord webhook to mirror the user's tweets to. Default: none",
+          example: "\"https://discord.com/api/webhooks/481/token\"",
+      },
+      Field {
+          key: "timezone",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The UTC offset the user's quiet hours are in. Default: the global one",
+          example: "\"+02:00\"",
+      },
+      Field {
+          key: "quiet_hours",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The daily span of time none of the user's tweets are posted in. Default: the global one",
+          example: "\"23:00-07:00\"",
+      },
+      Field {
+          key: "jitter",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "Up to how many minutes after their scheduled time the user's tweets are posted. Default: the global amount",
+          example: "5",
+      },
+      Field {
+          key: "tags",
+          kind: Kind::Strings,
+          presence: Presence::Optional,
+          description: "Hashtags appended to the user's tweets. Default: the global ones",
+          example: "[\"#tweetr\"]",
+      },
+      Field {
+          key: "sensitive",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to mark the user's tweets as possibly sensitive. Default: the global setting",
+          example: "false",
+      },
+      Field {
+          key: "test_mode",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to rehearse posting the user's tweets instead of publishing them. Default: false",
+          example: "false",
+      },
+      Field {
+          key: "test_account",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Name of the user to post the user's tweets as in test mode, instead of marking them as tests. Default: none",
+          example: "\"tweetr_test\"",
+      },
+      Field {
+          key: "added",
+          kind: Kind::DateTime,
+          presence: Presence::Optional,
+          description: "When the user was added",
+          example: "\"2016-09-10T12:00:00+02:00\"",
+      },
+      Field {
+          key: "suspended",
+          kind: Kind::DateTime,
+          presence: Presence::Optional,
+          description: "Since when posting the user's tweets is paused, because their account was found suspended or locked; remove to resume",
+          example: "\"2016-09-11T12:00:00+02:00\"",
+      },
+      Field {
+          key: "max_posts_per_day",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "The most tweets by the user to post a day; the ones over it are carried over to the next day. Default: unlimited",
+          example: "10",
+      },
+      Field {
+          key: "lint",
+          kind: Kind::Table(LINT_FIELDS),
+          presence: Presence::Optional,
+          description: "The style rules to check the user's tweets against when queueing them. Default: none",
+          example: "",
+      }];
+
+static LINT_FIELDS: &'static [Field] = &[Field {
+                                               key: "max_hashtags",
+                                               kind: Kind::Unsigned,
+                                               presence: Presence::Optional,
+                                               description: "The most hashtags a tweet can contain. Default: unlimited",
+                                               example: "2",
+                                           },
+                                           Field {
+                                               key: "max_mentions",
+                                               kind: Kind::Unsigned,
+                                               presence: Presence::Optional,
+                                               description: "The most accounts a tweet can mention. Default: unlimited",
+                                               example: "3",
+                                           },
+                                           Field {
+                                               key: "max_caps",
+                                               kind: Kind::Unsigned,
+                                               presence: Presence::Optional,
+                                               description: "The most letters a word in ALL CAPS can have. Default: unlimited",
+                                               example: "4",
+                                           },
+                                           Field {
+                                               key: "link_required_for",
+                                               kind: Kind::Strings,
+                                               presence: Presence::Optional,
+                                               description: "Hashtags tweets containing which must also contain a link, with * \
+                                                             standing for anything. Default: none",
+                                               example: "[\"#release*\"]",
+                                           },
+                                           Field {
+                                               key: "block",
+                                               kind: Kind::Boolean,
+                                               presence: Presence::Optional,
+                                               description: "Whether to refuse to queue tweets breaking the rules, instead of \
+                                                             warning about them. Default: false",
+                                               example: "false",
+                                           }];
+
+static TWEETS_FIELDS: &'static [Field] = &[Field {
+                                                 key: "tweet",
+                                                 kind: Kind::Tables(TWEET_FIELDS),
+                                                 presence: Presence::Required,
+                                                 description: "The tweets, in any order",
+                                                 example: "",
+                                             }];
+
+static TWEET_FIELDS: &'static [Field] =
+    &[Field {
+          key: "author",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "Name of the user or sink to post the tweet as",
+          example: "\"nabijaczleweli\"",
+      },
+      Field {
+          key: "time",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "When to post the tweet, in RFC 3339, or relative to another tweet by its name, like \"15 minutes after \
+                        launch\"",
+          example: "\"2016-09-10T12:00:00+02:00\"",
+      },
+      Field {
+          key: "content",
+          kind: Kind::String,
+          presence: Presence::Required,
+          description: "The tweet's text",
+          example: "\"Abolish the bourgeoisie\"",
+      },
+      Field {
+          key: "media",
+          kind: Kind::Strings,
+          presence: Presence::Optional,
+          description: "Paths to the media to attach to the tweet. Default: none",
+          example: "[\"/srv/charts/weekly.png\"]",
+      },
+      Field {
+          key: "thread",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Name of the thread the tweet is a part of, posted as a reply to the thread's previous tweet. Default: none",
+          example: "\"release\"",
+      },
+      Field {
+          key: "in_reply_to",
+          kind: Kind::Integer,
+          presence: Presence::Optional,
+          description: "ID of the tweet to post the tweet as a reply to. Default: none",
+          example: "774541307568103424",
+      },
+      Field {
+          key: "extra_params",
+          kind: Kind::StringMap,
+          presence: Presence::Optional,
+          description: "Additional parameters to post the tweet with, by name. Default: none",
+          example: "{ card_uri = \"card://774541307568103424\" }",
+      },
+      Field {
+          key: "variants",
+          kind: Kind::StringMap,
+          presence: Presence::Optional,
+          description: "The content to mirror the tweet with instead, by backend, \"telegram\" or \"discord\". Default: none",
+          example: "{ telegram = \"Abolish the bourgeoisie, read more at https://example.com/manifesto\" }",
+      },
+      Field {
+          key: "content_warning",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "What to warn about the content with, folded into it when posting. Default: none",
+          example: "\"politics\"",
+      },
+      Field {
+          key: "lat",
+          kind: Kind::Number(-90, 90),
+          presence: Presence::Optional,
+          description: "The latitude to tag the tweet with, together with long. Default: none",
+          example: "52.2297",
+      },
+      Field {
+          key: "long",
+          kind: Kind::Number(-180, 180),
+          presence: Presence::Optional,
+          description: "The longitude to tag the tweet with, together with lat. Default: none",
+          example: "21.0122",
+      },
+      Field {
+          key: "place_id",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "ID of the Twitter place to tag the tweet with. Default: none",
+          example: "\"df51dec6f4ee2b2c\"",
+      },
+      Field {
+          key: "reply_settings",
+          kind: Kind::OneOf(&["everyone", "following", "mentionedUsers"]),
+          presence: Presence::Optional,
+          description: "Who can reply to the tweet. Default: \"everyone\"",
+          example: "\"following\"",
+      },
+      Field {
+          key: "name",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Name other tweets can be scheduled relative to the tweet by. Default: none",
+          example: "\"launch\"",
+      },
+      Field {
+          key: "paused",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to hold the tweet back from being posted. Default: false",
+          example: "true",
+      },
+      Field {
+          key: "blocked",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Why the tweet is held back, after a tweet before it in its thread failed permanently; cleared by requeue-thread. Default: none",
+          example: "\"Status is a duplicate. (code 187)\"",
+      },
+      Field {
+          key: "timezone",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The UTC offset the tweet's quiet hours are in. Default: the author's",
+          example: "\"+02:00\"",
+      },
+      Field {
+          key: "quiet_hours",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "The daily span of time the tweet isn't posted in. Default: the author's",
+          example: "\"23:00-07:00\"",
+      },
+      Field {
+          key: "jitter",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "Up to how many minutes after its scheduled time the tweet is posted. Default: the author's",
+          example: "5",
+      },
+      Field {
+          key: "tags",
+          kind: Kind::Strings,
+          presence: Presence::Optional,
+          description: "Hashtags appended to the tweet. Default: the author's",
+          example: "[\"#tweetr\"]",
+      },
+      Field {
+          key: "sensitive",
+          kind: Kind::Boolean,
+          presence: Presence::Optional,
+          description: "Whether to mark the tweet as possibly sensitive. Default: the author's setting",
+          example: "false",
+      },
+      Field {
+          key: "time_posted",
+          kind: Kind::DateTime,
+          presence: Presence::Posted,
+          description: "When the tweet was posted",
+          example: "\"2016-09-10T12:00:05+02:00\"",
+      },
+      Field {
+          key: "id",
+          kind: Kind::Integer,
+          presence: Presence::Posted,
+          description: "ID of the posted tweet",
+          example: "774541307568103424",
+      },
+      Field {
+          key: "app",
+          kind: Kind::String,
+          presence: Presence::Optional,
+          description: "Name of the app the tweet was posted through, which Twitter shows it was sent via. Default: the default app",
+          example: "\"work\"",
+      },
+      Field {
+          key: "retries",
+          kind: Kind::Unsigned,
+          presence: Presence::Optional,
+          description: "How many times posting the tweet failed and it was retried. Default: 0",
+          example: "2",
+      }];
@@ -0,0 +1,444 @@
+//! Settings queued tweets inherit from their author's account and the global configuration, unless they override them.
+//!
+//! Each setting is taken from the first of these to specify it:
+//!
+//!   1. the queued tweet itself,
+//!   2. its author's entry in the users file,
+//!   3. the global configuration,
+//!   4. the built-in default.
+//!
+//! Tweets delivered to sinks (see `ops::backend`) have no account, and so inherit straight from the global configuration.
+//!
+//! The settings are resolved by the daemon right before posting each tweet, so changing an account's defaults affects all of its
+//! already queued tweets that don't override them.
+
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveTime};
+use std::collections::hash_map::DefaultHasher;
+use self::super::super::Outcome;
+use std::hash::{Hash, Hasher};
+use self::super::QueuedTweet;
+
+
+/// Settings overridable on each level, all optional.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Settings {
+    /// The UTC offset quiet hours are in, like `"+02:00"`, `"-05:30"` or `"UTC"`. Default: local time
+    ///
+    /// See `parse_timezone()` for the format.
+    pub timezone: Option<String>,
+    /// The daily span of time no tweets are posted in, like `"23:00-07:00"`. Default: `None`
+    ///
+    /// Tweets due in it are posted when it ends. See `QuietHours::parse()` for the format.
+    pub quiet_hours: Option<String>,
+    /// Up to how many minutes after their scheduled time tweets are posted. Default: `0`
+    ///
+    /// The delay is picked pseudo-randomly, but consistently for each tweet, see `Effective::posting_time()`.
+    pub jitter: Option<u32>,
+    /// Hashtags appended to tweets not already containing them, with or without the leading `#`. Default: `[]`
+    pub tags: Option<Vec<String>>,
+    /// Whether to mark tweets as possibly sensitive. Default: `false`
+    pub sensitive: Option<bool>,
+}
+
+/// Where an effective setting comes from.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Source {
+    /// The queued tweet overrides it.
+    Tweet,
+    /// The tweet's author's entry in the users file.
+    Account,
+    /// The global configuration.
+    Config,
+    /// None of the above specify it.
+    Default,
+}
+
+/// A daily span of time, wrapping around midnight if it ends before it starts.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct QuietHours {
+    /// When the span starts, inclusive.
+    pub start: NaiveTime,
+    /// When the span ends, exclusive.
+    pub end: NaiveTime,
+}
+
+/// The settings applying to a tweet, each with where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Effective {
+    /// The UTC offset quiet hours are in, or `None` for local time.
+    pub timezone: (Option<FixedOffset>, Source),
+    /// The daily span of time no tweets are posted in, if any.
+    pub quiet_hours: (Option<QuietHours>, Source),
+    /// Up to how many minutes after their scheduled time tweets are posted.
+    pub jitter: (u32, Source),
+    /// Hashtags appended to tweets, each with the leading `#`.
+    pub tags: (Vec<String>, Source),
+    /// Whether to mark tweets as possibly sensitive.
+    pub sensitive: (bool, Source),
+}
+
+
+/// Parse a UTC offset in the format `[+-]HH:MM`, or `UTC`/`Z` for no offset.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::settings;
+/// # use chrono::FixedOffset;
+/// # fn main() {
+/// assert_eq!(settings::parse_timezone("+02:00"), Some(FixedOffset::east(2 * 60 * 60)));
+/// assert_eq!(settings::parse_timezone("-05:30"), Some(FixedOffset::west((5 * 60 + 30) * 60)));
+/// assert_eq!(settings::parse_timezone("UTC"), Some(FixedOffset::east(0)));
+/// assert_eq!(settings::parse_timezone("Europe/Warsaw"), None);
+/// assert_eq!(settings::parse_timezone("+25:00"), None);
+/// # }
+/// ```
+pub fn parse_timezone(tz: &str) -> Option<FixedOffset> {
+    let tz = tz.trim();
+    if tz == "UTC" || tz == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+
+    let sign = match tz.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+    let mut parts = tz[1..].splitn(2, ':');
+    match (parts.next().and_then(|h| h.parse::<i32>().ok()), parts.next().and_then(|m| m.parse::<i32>().ok())) {
+        (Some(h), Some(m)) if h < 24 && m < 60 => FixedOffset::east_opt(sign * (h * 60 + m) * 60),
+        _ => None,
+    }
+}
+
+
+impl Settings {
+    /// Check whether no setting is specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::settings::Settings;
+    /// assert!(Settings::default().is_empty());
+    /// assert!(!Settings { sensitive: Some(false), ..Settings::default() }.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        *self == Settings::default()
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            timezone: None,
+            quiet_hours: None,
+            jitter: None,
+            tags: None,
+            sensitive: None,
+        }
+    }
+}
+
+impl Source {
+    /// Get a human-readable name of the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::settings::Source;
+    /// assert_eq!(Source::Account.name(), "account");
+    /// assert_eq!(Source::Config.name(), "configuration");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Source::Tweet => "tweet",
+            Source::Account => "account",
+            Source::Config => "configuration",
+            Source::Default => "default",
+        }
+    }
+
+    /// Get the description of the file the settings from this source are read from, as used by `Outcome::FileParsingFailed`.
+    fn file_desc(&self) -> &'static str {
+        match *self {
+            Source::Tweet => "queued tweets",
+            Source::Account => "users",
+            Source::Config | Source::Default => "configuration",
+        }
+    }
+}
+
+impl QuietHours {
+    /// Parse a span of time in the format `HH:MM-HH:MM`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::settings::QuietHours;
+    /// # use chrono::NaiveTime;
+    /// # fn main() {
+    /// assert_eq!(QuietHours::parse("23:00-07:30"),
+    ///            Some(QuietHours {
+    ///                start: NaiveTime::from_hms(23, 0, 0),
+    ///                end: NaiveTime::from_hms(7, 30, 0),
+    ///            }));
+    /// assert_eq!(QuietHours::parse("23:00"), None);
+    /// assert_eq!(QuietHours::parse("12:00-12:00"), None);
+    /// # }
+    /// ```
+    pub fn parse(s: &str) -> Option<QuietHours> {
+        let mut parts = s.splitn(2, '-');
+        match (parts.next().and_then(|t| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok()),
+               parts.next().and_then(|t| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok())) {
+            (Some(start), Some(end)) if start != end => {
+                Some(QuietHours {
+                    start: start,
+                    end: end,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether the specified time of day falls in the span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::settings::QuietHours;
+    /// # use chrono::NaiveTime;
+    /// # fn main() {
+    /// let night = QuietHours::parse("23:00-07:00").unwrap();
+    /// assert!(night.contains(NaiveTime::from_hms(23, 0, 0)));
+    /// assert!(night.contains(NaiveTime::from_hms(3, 0, 0)));
+    /// assert!(!night.contains(NaiveTime::from_hms(7, 0, 0)));
+    /// assert!(!night.contains(NaiveTime::from_hms(12, 0, 0)));
+    /// # }
+    /// ```
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+
+    /// Get when the span the specified time falls in ends, if it falls in one, in the time's offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::settings::QuietHours;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let night = QuietHours::parse("23:00-07:00").unwrap();
+    /// assert_eq!(night.end_after(DateTime::parse_from_rfc3339("2016-09-10T23:30:00+02:00").unwrap()),
+    ///            Some(DateTime::parse_from_rfc3339("2016-09-11T07:00:00+02:00").unwrap()));
+    /// assert_eq!(night.end_after(DateTime::parse_from_rfc3339("2016-09-11T03:00:00+02:00").unwrap()),
+    ///            Some(DateTime::parse_from_rfc3339("2016-09-11T07:00:00+02:00").unwrap()));
+    /// assert_eq!(night.end_after(DateTime::parse_from_rfc3339("2016-09-11T12:00:00+02:00").unwrap()), None);
+    /// # }
+    /// ```
+    pub fn end_after(&self, time: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        let t = time.time();
+        if !self.contains(t) {
+            return None;
+        }
+
+        if t < self.end {
+            time.date().and_time(self.end)
+        } else {
+            time.date().succ().and_time(self.end)
+        }
+    }
+}
+
+impl Effective {
+    /// Resolve the settings from the tweet's overrides, its author's account's defaults, if it has an account, and the global
+    /// configuration, in that order of precedence.
+    ///
+    /// Fails if a timezone or quiet hours are invalid, pointing at the file specifying them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::settings::{Effective, Settings, Source};
+    /// # use tweetr::Outcome;
+    /// let tweet = Settings { sensitive: Some(true), ..Settings::default() };
+    /// let account = Settings {
+    ///     tags: Some(vec!["tweetr".to_string()]),
+    ///     sensitive: Some(false),
+    ///     ..Settings::default()
+    /// };
+    /// let config = Settings { jitter: Some(5), ..Settings::default() };
+    ///
+    /// let effective = Effective::resolve(Some(&tweet), Some(&account), &config).unwrap();
+    /// assert_eq!(effective.sensitive, (true, Source::Tweet));
+    /// assert_eq!(effective.tags, (vec!["#tweetr".to_string()], Source::Account));
+    /// assert_eq!(effective.jitter, (5, Source::Config));
+    /// assert_eq!(effective.quiet_hours, (None, Source::Default));
+    ///
+    /// assert_eq!(Effective::resolve(None, Some(&Settings { timezone: Some("CEST".to_string()), ..Settings::default() }), &config),
+    ///            Err(Outcome::FileParsingFailed {
+    ///                desc: "users",
+    ///                errors: vec!["error: invalid UTC offset \"CEST\" for the key `timezone`".to_string()],
+    ///            }));
+    /// ```
+    pub fn resolve(tweet: Option<&Settings>, account: Option<&Settings>, config: &Settings) -> Result<Effective, Outcome> {
+        let layers = [(Source::Tweet, tweet), (Source::Account, account), (Source::Config, Some(config))];
+
+        Ok(Effective {
+            timezone: match pick(&layers, |s| s.timezone.as_ref()) {
+                Some((tz, src)) => (Some(try!(parse_timezone(&tz).ok_or_else(|| invalid(src, "UTC offset", &tz, "timezone")))), src),
+                None => (None, Source::Default),
+            },
+            quiet_hours: match pick(&layers, |s| s.quiet_hours.as_ref()) {
+                Some((qh, src)) => (Some(try!(QuietHours::parse(&qh).ok_or_else(|| invalid(src, "span of time", &qh, "quiet_hours")))), src),
+                None => (None, Source::Default),
+            },
+            jitter: pick(&layers, |s| s.jitter.as_ref()).unwrap_or((0, Source::Default)),
+            tags: pick(&layers, |s| s.tags.as_ref())
+                .map(|(tags, src)| (tags.into_iter().map(|t| if t.starts_with('#') { t } else { format!("#{}", t) }).collect(), src))
+                .unwrap_or((vec![], Source::Default)),
+            sensitive: pick(&layers, |s| s.sensitive.as_ref()).unwrap_or((false, Source::Default)),
+        })
+    }
+
+    /// Get when the specified tweet is to be posted, in its offset.
+    ///
+    /// This is its scheduled time, delayed by the jitter, and then until the end of the quiet hours, if it falls in them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::settings::{Effective, Settings};
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::{DateTime, Duration};
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T23:30:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let night = Settings {
+    ///     timezone: Some("+02:00".to_string()),
+    ///     quiet_hours: Some("23:00-07:00".to_string()),
+    ///     ..Settings::default()
+    /// };
+    /// assert_eq!(Effective::resolve(None, None, &night).unwrap().posting_time(&tweet),
+    ///            DateTime::parse_from_rfc3339("2016-09-11T07:00:00+02:00").unwrap());
+    ///
+    /// let jitter = Settings { jitter: Some(10), ..Settings::default() };
+    /// let posting_time = Effective::resolve(None, None, &jitter).unwrap().posting_time(&tweet);
+    /// assert!(tweet.time <= posting_time && posting_time <= tweet.time + Duration::minutes(10));
+    /// # }
+    /// ```
+    pub fn posting_time(&self, tweet: &QueuedTweet) -> DateTime<FixedOffset> {
+        let time = tweet.time + jitter(tweet, self.jitter.0);
+
+        match self.quiet_hours.0 {
+            Some(qh) => {
+                let tz = self.timezone.0.unwrap_or_else(|| *time.with_timezone(&Local).offset());
+                qh.end_after(time.with_timezone(&tz)).map(|end| end.with_timezone(tweet.time.offset())).unwrap_or(time)
+            }
+            None => time,
+        }
+    }
+
+    /// Apply the tags and the sensitivity flag to the specified tweet.
+    ///
+    /// Tags already in the tweet (in any case) aren't added again, and `possibly_sensitive` isn't set if the tweet specifies it
+    /// in its `extra_params`, so applying the settings multiple times is the same as applying them once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::settings::{Effective, Settings};
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "Abolish the bourgeoisie with #Tweetr!".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let effective = Effective::resolve(None,
+    ///                                    None,
+    ///                                    &Settings {
+    ///                                        tags: Some(vec!["tweetr".to_string(), "#communism".to_string()]),
+    ///                                        sensitive: Some(true),
+    ///                                        ..Settings::default()
+    ///                                    })
+    ///     .unwrap();
+    ///
+    /// let applied = effective.apply(&tweet);
+    /// assert_eq!(applied.content, "Abolish the bourgeoisie with #Tweetr! #communism");
+    /// assert_eq!(applied.extra_params.get("possibly_sensitive").map(|s| &s[..]), Some("true"));
+    /// assert_eq!(effective.apply(&applied), applied);
+    /// # }
+    /// ```
+    pub fn apply(&self, tweet: &QueuedTweet) -> QueuedTweet {
+        let mut tweet = tweet.clone();
+
+        let missing: Vec<_> = self.tags
+            .0
+            .iter()
+            .filter(|tag| {
+                let tag = tag.to_lowercase();
+                !tweet.content
+                    .split_whitespace()
+                    .any(|w| w.trim_right_matches(|c: char| !c.is_alphanumeric() && c != '_').to_lowercase() == tag)
+            })
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            tweet.content = format!("{} {}", tweet.content, missing.join(" "));
+        }
+
+        if self.sensitive.0 {
+            tweet.extra_params.entry("possibly_sensitive".to_string()).or_insert_with(|| "true".to_string());
+        }
+
+        tweet
+    }
+}
+
+
+fn pick<T: Clone, F: Fn(&Settings) -> Option<&T>>(layers: &[(Source, Option<&Settings>)], f: F) -> Option<(T, Source)> {
+    layers.iter().flat_map(|&(src, s)| s.and_then(|s| f(s)).map(|v| (v.clone(), src))).next()
+}
+
+fn invalid(src: Source, what: &str, value: &str, key: &str) -> Outcome {
+    Outcome::FileParsingFailed {
+        desc: src.file_desc(),
+        errors: vec![format!("error: invalid {} \"{}\" for the key `{}`", what, value, key)],
+    }
+}
+
+/// Pseudo-random, but the same for each tweet, since it's recalculated on every iteration of the daemon.
+fn jitter(tweet: &QueuedTweet, minutes: u32) -> Duration {
+    if minutes == 0 {
+        return Duration::zero();
+    }
+
+    let mut hasher = DefaultHasher::new();
+    tweet.author.hash(&mut hasher);
+    tweet.time.hash(&mut hasher);
+    Duration::seconds((hasher.finish() % (minutes as u64 * 60 + 1)) as i64)
+}
@@ -0,0 +1,263 @@
+//! This module contains the functions used only by the `setup` subsystem.
+//!
+//! The `setup` subsystem chains the `init`, `add-user` and `queue-tweet` subsystems, skipping the steps that were already
+//! done, so its flow is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::setup::progress()
+//! |> ops::setup::print_step_header()
+//! |> init flow, unless already initialised
+//! |> ops::setup::print_step_header()
+//! |> add-user flow, unless a user was already added
+//! |> ops::setup::print_step_header()
+//! |> queue-tweet flow
+//! |> ops::setup::test_tweet()
+//! ```
+
+
+use self::super::super::util::span_r;
+use self::super::start_daemon::post_tweet;
+use self::super::clock::{Clock, SystemClock};
+use self::super::backend::Twitter;
+use self::super::settings::Settings;
+use self::super::doctor::VERIFY_CREDENTIALS_URL;
+use self::super::{network, oauth};
+use self::super::{QueuedTweet, User};
+use self::super::super::Outcome;
+use egg_mode::{Token, verify_tokens};
+use rustc_serialize::json::Json;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+
+/// All the steps taken by the `setup` subsystem, in order.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SetupStep {
+    /// Entering the global app configuration, equivalent to the `init` subsystem.
+    Init,
+    /// Authorising a user, equivalent to the `add-user` subsystem.
+    AddUser,
+    /// Queueing tweets, equivalent to the `queue-tweet` subsystem.
+    QueueTweet,
+    /// Verifying the whole pipeline works by posting (or pretending to post) a test tweet.
+    TestTweet,
+}
+
+/// Which parts of the configuration already exist in a configuration directory.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct SetupProgress {
+    /// Whether the global app configuration exists.
+    pub app: bool,
+    /// Whether the global users data exists.
+    pub users: bool,
+    /// Whether the global tweet queue exists.
+    pub tweets: bool,
+}
+
+
+impl SetupStep {
+    /// Get the short human-readable name of this step.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::setup::SetupStep;
+    /// assert_eq!(SetupStep::AddUser.name(), "Authorising a user");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SetupStep::Init => "Configuring the application",
+            SetupStep::AddUser => "Authorising a user",
+            SetupStep::QueueTweet => "Queueing tweets",
+            SetupStep::TestTweet => "Testing the setup",
+        }
+    }
+
+    /// Get the explanation of what this step does and why it's needed.
+    pub fn explanation(&self) -> &'static str {
+        match *self {
+            SetupStep::Init => {
+                "tweetr needs to know which Twitter application it's posting as.\n\
+                 Create one on https://apps.twitter.com and copy its consumer key and secret from the \"Keys and Access Tokens\" tab.\n\
+                 This is the same as running \"tweetr init\"."
+            }
+            SetupStep::AddUser => {
+                "tweetr needs permission to post on behalf of each account tweets are queued for.\n\
+                 Open the URL below in a browser where you're logged in as that account, authorise the app and enter the PIN you get.\n\
+                 More accounts can be added later with \"tweetr add-user\"."
+            }
+            SetupStep::QueueTweet => {
+                "Tweets are queued for an author and a time, and will be posted by the daemon (\"tweetr start-daemon\").\n\
+                 Leave the author empty to finish queueing. More tweets can be queued later with \"tweetr queue-tweet\"."
+            }
+            SetupStep::TestTweet => {
+                "Verifying the whole pipeline works by sending a test tweet.\n\
+                 With --dry-run the user's credentials are only verified and nothing is posted."
+            }
+        }
+    }
+}
+
+/// Check which parts of the configuration in the specified directory already exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::setup::{self, SetupProgress};
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-setup-progress-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap();
+/// let _ = fs::remove_file(tf.join("users.toml"));
+/// let _ = fs::remove_file(tf.join("tweets.toml"));
+///
+/// assert_eq!(setup::progress(&tf),
+///            SetupProgress {
+///                app: true,
+///                users: false,
+///                tweets: false,
+///            });
+/// ```
+pub fn progress(config_dir: &Path) -> SetupProgress {
+    SetupProgress {
+        app: config_dir.join("app.toml").exists(),
+        users: config_dir.join("users.toml").exists(),
+        tweets: config_dir.join("tweets.toml").exists(),
+    }
+}
+
+/// Print the header of the specified step, its number and either the explanation or a notice that it's already been done.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::setup::{self, SetupStep};
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// setup::print_step_header(&mut out, SetupStep::Init, true);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "Step 1/4: Configuring the application\nAlready done, skipping.\n\n".to_string());
+/// ```
+pub fn print_step_header<W: Write>(output: &mut W, step: SetupStep, already_done: bool) {
+    writeln!(output,
+             "Step {}/4: {}",
+             match step {
+                 SetupStep::Init => 1,
+                 SetupStep::AddUser => 2,
+                 SetupStep::QueueTweet => 3,
+                 SetupStep::TestTweet => 4,
+             },
+             step.name())
+        .unwrap();
+    if already_done {
+        writeln!(output, "Already done, skipping.").unwrap();
+    } else {
+        writeln!(output, "{}", step.explanation()).unwrap();
+    }
+    writeln!(output, "").unwrap();
+}
+
+/// Verify the pipeline works for the specified user by posting a test tweet on its behalf.
+///
+/// If `dry_run` is `true`, only verify the user's credentials instead of posting anything.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{setup, AppTokens, User};
+/// let result = setup::test_tweet(&User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 0x81423,
+///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// }, &AppTokens {
+///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+/// }.into(), true, &mut vec![]);
+///
+/// assert_eq!(result.exit_value(), 0);
+/// ```
+pub fn test_tweet<'a, W: Write>(user: &User, app: &Token<'a>, dry_run: bool, output: &mut W) -> Outcome {
+    if dry_run {
+        let desc = format!("verifying the credentials for {}", user.name);
+        let app = network::owned_token(app);
+        let verified_user = user.clone();
+        match span_r(|| {
+            network::request(&desc.clone(), move || if network::bind_address().is_some() {
+                let mut params = BTreeMap::new();
+                params.insert("skip_status".to_string(), "true".to_string());
+                oauth::get(VERIFY_CREDENTIALS_URL, &params, &app, &verified_user.raw_token()).and_then(|resp| {
+                    resp.find("screen_name")
+                        .and_then(Json::as_string)
+                        .map(str::to_string)
+                        .ok_or_else(|| Outcome::TwitterAPIError(format!("invalid response: {}", resp)))
+                })
+            } else {
+                verify_tokens(&app, &verified_user.raw_token()).map(|resp| resp.response.screen_name).map_err(|e| network::egg_mode_error(&desc, e))
+            })
+        }) {
+            (dur, Ok(screen_name)) => {
+                writeln!(output,
+                         "Credentials for {} verified in {}ms, the daemon will be able to post as @{}.",
+                         user.name,
+                         dur.num_milliseconds(),
+                         screen_name)
+                    .unwrap();
+                Outcome::NoError
+            }
+            (_, Err(out)) => out,
+        }
+    } else {
+        let now = SystemClock.now();
+        let mut tweet = QueuedTweet {
+            author: user.name.clone(),
+            time: now,
+            content: format!("Testing tweetr setup at {}", now.to_rfc3339()),
+            media: vec![],
+            thread: None,
+            in_reply_to: None,
+            extra_params: BTreeMap::new(),
+            variants: BTreeMap::new(),
+            content_warning: None,
+            coordinates: None,
+            place_id: None,
+            reply_settings: None,
+            name: None,
+            after: None,
+            paused: false,
+            blocked: None,
+            settings: Settings::default(),
+            time_posted: None,
+            id: None,
+            app: None,
+            retries: 0,
+        };
+        post_tweet(&mut tweet,
+                   &Twitter {
+                       user: user,
+                       app: app,
+                       uploads: None,
+                   },
+                   &SystemClock,
+                   false,
+                   output)
+    }
+}
@@ -0,0 +1,107 @@
+//! How far along configuring tweetr is, to point at what to do next when a subsystem finds files it needs missing.
+//!
+//! The app configuration, the users and the queue are produced by different subsystems, usually in that order, so a
+//! configuration directory with only some of them is half-way through being set up. Instead of pointing at just the file
+//! the subsystem happened to check first, `verify_file()` reports the state the whole directory is in, along with the
+//! next step to take from there.
+//!
+//! The flow of detecting the state is as follows:
+//!
+//! ```plaintext
+//! ops::setup_state::SetupState::detect()
+//! |> ops::setup_state::SetupState::next_step()
+//! ```
+
+
+use std::path::Path;
+
+
+/// How far along configuring tweetr in a configuration directory is.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SetupState {
+    /// Nothing's configured yet, this is the first run.
+    FirstRun,
+    /// Users were authorised or tweets queued, but the app isn't configured.
+    NoApp,
+    /// The app is configured, but no users were authorised.
+    NoUsers,
+    /// The app is configured and users authorised, but no tweets were queued.
+    NoQueue,
+    /// Everything's configured.
+    Complete,
+}
+
+impl SetupState {
+    /// Check how far along configuring tweetr in the specified directory is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::setup_state::SetupState;
+    /// # use std::fs::{self, File};
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-setup-state-detect-0");
+    /// let _ = fs::remove_dir_all(&tf);
+    /// fs::create_dir_all(&tf).unwrap();
+    /// assert_eq!(SetupState::detect(&tf), SetupState::FirstRun);
+    ///
+    /// File::create(tf.join("app.toml")).unwrap();
+    /// assert_eq!(SetupState::detect(&tf), SetupState::NoUsers);
+    ///
+    /// File::create(tf.join("users.toml")).unwrap();
+    /// assert_eq!(SetupState::detect(&tf), SetupState::NoQueue);
+    ///
+    /// File::create(tf.join("tweets.toml")).unwrap();
+    /// assert_eq!(SetupState::detect(&tf), SetupState::Complete);
+    ///
+    /// fs::remove_file(tf.join("app.toml")).unwrap();
+    /// assert_eq!(SetupState::detect(&tf), SetupState::NoApp);
+    /// ```
+    pub fn detect(config_dir: &Path) -> SetupState {
+        match (config_dir.join("app.toml").exists(), config_dir.join("users.toml").exists(), config_dir.join("tweets.toml").exists()) {
+            (false, false, false) => SetupState::FirstRun,
+            (false, _, _) => SetupState::NoApp,
+            (true, false, _) => SetupState::NoUsers,
+            (true, true, false) => SetupState::NoQueue,
+            (true, true, true) => SetupState::Complete,
+        }
+    }
+
+    /// Get the subsystem to run next, or `None` if everything's configured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::setup_state::SetupState;
+    /// assert_eq!(SetupState::FirstRun.next_step(), Some("setup"));
+    /// assert_eq!(SetupState::NoUsers.next_step(), Some("add-user"));
+    /// assert_eq!(SetupState::Complete.next_step(), None);
+    /// ```
+    pub fn next_step(&self) -> Option<&'static str> {
+        match *self {
+            SetupState::FirstRun => Some("setup"),
+            SetupState::NoApp => Some("init"),
+            SetupState::NoUsers => Some("add-user"),
+            SetupState::NoQueue => Some("queue-tweet"),
+            SetupState::Complete => None,
+        }
+    }
+
+    /// Get the ID of the message explaining this state and what to do next, see `l10n::tr()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::setup_state::SetupState;
+    /// assert_eq!(SetupState::NoQueue.message_id(), "setup_no_queue");
+    /// ```
+    pub fn message_id(&self) -> &'static str {
+        match *self {
+            SetupState::FirstRun => "setup_first_run",
+            SetupState::NoApp => "setup_no_app",
+            SetupState::NoUsers => "setup_no_users",
+            SetupState::NoQueue => "setup_no_queue",
+            SetupState::Complete => "setup_complete",
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! This module contains the functions used only by the `shift-queue` subsystem.
+//!
+//! The flow of the `shift-queue` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::shift_queue::verify()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::shift_queue::shift()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+//!
+//! Only unposted tweets are moved. Tweets scheduled relative to others aren't moved themselves, but along with the tweets
+//! they're scheduled after, whether those match or not. Tweets can't be moved later past the horizon, see `ops::schedule`.
+
+
+use chrono::{DateTime, FixedOffset, Local};
+use self::super::{QueuedTweet, schedule, verify_file};
+use self::super::super::util::format_amount_of_time;
+use self::super::super::Outcome;
+use std::path::PathBuf;
+
+
+/// Which tweets to move.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Filter {
+    /// Only move tweets scheduled at or after this time, if specified.
+    pub from: Option<DateTime<FixedOffset>>,
+    /// Only move tweets by this account, if specified.
+    pub author: Option<String>,
+    /// Only move tweets tagged with this hashtag, with or without the leading `#`, if specified.
+    pub tag: Option<String>,
+}
+
+impl Filter {
+    /// Check whether the specified tweet is to be moved.
+    ///
+    /// A tweet is tagged with a hashtag if it contains it, or its `tags` setting does, case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::shift_queue::Filter;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+    ///     content: "tweetr v1.4.0 is out! #Release".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut filter = Filter {
+    ///     from: Some(DateTime::parse_from_rfc3339("2016-09-10T00:00:00+02:00").unwrap()),
+    ///     author: Some("nabijaczleweli".to_string()),
+    ///     tag: Some("release".to_string()),
+    /// };
+    /// assert!(filter.matches(&tweet));
+    ///
+    /// filter.tag = Some("#launch".to_string());
+    /// assert!(!filter.matches(&tweet));
+    /// # }
+    /// ```
+    pub fn matches(&self, tweet: &QueuedTweet) -> bool {
+        self.from.map(|from| tweet.time >= from).unwrap_or(true) && self.author.as_ref().map(|a| tweet.author == *a).unwrap_or(true) &&
+        self.tag.as_ref().map(|t| tagged(tweet, t)).unwrap_or(true)
+    }
+}
+
+
+/// Verify if specified configuration directory contains the tweet queue.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::{self, File};
+/// # use tweetr::ops::shift_queue;
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-shift-queue-verify-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(shift_queue::verify(&("$TEMP/ops-shift-queue-verify-0".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("tweets.toml")).unwrap();
+/// assert_eq!(shift_queue::verify(&("$TEMP/ops-shift-queue-verify-0".to_string(), tf.clone())),
+///            Ok(tf.join("tweets.toml")));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("tweets.toml", true, config_dir, false, "queue-tweet")
+}
+
+/// Move the unposted tweets matching the specified filter by the specified amount of seconds, earlier if negative, and the
+/// tweets scheduled after them along with them.
+///
+/// Returns how many tweets matched and how many others were moved with them, or why the tweets can't be moved, in which case
+/// none are.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::shift_queue::{self, Filter};
+/// # use tweetr::ops::{QueuedTweet, Anchor};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let launch = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "tweetr v1.4.0 is out!".to_string(),
+///     name: Some("launch".to_string()),
+///     ..Default::default()
+/// };
+/// let follow_up = QueuedTweet {
+///     author: "not-nabijaczleweli".to_string(),
+///     time: launch.time + Duration::hours(2),
+///     content: "In case you missed it: tweetr v1.4.0 is out!".to_string(),
+///     name: None,
+///     after: Some(Anchor {
+///         name: "launch".to_string(),
+///         delay: 2 * 60 * 60,
+///     }),
+///     ..launch.clone()
+/// };
+///
+/// let mut tweets = vec![launch.clone(), follow_up.clone()];
+/// let filter = Filter {
+///     from: None,
+///     author: Some("nabijaczleweli".to_string()),
+///     tag: None,
+/// };
+/// assert_eq!(shift_queue::shift(&mut tweets, &filter, 24 * 60 * 60), Ok((1, 1)));
+/// assert_eq!(tweets[0].time, launch.time + Duration::days(1));
+/// assert_eq!(tweets[1].time, follow_up.time + Duration::days(1));
+///
+/// assert_eq!(shift_queue::shift(&mut tweets, &filter, 20000000 * 7 * 24 * 60 * 60),
+///            Err(vec!["moving \"tweetr v1.4.0 is out!\" 20000000 weeks later is too late".to_string()]));
+/// assert_eq!(tweets[0].time, launch.time + Duration::days(1));
+/// # }
+/// ```
+pub fn shift(tweets: &mut [QueuedTweet], filter: &Filter, by: i64) -> Result<(usize, usize), Vec<String>> {
+    let (direction, limit) = if by < 0 { ("earlier", "early") } else { ("later", "late") };
+    let by_desc = format_amount_of_time(if by < 0 { (by as u64).wrapping_neg() } else { by as u64 });
+
+    let mut shifted = tweets.to_vec();
+    let mut matched = vec![false; tweets.len()];
+    let mut errors = vec![];
+    for (i, tweet) in shifted.iter_mut().enumerate() {
+        if tweet.id.is_some() || tweet.after.is_some() || !filter.matches(tweet) {
+            continue;
+        }
+
+        match schedule::shifted(tweet.time, by) {
+            Ok(time) => {
+                tweet.time = time;
+                matched[i] = true;
+            }
+            Err(_) => errors.push(format!("moving \"{}\" {} {} is too {}", tweet.content, by_desc, direction, limit)),
+        }
+    }
+
+    for (i, _, e) in QueuedTweet::schedule(&mut shifted, &[]) {
+        errors.push(format!("\"{}\": {}", shifted[i].content, e));
+    }
+
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    for (old, new) in tweets.iter().zip(&shifted).filter(|&(old, new)| new.time > old.time) {
+        if let Err(e) = schedule::within_horizon(new.time, now) {
+            errors.push(format!("moving \"{}\" {} {} puts it {}", old.content, by_desc, direction, e));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let moved = tweets.iter().zip(&shifted).enumerate().filter(|&(i, (old, new))| !matched[i] && old.time != new.time).count();
+    tweets.clone_from_slice(&shifted);
+    Ok((matched.iter().filter(|&&m| m).count(), moved))
+}
+
+
+fn tagged(tweet: &QueuedTweet, tag: &str) -> bool {
+    let tag = format!("#{}", tag.trim_left_matches('#')).to_lowercase();
+
+    tweet.content.split_whitespace().any(|w| w.trim_right_matches(|c: char| !c.is_alphanumeric() && c != '_').to_lowercase() == tag) ||
+    tweet.settings
+        .tags
+        .as_ref()
+        .map(|tags| tags.iter().any(|t| format!("#{}", t.trim_left_matches('#')).to_lowercase() == tag))
+        .unwrap_or(false)
+}
@@ -0,0 +1,154 @@
+//! This module contains the functions used only by the `show-effective-config` subsystem.
+//!
+//! The flow of the `show-effective-config` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::show_effective_config::verify()
+//! |> ops::User::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::settings::Effective::resolve(), for each account and for each of its unposted tweets
+//! |> ops::show_effective_config::print_account()
+//! |> ops::show_effective_config::print_tweet(), for each unposted tweet
+//! ```
+
+
+use self::super::settings::{Effective, Source};
+use self::super::super::Outcome;
+use self::super::{QueuedTweet, verify_file};
+use self::super::l10n::format_datetime;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// Verify if specified configuration directory contains the users file.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::{self, File};
+/// # use tweetr::ops::show_effective_config;
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-show-effective-config-verify-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(show_effective_config::verify(&("$TEMP/ops-show-effective-config-verify-0".to_string(), tf.clone())),
+///            Err(Outcome::SetupIncomplete(SetupState::FirstRun)));
+///
+/// File::create(tf.join("users.toml")).unwrap();
+/// assert_eq!(show_effective_config::verify(&("$TEMP/ops-show-effective-config-verify-0".to_string(), tf.clone())),
+///            Ok(tf.join("users.toml")));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Print the settings the specified account's tweets inherit, each with where it comes from.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::settings::{Effective, Settings};
+/// # use tweetr::ops::show_effective_config;
+/// # use std::iter::FromIterator;
+/// let effective = Effective::resolve(None,
+///                                    Some(&Settings { tags: Some(vec!["tweetr".to_string()]), ..Settings::default() }),
+///                                    &Settings { quiet_hours: Some("23:00-07:00".to_string()), ..Settings::default() })
+///     .unwrap();
+///
+/// let mut out = Vec::new();
+/// show_effective_config::print_account(&mut out, "nabijaczleweli", &effective);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "nabijaczleweli:\n  \
+///               timezone    = local time   (default)\n  \
+///               quiet_hours = 23:00-07:00  (configuration)\n  \
+///               jitter      = 0 minutes    (default)\n  \
+///               tags        = #tweetr      (account)\n  \
+///               sensitive   = false        (default)\n".to_string());
+/// ```
+pub fn print_account<W: Write>(output: &mut W, name: &str, effective: &Effective) {
+    writeln!(output, "{}:", name).unwrap();
+
+    let values = values(effective);
+    let width = values.iter().map(|&(_, ref v, _)| v.len()).max().unwrap_or(0);
+    for (key, value, source) in values {
+        writeln!(output, "  {:11} = {:w$}  ({})", key, value, source.name(), w = width).unwrap();
+    }
+}
+
+/// Print when the specified tweet is going to be posted, and the settings it overrides.
+///
+/// The posting time is only printed if it's different from the scheduled time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::settings::{Effective, Settings};
+/// # use tweetr::ops::{QueuedTweet, show_effective_config};
+/// # use std::iter::FromIterator;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T23:30:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     settings: Settings {
+///         timezone: Some("+02:00".to_string()),
+///         quiet_hours: Some("23:00-07:00".to_string()),
+///         ..Settings::default()
+///     },
+///     ..Default::default()
+/// };
+/// let effective = Effective::resolve(Some(&tweet.settings), None, &Settings::default()).unwrap();
+///
+/// let mut out = Vec::new();
+/// show_effective_config::print_tweet(&mut out, &tweet, &effective);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "  \"Abolish the bourgeoisie\" scheduled for 2016-09-10T23:30:00+02:00, posted at 2016-09-11T07:00:00+02:00\n    \
+///                 timezone    = +02:00\n    \
+///                 quiet_hours = 23:00-07:00\n".to_string());
+/// # }
+/// ```
+pub fn print_tweet<W: Write>(output: &mut W, tweet: &QueuedTweet, effective: &Effective) {
+    let posting_time = effective.posting_time(tweet);
+    write!(output,
+           "  \"{}\" scheduled for {}",
+           tweet.content.replace('\n', " "),
+           format_datetime(&tweet.time))
+        .unwrap();
+    if posting_time != tweet.time {
+        write!(output, ", posted at {}", format_datetime(&posting_time)).unwrap();
+    }
+    writeln!(output, "").unwrap();
+
+    for (key, value, _) in values(effective).into_iter().filter(|&(_, _, source)| source == Source::Tweet) {
+        writeln!(output, "    {:11} = {}", key, value).unwrap();
+    }
+}
+
+
+fn values(effective: &Effective) -> Vec<(&'static str, String, Source)> {
+    vec![("timezone",
+          effective.timezone.0.map(|tz| tz.to_string()).unwrap_or_else(|| "local time".to_string()),
+          effective.timezone.1),
+         ("quiet_hours",
+          effective.quiet_hours
+              .0
+              .map(|qh| format!("{}-{}", qh.start.format("%H:%M"), qh.end.format("%H:%M")))
+              .unwrap_or_else(|| "none".to_string()),
+          effective.quiet_hours.1),
+         ("jitter", format!("{} minutes", effective.jitter.0), effective.jitter.1),
+         ("tags",
+          if effective.tags.0.is_empty() {
+              "none".to_string()
+          } else {
+              effective.tags.0.join(" ")
+          },
+          effective.tags.1),
+         ("sensitive", effective.sensitive.0.to_string(), effective.sensitive.1)]
+}
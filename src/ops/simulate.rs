@@ -0,0 +1,275 @@
+//! This module contains the functions used only by the `simulate` subsystem.
+//!
+//! The daemon's scheduling is run over a copy of the queue with a virtual clock, jumping straight to the next time anything
+//! can happen, so the timeline the queue and configuration result in can be checked without waiting for it or posting
+//! anything. Jitter is derived from the tweets themselves (see `ops::settings::Effective::posting_time()`), so the
+//! simulated times are the ones the daemon would post at.
+//!
+//! Nothing is written, posted or run: hooks, transforms and the evergreen pool aren't simulated.
+//!
+//! The flow of the `simulate` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::User::read()
+//! |> ops::QueuedTweet::read()
+//! |> ops::archive::read_since()
+//! |> ops::simulate::run()
+//! |> ops::simulate::print_timeline()
+//! ```
+//!
+//! Then, at each point in time, as by the `start-daemon` subsystem:
+//!
+//! ```plaintext
+//! ops::start_daemon::tweet_indices_to_post()
+//! |> ops::settings::Effective::posting_time()
+//! |> ops::embargo::lifted()
+//! |> ops::daily_cap::posted_on()
+//! |> ops::daily_cap::carry_over(), if the cap's reached
+//! |> ops::start_daemon::anchor_posted()
+//! |> ops::start_daemon::thread_predecessor()
+//! |> ops::settings::Effective::apply()
+//! |> ops::embargo::reschedule()
+//! |> ops::QueuedTweet::schedule()
+//! |> ops::start_daemon::next_wake()
+//! ```
+
+
+use self::super::super::util::table::{Column, Table};
+use self::super::start_daemon::{anchor_posted, next_wake, thread_predecessor, tweet_indices_to_post};
+use self::super::settings::{Effective, Settings};
+use self::super::embargo::{self, Policy, Window};
+use self::super::clock::{Clock, ManualClock};
+use self::super::l10n::format_datetime;
+use self::super::suggest_time::Slot;
+use self::super::{QueuedTweet, User, daily_cap};
+use self::super::super::Outcome;
+use chrono::{DateTime, Duration, FixedOffset, Local};
+use std::io::Write;
+use std::cmp;
+
+
+/// What the daemon would do with the configuration, besides the queue.
+#[derive(Debug, Clone)]
+pub struct Rules<'a> {
+    /// The users, for their settings and daily caps.
+    pub users: &'a [User],
+    /// The global settings, see `Config::settings()`.
+    pub settings: &'a Settings,
+    /// The embargoes, see `Config::embargoes()`.
+    pub embargoes: &'a [Window],
+    /// Where to reschedule embargoed tweets to.
+    pub embargo_policy: Policy,
+    /// How far apart to spread embargoed tweets, see `Config::collision_window()`.
+    pub collision_window: Duration,
+    /// The posting slots, for rescheduling embargoed tweets with `Policy::Slots`.
+    pub slots: &'a [Slot],
+}
+
+/// What happened to a tweet in the simulation.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Step {
+    /// It was posted.
+    Posted,
+    /// Its author's daily cap was reached, so it was carried over to the specified time.
+    CarriedOver(DateTime<FixedOffset>),
+    /// It came due during an embargo, so it was rescheduled to the specified time.
+    Embargoed(DateTime<FixedOffset>),
+}
+
+/// A point in the simulated timeline.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Entry {
+    /// When it happened.
+    pub time: DateTime<FixedOffset>,
+    /// The tweet's author.
+    pub author: String,
+    /// The tweet's content, as it'd be posted.
+    pub content: String,
+    /// What happened.
+    pub step: Step,
+}
+
+
+/// Run the daemon's scheduling over the specified queue from and to the specified times, with the specified tweets posted
+/// before, for the daily caps.
+///
+/// The tweets are updated as the daemon would update them, and the timeline of what happened to them is returned, in order.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::simulate::{self, Rules, Step};
+/// # use tweetr::ops::embargo::Policy;
+/// # use tweetr::ops::settings::Settings;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: time("2016-09-10T23:30:00+02:00"),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let settings = Settings {
+///     timezone: Some("+02:00".to_string()),
+///     quiet_hours: Some("23:00-07:00".to_string()),
+///     ..Settings::default()
+/// };
+/// let rules = Rules {
+///     users: &[],
+///     settings: &settings,
+///     embargoes: &[],
+///     embargo_policy: Policy::End,
+///     collision_window: Duration::minutes(10),
+///     slots: &[],
+/// };
+///
+/// let mut tweets = vec![tweet.clone()];
+/// let timeline = simulate::run(&mut tweets, &[], &rules, time("2016-09-10T12:00:00+02:00"), time("2016-09-12T00:00:00+02:00")).unwrap();
+/// assert_eq!(timeline.len(), 1);
+/// assert_eq!(timeline[0].time, time("2016-09-11T07:00:00+02:00"));
+/// assert_eq!(timeline[0].step, Step::Posted);
+/// assert_eq!(tweets[0].time_posted, Some(timeline[0].time));
+///
+/// let mut tweets = vec![tweet.clone()];
+/// assert_eq!(simulate::run(&mut tweets, &[], &rules, time("2016-09-10T12:00:00+02:00"), time("2016-09-11T06:00:00+02:00")),
+///            Ok(vec![]));
+/// # }
+/// ```
+pub fn run(tweets: &mut Vec<QueuedTweet>, history: &[QueuedTweet], rules: &Rules, from: DateTime<FixedOffset>, to: DateTime<FixedOffset>)
+           -> Result<Vec<Entry>, Outcome> {
+    let clock = ManualClock::new(from);
+    let mut timeline = vec![];
+    while clock.now() <= to {
+        let now = clock.now();
+        let mut due = tweet_indices_to_post(tweets, &clock);
+        due.sort_by_key(|&i| tweets[i].time);
+        let embargo_lifted = embargo::lifted(rules.embargoes, now);
+        let mut deferred = vec![];
+        let mut embargoed = vec![];
+        let mut posted = false;
+
+        for i in due {
+            let user = rules.users.iter().find(|u| u.name == tweets[i].author);
+            let effective = try!(Effective::resolve(Some(&tweets[i].settings), user.map(|u| u.settings()).as_ref(), rules.settings));
+            let posting_time = effective.posting_time(&tweets[i]);
+            if posting_time > now {
+                deferred.push(posting_time);
+                continue;
+            }
+            if embargo_lifted.is_some() {
+                embargoed.push(i);
+                continue;
+            }
+            if let Some(max) = user.and_then(|u| u.max_posts_per_day) {
+                let tz = effective.timezone.0.unwrap_or_else(|| *now.with_timezone(&Local).offset());
+                if daily_cap::posted_on(tweets, &tweets[i].author, now, tz) + daily_cap::posted_on(history, &tweets[i].author, now, tz) >=
+                   max as usize {
+                    daily_cap::carry_over(&mut tweets[i], now, tz);
+                    timeline.push(Entry::new(now, &tweets[i], Step::CarriedOver(tweets[i].time)));
+                    continue;
+                }
+            }
+            if user.map(|u| u.suspended.is_some()).unwrap_or(false) || !anchor_posted(tweets, i) || thread_predecessor(tweets, i).is_err() {
+                continue;
+            }
+
+            tweets[i] = effective.apply(&tweets[i]);
+            tweets[i].time_posted = Some(now);
+            tweets[i].id = Some(0);
+            timeline.push(Entry::new(now, &tweets[i], Step::Posted));
+            posted = true;
+        }
+
+        if let Some(lifted) = embargo_lifted {
+            embargo::reschedule(tweets, &embargoed, rules.embargoes, lifted, rules.embargo_policy, rules.collision_window, rules.slots);
+            for &i in &embargoed {
+                timeline.push(Entry::new(now, &tweets[i], Step::Embargoed(tweets[i].time)));
+            }
+        }
+        // The tweets waiting for the ones just posted can go now, scheduled relative to when they were
+        if posted {
+            QueuedTweet::schedule(tweets, &[]);
+            continue;
+        }
+
+        clock.set(deferred.into_iter().fold(next_wake(tweets, &clock, to - now + Duration::seconds(1)), cmp::min));
+    }
+    Ok(timeline)
+}
+
+/// Print the specified timeline, simulated from and to the specified times, fitting it in the specified width, if any.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::simulate::{self, Entry, Step};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let time = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+/// let timeline = [Entry {
+///                     time: time("2016-09-10T12:00:00+02:00"),
+///                     author: "nabijaczleweli".to_string(),
+///                     content: "Abolish the bourgeoisie".to_string(),
+///                     step: Step::Posted,
+///                 },
+///                 Entry {
+///                     time: time("2016-09-10T12:30:00+02:00"),
+///                     author: "nabijaczleweli".to_string(),
+///                     content: "Abolish the bourgeoisie".to_string(),
+///                     step: Step::CarriedOver(time("2016-09-11T12:30:00+02:00")),
+///                 }];
+///
+/// let mut out = Vec::new();
+/// simulate::print_timeline(&mut out, &timeline, time("2016-09-10T00:00:00+02:00"), time("2016-09-11T00:00:00+02:00"), Some(100));
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "1 tweet would be posted between 2016-09-10T00:00:00+02:00 and 2016-09-11T00:00:00+02:00:\n\
+///             Time                       Author          Event                                                  Content\n\
+///             2016-09-10T12:00:00+02:00  nabijaczleweli  posted                                                 Abolis…\n\
+///             2016-09-10T12:30:00+02:00  nabijaczleweli  daily cap reached, moved to 2016-09-11T12:30:00+02:00  Abolis…\n");
+/// # }
+/// ```
+pub fn print_timeline<W: Write>(output: &mut W, timeline: &[Entry], from: DateTime<FixedOffset>, to: DateTime<FixedOffset>,
+                                max_width: Option<usize>) {
+    let posted = timeline.iter().filter(|e| e.step == Step::Posted).count();
+    writeln!(output,
+             "{} tweet{} would be posted between {} and {}:",
+             posted,
+             if posted == 1 { "" } else { "s" },
+             format_datetime(&from),
+             format_datetime(&to))
+        .unwrap();
+
+    let mut table = Table::new(vec![Column::new("Time"), Column::new("Author"), Column::new("Event"), Column::new("Content").shrinkable()]);
+    table.max_width = max_width;
+    for entry in timeline {
+        table.row(vec![format_datetime(&entry.time),
+                       entry.author.clone(),
+                       match entry.step {
+                           Step::Posted => "posted".to_string(),
+                           Step::CarriedOver(time) => format!("daily cap reached, moved to {}", format_datetime(&time)),
+                           Step::Embargoed(time) => format!("embargoed, moved to {}", format_datetime(&time)),
+                       },
+                       entry.content.clone()]);
+    }
+    table.write(output);
+}
+
+
+impl Entry {
+    fn new(time: DateTime<FixedOffset>, tweet: &QueuedTweet, step: Step) -> Entry {
+        Entry {
+            time: time,
+            author: tweet.author.clone(),
+            content: tweet.content.clone(),
+            step: step,
+        }
+    }
+}
@@ -7,29 +7,136 @@
 //! ```plaintext
 //! Options::parse()
 //! |> ops::start_daemon::verify()
-//! |> ops::AppTokens::read()
+//! |> ops::Apps::read()
+//! |> ops::Config::read()
+//! |> ops::preflight::run()
+//! |> ops::backend::sinks()
+//! |> ops::transform::pipeline()
+//! |> ops::start_daemon::print_summary(), unless quiet
 //! ```
 //!
 //! Then, in a loop:
 //!
 //! ```plaintext
 //! init_data
-//! |> ops::User::read()
-//! |> ops::QueuedTweet::read()
-//! |> ops::start_daemon::tweet_indices_to_post()
-//! |> ops::start_daemon::find_user_index_for_tweet()
+//! |> ops::start_daemon::Cached::get(), with ops::User::read()
+//! |> ops::start_daemon::Cached::get(), with ops::QueuedTweet::read()
+//! |> ops::events::Tracker::queued()
+//! |> ops::start_daemon::Due::due()
+//! |> ops::events::Tracker::due()
+//! |> ops::start_daemon::anchor_posted()
+//! |> ops::start_daemon::thread_predecessor()
+//! |> ops::settings::Effective::apply()
+//! |> ops::transform::Pipeline::apply()
+//! |> ops::start_daemon::fold_content_warning()
+//! |> ops::start_daemon::find_user_index_for_tweet() or ops::Sink
 //! |> ops::start_daemon::post_tweet()
+//! |> ops::start_daemon::mirror_tweet()
+//! |> ops::start_daemon::suspend(), if the account's suspended or locked
+//! |> ops::QueuedTweet::write(), if changed
+//! |> ops::start_daemon::Cached::written()
+//! |> ops::start_daemon::Due::next_wake()
+//! |> ops::events::EventLog::emit(), for ops::events::Event::Sleeping
+//! |> ops::clock::sleep_until()
 //! ```
+//!
+//! The users and queue are only re-read when their files change, and the `Due` tweets only re-sorted when the queue does,
+//! so each wakeup with nothing to post is only `stat()`ing the files and peeking at the earliest tweet.
+//!
+//! Tweets by accounts found suspended or locked (see `ops::events::suspended()`) aren't posted until the `suspended` key is
+//! removed from the account in the users file, instead of failing over and over.
 
 
-use self::super::super::util::{TWEET_DATETIME_FORMAT, span_r};
 use self::super::{QueuedTweet, User, verify_file};
+use self::super::backend::{self, PostingBackend, Sink};
+use self::super::l10n::{format_datetime, tr};
+use self::super::clock::Clock;
+use self::super::super::util::span_r;
 use self::super::super::Outcome;
-use egg_mode::tweet::DraftTweet;
-use chrono::{DateTime, Local};
-use std::path::PathBuf;
-use egg_mode::Token;
-use std::io::Write;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BinaryHeap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::cmp::{self, Ordering};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+use std::process;
+use std::time::SystemTime;
+
+
+/// What tweets by users in test mode are prefixed with, unless posted as another user.
+pub static TEST_PREFIX: &'static str = "[TEST] ";
+
+
+/// How to post a user's tweets, see `test_mode()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum TestMode {
+    /// Post them as the user.
+    Off,
+    /// Post them as the user at the specified index instead.
+    Reroute(usize),
+    /// Post them as the user, marked as tests with `TEST_PREFIX`.
+    Mark,
+}
+
+/// What the daemon is starting with, see `print_summary()`.
+#[derive(Debug, Clone)]
+pub struct Summary<'a> {
+    /// The configuration directory, as specified.
+    pub config_dir: &'a str,
+    /// The directory the hooks are run from.
+    pub hooks_dir: &'a Path,
+    /// The file the events are appended to, if any.
+    pub events: Option<&'a Path>,
+    /// The authenticated users, if they could be read.
+    pub users: Option<&'a [User]>,
+    /// The configured sinks.
+    pub sinks: &'a [Sink],
+    /// The queued tweets, if they could be read.
+    pub tweets: Option<&'a [QueuedTweet]>,
+    /// The longest to wait between trying to post again.
+    pub delay: Duration,
+}
+
+/// A file's contents, as read by a function like `User::read()`, only read again when the file changes.
+///
+/// Changes are detected by the file's modification time and size, which only need a `stat()`, and then confirmed by hashing
+/// the file, which is still much cheaper than parsing it, so files touched without changing aren't parsed again. Changes
+/// keeping the size made within the modification time's granularity of the last read go unnoticed until the next change.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+    hash: Option<u64>,
+    value: Option<T>,
+}
+
+/// The claim on the configuration directory of the process posting its tweets, released when dropped.
+///
+/// While it's held, `daemon.pid` in the configuration directory contains the process's ID, so the daemon and `post-all`
+/// don't post the same tweets at the same time.
+#[derive(Debug)]
+pub struct DaemonLock {
+    path: PathBuf,
+}
+
+/// The waiting tweets, neither posted nor paused, ordered by the time they're scheduled for.
+///
+/// Getting the due tweets and the next wake is then logarithmic in the size of the queue, instead of linear, see
+/// `tweet_indices_to_post()` and `next_wake()`, which they're equivalent to, as long as the queue doesn't change.
+#[derive(Debug, Clone)]
+pub struct Due {
+    heap: BinaryHeap<Waiting>,
+    overdue: Vec<Waiting>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Waiting {
+    time: DateTime<FixedOffset>,
+    index: usize,
+}
 
 
 /// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `start-daemon`
@@ -65,6 +172,7 @@ use std::io::Write;
 /// # use std::fs::{self, File};
 /// # use tweetr::ops::start_daemon;
 /// # use std::env::temp_dir;
+/// # use tweetr::ops::setup_state::SetupState;
 /// # use tweetr::Outcome;
 /// # use std::io::Write;
 /// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-verify-1");
@@ -73,10 +181,7 @@ use std::io::Write;
 /// File::create(tf.join("tweets.toml")).unwrap().write(&[]).unwrap();
 ///
 /// assert_eq!(start_daemon::verify(&("$TEMP/ops-start-daemon-verify-1".to_string(), tf)),
-///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
-///                subsys: "add-user",
-///                fname: "$TEMP/ops-start-daemon-verify-1/users.toml".to_string(),
-///            }));
+///            Err(Outcome::SetupIncomplete(SetupState::NoUsers)));
 /// ```
 pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf, PathBuf), Outcome> {
     let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
@@ -86,8 +191,174 @@ pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf, PathB
     Ok((app, users, tweets))
 }
 
-/// Get the indices of tweets to post now from the provided batch based on whether thy've been posted already and the current
-/// time.
+/// Print what the daemon is starting with: where its files are, the accounts and sinks it posts to, what's queued, and how
+/// often it checks for tweets to post.
+///
+/// Secrets aren't printed: access tokens are redacted with `redact()`, and bot tokens, Discord webhooks and sinks' commands
+/// are left out, so the summary can be pasted into bug reports as-is.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon::{self, Summary};
+/// # use tweetr::ops::{QueuedTweet, User};
+/// # use tweetr::ops::backend::Sink;
+/// # use chrono::{DateTime, Duration};
+/// # use std::path::Path;
+/// # fn main() {
+/// let users = vec![User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: Some("agency".to_string()),
+///     telegram_bot_token: Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string()),
+///     telegram_chat_id: Some("@tweetr_mirror".to_string()),
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// }];
+/// let sinks = vec![Sink {
+///     name: "irc".to_string(),
+///     file: None,
+///     command: Some("ii-announce '#tweetr'".to_string()),
+///     discord_webhook: None,
+/// }];
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+/// let tweets = vec![QueuedTweet { paused: true, ..tweet.clone() }, tweet];
+///
+/// let mut out = Vec::new();
+/// start_daemon::print_summary(&mut out, &Summary {
+///     config_dir: "$HOME/.tweetr",
+///     hooks_dir: Path::new("/home/nab/.tweetr/hooks"),
+///     events: None,
+///     users: Some(&users),
+///     sinks: &sinks,
+///     tweets: Some(&tweets),
+///     delay: Duration::seconds(60),
+/// });
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Starting the daemon with:\n  \
+///               Configuration directory: $HOME/.tweetr\n  \
+///               Hooks directory: /home/nab/.tweetr/hooks\n  \
+///               Events: not logged\n  \
+///               1 account:\n    \
+///                 nabijaczleweli#481, access token ****ESD4, via app agency, mirrored to Telegram chat @tweetr_mirror\n  \
+///               1 sink:\n    \
+///                 irc, to a command\n  \
+///               2 tweets queued, 1 to post, the next scheduled for 2016-09-10T12:00:00+02:00\n  \
+///               Checking for tweets to post every 60000ms, or when the next one is due\n");
+/// # }
+/// ```
+pub fn print_summary<W: Write>(output: &mut W, summary: &Summary) {
+    writeln!(output, "Starting the daemon with:").unwrap();
+    writeln!(output, "  Configuration directory: {}", summary.config_dir).unwrap();
+    writeln!(output, "  Hooks directory: {}", summary.hooks_dir.display()).unwrap();
+    match summary.events {
+        Some(events) => writeln!(output, "  Events: appended to {}", events.display()).unwrap(),
+        None => writeln!(output, "  Events: not logged").unwrap(),
+    }
+
+    match summary.users {
+        Some(users) => {
+            writeln!(output,
+                     "  {} account{}{}",
+                     users.len(),
+                     if users.len() == 1 { "" } else { "s" },
+                     if users.is_empty() { "" } else { ":" })
+                .unwrap();
+            for user in users {
+                write!(output, "    {}#{}, access token {}", user.name, user.id, redact(&user.access_token_key)).unwrap();
+                if let Some(ref app) = user.app {
+                    write!(output, ", via app {}", app).unwrap();
+                }
+                let mirrors: Vec<_> = user.telegram().map(|t| t.description()).into_iter().chain(user.discord().map(|d| d.description())).collect();
+                if !mirrors.is_empty() {
+                    write!(output, ", mirrored to {}", mirrors.join(" and ")).unwrap();
+                }
+                if user.test_mode.unwrap_or(false) {
+                    write!(output, ", in test mode").unwrap();
+                }
+                if let Some(ref suspended) = user.suspended {
+                    write!(output,
+                           ", paused since {}, suspended or locked",
+                           DateTime::parse_from_rfc3339(suspended).map(|s| format_datetime(&s)).unwrap_or_else(|_| suspended.clone()))
+                        .unwrap();
+                }
+                writeln!(output, "").unwrap();
+            }
+        }
+        None => writeln!(output, "  Accounts: couldn't be read").unwrap(),
+    }
+
+    writeln!(output,
+             "  {} sink{}{}",
+             summary.sinks.len(),
+             if summary.sinks.len() == 1 { "" } else { "s" },
+             if summary.sinks.is_empty() { "" } else { ":" })
+        .unwrap();
+    for sink in summary.sinks {
+        match (&sink.file, &sink.command) {
+            (&Some(ref file), _) => writeln!(output, "    {}, to {}", sink.name, file).unwrap(),
+            (_, &Some(_)) => writeln!(output, "    {}, to a command", sink.name).unwrap(),
+            _ => writeln!(output, "    {}, to a Discord webhook", sink.name).unwrap(),
+        }
+    }
+
+    match summary.tweets {
+        Some(tweets) => {
+            let pending: Vec<_> = tweets.iter().filter(|t| t.id.is_none() && !t.paused && t.blocked.is_none()).collect();
+            write!(output, "  {} tweet{} queued, {} to post", tweets.len(), if tweets.len() == 1 { "" } else { "s" }, pending.len()).unwrap();
+            match pending.iter().map(|t| t.time).min() {
+                Some(next) => writeln!(output, ", the next scheduled for {}", format_datetime(&next)).unwrap(),
+                None => writeln!(output, "").unwrap(),
+            }
+        }
+        None => writeln!(output, "  Queue: couldn't be read").unwrap(),
+    }
+
+    writeln!(output,
+             "  Checking for tweets to post every {}ms, or when the next one is due",
+             summary.delay.num_milliseconds())
+        .unwrap();
+}
+
+/// Redact the specified secret, leaving only its last four characters, if it's long enough for that not to give it away.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::start_daemon;
+/// assert_eq!(start_daemon::redact("481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4"), "****ESD4");
+/// assert_eq!(start_daemon::redact("hunter2"), "****");
+/// ```
+pub fn redact(secret: &str) -> String {
+    let len = secret.chars().count();
+    if len < 16 {
+        "****".to_string()
+    } else {
+        format!("****{}", secret.chars().skip(len - 4).collect::<String>())
+    }
+}
+
+/// Get the indices of tweets to post now from the provided batch based on whether thy've been posted already, whether they're
+/// paused and the current time.
 ///
 /// All returned indices are guaranteed to be valid.
 ///
@@ -96,26 +367,24 @@ pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf, PathB
 /// ```
 /// # extern crate tweetr;
 /// # extern crate chrono;
+/// # use tweetr::ops::clock::{Clock, SystemClock};
 /// # use tweetr::ops::{QueuedTweet, start_daemon};
-/// # use chrono::{Duration, Local};
+/// # use chrono::Duration;
 /// # fn main() {
-/// let now = Local::now();
-/// let now = now.with_timezone(now.offset());
+/// let now = SystemClock.now();
 ///
 /// assert_eq!(start_daemon::tweet_indices_to_post(&vec![
 ///     QueuedTweet {
 ///         author: "nabijaczleweli".to_string(),
 ///         time: now + Duration::hours(1),
 ///         content: "This tweet is not going to be posted (it's too early)".to_string(),
-///         time_posted: None,
-///         id: None,
+///         ..Default::default()
 ///     },
 ///     QueuedTweet {
 ///         author: "nabijaczleweli".to_string(),
 ///         time: now - Duration::hours(1),
 ///         content: "This tweet is going to be posted".to_string(),
-///         time_posted: None,
-///         id: None,
+///         ..Default::default()
 ///     },
 ///     QueuedTweet {
 ///         author: "nabijaczleweli".to_string(),
@@ -123,17 +392,24 @@ pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf, PathB
 ///         content: "This tweet is not going to be posted (it already was)".to_string(),
 ///         time_posted: Some(now - Duration::minutes(30)),
 ///         id: Some(6908265),
+///         ..Default::default()
+///     },
+///     QueuedTweet {
+///         author: "nabijaczleweli".to_string(),
+///         time: now - Duration::hours(1),
+///         content: "This tweet is not going to be posted (it's paused)".to_string(),
+///         paused: true,
+///         ..Default::default()
 ///     },
-/// ]), vec![1]);
+/// ], &SystemClock), vec![1]);
 /// # }
 /// ```
-pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
-    let now = Local::now();
-    let now = now.with_timezone(now.offset());
+pub fn tweet_indices_to_post<C: Clock + ?Sized>(tweets: &Vec<QueuedTweet>, clock: &C) -> Vec<usize> {
+    let now = clock.now();
 
     tweets.iter()
         .enumerate()
-        .flat_map(|(i, ref t)| if t.id.is_none() && t.time <= now {
+        .flat_map(|(i, ref t)| if t.id.is_none() && !t.paused && t.blocked.is_none() && t.time <= now {
             Some(i)
         } else {
             None
@@ -141,6 +417,187 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
         .collect()
 }
 
+/// Get the time the daemon should next check the queue at: the time the earliest tweet that isn't due yet is scheduled for,
+/// but no later than the specified delay from now.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::clock::{Clock, ManualClock};
+/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let clock = ManualClock::new(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:30.500+02:00").unwrap(),
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(start_daemon::next_wake(&[tweet.clone()], &clock, Duration::minutes(1)), tweet.time);
+/// assert_eq!(start_daemon::next_wake(&[tweet.clone()], &clock, Duration::seconds(10)), clock.now() + Duration::seconds(10));
+/// # }
+/// ```
+pub fn next_wake<C: Clock + ?Sized>(tweets: &[QueuedTweet], clock: &C, max_delay: Duration) -> DateTime<FixedOffset> {
+    let now = clock.now();
+
+    tweets.iter()
+        .filter(|t| t.id.is_none() && !t.paused && t.blocked.is_none() && t.time > now)
+        .map(|t| t.time)
+        .fold(now + max_delay, cmp::min)
+}
+
+/// Check whether the tweet the tweet at the specified index is scheduled relative to, if any, was posted.
+///
+/// Tweets scheduled relative to others wait for them, so they aren't posted first when the others are delayed, by quiet hours
+/// for example.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{Anchor, QueuedTweet, start_daemon};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let launch = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: now - Duration::hours(1),
+///     content: "tweetr v0.3.0 is out!".to_string(),
+///     name: Some("launch".to_string()),
+///     ..Default::default()
+/// };
+/// let mut tweets = vec![launch.clone(),
+///                       QueuedTweet {
+///                           name: None,
+///                           after: Some(Anchor {
+///                               name: "launch".to_string(),
+///                               delay: 0,
+///                           }),
+///                           ..launch.clone()
+///                       }];
+///
+/// assert!(start_daemon::anchor_posted(&tweets, 0));
+/// assert!(!start_daemon::anchor_posted(&tweets, 1));
+///
+/// tweets[0].time_posted = Some(now);
+/// tweets[0].id = Some(6908265);
+/// assert!(start_daemon::anchor_posted(&tweets, 1));
+/// # }
+/// ```
+pub fn anchor_posted(tweets: &[QueuedTweet], idx: usize) -> bool {
+    match tweets[idx].after {
+        Some(ref anchor) => tweets.iter().find(|t| t.name.as_ref() == Some(&anchor.name)).map(|t| t.id.is_some()).unwrap_or(true),
+        None => true,
+    }
+}
+
+/// Find the ID of the tweet the tweet at the specified index should reply to as part of its thread.
+///
+/// Returns `Ok(None)` if the tweet isn't in a thread or is the first one in it, `Ok(Some(id))` if the previous tweet in the
+/// thread was already posted, and `Err(index)` of the previous tweet in the thread if it wasn't posted yet.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let mut tweets = vec![
+///     QueuedTweet {
+///         author: "nabijaczleweli".to_string(),
+///         time: now - Duration::hours(1),
+///         content: "1/2 Abolish".to_string(),
+///         thread: Some("manifesto".to_string()),
+///         ..Default::default()
+///     },
+///     QueuedTweet {
+///         author: "nabijaczleweli".to_string(),
+///         time: now - Duration::hours(1),
+///         content: "2/2 the bourgeoisie".to_string(),
+///         thread: Some("manifesto".to_string()),
+///         ..Default::default()
+///     },
+/// ];
+///
+/// assert_eq!(start_daemon::thread_predecessor(&tweets, 0), Ok(None));
+/// assert_eq!(start_daemon::thread_predecessor(&tweets, 1), Err(0));
+///
+/// tweets[0].time_posted = Some(now);
+/// tweets[0].id = Some(6908265);
+/// assert_eq!(start_daemon::thread_predecessor(&tweets, 1), Ok(Some(6908265)));
+/// # }
+/// ```
+pub fn thread_predecessor(tweets: &[QueuedTweet], idx: usize) -> Result<Option<i64>, usize> {
+    match tweets[idx].thread {
+        Some(ref thread) => {
+            match tweets[..idx].iter().enumerate().rev().find(|&(_, t)| t.thread.as_ref() == Some(thread)) {
+                Some((pred_i, pred)) => pred.id.map(Some).ok_or(pred_i),
+                None => Ok(None),
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+/// Hold back the tweet at the specified index, which failed permanently for the specified reason, and the tweets after it in
+/// its thread, so no replies to it are posted, until requeued with the `requeue-thread` subsystem.
+///
+/// Returns the indices of the tweets held back, in order, or nothing if the tweet isn't in a thread.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = |content: &str, thread: Option<&str>| QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///     content: content.to_string(),
+///     thread: thread.map(str::to_string),
+///     ..Default::default()
+/// };
+/// let mut tweets = vec![tweet("1/3 Abolish", Some("manifesto")),
+///                       tweet("Unrelated", None),
+///                       tweet("2/3 the", Some("manifesto")),
+///                       tweet("3/3 bourgeoisie", Some("manifesto"))];
+///
+/// let reason = "Status is a duplicate. (code 187)";
+/// assert_eq!(start_daemon::block_thread(&mut tweets, 1, reason), Vec::<usize>::new());
+/// assert_eq!(start_daemon::block_thread(&mut tweets, 2, reason), vec![2, 3]);
+/// assert_eq!(tweets[0].blocked, None);
+/// assert_eq!(tweets[1].blocked, None);
+/// assert_eq!(tweets[2].blocked, Some(reason.to_string()));
+/// assert_eq!(tweets[3].blocked, Some(reason.to_string()));
+/// # }
+/// ```
+pub fn block_thread(tweets: &mut [QueuedTweet], idx: usize, reason: &str) -> Vec<usize> {
+    let thread = match tweets[idx].thread {
+        Some(ref thread) => thread.clone(),
+        None => return vec![],
+    };
+
+    let blocked: Vec<_> = (idx..tweets.len()).filter(|&i| tweets[i].thread.as_ref() == Some(&thread) && tweets[i].id.is_none()).collect();
+    for &i in &blocked {
+        tweets[i].blocked = Some(reason.to_string());
+    }
+    blocked
+}
+
 /// Try to get the index of the user to post the given tweet.
 ///
 /// This will fail iff there's no suitable user.
@@ -164,8 +621,7 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
 ///     author: "nabijaczleweli".to_string(),
 ///     time: now,
 ///     content: "dummy".to_string(),
-///     time_posted: None,
-///     id: None,
+///     ..Default::default()
 /// };
 ///
 /// assert!(start_daemon::find_user_index_for_tweet(&tweet, &vec![]).is_err());
@@ -174,6 +630,21 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
 ///     id: 0x4208142311,
 ///     access_token_key: "key".to_string(),
 ///     access_token_secret: "secret".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
 /// }]).is_err());
 /// # }
 /// ```
@@ -193,13 +664,27 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
 ///     author: "danerangLP".to_string(),
 ///     time: now,
 ///     content: "dummy".to_string(),
-///     time_posted: None,
-///     id: None,
+///     ..Default::default()
 /// }, &vec![User {
 ///     name: "danerangLP".to_string(),
 ///     id: 0x4208142311,
 ///     access_token_key: "key".to_string(),
 ///     access_token_secret: "secret".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
 /// }]), Ok(0));
 /// # }
 /// ```
@@ -218,9 +703,199 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
     }
 }
 
-/// Post the specified tweet on behalf of the specified user and application, optionally printing progress.
+/// Get how to post the tweets by the user at the specified index.
+///
+/// Users with `test_mode` enabled have their tweets posted as their `test_account`, if specified, or marked as tests, so the
+/// whole posting pipeline can be rehearsed without publishing them. Fails if the test account doesn't exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::start_daemon::{self, TestMode};
+/// # use tweetr::ops::User;
+/// # use tweetr::Outcome;
+/// let brand = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: Some(true),
+///     test_account: Some("tweetr_test".to_string()),
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// let test = User {
+///     name: "tweetr_test".to_string(),
+///     id: 482,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+///     ..brand.clone()
+/// };
+///
+/// let mut users = vec![brand, test];
+/// assert_eq!(start_daemon::test_mode(&users, 0), Ok(TestMode::Reroute(1)));
+/// assert_eq!(start_daemon::test_mode(&users, 1), Ok(TestMode::Off));
+///
+/// users[0].test_account = None;
+/// assert_eq!(start_daemon::test_mode(&users, 0), Ok(TestMode::Mark));
+///
+/// users[0].test_account = Some("tweetr_rehearsal".to_string());
+/// assert_eq!(start_daemon::test_mode(&users, 0),
+///            Err(Outcome::RequiredDataFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                desc: "add and authorise user with name \"tweetr_rehearsal\" (required to post nabijaczleweli's tweets in test mode)"
+///                    .to_string(),
+///            }));
+/// ```
+pub fn test_mode(users: &[User], user_i: usize) -> Result<TestMode, Outcome> {
+    let user = &users[user_i];
+    if !user.test_mode.unwrap_or(false) {
+        return Ok(TestMode::Off);
+    }
+
+    match user.test_account {
+        Some(ref name) => {
+            users.iter().position(|u| u.name == *name).map(TestMode::Reroute).ok_or_else(|| {
+                Outcome::RequiredDataFromSubsystemNonexistant {
+                    subsys: "add-user",
+                    desc: format!("add and authorise user with name \"{}\" (required to post {}'s tweets in test mode)", name, user.name),
+                }
+            })
+        }
+        None => Ok(TestMode::Mark),
+    }
+}
+
+/// Mark the specified tweet content as a test by prefixing it with `TEST_PREFIX`, unless already marked.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::start_daemon;
+/// assert_eq!(start_daemon::mark_test("Abolish the bourgeoisie"), "[TEST] Abolish the bourgeoisie");
+/// assert_eq!(start_daemon::mark_test("[TEST] Abolish the bourgeoisie"), "[TEST] Abolish the bourgeoisie");
+/// ```
+pub fn mark_test(content: &str) -> String {
+    if content.starts_with(TEST_PREFIX) {
+        content.to_string()
+    } else {
+        format!("{}{}", TEST_PREFIX, content)
+    }
+}
+
+/// Fold the specified content warning into the specified content with the specified template, unless already folded.
+///
+/// In the template, `{warning}` is replaced with the content warning, and `{content}` with the content (if `{content}` is
+/// missing, the content is appended to the template).
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::start_daemon;
+/// let format = "CW: {warning}\n\n{content}";
+/// assert_eq!(start_daemon::fold_content_warning("Abolish the bourgeoisie", None, format), "Abolish the bourgeoisie");
+/// assert_eq!(start_daemon::fold_content_warning("Abolish the bourgeoisie", Some("politics"), format),
+///            "CW: politics\n\nAbolish the bourgeoisie");
+/// assert_eq!(start_daemon::fold_content_warning("CW: politics\n\nAbolish the bourgeoisie", Some("politics"), format),
+///            "CW: politics\n\nAbolish the bourgeoisie");
+/// assert_eq!(start_daemon::fold_content_warning("Abolish the bourgeoisie", Some("politics"), "[{warning}] "),
+///            "[politics] Abolish the bourgeoisie");
+/// ```
+pub fn fold_content_warning(content: &str, warning: Option<&str>, format: &str) -> String {
+    let warning = match warning {
+        Some(warning) => warning,
+        None => return content.to_string(),
+    };
+
+    let format = format.replace("{warning}", warning);
+    let (prefix, suffix) = match format.find("{content}") {
+        Some(idx) => (&format[..idx], &format[idx + "{content}".len()..]),
+        None => (&format[..], ""),
+    };
+    if content.len() >= prefix.len() + suffix.len() && content.starts_with(prefix) && content.ends_with(suffix) {
+        content.to_string()
+    } else {
+        format!("{}{}{}", prefix, content, suffix)
+    }
+}
+
+/// Pause posting the tweets by the user with the specified name in the authenticated users list at the specified path,
+/// since the specified time, because their account was found suspended or locked.
+///
+/// The list is reread and written back as-is otherwise, so environment variable references in it are kept. Users no longer in
+/// it are skipped.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{start_daemon, User};
+/// # use std::env::temp_dir;
+/// # use chrono::DateTime;
+/// # use std::fs;
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-suspend");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("users.toml");
+///
+/// let user = User {
+///     name: "nabijaczleweli".to_string(),
+///     id: 481,
+///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///     access_token_secret: "${TWEETR_SECRET}".to_string(),
+///     app: None,
+///     telegram_bot_token: None,
+///     telegram_chat_id: None,
+///     discord_webhook: None,
+///     timezone: None,
+///     quiet_hours: None,
+///     jitter: None,
+///     tags: None,
+///     sensitive: None,
+///     test_mode: None,
+///     test_account: None,
+///     added: None,
+///     suspended: None,
+///     max_posts_per_day: None,
+///     lint: None,
+/// };
+/// User::write(vec![user.clone()], &tf).unwrap();
+///
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// assert_eq!(start_daemon::suspend(&tf, "nabijaczleweli", now), Ok(()));
+/// assert_eq!(User::read_verbatim(&tf),
+///            Ok(vec![User { suspended: Some("2016-09-10T12:00:00+02:00".to_string()), ..user }]));
+/// # }
+/// ```
+pub fn suspend(users_path: &Path, name: &str, now: DateTime<FixedOffset>) -> Result<(), Outcome> {
+    let mut users = try!(User::read_verbatim(users_path).map_err(Option::unwrap));
+    match users.iter_mut().find(|u| u.name == name) {
+        Some(user) => user.suspended = Some(now.to_rfc3339()),
+        None => return Ok(()),
+    }
+    User::write(users, users_path)
+}
+
+/// Deliver the specified tweet via the specified backend, optionally printing progress.
 ///
-/// The tweet is updated with the data returned by the Twitter API.
+/// The tweet is updated with the ID returned by the backend and the time it was posted at according to the specified clock,
+/// with the clock's precision, so that the delay between that and the time it was scheduled for is measured on one clock.
 ///
 /// # Examples
 ///
@@ -228,6 +903,8 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
 /// # extern crate tweetr;
 /// # extern crate chrono;
 /// # use tweetr::ops::{QueuedTweet, AppTokens, User, start_daemon};
+/// # use tweetr::ops::clock::SystemClock;
+/// # use tweetr::ops::backend::Twitter;
 /// # use chrono::{Duration, Local};
 /// # fn main() {
 /// let now = Local::now();
@@ -237,56 +914,435 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
 ///     author: "nabijaczleweli".to_string(),
 ///     time: now,
 ///     content: "This tweet will be posted, no matter the cost!".to_string(),
-///     time_posted: None,
-///     id: None,
+///     ..Default::default()
 /// };
 ///
-/// let result = start_daemon::post_tweet(&mut tweet, &User {
-///     name: "nabijaczleweli".to_string(),
-///     id: 0x81423,
-///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
-///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
-/// }, &AppTokens {
-///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
-///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
-/// }.into(), false, &mut vec![]);
+/// let result = start_daemon::post_tweet(&mut tweet, &Twitter {
+///     user: &User {
+///         name: "nabijaczleweli".to_string(),
+///         id: 0x81423,
+///         access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+///         access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+///         app: None,
+///         telegram_bot_token: None,
+///         telegram_chat_id: None,
+///         discord_webhook: None,
+///         timezone: None,
+///         quiet_hours: None,
+///         jitter: None,
+///         tags: None,
+///         sensitive: None,
+///         test_mode: None,
+///         test_account: None,
+///         added: None,
+///         suspended: None,
+///         max_posts_per_day: None,
+///         lint: None,
+///     },
+///     app: &AppTokens {
+///         key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///         secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     }.into(),
+///     uploads: None,
+/// }, &SystemClock, false, &mut vec![]);
 ///
 /// assert_eq!(result.exit_value(), 0);
 /// assert!(tweet.time_posted.is_some());
 /// assert!(tweet.id.is_some());
 /// # }
 /// ```
-pub fn post_tweet<'a, W: Write>(tweet: &mut QueuedTweet, on_behalf_of: &User, app: &Token<'a>, verbose: bool, output: &mut W) -> Outcome {
+pub fn post_tweet<B, C, W>(tweet: &mut QueuedTweet, backend: &B, clock: &C, verbose: bool, output: &mut W) -> Outcome
+    where B: PostingBackend + ?Sized,
+          C: Clock + ?Sized,
+          W: Write
+{
     if verbose {
-        write!(output, "Posting tweet scheduled for {:?}...", tweet.time).unwrap();
+        write!(output, "{}", tr("posting", &[("time", &format_datetime(&tweet.time))])).unwrap();
         output.flush().unwrap();
     }
 
-    match span_r(|| DraftTweet::new(&tweet.content).send(app, &Token::new(&on_behalf_of.access_token_key[..], &on_behalf_of.access_token_secret[..]))) {
-        (dur, Ok(resp)) => {
+    match span_r(|| backend.post(tweet)) {
+        (dur, Ok(id)) => {
             if verbose {
                 writeln!(output, " {}ms", dur.num_milliseconds()).unwrap();
             }
 
-            tweet.time_posted = Some(DateTime::parse_from_str(&resp.response.created_at, TWEET_DATETIME_FORMAT).unwrap());
-            tweet.id = Some(resp.response.id);
+            tweet.time_posted = Some(clock.now());
+            tweet.id = Some(id);
+            tweet.app = backend.app();
 
             writeln!(output,
-                     "Posted tweet \"{}\" scheduled for {:?} by {} at {:?} with ID {}",
-                     tweet.content,
-                     tweet.time,
-                     tweet.author,
-                     tweet.time_posted.as_ref().unwrap(),
-                     resp.response.id)
+                     "{}",
+                     tr("posted",
+                        &[("content", &tweet.content),
+                          ("time", &format_datetime(&tweet.time)),
+                          ("author", &tweet.author),
+                          ("time_posted", &format_datetime(tweet.time_posted.as_ref().unwrap())),
+                          ("id", &id)]))
                 .unwrap();
 
             Outcome::NoError
         }
-        (_, Err(e)) => {
+        (_, Err(out)) => {
+            if verbose {
+                writeln!(output, " {}", tr("failed", &[])).unwrap();
+            }
+            out
+        }
+    }
+}
+
+/// Deliver the specified already posted tweet via the specified additional backend, optionally printing progress.
+///
+/// Unlike with `post_tweet()`, the tweet is left unchanged, so failures aren't retried.
+/// The content is adapted to the backend first, see `backend::adapt()`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use tweetr::ops::backend::Telegram;
+/// # use chrono::Local;
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: now,
+///     content: "This tweet was posted and will be mirrored".to_string(),
+///     time_posted: Some(now),
+///     id: Some(774560457755590656),
+///     ..Default::default()
+/// };
+///
+/// let result = start_daemon::mirror_tweet(&tweet, &Telegram {
+///     bot_token: "110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string(),
+///     chat_id: "@tweetr_announcements".to_string(),
+/// }, false, &mut vec![]);
+///
+/// assert_eq!(result.exit_value(), 0);
+/// # }
+/// ```
+pub fn mirror_tweet<B: PostingBackend + ?Sized, W: Write>(tweet: &QueuedTweet, backend: &B, verbose: bool, output: &mut W) -> Outcome {
+    if verbose {
+        write!(output, "{}", tr("mirroring", &[("backend", &backend.description())])).unwrap();
+        output.flush().unwrap();
+    }
+
+    let tweet = &match backend.variant() {
+        Some(variant) => backend::adapt(tweet, variant),
+        None => tweet.clone(),
+    };
+    match span_r(|| backend.post(tweet)) {
+        (dur, Ok(id)) => {
+            if verbose {
+                writeln!(output, " {}ms", dur.num_milliseconds()).unwrap();
+            }
+
+            writeln!(output,
+                     "{}",
+                     tr("mirrored", &[("content", &tweet.content), ("backend", &backend.description()), ("id", &id)]))
+                .unwrap();
+            Outcome::NoError
+        }
+        (_, Err(out)) => {
             if verbose {
-                writeln!(output, " FAILED").unwrap();
+                writeln!(output, " {}", tr("failed", &[])).unwrap();
+            }
+            out
+        }
+    }
+}
+
+
+impl<T> Cached<T> {
+    /// Cache the contents of the specified file, not read yet.
+    pub fn new(path: PathBuf) -> Cached<T> {
+        Cached {
+            path: path,
+            stamp: None,
+            hash: None,
+            value: None,
+        }
+    }
+
+    /// Get the file's contents, read with the specified function if the file changed since it was last read, also returning
+    /// whether it was read.
+    ///
+    /// Errors aren't cached, so the file's read again next time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::start_daemon::Cached;
+    /// # use std::fs::{self, File};
+    /// # use std::env::temp_dir;
+    /// # use std::io::{Read, Write};
+    /// # use std::path::Path;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-cached-get");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// let tf = tf.join("motd");
+    /// File::create(&tf).unwrap().write_all(b"Capitalism").unwrap();
+    ///
+    /// fn read(p: &Path) -> Result<String, Option<tweetr::Outcome>> {
+    ///     let mut buf = String::new();
+    ///     File::open(p).unwrap().read_to_string(&mut buf).unwrap();
+    ///     Ok(buf)
+    /// }
+    ///
+    /// let mut cached = Cached::new(tf.clone());
+    /// assert_eq!(cached.get(read).map(|(v, r)| (v.clone(), r)), Ok(("Capitalism".to_string(), true)));
+    /// assert_eq!(cached.get(read).map(|(v, r)| (v.clone(), r)), Ok(("Capitalism".to_string(), false)));
+    ///
+    /// File::create(&tf).unwrap().write_all(b"Communism").unwrap();
+    /// assert_eq!(cached.get(read).map(|(v, r)| (v.clone(), r)), Ok(("Communism".to_string(), true)));
+    /// ```
+    pub fn get<F>(&mut self, read: F) -> Result<(&mut T, bool), Option<Outcome>>
+        where F: FnOnce(&Path) -> Result<T, Option<Outcome>>
+    {
+        // Taken before hashing, so changes made in between are caught by the next call
+        let stamp = file_stamp(&self.path);
+        if stamp.is_some() && stamp == self.stamp && self.value.is_some() {
+            return Ok((self.value.as_mut().unwrap(), false));
+        }
+
+        let hash = hash_file(&self.path);
+        let reread = hash.is_none() || hash != self.hash || self.value.is_none();
+        if reread {
+            self.stamp = None;
+            self.hash = None;
+            self.value = Some(try!(read(&self.path)));
+            self.hash = hash;
+        }
+        self.stamp = stamp;
+
+        Ok((self.value.as_mut().unwrap(), reread))
+    }
+
+    /// Note that the file was written with the cached contents, as modified, so it needn't be read again.
+    pub fn written(&mut self) {
+        self.stamp = file_stamp(&self.path);
+        self.hash = hash_file(&self.path);
+    }
+}
+
+impl Due {
+    /// Order the waiting tweets in the specified queue by when they're scheduled for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::clock::{Clock, ManualClock};
+    /// # use tweetr::ops::start_daemon::{self, Due};
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::{DateTime, Duration};
+    /// # fn main() {
+    /// let tweet = |time: &str, paused: bool| QueuedTweet {
+    ///     author: "nabijaczleweli".to_string(),
+    ///     time: DateTime::parse_from_rfc3339(time).unwrap(),
+    ///     content: "Abolish the bourgeoisie".to_string(),
+    ///     paused: paused,
+    ///     ..Default::default()
+    /// };
+    /// let tweets = vec![tweet("2016-09-10T12:00:30+02:00", false),
+    ///                   tweet("2016-09-10T11:30:00+02:00", false),
+    ///                   tweet("2016-09-10T11:00:00+02:00", true),
+    ///                   tweet("2016-09-10T11:59:00+02:00", false)];
+    /// let clock = ManualClock::new(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+    ///
+    /// let mut due = Due::new(&tweets);
+    /// assert_eq!(due.due(&clock), vec![1, 3]);
+    /// assert_eq!(due.due(&clock), start_daemon::tweet_indices_to_post(&tweets, &clock));
+    /// assert_eq!(due.next_wake(&clock, Duration::minutes(1)), tweets[0].time);
+    /// assert_eq!(due.next_wake(&clock, Duration::seconds(10)), clock.now() + Duration::seconds(10));
+    /// # }
+    /// ```
+    pub fn new(tweets: &[QueuedTweet]) -> Due {
+        Due {
+            heap: tweets.iter()
+                .enumerate()
+                .filter(|&(_, t)| t.id.is_none() && !t.paused && t.blocked.is_none())
+                .map(|(i, t)| {
+                    Waiting {
+                        time: t.time,
+                        index: i,
+                    }
+                })
+                .collect(),
+            overdue: vec![],
+        }
+    }
+
+    /// Get the indices of the tweets due to be posted, in queue order, like `tweet_indices_to_post()`.
+    pub fn due<C: Clock + ?Sized>(&mut self, clock: &C) -> Vec<usize> {
+        let mut due: Vec<_> = self.due_waiting(clock.now()).iter().map(|w| w.index).collect();
+        due.sort();
+        due
+    }
+
+    /// Get the time the daemon should next check the queue at, like `next_wake()`.
+    pub fn next_wake<C: Clock + ?Sized>(&mut self, clock: &C, max_delay: Duration) -> DateTime<FixedOffset> {
+        let now = clock.now();
+
+        self.due_waiting(now);
+        self.heap.peek().map(|w| w.time).into_iter().fold(now + max_delay, cmp::min)
+    }
+
+    /// Get the tweets scheduled at or before the specified time, leaving the heap's top the earliest one scheduled after it.
+    ///
+    /// They're kept aside, since they stay due until posted, and the heap's rebuilt anyway when they are, unless the clock
+    /// jumps back to before them.
+    fn due_waiting(&mut self, now: DateTime<FixedOffset>) -> &[Waiting] {
+        if self.overdue.iter().any(|w| w.time > now) {
+            let (overdue, not_yet): (Vec<_>, Vec<_>) = self.overdue.drain(..).partition(|w| w.time <= now);
+            self.overdue = overdue;
+            self.heap.extend(not_yet);
+        }
+        while self.heap.peek().map(|w| w.time <= now).unwrap_or(false) {
+            let waiting = self.heap.pop().unwrap();
+            self.overdue.push(waiting);
+        }
+        &self.overdue
+    }
+}
+
+impl DaemonLock {
+    /// Get the path to the daemon's lock file in the specified configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::start_daemon::DaemonLock;
+    /// # use std::path::Path;
+    /// assert_eq!(DaemonLock::path(Path::new("config")), Path::new("config/daemon.pid"));
+    /// ```
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join("daemon.pid")
+    }
+
+    /// Claim the specified configuration directory for this process, failing if another running one holds it already.
+    ///
+    /// A lock left behind by a process that's no longer running is taken over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::start_daemon::DaemonLock;
+    /// # use std::env::temp_dir;
+    /// # use std::process;
+    /// # use std::fs;
+    /// let td = temp_dir().join("tweetr-doctest").join("ops-start-daemon-daemon-lock-acquire");
+    /// fs::create_dir_all(&td).unwrap();
+    ///
+    /// let lock = DaemonLock::acquire(&td).unwrap();
+    /// assert_eq!(DaemonLock::holder(&td), Some(process::id()));
+    /// assert!(DaemonLock::acquire(&td).is_err());
+    ///
+    /// drop(lock);
+    /// assert_eq!(DaemonLock::holder(&td), None);
+    /// ```
+    pub fn acquire(config_dir: &Path) -> Result<DaemonLock, Outcome> {
+        let path = DaemonLock::path(config_dir);
+        if let Some(pid) = DaemonLock::holder(config_dir) {
+            return Err(DaemonLock::held(&path, pid));
+        }
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut f) => {
+                    try!(write!(f, "{}", process::id()).map_err(|e| Outcome::from_io("write", &path, e)));
+                    return Ok(DaemonLock { path: path });
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    match DaemonLock::holder(config_dir) {
+                        Some(pid) => return Err(DaemonLock::held(&path, pid)),
+                        None => try!(fs::remove_file(&path).map_err(|e| Outcome::from_io("remove", &path, e))),
+                    }
+                }
+                Err(e) => return Err(Outcome::from_io("create", &path, e)),
             }
-            Outcome::TwitterAPIError(e.to_string())
         }
     }
+
+    /// Get the ID of the running process holding the lock on the specified configuration directory, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::start_daemon::DaemonLock;
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// let td = temp_dir().join("tweetr-doctest").join("ops-start-daemon-daemon-lock-holder");
+    /// fs::create_dir_all(&td).unwrap();
+    ///
+    /// assert_eq!(DaemonLock::holder(&td), None);
+    /// ```
+    pub fn holder(config_dir: &Path) -> Option<u32> {
+        let mut pid = String::new();
+        File::open(DaemonLock::path(config_dir))
+            .and_then(|mut f| f.read_to_string(&mut pid))
+            .ok()
+            .and_then(|_| u32::from_str(pid.trim()).ok())
+            .and_then(|pid| if process_running(pid) { Some(pid) } else { None })
+    }
+
+    fn held(path: &Path, pid: u32) -> Outcome {
+        Outcome::PreflightFailed(vec![format!("process {} is already posting tweets, stop it first, or remove {} if it isn't running",
+                                              pid,
+                                              path.display())])
+    }
+}
+
+impl Drop for DaemonLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Ord for Waiting {
+    /// Reversed, so the `BinaryHeap` pops the earliest tweet first.
+    fn cmp(&self, other: &Waiting) -> Ordering {
+        other.time.cmp(&self.time).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Waiting {
+    fn partial_cmp(&self, other: &Waiting) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    use libc;
+
+    // Signal 0 only checks whether the process exists, and may be signalled by us
+    pid as libc::pid_t > 0 &&
+    (unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+#[cfg(not(unix))]
+fn process_running(pid: u32) -> bool {
+    // Without a way to check, a lock's assumed held
+    let _ = pid;
+    true
+}
+
+fn file_stamp(p: &Path) -> Option<(SystemTime, u64)> {
+    fs::metadata(p).and_then(|m| m.modified().map(|t| (t, m.len()))).ok()
+}
+
+fn hash_file(p: &Path) -> Option<u64> {
+    let mut buf = vec![];
+    File::open(p).and_then(|mut f| f.read_to_end(&mut buf)).ok().map(|_| {
+        let mut hasher = DefaultHasher::new();
+        buf.hash(&mut hasher);
+        hasher.finish()
+    })
 }
@@ -0,0 +1,197 @@
+//! This module contains the functions used only by the `suggest-time` subsystem.
+//!
+//! The flow of the `suggest-time` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::Config::read()
+//! |> ops::suggest_time::grid()
+//! |> ops::QueuedTweet::read()
+//! |> ops::suggest_time::free_slots()
+//! ```
+
+
+use chrono::{DateTime, FixedOffset, Datelike, Duration, Local, Weekday};
+use self::super::{Config, QueuedTweet, key_error};
+use self::super::super::Outcome;
+use std::str::FromStr;
+use std::path::Path;
+
+
+/// A weekly recurring time to post at, in local time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Slot {
+    /// The day of the week.
+    pub weekday: Weekday,
+    /// The hour, in range `[0, 24)`.
+    pub hour: u32,
+    /// The minute, in range `[0, 60)`.
+    pub minute: u32,
+}
+
+
+/// Parse a slot specification into the slots it contains.
+///
+/// The format is one or more `/`-separated three-letter weekday names followed by one or more `HH:MM` times,
+/// e.g. `"Mon/Wed/Fri 09:00 17:00"`, which specifies all combinations thereof.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::suggest_time::{self, Slot};
+/// # use chrono::Weekday;
+/// # fn main() {
+/// assert_eq!(suggest_time::parse_slots("Mon/fri 09:00 17:30"),
+///            Ok(vec![Slot { weekday: Weekday::Mon, hour: 9, minute: 0 },
+///                    Slot { weekday: Weekday::Mon, hour: 17, minute: 30 },
+///                    Slot { weekday: Weekday::Fri, hour: 9, minute: 0 },
+///                    Slot { weekday: Weekday::Fri, hour: 17, minute: 30 }]));
+/// assert!(suggest_time::parse_slots("Mon").is_err());
+/// assert!(suggest_time::parse_slots("Mon 25:00").is_err());
+/// assert!(suggest_time::parse_slots("Monty 09:00").is_err());
+/// # }
+/// ```
+pub fn parse_slots(spec: &str) -> Result<Vec<Slot>, String> {
+    let mut parts = spec.split_whitespace();
+    let weekdays = try!(parts.next()
+        .ok_or_else(|| "empty slot specification".to_string())
+        .and_then(|days| days.split('/').map(parse_weekday).collect::<Result<Vec<_>, _>>()));
+
+    let times: Vec<(u32, u32)> = try!(parts.map(parse_time).collect());
+    if times.is_empty() {
+        return Err(format!("no times in \"{}\"", spec));
+    }
+
+    Ok(weekdays.into_iter().flat_map(|weekday| {
+            times.iter().map(move |&(hour, minute)| {
+                Slot {
+                    weekday: weekday,
+                    hour: hour,
+                    minute: minute,
+                }
+            })
+        })
+        .collect())
+}
+
+/// Parse the weekly grid of posting slots from the specified configuration.
+///
+/// Fails if any slot specification is invalid or no slots are configured.
+pub fn grid(config: &Config, config_dir: &Path) -> Result<Vec<Slot>, Outcome> {
+    let config_path = Config::path(config_dir);
+
+    let mut slots = vec![];
+    for (i, spec) in config.posting_slots().iter().enumerate() {
+        slots.extend(try!(parse_slots(spec)
+            .map_err(|e| key_error(&config_path, "configuration", &format!("posting_slots.{}", i), &format!("invalid posting slot ({})", e)))));
+    }
+
+    if slots.is_empty() {
+        Err(key_error(&config_path, "configuration", "posting_slots", "no posting slots configured"))
+    } else {
+        Ok(slots)
+    }
+}
+
+/// Find the specified amount of the earliest slots after the specified time at which no unposted tweet is scheduled
+/// less than the specified window apart.
+///
+/// If an author is specified, only their tweets are considered.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::suggest_time;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{Duration, Local, TimeZone};
+/// # fn main() {
+/// // Monday
+/// let from = Local.ymd(2016, 9, 12).and_hms(8, 0, 0);
+/// let grid = suggest_time::parse_slots("Mon/Wed 09:00 17:00").unwrap();
+///
+/// let taken = Local.ymd(2016, 9, 12).and_hms(9, 5, 0);
+/// let queue = vec![QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: taken.with_timezone(taken.offset()),
+///     content: "Abolish".to_string(),
+///     ..Default::default()
+/// }];
+///
+/// let slots = suggest_time::free_slots(&grid, Some("nabijaczleweli"), &queue, Duration::minutes(10), from, 2);
+/// assert_eq!(slots.len(), 2);
+/// assert_eq!(slots[0], Local.ymd(2016, 9, 12).and_hms(17, 0, 0));
+/// assert_eq!(slots[1], Local.ymd(2016, 9, 14).and_hms(9, 0, 0));
+///
+/// let slots = suggest_time::free_slots(&grid, Some("danerangLP"), &queue, Duration::minutes(10), from, 1);
+/// assert_eq!(slots, vec![Local.ymd(2016, 9, 12).and_hms(9, 0, 0)]);
+/// # }
+/// ```
+pub fn free_slots(grid: &[Slot], author: Option<&str>, queue: &[QueuedTweet], window: Duration, from: DateTime<Local>, count: usize)
+                  -> Vec<DateTime<FixedOffset>> {
+    let mut grid = grid.to_vec();
+    grid.sort_by_key(|s| (s.hour, s.minute));
+
+    let mut slots = vec![];
+    let mut date = from.date();
+    while slots.len() < count && !grid.is_empty() {
+        for slot in grid.iter().filter(|s| s.weekday == date.weekday()) {
+            // Times skipped by DST changes don't exist
+            if let Some(time) = date.and_hms_opt(slot.hour, slot.minute, 0) {
+                let fixed_time = time.with_timezone(time.offset());
+                if time > from && slots.len() < count && !taken(fixed_time, author, queue, window) {
+                    slots.push(fixed_time);
+                }
+            }
+        }
+        date = date.succ();
+    }
+    slots
+}
+
+/// Parse the specified abbreviated English name of a day of the week, in any case.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::suggest_time;
+/// # use chrono::Weekday;
+/// # fn main() {
+/// assert_eq!(suggest_time::parse_weekday("Mon"), Ok(Weekday::Mon));
+/// assert_eq!(suggest_time::parse_weekday("SUN"), Ok(Weekday::Sun));
+/// assert_eq!(suggest_time::parse_weekday("Monty"), Err("\"Monty\" is not a weekday".to_string()));
+/// # }
+/// ```
+pub fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    match &s.to_lowercase()[..] {
+        "mon" => Ok(Weekday::Mon),
+        "tue" => Ok(Weekday::Tue),
+        "wed" => Ok(Weekday::Wed),
+        "thu" => Ok(Weekday::Thu),
+        "fri" => Ok(Weekday::Fri),
+        "sat" => Ok(Weekday::Sat),
+        "sun" => Ok(Weekday::Sun),
+        _ => Err(format!("\"{}\" is not a weekday", s)),
+    }
+}
+
+
+fn taken(time: DateTime<FixedOffset>, author: Option<&str>, queue: &[QueuedTweet], window: Duration) -> bool {
+    queue.iter().any(|q| {
+        q.id.is_none() && author.map(|a| q.author == a).unwrap_or(true) &&
+        (q.time == time || (q.time - time).num_seconds().abs() < window.num_seconds())
+    })
+}
+
+fn parse_time(s: &str) -> Result<(u32, u32), String> {
+    let mut hm = s.splitn(2, ':').map(u32::from_str);
+    match (hm.next(), hm.next()) {
+        (Some(Ok(hour)), Some(Ok(minute))) if hour < 24 && minute < 60 => Ok((hour, minute)),
+        _ => Err(format!("\"{}\" is not a HH:MM time", s)),
+    }
+}
@@ -0,0 +1,158 @@
+use self::super::{key_error, read_toml_file};
+use self::super::super::Outcome;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+
+/// A named tweet body, with placeholders filled in with values specified when queueing it.
+///
+/// Placeholders are names in braces, like `{version}`, and literal braces are doubled, like `{{`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Template {
+    /// The name the template is chosen by
+    pub name: String,
+    /// The tweet content, with placeholders
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct Templates {
+    template: Vec<Template>,
+}
+
+
+impl Template {
+    /// Get the path to the templates file in the specified configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Template;
+    /// # use std::env::temp_dir;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-template-path-0");
+    /// assert_eq!(Template::path(&tf), tf.join("templates.toml"));
+    /// ```
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join("templates.toml")
+    }
+
+    /// Read all templates from the specified file.
+    ///
+    /// Two templates with the same name, and malformed placeholders are errors.
+    pub fn read(p: &Path) -> Result<Vec<Template>, Option<Outcome>> {
+        let templates = try!(read_toml_file(p, "templates").map(|ts: Templates| ts.template));
+
+        for (i, template) in templates.iter().enumerate() {
+            if templates[..i].iter().any(|t| t.name == template.name) {
+                return Err(Some(key_error(p, "templates", &format!("template.{}.name", i), &format!("duplicate template \"{}\"", template.name))));
+            }
+            if let Err(e) = template.placeholders() {
+                return Err(Some(key_error(p, "templates", &format!("template.{}.content", i), &e)));
+            }
+        }
+
+        Ok(templates)
+    }
+
+    /// Get the names of the template's placeholders, in order of first appearance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Template;
+    /// let template = Template {
+    ///     name: "release".to_string(),
+    ///     content: "tweetr {version} is out! {{ {notes} }} Get {version} at {url}".to_string(),
+    /// };
+    /// assert_eq!(template.placeholders(), Ok(vec!["version".to_string(), "notes".to_string(), "url".to_string()]));
+    ///
+    /// let template = Template { content: "tweetr {version".to_string(), ..template };
+    /// assert_eq!(template.placeholders(), Err("unclosed placeholder at character 7".to_string()));
+    /// ```
+    pub fn placeholders(&self) -> Result<Vec<String>, String> {
+        let mut placeholders: Vec<String> = vec![];
+        try!(self.substitute(|name| {
+            if !placeholders.iter().any(|p| p == name) {
+                placeholders.push(name.to_string());
+            }
+            String::new()
+        }));
+        Ok(placeholders)
+    }
+
+    /// Fill the template's placeholders with the specified values.
+    ///
+    /// Placeholders without a value, and values without a placeholder are errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Template;
+    /// # use std::collections::BTreeMap;
+    /// let template = Template {
+    ///     name: "release".to_string(),
+    ///     content: "tweetr {version} is out! {{{notes}}}".to_string(),
+    /// };
+    ///
+    /// let mut values = BTreeMap::new();
+    /// values.insert("version".to_string(), "v1.4.0".to_string());
+    /// assert_eq!(template.fill(&values), Err(vec!["no value for the placeholder {notes}".to_string()]));
+    ///
+    /// values.insert("notes".to_string(), "bugfixes".to_string());
+    /// assert_eq!(template.fill(&values), Ok("tweetr v1.4.0 is out! {bugfixes}".to_string()));
+    ///
+    /// values.insert("verison".to_string(), "v1.4.1".to_string());
+    /// assert_eq!(template.fill(&values), Err(vec!["no placeholder for the value of verison".to_string()]));
+    /// ```
+    pub fn fill(&self, values: &BTreeMap<String, String>) -> Result<String, Vec<String>> {
+        let placeholders = try!(self.placeholders().map_err(|e| vec![e]));
+
+        let errors: Vec<_> = placeholders.iter()
+            .filter(|p| !values.contains_key(&p[..]))
+            .map(|p| format!("no value for the placeholder {{{}}}", p))
+            .chain(values.keys().filter(|v| !placeholders.contains(v)).map(|v| format!("no placeholder for the value of {}", v)))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        self.substitute(|name| values[name].clone()).map_err(|e| vec![e])
+    }
+
+    fn substitute<F: FnMut(&str) -> String>(&self, mut value: F) -> Result<String, String> {
+        let mut result = String::new();
+        let mut chars = self.content.chars().enumerate().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    result.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    result.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) if c.is_alphanumeric() || c == '_' || c == '-' => name.push(c),
+                            Some((j, c)) => return Err(format!("invalid character '{}' in placeholder at character {}", c, j)),
+                            None => return Err(format!("unclosed placeholder at character {}", i)),
+                        }
+                    }
+                    if name.is_empty() {
+                        return Err(format!("empty placeholder at character {}", i));
+                    }
+                    result.push_str(&value(&name));
+                }
+                '}' => return Err(format!("unmatched '}}' at character {}", i)),
+                c => result.push(c),
+            }
+        }
+
+        Ok(result)
+    }
+}
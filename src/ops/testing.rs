@@ -0,0 +1,210 @@
+//! Test doubles for testing integrations embedding tweetr without posting anything, available with the `testing` feature.
+//!
+//! `FakeBackend` stands in for the posting backends (see `ops::backend`): it records every tweet it's asked to deliver, and
+//! succeeds, fails or takes as long as scripted.
+//!
+//! tweetr keeps all of its state in the configuration directory, which the subsystems read and write by path, so there's no
+//! storage layer to swap out; `ConfigDir` instead sets up a throwaway configuration directory from the in-memory apps,
+//! users, queue and configuration, removed again when dropped.
+
+
+use self::super::{Apps, Config, QueuedTweet, User};
+use self::super::backend::PostingBackend;
+use self::super::super::Outcome;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::env::temp_dir;
+use std::{fs, thread};
+
+
+/// A posting backend recording the tweets it's asked to deliver instead of posting them anywhere.
+///
+/// Unless scripted otherwise with `then()`, each delivery succeeds, with IDs counting up from 1.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use tweetr::ops::testing::FakeBackend;
+/// # use tweetr::ops::clock::ManualClock;
+/// # use tweetr::Outcome;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+/// let mut tweet = QueuedTweet {
+///     author: "nabijaczleweli".to_string(),
+///     time: now,
+///     content: "Abolish the bourgeoisie".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let backend = FakeBackend::new("fake timeline");
+/// let rate_limited = Outcome::RateLimited {
+///     desc: "posting to fake timeline".to_string(),
+///     reset: None,
+/// };
+/// backend.then(Err(rate_limited.clone()));
+///
+/// let clock = ManualClock::new(now);
+/// assert_eq!(start_daemon::post_tweet(&mut tweet, &backend, &clock, false, &mut vec![]), rate_limited);
+/// assert_eq!(start_daemon::post_tweet(&mut tweet, &backend, &clock, false, &mut vec![]), Outcome::NoError);
+///
+/// assert_eq!(tweet.id, Some(1));
+/// assert_eq!(backend.calls().len(), 2);
+/// assert_eq!(backend.posted(), vec![QueuedTweet { time_posted: None, id: None, ..tweet }]);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FakeBackend {
+    description: String,
+    variant: Option<&'static str>,
+    latency: Duration,
+    script: RefCell<VecDeque<Result<i64, Outcome>>>,
+    calls: RefCell<Vec<(QueuedTweet, Result<i64, Outcome>)>>,
+    next_id: Cell<i64>,
+}
+
+/// A configuration directory in the temporary directory, with the specified files, removed when dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::testing::ConfigDir;
+/// # use tweetr::ops::{Config, QueuedTweet, User, start_daemon};
+/// let config_dir = ConfigDir::new("ops-testing-config_dir-0")
+///     .with_users(vec![])
+///     .with_tweets(vec![])
+///     .with_config(&Config { collision_window: Some(30), ..Config::default() });
+///
+/// assert_eq!(User::read(&config_dir.path().join("users.toml")), Ok(vec![]));
+/// assert_eq!(Config::read(&config_dir.path().join("config.toml")).unwrap().collision_window, Some(30));
+/// assert!(start_daemon::verify(&config_dir.config_dir()).is_err()); // No app.toml
+///
+/// let path = config_dir.path().to_path_buf();
+/// drop(config_dir);
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct ConfigDir {
+    path: PathBuf,
+}
+
+
+impl FakeBackend {
+    /// Create a backend with the specified description, delivering everything.
+    pub fn new<S: Into<String>>(description: S) -> FakeBackend {
+        FakeBackend {
+            description: description.into(),
+            variant: None,
+            latency: Duration::from_millis(0),
+            script: RefCell::new(VecDeque::new()),
+            calls: RefCell::new(vec![]),
+            next_id: Cell::new(1),
+        }
+    }
+
+    /// Take the specified amount of time to deliver each tweet.
+    pub fn with_latency(self, latency: Duration) -> FakeBackend {
+        FakeBackend { latency: latency, ..self }
+    }
+
+    /// Accept tweets' `variants` with the specified key, like `"telegram"`, see `PostingBackend::variant()`.
+    pub fn with_variant(self, variant: &'static str) -> FakeBackend {
+        FakeBackend { variant: Some(variant), ..self }
+    }
+
+    /// Finish the next unscripted delivery with the specified result.
+    pub fn then(&self, result: Result<i64, Outcome>) -> &FakeBackend {
+        self.script.borrow_mut().push_back(result);
+        self
+    }
+
+    /// Get all tweets the backend was asked to deliver, in order, with how each delivery finished.
+    pub fn calls(&self) -> Vec<(QueuedTweet, Result<i64, Outcome>)> {
+        self.calls.borrow().clone()
+    }
+
+    /// Get the tweets delivered successfully, in order.
+    pub fn posted(&self) -> Vec<QueuedTweet> {
+        self.calls.borrow().iter().filter(|c| c.1.is_ok()).map(|c| c.0.clone()).collect()
+    }
+}
+
+impl PostingBackend for FakeBackend {
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn post(&self, tweet: &QueuedTweet) -> Result<i64, Outcome> {
+        if self.latency != Duration::from_millis(0) {
+            thread::sleep(self.latency);
+        }
+
+        let result = self.script.borrow_mut().pop_front().unwrap_or_else(|| {
+            let id = self.next_id.get();
+            self.next_id.set(id + 1);
+            Ok(id)
+        });
+        self.calls.borrow_mut().push((tweet.clone(), result.clone()));
+        result
+    }
+
+    fn variant(&self) -> Option<&'static str> {
+        self.variant
+    }
+}
+
+impl ConfigDir {
+    /// Create an empty configuration directory with the specified name in the temporary directory, replacing any
+    /// previous one.
+    pub fn new(name: &str) -> ConfigDir {
+        let path = temp_dir().join("tweetr-testing").join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        ConfigDir { path: path }
+    }
+
+    /// Write the specified apps to `app.toml`.
+    pub fn with_apps(self, apps: &Apps) -> ConfigDir {
+        apps.write(&self.path.join("app.toml")).unwrap();
+        self
+    }
+
+    /// Write the specified users to `users.toml`.
+    pub fn with_users(self, users: Vec<User>) -> ConfigDir {
+        User::write(users, &self.path.join("users.toml")).unwrap();
+        self
+    }
+
+    /// Write the specified queue to `tweets.toml`.
+    pub fn with_tweets(self, tweets: Vec<QueuedTweet>) -> ConfigDir {
+        QueuedTweet::write(tweets, &self.path.join("tweets.toml")).unwrap();
+        self
+    }
+
+    /// Write the specified configuration to `config.toml`.
+    pub fn with_config(self, config: &Config) -> ConfigDir {
+        config.write(&Config::path(&self.path)).unwrap();
+        self
+    }
+
+    /// Get the path to the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the directory as specified to the subsystems, see `Options::config_dir`.
+    pub fn config_dir(&self) -> (String, PathBuf) {
+        (self.path.display().to_string(), self.path.clone())
+    }
+}
+
+impl Drop for ConfigDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,10 +1,10 @@
 use self::super::super::Outcome;
-use self::super::read_toml_file;
+use self::super::{key_error, read_interpolated_toml_file, read_toml_file, write_file};
+use std::collections::BTreeMap;
 use toml::encode_str;
+#[cfg(feature = "network")]
 use egg_mode::Token;
 use std::path::Path;
-use std::io::Write;
-use std::fs::File;
 
 
 /// The tokens needed to authenticate the app itself.
@@ -16,29 +16,70 @@ pub struct AppTokens {
     pub secret: String,
 }
 
+/// All the apps in `app.toml`: the default one, and the named ones users can be bound to instead.
+///
+/// The named apps are stored as an array of tables alongside the default app's tokens:
+///
+/// ```toml
+/// key = "qzuqpwr101q4RtK9mDorI9ndm"
+/// secret = "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5"
+///
+/// [[app]]
+/// name = "client"
+/// key = "GeVFiYk7q8DhUmgMXE0iODrFa"
+/// secret = "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe"
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Apps {
+    /// The app used by users not bound to any other one
+    pub default: AppTokens,
+    /// The apps users can be bound to, by name
+    pub named: BTreeMap<String, AppTokens>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct NamedAppTokens {
+    name: String,
+    key: String,
+    secret: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct AppsForSerialisation {
+    key: String,
+    secret: String,
+    app: Option<Vec<NamedAppTokens>>,
+}
+
+
 impl AppTokens {
     /// Read the application tokens from the specified file.
+    ///
+    /// `${VAR}` references in the values are replaced with the environment variables' values, see `util::interpolate_env()`.
     pub fn read(p: &Path) -> Result<AppTokens, Option<Outcome>> {
-        read_toml_file(p, "application tokens")
+        read_interpolated_toml_file(p, "application tokens")
     }
 
     /// Save the application tokens to the specified file.
-    pub fn write(&self, p: &Path) {
-        File::create(p).unwrap().write_all(encode_str(&self).as_bytes()).unwrap();
+    pub fn write(&self, p: &Path) -> Result<(), Outcome> {
+        write_file(p, &encode_str(&self))
     }
 
     /// Borrows the current key and secret into an `egg_mode::Token`.
+    #[cfg(feature = "network")]
     pub fn raw_token<'a>(&'a self) -> Token<'a> {
         Token::new(&self.key[..], &self.secret[..])
     }
 }
 
+#[cfg(feature = "network")]
 impl<'a> Into<Token<'a>> for AppTokens {
     fn into(self) -> Token<'a> {
         Token::new(self.key, self.secret)
     }
 }
 
+#[cfg(feature = "network")]
 impl<'a> From<Token<'a>> for AppTokens {
     fn from(tkn: Token<'a>) -> AppTokens {
         AppTokens {
@@ -47,3 +88,112 @@ impl<'a> From<Token<'a>> for AppTokens {
         }
     }
 }
+
+impl Apps {
+    /// Read all the apps from the specified file.
+    ///
+    /// Two apps with the same name are an error.
+    ///
+    /// `${VAR}` references in the values are replaced with the environment variables' values, see `util::interpolate_env()`.
+    pub fn read(p: &Path) -> Result<Apps, Option<Outcome>> {
+        Apps::from_serialisation(p, try!(read_interpolated_toml_file(p, "application tokens")))
+    }
+
+    /// Read all the apps from the specified file, leaving the environment variable references in the values as-is, to be
+    /// written back.
+    pub fn read_verbatim(p: &Path) -> Result<Apps, Option<Outcome>> {
+        Apps::from_serialisation(p, try!(read_toml_file(p, "application tokens")))
+    }
+
+    fn from_serialisation(p: &Path, apps: AppsForSerialisation) -> Result<Apps, Option<Outcome>> {
+        let mut named = BTreeMap::new();
+        for (i, app) in apps.app.unwrap_or(vec![]).into_iter().enumerate() {
+            if named.contains_key(&app.name) {
+                return Err(Some(key_error(p, "application tokens", &format!("app.{}.name", i), &format!("duplicate app \"{}\"", app.name))));
+            }
+            named.insert(app.name,
+                         AppTokens {
+                             key: app.key,
+                             secret: app.secret,
+                         });
+        }
+
+        Ok(Apps {
+            default: AppTokens {
+                key: apps.key,
+                secret: apps.secret,
+            },
+            named: named,
+        })
+    }
+
+    /// Save all the apps to the specified file.
+    pub fn write(&self, p: &Path) -> Result<(), Outcome> {
+        let apps = AppsForSerialisation {
+            key: self.default.key.clone(),
+            secret: self.default.secret.clone(),
+            app: if self.named.is_empty() {
+                None
+            } else {
+                Some(self.named
+                    .iter()
+                    .map(|(name, app)| {
+                        NamedAppTokens {
+                            name: name.clone(),
+                            key: app.key.clone(),
+                            secret: app.secret.clone(),
+                        }
+                    })
+                    .collect())
+            },
+        };
+
+        write_file(p, &encode_str(&apps))
+    }
+
+    /// Get the app with the specified name, or the default one if none is specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{AppTokens, Apps};
+    /// # use std::collections::BTreeMap;
+    /// # use tweetr::Outcome;
+    /// let default = AppTokens {
+    ///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+    ///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+    /// };
+    /// let client = AppTokens {
+    ///     key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+    ///     secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+    /// };
+    ///
+    /// let mut named = BTreeMap::new();
+    /// named.insert("client".to_string(), client.clone());
+    /// let apps = Apps {
+    ///     default: default.clone(),
+    ///     named: named,
+    /// };
+    ///
+    /// assert_eq!(apps.get(None), Ok(&default));
+    /// assert_eq!(apps.get(Some("client")), Ok(&client));
+    /// assert_eq!(apps.get(Some("agency")),
+    ///            Err(Outcome::RequiredDataFromSubsystemNonexistant {
+    ///                subsys: "init",
+    ///                desc: "add the app \"agency\"".to_string(),
+    ///            }));
+    /// ```
+    pub fn get(&self, name: Option<&str>) -> Result<&AppTokens, Outcome> {
+        match name {
+            None => Ok(&self.default),
+            Some(name) => {
+                self.named.get(name).ok_or_else(|| {
+                    Outcome::RequiredDataFromSubsystemNonexistant {
+                        subsys: "init",
+                        desc: format!("add the app \"{}\"", name),
+                    }
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Session transcripts, recordings of the interactive prompts and the answers given to them, to troubleshoot onboarding.
+//!
+//! With `--transcript`, everything the `init`, `add-user`, `setup` and `queue-tweet` subsystems print to the standard
+//! output, prompts included, every line read from the standard input, and the error the run fails with, if any, is
+//! appended to the specified file as it happens, so it's there even if the session is interrupted. Answers to prompts for
+//! secrets, like the app's key and secret and the authorisation PIN, are recorded only as how long they were, see
+//! `secret()`, and so are they and other secrets, like users' access tokens, when printed afterwards, see `hide()`.
+//!
+//! The flow of recording a transcript is as follows:
+//!
+//! ```plaintext
+//! ops::transcript::start()
+//! |> ops::transcript::Transcribed::new(), around the standard input and output
+//! |> the subsystem, with ops::transcript::secret() held while prompting for secrets
+//!    |> ops::transcript::hide(), for the other secrets it prints
+//! ```
+
+
+use std::io::{self, BufRead, Read, Write};
+use self::super::super::Outcome;
+use std::cell::RefCell;
+use std::str;
+use std::path::Path;
+use std::fs::{File, OpenOptions};
+use chrono::Local;
+
+
+thread_local! {
+    static TRANSCRIPT: RefCell<Option<State>> = RefCell::new(None);
+}
+
+
+/// A reader or writer whose data is also recorded in the transcript, if one is being recorded.
+///
+/// Data read is recorded as answers, a line at a time, and data written as is.
+#[derive(Debug)]
+pub struct Transcribed<T>(T);
+
+/// While held, answers are recorded as how long they were instead of verbatim, see `secret()`.
+#[derive(Debug)]
+pub struct Secret(());
+
+struct State {
+    file: File,
+    answer: Vec<u8>,
+    secrets: usize,
+    hidden: Vec<String>,
+}
+
+
+/// Start recording the transcript to the specified file, replacing it.
+///
+/// On Unix, the file is made readable only by the user, since it has the tweet drafts in it.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::transcript::{self, Transcribed};
+/// # use std::io::{BufRead, Read, Write};
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-transcript-start-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("transcript.txt");
+///
+/// transcript::start(&tf).unwrap();
+/// let mut input = Transcribed::new(b"qdPD7N8CcPYDKiNv81QWNWaHK\n" as &[u8]);
+/// write!(Transcribed::new(Vec::new()), "App key: ").unwrap();
+/// {
+///     let _secret = transcript::secret();
+///     input.read_line(&mut String::new()).unwrap();
+/// }
+/// transcript::stop();
+///
+/// let mut written = String::new();
+/// File::open(&tf).unwrap().read_to_string(&mut written).unwrap();
+/// assert!(written.starts_with("# tweetr transcript, started "));
+/// assert!(written.ends_with("\nApp key: <redacted, 25 characters>\n"));
+/// ```
+pub fn start(path: &Path) -> Result<(), Outcome> {
+    let mut file = try!(create_private(path).map_err(|e| Outcome::from_io("create", path, e)));
+    try!(writeln!(file, "# tweetr transcript, started {}", Local::now().to_rfc3339()).map_err(|e| Outcome::from_io("write", path, e)));
+    TRANSCRIPT.with(|t| {
+        *t.borrow_mut() = Some(State {
+            file: file,
+            answer: vec![],
+            secrets: 0,
+            hidden: vec![],
+        })
+    });
+    Ok(())
+}
+
+/// Stop recording the transcript, recording the answer being read, if any.
+pub fn stop() {
+    TRANSCRIPT.with(|t| if let Some(mut state) = t.borrow_mut().take() {
+        state.finish_answer();
+    });
+}
+
+/// Record answers as how long they were instead of verbatim until the returned value is dropped.
+///
+/// This covers the answers rejected and prompted for again, too.
+pub fn secret() -> Secret {
+    TRANSCRIPT.with(|t| if let Some(ref mut state) = *t.borrow_mut() {
+        state.secrets += 1;
+    });
+    Secret(())
+}
+
+/// Record the specified secret as how long it was wherever it's printed for the rest of the transcript.
+///
+/// Answers given while `secret()` is held are hidden like this, too.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::transcript::{self, Transcribed};
+/// # use std::io::{Read, Write};
+/// # use std::env::temp_dir;
+/// # use std::fs::{self, File};
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-transcript-hide-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("transcript.txt");
+///
+/// transcript::start(&tf).unwrap();
+/// transcript::hide("8fEBRyQ4NzcqXoS5kvNe9ihhZSWfxq6LRxr1Wm8rZrnLDOELTL");
+/// writeln!(Transcribed::new(Vec::new()), "  Secret: {}", "8fEBRyQ4NzcqXoS5kvNe9ihhZSWfxq6LRxr1Wm8rZrnLDOELTL").unwrap();
+/// transcript::stop();
+///
+/// let mut written = String::new();
+/// File::open(&tf).unwrap().read_to_string(&mut written).unwrap();
+/// assert!(written.ends_with("\n  Secret: <redacted, 50 characters>\n"));
+/// ```
+pub fn hide(secret: &str) {
+    TRANSCRIPT.with(|t| if let Some(ref mut state) = *t.borrow_mut() {
+        state.hide(secret);
+    });
+}
+
+/// Get how an answer is recorded, verbatim or, if secret, as how long it was.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::transcript::redact;
+/// assert_eq!(redact("Abolish the bourgeoisie", false), "Abolish the bourgeoisie");
+/// assert_eq!(redact("1234567", true), "<redacted, 7 characters>");
+/// assert_eq!(redact("", true), "<redacted, 0 characters>");
+/// ```
+pub fn redact(answer: &str, secret: bool) -> String {
+    if secret {
+        let len = answer.chars().count();
+        format!("<redacted, {} character{}>", len, if len == 1 { "" } else { "s" })
+    } else {
+        answer.to_string()
+    }
+}
+
+
+impl<T> Transcribed<T> {
+    /// Record the data read from or written to the specified reader or writer.
+    pub fn new(inner: T) -> Transcribed<T> {
+        Transcribed(inner)
+    }
+
+    /// Get the wrapped reader or writer back.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<W: Write> Write for Transcribed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = try!(self.0.write(buf));
+        TRANSCRIPT.with(|t| if let Some(ref mut state) = *t.borrow_mut() {
+            state.finish_answer();
+            let _ = match str::from_utf8(&buf[..written]) {
+                Ok(data) => {
+                    let data = state.hidden.iter().fold(data.to_string(), |data, secret| data.replace(&secret[..], &redact(secret, true)));
+                    state.file.write_all(data.as_bytes())
+                }
+                Err(_) => state.file.write_all(&buf[..written]),
+            };
+        });
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<R: Read> Read for Transcribed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = try!(self.0.read(buf));
+        record_answer(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Transcribed<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The data's still buffered, so getting it again doesn't read anything
+        if let Ok(buf) = self.0.fill_buf() {
+            record_answer(&buf[..amt]);
+        }
+        self.0.consume(amt)
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        TRANSCRIPT.with(|t| if let Some(ref mut state) = *t.borrow_mut() {
+            state.secrets = state.secrets.saturating_sub(1);
+        });
+    }
+}
+
+impl State {
+    fn finish_answer(&mut self) {
+        if !self.answer.is_empty() {
+            self.answer.push(b'\n');
+            self.record_lines();
+        }
+    }
+
+    fn record_lines(&mut self) {
+        while let Some(idx) = self.answer.iter().position(|&b| b == b'\n') {
+            let line: Vec<_> = self.answer.drain(..idx + 1).collect();
+            let line = String::from_utf8_lossy(&line[..idx]);
+            let line = line.trim_right_matches('\r');
+            let _ = writeln!(self.file, "{}", redact(line, self.secrets != 0));
+            if self.secrets != 0 {
+                self.hide(line);
+            }
+        }
+    }
+
+    fn hide(&mut self, secret: &str) {
+        if !secret.is_empty() && !self.hidden.iter().any(|h| h == secret) {
+            self.hidden.push(secret.to_string());
+        }
+    }
+}
+
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    use std::fs::Permissions;
+
+    let file = try!(OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path));
+    // The mode only applies to new files, a replaced one keeps its own
+    try!(file.set_permissions(Permissions::from_mode(0o600)));
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<File> {
+    OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
+
+fn record_answer(data: &[u8]) {
+    TRANSCRIPT.with(|t| if let Some(ref mut state) = *t.borrow_mut() {
+        state.answer.extend_from_slice(data);
+        state.record_lines();
+    });
+}
@@ -0,0 +1,274 @@
+//! Cleaning up tweets' content before they're posted.
+//!
+//! The steps, each enabled by its key in the `[transform]` table of the global configuration (see `Config::transform`), are,
+//! in the order they're applied in:
+//!
+//!   * `collapse_spaces` - collapse runs of spaces and tabs into a single space, and remove them from the ends of lines,
+//!   * `smart_quotes` - replace typographic quotes and apostrophes with the plain `"` and `'`,
+//!   * `hashtags` - change the case of hashtags to the one in the specified list, one per line, like `#TweetrRelease`,
+//!   * `campaign_tag` - append the specified hashtag, unless already present.
+//!
+//! The steps are applied to all tweets posted, after their settings, see `ops::settings::Effective::apply()`.
+
+
+use self::super::super::Outcome;
+use self::super::Config;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::fs::File;
+use std::path::Path;
+
+
+/// The `[transform]` table in the global configuration, specifying which steps to apply.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Transforms {
+    /// Whether to collapse runs of whitespace. Default: `false`
+    pub collapse_spaces: Option<bool>,
+    /// Whether to replace typographic quotes with plain ones. Default: `false`
+    pub smart_quotes: Option<bool>,
+    /// File containing the hashtags to change the case of hashtags to, one per line, with or without the leading `#`.
+    /// Default: `None`
+    ///
+    /// Relative paths are resolved against the configuration directory.
+    pub hashtags: Option<String>,
+    /// Hashtag to append to all tweets, with or without the leading `#`. Default: `None`
+    pub campaign_tag: Option<String>,
+}
+
+/// A single step of the pipeline.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Step {
+    /// Collapse runs of spaces and tabs into a single space, and remove them from the ends of lines and of the content.
+    CollapseSpaces,
+    /// Replace typographic quotes and apostrophes with plain ones.
+    SmartQuotes,
+    /// Change the case of the hashtags in the specified map, by their lowercased names without the `#`.
+    HashtagCase(BTreeMap<String, String>),
+    /// Append the specified hashtag, unless already present.
+    CampaignTag(String),
+}
+
+/// The steps to apply to tweets' content, in order.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Default)]
+pub struct Pipeline {
+    /// The enabled steps.
+    pub steps: Vec<Step>,
+}
+
+
+impl Step {
+    /// Get the step's name, as its key in the configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::transform::Step;
+    /// assert_eq!(Step::CollapseSpaces.name(), "collapse_spaces");
+    /// assert_eq!(Step::CampaignTag("#tweetr".to_string()).name(), "campaign_tag");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Step::CollapseSpaces => "collapse_spaces",
+            Step::SmartQuotes => "smart_quotes",
+            Step::HashtagCase(_) => "hashtags",
+            Step::CampaignTag(_) => "campaign_tag",
+        }
+    }
+
+    /// Apply the step to the specified content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::transform::Step;
+    /// # use std::collections::BTreeMap;
+    /// assert_eq!(Step::CollapseSpaces.apply("Abolish  the\tbourgeoisie  \nnow "), "Abolish the bourgeoisie\nnow");
+    /// assert_eq!(Step::SmartQuotes.apply("“Abolish the bourgeoisie,” they’d say"), "\"Abolish the bourgeoisie,\" they'd say");
+    ///
+    /// let mut hashtags = BTreeMap::new();
+    /// hashtags.insert("tweetrrelease".to_string(), "TweetrRelease".to_string());
+    /// assert_eq!(Step::HashtagCase(hashtags).apply("v1.4.0 is out! #tweetrrelease, #other"), "v1.4.0 is out! #TweetrRelease, #other");
+    ///
+    /// assert_eq!(Step::CampaignTag("Tweetr".to_string()).apply("v1.4.0 is out!"), "v1.4.0 is out! #Tweetr");
+    /// assert_eq!(Step::CampaignTag("#Tweetr".to_string()).apply("#tweetr v1.4.0 is out!"), "#tweetr v1.4.0 is out!");
+    /// ```
+    pub fn apply(&self, content: &str) -> String {
+        match *self {
+            Step::CollapseSpaces => {
+                content.lines()
+                    .map(|l| l.split(|c| c == ' ' || c == '\t').filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" "))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .trim()
+                    .to_string()
+            }
+            Step::SmartQuotes => {
+                content.chars()
+                    .map(|c| match c {
+                        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{00AB}' | '\u{00BB}' => '"',
+                        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2039}' | '\u{203A}' => '\'',
+                        c => c,
+                    })
+                    .collect()
+            }
+            Step::HashtagCase(ref hashtags) => {
+                let mut result = String::with_capacity(content.len());
+                let mut rest = content;
+                while let Some(idx) = rest.find('#') {
+                    result.push_str(&rest[..idx + 1]);
+                    rest = &rest[idx + 1..];
+
+                    let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+                    match hashtags.get(&rest[..len].to_lowercase()) {
+                        Some(hashtag) => result.push_str(hashtag),
+                        None => result.push_str(&rest[..len]),
+                    }
+                    rest = &rest[len..];
+                }
+                result.push_str(rest);
+                result
+            }
+            Step::CampaignTag(ref tag) => {
+                let tag = format!("#{}", tag.trim_left_matches('#'));
+                if content.split_whitespace()
+                    .any(|w| w.trim_right_matches(|c: char| !c.is_alphanumeric() && c != '_').to_lowercase() == tag.to_lowercase()) {
+                    content.to_string()
+                } else {
+                    format!("{} {}", content, tag)
+                }
+            }
+        }
+    }
+}
+
+impl Pipeline {
+    /// Apply all steps to the specified content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::transform::{Pipeline, Step};
+    /// let pipeline = Pipeline { steps: vec![Step::CollapseSpaces, Step::CampaignTag("tweetr".to_string())] };
+    /// assert_eq!(pipeline.apply("Abolish  the bourgeoisie "), "Abolish the bourgeoisie #tweetr");
+    /// assert_eq!(Pipeline::default().apply("Abolish  the bourgeoisie "), "Abolish  the bourgeoisie ");
+    /// ```
+    pub fn apply(&self, content: &str) -> String {
+        self.steps.iter().fold(content.to_string(), |content, step| step.apply(&content))
+    }
+
+    /// Get the content after each step that changes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::transform::{Pipeline, Step};
+    /// let pipeline = Pipeline { steps: vec![Step::CollapseSpaces, Step::SmartQuotes, Step::CampaignTag("tweetr".to_string())] };
+    /// assert_eq!(pipeline.preview("Abolish  the bourgeoisie"),
+    ///            vec![(&Step::CollapseSpaces, "Abolish the bourgeoisie".to_string()),
+    ///                 (&Step::CampaignTag("tweetr".to_string()), "Abolish the bourgeoisie #tweetr".to_string())]);
+    /// ```
+    pub fn preview(&self, content: &str) -> Vec<(&Step, String)> {
+        let mut content = content.to_string();
+        let mut changes = vec![];
+        for step in &self.steps {
+            let transformed = step.apply(&content);
+            if transformed != content {
+                changes.push((step, transformed.clone()));
+                content = transformed;
+            }
+        }
+        changes
+    }
+}
+
+
+/// Get the pipeline enabled in the specified configuration, reading the hashtags from the file relative to the specified
+/// configuration directory.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::transform::{self, Pipeline, Step, Transforms};
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::Config;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-transform-pipeline");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("hashtags")).unwrap().write_all(b"#TweetrRelease\n\nAbolishTheBourgeoisie\n").unwrap();
+///
+/// assert_eq!(transform::pipeline(&Config::default(), &tf), Ok(Pipeline::default()));
+///
+/// let config = Config {
+///     transform: Some(Transforms {
+///         collapse_spaces: Some(true),
+///         smart_quotes: Some(false),
+///         hashtags: Some("hashtags".to_string()),
+///         campaign_tag: None,
+///     }),
+///     ..Config::default()
+/// };
+/// let pipeline = transform::pipeline(&config, &tf).unwrap();
+/// assert_eq!(pipeline.steps.len(), 2);
+/// assert_eq!(pipeline.steps[0], Step::CollapseSpaces);
+/// assert_eq!(pipeline.apply("#abolishthebourgeoisie  #TWEETRRELEASE"), "#AbolishTheBourgeoisie #TweetrRelease");
+/// ```
+pub fn pipeline(config: &Config, config_dir: &Path) -> Result<Pipeline, Outcome> {
+    let transforms = match config.transform {
+        Some(ref transforms) => transforms,
+        None => return Ok(Pipeline::default()),
+    };
+
+    let mut steps = vec![];
+    if transforms.collapse_spaces.unwrap_or(false) {
+        steps.push(Step::CollapseSpaces);
+    }
+    if transforms.smart_quotes.unwrap_or(false) {
+        steps.push(Step::SmartQuotes);
+    }
+    if let Some(ref hashtags) = transforms.hashtags {
+        let path = config_dir.join(hashtags);
+        let lines = try!(File::open(&path)
+            .and_then(|f| BufReader::new(f).lines().collect::<Result<Vec<_>, _>>())
+            .map_err(|e| Outcome::PreflightFailed(vec![format!("couldn't read the hashtags from {}: {}", path.display(), e)])));
+        steps.push(Step::HashtagCase(lines.iter()
+            .map(|l| l.trim().trim_left_matches('#'))
+            .filter(|l| !l.is_empty())
+            .map(|l| (l.to_lowercase(), l.to_string()))
+            .collect()));
+    }
+    if let Some(ref tag) = transforms.campaign_tag {
+        steps.push(Step::CampaignTag(tag.clone()));
+    }
+
+    Ok(Pipeline { steps: steps })
+}
+
+/// Print how the specified pipeline changes the specified content, step by step.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::transform::{self, Pipeline, Step};
+/// let pipeline = Pipeline { steps: vec![Step::CollapseSpaces, Step::SmartQuotes] };
+///
+/// let mut out = Vec::new();
+/// transform::print_preview(&mut out, &pipeline, "Abolish  the bourgeoisie");
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Before posting, the tweet will be changed by:\n  collapse_spaces: \"Abolish the bourgeoisie\"\n");
+///
+/// let mut out = Vec::new();
+/// transform::print_preview(&mut out, &pipeline, "Abolish the bourgeoisie");
+/// assert_eq!(String::from_utf8(out).unwrap(), "Before posting, the tweet won't be changed.\n");
+/// ```
+pub fn print_preview<W: Write>(output: &mut W, pipeline: &Pipeline, content: &str) {
+    let changes = pipeline.preview(content);
+    if changes.is_empty() {
+        writeln!(output, "Before posting, the tweet won't be changed.").unwrap();
+    } else {
+        writeln!(output, "Before posting, the tweet will be changed by:").unwrap();
+        for (step, content) in changes {
+            writeln!(output, "  {}: \"{}\"", step.name(), content).unwrap();
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! This module contains the functions used only by the `tui` subsystem.
+//!
+//! The interface itself is only available with the `tui` feature, but the state it operates on isn't,
+//! to keep it usable (and testable) without a terminal.
+//!
+//! The flow of the `tui` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::QueuedTweet::read_recovering()
+//! |> ops::archive::read_all()
+//! |> ops::tui::State::new()
+//! |> ops::tui::run()
+//! |> ops::QueuedTweet::write_with_broken()
+//! ```
+
+
+use chrono::{DateTime, FixedOffset};
+use self::super::{queue_tweet, schedule};
+use self::super::{QueuedTweet, Anchor};
+use self::super::settings::Settings;
+use std::collections::BTreeMap;
+#[cfg(feature = "tui")]
+use self::super::hooks::{self, Hook};
+#[cfg(feature = "tui")]
+use self::super::l10n::format_datetime;
+#[cfg(feature = "tui")]
+use chrono::Local;
+#[cfg(feature = "tui")]
+use std::path::Path;
+#[cfg(feature = "tui")]
+use std::io::{self, Write};
+
+
+/// The part of the screen being interacted with.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Pane {
+    /// The unposted tweets.
+    Queue,
+    /// The posted tweets.
+    Archive,
+    /// The box for writing new tweets and editing queued ones.
+    Compose,
+}
+
+/// The field of the compose box being written to.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Field {
+    /// The author to post on behalf of.
+    Author,
+    /// The time to post at, in any format accepted by `queue_tweet::parse_time()` or `Anchor::parse()`.
+    Time,
+    /// The tweet's content.
+    Content,
+}
+
+/// The contents of the compose box.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Compose {
+    /// The author, as written.
+    pub author: String,
+    /// The time, as written.
+    pub time: String,
+    /// The content, as written.
+    pub content: String,
+    /// The field being written to.
+    pub field: Field,
+    /// The index of the queued tweet being edited, or `None` for a new one.
+    pub editing: Option<usize>,
+}
+
+/// The state of the interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct State {
+    /// The whole queue, posted tweets included.
+    pub tweets: Vec<QueuedTweet>,
+    /// The posted tweets, archived and still in the queue, by when they were posted, only listed.
+    pub posted: Vec<QueuedTweet>,
+    /// The pane being interacted with.
+    pub pane: Pane,
+    /// The position of the selected tweet in the active pane's list.
+    pub selected: usize,
+    /// The compose box.
+    pub compose: Compose,
+    /// The message to display in the status line, if any.
+    pub message: Option<String>,
+    /// Whether the queue was changed and needs to be written back.
+    pub dirty: bool,
+}
+
+
+impl Field {
+    /// Get the field after this one, wrapping around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::tui::Field;
+    /// assert_eq!(Field::Author.next(), Field::Time);
+    /// assert_eq!(Field::Content.next(), Field::Author);
+    /// ```
+    pub fn next(&self) -> Field {
+        match *self {
+            Field::Author => Field::Time,
+            Field::Time => Field::Content,
+            Field::Content => Field::Author,
+        }
+    }
+}
+
+impl Compose {
+    /// Get the text of the specified field.
+    pub fn text(&self, field: Field) -> &str {
+        match field {
+            Field::Author => &self.author,
+            Field::Time => &self.time,
+            Field::Content => &self.content,
+        }
+    }
+
+    /// Get the text of the field being written to, for modification.
+    pub fn text_mut(&mut self) -> &mut String {
+        match self.field {
+            Field::Author => &mut self.author,
+            Field::Time => &mut self.time,
+            Field::Content => &mut self.content,
+        }
+    }
+}
+
+impl Default for Compose {
+    fn default() -> Compose {
+        Compose {
+            author: String::new(),
+            time: String::new(),
+            content: String::new(),
+            field: Field::Author,
+            editing: None,
+        }
+    }
+}
+
+impl State {
+    /// Start interacting with the specified queue and archived tweets, with the first queued tweet selected.
+    pub fn new(tweets: Vec<QueuedTweet>, archived: Vec<QueuedTweet>) -> State {
+        let mut posted = archived;
+        posted.extend(tweets.iter().filter(|t| t.id.is_some()).cloned());
+        posted.sort_by_key(|t| t.time_posted);
+
+        State {
+            tweets: tweets,
+            posted: posted,
+            pane: Pane::Queue,
+            selected: 0,
+            compose: Compose::default(),
+            message: None,
+            dirty: false,
+        }
+    }
+
+    /// Get the indices of the tweets listed in the specified pane, in order.
+    ///
+    /// The queue pane lists the unposted tweets, by their indices in `tweets`, the archive pane all posted ones, by their
+    /// indices in `posted`, and the compose pane nothing.
+    pub fn listed(&self, pane: Pane) -> Vec<usize> {
+        match pane {
+            Pane::Queue => self.tweets.iter().enumerate().filter(|&(_, t)| t.id.is_none()).map(|(i, _)| i).collect(),
+            Pane::Archive => (0..self.posted.len()).collect(),
+            Pane::Compose => vec![],
+        }
+    }
+
+    /// Get the index of the selected tweet, if the queue or archive pane is active and isn't empty.
+    pub fn selected_index(&self) -> Option<usize> {
+        self.listed(self.pane).get(self.selected).cloned()
+    }
+
+    /// Switch to the pane after the current one, queue to archive to compose, resetting the selection.
+    pub fn next_pane(&mut self) {
+        self.pane = match self.pane {
+            Pane::Queue => Pane::Archive,
+            Pane::Archive => Pane::Compose,
+            Pane::Compose => Pane::Queue,
+        };
+        self.selected = 0;
+    }
+
+    /// Select the next tweet in the current pane, if any.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.listed(self.pane).len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select the previous tweet in the current pane, if any.
+    pub fn select_prev(&mut self) {
+        if self.selected != 0 {
+            self.selected -= 1;
+        }
+    }
+
+    /// Remove the selected tweet from the queue, returning it.
+    ///
+    /// Posted tweets can't be deleted.
+    pub fn delete_selected(&mut self) -> Option<QueuedTweet> {
+        match (self.pane, self.selected_index()) {
+            (Pane::Queue, Some(idx)) => {
+                let tweet = self.tweets.remove(idx);
+                let listed = self.listed(Pane::Queue).len();
+                if self.selected >= listed && self.selected != 0 {
+                    self.selected = listed.saturating_sub(1);
+                }
+                if let Some(editing) = self.compose.editing {
+                    if editing == idx {
+                        self.compose.editing = None;
+                    } else if editing > idx {
+                        self.compose.editing = Some(editing - 1);
+                    }
+                }
+
+                self.dirty = true;
+                self.message = Some(format!("Deleted tweet \"{}\"", tweet.content));
+                Some(tweet)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pause the selected queued tweet if it isn't, unpause it otherwise.
+    pub fn toggle_pause_selected(&mut self) {
+        if let (Pane::Queue, Some(idx)) = (self.pane, self.selected_index()) {
+            let tweet = &mut self.tweets[idx];
+            tweet.paused = !tweet.paused;
+
+            self.dirty = true;
+            self.message = Some(format!("{} tweet \"{}\"", if tweet.paused { "Paused" } else { "Unpaused" }, tweet.content));
+        }
+    }
+
+    /// Load the selected queued tweet into the compose box for editing and switch to it.
+    pub fn edit_selected(&mut self) {
+        if let (Pane::Queue, Some(idx)) = (self.pane, self.selected_index()) {
+            self.compose = Compose {
+                author: self.tweets[idx].author.clone(),
+                time: match self.tweets[idx].after {
+                    Some(ref after) => after.to_string(),
+                    None => self.tweets[idx].time.to_rfc3339(),
+                },
+                content: self.tweets[idx].content.clone(),
+                field: Field::Content,
+                editing: Some(idx),
+            };
+            self.pane = Pane::Compose;
+            self.selected = 0;
+        }
+    }
+
+    /// Make a tweet of the contents of the compose box, with relative times relative to the specified time.
+    ///
+    /// When editing, all data not in the compose box is kept from the edited tweet.
+    ///
+    /// Tweets scheduled relative to others are scheduled relative to the rest of the queue.
+    pub fn composed(&self, now: DateTime<FixedOffset>) -> Result<QueuedTweet, String> {
+        if self.compose.author.trim().is_empty() {
+            return Err("No author".to_string());
+        }
+        if self.compose.content.trim().is_empty() {
+            return Err("No content".to_string());
+        }
+        let after = match Anchor::parse(self.compose.time.trim()) {
+            Some(after) => Some(try!(after.map_err(|e| format!("\"{}\" is not a valid relative time: {}", self.compose.time.trim(), e)))),
+            None => None,
+        };
+        let time = match after {
+            Some(_) => now,
+            None => {
+                try!(queue_tweet::parse_time(self.compose.time.trim(), now)
+                    .and_then(|t| schedule::within_horizon(t, now).map_err(|e| format!("\"{}\" is {}", self.compose.time.trim(), e))))
+            }
+        };
+
+        let mut tweet = match self.compose.editing {
+            Some(idx) => self.tweets[idx].clone(),
+            None => {
+                QueuedTweet {
+                    author: String::new(),
+                    time: time,
+                    content: String::new(),
+                    media: vec![],
+                    thread: None,
+                    in_reply_to: None,
+                    extra_params: BTreeMap::new(),
+                    variants: BTreeMap::new(),
+                    content_warning: None,
+                    coordinates: None,
+                    place_id: None,
+                    reply_settings: None,
+                    name: None,
+                    after: None,
+                    paused: false,
+                    blocked: None,
+                    settings: Settings::default(),
+                    time_posted: None,
+                    id: None,
+                    app: None,
+                    retries: 0,
+                }
+            }
+        };
+        tweet.author = self.compose.author.trim().to_string();
+        tweet.time = time;
+        tweet.after = after;
+        tweet.content = self.compose.content.clone();
+
+        if tweet.after.is_none() {
+            return Ok(tweet);
+        }
+
+        let others: Vec<_> = self.tweets
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != self.compose.editing)
+            .map(|(_, t)| t.clone())
+            .collect();
+        let mut tweets = vec![tweet];
+        match QueuedTweet::schedule(&mut tweets, &others).into_iter().next() {
+            Some((_, _, e)) => Err(format!("Can't schedule the tweet: {}", e)),
+            None => Ok(tweets.remove(0)),
+        }
+    }
+
+    /// Put the specified composed tweet in place of the edited one or at the end of the queue, and clear the compose box.
+    ///
+    /// Tweets scheduled relative to the edited one are rescheduled along with it.
+    pub fn submit(&mut self, tweet: QueuedTweet) {
+        self.message = Some(match self.compose.editing {
+            Some(idx) => {
+                self.tweets[idx] = tweet;
+                "Saved the edited tweet".to_string()
+            }
+            None => {
+                self.tweets.push(tweet);
+                "Queued the tweet".to_string()
+            }
+        });
+
+        QueuedTweet::schedule(&mut self.tweets, &[]);
+        self.compose = Compose::default();
+        self.dirty = true;
+    }
+}
+
+
+/// Run the interface on the specified state until the user quits, running hooks from the specified directory.
+///
+/// The keys are:
+///
+///   * `Tab` - switch panes
+///   * `Up`/`Down` or `k`/`j` - select tweets in the queue and archive panes, fields in the compose pane
+///   * `e` or `Enter` - edit the selected queued tweet
+///   * `d` - delete the selected queued tweet
+///   * `p` - pause or unpause the selected queued tweet
+///   * `Ctrl-S` - queue the composed tweet, after running the pre-queue hook
+///   * `Esc` - clear the compose box
+///   * `q` - quit
+#[cfg(feature = "tui")]
+pub fn run(state: &mut State, hooks_dir: &Path) -> io::Result<()> {
+    use termion::raw::IntoRawMode;
+    use termion::input::TermRead;
+    use termion::event::Key;
+    use termion::cursor;
+
+    let stdin = io::stdin();
+    let mut stdout = try!(io::stdout().into_raw_mode());
+    try!(write!(stdout, "{}", cursor::Hide));
+    try!(draw(&mut stdout, state));
+
+    for key in stdin.keys() {
+        state.message = None;
+        match (state.pane, try!(key)) {
+            (_, Key::Char('\t')) => state.next_pane(),
+
+            (Pane::Compose, Key::Ctrl('s')) => {
+                let now = Local::now();
+                match state.composed(now.with_timezone(now.offset())).map_err(|e| e.to_string()).and_then(|tweet| {
+                    let mut err = Vec::new();
+                    hooks::run(hooks_dir, Hook::PreQueue, tweet).map_err(|out| {
+                        out.print_error(&mut err);
+                        String::from_utf8_lossy(&err).trim().to_string()
+                    })
+                }) {
+                    Ok(tweet) => state.submit(tweet),
+                    Err(e) => state.message = Some(e),
+                }
+            }
+            (Pane::Compose, Key::Esc) => state.compose = Compose::default(),
+            (Pane::Compose, Key::Up) => {
+                state.compose.field = state.compose.field.next().next();
+            }
+            (Pane::Compose, Key::Down) => state.compose.field = state.compose.field.next(),
+            (Pane::Compose, Key::Char('\n')) => {
+                if state.compose.field == Field::Content {
+                    state.compose.content.push('\n');
+                } else {
+                    state.compose.field = state.compose.field.next();
+                }
+            }
+            (Pane::Compose, Key::Char(c)) => state.compose.text_mut().push(c),
+            (Pane::Compose, Key::Backspace) => {
+                state.compose.text_mut().pop();
+            }
+
+            (_, Key::Char('q')) => break,
+            (_, Key::Up) |
+            (_, Key::Char('k')) => state.select_prev(),
+            (_, Key::Down) |
+            (_, Key::Char('j')) => state.select_next(),
+            (_, Key::Char('e')) |
+            (_, Key::Char('\n')) => state.edit_selected(),
+            (_, Key::Char('d')) => {
+                state.delete_selected();
+            }
+            (_, Key::Char('p')) => state.toggle_pause_selected(),
+            _ => (),
+        }
+
+        try!(draw(&mut stdout, state));
+    }
+
+    write!(stdout, "{}{}{}", ::termion::clear::All, cursor::Goto(1, 1), cursor::Show)
+}
+
+
+#[cfg(feature = "tui")]
+fn draw<W: Write>(out: &mut W, state: &State) -> io::Result<()> {
+    use termion::{clear, cursor, style, terminal_size};
+
+    let (width, height) = try!(terminal_size());
+    let (width, height) = (width as usize, height as usize);
+    // Two list panes, each with a title line, then the three compose lines with a title and the status line
+    let list_height = height.saturating_sub(4 + 2 + 1) / 2;
+
+    try!(write!(out, "{}{}", clear::All, cursor::Goto(1, 1)));
+    let mut line = 1;
+    for &(pane, title) in &[(Pane::Queue, "Queue"), (Pane::Archive, "Archive")] {
+        let listed = state.listed(pane);
+        let active = state.pane == pane;
+        try!(write!(out, "{}{}{} ({}){}", cursor::Goto(1, line as u16), if active { style::Bold.to_string() } else { String::new() }, title, listed.len(), style::Reset));
+        line += 1;
+
+        let first = if active && state.selected >= list_height { state.selected + 1 - list_height } else { 0 };
+        for (pos, &idx) in listed.iter().enumerate().skip(first).take(list_height) {
+            let tweet = if pane == Pane::Archive { &state.posted[idx] } else { &state.tweets[idx] };
+            let entry = format!("{} {} {}{}: {}",
+                                if active && pos == state.selected { '>' } else { ' ' },
+                                format_datetime(&tweet.time),
+                                tweet.author,
+                                if tweet.paused {
+                                    " (paused)"
+                                } else if tweet.blocked.is_some() {
+                                    " (blocked)"
+                                } else {
+                                    ""
+                                },
+                                tweet.content.replace('\n', " "));
+            try!(write!(out, "{}{}", cursor::Goto(1, line as u16), entry.chars().take(width).collect::<String>()));
+            line += 1;
+        }
+        line = line.max(if pane == Pane::Queue { 2 + list_height } else { 3 + 2 * list_height });
+    }
+
+    try!(write!(out,
+                "{}{}Compose{}{}",
+                cursor::Goto(1, line as u16),
+                if state.pane == Pane::Compose { style::Bold.to_string() } else { String::new() },
+                if state.compose.editing.is_some() { " (editing)" } else { "" },
+                style::Reset));
+    line += 1;
+    for &(field, label) in &[(Field::Author, "Author"), (Field::Time, "Time"), (Field::Content, "Content")] {
+        let entry = format!("{} {}: {}",
+                            if state.pane == Pane::Compose && state.compose.field == field { '>' } else { ' ' },
+                            label,
+                            state.compose.text(field).replace('\n', "⏎"));
+        try!(write!(out, "{}{}", cursor::Goto(1, line as u16), entry.chars().take(width).collect::<String>()));
+        line += 1;
+    }
+
+    try!(write!(out,
+                "{}{}",
+                cursor::Goto(1, height as u16),
+                state.message.as_ref().map(|m| &m[..]).unwrap_or("Tab: switch pane, e: edit, d: delete, p: pause, ^S: queue, q: quit")));
+    out.flush()
+}
@@ -0,0 +1,301 @@
+//! This module contains the functions used only by the `update` subsystem.
+//!
+//! The flow of the `update` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::update::latest_release()
+//! |> ops::update::is_newer()
+//! |> ops::update::Release::binary()
+//! |> ops::update::download(), for the binary and its signature
+//! |> ops::update::verify_signature()
+//! |> ops::update::replace_executable()
+//! ```
+//!
+//! Each release has a prebuilt binary for each platform, named by `asset_name()`, and its Ed25519 signature, made by CI
+//! with the key `RELEASE_KEY` is the public half of, base64-encoded, in an asset named like the binary, but suffixed with
+//! `.sig`. Since the key's pinned here, rather than published alongside the binary, whoever can replace a release's assets
+//! can't make an update install anything else. The signatures are made by `examples/sign-release.rs`, which also prints the
+//! public key to pin; until one is, updates are refused.
+
+
+use hyper::header::{Accept, UserAgent, qitem};
+use std::collections::BTreeMap;
+use rustc_serialize::json::Json;
+use self::super::super::Outcome;
+use std::fs::{self, File};
+use rustc_serialize::base64::FromBase64;
+use crypto::ed25519;
+use std::io::{Read, Write};
+use self::super::network;
+use std::env::consts;
+use std::path::Path;
+
+
+/// The GitHub API endpoint describing the latest release.
+pub static RELEASES_URL: &'static str = "https://api.github.com/repos/nabijaczleweli/tweetr/releases/latest";
+
+/// The base64-encoded Ed25519 public key the released binaries are signed with, if one's pinned.
+pub static RELEASE_KEY: Option<&'static str> = None;
+
+
+/// A published release.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Release {
+    /// The release's version, as tagged, like `"v0.2.1"`.
+    pub version: String,
+    /// The URLs to download the release's assets from, by their names.
+    pub assets: BTreeMap<String, String>,
+}
+
+impl Release {
+    /// Parse a release from the specified GitHub API response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rustc_serialize;
+    /// # extern crate tweetr;
+    /// # use tweetr::ops::update::Release;
+    /// # use rustc_serialize::json::Json;
+    /// # fn main() {
+    /// let release = Release::from_json(&Json::from_str(r#"{
+    ///     "tag_name": "v0.2.1",
+    ///     "assets": [{
+    ///         "name": "tweetr-v0.2.1.exe",
+    ///         "browser_download_url": "https://github.com/nabijaczleweli/tweetr/releases/download/v0.2.1/tweetr-v0.2.1.exe"
+    ///     }]
+    /// }"#).unwrap()).unwrap();
+    /// assert_eq!(release.version, "v0.2.1");
+    /// assert_eq!(release.assets["tweetr-v0.2.1.exe"],
+    ///            "https://github.com/nabijaczleweli/tweetr/releases/download/v0.2.1/tweetr-v0.2.1.exe");
+    ///
+    /// assert_eq!(Release::from_json(&Json::from_str(r#"{"message": "Not Found"}"#).unwrap()),
+    ///            Err("no tag_name in {\"message\":\"Not Found\"}".to_string()));
+    /// # }
+    /// ```
+    pub fn from_json(json: &Json) -> Result<Release, String> {
+        let version = try!(json.find("tag_name").and_then(Json::as_string).ok_or_else(|| format!("no tag_name in {}", json)));
+        let assets = try!(json.find("assets").and_then(Json::as_array).ok_or_else(|| format!("no assets in {}", json)));
+
+        Ok(Release {
+            version: version.to_string(),
+            assets: try!(assets.iter()
+                .map(|asset| match (asset.find("name").and_then(Json::as_string),
+                                    asset.find("browser_download_url").and_then(Json::as_string)) {
+                    (Some(name), Some(url)) => Ok((name.to_string(), url.to_string())),
+                    _ => Err(format!("invalid asset {}", asset)),
+                })
+                .collect()),
+        })
+    }
+
+    /// Get the URLs of the binary for this platform and of its signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::update::{self, Release};
+    /// # use std::collections::BTreeMap;
+    /// let name = update::asset_name("v0.2.1");
+    /// let mut assets = BTreeMap::new();
+    /// assets.insert(name.clone(), "https://example.com/binary".to_string());
+    ///
+    /// let mut release = Release {
+    ///     version: "v0.2.1".to_string(),
+    ///     assets: assets,
+    /// };
+    /// assert_eq!(release.binary(), Err(format!("v0.2.1 has no signature for {}", name)));
+    ///
+    /// release.assets.insert(format!("{}.sig", name), "https://example.com/signature".to_string());
+    /// assert_eq!(release.binary(), Ok(("https://example.com/binary", "https://example.com/signature")));
+    /// ```
+    pub fn binary(&self) -> Result<(&str, &str), String> {
+        let name = asset_name(&self.version);
+        let binary = try!(self.assets.get(&name).ok_or_else(|| format!("{} has no {} for this platform", self.version, name)));
+        let signature = try!(self.assets.get(&format!("{}.sig", name)).ok_or_else(|| format!("{} has no signature for {}", self.version, name)));
+        Ok((binary, signature))
+    }
+}
+
+
+/// Get the name of the prebuilt binary of the specified version for this platform.
+///
+/// Windows binaries are named `tweetr-<version>.exe`, and the rest `tweetr-<version>-<architecture>-<OS>`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::update;
+/// if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+///     assert_eq!(update::asset_name("v0.2.1"), "tweetr-v0.2.1-x86_64-linux");
+/// } else if cfg!(windows) {
+///     assert_eq!(update::asset_name("v0.2.1"), "tweetr-v0.2.1.exe");
+/// }
+/// ```
+pub fn asset_name(version: &str) -> String {
+    if cfg!(windows) {
+        format!("tweetr-{}.exe", version)
+    } else {
+        format!("tweetr-{}-{}-{}", version, consts::ARCH, consts::OS)
+    }
+}
+
+/// Parse the specified `major.minor.patch` version, optionally prefixed with `v`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::update;
+/// assert_eq!(update::parse_version("v0.2.1"), Some((0, 2, 1)));
+/// assert_eq!(update::parse_version("1.10.0"), Some((1, 10, 0)));
+/// assert_eq!(update::parse_version("v1.0"), None);
+/// assert_eq!(update::parse_version("latest"), None);
+/// ```
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = if version.starts_with('v') {
+        &version[1..]
+    } else {
+        version
+    };
+
+    let parts: Vec<_> = version.split('.').map(|p| p.parse().ok()).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    match (parts[0], parts[1], parts[2]) {
+        (Some(major), Some(minor), Some(patch)) => Some((major, minor, patch)),
+        _ => None,
+    }
+}
+
+/// Check whether the specified released version is newer than the specified current one.
+///
+/// Versions that can't be parsed are never newer.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::update;
+/// assert!(update::is_newer("v0.10.0", "0.2.1"));
+/// assert!(!update::is_newer("v0.2.1", "0.2.1"));
+/// assert!(!update::is_newer("nightly", "0.2.1"));
+/// ```
+pub fn is_newer(released: &str, current: &str) -> bool {
+    match (parse_version(released), parse_version(current)) {
+        (Some(released), Some(current)) => released > current,
+        _ => false,
+    }
+}
+
+/// Get the latest release from `RELEASES_URL`.
+pub fn latest_release() -> Result<Release, Outcome> {
+    let desc = "checking for updates";
+    network::run(desc, move || {
+        let mut resp = try!(network::client()
+            .get(RELEASES_URL)
+            .header(UserAgent(format!("tweetr/{}", env!("CARGO_PKG_VERSION"))))
+            .header(Accept(vec![qitem("application/vnd.github.v3+json".parse().unwrap())]))
+            .send()
+            .map_err(|e| network::request_error(desc, e, Outcome::UpdateFailed)));
+
+        let mut resp_body = String::new();
+        try!(resp.read_to_string(&mut resp_body).map_err(|e| network::io_error(desc, e, Outcome::UpdateFailed)));
+        if !resp.status.is_success() {
+            return Err(Outcome::UpdateFailed(format!("{} returned {}", RELEASES_URL, resp.status)));
+        }
+
+        let json = try!(Json::from_str(&resp_body).map_err(|e| Outcome::UpdateFailed(format!("{} (\"{}\")", e, resp_body))));
+        Release::from_json(&json).map_err(Outcome::UpdateFailed)
+    })
+}
+
+/// Download the specified URL, giving up after the total timeout, like other requests.
+pub fn download(url: &str) -> Result<Vec<u8>, Outcome> {
+    let desc = format!("downloading {}", url);
+    let url = url.to_string();
+    network::run(&desc.clone(), move || {
+        let mut resp = try!(network::client()
+            .get(&url)
+            .header(UserAgent(format!("tweetr/{}", env!("CARGO_PKG_VERSION"))))
+            .send()
+            .map_err(|e| network::request_error(&desc, e, Outcome::UpdateFailed)));
+        if !resp.status.is_success() {
+            return Err(Outcome::UpdateFailed(format!("{} returned {}", url, resp.status)));
+        }
+
+        let mut data = vec![];
+        try!(resp.read_to_end(&mut data).map_err(|e| network::io_error(&desc, e, Outcome::UpdateFailed)));
+        Ok(data)
+    })
+}
+
+/// Check the specified data against the specified base64-encoded signature, made with the key the specified base64-encoded
+/// public key is the public half of.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::update;
+/// let key = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+/// assert_eq!(update::verify_signature(b"tweetr v0.2.2", "c2lnbmF0dXJl", key),
+///            Err("invalid signature: 9 bytes long, not 64".to_string()));
+/// assert_eq!(update::verify_signature(b"tweetr v0.2.2", "c2lnbmF0dXJl", "a2V5"),
+///            Err("invalid key: 3 bytes long, not 32".to_string()));
+/// assert!(update::verify_signature(b"tweetr v0.2.2", "not base64", key).is_err());
+/// ```
+pub fn verify_signature(data: &[u8], signature: &str, key: &str) -> Result<(), String> {
+    let key = try!(key.from_base64().map_err(|e| format!("invalid key: {}", e)));
+    let signature = try!(signature.trim().from_base64().map_err(|e| format!("invalid signature: {}", e)));
+    if key.len() != 32 {
+        return Err(format!("invalid key: {} bytes long, not 32", key.len()));
+    }
+    if signature.len() != 64 {
+        return Err(format!("invalid signature: {} bytes long, not 64", signature.len()));
+    }
+
+    if ed25519::verify(data, &key, &signature) {
+        Ok(())
+    } else {
+        Err("signature mismatch: not signed with the release key".to_string())
+    }
+}
+
+/// Replace the specified executable with the specified data.
+///
+/// The data is written next to the executable first, with its permissions, and then moved over it, so an interrupted update
+/// leaves the old version in place. Windows doesn't allow replacing a running executable, so the old one is moved out of the
+/// way to `<executable>.old` there first.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::update;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use std::io::{Read, Write};
+/// let td = temp_dir().join("tweetr-doctest").join("ops-update-replace_executable");
+/// fs::create_dir_all(&td).unwrap();
+/// File::create(td.join("tweetr")).unwrap().write_all(b"v0.2.1").unwrap();
+///
+/// update::replace_executable(&td.join("tweetr"), b"v0.2.2").unwrap();
+/// let mut data = String::new();
+/// File::open(td.join("tweetr")).unwrap().read_to_string(&mut data).unwrap();
+/// assert_eq!(data, "v0.2.2");
+/// ```
+pub fn replace_executable(exe: &Path, data: &[u8]) -> Result<(), String> {
+    let permissions = try!(fs::metadata(exe).map_err(|e| format!("couldn't read {}: {}", exe.display(), e))).permissions();
+    let new = exe.with_file_name(format!(".{}.new", exe.file_name().unwrap().to_string_lossy()));
+
+    try!(File::create(&new)
+        .and_then(|mut f| f.write_all(data).and_then(|_| f.sync_all()))
+        .and_then(|_| fs::set_permissions(&new, permissions))
+        .map_err(|e| format!("couldn't write {}: {}", new.display(), e)));
+
+    if cfg!(windows) {
+        let old = exe.with_file_name(format!("{}.old", exe.file_name().unwrap().to_string_lossy()));
+        let _ = fs::remove_file(&old);
+        try!(fs::rename(exe, &old).map_err(|e| format!("couldn't move {} to {}: {}", exe.display(), old.display(), e)));
+    }
+    fs::rename(&new, exe).map_err(|e| format!("couldn't move {} to {}: {}", new.display(), exe.display(), e))
+}
@@ -1,11 +1,13 @@
 use self::super::super::Outcome;
-use self::super::read_toml_file;
+use self::super::backend::{Discord, Telegram};
+use self::super::settings::Settings;
+use self::super::lint::Lint;
+use self::super::{key_error, read_interpolated_toml_file, read_toml_file, replace_file};
 use std::cmp::Ordering;
 use toml::encode_str;
+#[cfg(feature = "network")]
 use egg_mode::Token;
 use std::path::Path;
-use std::io::Write;
-use std::fs::File;
 
 
 /// All user data required to connect to the Twitter API.
@@ -19,6 +21,38 @@ pub struct User {
     pub access_token_key: String,
     /// The key part of the access token (the one actually used to access the API)
     pub access_token_secret: String,
+    /// Name of the app the access token was issued for, if not the default one, see `Apps`
+    pub app: Option<String>,
+    /// Token of the Telegram bot to mirror the user's tweets with, if any
+    pub telegram_bot_token: Option<String>,
+    /// ID or `@username` of the Telegram chat or channel to mirror the user's tweets to, if any
+    pub telegram_chat_id: Option<String>,
+    /// URL of the Discord webhook to mirror the user's tweets to, if any
+    pub discord_webhook: Option<String>,
+    /// The UTC offset the user's quiet hours are in, if not the global one, see `Settings::timezone`
+    pub timezone: Option<String>,
+    /// The daily span of time none of the user's tweets are posted in, if not the global one, see `Settings::quiet_hours`
+    pub quiet_hours: Option<String>,
+    /// Up to how many minutes after their scheduled time the user's tweets are posted, if not the global amount,
+    /// see `Settings::jitter`
+    pub jitter: Option<u32>,
+    /// Hashtags appended to the user's tweets, if not the global ones, see `Settings::tags`
+    pub tags: Option<Vec<String>>,
+    /// Whether to mark the user's tweets as possibly sensitive, if not globally, see `Settings::sensitive`
+    pub sensitive: Option<bool>,
+    /// Whether to rehearse posting the user's tweets instead of publishing them, see `ops::start_daemon::test_mode()`
+    pub test_mode: Option<bool>,
+    /// Name of the user to post the user's tweets as in test mode, instead of marking them as tests
+    pub test_account: Option<String>,
+    /// When the user was added, in RFC3339, if it was by a version recording it
+    pub added: Option<String>,
+    /// When posting the user's tweets was paused because their account was found suspended or locked, in RFC3339, if it was;
+    /// removing it resumes posting
+    pub suspended: Option<String>,
+    /// The most tweets by the user to post a day, if limited, see `ops::daily_cap`
+    pub max_posts_per_day: Option<u32>,
+    /// The style rules to check the user's tweets against when queueing them, if any, see `ops::lint`
+    pub lint: Option<Lint>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -29,6 +63,7 @@ struct Users {
 
 impl User {
     /// Create a `User` instance straight from the return value of `egg_mode::access_token()`
+    #[cfg(feature = "network")]
     pub fn from_raw_access_token<'t>(raw: (Token<'t>, i64, String)) -> User {
         let (access_token, user_id, username) = raw;
         User {
@@ -36,17 +71,195 @@ impl User {
             id: user_id,
             access_token_key: access_token.key.to_owned().to_string(),
             access_token_secret: access_token.secret.to_owned().to_string(),
+            app: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            discord_webhook: None,
+            timezone: None,
+            quiet_hours: None,
+            jitter: None,
+            tags: None,
+            sensitive: None,
+            test_mode: None,
+            test_account: None,
+            added: None,
+            suspended: None,
+            max_posts_per_day: None,
+            lint: None,
+        }
+    }
+
+    /// Borrows the current access token key and secret into an `egg_mode::Token`.
+    #[cfg(feature = "network")]
+    pub fn raw_token<'a>(&'a self) -> Token<'a> {
+        Token::new(&self.access_token_key[..], &self.access_token_secret[..])
+    }
+
+    /// Get the Telegram chat to mirror the user's tweets to, if both the bot token and the chat ID are specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::backend::Telegram;
+    /// # use tweetr::ops::User;
+    /// let mut user = User {
+    ///     name: "nabijaczleweli".to_string(),
+    ///     id: 481,
+    ///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+    ///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+    ///     app: None,
+    ///     telegram_bot_token: None,
+    ///     telegram_chat_id: Some("@tweetr_announcements".to_string()),
+    ///     discord_webhook: None,
+    ///     timezone: None,
+    ///     quiet_hours: None,
+    ///     jitter: None,
+    ///     tags: None,
+    ///     sensitive: None,
+    ///     test_mode: None,
+    ///     test_account: None,
+    ///     added: None,
+    ///     suspended: None,
+    ///     max_posts_per_day: None,
+    ///     lint: None,
+    /// };
+    /// assert_eq!(user.telegram(), None);
+    ///
+    /// user.telegram_bot_token = Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string());
+    /// assert_eq!(user.telegram(),
+    ///            Some(Telegram {
+    ///                bot_token: "110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string(),
+    ///                chat_id: "@tweetr_announcements".to_string(),
+    ///            }));
+    /// ```
+    pub fn telegram(&self) -> Option<Telegram> {
+        match (&self.telegram_bot_token, &self.telegram_chat_id) {
+            (&Some(ref bot_token), &Some(ref chat_id)) => {
+                Some(Telegram {
+                    bot_token: bot_token.clone(),
+                    chat_id: chat_id.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the Discord webhook to mirror the user's tweets to, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::backend::Discord;
+    /// # use tweetr::ops::User;
+    /// let mut user = User {
+    ///     name: "nabijaczleweli".to_string(),
+    ///     id: 481,
+    ///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+    ///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+    ///     app: None,
+    ///     telegram_bot_token: None,
+    ///     telegram_chat_id: None,
+    ///     discord_webhook: None,
+    ///     timezone: None,
+    ///     quiet_hours: None,
+    ///     jitter: None,
+    ///     tags: None,
+    ///     sensitive: None,
+    ///     test_mode: None,
+    ///     test_account: None,
+    ///     added: None,
+    ///     suspended: None,
+    ///     max_posts_per_day: None,
+    ///     lint: None,
+    /// };
+    /// assert_eq!(user.discord(), None);
+    ///
+    /// user.discord_webhook = Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string());
+    /// assert_eq!(user.discord(), Some(Discord { webhook_url: "https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string() }));
+    /// ```
+    pub fn discord(&self) -> Option<Discord> {
+        self.discord_webhook.as_ref().map(|webhook_url| Discord { webhook_url: webhook_url.clone() })
+    }
+
+    /// Get the defaults the user's tweets inherit, see `ops::settings`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::settings::Settings;
+    /// # use tweetr::ops::User;
+    /// let user = User {
+    ///     name: "nabijaczleweli".to_string(),
+    ///     id: 481,
+    ///     access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+    ///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+    ///     app: None,
+    ///     telegram_bot_token: None,
+    ///     telegram_chat_id: None,
+    ///     discord_webhook: None,
+    ///     timezone: Some("+02:00".to_string()),
+    ///     quiet_hours: None,
+    ///     jitter: None,
+    ///     tags: Some(vec!["tweetr".to_string()]),
+    ///     sensitive: None,
+    ///     test_mode: None,
+    ///     test_account: None,
+    ///     added: None,
+    ///     suspended: None,
+    ///     max_posts_per_day: None,
+    ///     lint: None,
+    /// };
+    /// assert_eq!(user.settings(),
+    ///            Settings {
+    ///                timezone: Some("+02:00".to_string()),
+    ///                tags: Some(vec!["tweetr".to_string()]),
+    ///                ..Settings::default()
+    ///            });
+    /// ```
+    pub fn settings(&self) -> Settings {
+        Settings {
+            timezone: self.timezone.clone(),
+            quiet_hours: self.quiet_hours.clone(),
+            jitter: self.jitter,
+            tags: self.tags.clone(),
+            sensitive: self.sensitive,
         }
     }
 
     /// Read all user data from the specified file.
+    ///
+    /// `${VAR}` references in the values are replaced with the environment variables' values, see `util::interpolate_env()`.
+    ///
+    /// Only one of the `telegram_bot_token` and `telegram_chat_id` is an error, since nothing'd be mirrored to Telegram.
     pub fn read(p: &Path) -> Result<Vec<User>, Option<Outcome>> {
+        let users = try!(read_interpolated_toml_file(p, "users").map(|us: Users| us.user));
+
+        for (i, user) in users.iter().enumerate() {
+            match (&user.telegram_bot_token, &user.telegram_chat_id) {
+                (&Some(_), &None) => {
+                    return Err(Some(key_error(p, "users", &format!("user.{}.telegram_bot_token", i), "no telegram_chat_id to go with it")))
+                }
+                (&None, &Some(_)) => {
+                    return Err(Some(key_error(p, "users", &format!("user.{}.telegram_chat_id", i), "no telegram_bot_token to go with it")))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(users)
+    }
+
+    /// Read all user data from the specified file, leaving the environment variable references in the values as-is, to be
+    /// written back.
+    pub fn read_verbatim(p: &Path) -> Result<Vec<User>, Option<Outcome>> {
         read_toml_file(p, "users").map(|us: Users| us.user)
     }
 
     /// Save all user data to the specified file.
-    pub fn write(users: Vec<User>, p: &Path) {
-        File::create(p).unwrap().write_all(encode_str(&Users { user: users }).as_bytes()).unwrap();
+    ///
+    /// The data is written next to the file first and then moved over it, so the daemon never reads it partially written.
+    pub fn write(users: Vec<User>, p: &Path) -> Result<(), Outcome> {
+        replace_file(p, &encode_str(&Users { user: users }))
     }
 }
 
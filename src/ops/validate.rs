@@ -0,0 +1,220 @@
+//! This module contains the functions used only by the `validate` subsystem.
+//!
+//! The flow of the `validate` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::validate::check_files()
+//! |> ops::validate::print_results()
+//! |> ops::validate::summarise()
+//! ```
+
+
+use self::super::{Apps, Config, QueuedTweet, Template, User};
+use self::super::evergreen::{EvergreenGroup, EvergreenTweet};
+use self::super::recovery::Checkpoint;
+use self::super::{media, prune_posted};
+use self::super::super::Outcome;
+use std::path::Path;
+use std::io::Write;
+
+
+/// The files checked, in order, as their name, description and a function reading them.
+static FILES: &'static [(&'static str, &'static str, fn(&Path) -> Result<(), Option<Outcome>>)] =
+    &[("app.toml", "application tokens", check_app),
+      ("users.toml", "users", check_users),
+      ("tweets.toml", "queued tweets", check_tweets),
+      ("config.toml", "configuration", check_config),
+      ("templates.toml", "templates", check_templates),
+      ("evergreen.toml", "evergreen tweets", check_evergreen),
+      ("pruned.toml", "deleted tweets", check_pruned),
+      ("uploads.toml", "media uploads", check_uploads),
+      ("checkpoint.toml", "daemon checkpoint", check_checkpoint)];
+
+
+/// Read every file in the specified configuration directory, without doing anything with it.
+///
+/// Returns the name of each file and the result of reading it, or `None` if it doesn't exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::validate;
+/// # use tweetr::Outcome;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-validate-check_files-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap().write_all(b"key = \"a\"\nsecret = \"b\"\n").unwrap();
+/// File::create(tf.join("users.toml")).unwrap().write_all(b"[[user]]\nname = \"a\"\n").unwrap();
+/// let _ = fs::remove_file(tf.join("tweets.toml"));
+/// let _ = fs::remove_file(tf.join("config.toml"));
+/// let _ = fs::remove_file(tf.join("templates.toml"));
+/// let _ = fs::remove_file(tf.join("evergreen.toml"));
+/// let _ = fs::remove_file(tf.join("pruned.toml"));
+/// let _ = fs::remove_file(tf.join("uploads.toml"));
+/// let _ = fs::remove_file(tf.join("checkpoint.toml"));
+///
+/// assert_eq!(validate::check_files(&tf),
+///            vec![("app.toml", Some(Outcome::NoError)),
+///                 ("users.toml",
+///                  Some(Outcome::FileParsingFailed {
+///                      desc: "users",
+///                      errors: vec!["error: 1:1: expected a value of type `integer` for the key `user.0.id`".to_string()],
+///                  })),
+///                 ("tweets.toml", None),
+///                 ("config.toml", None),
+///                 ("templates.toml", None),
+///                 ("evergreen.toml", None),
+///                 ("pruned.toml", None),
+///                 ("uploads.toml", None),
+///                 ("checkpoint.toml", None)]);
+/// ```
+pub fn check_files(config_dir: &Path) -> Vec<(&'static str, Option<Outcome>)> {
+    FILES.iter()
+        .map(|&(fname, desc, check)| {
+            let path = config_dir.join(fname);
+            (fname,
+             if path.exists() {
+                Some(match check(&path) {
+                    Ok(()) => Outcome::NoError,
+                    Err(Some(out)) => out,
+                    Err(None) => {
+                        Outcome::FileParsingFailed {
+                            desc: desc,
+                            errors: vec!["error: couldn't read the file".to_string()],
+                        }
+                    }
+                })
+            } else {
+                None
+            })
+        })
+        .collect()
+}
+
+/// Print the status of each checked file, with the reason for the invalid ones.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::validate;
+/// # use tweetr::Outcome;
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// validate::print_results(&mut out,
+///                         &[("app.toml", Some(Outcome::NoError)),
+///                           ("users.toml", None),
+///                           ("tweets.toml",
+///                            Some(Outcome::FileParsingFailed {
+///                                desc: "queued tweets",
+///                                errors: vec!["error: 2:1: expected a value of type `string` for the key `tweet.0.author`".to_string()],
+///                            })),
+///                           ("config.toml",
+///                            Some(Outcome::IoError {
+///                                path: "/home/nabijaczleweli/.tweetr/config.toml".to_string(),
+///                                op: "open",
+///                                cause: "Permission denied (os error 13)".to_string(),
+///                            }))]);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "app.toml: OK\n\
+///             users.toml: not present\n\
+///             tweets.toml: invalid\n  \
+///               error: 2:1: expected a value of type `string` for the key `tweet.0.author`\n\
+///             config.toml: couldn't open: Permission denied (os error 13)\n".to_string());
+/// ```
+pub fn print_results<W: Write>(output: &mut W, results: &[(&'static str, Option<Outcome>)]) {
+    for &(fname, ref result) in results {
+        match *result {
+            None => writeln!(output, "{}: not present", fname).unwrap(),
+            Some(Outcome::NoError) => writeln!(output, "{}: OK", fname).unwrap(),
+            Some(Outcome::FileParsingFailed { ref errors, .. }) => {
+                writeln!(output, "{}: invalid", fname).unwrap();
+                for err in errors {
+                    writeln!(output, "  {}", err).unwrap();
+                }
+            }
+            Some(Outcome::IoError { ref op, ref cause, .. }) => writeln!(output, "{}: couldn't {}: {}", fname, op, cause).unwrap(),
+            Some(ref out) => {
+                writeln!(output, "{}: invalid", fname).unwrap();
+                out.print_error(output);
+            }
+        }
+    }
+}
+
+/// Combine the results of checking all files into one `Outcome`, with the errors prefixed with their files' names.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::validate;
+/// # use tweetr::Outcome;
+/// assert_eq!(validate::summarise(&[("app.toml", Some(Outcome::NoError)), ("users.toml", None)]), Outcome::NoError);
+/// assert_eq!(validate::summarise(&[("app.toml",
+///                                   Some(Outcome::FileParsingFailed {
+///                                       desc: "application tokens",
+///                                       errors: vec!["error: expected a value of type `string` for the key `key`".to_string()],
+///                                   }))]),
+///            Outcome::FileParsingFailed {
+///                desc: "configuration directory",
+///                errors: vec!["app.toml: error: expected a value of type `string` for the key `key`".to_string()],
+///            });
+/// ```
+pub fn summarise(results: &[(&'static str, Option<Outcome>)]) -> Outcome {
+    let errors: Vec<_> = results.iter()
+        .flat_map(|&(fname, ref result)| match *result {
+            Some(Outcome::FileParsingFailed { ref errors, .. }) => errors.iter().map(|e| format!("{}: {}", fname, e)).collect(),
+            Some(Outcome::IoError { ref op, ref cause, .. }) => vec![format!("{}: couldn't {}: {}", fname, op, cause)],
+            None | Some(Outcome::NoError) => vec![],
+            Some(_) => vec![format!("{}: invalid", fname)],
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Outcome::NoError
+    } else {
+        Outcome::FileParsingFailed {
+            desc: "configuration directory",
+            errors: errors,
+        }
+    }
+}
+
+
+fn check_app(p: &Path) -> Result<(), Option<Outcome>> {
+    Apps::read(p).map(|_| ())
+}
+
+fn check_users(p: &Path) -> Result<(), Option<Outcome>> {
+    User::read(p).map(|_| ())
+}
+
+fn check_tweets(p: &Path) -> Result<(), Option<Outcome>> {
+    QueuedTweet::read(p).map(|_| ())
+}
+
+fn check_config(p: &Path) -> Result<(), Option<Outcome>> {
+    Config::read(p).map(|_| ())
+}
+
+fn check_templates(p: &Path) -> Result<(), Option<Outcome>> {
+    Template::read(p).map(|_| ())
+}
+
+fn check_evergreen(p: &Path) -> Result<(), Option<Outcome>> {
+    EvergreenTweet::read(p).and_then(|_| EvergreenGroup::read(p)).map(|_| ())
+}
+
+fn check_pruned(p: &Path) -> Result<(), Option<Outcome>> {
+    prune_posted::read_pruned(p).map(|_| ()).map_err(Some)
+}
+
+fn check_uploads(p: &Path) -> Result<(), Option<Outcome>> {
+    media::read_uploads(p).map(|_| ()).map_err(Some)
+}
+
+fn check_checkpoint(p: &Path) -> Result<(), Option<Outcome>> {
+    Checkpoint::read(p).map(|_| ()).map_err(Some)
+}
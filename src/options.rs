@@ -9,12 +9,34 @@
 //! let options = Options::parse();
 //! println!("Config directory: {}", options.config_dir.0);
 //! ```
+//!
+//! Invocations can also be parsed from any arguments, or built directly, without faking the command line:
+//!
+//! ```
+//! # use tweetr::options::{Options, Subsystem};
+//! let options = Options::parse_from(&["tweetr", "list-users", "--check"]).unwrap();
+//! assert_eq!(options.subsystem, Subsystem::ListUsers { check: true });
+//!
+//! let options = Options::default().with_config_dir("/etc/tweetr").with_subsystem(Subsystem::ListUsers { check: true });
+//! assert_eq!(options.config_dir.0, "/etc/tweetr");
+//! ```
 
 
 use clap::{self, App, SubCommand, Arg, AppSettings};
+use chrono::{DateTime, FixedOffset, Local};
+use self::super::ops::queue_tweet::parse_time;
+use self::super::ops::quick_queue::check_delimiter;
+use self::super::ops::prune_posted::cutoff;
+use self::super::ops::export_posted::Format;
+use self::super::ops::diagnostics::Verbosity;
+use self::super::util::encoding::Encoding;
+use self::super::ops::schema;
+use self::super::util::{Confirmation, parse_time_shift, stdin_is_tty};
+use std::collections::BTreeMap;
 use std::time::Duration;
-use std::path::PathBuf;
-use std::env::home_dir;
+use std::path::{Path, PathBuf};
+use std::ffi::OsString;
+use std::env::{self, home_dir};
 use std::str::FromStr;
 use std::fs;
 
@@ -26,32 +48,352 @@ pub enum Subsystem {
     Init {
         /// Whether to override current app configuration. Default: `false`
         force: bool,
+        /// Name of the app to add instead of the default one, if any. Default: `None`
+        app: Option<String>,
     },
     /// Add and authorise a user
     AddUser {
-        /// Whether to print more user data. Default: `false`
-        verbose: bool,
+        /// Name of the app to authorise the user for instead of the default one, if any. Default: `None`
+        app: Option<String>,
+    },
+    /// Reauthorise a user, replacing its access tokens
+    RotateToken {
+        /// The name of the user to reauthorise.
+        account: String,
+    },
+    /// List the authenticated users
+    ListUsers {
+        /// Whether to verify each user's access tokens with Twitter. Default: `false`
+        check: bool,
+    },
+    /// Remove an authenticated user
+    RemoveUser {
+        /// The name of the user to remove.
+        account: String,
+        /// Whether to remove the user without asking first. Default: `false`
+        force: bool,
+        /// Whether to remove the user even if tweets by it are still queued. Default: `false`
+        orphan: bool,
     },
     /// Add a tweet to the queue
     QueueTweet {
         /// File to load tweets from, if any. Default: `None`
         file_to_load: Option<PathBuf>,
+        /// Whether to check links, spelling and invisible characters in the queued tweets. Default: `true`
+        validate: bool,
+        /// Whether to also look up the accounts @mentioned in the queued tweets on Twitter, if validating. Default: `false`
+        check_mentions: bool,
+        /// Whether to split tweets too long to fit into threads. Default: `false`
+        auto_split: bool,
+        /// Whether to prompt for the location to tag each tweet with. Default: `false`
+        geo: bool,
+        /// Whether to compose the tweets' content in an external editor. Default: `false`
+        editor: bool,
+        /// Whether to schedule the tweets for the next free preferred posting slots instead of at the specified times.
+        /// Default: `false`
+        best_time: bool,
+        /// Name of the template to fill in for the tweets' content instead of prompting for it, if any. Default: `None`
+        template: Option<String>,
+        /// Values to fill the template's placeholders with, by placeholder name. Default: empty
+        vars: BTreeMap<String, String>,
+        /// Whether to show how the configured transformations will change the tweets. Default: `false`
+        preview: bool,
+        /// Whether to only show what loading the tweets from the file would queue, without queueing them. Default: `false`
+        dry_run: bool,
+        /// The encoding of the file to load tweets from, if not detected. Default: `None`
+        encoding: Option<Encoding>,
+    },
+    /// Add a tweet specified in a single argument to the queue
+    QuickQueue {
+        /// The tweet's content, time and account, separated by the delimiter.
+        spec: String,
+        /// The delimiter to use instead of the configured one, if any. Default: `None`
+        delimiter: Option<String>,
+    },
+    /// Queue a breaking tweet to be posted right away, before the account's scheduled ones
+    PostNow {
+        /// The account to post the tweet as.
+        account: String,
+        /// The tweet's content.
+        content: String,
+    },
+    /// Queue the announcement of a release, from its changelog
+    ReleaseAnnounce {
+        /// The repository or changelog file to read the release from.
+        source: PathBuf,
+        /// The name of the template to fill in. Default: `"release"`
+        template: String,
+        /// The account to post the announcement as.
+        account: String,
+        /// The version to announce instead of the latest one, if any. Default: `None`
+        version: Option<String>,
+        /// The link to the release instead of the one to its GitHub or GitLab page, if any. Default: `None`
+        url: Option<String>,
+        /// When to post the announcement, if not immediately. Default: `None`
+        time: Option<DateTime<FixedOffset>>,
+        /// How many changelog entries to list, at most. Default: `3`
+        highlights: usize,
+        /// Whether to only show the announcement instead of queueing it. Default: `false`
+        dry_run: bool,
+    },
+    /// Guide the user through the whole setup
+    Setup {
+        /// Whether to verify the setup by posting a test tweet. Default: `false`
+        test_tweet: bool,
+        /// Whether to only verify the user's credentials instead of posting the test tweet. Default: `false`
+        dry_run: bool,
+    },
+    /// Check all configuration files without doing anything else
+    Validate,
+    /// Suggest the next free preferred posting slots
+    SuggestTime {
+        /// The account to find free slots for, or all accounts. Default: `None`
+        author: Option<String>,
+        /// How many slots to suggest. Default: `1`
+        count: usize,
+    },
+    /// Show the queue as a calendar
+    Calendar {
+        /// Whether to show the whole month instead of the week. Default: `false`
+        month: bool,
+        /// The width of each day's cell. Default: `16`
+        width: usize,
+        /// Whether to colour each account's tweets differently. Default: `true`
+        colour: bool,
+    },
+    /// Show when the queued tweets would be posted, without posting them
+    Simulate {
+        /// Simulate from this time. Default: now
+        from: DateTime<FixedOffset>,
+        /// Simulate up to this time.
+        to: DateTime<FixedOffset>,
+    },
+    /// Check the configuration, credentials, network and clock for problems
+    Doctor {
+        /// Whether to skip the checks needing the network. Default: `false`
+        offline: bool,
+    },
+    /// Remove archive files past their retention
+    Prune {
+        /// How many months before the current one to keep, overriding the configured retention. Default: `None`
+        keep: Option<u32>,
+        /// Whether to only list the files to be removed. Default: `false`
+        dry_run: bool,
+    },
+    /// Remove downloaded media no longer attached to tweets or past their retention
+    GcMedia {
+        /// How many days after posting to keep media for, overriding the configured retention. Default: `None`
+        keep: Option<u32>,
+        /// Whether to only list the files to be removed. Default: `false`
+        dry_run: bool,
+    },
+    /// Show the settings each account's tweets inherit, and the ones queued tweets override
+    ShowEffectiveConfig {
+        /// The account to show the settings of, or all accounts. Default: `None`
+        author: Option<String>,
+    },
+    /// Export the posted tweets for analysis
+    ExportPosted {
+        /// What to export the posted tweets as. Default: `Format::Csv`
+        format: Format,
+        /// Only export the tweets posted since this time, if any. Default: `None`
+        since: Option<DateTime<FixedOffset>>,
+    },
+    /// Delete old posted tweets from Twitter
+    PrunePosted {
+        /// Delete the tweets posted more than this many seconds ago.
+        older_than: i64,
+        /// Only delete tweets by this account, if specified. Default: `None`
+        account: Option<String>,
+        /// The CSV file with the posted tweets' engagements, if any. Default: `None`
+        metrics: Option<PathBuf>,
+        /// The encoding of the metrics file, if not detected. Default: `None`
+        encoding: Option<Encoding>,
+        /// Keep the tweets with at least this many engagements, if specified. Default: `None`
+        min_engagement: Option<u64>,
+        /// How long to wait between deleting tweets. Default: 1s
+        interval: Duration,
+        /// Whether to only list the tweets to be deleted. Default: `false`
+        dry_run: bool,
+    },
+    /// Cross-check the archive and queue against the accounts' timelines
+    Reconcile {
+        /// Only check this account, if specified. Default: `None`
+        account: Option<String>,
+    },
+    /// Rewrite the queue in canonical order, without duplicates and posted tweets
+    FmtQueue,
+    /// Move queued tweets in bulk
+    ShiftQueue {
+        /// How many seconds to move the tweets by, earlier if negative.
+        by: i64,
+        /// Only move tweets scheduled at or after this time, if specified. Default: `None`
+        from: Option<DateTime<FixedOffset>>,
+        /// Only move tweets by this account, if specified. Default: `None`
+        author: Option<String>,
+        /// Only move tweets tagged with this hashtag, if specified. Default: `None`
+        tag: Option<String>,
+    },
+    /// Let a thread held back after a permanent failure be posted again
+    RequeueThread {
+        /// The thread to requeue.
+        thread: String,
+    },
+    /// Show the journalled changes to a queued tweet
+    History {
+        /// The entry's journal identifier, or the posted tweet's ID.
+        id: String,
+    },
+    /// List the tweet templates and their placeholders
+    ListTemplates,
+    /// Print the JSON Schema of, or an example file in, an on-disk format
+    Schema {
+        /// The format to describe.
+        format: schema::Format,
+        /// Whether to print a commented example file instead of the JSON Schema. Default: `false`
+        example: bool,
+    },
+    /// Update to the latest release
+    Update {
+        /// Whether to only check whether a newer release is available. Default: `false`
+        check: bool,
+    },
+    /// Manage the queue interactively
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Post the tweets scheduled up to some time now
+    PostAll {
+        /// Post the tweets scheduled up to this time.
+        up_to: DateTime<FixedOffset>,
+        /// How long to wait between posting tweets. Default: 5s
+        interval: Duration,
     },
     /// Start the tweet-posting daemon.
     StartDaemon {
-        /// How long to wait between trying to post again. Default: 60s
+        /// The longest to wait between trying to post again, if no tweet is due sooner, at most a week. Default: 60s
         delay: Duration,
-        /// Whether to log all network requests. Default: `false`
-        verbose: bool,
+        /// The file to append the newline-delimited JSON event stream to, if any. Default: `None`
+        events: Option<PathBuf>,
+        /// Whether to show desktop notifications when tweets are posted or fail to, only with the `notify` feature.
+        /// Default: `false`
+        notify: bool,
+        /// Whether to fill posting slots nothing is scheduled in from the evergreen pool. Default: `false`
+        evergreen: bool,
     },
 }
 
+impl Subsystem {
+    /// Get the subsystem's name, as specified on the commandline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Subsystem;
+    /// assert_eq!(Subsystem::Validate.name(), "validate");
+    /// assert_eq!(Subsystem::AddUser { app: None }.name(), "add-user");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Subsystem::Init { .. } => "init",
+            Subsystem::AddUser { .. } => "add-user",
+            Subsystem::RotateToken { .. } => "rotate-token",
+            Subsystem::ListUsers { .. } => "list-users",
+            Subsystem::RemoveUser { .. } => "remove-user",
+            Subsystem::QueueTweet { .. } => "queue-tweet",
+            Subsystem::QuickQueue { .. } => "quick-queue",
+            Subsystem::PostNow { .. } => "post-now",
+            Subsystem::ReleaseAnnounce { .. } => "release-announce",
+            Subsystem::Setup { .. } => "setup",
+            Subsystem::Validate => "validate",
+            Subsystem::SuggestTime { .. } => "suggest-time",
+            Subsystem::Calendar { .. } => "calendar",
+            Subsystem::Simulate { .. } => "simulate",
+            Subsystem::Doctor { .. } => "doctor",
+            Subsystem::Prune { .. } => "prune",
+            Subsystem::GcMedia { .. } => "gc-media",
+            Subsystem::ShowEffectiveConfig { .. } => "show-effective-config",
+            Subsystem::ExportPosted { .. } => "export-posted",
+            Subsystem::PrunePosted { .. } => "prune-posted",
+            Subsystem::Reconcile { .. } => "reconcile",
+            Subsystem::FmtQueue => "fmt-queue",
+            Subsystem::ShiftQueue { .. } => "shift-queue",
+            Subsystem::RequeueThread { .. } => "requeue-thread",
+            Subsystem::History { .. } => "history",
+            Subsystem::ListTemplates => "list-templates",
+            Subsystem::Schema { .. } => "schema",
+            Subsystem::Update { .. } => "update",
+            #[cfg(feature = "tui")]
+            Subsystem::Tui => "tui",
+            Subsystem::PostAll { .. } => "post-all",
+            Subsystem::StartDaemon { .. } => "start-daemon",
+        }
+    }
+
+    /// Check whether the subsystem writes to the configuration directory, and so can't run with `--read-only`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Subsystem;
+    /// assert!(!Subsystem::Validate.writes());
+    /// assert!(Subsystem::Init {
+    ///             force: false,
+    ///             app: None,
+    ///         }
+    ///         .writes());
+    /// ```
+    pub fn writes(&self) -> bool {
+        match *self {
+            Subsystem::Validate |
+            Subsystem::SuggestTime { .. } |
+            Subsystem::Calendar { .. } |
+            Subsystem::Simulate { .. } |
+            Subsystem::ListUsers { .. } |
+            Subsystem::Doctor { .. } |
+            Subsystem::ShowEffectiveConfig { .. } |
+            Subsystem::ExportPosted { .. } |
+            Subsystem::Reconcile { .. } |
+            Subsystem::History { .. } |
+            Subsystem::ListTemplates |
+            Subsystem::Schema { .. } |
+            Subsystem::Update { .. } |
+            Subsystem::Prune { dry_run: true, .. } |
+            Subsystem::GcMedia { dry_run: true, .. } |
+            Subsystem::PrunePosted { dry_run: true, .. } |
+            Subsystem::QueueTweet { dry_run: true, .. } |
+            Subsystem::ReleaseAnnounce { dry_run: true, .. } => false,
+            _ => true,
+        }
+    }
+}
+
 
 /// Representation of the application's all configurable values.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Options {
-    /// Directory containing configuration. Default: `"$HOME/.tweetr"`
+    /// Directory containing configuration. Default: `$TWEETR_CONFIG_DIR` or `"$HOME/.tweetr"`
     pub config_dir: (String, PathBuf),
+    /// Whether to only allow subsystems that don't write to the configuration directory. Default: `false`
+    pub read_only: bool,
+    /// How to answer the prompts confirming destructive actions. Default: ask, if the standard input is a terminal
+    pub confirmation: Confirmation,
+    /// Locale to translate the messages to instead of the one set in the environment, if any. Default: `None`
+    pub locale: Option<String>,
+    /// How long, in seconds, to wait for connecting to a server instead of the configured time, if at all. Default: `None`
+    pub connect_timeout: Option<u64>,
+    /// How long, in seconds, to wait for a server to respond instead of the configured time, if at all. Default: `None`
+    pub read_timeout: Option<u64>,
+    /// How long, in seconds, to wait for a server to accept a request instead of the configured time, if at all. Default: `None`
+    pub write_timeout: Option<u64>,
+    /// File to write a JSON summary of what the subsystem did to, if any, see `ops::report`. Default: `None`
+    pub report: Option<PathBuf>,
+    /// Whether to print a JSON object describing what the subsystem did to the standard output, and everything else to the
+    /// standard error, see `ops::results`. Default: `false`
+    pub json: bool,
+    /// File to record the interactive prompts and the answers to them in, if any, see `ops::transcript`. Default: `None`
+    pub transcript: Option<PathBuf>,
+    /// How many diagnostics to print, see `ops::diagnostics`. Default: `Verbosity::Normal`
+    pub verbosity: Verbosity,
     /// The specified subsystem.
     pub subsystem: Subsystem,
 }
@@ -59,7 +401,37 @@ pub struct Options {
 impl Options {
     /// Parse `env`-wide command-line arguments into an `Options` instance
     pub fn parse() -> Options {
-        let matches = App::new("tweetr")
+        Options::parse_from(env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parse the specified command-line arguments, starting with the executable name, into an `Options` instance
+    ///
+    /// Unlike with `parse()`, invalid arguments, `--help` and `--version` are returned as errors instead of exiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate clap;
+    /// # extern crate tweetr;
+    /// # use tweetr::options::{Options, Subsystem};
+    /// # fn main() {
+    /// let options = Options::parse_from(&["tweetr", "--read-only", "remove-user", "nabijaczleweli", "--orphan"]).unwrap();
+    /// assert!(options.read_only);
+    /// assert_eq!(options.subsystem,
+    ///            Subsystem::RemoveUser {
+    ///                account: "nabijaczleweli".to_string(),
+    ///                force: false,
+    ///                orphan: true,
+    ///            });
+    ///
+    /// assert_eq!(Options::parse_from(&["tweetr", "remove-user"]).unwrap_err().kind, clap::ErrorKind::MissingRequiredArgument);
+    /// # }
+    /// ```
+    pub fn parse_from<I, T>(args: I) -> Result<Options, clap::Error>
+        where I: IntoIterator<Item = T>,
+              T: Into<OsString> + Clone
+    {
+        let app = App::new("tweetr")
             .version(crate_version!())
             .author(crate_authors!())
             .setting(AppSettings::ColoredHelp)
@@ -68,63 +440,546 @@ impl Options {
             .about("tweetr is a platform that allows you to create and queue tweets to be shared when YOU want.\n\
                     You create content when you have time  and then use FOSS and NOT pay whatever-ridiculous\n\
                     amount of $$$ for posting them automatically")
-            .arg(Arg::from_usage("-c --config-dir=[CONFIG_DIR] 'Directory containing configuration. Default: $HOME/.tweetr'")
-                .validator(Options::config_dir_validator))
+            .arg(Arg::from_usage("-c --config-dir=[CONFIG_DIR] 'Directory containing configuration. Default: $TWEETR_CONFIG_DIR or $HOME/.tweetr'")
+                .validator(Options::config_dir_validator)
+                .global(true))
+            .arg(Arg::from_usage("--read-only 'Only allow subsystems that don't write to the configuration directory'"))
+            .args(&[Arg::from_usage("-y --yes 'Confirm overriding, removing and posting without asking'").global(true),
+                    Arg::from_usage("--no-input 'Never ask for confirmation, refusing unless --yes'").global(true)])
+            .arg(Arg::from_usage("--locale=[LOCALE] 'Locale to translate the messages to. Default: from $LC_ALL, $LC_MESSAGES or $LANG'"))
+            .args(&[Arg::from_usage("--connect-timeout=[SECONDS] 'How long to wait for connecting to a server, 0 for indefinitely'")
+                        .validator(Options::seconds_validator),
+                    Arg::from_usage("--read-timeout=[SECONDS] 'How long to wait for a server to respond, 0 for indefinitely'")
+                        .validator(Options::seconds_validator),
+                    Arg::from_usage("--write-timeout=[SECONDS] 'How long to wait for a server to accept a request, 0 for indefinitely'")
+                        .validator(Options::seconds_validator)])
+            .arg(Arg::from_usage("--report=[FILE] 'Write a JSON summary of what the subsystem did to the specified file'").global(true))
+            .arg(Arg::from_usage("--json 'Print a JSON object describing what the subsystem did, and everything else to stderr'").global(true))
+            .arg(Arg::from_usage("--transcript=[FILE] 'Record the prompts and the answers to them in the specified file'").global(true))
+            .args(&[Arg::from_usage("-q --quiet 'Only print the data and the warnings and errors, not what's being done'").global(true),
+                    Arg::from_usage("-v --verbose 'Also print the network requests made and the access tokens received'").global(true)])
             .subcommand(SubCommand::with_name("init")
                 .about("Initialise global app data")
-                .arg(Arg::from_usage("-f --force 'Override current app configuration'")))
+                .args(&[Arg::from_usage("-f --force 'Override current app configuration'"),
+                        Arg::from_usage("-a --app=[name] 'Add the specified named app users can be bound to instead of the default one'")]))
             .subcommand(SubCommand::with_name("add-user")
                 .about("Add and authorise a user")
-                .arg(Arg::from_usage("-v --verbose 'Print more user data'")))
+                .arg(Arg::from_usage("-a --app=[name] 'Authorise the user for the specified named app instead of the default one'")))
+            .subcommand(SubCommand::with_name("rotate-token")
+                .about("Reauthorise a user, replacing its access tokens")
+                .arg(Arg::from_usage("<ACCOUNT> 'The user to reauthorise'")))
+            .subcommand(SubCommand::with_name("list-users")
+                .about("List the authenticated users")
+                .arg(Arg::from_usage("--check 'Verify each user's access tokens with Twitter'")))
+            .subcommand(SubCommand::with_name("remove-user")
+                .about("Remove an authenticated user")
+                .args(&[Arg::from_usage("<ACCOUNT> 'The user to remove'"),
+                        Arg::from_usage("-f --force 'Don't ask before removing the user'"),
+                        Arg::from_usage("--orphan 'Remove the user even if tweets by it are still queued'")]))
             .subcommand(SubCommand::with_name("queue-tweet")
                 .about("Add a tweet to the queue")
-                .arg(Arg::from_usage("-f --file=[file] 'Load tweets from the specified file'").validator(Options::tweets_file_validator)))
-            .subcommand(SubCommand::with_name("start-daemon")
+                .args(&[Arg::from_usage("-f --file=[file] 'Load tweets from the specified file'").validator(Options::tweets_file_validator),
+                        Arg::from_usage("--no-validate 'Don't check links, spelling and invisible characters in the queued tweets'"),
+                        Arg::from_usage("--check-mentions 'Also look up the @mentioned accounts, warning about nonexistent and suspended ones'")
+                            .conflicts_with("no-validate"),
+                        Arg::from_usage("-s --auto-split 'Split tweets too long to fit into numbered threads'"),
+                        Arg::from_usage("-g --geo 'Prompt for the location to tag each tweet with'").conflicts_with("file"),
+                        Arg::from_usage("-e --editor 'Compose the tweets in $VISUAL or $EDITOR'").conflicts_with("file"),
+                        Arg::from_usage("-T --template=[template] 'Fill in the specified template for the tweets' content'")
+                            .conflicts_with_all(&["file", "editor"]),
+                        Arg::from_usage("--var=[var]... 'Fill the template's placeholder with the specified value'")
+                            .value_name("name=value")
+                            .number_of_values(1)
+                            .requires("template")
+                            .validator(Options::var_validator),
+                        Arg::from_usage("-t --time=[time] 'Schedule the tweets for the next free preferred posting slots'").possible_values(&["best"]),
+                        Arg::from_usage("-p --preview 'Show how the configured transformations will change the tweets'"),
+                        Arg::from_usage("-n --dry-run 'Only show which tweets from the file would be queued'").requires("file"),
+                        Arg::from_usage("--encoding=[encoding] 'Read the file in the specified encoding instead of detecting it'")
+                            .requires("file")
+                            .validator(Options::encoding_validator)]))
+            .subcommand(SubCommand::with_name("quick-queue")
+                .visible_alias("q")
+                .about("Add a tweet specified in a single argument to the queue")
+                .args(&[Arg::from_usage("<TWEET> 'The tweet as \"content | time | account\"'"),
+                        Arg::from_usage("-d --delimiter=[delimiter] 'Separate the tweet's content, time and account with this instead'")
+                            .validator(Options::delimiter_validator)]))
+            .subcommand(SubCommand::with_name("post-now")
+                .about("Queue a breaking tweet to be posted right away, before the account's scheduled ones")
+                .args(&[Arg::from_usage("<CONTENT> 'The tweet's content'"),
+                        Arg::from_usage("-a --account=<account> 'The account to post the tweet as'")]))
+            .subcommand(SubCommand::with_name("release-announce")
+                .about("Queue the announcement of a release, from its changelog")
+                .args(&[Arg::from_usage("<SOURCE> 'The repository or changelog file to read the release from'"),
+                        Arg::from_usage("-a --account=<account> 'The account to post the announcement as'"),
+                        Arg::from_usage("-T --template=[template] 'The template to fill in'").default_value("release"),
+                        Arg::from_usage("-V --release=[version] 'Announce the specified version instead of the latest tag or release'"),
+                        Arg::from_usage("-u --url=[url] 'Link to the specified URL instead of the release's GitHub or GitLab page'"),
+                        Arg::from_usage("-t --time=[time] 'Post the announcement at the specified time instead of immediately'")
+                            .validator(Options::time_validator),
+                        Arg::from_usage("--highlights=[count] 'List at most the specified amount of changelog entries'")
+                            .default_value("3")
+                            .validator(Options::count_validator),
+                        Arg::from_usage("-n --dry-run 'Only show the announcement'")]))
+            .subcommand(SubCommand::with_name("setup")
+                .about("Guide through the whole setup")
+                .args(&[Arg::from_usage("-t --test-tweet 'Verify the setup by posting a test tweet'"),
+                        Arg::from_usage("-n --dry-run 'Only verify the credentials instead of posting the test tweet'").requires("test-tweet")]))
+            .subcommand(SubCommand::with_name("suggest-time")
+                .about("Suggest the next free preferred posting slots")
+                .args(&[Arg::from_usage("-a --author=[author] 'Only consider tweets by the specified account'"),
+                        Arg::from_usage("-n --count=[count] 'How many slots to suggest'")
+                            .default_value("1")
+                            .validator(Options::count_validator)]))
+            .subcommand(SubCommand::with_name("calendar")
+                .about("Show the queue as a calendar")
+                .args(&[Arg::from_usage("-m --month 'Show the whole month instead of the week'"),
+                        Arg::from_usage("-w --width=[width] 'The width of each day'")
+                            .default_value("16")
+                            .validator(Options::width_validator),
+                        Arg::from_usage("--no-colour 'Don't colour each account's tweets differently'")]))
+            .subcommand(SubCommand::with_name("simulate")
+                .about("Show when the queued tweets would be posted, without posting them")
+                .args(&[Arg::from_usage("-f --from=[time] 'Simulate from this time instead of now'").validator(Options::time_validator),
+                        Arg::from_usage("-t --to=<time> 'Simulate up to this time'").validator(Options::time_validator)]))
+            .subcommand(SubCommand::with_name("doctor")
+                .about("Check the configuration, credentials, network and clock for problems")
+                .arg(Arg::from_usage("--offline 'Skip the checks needing the network'")))
+            .subcommand(SubCommand::with_name("prune")
+                .about("Remove archive files past their retention")
+                .args(&[Arg::from_usage("-k --keep=[months] 'How many months before the current one to keep the archives of'")
+                            .validator(Options::months_validator),
+                        Arg::from_usage("--dry-run 'Only list the files to be removed'")]))
+            .subcommand(SubCommand::with_name("gc-media")
+                .about("Remove downloaded media no longer attached to tweets or past their retention")
+                .args(&[Arg::from_usage("-k --keep=[days] 'How many days after posting to keep the media attached to tweets for'")
+                            .validator(Options::days_validator),
+                        Arg::from_usage("--dry-run 'Only list the files to be removed'")]))
+            .subcommand(SubCommand::with_name("show-effective-config")
+                .about("Show the settings each account's tweets inherit")
+                .arg(Arg::from_usage("-a --author=[author] 'Only show the settings of the specified account'")))
+            .subcommand(SubCommand::with_name("export-posted")
+                .about("Export the posted tweets for analysis")
+                .args(&[Arg::from_usage("-f --format=[format] 'What to export the tweets as, CSV by default'").possible_values(&["csv", "json"]),
+                        Arg::from_usage("-s --since=[time] 'Only export the tweets posted since this time'").validator(Options::time_validator)]))
+            .subcommand(SubCommand::with_name("prune-posted")
+                .about("Delete old posted tweets from Twitter")
+                .args(&[Arg::from_usage("--older-than=<age> 'Delete the tweets posted longer ago than this, e.g. \"90 days\"'")
+                            .validator(Options::age_validator),
+                        Arg::from_usage("-a --account=[account] 'Only delete tweets by the specified account'"),
+                        Arg::from_usage("--metrics=[FILE] 'CSV file with the engagements each tweet got'"),
+                        Arg::from_usage("--min-engagement=[N] 'Keep the tweets with at least this many engagements'")
+                            .requires("metrics")
+                            .validator(Options::engagement_validator),
+                        Arg::from_usage("--encoding=[encoding] 'Read the metrics file in the specified encoding instead of detecting it'")
+                            .requires("metrics")
+                            .validator(Options::encoding_validator),
+                        Arg::from_usage("--interval=<interval> 'How long to wait between deleting tweets [ms]'")
+                            .default_value("1000")
+                            .validator(Options::duration_validator),
+                        Arg::from_usage("--dry-run 'Only list the tweets to be deleted'")]))
+            .subcommand(SubCommand::with_name("reconcile")
+                .about("Cross-check the archive and queue against the accounts' timelines")
+                .arg(Arg::from_usage("-a --account=[account] 'Only check the specified account'")))
+            .subcommand(SubCommand::with_name("fmt-queue").about("Rewrite the queue in canonical order, without duplicates and posted tweets"))
+            .subcommand(SubCommand::with_name("shift-queue")
+                .about("Move queued tweets in bulk")
+                .args(&[Arg::from_usage("-b --by=<by> 'How much later to move the tweets, e.g. \"2 hours\", or earlier, e.g. \"-1 day\"'")
+                            .validator(Options::shift_validator),
+                        Arg::from_usage("-f --from=[time] 'Only move tweets scheduled at or after the specified time'")
+                            .validator(Options::time_validator),
+                        Arg::from_usage("-a --account=[account] 'Only move tweets by the specified account'"),
+                        Arg::from_usage("-t --tag=[tag] 'Only move tweets tagged with the specified hashtag'")]))
+            .subcommand(SubCommand::with_name("requeue-thread")
+                .about("Let a thread held back after a permanent failure be posted again")
+                .arg(Arg::from_usage("<THREAD> 'The thread to requeue'")))
+            .subcommand(SubCommand::with_name("history")
+                .about("Show the journalled changes to a queued tweet")
+                .arg(Arg::from_usage("<ID> 'The entry's journal identifier or the posted tweet's ID'")))
+            .subcommand(SubCommand::with_name("list-templates").about("List the tweet templates and their placeholders"))
+            .subcommand(SubCommand::with_name("schema")
+                .about("Print the JSON Schema of an on-disk format")
+                .args(&[Arg::from_usage("<FORMAT> 'The format to describe'").possible_values(&["config", "users", "queue", "archive"]),
+                        Arg::from_usage("-e --example 'Print a commented example file instead'")]))
+            .subcommand(SubCommand::with_name("update")
+                .about("Update to the latest release")
+                .arg(Arg::from_usage("--check 'Only check whether a newer release is available'")))
+            .subcommand(SubCommand::with_name("validate").about("Check all configuration files without doing anything else"))
+            .subcommand(Options::with_notify(SubCommand::with_name("start-daemon"))
                 .about("Start the tweet-posting daemon")
-                .args(&[Arg::from_usage("-v --verbose 'Log all network requests'"),
-                        Arg::from_usage("--delay=<delay> 'How long to wait between trying to post again [ms]'")
+                .args(&[Arg::from_usage("--delay=<delay> 'The longest to wait between trying to post again [ms]'")
                             .default_value("60000")
-                            .validator(Options::duration_validator)]))
-            .get_matches();
+                            .validator(Options::delay_validator),
+                        Arg::from_usage("--events=[FILE] 'Append newline-delimited JSON events to FILE'"),
+                        Arg::from_usage("--evergreen 'Fill empty posting slots with tweets from the evergreen pool'")]))
+            .subcommand(SubCommand::with_name("post-all")
+                .about("Post the tweets scheduled up to some time now")
+                .args(&[Arg::from_usage("-u --up-to=<time> 'Post the tweets scheduled up to this time'").validator(Options::time_validator),
+                        Arg::from_usage("--interval=<interval> 'How long to wait between posting tweets [ms]'")
+                            .default_value("5000")
+                            .validator(Options::duration_validator)]));
+        let matches = try!(Options::with_tui(app).get_matches_from_safe(args));
+        // Global, so it can also be specified after the subsystem
+        let config_dir = matches.value_of("config-dir")
+            .or_else(|| matches.subcommand().1.and_then(|m| m.value_of("config-dir")))
+            .map(|d| (d.to_string(), "--config-dir"))
+            .or_else(|| env::var("TWEETR_CONFIG_DIR").ok().and_then(|d| if d.is_empty() { None } else { Some((d, "$TWEETR_CONFIG_DIR")) }));
+        let config_dir = match config_dir {
+            Some((dirs, source)) => {
+                match fs::canonicalize(&dirs) {
+                    Ok(cd) => (dirs, cd),
+                    Err(_) => {
+                        return Err(clap::Error {
+                            message: format!("Configuration directory \"{}\" from {} not found", dirs, source),
+                            kind: clap::ErrorKind::InvalidValue,
+                            info: None,
+                        })
+                    }
+                }
+            }
+            None => {
+                match home_dir() {
+                    Some(mut hd) => {
+                        hd = hd.canonicalize().unwrap();
+                        hd.push(".tweetr");
 
-        Options {
-            config_dir: match matches.value_of("config-dir") {
-                Some(dirs) => (dirs.to_string(), fs::canonicalize(dirs).unwrap()),
-                None => {
-                    match home_dir() {
-                        Some(mut hd) => {
-                            hd = hd.canonicalize().unwrap();
-                            hd.push(".tweetr");
-
-                            fs::create_dir_all(&hd).unwrap();
-                            ("$HOME/.tweetr".to_string(), hd)
-                        }
-                        None => {
-                            clap::Error {
-                                    message: "Couldn't automatically get home directory, please specify configuration directory with the -c option".to_string(),
-                                    kind: clap::ErrorKind::MissingRequiredArgument,
-                                    info: None,
-                                }
-                                .exit()
-                        }
+                        fs::create_dir_all(&hd).unwrap();
+                        ("$HOME/.tweetr".to_string(), hd)
+                    }
+                    None => {
+                        return Err(clap::Error {
+                            message: "Couldn't automatically get home directory, please specify configuration directory with the -c option".to_string(),
+                            kind: clap::ErrorKind::MissingRequiredArgument,
+                            info: None,
+                        })
                     }
                 }
-            },
+            }
+        };
+
+        Ok(Options {
+            config_dir: config_dir,
+            read_only: matches.is_present("read-only"),
+            confirmation: Confirmation::new(Options::global_flag(&matches, "yes"), Options::global_flag(&matches, "no-input"), stdin_is_tty()),
+            locale: matches.value_of("locale").map(String::from),
+            connect_timeout: matches.value_of("connect-timeout").map(|s| u64::from_str(s).unwrap()),
+            read_timeout: matches.value_of("read-timeout").map(|s| u64::from_str(s).unwrap()),
+            write_timeout: matches.value_of("write-timeout").map(|s| u64::from_str(s).unwrap()),
+            report: matches.value_of("report").or_else(|| matches.subcommand().1.and_then(|m| m.value_of("report"))).map(PathBuf::from),
+            json: Options::global_flag(&matches, "json"),
+            transcript: matches.value_of("transcript").or_else(|| matches.subcommand().1.and_then(|m| m.value_of("transcript"))).map(PathBuf::from),
+            verbosity: Verbosity::new(Options::global_flag(&matches, "quiet"), Options::global_flag(&matches, "verbose")),
             subsystem: match matches.subcommand() {
-                ("init", Some(init_matches)) => Subsystem::Init { force: init_matches.is_present("force") },
-                ("add-user", Some(add_user_matches)) => Subsystem::AddUser { verbose: add_user_matches.is_present("verbose") },
+                ("init", Some(init_matches)) => {
+                    Subsystem::Init {
+                        force: init_matches.is_present("force"),
+                        app: init_matches.value_of("app").map(String::from),
+                    }
+                }
+                ("add-user", Some(add_user_matches)) => Subsystem::AddUser { app: add_user_matches.value_of("app").map(String::from) },
+                ("rotate-token", Some(rotate_token_matches)) => {
+                    Subsystem::RotateToken { account: rotate_token_matches.value_of("ACCOUNT").unwrap().to_string() }
+                }
+                ("list-users", Some(list_users_matches)) => Subsystem::ListUsers { check: list_users_matches.is_present("check") },
+                ("remove-user", Some(remove_user_matches)) => {
+                    Subsystem::RemoveUser {
+                        account: remove_user_matches.value_of("ACCOUNT").unwrap().to_string(),
+                        force: remove_user_matches.is_present("force"),
+                        orphan: remove_user_matches.is_present("orphan"),
+                    }
+                }
                 ("queue-tweet", Some(queue_tweet_matches)) => {
-                    Subsystem::QueueTweet { file_to_load: queue_tweet_matches.value_of("file").map(fs::canonicalize).map(Result::unwrap) }
+                    Subsystem::QueueTweet {
+                        file_to_load: queue_tweet_matches.value_of("file").map(fs::canonicalize).map(Result::unwrap),
+                        validate: !queue_tweet_matches.is_present("no-validate"),
+                        check_mentions: queue_tweet_matches.is_present("check-mentions"),
+                        auto_split: queue_tweet_matches.is_present("auto-split"),
+                        geo: queue_tweet_matches.is_present("geo"),
+                        editor: queue_tweet_matches.is_present("editor"),
+                        best_time: queue_tweet_matches.value_of("time") == Some("best"),
+                        preview: queue_tweet_matches.is_present("preview"),
+                        dry_run: queue_tweet_matches.is_present("dry-run"),
+                        encoding: queue_tweet_matches.value_of("encoding").map(|e| Encoding::parse(e).unwrap()),
+                        template: queue_tweet_matches.value_of("template").map(String::from),
+                        vars: queue_tweet_matches.values_of("var")
+                            .map(|vars| {
+                                vars.map(|var| {
+                                        let mut parts = var.splitn(2, '=');
+                                        (parts.next().unwrap().to_string(), parts.next().unwrap().to_string())
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_else(BTreeMap::new),
+                    }
+                }
+                ("setup", Some(setup_matches)) => {
+                    Subsystem::Setup {
+                        test_tweet: setup_matches.is_present("test-tweet"),
+                        dry_run: setup_matches.is_present("dry-run"),
+                    }
+                }
+                ("suggest-time", Some(suggest_time_matches)) => {
+                    Subsystem::SuggestTime {
+                        author: suggest_time_matches.value_of("author").map(String::from),
+                        count: usize::from_str(suggest_time_matches.value_of("count").unwrap()).unwrap(),
+                    }
+                }
+                ("calendar", Some(calendar_matches)) => {
+                    Subsystem::Calendar {
+                        month: calendar_matches.is_present("month"),
+                        width: usize::from_str(calendar_matches.value_of("width").unwrap()).unwrap(),
+                        colour: !calendar_matches.is_present("no-colour"),
+                    }
+                }
+                ("simulate", Some(simulate_matches)) => {
+                    Subsystem::Simulate {
+                        from: simulate_matches.value_of("from").map(|f| parse_time(f, now()).unwrap()).unwrap_or_else(now),
+                        to: parse_time(simulate_matches.value_of("to").unwrap(), now()).unwrap(),
+                    }
+                }
+                ("doctor", Some(doctor_matches)) => Subsystem::Doctor { offline: doctor_matches.is_present("offline") },
+                ("prune", Some(prune_matches)) => {
+                    Subsystem::Prune {
+                        keep: prune_matches.value_of("keep").map(|k| u32::from_str(k).unwrap()),
+                        dry_run: prune_matches.is_present("dry-run"),
+                    }
+                }
+                ("gc-media", Some(gc_media_matches)) => {
+                    Subsystem::GcMedia {
+                        keep: gc_media_matches.value_of("keep").map(|k| u32::from_str(k).unwrap()),
+                        dry_run: gc_media_matches.is_present("dry-run"),
+                    }
+                }
+                ("show-effective-config", Some(show_effective_config_matches)) => {
+                    Subsystem::ShowEffectiveConfig { author: show_effective_config_matches.value_of("author").map(String::from) }
+                }
+                ("export-posted", Some(export_posted_matches)) => {
+                    Subsystem::ExportPosted {
+                        format: match export_posted_matches.value_of("format") {
+                            Some("json") => Format::Json,
+                            _ => Format::Csv,
+                        },
+                        since: export_posted_matches.value_of("since").map(|s| parse_time(s, now()).unwrap()),
+                    }
+                }
+                ("reconcile", Some(reconcile_matches)) => Subsystem::Reconcile { account: reconcile_matches.value_of("account").map(String::from) },
+                ("prune-posted", Some(prune_posted_matches)) => {
+                    Subsystem::PrunePosted {
+                        older_than: parse_time_shift(prune_posted_matches.value_of("older-than").unwrap()).unwrap(),
+                        account: prune_posted_matches.value_of("account").map(String::from),
+                        metrics: prune_posted_matches.value_of("metrics").map(PathBuf::from),
+                        encoding: prune_posted_matches.value_of("encoding").map(|e| Encoding::parse(e).unwrap()),
+                        min_engagement: prune_posted_matches.value_of("min-engagement").map(|e| u64::from_str(e).unwrap()),
+                        interval: Duration::from_millis(u64::from_str(prune_posted_matches.value_of("interval").unwrap()).unwrap()),
+                        dry_run: prune_posted_matches.is_present("dry-run"),
+                    }
+                }
+                ("quick-queue", Some(quick_queue_matches)) => {
+                    Subsystem::QuickQueue {
+                        spec: quick_queue_matches.value_of("TWEET").unwrap().to_string(),
+                        delimiter: quick_queue_matches.value_of("delimiter").map(String::from),
+                    }
+                }
+                ("post-now", Some(post_now_matches)) => {
+                    Subsystem::PostNow {
+                        account: post_now_matches.value_of("account").unwrap().trim_left_matches('@').to_string(),
+                        content: post_now_matches.value_of("CONTENT").unwrap().to_string(),
+                    }
+                }
+                ("release-announce", Some(release_announce_matches)) => {
+                    Subsystem::ReleaseAnnounce {
+                        source: PathBuf::from(release_announce_matches.value_of("SOURCE").unwrap()),
+                        template: release_announce_matches.value_of("template").unwrap().to_string(),
+                        account: release_announce_matches.value_of("account").unwrap().trim_left_matches('@').to_string(),
+                        version: release_announce_matches.value_of("release").map(String::from),
+                        url: release_announce_matches.value_of("url").map(String::from),
+                        time: release_announce_matches.value_of("time").map(|t| parse_time(t, now()).unwrap()),
+                        highlights: usize::from_str(release_announce_matches.value_of("highlights").unwrap()).unwrap(),
+                        dry_run: release_announce_matches.is_present("dry-run"),
+                    }
+                }
+                ("fmt-queue", Some(_)) => Subsystem::FmtQueue,
+                ("shift-queue", Some(shift_queue_matches)) => {
+                    Subsystem::ShiftQueue {
+                        by: parse_time_shift(shift_queue_matches.value_of("by").unwrap()).unwrap(),
+                        from: shift_queue_matches.value_of("from").map(|f| parse_time(f, now()).unwrap()),
+                        author: shift_queue_matches.value_of("account").map(String::from),
+                        tag: shift_queue_matches.value_of("tag").map(String::from),
+                    }
                 }
+                ("requeue-thread", Some(requeue_thread_matches)) => {
+                    Subsystem::RequeueThread { thread: requeue_thread_matches.value_of("THREAD").unwrap().to_string() }
+                }
+                ("history", Some(history_matches)) => Subsystem::History { id: history_matches.value_of("ID").unwrap().to_string() },
+                ("list-templates", Some(_)) => Subsystem::ListTemplates,
+                ("schema", Some(schema_matches)) => {
+                    Subsystem::Schema {
+                        format: schema::Format::parse(schema_matches.value_of("FORMAT").unwrap()).unwrap(),
+                        example: schema_matches.is_present("example"),
+                    }
+                }
+                ("update", Some(update_matches)) => Subsystem::Update { check: update_matches.is_present("check") },
+                ("validate", Some(_)) => Subsystem::Validate,
+                #[cfg(feature = "tui")]
+                ("tui", Some(_)) => Subsystem::Tui,
                 ("start-daemon", Some(start_daemon_matches)) => {
                     Subsystem::StartDaemon {
                         delay: Duration::from_millis(u64::from_str(start_daemon_matches.value_of("delay").unwrap()).unwrap()),
-                        verbose: start_daemon_matches.is_present("verbose"),
+                        events: start_daemon_matches.value_of("events").map(PathBuf::from),
+                        notify: start_daemon_matches.is_present("notify"),
+                        evergreen: start_daemon_matches.is_present("evergreen"),
+                    }
+                }
+                ("post-all", Some(post_all_matches)) => {
+                    Subsystem::PostAll {
+                        up_to: parse_time(post_all_matches.value_of("up-to").unwrap(), now()).unwrap(),
+                        interval: Duration::from_millis(u64::from_str(post_all_matches.value_of("interval").unwrap()).unwrap()),
                     }
                 }
                 _ => panic!("No subcommand passed"),
             },
-        }
+        })
+    }
+
+    /// Use the specified configuration directory, as specified and as the path to it, which isn't checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use std::path::Path;
+    /// let options = Options::default().with_config_dir("/etc/tweetr");
+    /// assert_eq!(options.config_dir, ("/etc/tweetr".to_string(), Path::new("/etc/tweetr").to_path_buf()));
+    /// ```
+    pub fn with_config_dir<P: AsRef<Path>>(self, config_dir: P) -> Options {
+        let config_dir = config_dir.as_ref();
+        Options { config_dir: (config_dir.display().to_string(), config_dir.to_path_buf()), ..self }
+    }
+
+    /// Run the specified subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::{Options, Subsystem};
+    /// let options = Options::default().with_subsystem(Subsystem::Doctor { offline: true });
+    /// assert_eq!(options.subsystem, Subsystem::Doctor { offline: true });
+    /// ```
+    pub fn with_subsystem(self, subsystem: Subsystem) -> Options {
+        Options { subsystem: subsystem, ..self }
+    }
+
+    /// Set whether to only allow subsystems that don't write to the configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// assert!(Options::default().with_read_only(true).read_only);
+    /// ```
+    pub fn with_read_only(self, read_only: bool) -> Options {
+        Options { read_only: read_only, ..self }
+    }
+
+    /// Answer the prompts confirming destructive actions as specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use tweetr::util::Confirmation;
+    /// assert_eq!(Options::default().with_confirmation(Confirmation::Yes).confirmation, Confirmation::Yes);
+    /// ```
+    pub fn with_confirmation(self, confirmation: Confirmation) -> Options {
+        Options { confirmation: confirmation, ..self }
+    }
+
+    /// Translate the messages to the specified locale instead of the one set in the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// assert_eq!(Options::default().with_locale("pl").locale, Some("pl".to_string()));
+    /// ```
+    pub fn with_locale<S: Into<String>>(self, locale: S) -> Options {
+        Options { locale: Some(locale.into()), ..self }
+    }
+
+    /// Write a JSON summary of what the subsystem did to the specified file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use std::path::PathBuf;
+    /// assert_eq!(Options::default().with_report("report.json").report, Some(PathBuf::from("report.json")));
+    /// ```
+    pub fn with_report<P: AsRef<Path>>(self, report: P) -> Options {
+        Options { report: Some(report.as_ref().to_path_buf()), ..self }
+    }
+
+    /// Record the interactive prompts and the answers to them in the specified file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use std::path::PathBuf;
+    /// assert_eq!(Options::default().with_transcript("transcript.txt").transcript, Some(PathBuf::from("transcript.txt")));
+    /// ```
+    pub fn with_transcript<P: AsRef<Path>>(self, transcript: P) -> Options {
+        Options { transcript: Some(transcript.as_ref().to_path_buf()), ..self }
+    }
+
+    /// Print a JSON object describing what the subsystem did, or don't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// assert!(Options::default().with_json(true).json);
+    /// ```
+    pub fn with_json(self, json: bool) -> Options {
+        Options { json: json, ..self }
+    }
+
+    /// Print as many diagnostics as specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use tweetr::ops::diagnostics::Verbosity;
+    /// assert_eq!(Options::default().with_verbosity(Verbosity::Quiet).verbosity, Verbosity::Quiet);
+    /// ```
+    pub fn with_verbosity(self, verbosity: Verbosity) -> Options {
+        Options { verbosity: verbosity, ..self }
+    }
+
+    /// Global flags can be specified before or after the subsystem.
+    fn global_flag(matches: &clap::ArgMatches, name: &str) -> bool {
+        matches.is_present(name) || matches.subcommand().1.map(|m| m.is_present(name)).unwrap_or(false)
+    }
+
+    #[cfg(feature = "tui")]
+    fn with_tui<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.subcommand(SubCommand::with_name("tui").about("Manage the queue interactively"))
+    }
+
+    #[cfg(not(feature = "tui"))]
+    fn with_tui<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app
+    }
+
+    #[cfg(feature = "notify")]
+    fn with_notify<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+        subcommand.arg(Arg::from_usage("--notify 'Show desktop notifications when tweets are posted or fail to'"))
+    }
+
+    #[cfg(not(feature = "notify"))]
+    fn with_notify<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+        subcommand
     }
 
     fn config_dir_validator(s: String) -> Result<(), String> {
@@ -135,7 +990,118 @@ impl Options {
         fs::canonicalize(&s).map(|_| ()).map_err(|_| format!("File with tweets \"{}\" not found", s))
     }
 
+    fn var_validator(s: String) -> Result<(), String> {
+        match s.find('=') {
+            Some(0) | None => Err(format!("\"{}\" is not a placeholder name followed by = and the value", s)),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn delimiter_validator(s: String) -> Result<(), String> {
+        check_delimiter(&s)
+    }
+
+    fn count_validator(s: String) -> Result<(), String> {
+        match usize::from_str(&s) {
+            Ok(0) => Err("Count must be positive".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("\"{}\" is not a valid count", s)),
+        }
+    }
+
+    fn width_validator(s: String) -> Result<(), String> {
+        match usize::from_str(&s) {
+            Ok(w) if w < 8 => Err("Width must be at least 8".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("\"{}\" is not a valid width", s)),
+        }
+    }
+
+    fn months_validator(s: String) -> Result<(), String> {
+        match u32::from_str(&s) {
+            Ok(m) if m > 12 * 10000 => Err(format!("\"{}\" is not a valid amount of months: over 10000 years", s)),
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("\"{}\" is not a valid amount of months", s)),
+        }
+    }
+
+    fn days_validator(s: String) -> Result<(), String> {
+        u32::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid amount of days", s))
+    }
+
+    fn seconds_validator(s: String) -> Result<(), String> {
+        u64::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid amount of seconds", s))
+    }
+
+    fn shift_validator(s: String) -> Result<(), String> {
+        parse_time_shift(&s).map(|_| ()).map_err(|e| format!("\"{}\" is not a valid amount of time: {}", s, e))
+    }
+
+    fn age_validator(s: String) -> Result<(), String> {
+        match parse_time_shift(&s) {
+            Ok(age) if age < 0 => Err(format!("\"{}\" is not a valid age: it's negative", s)),
+            Ok(age) if cutoff(now(), age).is_none() => Err(format!("\"{}\" is not a valid age: it's too long", s)),
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("\"{}\" is not a valid age: {}", s, e)),
+        }
+    }
+
+    fn engagement_validator(s: String) -> Result<(), String> {
+        u64::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid amount of engagements", s))
+    }
+
+    fn encoding_validator(s: String) -> Result<(), String> {
+        Encoding::parse(&s).map(|_| ())
+    }
+
+    fn time_validator(s: String) -> Result<(), String> {
+        parse_time(&s, now()).map(|_| ())
+    }
+
     fn duration_validator(s: String) -> Result<(), String> {
         u64::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid amount of milliseconds", s))
     }
+
+    fn delay_validator(s: String) -> Result<(), String> {
+        match u64::from_str(&s) {
+            Ok(ms) if ms > 7 * 24 * 60 * 60 * 1000 => Err(format!("\"{}\" is too long a delay: it's over a week", s)),
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("\"{}\" is not a valid amount of milliseconds", s)),
+        }
+    }
+}
+
+impl Default for Options {
+    /// The options of an invocation specifying nothing but the `validate` subsystem, without touching the configuration
+    /// directory.
+    fn default() -> Options {
+        let config_dir = match env::var("TWEETR_CONFIG_DIR").ok().and_then(|d| if d.is_empty() { None } else { Some(d) }) {
+            Some(dirs) => {
+                let cd = PathBuf::from(&dirs);
+                (dirs, cd)
+            }
+            None => ("$HOME/.tweetr".to_string(), home_dir().unwrap_or_default().join(".tweetr")),
+        };
+
+        Options {
+            config_dir: config_dir,
+            read_only: false,
+            confirmation: Confirmation::new(false, false, stdin_is_tty()),
+            locale: None,
+            connect_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            report: None,
+            json: false,
+            transcript: None,
+            verbosity: Verbosity::Normal,
+            subsystem: Subsystem::Validate,
+        }
+    }
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    let now = Local::now();
+    now.with_timezone(now.offset())
 }
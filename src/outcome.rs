@@ -1,4 +1,8 @@
-use std::io::Write;
+use self::super::ops::l10n::{format_datetime, tr};
+use self::super::ops::setup_state::SetupState;
+use chrono::{Local, TimeZone};
+use std::io::{Error as IoError, Write};
+use std::path::Path;
 
 
 /// Enum representing all possible values the application can fail.
@@ -15,6 +19,8 @@ pub enum Outcome {
         /// The file the specified subsystem produces.
         fname: String,
     },
+    /// The configuration is only partially there (or not at all), and the next step needs to be taken first.
+    SetupIncomplete(SetupState),
     /// The specified subsystem needs to be run beforehand to produce the specified data.
     RequiredDataFromSubsystemNonexistant {
         /// The subsystem that needs to be run.
@@ -31,10 +37,108 @@ pub enum Outcome {
         /// The parsing errors that occured.
         errors: Vec<String>,
     },
+    /// The specified hook rejected a tweet.
+    HookVetoed {
+        /// The name of the hook that rejected the tweet.
+        hook: &'static str,
+        /// Why the tweet was rejected.
+        reason: String,
+    },
+    /// The checks run before starting a subsystem found the specified problems.
+    PreflightFailed(Vec<String>),
+    /// A backend other than Twitter failed to deliver a tweet.
+    BackendError {
+        /// The backend's description.
+        backend: String,
+        /// The error that occured.
+        error: String,
+    },
+    /// The specified network request timed out.
+    NetworkTimeout {
+        /// What the request was doing.
+        desc: String,
+    },
+    /// The specified network request hit the rate limits.
+    RateLimited {
+        /// What the request was doing.
+        desc: String,
+        /// When the rate limits reset, in seconds since the epoch, if known.
+        reset: Option<i64>,
+    },
+    /// The specified template couldn't be used to make a tweet.
+    TemplateError {
+        /// The template's name.
+        name: String,
+        /// Why it couldn't be used.
+        errors: Vec<String>,
+    },
+    /// Updating to the latest release failed.
+    UpdateFailed(String),
+    /// Accessing the specified file failed.
+    IoError {
+        /// The file that couldn't be accessed.
+        path: String,
+        /// What was being done to it, like `"read"` or `"write"`.
+        op: &'static str,
+        /// Why it failed.
+        cause: String,
+    },
+    /// Multiple independent operations failed with the specified outcomes.
+    Multiple(Vec<Outcome>),
 }
 
 impl Outcome {
-    /// Get the executable exit value from an `Outcome` instance.
+    /// Combine the outcomes of multiple independent operations into one.
+    ///
+    /// Successes are skipped, and a single failure is returned as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::Outcome;
+    /// assert_eq!(Outcome::from_many(vec![Outcome::NoError, Outcome::NoError]), Outcome::NoError);
+    /// assert_eq!(Outcome::from_many(vec![Outcome::NoError, Outcome::TwitterAPIError("Status is a duplicate.".to_string())]),
+    ///            Outcome::TwitterAPIError("Status is a duplicate.".to_string()));
+    /// assert_eq!(Outcome::from_many(vec![Outcome::TwitterAPIError("Status is a duplicate.".to_string()),
+    ///                                    Outcome::OverrideNoForce("tweets.toml".to_string())]),
+    ///            Outcome::Multiple(vec![Outcome::TwitterAPIError("Status is a duplicate.".to_string()),
+    ///                                   Outcome::OverrideNoForce("tweets.toml".to_string())]));
+    /// ```
+    pub fn from_many(outcomes: Vec<Outcome>) -> Outcome {
+        let mut failures: Vec<_> = outcomes.into_iter().filter(|o| *o != Outcome::NoError).collect();
+        match failures.len() {
+            0 => Outcome::NoError,
+            1 => failures.remove(0),
+            _ => Outcome::Multiple(failures),
+        }
+    }
+
+    /// Describe the specified failure to do the specified operation on the specified file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::Outcome;
+    /// # use std::io::{Error, ErrorKind};
+    /// # use std::path::Path;
+    /// assert_eq!(Outcome::from_io("write", Path::new("/etc/tweets.toml"), Error::new(ErrorKind::PermissionDenied, "Permission denied")),
+    ///            Outcome::IoError {
+    ///                path: "/etc/tweets.toml".to_string(),
+    ///                op: "write",
+    ///                cause: "Permission denied".to_string(),
+    ///            });
+    /// ```
+    pub fn from_io(op: &'static str, path: &Path, cause: IoError) -> Outcome {
+        Outcome::IoError {
+            path: path.display().to_string(),
+            op: op,
+            cause: cause.to_string(),
+        }
+    }
+
+    /// Print the error message for an `Outcome` instance.
+    ///
+    /// Each of multiple outcomes is printed numbered and indented.
     ///
     /// # Examples
     ///
@@ -46,37 +150,106 @@ impl Outcome {
     /// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
     ///            "File \"doctest\" was not overriden to prevent data loss.\n\
     ///             Pass --force to override it.\n".to_string());
+    ///
+    /// let mut out = Vec::new();
+    /// Outcome::Multiple(vec![Outcome::TwitterAPIError("Status is a duplicate.".to_string()),
+    ///                        Outcome::FileParsingFailed {
+    ///                            desc: "queued tweets",
+    ///                            errors: vec!["error: 3:1: expected a key".to_string()],
+    ///                        }])
+    ///     .print_error(&mut out);
+    /// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+    ///            "2 errors occured:\n  \
+    ///               1. Twitter API error: Status is a duplicate.\n  \
+    ///               2. Failed to parse queued tweets:\n       \
+    ///                    error: 3:1: expected a key\n".to_string());
     /// ```
     pub fn print_error<W: Write>(&self, err_out: &mut W) {
         match *self {
             Outcome::NoError => (),
             Outcome::OverrideNoForce(ref fname) => {
-                writeln!(err_out, "File \"{}\" was not overriden to prevent data loss.", fname).unwrap();
-                writeln!(err_out, "Pass --force to override it.").unwrap();
+                writeln!(err_out, "{}", tr("override_no_force", &[("fname", fname)])).unwrap()
             }
             Outcome::RequiredFileFromSubsystemNonexistant { ref subsys, ref fname } => {
-                writeln!(err_out, "Run the {} subsystem first to produce \"{}\".", subsys, fname).unwrap()
+                writeln!(err_out, "{}", tr("required_file", &[("subsys", subsys), ("fname", fname)])).unwrap()
             }
+            Outcome::SetupIncomplete(ref state) => writeln!(err_out, "{}", tr(state.message_id(), &[])).unwrap(),
             Outcome::RequiredDataFromSubsystemNonexistant { ref subsys, ref desc } => {
-                writeln!(err_out, "Run the {} subsystem first to {}.", subsys, desc).unwrap()
+                writeln!(err_out, "{}", tr("required_data", &[("subsys", subsys), ("desc", desc)])).unwrap()
             }
-            Outcome::TwitterAPIError(ref error) => writeln!(err_out, "Twitter API error: {}", error).unwrap(),
+            Outcome::TwitterAPIError(ref error) => writeln!(err_out, "{}", tr("twitter_api_error", &[("error", error)])).unwrap(),
             Outcome::FileParsingFailed { ref desc, ref errors } => {
-                writeln!(err_out, "Failed to parse {}{}", desc, if errors.is_empty() { '.' } else { ':' }).unwrap();
+                let id = if errors.is_empty() {
+                    "file_parsing_failed"
+                } else {
+                    "file_parsing_failed_with_errors"
+                };
+                writeln!(err_out, "{}", tr(id, &[("desc", desc)])).unwrap();
+                for err in errors {
+                    writeln!(err_out, "  {}", err).unwrap()
+                }
+            }
+            Outcome::HookVetoed { ref hook, ref reason } => writeln!(err_out, "{}", tr("hook_vetoed", &[("hook", hook), ("reason", reason)])).unwrap(),
+            Outcome::PreflightFailed(ref problems) => {
+                writeln!(err_out, "{}", tr("preflight_failed", &[])).unwrap();
+                for problem in problems {
+                    writeln!(err_out, "  {}", problem).unwrap()
+                }
+            }
+            Outcome::BackendError { ref backend, ref error } => writeln!(err_out, "{}", tr("backend_error", &[("backend", backend), ("error", error)])).unwrap(),
+            Outcome::NetworkTimeout { ref desc } => writeln!(err_out, "{}", tr("network_timeout", &[("desc", desc)])).unwrap(),
+            Outcome::RateLimited { ref desc, reset: Some(reset) } => {
+                writeln!(err_out,
+                         "{}",
+                         tr("rate_limited", &[("desc", desc), ("time", &format_datetime(&Local.timestamp(reset, 0)))]))
+                    .unwrap()
+            }
+            Outcome::RateLimited { ref desc, reset: None } => writeln!(err_out, "{}", tr("rate_limited_later", &[("desc", desc)])).unwrap(),
+            Outcome::TemplateError { ref name, ref errors } => {
+                writeln!(err_out, "{}", tr("template_error", &[("name", name)])).unwrap();
                 for err in errors {
                     writeln!(err_out, "  {}", err).unwrap()
                 }
             }
+            Outcome::UpdateFailed(ref error) => writeln!(err_out, "{}", tr("update_failed", &[("error", error)])).unwrap(),
+            Outcome::IoError { ref path, ref op, ref cause } => {
+                writeln!(err_out, "{}", tr("io_error", &[("op", op), ("path", path), ("cause", cause)])).unwrap()
+            }
+            Outcome::Multiple(ref outcomes) => {
+                writeln!(err_out, "{}", tr("multiple_errors", &[("count", &outcomes.len())])).unwrap();
+                for (i, outcome) in outcomes.iter().enumerate() {
+                    let mut out = Vec::new();
+                    outcome.print_error(&mut out);
+
+                    let prefix = format!("  {}. ", i + 1);
+                    for (j, line) in String::from_utf8_lossy(&out).lines().enumerate() {
+                        if j == 0 {
+                            writeln!(err_out, "{}{}", prefix, line).unwrap();
+                        } else {
+                            writeln!(err_out, "{:2$}{}", "", line, prefix.len()).unwrap();
+                        }
+                    }
+                }
+            }
         }
     }
 
     /// Get the executable exit value from an `Outcome` instance.
     ///
+    /// Multiple outcomes exit with the value of the most severe one, or the first one of those.
+    ///
     /// # Examples
     ///
     /// ```
     /// # use std::process::exit;
     /// # use tweetr::Outcome;
+    /// assert_eq!(Outcome::Multiple(vec![Outcome::HookVetoed {
+    ///                                       hook: "pre-post",
+    ///                                       reason: "Too early".to_string(),
+    ///                                   },
+    ///                                   Outcome::TwitterAPIError("Status is a duplicate.".to_string())])
+    ///                .exit_value(),
+    ///            3);
     /// exit(Outcome::NoError.exit_value());
     /// ```
     pub fn exit_value(&self) -> i32 {
@@ -84,9 +257,50 @@ impl Outcome {
             Outcome::NoError => 0,
             Outcome::OverrideNoForce(_) => 1,
             Outcome::RequiredFileFromSubsystemNonexistant { .. } |
+            Outcome::SetupIncomplete(_) |
             Outcome::RequiredDataFromSubsystemNonexistant { .. } => 2,
             Outcome::TwitterAPIError(_) => 3,
             Outcome::FileParsingFailed { .. } => 4,
+            Outcome::HookVetoed { .. } => 5,
+            Outcome::PreflightFailed(_) => 6,
+            Outcome::BackendError { .. } => 7,
+            Outcome::TemplateError { .. } => 8,
+            Outcome::NetworkTimeout { .. } => 9,
+            Outcome::UpdateFailed(_) => 10,
+            Outcome::RateLimited { .. } => 11,
+            Outcome::IoError { .. } => 12,
+            Outcome::Multiple(ref outcomes) => {
+                outcomes.iter()
+                    .fold(None, |worst: Option<&Outcome>, o| match worst {
+                        Some(w) if w.severity() >= o.severity() => Some(w),
+                        _ => Some(o),
+                    })
+                    .map(Outcome::exit_value)
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    /// Configuration problems need fixing before anything works, delivery failures may go away by themselves,
+    /// and rejections are deliberate.
+    fn severity(&self) -> u8 {
+        match *self {
+            Outcome::NoError => 0,
+            Outcome::HookVetoed { .. } => 1,
+            Outcome::TwitterAPIError(_) |
+            Outcome::BackendError { .. } |
+            Outcome::NetworkTimeout { .. } |
+            Outcome::RateLimited { .. } |
+            Outcome::UpdateFailed(_) => 2,
+            Outcome::OverrideNoForce(_) |
+            Outcome::RequiredFileFromSubsystemNonexistant { .. } |
+            Outcome::SetupIncomplete(_) |
+            Outcome::RequiredDataFromSubsystemNonexistant { .. } |
+            Outcome::FileParsingFailed { .. } |
+            Outcome::PreflightFailed(_) |
+            Outcome::TemplateError { .. } |
+            Outcome::IoError { .. } => 3,
+            Outcome::Multiple(ref outcomes) => outcomes.iter().map(Outcome::severity).max().unwrap_or(0),
         }
     }
 }
@@ -0,0 +1,338 @@
+//! Decoding imported files, which needn't be in UTF-8.
+//!
+//! Files exported by other programs, especially on Windows, are often in UTF-16, or in Windows-1252, or start with a byte
+//! order mark. Without an encoding specified, it's detected: from the byte order mark, if any, then UTF-8, if the file is
+//! valid UTF-8, UTF-16, if every other byte is mostly zero, as in mostly ASCII text, and Windows-1252 otherwise.
+//!
+//! Invalid sequences are reported with the line and column they're at, and their offset in bytes from the start of the file.
+
+
+use std::fmt;
+use std::char;
+
+
+/// The encodings imported files can be in.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, with or without a byte order mark.
+    Utf8,
+    /// Little-endian UTF-16, with or without a byte order mark.
+    Utf16Le,
+    /// Big-endian UTF-16, with or without a byte order mark.
+    Utf16Be,
+    /// Windows-1252, a superset of ISO-8859-1 in all printable characters.
+    Windows1252,
+}
+
+/// A sequence of bytes invalid in the encoding a file's decoded from.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Invalid {
+    /// The offset of the sequence's first byte from the start of the file.
+    pub offset: usize,
+    /// The 1-based line the sequence is on.
+    pub line: usize,
+    /// The 1-based column of the sequence in its line, in characters.
+    pub column: usize,
+    /// The invalid bytes.
+    pub bytes: Vec<u8>,
+    /// The encoding they're invalid in.
+    pub encoding: Encoding,
+}
+
+
+/// Decode the specified file contents from the specified encoding, or the detected one, if `None`.
+///
+/// A leading byte order mark is removed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::encoding::{self, Encoding, Invalid};
+/// assert_eq!(encoding::decode(b"\xEF\xBB\xBFZa\xC5\xBC\xC3\xB3\xC5\x82\xC4\x87", None),
+///            Ok(("Zażółć".to_string(), Encoding::Utf8)));
+/// assert_eq!(encoding::decode(b"\xFF\xFEZ\x00a\x00|\x01", None), Ok(("Zaż".to_string(), Encoding::Utf16Le)));
+/// assert_eq!(encoding::decode(b"Caf\xE9 \x80", None), Ok(("Café €".to_string(), Encoding::Windows1252)));
+///
+/// assert_eq!(encoding::decode(b"Abolish\nthe\xE9 bourgeoisie", Some(Encoding::Utf8)),
+///            Err(vec![Invalid {
+///                         offset: 11,
+///                         line: 2,
+///                         column: 4,
+///                         bytes: vec![0xE9],
+///                         encoding: Encoding::Utf8,
+///                     }]));
+/// ```
+pub fn decode(bytes: &[u8], encoding: Option<Encoding>) -> Result<(String, Encoding), Vec<Invalid>> {
+    let (decoded, encoding, invalid) = decode_lossy(bytes, encoding);
+    if invalid.is_empty() {
+        Ok((decoded, encoding))
+    } else {
+        Err(invalid)
+    }
+}
+
+/// Decode the specified file contents like `decode()`, but replacing invalid sequences with U+FFFD, also returning them.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::encoding::{self, Encoding};
+/// let (decoded, encoding, invalid) = encoding::decode_lossy(b"\xFE\xFF\x00Z\xD8\x00\x00a", None);
+/// assert_eq!(decoded, "Z\u{FFFD}a");
+/// assert_eq!(encoding, Encoding::Utf16Be);
+/// assert_eq!(invalid.iter().map(|i| (i.offset, &i.bytes[..])).collect::<Vec<_>>(), vec![(4, &[0xD8, 0x00][..])]);
+/// ```
+pub fn decode_lossy(bytes: &[u8], encoding: Option<Encoding>) -> (String, Encoding, Vec<Invalid>) {
+    let encoding = encoding.unwrap_or_else(|| Encoding::detect(bytes));
+    let (decoded, invalid) = match encoding {
+        Encoding::Utf8 => decode_utf8(strip_prefix(bytes, b"\xEF\xBB\xBF")),
+        Encoding::Utf16Le => decode_utf16(strip_prefix(bytes, b"\xFF\xFE"), |b| b[0] as u16 | ((b[1] as u16) << 8)),
+        Encoding::Utf16Be => decode_utf16(strip_prefix(bytes, b"\xFE\xFF"), |b| ((b[0] as u16) << 8) | b[1] as u16),
+        Encoding::Windows1252 => decode_windows1252(bytes),
+    };
+
+    let bom = match encoding {
+        Encoding::Utf8 if bytes.starts_with(b"\xEF\xBB\xBF") => 3,
+        Encoding::Utf16Le if bytes.starts_with(b"\xFF\xFE") => 2,
+        Encoding::Utf16Be if bytes.starts_with(b"\xFE\xFF") => 2,
+        _ => 0,
+    };
+    let invalid = invalid.into_iter()
+        .map(|(offset, len, pos)| {
+            let before = &decoded[..pos];
+            Invalid {
+                offset: bom + offset,
+                line: before.matches('\n').count() + 1,
+                column: before.rsplit('\n').next().unwrap_or("").chars().count() + 1,
+                bytes: bytes[bom + offset..bom + offset + len].to_vec(),
+                encoding: encoding,
+            }
+        })
+        .collect();
+    (decoded, encoding, invalid)
+}
+
+
+impl Encoding {
+    /// Parse an encoding from its name, in any case, with or without the dash.
+    ///
+    /// `cp1252` is accepted for Windows-1252.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::encoding::Encoding;
+    /// assert_eq!(Encoding::parse("utf-8"), Ok(Encoding::Utf8));
+    /// assert_eq!(Encoding::parse("UTF16LE"), Ok(Encoding::Utf16Le));
+    /// assert_eq!(Encoding::parse("cp1252"), Ok(Encoding::Windows1252));
+    /// assert_eq!(Encoding::parse("latin2"),
+    ///            Err("\"latin2\" is not one of utf-8, utf-16le, utf-16be or windows-1252".to_string()));
+    /// ```
+    pub fn parse(s: &str) -> Result<Encoding, String> {
+        match &s.to_lowercase().replace('-', "")[..] {
+            "utf8" => Ok(Encoding::Utf8),
+            "utf16le" => Ok(Encoding::Utf16Le),
+            "utf16be" => Ok(Encoding::Utf16Be),
+            "windows1252" | "cp1252" => Ok(Encoding::Windows1252),
+            _ => Err(format!("\"{}\" is not one of utf-8, utf-16le, utf-16be or windows-1252", s)),
+        }
+    }
+
+    /// Get the encoding's name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::encoding::Encoding;
+    /// assert_eq!(Encoding::Utf16Be.name(), "UTF-16BE");
+    /// assert_eq!(Encoding::Windows1252.name(), "Windows-1252");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "Windows-1252",
+        }
+    }
+
+    /// Detect the encoding of the specified file contents.
+    ///
+    /// Byte order marks win, then UTF-16 is assumed if every other byte is mostly zero, since text never has that many NULs,
+    /// then UTF-8 if it's valid, and Windows-1252 otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::encoding::Encoding;
+    /// assert_eq!(Encoding::detect(b"\xFE\xFF\x00Z\x00a"), Encoding::Utf16Be);
+    /// assert_eq!(Encoding::detect(b"Z\x00a\x00|\x01"), Encoding::Utf16Le);
+    /// assert_eq!(Encoding::detect("Zażółć".as_bytes()), Encoding::Utf8);
+    /// assert_eq!(Encoding::detect(b"Caf\xE9"), Encoding::Windows1252);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        if bytes.starts_with(b"\xEF\xBB\xBF") {
+            return Encoding::Utf8;
+        } else if bytes.starts_with(b"\xFF\xFE") {
+            return Encoding::Utf16Le;
+        } else if bytes.starts_with(b"\xFE\xFF") {
+            return Encoding::Utf16Be;
+        }
+
+        let units = bytes.len() / 2;
+        let zeroes = |first: usize| bytes.iter().enumerate().filter(|&(i, &b)| i % 2 == first && b == 0).count();
+        if units != 0 && bytes.len() % 2 == 0 {
+            if zeroes(1) * 2 > units {
+                return Encoding::Utf16Le;
+            } else if zeroes(0) * 2 > units {
+                return Encoding::Utf16Be;
+            }
+        }
+
+        if ::std::str::from_utf8(bytes).is_ok() {
+            Encoding::Utf8
+        } else {
+            Encoding::Windows1252
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Invalid {
+    /// Describe the sequence, without its position in lines and columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::encoding::{Encoding, Invalid};
+    /// assert_eq!(Invalid {
+    ///                    offset: 11,
+    ///                    line: 2,
+    ///                    column: 4,
+    ///                    bytes: vec![0xC3, 0x28],
+    ///                    encoding: Encoding::Utf8,
+    ///                }
+    ///                .description(),
+    ///            "invalid UTF-8 sequence 0xC3 0x28 at byte offset 11");
+    /// ```
+    pub fn description(&self) -> String {
+        format!("invalid {} sequence {} at byte offset {}",
+                self.encoding,
+                self.bytes.iter().map(|b| format!("0x{:02X}", b)).collect::<Vec<_>>().join(" "),
+                self.offset)
+    }
+}
+
+impl fmt::Display for Invalid {
+    /// Formatted like the other file parsing errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::encoding::{Encoding, Invalid};
+    /// assert_eq!(Invalid {
+    ///                    offset: 11,
+    ///                    line: 2,
+    ///                    column: 4,
+    ///                    bytes: vec![0x81],
+    ///                    encoding: Encoding::Windows1252,
+    ///                }
+    ///                .to_string(),
+    ///            "error: 2:4: invalid Windows-1252 sequence 0x81 at byte offset 11");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}:{}: {}", self.line, self.column, self.description())
+    }
+}
+
+
+fn strip_prefix<'b>(bytes: &'b [u8], prefix: &[u8]) -> &'b [u8] {
+    if bytes.starts_with(prefix) {
+        &bytes[prefix.len()..]
+    } else {
+        bytes
+    }
+}
+
+/// Decode, replacing invalid sequences with U+FFFD, and returning them as their offset, length, and position in the
+/// decoded string.
+fn decode_utf8(mut bytes: &[u8]) -> (String, Vec<(usize, usize, usize)>) {
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut invalid = vec![];
+    let mut offset = 0;
+    loop {
+        match ::std::str::from_utf8(bytes) {
+            Ok(s) => {
+                decoded.push_str(s);
+                break;
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                decoded.push_str(::std::str::from_utf8(&bytes[..valid]).unwrap());
+                let len = e.error_len().unwrap_or(bytes.len() - valid);
+                invalid.push((offset + valid, len, decoded.len()));
+                decoded.push(char::REPLACEMENT_CHARACTER);
+
+                bytes = &bytes[valid + len..];
+                offset += valid + len;
+            }
+        }
+    }
+    (decoded, invalid)
+}
+
+fn decode_utf16<F: Fn(&[u8]) -> u16>(bytes: &[u8], unit: F) -> (String, Vec<(usize, usize, usize)>) {
+    let mut decoded = String::with_capacity(bytes.len() / 2);
+    let mut invalid = vec![];
+    let mut offset = 0;
+    for c in char::decode_utf16(bytes.chunks(2).filter(|c| c.len() == 2).map(|c| unit(c))) {
+        match c {
+            Ok(c) => {
+                decoded.push(c);
+                offset += c.len_utf16() * 2;
+            }
+            Err(_) => {
+                invalid.push((offset, 2, decoded.len()));
+                decoded.push(char::REPLACEMENT_CHARACTER);
+                offset += 2;
+            }
+        }
+    }
+    if bytes.len() % 2 != 0 {
+        invalid.push((bytes.len() - 1, 1, decoded.len()));
+        decoded.push(char::REPLACEMENT_CHARACTER);
+    }
+    (decoded, invalid)
+}
+
+/// The characters Windows-1252 has in 0x80-0x9F, where ISO-8859-1 has control characters, `None` where it has nothing.
+static WINDOWS1252_HIGH: [Option<char>; 32] = [Some('€'), None, Some('‚'), Some('ƒ'), Some('„'), Some('…'), Some('†'), Some('‡'),
+                                                Some('ˆ'), Some('‰'), Some('Š'), Some('‹'), Some('Œ'), None, Some('Ž'), None, None,
+                                                Some('‘'), Some('’'), Some('“'), Some('”'), Some('•'), Some('–'), Some('—'),
+                                                Some('˜'), Some('™'), Some('š'), Some('›'), Some('œ'), None, Some('ž'), Some('Ÿ')];
+
+fn decode_windows1252(bytes: &[u8]) -> (String, Vec<(usize, usize, usize)>) {
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut invalid = vec![];
+    for (offset, &b) in bytes.iter().enumerate() {
+        match b {
+            0x80...0x9F => {
+                match WINDOWS1252_HIGH[(b - 0x80) as usize] {
+                    Some(c) => decoded.push(c),
+                    None => {
+                        invalid.push((offset, 1, decoded.len()));
+                        decoded.push(char::REPLACEMENT_CHARACTER);
+                    }
+                }
+            }
+            // The rest matches ISO-8859-1, and so Unicode's first 256 code points
+            b => decoded.push(b as char),
+        }
+    }
+    (decoded, invalid)
+}
@@ -0,0 +1,1146 @@
+//! Module containing various utility functions.
+
+
+use std::io::{BufRead, Write, Result as IoResult, Error, ErrorKind};
+use chrono::{Duration as ChronoDuration};
+use std::time::Duration;
+use std::process::Command;
+use std::env::{self, VarError};
+use std::str::FromStr;
+use std::iter;
+use self::super::ops::queue_tweet;
+use std::fmt;
+use atty;
+
+pub mod encoding;
+pub mod table;
+pub mod terminal;
+
+
+/// The datetime format returned by Twitter when posting.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::util::TWEET_DATETIME_FORMAT;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// assert_eq!(DateTime::parse_from_str("Mon Sep 05 20:30:51 +0000 2016", TWEET_DATETIME_FORMAT),
+///            DateTime::parse_from_rfc3339("2016-09-05T20:30:51+00:00"));
+/// # }
+/// ```
+pub static TWEET_DATETIME_FORMAT: &'static str = "%a %b %d %T %z %Y";
+
+
+
+/// The maximal weighted length of a tweet, as measured by `tweet_length()`.
+pub const TWEET_MAX_LENGTH: usize = 280;
+
+/// The weighted length of every link in a tweet, regardless of its actual length.
+pub const TWEET_URL_LENGTH: usize = 23;
+
+
+/// Runs a closure, returning the duration of time it took to run the closure and the closure's return value.
+///
+/// Equivalent to https://github.com/rust-lang-deprecated/time/pull/139
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::span_r;
+/// # use std::time::Duration;
+/// # use std::thread;
+/// let (dur, i) = span_r(|| {
+///     thread::sleep(Duration::from_millis(10));
+///     420
+/// });
+/// assert_eq!(i, 420);
+/// assert!(dur.num_milliseconds() >= 10);
+/// ```
+pub fn span_r<F, R>(f: F) -> (ChronoDuration, R)
+    where F: FnOnce() -> R
+{
+    let mut res = None;
+    let span = ChronoDuration::span(|| res = Some(f()));
+    (span, res.unwrap())
+}
+
+/// Create a string consisting of `n` repetitions of `what`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::mul_str;
+/// assert_eq!(mul_str("Го! ", 3), "Го! Го! Го! ".to_string());
+/// ```
+pub fn mul_str(what: &str, n: usize) -> String {
+    iter::repeat(what).take(n).collect()
+}
+
+/// Why a relative datetime couldn't be parsed, see `parse_relative_time()`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum RelativeTimeError {
+    /// The unit of time isn't one of the supported ones.
+    UnknownUnit {
+        /// The unit specified.
+        got: String,
+    },
+    /// The amount of time is too large to represent.
+    NumberOverflow,
+    /// The datetime doesn't have the expected form.
+    BadSyntax {
+        /// What was expected instead.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for RelativeTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RelativeTimeError::UnknownUnit { ref got } => {
+                write!(f, "unknown unit \"{}\", expected one of seconds, minutes, hours, days or weeks", got)
+            }
+            RelativeTimeError::NumberOverflow => write!(f, "amount of time too large"),
+            RelativeTimeError::BadSyntax { expected } => write!(f, "expected {}", expected),
+        }
+    }
+}
+
+/// Parse a relative datetime into a `Duration`.
+///
+/// This has the form of
+///
+///   * `now` - current datetime
+///   * `in` *n* [`second`|`minute`|`hour`|`day`|`week`]{,`s`} {{`and`} *n* [...]{,`s`}...} (case-insensitive) -
+///       current datetime plus the specified amount of time, summed if in multiple parts, in any order
+///   * `+`*n* - current datetime plus the specified amount of seconds
+///   * {`in`} `P`{*n*`W`}{*n*`D`}{`T`{*n*`H`}{*n*`M`}{*n*`S`}} (case-insensitive) - current datetime plus the specified
+///       ISO 8601 duration, without years and months, which vary in length, nor fractions
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{RelativeTimeError, parse_relative_time};
+/// # use std::time::Duration;
+/// assert_eq!(parse_relative_time("now").unwrap(), Duration::from_secs(0));
+///
+/// assert_eq!(parse_relative_time("in 3 seconds").unwrap(), Duration::from_secs(3));
+/// assert_eq!(parse_relative_time("in 5 MINUTES").unwrap(), Duration::from_secs(60*5));
+/// assert_eq!(parse_relative_time("iN 1 hOur").unwrap(), Duration::from_secs(60*60*1));
+/// assert_eq!(parse_relative_time("in 2 daYs").unwrap(), Duration::from_secs(60*60*24*2));
+/// assert_eq!(parse_relative_time("in 4 weeks").unwrap(), Duration::from_secs(60*60*24*7*4));
+/// assert_eq!(parse_relative_time("in 1 hour 30 minutes").unwrap(), Duration::from_secs(60*90));
+/// assert_eq!(parse_relative_time("in 2 days and 4 hours").unwrap(), Duration::from_secs(60*60*52));
+/// assert_eq!(parse_relative_time("in PT2H30M").unwrap(), Duration::from_secs(60*60*2 + 60*30));
+/// assert_eq!(parse_relative_time("PT45M").unwrap(), Duration::from_secs(60*45));
+/// assert_eq!(parse_relative_time("P1DT12H").unwrap(), Duration::from_secs(60*60*36));
+/// assert_eq!(parse_relative_time("+3600").unwrap(), Duration::from_secs(60*60));
+///
+/// assert!(parse_relative_time("in a23d weeks").is_err());
+/// assert!(parse_relative_time("in 23 wsfas1eeks").is_err());
+/// assert!(parse_relative_time("23 wsfas1eeks").is_err());
+///
+/// assert_eq!(parse_relative_time("in 5 fortnights"),
+///            Err(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() }));
+/// assert_eq!(parse_relative_time("in 99999999999999999999 seconds"), Err(RelativeTimeError::NumberOverflow));
+/// assert_eq!(parse_relative_time("in five minutes"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"in\"" }));
+/// assert_eq!(parse_relative_time("+1h"), Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"+\"" }));
+/// assert_eq!(parse_relative_time("P1M"),
+///            Err(RelativeTimeError::BadSyntax { expected: "only weeks and days before \"T\" in the ISO 8601 duration" }));
+/// ```
+pub fn parse_relative_time(delta: &str) -> Result<Duration, RelativeTimeError> {
+    let mut words = delta.split_whitespace();
+
+    match words.next().map(|w| w.to_lowercase()) {
+        Some(ref w) if w == "now" => {
+            return match words.next() {
+                Some(_) => Err(RelativeTimeError::BadSyntax { expected: "nothing after \"now\"" }),
+                None => Ok(Duration::new(0, 0)),
+            }
+        }
+        Some(ref w) if w == "in" => (),
+        Some(ref w) if w.starts_with('+') => {
+            let secs = try!(whole_number(&w[1..], "a whole number of seconds after \"+\""));
+            return match words.next() {
+                Some(_) => Err(RelativeTimeError::BadSyntax { expected: "nothing after the amount of seconds" }),
+                None => Ok(Duration::from_secs(secs)),
+            };
+        }
+        Some(ref w) if is_iso8601_duration(w) => return iso8601_duration(w, words.next()),
+        _ => return Err(RelativeTimeError::BadSyntax { expected: "\"now\" or \"in\"" }),
+    }
+
+    let n = words.next();
+    if let Some(w) = n.map(|w| w.to_lowercase()) {
+        if is_iso8601_duration(&w) {
+            return iso8601_duration(&w, words.next());
+        }
+    }
+
+    let mut secs = try!(amount_of_time(n, words.next(), "a whole number after \"in\""));
+    while let Some(w) = words.next() {
+        let part = if w.to_lowercase() == "and" {
+            try!(amount_of_time(words.next(), words.next(), "a whole number after \"and\""))
+        } else if w.starts_with(|c: char| c.is_digit(10)) {
+            try!(amount_of_time(Some(w), words.next(), "a whole number after the unit of time"))
+        } else {
+            return Err(RelativeTimeError::BadSyntax { expected: "nothing after the unit of time" });
+        };
+        secs = try!(secs.checked_add(part).ok_or(RelativeTimeError::NumberOverflow));
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Parse a datetime relative to another tweet into the amount of seconds after it and its name, if it looks like one.
+///
+/// This has the form of `after` *name* or *n* [`second`|`minute`|`hour`|`day`|`week`]{,`s`} `after` *name*
+/// (case-insensitive), with *name* being a single word.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{RelativeTimeError, parse_anchored_time};
+/// assert_eq!(parse_anchored_time("15 minutes after launch"), Some(Ok((15 * 60, "launch"))));
+/// assert_eq!(parse_anchored_time("After launch"), Some(Ok((0, "launch"))));
+///
+/// assert_eq!(parse_anchored_time("in 15 minutes"), None);
+/// assert_eq!(parse_anchored_time("15 fortnights after launch"),
+///            Some(Err(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() })));
+/// assert_eq!(parse_anchored_time("15 minutes after the launch"),
+///            Some(Err(RelativeTimeError::BadSyntax { expected: "nothing after the tweet name" })));
+/// ```
+pub fn parse_anchored_time(time: &str) -> Option<Result<(u64, &str), RelativeTimeError>> {
+    let words: Vec<_> = time.split_whitespace().collect();
+    let is_after = |i: usize| words.get(i).map(|w| w.to_lowercase() == "after").unwrap_or(false);
+
+    let (secs, rest) = if is_after(0) {
+        (Ok(0), &words[1..])
+    } else if is_after(2) {
+        (amount_of_time(words.get(0).cloned(), words.get(1).cloned(), "a whole number before the unit of time"), &words[3..])
+    } else {
+        return None;
+    };
+
+    Some(secs.and_then(|secs| match rest.len() {
+        0 => Err(RelativeTimeError::BadSyntax { expected: "a tweet name after \"after\"" }),
+        1 => Ok((secs, rest[0])),
+        _ => Err(RelativeTimeError::BadSyntax { expected: "nothing after the tweet name" }),
+    }))
+}
+
+/// Parse a Unix time, in seconds since the epoch, if it looks like one.
+///
+/// This has the form of `@`*n*, with *n* optionally preceded by `-`, for times before 1970.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{RelativeTimeError, parse_unix_time};
+/// assert_eq!(parse_unix_time("@1719842400"), Some(Ok(1719842400)));
+/// assert_eq!(parse_unix_time(" @-86400 "), Some(Ok(-86400)));
+///
+/// assert_eq!(parse_unix_time("in 2 hours"), None);
+/// assert_eq!(parse_unix_time("@tomorrow"),
+///            Some(Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"@\"" })));
+/// assert_eq!(parse_unix_time("@99999999999999999999"), Some(Err(RelativeTimeError::NumberOverflow)));
+/// ```
+pub fn parse_unix_time(time: &str) -> Option<Result<i64, RelativeTimeError>> {
+    let time = time.trim();
+    if !time.starts_with('@') {
+        return None;
+    }
+
+    let (before, time) = if time[1..].starts_with('-') {
+        (true, &time[2..])
+    } else {
+        (false, &time[1..])
+    };
+    Some(whole_number(time, "a whole number of seconds after \"@\"").and_then(|secs| if secs > i64::max_value() as u64 {
+        Err(RelativeTimeError::NumberOverflow)
+    } else if before {
+        Ok(-(secs as i64))
+    } else {
+        Ok(secs as i64)
+    }))
+}
+
+/// Parse an amount of time to move tweets by into seconds, negative to move them earlier.
+///
+/// This has the form of {`-`}*n* [`second`|`minute`|`hour`|`day`|`week`]{,`s`} (case-insensitive).
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{RelativeTimeError, parse_time_shift};
+/// assert_eq!(parse_time_shift("2 hours"), Ok(2 * 60 * 60));
+/// assert_eq!(parse_time_shift("-1 DAY"), Ok(-60 * 60 * 24));
+///
+/// assert_eq!(parse_time_shift("in 2 hours"),
+///            Err(RelativeTimeError::BadSyntax { expected: "a whole number, optionally preceded by \"-\"" }));
+/// assert_eq!(parse_time_shift("2 hours later"),
+///            Err(RelativeTimeError::BadSyntax { expected: "nothing after the unit of time" }));
+/// assert_eq!(parse_time_shift("99999999999999999 weeks"), Err(RelativeTimeError::NumberOverflow));
+/// ```
+pub fn parse_time_shift(shift: &str) -> Result<i64, RelativeTimeError> {
+    let shift = shift.trim_left();
+    let (earlier, shift) = if shift.starts_with('-') {
+        (true, &shift[1..])
+    } else {
+        (false, shift)
+    };
+
+    let mut words = shift.split_whitespace();
+    let secs = try!(amount_of_time(words.next(), words.next(), "a whole number, optionally preceded by \"-\""));
+    if words.next().is_some() {
+        return Err(RelativeTimeError::BadSyntax { expected: "nothing after the unit of time" });
+    }
+
+    if secs > i64::max_value() as u64 {
+        Err(RelativeTimeError::NumberOverflow)
+    } else if earlier {
+        Ok(-(secs as i64))
+    } else {
+        Ok(secs as i64)
+    }
+}
+
+/// Format the specified amount of seconds in the largest unit of `parse_relative_time()` it's a whole number of.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::format_amount_of_time;
+/// assert_eq!(format_amount_of_time(15 * 60), "15 minutes");
+/// assert_eq!(format_amount_of_time(60 * 60 * 24 * 7), "1 week");
+/// assert_eq!(format_amount_of_time(90), "90 seconds");
+/// ```
+pub fn format_amount_of_time(secs: u64) -> String {
+    let &(unit, mul) = [("week", 60 * 60 * 24 * 7), ("day", 60 * 60 * 24), ("hour", 60 * 60), ("minute", 60)]
+        .iter()
+        .find(|&&(_, mul)| secs != 0 && secs % mul == 0)
+        .unwrap_or(&("second", 1));
+
+    let n = secs / mul;
+    format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+}
+
+/// Format the specified amount of seconds in the largest unit of `parse_relative_time()` it's at least one of, rounding down.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::format_approximate_time;
+/// assert_eq!(format_approximate_time(3 * 60 * 60 + 25 * 60), "3 hours");
+/// assert_eq!(format_approximate_time(60 * 60 * 24 * 10), "1 week");
+/// assert_eq!(format_approximate_time(90), "1 minute");
+/// assert_eq!(format_approximate_time(0), "0 seconds");
+/// ```
+pub fn format_approximate_time(secs: u64) -> String {
+    let &(unit, mul) = [("week", 60 * 60 * 24 * 7), ("day", 60 * 60 * 24), ("hour", 60 * 60), ("minute", 60)]
+        .iter()
+        .find(|&&(_, mul)| secs >= mul)
+        .unwrap_or(&("second", 1));
+
+    let n = secs / mul;
+    format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+}
+
+/// Format the specified amount of bytes in the largest binary unit it's at least one of.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::format_size;
+/// assert_eq!(format_size(0), "0 B");
+/// assert_eq!(format_size(1023), "1023 B");
+/// assert_eq!(format_size(1536), "1.5 KiB");
+/// assert_eq!(format_size(15 * 1024 * 1024), "15.0 MiB");
+/// ```
+pub fn format_size(bytes: u64) -> String {
+    match [("GiB", 1024 * 1024 * 1024), ("MiB", 1024 * 1024), ("KiB", 1024)].iter().find(|&&(_, mul)| bytes >= mul) {
+        Some(&(unit, mul)) => format!("{:.1} {}", bytes as f64 / mul as f64, unit),
+        None => format!("{} B", bytes),
+    }
+}
+
+/// Get the weighted length of the specified tweet content, as counted by Twitter.
+///
+/// Links count as `TWEET_URL_LENGTH` characters, characters from the Latin-1 to Georgian blocks and some punctuation count
+/// as one, and everything else (e.g. CJK and emoji) counts as two.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::tweet_length;
+/// assert_eq!(tweet_length("Abolish the bourgeoisie!"), 24);
+/// assert_eq!(tweet_length("Zażółć gęślą jaźń"), 17);
+/// assert_eq!(tweet_length("資本主義"), 8);
+/// assert_eq!(tweet_length("Read https://github.com/nabijaczleweli/tweetr/blob/master/README.md"), 5 + 23);
+/// ```
+pub fn tweet_length(content: &str) -> usize {
+    let mut len = 0;
+    let mut last_end = 0;
+    for url in queue_tweet::find_urls(content) {
+        let start = url.as_ptr() as usize - content.as_ptr() as usize;
+        len += weighted_char_length(&content[last_end..start]) + TWEET_URL_LENGTH;
+        last_end = start + url.len();
+    }
+    len + weighted_char_length(&content[last_end..])
+}
+
+/// Create a command running the specified command line in the system shell, `sh` or `cmd`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::shell;
+/// # #[cfg(not(windows))]
+/// assert_eq!(shell("printf '%s' \"Abolish the bourgeoisie\"").output().unwrap().stdout, b"Abolish the bourgeoisie");
+/// ```
+#[cfg(not(windows))]
+pub fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+/// Create a command running the specified command line in the system shell, `sh` or `cmd`.
+#[cfg(windows)]
+pub fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Ask the user to input a string of the exact length of `desired_len`, (re)prompting as necessary.
+///
+/// # Examples
+///
+/// Allow anything 10 charactes long:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::prompt_exact_len;
+/// assert_eq!(prompt_exact_len(&mut Cursor::new(b"0123456789"),
+///                             &mut Vec::new(),
+///                             "Allowed chars",
+///                             |_| true,
+///                             10).unwrap(),
+///            "0123456789".to_string());
+/// ```
+///
+/// Allow a 10-character-long `u64`:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use std::str::FromStr;
+/// # use tweetr::util::prompt_exact_len;
+/// assert_eq!(prompt_exact_len(&mut Cursor::new(b"1234567890"),
+///                             &mut Vec::new(),
+///                             "Long number",
+///                             |s| u64::from_str(s).is_ok(),
+///                             10).unwrap(),
+///            "1234567890".to_string());
+/// assert!(prompt_exact_len(&mut Cursor::new(b"1234abcdef"),
+///                          &mut Vec::new(),
+///                          "Long number",
+///                          |s| u64::from_str(s).is_ok(),
+///                          10).is_err());
+/// ```
+pub fn prompt_exact_len<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: F, desired_len: usize) -> IoResult<String>
+    where R: BufRead,
+          W: Write,
+          F: Fn(&String) -> bool
+{
+    let mut out = String::new();
+
+    while out.len() != desired_len {
+        try!(prompt(input, output, prompt_s, &verifier, false, true, &mut out));
+    }
+
+    Ok(out)
+}
+
+/// Ask the user to input a string of non-zero length, (re)prompting as necessary.
+///
+/// # Examples
+///
+/// Allow anything as long as it's *some*thing:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::prompt_nonzero_len;
+/// assert_eq!(prompt_nonzero_len(&mut Cursor::new(b"123456789"),
+///                               &mut Vec::new(),
+///                               "Allowed chars",
+///                               |_| true).unwrap(),
+///            "123456789".to_string());
+/// ```
+///
+/// Allow valid `u64`s:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use std::str::FromStr;
+/// # use tweetr::util::prompt_nonzero_len;
+/// assert_eq!(prompt_nonzero_len(&mut Cursor::new(b"123456789"),
+///                               &mut Vec::new(),
+///                               "Number",
+///                               |s| u64::from_str(s).is_ok()).unwrap(),
+///            "123456789".to_string());
+/// assert!(prompt_nonzero_len(&mut Cursor::new(b"123abcdef"),
+///                            &mut Vec::new(),
+///                            "Number",
+///                            |s| u64::from_str(s).is_ok()).is_err());
+/// ```
+pub fn prompt_nonzero_len<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: F) -> IoResult<String>
+    where R: BufRead,
+          W: Write,
+          F: Fn(&String) -> bool
+{
+    let mut out = String::new();
+
+    while out.is_empty() {
+        try!(prompt(input, output, prompt_s, &verifier, false, true, &mut out));
+    }
+
+    Ok(out)
+}
+
+/// Ask the user to input a string of any length after printing a prompt prompting.
+///
+/// Will return `None` if the string is empty.
+///
+/// # Examples
+///
+/// Allow anything:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::prompt_any_len;
+/// assert_eq!(prompt_any_len(&mut Cursor::new(b"123456789"),
+///                           &mut Vec::new(),
+///                           "Allowed chars",
+///                           |_| true).unwrap().unwrap(),
+///            "123456789".to_string());
+/// assert_eq!(prompt_any_len(&mut Cursor::new(b""),
+///                           &mut Vec::new(),
+///                           "Allowed chars",
+///                           |_| true).unwrap(),
+///            None);
+/// ```
+///
+/// Allow valid `u64`s:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use std::str::FromStr;
+/// # use tweetr::util::prompt_any_len;
+/// assert_eq!(prompt_any_len(&mut Cursor::new(b"123456789"),
+///                           &mut Vec::new(),
+///                           "Number",
+///                           |s| u64::from_str(s).is_ok()).unwrap().unwrap(),
+///            "123456789".to_string());
+/// assert_eq!(prompt_any_len(&mut Cursor::new(b"123abcdef"),
+///                           &mut Vec::new(),
+///                           "Number",
+///                           |s| u64::from_str(s).is_ok()).map_err(|_| ()),
+///            Ok(None));
+/// ```
+pub fn prompt_any_len<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: F) -> IoResult<Option<String>>
+    where R: BufRead,
+          W: Write,
+          F: Fn(&String) -> bool
+{
+    let mut out = String::new();
+    try!(prompt(input, output, prompt_s, &verifier, true, true, &mut out));
+
+    if out.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(out))
+    }
+}
+
+/// Ask the user to input a multiline string, (re)prompting as necessary.
+///
+/// Each line is separated by a `\`, but can be escaped by `\\`, e.g.
+///
+/// ```plaintext
+/// Prompt: Abolish\
+///         the\
+///         burgeoisie!
+/// ```
+///
+/// Will yield `"Abolish\nthe\nburgeoisie!"`, but
+///
+/// ```plaintext
+/// Prompt: Capitalism\\
+/// ```
+///
+/// Will yield `r"Capitalism\"`.
+///
+/// The lines after the first one are prompted for as configured by the specified `MultilineOptions`, see
+/// `MultilineOptions::reprompt()`.
+///
+/// # Examples
+///
+/// Reading multiple lines:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::{MultilineOptions, prompt_multiline};
+/// assert_eq!(prompt_multiline(&mut Cursor::new(b"Line 1\\\nLine 2\\\nLine 3"),
+///                             &mut Vec::new(),
+///                             "Lines",
+///                             |_| true,
+///                             MultilineOptions::default()).unwrap(),
+///            "Line 1\nLine 2\nLine 3".to_string());
+/// ```
+///
+/// Escaping newline:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::{MultilineOptions, prompt_multiline};
+/// assert_eq!(prompt_multiline(&mut Cursor::new(b"Line 0\\\\\n"),
+///                             &mut Vec::new(),
+///                             "Escaped line",
+///                             |_| true,
+///                             MultilineOptions::default()).unwrap(),
+///            "Line 0\\".to_string());
+/// ```
+///
+/// Accepting only two-line strings:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::{MultilineOptions, prompt_multiline};
+/// assert_eq!(prompt_multiline(&mut Cursor::new(b"Line 1\\\nLine 2\n"),
+///                             &mut Vec::new(),
+///                             "2 lines",
+///                             |s| s.lines().count() == 2,
+///                             MultilineOptions::default()).unwrap(),
+///            "Line 1\nLine 2".to_string());
+/// ```
+///
+/// Numbering the lines:
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::{MultilineOptions, prompt_multiline};
+/// let mut out = Vec::new();
+/// assert_eq!(prompt_multiline(&mut Cursor::new(b"Abolish\\\nthe\\\nburgeoisie!\n"),
+///                             &mut out,
+///                             "Content",
+///                             |_| true,
+///                             MultilineOptions {
+///                                 line_numbers: true,
+///                                 char_count: false,
+///                             }).unwrap(),
+///            "Abolish\nthe\nburgeoisie!".to_string());
+/// assert_eq!(String::from_utf8(out).unwrap(), "Content:      2 |      3 | ");
+/// ```
+pub fn prompt_multiline<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: F, options: MultilineOptions) -> IoResult<String>
+    where R: BufRead,
+          W: Write,
+          F: Fn(&String) -> bool
+{
+    let mut lbuf = String::new();
+    let mut buf = String::new();
+
+    while buf.is_empty() {
+        buf = try!(prompt_nonzero_len(input, output, prompt_s, |_| true));
+
+        let mut line = 1;
+        while buf.ends_with(r"\") && !buf.ends_with(r"\\") {
+            buf.pop();
+            buf.push('\n');
+            line += 1;
+
+            try!(prompt(input,
+                        output,
+                        &options.reprompt_wrapped(prompt_s, line, &buf, terminal::prompt_width()),
+                        &|_| true,
+                        false,
+                        false,
+                        &mut lbuf));
+            buf.push_str(&lbuf);
+        }
+
+        if buf.ends_with(r"\\") {
+            buf.pop();
+        }
+
+        if !verifier(&buf) {
+            buf.clear();
+        }
+    }
+
+    Ok(buf)
+}
+
+/// How `prompt_multiline()` prompts for the lines after the first one.
+///
+/// By default, they're prompted for with just enough spaces to align them with the first line.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+pub struct MultilineOptions {
+    /// Number the lines, as in `  2 | `.
+    pub line_numbers: bool,
+    /// Tally the length of the string so far, as measured by `tweet_length()`, as in `[57] `.
+    pub char_count: bool,
+}
+
+impl MultilineOptions {
+    /// Get the prompt for the specified line of a multiline string, with the specified contents so far, that was first
+    /// prompted for with the specified prompt.
+    ///
+    /// The prompt is right-aligned with the first one's colon, if it fits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::MultilineOptions;
+    /// assert_eq!(MultilineOptions::default().reprompt("Content", 2, "Abolish\n"), "         ");
+    /// assert_eq!(MultilineOptions {
+    ///                    line_numbers: true,
+    ///                    char_count: false,
+    ///                }
+    ///                .reprompt("Content", 2, "Abolish\n"),
+    ///            "     2 | ");
+    /// assert_eq!(MultilineOptions {
+    ///                    line_numbers: true,
+    ///                    char_count: true,
+    ///                }
+    ///                .reprompt("Content", 3, "Abolish\nthe\n"),
+    ///            "[12] 3 | ");
+    /// assert_eq!(MultilineOptions {
+    ///                    line_numbers: false,
+    ///                    char_count: true,
+    ///                }
+    ///                .reprompt("Treść", 2, "Znieść\n"),
+    ///            "   [7] ");
+    /// ```
+    pub fn reprompt(&self, prompt_s: &str, line: usize, content: &str) -> String {
+        self.reprompt_wrapped(prompt_s, line, content, None)
+    }
+
+    /// Get the prompt for the specified line of a multiline string, like `reprompt()`, but with the first prompt wrapped to
+    /// the specified width, if any, as `prompt_multiline()` does in terminals.
+    ///
+    /// The prompt is then right-aligned with the colon at the end of the first prompt's last line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::MultilineOptions;
+    /// assert_eq!(MultilineOptions::default().reprompt_wrapped("Content of the tweet", 2, "Abolish\n", Some(16)), "       ");
+    /// assert_eq!(MultilineOptions {
+    ///                    line_numbers: true,
+    ///                    char_count: false,
+    ///                }
+    ///                .reprompt_wrapped("Content of the tweet", 2, "Abolish\n", Some(16)),
+    ///            "   2 | ");
+    /// assert_eq!(MultilineOptions::default().reprompt_wrapped("Content of the tweet", 2, "Abolish\n", None),
+    ///            MultilineOptions::default().reprompt("Content of the tweet", 2, "Abolish\n"));
+    /// ```
+    pub fn reprompt_wrapped(&self, prompt_s: &str, line: usize, content: &str, width: Option<usize>) -> String {
+        let prompt_len = match width {
+            Some(width) => terminal::wrap(prompt_s, width.saturating_sub(2)).last().map(|l| l.chars().count()).unwrap_or(0),
+            None => prompt_s.chars().count(),
+        };
+
+        let mut label = String::new();
+        if self.char_count {
+            label.push_str(&format!("[{}] ", tweet_length(content)));
+        }
+        if self.line_numbers {
+            label.push_str(&format!("{} | ", line));
+        }
+        format!("{:>1$}", label, prompt_len + 2)
+    }
+}
+
+/// Replace the `${VAR}` references in the specified string with the values of the environment variables they name.
+///
+/// `$${` stands for a literal `${`, and other `$`s are left as-is, so values that happen to contain them, like secrets,
+/// needn't be escaped.
+///
+/// The error is what's wrong with the string or which variable isn't set.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::interpolate_env;
+/// # use std::env;
+/// env::set_var("TWEETR_DOCTEST_INTERPOLATE_ENV", "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+/// env::remove_var("TWEETR_DOCTEST_INTERPOLATE_ENV_UNSET");
+///
+/// assert_eq!(interpolate_env("${TWEETR_DOCTEST_INTERPOLATE_ENV}"),
+///            Ok("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string()));
+/// assert_eq!(interpolate_env("$5 for $${TWEETR_DOCTEST_INTERPOLATE_ENV}"),
+///            Ok("$5 for ${TWEETR_DOCTEST_INTERPOLATE_ENV}".to_string()));
+/// assert_eq!(interpolate_env("${TWEETR_DOCTEST_INTERPOLATE_ENV_UNSET}"),
+///            Err("environment variable TWEETR_DOCTEST_INTERPOLATE_ENV_UNSET isn't set".to_string()));
+/// assert_eq!(interpolate_env("${TWEETR_DOCTEST_INTERPOLATE_ENV"),
+///            Err("unterminated \"${\"".to_string()));
+/// ```
+pub fn interpolate_env(s: &str) -> Result<String, String> {
+    let mut interpolated = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find("${") {
+        if rest[..i].ends_with('$') {
+            interpolated.push_str(&rest[..i - 1]);
+            interpolated.push_str("${");
+            rest = &rest[i + 2..];
+            continue;
+        }
+        interpolated.push_str(&rest[..i]);
+
+        let end = try!(rest[i..].find('}').ok_or_else(|| "unterminated \"${\"".to_string()));
+        let var = &rest[i + 2..i + end];
+        if var.is_empty() {
+            return Err("empty environment variable name in \"${}\"".to_string());
+        }
+        match env::var(var) {
+            Ok(value) => interpolated.push_str(&value),
+            Err(VarError::NotPresent) => return Err(format!("environment variable {} isn't set", var)),
+            Err(VarError::NotUnicode(_)) => return Err(format!("environment variable {} isn't valid UTF-8", var)),
+        }
+        rest = &rest[i + end + 1..];
+    }
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
+}
+
+/// How to answer the prompts confirming destructive or irreversible actions, like overriding files, removing users or
+/// posting tweets early.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Ask the user.
+    Ask,
+    /// Confirm without asking, with `--yes`.
+    Yes,
+    /// Refuse without asking, with `--no-input`.
+    Refuse,
+    /// Refuse without asking, or answer with the prompt's default if it has one, if the standard input isn't a terminal.
+    NotTerminal,
+}
+
+impl Confirmation {
+    /// Get how to answer the confirmation prompts given whether `--yes` and `--no-input` were specified and whether the
+    /// standard input is a terminal.
+    ///
+    /// `--yes` wins over `--no-input`, so the actions are confirmed without prompting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::Confirmation;
+    /// assert_eq!(Confirmation::new(false, false, true), Confirmation::Ask);
+    /// assert_eq!(Confirmation::new(false, false, false), Confirmation::NotTerminal);
+    /// assert_eq!(Confirmation::new(false, true, true), Confirmation::Refuse);
+    /// assert_eq!(Confirmation::new(true, true, false), Confirmation::Yes);
+    /// ```
+    pub fn new(yes: bool, no_input: bool, tty: bool) -> Confirmation {
+        if yes {
+            Confirmation::Yes
+        } else if no_input {
+            Confirmation::Refuse
+        } else if !tty {
+            Confirmation::NotTerminal
+        } else {
+            Confirmation::Ask
+        }
+    }
+
+    /// Check whether prompts without a default are refused without asking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::Confirmation;
+    /// assert!(Confirmation::Refuse.refuses());
+    /// assert!(Confirmation::NotTerminal.refuses());
+    /// assert!(!Confirmation::Ask.refuses());
+    /// assert!(!Confirmation::Yes.refuses());
+    /// ```
+    pub fn refuses(&self) -> bool {
+        match *self {
+            Confirmation::Refuse | Confirmation::NotTerminal => true,
+            Confirmation::Ask | Confirmation::Yes => false,
+        }
+    }
+}
+
+/// Check whether the standard input is a terminal, which can be prompted.
+pub fn stdin_is_tty() -> bool {
+    atty::is(atty::Stream::Stdin)
+}
+
+/// Confirm an action with the specified `[y/N]` prompt, asking the user or not as specified.
+///
+/// The automatic answer is printed after the prompt, so logs of scripts show what was done.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{Confirmation, confirm};
+/// # use std::io::Cursor;
+/// assert!(confirm(Confirmation::Ask, &mut Cursor::new(b"y\n"), &mut Vec::new(), "Remove it? [y/N]"));
+/// assert!(!confirm(Confirmation::Ask, &mut Cursor::new(b"\n"), &mut Vec::new(), "Remove it? [y/N]"));
+///
+/// let mut out = Vec::new();
+/// assert!(confirm(Confirmation::Yes, &mut Cursor::new(b""), &mut out, "Remove it? [y/N]"));
+/// assert_eq!(String::from_utf8(out).unwrap(), "Remove it? [y/N]: y (--yes)\n\n");
+///
+/// let mut out = Vec::new();
+/// assert!(!confirm(Confirmation::Refuse, &mut Cursor::new(b"y\n"), &mut out, "Remove it? [y/N]"));
+/// assert_eq!(String::from_utf8(out).unwrap(), "Remove it? [y/N]: n (not asking, pass --yes to confirm)\n\n");
+///
+/// let mut out = Vec::new();
+/// assert!(!confirm(Confirmation::NotTerminal, &mut Cursor::new(b"y\n"), &mut out, "Remove it? [y/N]"));
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "Remove it? [y/N]: n (the standard input isn't a terminal, pass --yes to confirm)\n\n");
+/// ```
+pub fn confirm<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W, prompt_s: &str) -> bool {
+    let confirmed = match confirmation {
+        Confirmation::Ask => {
+            prompt_any_len(input, output, prompt_s, |s| ["y", "n", "Y", "N"].contains(&&s[..]))
+                .unwrap()
+                .map(|a| a.to_lowercase() == "y")
+                .unwrap_or(false)
+        }
+        Confirmation::Yes => {
+            writeln!(output, "{}: y (--yes)", prompt_s).unwrap();
+            true
+        }
+        Confirmation::Refuse => {
+            writeln!(output, "{}: n (not asking, pass --yes to confirm)", prompt_s).unwrap();
+            false
+        }
+        Confirmation::NotTerminal => {
+            writeln!(output, "{}: n (the standard input isn't a terminal, pass --yes to confirm)", prompt_s).unwrap();
+            false
+        }
+    };
+    writeln!(output, "").unwrap();
+
+    confirmed
+}
+
+/// Confirm an action with the specified `[y/N]` prompt like `confirm()`, but answer with the specified default instead of
+/// refusing if the standard input isn't a terminal.
+///
+/// For prompts guarding nothing destructive, so scripts piping answers in aren't stopped by them.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{Confirmation, confirm_or};
+/// # use std::io::Cursor;
+/// let mut out = Vec::new();
+/// assert!(confirm_or(Confirmation::NotTerminal, true, &mut Cursor::new(b"n\n"), &mut out, "Queue it anyway? [y/N]"));
+/// assert_eq!(String::from_utf8(out).unwrap(), "Queue it anyway? [y/N]: y (the standard input isn't a terminal)\n\n");
+///
+/// assert!(!confirm_or(Confirmation::Refuse, true, &mut Cursor::new(b"y\n"), &mut Vec::new(), "Queue it anyway? [y/N]"));
+/// ```
+pub fn confirm_or<R: BufRead, W: Write>(confirmation: Confirmation, default: bool, input: &mut R, output: &mut W, prompt_s: &str) -> bool {
+    match confirmation {
+        Confirmation::NotTerminal => {
+            writeln!(output, "{}: {} (the standard input isn't a terminal)", prompt_s, if default { "y" } else { "n" }).unwrap();
+            writeln!(output, "").unwrap();
+            default
+        }
+        confirmation => confirm(confirmation, input, output, prompt_s),
+    }
+}
+
+/// How to resolve writing data conflicting with what's already in a file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the existing data, updating only the conflicting part.
+    Merge,
+    /// Replace the whole file.
+    Replace,
+    /// Leave the file alone.
+    Abort,
+}
+
+/// Choose how to resolve a conflict with the specified `[m/r/A]` prompt, asking the user or not as specified.
+///
+/// `--yes` merges, since it's the choice that doesn't lose data, and refusing aborts.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{Confirmation, Resolution, resolve};
+/// # use std::io::Cursor;
+/// assert_eq!(resolve(Confirmation::Ask, &mut Cursor::new(b"m\n"), &mut Vec::new(), "Merge, replace or abort? [m/r/A]"),
+///            Resolution::Merge);
+/// assert_eq!(resolve(Confirmation::Ask, &mut Cursor::new(b"R\n"), &mut Vec::new(), "Merge, replace or abort? [m/r/A]"),
+///            Resolution::Replace);
+/// assert_eq!(resolve(Confirmation::Ask, &mut Cursor::new(b"\n"), &mut Vec::new(), "Merge, replace or abort? [m/r/A]"),
+///            Resolution::Abort);
+///
+/// let mut out = Vec::new();
+/// assert_eq!(resolve(Confirmation::Yes, &mut Cursor::new(b""), &mut out, "Merge, replace or abort? [m/r/A]"),
+///            Resolution::Merge);
+/// assert_eq!(String::from_utf8(out).unwrap(), "Merge, replace or abort? [m/r/A]: m (--yes)\n\n");
+///
+/// let mut out = Vec::new();
+/// assert_eq!(resolve(Confirmation::Refuse, &mut Cursor::new(b"r\n"), &mut out, "Merge, replace or abort? [m/r/A]"),
+///            Resolution::Abort);
+/// assert_eq!(String::from_utf8(out).unwrap(), "Merge, replace or abort? [m/r/A]: a (not asking, pass --yes to merge)\n\n");
+/// ```
+pub fn resolve<R: BufRead, W: Write>(confirmation: Confirmation, input: &mut R, output: &mut W, prompt_s: &str) -> Resolution {
+    let resolution = match confirmation {
+        Confirmation::Ask => {
+            match prompt_any_len(input, output, prompt_s, |s| ["m", "r", "a", "M", "R", "A"].contains(&&s[..]))
+                .unwrap()
+                .map(|a| a.to_lowercase()) {
+                Some(ref a) if a == "m" => Resolution::Merge,
+                Some(ref a) if a == "r" => Resolution::Replace,
+                _ => Resolution::Abort,
+            }
+        }
+        Confirmation::Yes => {
+            writeln!(output, "{}: m (--yes)", prompt_s).unwrap();
+            Resolution::Merge
+        }
+        Confirmation::Refuse => {
+            writeln!(output, "{}: a (not asking, pass --yes to merge)", prompt_s).unwrap();
+            Resolution::Abort
+        }
+        Confirmation::NotTerminal => {
+            writeln!(output, "{}: a (the standard input isn't a terminal, pass --yes to merge)", prompt_s).unwrap();
+            Resolution::Abort
+        }
+    };
+    writeln!(output, "").unwrap();
+
+    resolution
+}
+
+fn prompt<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: &F, allow_empty: bool, colon: bool, out: &mut String) -> IoResult<()>
+    where R: BufRead,
+          W: Write,
+          F: Fn(&String) -> bool
+{
+    if colon {
+        match terminal::prompt_width() {
+            Some(width) => try!(write!(output, "{}: ", terminal::wrap(prompt_s, width.saturating_sub(2)).join("\n"))),
+            None => try!(write!(output, "{}: ", prompt_s)),
+        }
+    } else {
+        try!(write!(output, "{}", prompt_s));
+    }
+    try!(output.flush());
+
+    out.clear();
+    if try!(input.read_line(out)) == 0 && !allow_empty {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "Input too short"));
+    }
+
+    *out = out.trim().to_string();
+    if !verifier(out) {
+        out.clear();
+    }
+
+    Ok(())
+}
+
+fn weighted_char_length(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0...4351 | 8192...8205 | 8208...8223 | 8242...8247 => 1,
+            _ => 2,
+        })
+        .sum()
+}
+
+/// Whether the specified lowercase word starts like an ISO 8601 duration, i.e. with a `p` followed by a number or `t`.
+fn is_iso8601_duration(word: &str) -> bool {
+    word.starts_with('p') && word[1..].chars().next().map(|c| c == 't' || c.is_digit(10)).unwrap_or(false)
+}
+
+/// Parse the specified lowercase ISO 8601 duration, checking nothing follows it.
+fn iso8601_duration(duration: &str, trailing: Option<&str>) -> Result<Duration, RelativeTimeError> {
+    if trailing.is_some() {
+        return Err(RelativeTimeError::BadSyntax { expected: "nothing after the ISO 8601 duration" });
+    }
+
+    let mut secs: u64 = 0;
+    let mut time = false;
+    let mut number = String::new();
+    // Multiplier of the last designator, which must be larger than the next one's, and whether one followed the "T"
+    let mut last_mul = None;
+    let mut any_time = false;
+    for c in duration[1..].chars() {
+        match c {
+            '0'...'9' => number.push(c),
+            't' if !time && number.is_empty() => time = true,
+            c if number.is_empty() || !c.is_alphabetic() => {
+                return Err(RelativeTimeError::BadSyntax { expected: "a whole number before each designator of the ISO 8601 duration" })
+            }
+            designator => {
+                let mul: u64 = match (time, designator) {
+                    (false, 'w') => 60 * 60 * 24 * 7,
+                    (false, 'd') => 60 * 60 * 24,
+                    (true, 'h') => 60 * 60,
+                    (true, 'm') => 60,
+                    (true, 's') => 1,
+                    (false, 'y') | (false, 'm') => {
+                        return Err(RelativeTimeError::BadSyntax { expected: "only weeks and days before \"T\" in the ISO 8601 duration" })
+                    }
+                    _ => return Err(RelativeTimeError::UnknownUnit { got: designator.to_uppercase().collect() }),
+                };
+                if last_mul.map(|l| mul >= l).unwrap_or(false) {
+                    return Err(RelativeTimeError::BadSyntax { expected: "the designators of the ISO 8601 duration in order, each once" });
+                }
+
+                let n = try!(u64::from_str(&number).map_err(|_| RelativeTimeError::NumberOverflow));
+                secs = try!(n.checked_mul(mul).and_then(|s| secs.checked_add(s)).ok_or(RelativeTimeError::NumberOverflow));
+                number.clear();
+                last_mul = Some(mul);
+                any_time |= time;
+            }
+        }
+    }
+
+    if !number.is_empty() {
+        Err(RelativeTimeError::BadSyntax { expected: "a designator after each number of the ISO 8601 duration" })
+    } else if last_mul.is_none() || (time && !any_time) {
+        Err(RelativeTimeError::BadSyntax { expected: "an amount of time after each of \"P\" and \"T\" in the ISO 8601 duration" })
+    } else {
+        Ok(Duration::from_secs(secs))
+    }
+}
+
+fn amount_of_time(n: Option<&str>, unit: Option<&str>, expected_number: &'static str) -> Result<u64, RelativeTimeError> {
+    let n = try!(whole_number(n.unwrap_or(""), expected_number));
+
+    let unit = try!(unit.ok_or(RelativeTimeError::BadSyntax { expected: "a unit of time after the number" }));
+    let mul: u64 = match &unit.to_lowercase().trim_right_matches('s')[..] {
+        "second" => 1,
+        "minute" => 60,
+        "hour" => 60 * 60,
+        "day" => 60 * 60 * 24,
+        "week" => 60 * 60 * 24 * 7,
+        _ => return Err(RelativeTimeError::UnknownUnit { got: unit.to_string() }),
+    };
+
+    n.checked_mul(mul).ok_or(RelativeTimeError::NumberOverflow)
+}
+
+fn whole_number(n: &str, expected: &'static str) -> Result<u64, RelativeTimeError> {
+    if !n.is_empty() && n.chars().all(|c| c.is_digit(10)) {
+        u64::from_str(n).map_err(|_| RelativeTimeError::NumberOverflow)
+    } else {
+        Err(RelativeTimeError::BadSyntax { expected: expected })
+    }
+}
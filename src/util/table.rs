@@ -0,0 +1,241 @@
+//! Rendering rows of text in aligned columns, for the subsystems listing users and tweets.
+//!
+//! Each column is as wide as its widest cell, in characters, and newlines in cells are shown as `↵`. If the table is wider
+//! than its maximum width, usually the terminal's (see `terminal::terminal_width()`), the columns marked as shrinkable, like ones with
+//! tweets' content, are narrowed, widest first, and their cells truncated with an ellipsis, so long tweets don't wrap around
+//! and mangle the following rows.
+
+
+use self::super::mul_str;
+use std::io::Write;
+
+
+/// How to align a column's cells.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Align {
+    /// Against the left edge, for text.
+    Left,
+    /// Against the right edge, for numbers.
+    Right,
+}
+
+/// A table's column.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Column {
+    /// The text in the header row.
+    pub header: String,
+    /// How to align the cells.
+    pub align: Align,
+    /// Whether the column can be narrowed to fit the table in its maximum width, down to the header's width.
+    pub shrink: bool,
+}
+
+/// Rows of cells, rendered in aligned columns.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Table {
+    /// The columns, in order.
+    pub columns: Vec<Column>,
+    /// The rows added so far, each with a cell for each column.
+    pub rows: Vec<Vec<String>>,
+    /// Whether to draw borders around the cells. Default: `false`
+    pub borders: bool,
+    /// The width to fit the table in, if any. Default: `None`
+    pub max_width: Option<usize>,
+}
+
+
+/// Shorten the specified text to the specified width, in characters, marking it with an ellipsis if shortened.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::table::truncate;
+/// assert_eq!(truncate("Abolish the bourgeoisie", 10), "Abolish t…");
+/// assert_eq!(truncate("Abolish", 10), "Abolish");
+/// assert_eq!(truncate("Zażółć gęślą jaźń", 6), "Zażół…");
+/// assert_eq!(truncate("Abolish", 0), "");
+/// ```
+pub fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        s.chars().take(width - 1).chain(Some('…')).collect()
+    }
+}
+
+
+impl Column {
+    /// Get a left-aligned, not shrinkable column with the specified header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::table::{Align, Column};
+    /// assert_eq!(Column::new("Author"),
+    ///            Column {
+    ///                header: "Author".to_string(),
+    ///                align: Align::Left,
+    ///                shrink: false,
+    ///            });
+    /// ```
+    pub fn new(header: &str) -> Column {
+        Column {
+            header: header.to_string(),
+            align: Align::Left,
+            shrink: false,
+        }
+    }
+
+    /// Get the same column, but right-aligned.
+    pub fn right(self) -> Column {
+        Column { align: Align::Right, ..self }
+    }
+
+    /// Get the same column, but shrinkable.
+    pub fn shrinkable(self) -> Column {
+        Column { shrink: true, ..self }
+    }
+}
+
+impl Table {
+    /// Get an empty table with the specified columns.
+    pub fn new(columns: Vec<Column>) -> Table {
+        Table {
+            columns: columns,
+            rows: vec![],
+            borders: false,
+            max_width: None,
+        }
+    }
+
+    /// Add a row with the specified cells, missing ones being empty.
+    pub fn row(&mut self, mut cells: Vec<String>) {
+        cells.resize(self.columns.len(), String::new());
+        self.rows.push(cells);
+    }
+
+    /// Get the widths of the columns, in characters, narrowed to fit the table in its maximum width if possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::table::{Column, Table};
+    /// let mut table = Table::new(vec![Column::new("Author"), Column::new("Content").shrinkable()]);
+    /// table.row(vec!["nabijaczleweli".to_string(), "Abolish the bourgeoisie".to_string()]);
+    ///
+    /// assert_eq!(table.widths(), vec![14, 23]);
+    /// table.max_width = Some(30);
+    /// assert_eq!(table.widths(), vec![14, 14]);
+    /// table.max_width = Some(10);
+    /// assert_eq!(table.widths(), vec![14, 7]);
+    /// ```
+    pub fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<_> = self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| self.rows.iter().map(|r| cell(&r[i]).chars().count()).fold(c.header.chars().count(), |a, w| if w > a { w } else { a }))
+            .collect();
+
+        if let Some(max_width) = self.max_width {
+            let separators = if self.borders {
+                widths.len() * 3 + 1
+            } else {
+                widths.len().saturating_sub(1) * 2
+            };
+            let mut total = widths.iter().sum::<usize>() + separators;
+            while total > max_width {
+                let widest = self.columns
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, c)| c.shrink && widths[i] > c.header.chars().count())
+                    .max_by_key(|&(i, _)| widths[i])
+                    .map(|(i, _)| i);
+                match widest {
+                    Some(i) => widths[i] -= 1,
+                    None => break,
+                }
+                total -= 1;
+            }
+        }
+
+        widths
+    }
+
+    /// Write the table, with the header row first.
+    ///
+    /// Without borders, columns are separated by two spaces, and lines have no trailing whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::util::table::{Column, Table};
+    /// let mut table = Table::new(vec![Column::new("Author"), Column::new("ID").right(), Column::new("Content").shrinkable()]);
+    /// table.row(vec!["nabijaczleweli".to_string(), "481".to_string(), "Abolish the bourgeoisie".to_string()]);
+    /// table.row(vec!["tweetr_test".to_string(), "4169".to_string(), "Hello,\nworld!".to_string()]);
+    /// table.max_width = Some(40);
+    ///
+    /// let mut out = Vec::new();
+    /// table.write(&mut out);
+    /// assert_eq!(String::from_utf8(out).unwrap(),
+    ///            "Author            ID  Content\n\
+    ///             nabijaczleweli   481  Abolish the bourg…\n\
+    ///             tweetr_test     4169  Hello,↵world!\n");
+    ///
+    /// table.borders = true;
+    /// let mut out = Vec::new();
+    /// table.write(&mut out);
+    /// assert_eq!(String::from_utf8(out).unwrap(),
+    ///            "+----------------+------+--------------+\n\
+    ///             | Author         |   ID | Content      |\n\
+    ///             +----------------+------+--------------+\n\
+    ///             | nabijaczleweli |  481 | Abolish the… |\n\
+    ///             | tweetr_test    | 4169 | Hello,↵worl… |\n\
+    ///             +----------------+------+--------------+\n");
+    /// ```
+    pub fn write<W: Write>(&self, output: &mut W) {
+        let widths = self.widths();
+        let rule = || widths.iter().map(|&w| mul_str("-", w + 2)).fold("+".to_string(), |acc, r| acc + &r + "+");
+
+        if self.borders {
+            writeln!(output, "{}", rule()).unwrap();
+        }
+        self.write_row(output, &widths, &self.columns.iter().map(|c| c.header.clone()).collect::<Vec<_>>());
+        if self.borders {
+            writeln!(output, "{}", rule()).unwrap();
+        }
+        for row in &self.rows {
+            self.write_row(output, &widths, row);
+        }
+        if self.borders && !self.rows.is_empty() {
+            writeln!(output, "{}", rule()).unwrap();
+        }
+    }
+
+    fn write_row<W: Write>(&self, output: &mut W, widths: &[usize], cells: &[String]) {
+        let cells: Vec<_> = self.columns
+            .iter()
+            .zip(widths)
+            .zip(cells)
+            .map(|((c, &w), s)| {
+                let s = truncate(&cell(s), w);
+                match c.align {
+                    Align::Left => format!("{:<1$}", s, w),
+                    Align::Right => format!("{:>1$}", s, w),
+                }
+            })
+            .collect();
+
+        if self.borders {
+            writeln!(output, "| {} |", cells.join(" | ")).unwrap();
+        } else {
+            writeln!(output, "{}", cells.join("  ").trim_right()).unwrap();
+        }
+    }
+}
+
+
+fn cell(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\n', "↵")
+}
@@ -0,0 +1,146 @@
+//! Fitting output to the terminal's width, for prompts and tables.
+//!
+//! The width's read once and then again only after the terminal's resized, as reported by `SIGWINCH`, so tables rendered
+//! and prompts written after resizing fit the new width instead of the one tweetr started with.
+//!
+//! Prompts are only wrapped once `watch_resize()` is called, which the `tweetr` binary does before doing anything else, so
+//! prompts written elsewhere, like to buffers in tests, aren't wrapped to whatever terminal they happen to run in.
+
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Once;
+use std::env;
+use atty;
+
+
+static WATCHING: AtomicBool = AtomicBool::new(false);
+static RESIZED: AtomicBool = AtomicBool::new(false);
+/// The width last reported by the terminal, 0 if none.
+static REPORTED_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Start tracking the terminal's width, re-reading it when the terminal's resized, and wrapping prompts to it.
+///
+/// Calling it again does nothing.
+pub fn watch_resize() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        install_resize_handler();
+        RESIZED.store(true, Ordering::SeqCst);
+        WATCHING.store(true, Ordering::SeqCst);
+    });
+}
+
+/// Get the width of the terminal the standard output is, in characters, if it is one.
+///
+/// The `COLUMNS` environment variable takes precedence over the width reported by the terminal.
+///
+/// After `watch_resize()`, the reported width is only re-read after the terminal's resized.
+pub fn terminal_width() -> Option<usize> {
+    if !atty::is(atty::Stream::Stdout) {
+        return None;
+    }
+
+    env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).and_then(|c| if c == 0 { None } else { Some(c) }).or_else(|| {
+        if !WATCHING.load(Ordering::SeqCst) || RESIZED.swap(false, Ordering::SeqCst) {
+            REPORTED_WIDTH.store(reported_width().unwrap_or(0), Ordering::SeqCst);
+        }
+        match REPORTED_WIDTH.load(Ordering::SeqCst) {
+            0 => None,
+            w => Some(w),
+        }
+    })
+}
+
+/// Get the width to wrap prompts to: the terminal's, after `watch_resize()`, `None` otherwise.
+pub fn prompt_width() -> Option<usize> {
+    if WATCHING.load(Ordering::SeqCst) {
+        terminal_width()
+    } else {
+        None
+    }
+}
+
+/// Wrap the specified text to the specified width, in characters, at whitespace if possible.
+///
+/// Existing newlines are kept, runs of spaces between words are collapsed, and words longer than the width are broken up.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::terminal::wrap;
+/// assert_eq!(wrap("Abolish the bourgeoisie", 12), vec!["Abolish the", "bourgeoisie"]);
+/// assert_eq!(wrap("Abolish the bourgeoisie", 40), vec!["Abolish the bourgeoisie"]);
+/// assert_eq!(wrap("Abolish\nthe bourgeoisie", 40), vec!["Abolish", "the bourgeoisie"]);
+/// assert_eq!(wrap("Zażółć gęślą jaźń", 4), vec!["Zażó", "łć", "gęśl", "ą", "jaźń"]);
+/// ```
+pub fn wrap(s: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return s.split('\n').map(str::to_string).collect();
+    }
+
+    let mut lines = vec![];
+    for paragraph in s.split('\n') {
+        let mut line = String::new();
+        let mut line_len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<_> = word.chars().collect();
+            if line_len != 0 && line_len + 1 + word.len() > width {
+                lines.push(line);
+                line = String::new();
+                line_len = 0;
+            }
+            if line_len != 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+
+            while word.len() > width {
+                let rest = word.split_off(width);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
+            line_len += word.len();
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+
+#[cfg(unix)]
+fn install_resize_handler() {
+    use libc;
+
+    extern "C" fn on_resize(_: libc::c_int) {
+        RESIZED.store(true, Ordering::SeqCst);
+    }
+
+    unsafe {
+        libc::signal(libc::SIGWINCH, on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+fn install_resize_handler() {}
+
+#[cfg(unix)]
+fn reported_width() -> Option<usize> {
+    use libc;
+    use std::mem;
+
+    unsafe {
+        let mut size: libc::winsize = mem::zeroed();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col != 0 {
+            Some(size.ws_col as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn reported_width() -> Option<usize> {
+    None
+}
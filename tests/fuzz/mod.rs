@@ -0,0 +1,101 @@
+//! Property tests for the helpers munging user input, checked against random input.
+//!
+//! Run with `cargo test --features fuzz`. Each property is checked on `$TWEETR_FUZZ_CASES` (default: 1000) inputs generated
+//! from a random seed, which is printed if the property doesn't hold, and can be reused by setting `$TWEETR_FUZZ_SEED`.
+
+
+extern crate rand;
+
+use self::rand::{Rng, SeedableRng, XorShiftRng};
+use std::io::{Write, stderr};
+use std::{env, thread};
+
+mod prompt_multiline;
+mod parse_relative_time;
+mod queued_tweet;
+
+
+/// Characters that tend to confuse the parsers, picked more often than the rest.
+static TRICKY: &'static [char] = &['\\', '"', '\'', '\n', '\r', '\t', ' ', '#', '=', '[', ']', '{', '}', '.', ',', '-', '0', '9', '\u{0}', '\u{7f}',
+                                   '\u{85}', '\u{a0}', '\u{200b}', '\u{2028}', '\u{301}', '\u{1f4a9}', '\u{fffd}', 'ł'];
+
+
+/// Random input generator.
+pub struct Gen(XorShiftRng);
+
+impl Gen {
+    /// A random number in `[low, high)`.
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        self.0.gen_range(low, high)
+    }
+
+    /// `true` with the probability of 1 in `n`.
+    pub fn one_in(&mut self, n: u32) -> bool {
+        self.0.gen_weighted_bool(n)
+    }
+
+    /// A random element of the specified slice.
+    pub fn pick<'t, T>(&mut self, of: &'t [T]) -> &'t T {
+        self.0.choose(of).unwrap()
+    }
+
+    /// A random number, small, around a power of ten, or the largest ones, about equally often.
+    pub fn number(&mut self) -> u64 {
+        match self.range(0, 3) {
+            0 => self.range(0, 100) as u64,
+            1 => 10u64.pow(self.range(0, 20) as u32) + self.range(0, 3) as u64 - 1,
+            _ => u64::max_value() - self.range(0, 1000) as u64,
+        }
+    }
+
+    /// A random string of up to the specified amount of characters.
+    pub fn string(&mut self, max_len: usize) -> String {
+        let len = self.range(0, max_len + 1);
+        (0..len).map(|_| self.character()).collect()
+    }
+
+    /// A random string of up to the specified amount of characters, without line breaks.
+    pub fn line(&mut self, max_len: usize) -> String {
+        self.string(max_len).replace(|c: char| c == '\n' || c == '\r', "")
+    }
+
+    /// A random non-empty word of ASCII letters and digits.
+    pub fn word(&mut self) -> String {
+        let len = self.range(1, 16);
+        self.0.gen_ascii_chars().take(len).collect()
+    }
+
+    /// A random character, tricky, ASCII, or any.
+    pub fn character(&mut self) -> char {
+        match self.range(0, 3) {
+            0 => *self.pick(TRICKY),
+            1 => self.range(0x20, 0x7f) as u8 as char,
+            _ => self.0.gen(),
+        }
+    }
+}
+
+
+/// Check the specified property on the configured amount of random inputs.
+pub fn check<F: FnMut(&mut Gen)>(mut property: F) {
+    let seed = env::var("TWEETR_FUZZ_SEED").ok().and_then(|s| s.parse().ok()).unwrap_or_else(rand::random::<u32>);
+    let cases = env::var("TWEETR_FUZZ_CASES").ok().and_then(|s| s.parse().ok()).unwrap_or(1000);
+
+    let _reporter = SeedReporter(seed);
+    let mut gen = Gen(XorShiftRng::from_seed([seed, 0x193a6754, 0xa8a7d469, 0x97830e05]));
+    for _ in 0..cases {
+        property(&mut gen);
+    }
+}
+
+
+/// Print the seed if the property doesn't hold, since that's only found out by the panic unwinding past it.
+struct SeedReporter(u32);
+
+impl Drop for SeedReporter {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            writeln!(stderr(), "Property failed with TWEETR_FUZZ_SEED={}", self.0).unwrap();
+        }
+    }
+}
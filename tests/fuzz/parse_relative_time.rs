@@ -0,0 +1,81 @@
+extern crate tweetr;
+
+use self::tweetr::util::{RelativeTimeError, format_amount_of_time, parse_anchored_time, parse_relative_time, parse_time_shift, parse_unix_time};
+use self::super::check;
+use std::time::Duration;
+
+
+static UNITS: &'static [(&'static str, u64)] = &[("second", 1), ("minute", 60), ("hour", 60 * 60), ("day", 60 * 60 * 24), ("week", 60 * 60 * 24 * 7)];
+
+
+#[test]
+fn amount_in_unit() {
+    check(|gen| {
+        let n = gen.number();
+        let &(unit, mul) = gen.pick(UNITS);
+        let unit: String = unit.chars().map(|c| if gen.one_in(2) { c.to_ascii_uppercase() } else { c }).collect();
+        let delta = format!("in {} {}{}", n, unit, if gen.one_in(2) { "s" } else { "" });
+
+        assert_eq!(parse_relative_time(&delta),
+                   n.checked_mul(mul).map(Duration::from_secs).ok_or(RelativeTimeError::NumberOverflow),
+                   "input: {:?}",
+                   delta);
+    });
+}
+
+#[test]
+fn combined_units_sum() {
+    check(|gen| {
+        let parts: Vec<_> = (0..gen.range(1, 5)).map(|_| (gen.number() % 1000, *gen.pick(UNITS))).collect();
+        let delta = format!("in {}",
+                            parts.iter()
+                                .enumerate()
+                                .map(|(i, &(n, (unit, _)))| format!("{}{} {}s", if i != 0 && gen.one_in(2) { "and " } else { "" }, n, unit))
+                                .collect::<Vec<_>>()
+                                .join(" "));
+
+        assert_eq!(parse_relative_time(&delta),
+                   Ok(Duration::from_secs(parts.iter().map(|&(n, (_, mul))| n * mul).sum())),
+                   "input: {:?}",
+                   delta);
+    });
+}
+
+#[test]
+fn seconds_and_unix_time() {
+    check(|gen| {
+        let n = gen.number();
+
+        assert_eq!(parse_relative_time(&format!("+{}", n)), Ok(Duration::from_secs(n)));
+        assert_eq!(parse_unix_time(&format!("@{}", n)),
+                   Some(if n > i64::max_value() as u64 { Err(RelativeTimeError::NumberOverflow) } else { Ok(n as i64) }));
+    });
+}
+
+#[test]
+fn formatted_round_trip() {
+    check(|gen| {
+        let secs = if gen.one_in(2) {
+            gen.number()
+        } else {
+            gen.number() / UNITS[UNITS.len() - 1].1 * gen.pick(UNITS).1
+        };
+
+        assert_eq!(parse_relative_time(&format!("in {}", format_amount_of_time(secs))), Ok(Duration::from_secs(secs)));
+    });
+}
+
+#[test]
+fn arbitrary_input() {
+    check(|gen| {
+        let mut input = gen.string(30);
+        if gen.one_in(2) {
+            input = format!("{} {}", gen.pick(&["in", "now", "-", "+", "@", "after", "3 days after"]), input);
+        }
+
+        let _ = parse_relative_time(&input);
+        let _ = parse_anchored_time(&input);
+        let _ = parse_time_shift(&input);
+        let _ = parse_unix_time(&input);
+    });
+}
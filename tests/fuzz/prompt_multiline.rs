@@ -0,0 +1,49 @@
+extern crate tweetr;
+
+use self::tweetr::util::{MultilineOptions, prompt_multiline};
+use self::super::check;
+use std::io::Cursor;
+
+
+#[test]
+fn escaped_lines_round_trip() {
+    check(|gen| {
+        let count = gen.range(1, 6);
+        let mut lines: Vec<_> = (0..count).map(|_| gen.line(20).trim().to_string()).collect();
+        if lines[0].is_empty() {
+            lines[0] = gen.word();
+        }
+        // Only the last line can end with a backslash, escaped as two
+        for line in lines.iter_mut().take(count - 1) {
+            while line.ends_with('\\') {
+                line.pop();
+                *line = line.trim().to_string();
+            }
+        }
+        if lines[0].is_empty() {
+            lines[0] = gen.word();
+        }
+
+        let mut input = lines.join("\\\n");
+        if input.ends_with('\\') {
+            input.push('\\');
+        }
+        input.push('\n');
+
+        assert_eq!(prompt_multiline(&mut Cursor::new(input.as_bytes()), &mut Vec::new(), "Content", |_| true, MultilineOptions::default())
+                       .unwrap(),
+                   lines.join("\n"),
+                   "input: {:?}",
+                   input);
+    });
+}
+
+#[test]
+fn arbitrary_input() {
+    check(|gen| {
+        let input = gen.string(60);
+        if let Ok(content) = prompt_multiline(&mut Cursor::new(input.as_bytes()), &mut Vec::new(), "Content", |_| true, MultilineOptions::default()) {
+            assert!(!content.is_empty(), "input: {:?}", input);
+        }
+    });
+}
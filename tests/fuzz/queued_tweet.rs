@@ -0,0 +1,61 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, Coordinates};
+use self::tweetr::ops::settings::Settings;
+use self::chrono::{FixedOffset, TimeZone};
+use self::super::{Gen, check};
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn write_read_round_trip() {
+    let td = temp_dir().join("tweetr-test").join("fuzz-queued_tweet-write_read_round_trip");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("tweets.toml");
+
+    check(|gen| {
+        let tweets: Vec<_> = (0..gen.range(0, 4)).map(|i| tweet(gen, i)).collect();
+
+        QueuedTweet::write(tweets.clone(), &tf).unwrap();
+        assert_eq!(QueuedTweet::read(&tf), Ok(tweets));
+    });
+}
+
+
+fn tweet(gen: &mut Gen, i: usize) -> QueuedTweet {
+    let offset = FixedOffset::east(gen.range(0, 48 * 60 - 1) as i32 * 60 - 24 * 60 * 60 + 60);
+    let time = offset.timestamp(gen.range(0, 4102444800) as i64, if gen.one_in(2) { 0 } else { gen.range(0, 1000000000) as u32 });
+    let posted = gen.one_in(2);
+
+    QueuedTweet {
+        author: gen.string(15),
+        time: time,
+        content: gen.string(280),
+        thread: if gen.one_in(3) { Some(gen.string(20)) } else { None },
+        in_reply_to: if gen.one_in(3) { Some((gen.number() >> 1) as i64) } else { None },
+        extra_params: (0..gen.range(0, 3)).map(|_| (gen.word(), gen.string(20))).collect(),
+        variants: (0..gen.range(0, 2)).map(|_| ("telegram".to_string(), gen.string(280))).collect(),
+        content_warning: if gen.one_in(3) { Some(gen.string(40)) } else { None },
+        coordinates: if gen.one_in(3) {
+            Coordinates::new(gen.range(0, 180000001) as f64 / 1e6 - 90.0, gen.range(0, 360000001) as f64 / 1e6 - 180.0)
+        } else {
+            None
+        },
+        place_id: if gen.one_in(3) { Some(gen.word()) } else { None },
+        name: if gen.one_in(3) { Some(format!("{}{}", gen.word(), i)) } else { None },
+        paused: gen.one_in(4),
+        blocked: if gen.one_in(4) { Some(gen.string(60)) } else { None },
+        settings: Settings {
+            timezone: if gen.one_in(3) { Some(gen.pick(&["UTC", "+02:00", "-05:30"]).to_string()) } else { None },
+            quiet_hours: if gen.one_in(3) { Some(gen.pick(&["23:00-07:00", "12:00-13:30"]).to_string()) } else { None },
+            jitter: if gen.one_in(3) { Some(gen.range(0, 1440) as u32) } else { None },
+            tags: if gen.one_in(3) { Some((0..gen.range(0, 3)).map(|_| gen.string(15)).collect()) } else { None },
+            sensitive: if gen.one_in(3) { Some(gen.one_in(2)) } else { None },
+        },
+        time_posted: if posted { Some(time) } else { None },
+        id: if posted { Some((gen.number() >> 1) as i64) } else { None },
+        ..Default::default()
+    }
+}
@@ -1,2 +1,5 @@
 mod ops;
+mod options;
 mod util;
+#[cfg(feature = "fuzz")]
+mod fuzz;
@@ -0,0 +1,147 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::chrono::{DateTime, FixedOffset, Local, TimeZone};
+use self::tweetr::ops::{archive, QueuedTweet};
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+
+#[test]
+fn rotate_by_month() {
+    let td = config_dir("rotate_by_month");
+    let mut tweets = vec![posted("Abolish", 8, 31), posted("the bourgeoisie", 9, 1), unposted("now")];
+
+    archive::rotate(&mut tweets, &td, None).unwrap();
+    assert_eq!(tweets, vec![unposted("now")]);
+    assert_eq!(QueuedTweet::read(&td.join("archive-2016-08.toml")), Ok(vec![posted("Abolish", 8, 31)]));
+    assert_eq!(QueuedTweet::read(&td.join("archive-2016-09.toml")), Ok(vec![posted("the bourgeoisie", 9, 1)]));
+}
+
+#[test]
+fn rotate_by_size() {
+    let td = config_dir("rotate_by_size");
+    let mut tweets = vec![posted("Abolish", 9, 1)];
+    archive::rotate(&mut tweets, &td, Some(2)).unwrap();
+
+    let mut tweets = vec![posted("the", 9, 2), posted("bourgeoisie", 9, 3), posted("now", 9, 4)];
+    archive::rotate(&mut tweets, &td, Some(2)).unwrap();
+
+    assert_eq!(QueuedTweet::read(&td.join("archive-2016-09.toml")), Ok(vec![posted("Abolish", 9, 1), posted("the", 9, 2)]));
+    assert_eq!(QueuedTweet::read(&td.join("archive-2016-09.1.toml")),
+               Ok(vec![posted("bourgeoisie", 9, 3), posted("now", 9, 4)]));
+    assert!(!td.join("archive-2016-09.2.toml").exists());
+}
+
+#[test]
+fn rotate_keeps_threads() {
+    let td = config_dir("rotate_keeps_threads");
+    let mut first = posted("Abolish", 9, 1);
+    first.thread = Some("manifesto".to_string());
+    let mut second = unposted("the bourgeoisie");
+    second.thread = Some("manifesto".to_string());
+    let mut tweets = vec![first.clone(), second.clone()];
+
+    archive::rotate(&mut tweets, &td, None).unwrap();
+    assert_eq!(tweets, vec![first, second]);
+    assert!(archive::files(&td).is_empty());
+}
+
+#[test]
+fn rotate_broken_untouched() {
+    let td = config_dir("rotate_broken_untouched");
+    File::create(td.join("archive-2016-09.toml")).unwrap().write_all(b"[[tweet]]\nauthor = 420\n").unwrap();
+    let mut tweets = vec![posted("Abolish", 9, 1)];
+
+    assert!(archive::rotate(&mut tweets, &td, None).is_err());
+    assert_eq!(tweets, vec![posted("Abolish", 9, 1)]);
+}
+
+#[test]
+fn files_sorted() {
+    let td = config_dir("files_sorted");
+    for fname in &["archive-2016-09.1.toml", "archive-2016-10.toml", "archive-2016-09.toml", "archive-2016-13.toml", "archive-2016-09.0.toml",
+                   "archive.toml", "tweets.toml"] {
+        File::create(td.join(fname)).unwrap();
+    }
+
+    assert_eq!(archive::files(&td),
+               vec![(Local.ymd(2016, 9, 1), td.join("archive-2016-09.toml")),
+                    (Local.ymd(2016, 9, 1), td.join("archive-2016-09.1.toml")),
+                    (Local.ymd(2016, 10, 1), td.join("archive-2016-10.toml"))]);
+}
+
+#[test]
+fn read_since_across_files() {
+    let td = config_dir("read_since_across_files");
+    let mut tweets = vec![posted("Abolish", 7, 31), posted("the", 8, 20), posted("bourgeoisie", 9, 1)];
+    archive::rotate(&mut tweets, &td, None).unwrap();
+
+    assert_eq!(archive::read_since(&td, time(8, 10)), Ok(vec![posted("the", 8, 20), posted("bourgeoisie", 9, 1)]));
+    assert_eq!(archive::read_since(&td, time(10, 1)), Ok(vec![]));
+}
+
+#[test]
+fn read_all_across_files() {
+    let td = config_dir("read_all_across_files");
+    let mut tweets = vec![posted("Abolish", 7, 31), posted("the", 8, 20), unposted("bourgeoisie")];
+    archive::rotate(&mut tweets, &td, None).unwrap();
+
+    assert_eq!(archive::read_all(&td), Ok(vec![posted("Abolish", 7, 31), posted("the", 8, 20)]));
+    assert_eq!(archive::read_all(&config_dir("read_all_across_files-empty")), Ok(vec![]));
+}
+
+#[test]
+fn expired_across_years() {
+    let td = config_dir("expired_across_years");
+    for fname in &["archive-2015-11.toml", "archive-2015-12.toml", "archive-2016-01.toml"] {
+        File::create(td.join(fname)).unwrap();
+    }
+
+    assert_eq!(archive::expired(&td, 1, Local.ymd(2016, 1, 14)), vec![td.join("archive-2015-11.toml")]);
+    assert_eq!(archive::expired(&td, 0, Local.ymd(2016, 1, 14)),
+               vec![td.join("archive-2015-11.toml"), td.join("archive-2015-12.toml")]);
+}
+
+#[test]
+fn expired_before_year_one() {
+    let td = config_dir("expired_before_year_one");
+    File::create(td.join("archive-2015-11.toml")).unwrap();
+
+    assert!(archive::expired(&td, 2016 * 12, Local.ymd(2016, 1, 14)).is_empty());
+    assert!(archive::expired(&td, 100000000, Local.ymd(2016, 1, 14)).is_empty());
+    assert!(archive::expired(&td, 2015 * 12 + 1, Local.ymd(2016, 1, 14)).is_empty());
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-archive-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
+
+fn time(month: u32, day: u32) -> DateTime<FixedOffset> {
+    let time = Local.ymd(2016, month, day).and_hms(12, 0, 0);
+    time.with_timezone(time.offset())
+}
+
+fn unposted(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time(9, 14),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
+
+fn posted(content: &str, month: u32, day: u32) -> QueuedTweet {
+    QueuedTweet {
+        time: time(month, day),
+        time_posted: Some(time(month, day)),
+        id: Some(420),
+        ..unposted(content)
+    }
+}
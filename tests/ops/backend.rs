@@ -0,0 +1,144 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::backend::{self, PostingBackend, Sink};
+use self::tweetr::ops::{Config, Coordinates, QueuedTweet, ReplySettings};
+use self::tweetr::Outcome;
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Read;
+use std::fs;
+
+
+#[test]
+fn sinks_invalid() {
+    let td = config_dir("sinks_invalid");
+    let config = Config {
+        sink: Some(vec![sink(None, None)]),
+        ..Config::default()
+    };
+
+    assert_eq!(backend::sinks(&config, &td),
+               Err(Outcome::FileParsingFailed {
+                   desc: "configuration",
+                   errors: vec!["error: sink \"announcements\" needs exactly one of `file`, `command` and `discord_webhook` for the key `sink.0`".to_string()],
+               }));
+}
+
+#[test]
+fn file_appends() {
+    let td = config_dir("file_appends");
+    let sink = backend::sinks(&Config { sink: Some(vec![sink(Some("announcements.txt"), None)]), ..Config::default() }, &td).unwrap().remove(0);
+
+    sink.post(&tweet("Abolish")).unwrap();
+    sink.post(&tweet("the bourgeoisie")).unwrap();
+
+    let mut content = String::new();
+    fs::File::open(td.join("announcements.txt")).unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "Abolish\nthe bourgeoisie\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn command_pipes() {
+    let td = config_dir("command_pipes");
+    let out = td.join("out.txt");
+    let sink = sink(None, Some(&format!("(echo \"$TWEETR_AUTHOR $TWEETR_TIME\"; cat) > '{}'", out.display())));
+
+    let id = sink.post(&tweet("Abolish")).unwrap();
+    assert!(id > 0);
+
+    let mut content = String::new();
+    fs::File::open(&out).unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "announcements 2016-09-09T00:33:30+02:00\nAbolish");
+}
+
+#[cfg(unix)]
+#[test]
+fn command_fails() {
+    let sink = sink(None, Some("echo 'No announcements today' >&2; exit 1"));
+
+    assert_eq!(sink.post(&tweet("Abolish")),
+               Err(Outcome::BackendError {
+                   backend: "Sink \"announcements\"".to_string(),
+                   error: "No announcements today".to_string(),
+               }));
+}
+
+#[test]
+fn v2_body_place() {
+    let tweet = QueuedTweet {
+        reply_settings: Some(ReplySettings::Following),
+        place_id: Some("5a110d312052166f".to_string()),
+        ..tweet("The API is down")
+    };
+    assert_eq!(backend::tweet_v2_body(&tweet).map(|b| b.to_string()),
+               Ok(r#"{"geo":{"place_id":"5a110d312052166f"},"reply_settings":"following","text":"The API is down"}"#.to_string()));
+}
+
+#[test]
+fn v2_body_coordinates() {
+    let tweet = QueuedTweet {
+        reply_settings: Some(ReplySettings::MentionedUsers),
+        coordinates: Coordinates::new(52.2297, 21.0122),
+        ..tweet("The API is down")
+    };
+    assert_eq!(backend::tweet_v2_body(&tweet),
+               Err("tweets with reply_settings are posted via the v2 API, which doesn't take lat/long, tag it with a place_id instead".to_string()));
+}
+
+#[test]
+fn adapt_variants() {
+    let mut tweet = tweet("Abolish the bourgeoisie");
+    tweet.variants.insert("telegram".to_string(), "Abolish the bourgeoisie, full manifesto at https://example.com".to_string());
+
+    assert_eq!(backend::adapt(&tweet, "telegram"),
+               QueuedTweet { content: "Abolish the bourgeoisie, full manifesto at https://example.com".to_string(), ..tweet.clone() });
+    assert_eq!(backend::adapt(&tweet, "discord"), tweet);
+}
+
+#[test]
+fn adapt_truncates() {
+    let tweet = tweet(&"ą".repeat(2001));
+
+    let adapted = backend::adapt(&tweet, "discord");
+    assert_eq!(adapted.content, format!("{}…", "ą".repeat(1999)));
+    assert_eq!(backend::adapt(&tweet, "telegram"), tweet);
+}
+
+#[test]
+fn check_variants_fitting() {
+    let mut tweet = tweet("Abolish the bourgeoisie");
+    tweet.variants.insert("telegram".to_string(), "Abolish ".repeat(512));
+    tweet.variants.insert("discord".to_string(), "Abolish ".repeat(250));
+
+    assert_eq!(backend::check_variants(&tweet), Vec::<String>::new());
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-backend-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    fs::File::create(td.join("config.toml")).unwrap();
+    td
+}
+
+fn sink(file: Option<&str>, command: Option<&str>) -> Sink {
+    Sink {
+        name: "announcements".to_string(),
+        file: file.map(str::to_string),
+        command: command.map(str::to_string),
+        discord_webhook: None,
+    }
+}
+
+fn tweet(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "announcements".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,88 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::calendar::{self, Span};
+use self::tweetr::ops::l10n::{self, Formats};
+use self::tweetr::ops::QueuedTweet;
+use self::chrono::{Local, TimeZone, Weekday};
+
+
+#[test]
+fn empty_week() {
+    assert_eq!(render(&[], Span::Week, false),
+               "+----------+----------+----------+----------+----------+----------+----------+\n\
+                | Mon 12 … | Tue 13 … | Wed 14 … | Thu 15 … | Fri 16 … | Sat 17 … | Sun 18 … |\n\
+                +----------+----------+----------+----------+----------+----------+----------+\n");
+}
+
+#[test]
+fn week_sorted() {
+    let tweets = vec![tweet("nabijaczleweli", 9, 14, 17, "the bourgeoisie"), tweet("nabijaczleweli", 9, 14, 9, "Abolish")];
+
+    assert_eq!(render(&tweets, Span::Week, false).lines().skip(3).take(2).collect::<Vec<_>>(),
+               vec!["|          |          | 09:00 A… |          |          |          |          |",
+                    "|          |          | 17:00 t… |          |          |          |          |"]);
+}
+
+#[test]
+fn month_outside_empty() {
+    let tweets = vec![tweet("nabijaczleweli", 9, 30, 9, "Abolish"), tweet("nabijaczleweli", 10, 1, 9, "ignored")];
+    let rendered = render(&tweets, Span::Month, false);
+    let lines: Vec<_> = rendered.lines().collect();
+
+    assert_eq!(lines[1], "|          |          |          | Thu 01 … | Fri 02 … | Sat 03 … | Sun 04 … |");
+    assert_eq!(lines[lines.len() - 4], "| Mon 26 … | Tue 27 … | Wed 28 … | Thu 29 … | Fri 30 … |          |          |");
+    assert_eq!(lines[lines.len() - 2], "|          |          |          |          | 09:00 A… |          |          |");
+    assert!(!rendered.contains("ignored"));
+}
+
+#[test]
+fn colour_per_account() {
+    let tweets = vec![tweet("nabijaczleweli", 9, 12, 9, "Abolish"), tweet("danerangLP", 9, 13, 9, "Hewwo")];
+
+    assert_eq!(render(&tweets, Span::Week, true).lines().nth(3).unwrap(),
+               "| \x1b[33m09:00 A…\x1b[0m | \x1b[32m09:00 H…\x1b[0m |          |          |          |          |          |");
+}
+
+#[test]
+fn month_first_day_of_week() {
+    l10n::use_formats(Formats { first_day_of_week: Weekday::Sun, ..Formats::default() });
+
+    let rendered = render(&[], Span::Month, false);
+    let lines: Vec<_> = rendered.lines().collect();
+
+    assert_eq!(lines[1], "|          |          |          |          | Thu 01 … | Fri 02 … | Sat 03 … |");
+    assert_eq!(lines[lines.len() - 2], "| Sun 25 … | Mon 26 … | Tue 27 … | Wed 28 … | Thu 29 … | Fri 30 … |          |");
+}
+
+#[test]
+fn week_formats() {
+    l10n::use_formats(Formats {
+        date: "%d.%m".to_string(),
+        time: "%Hh".to_string(),
+        ..Formats::default()
+    });
+
+    let rendered = render(&[tweet("nabijaczleweli", 9, 14, 9, "Abolish")], Span::Week, false);
+    let lines: Vec<_> = rendered.lines().collect();
+
+    assert_eq!(lines[1], "| 12.09    | 13.09    | 14.09    | 15.09    | 16.09    | 17.09    | 18.09    |");
+    assert_eq!(lines[3], "|          |          | 09h Abo… |          |          |          |          |");
+}
+
+
+fn render(tweets: &[QueuedTweet], span: Span, colour: bool) -> String {
+    let mut out = Vec::new();
+    calendar::render(&mut out, tweets, span, Local.ymd(2016, 9, 14), 8, colour);
+    String::from_utf8(out).unwrap()
+}
+
+fn tweet(author: &str, month: u32, day: u32, hour: u32, content: &str) -> QueuedTweet {
+    let time = Local.ymd(2016, month, day).and_hms(hour, 0, 0);
+    QueuedTweet {
+        author: author.to_string(),
+        time: time.with_timezone(time.offset()),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,237 @@
+extern crate tweetr;
+
+use self::tweetr::ops::transform::Transforms;
+use self::tweetr::ops::embargo::Embargo;
+use self::tweetr::ops::backend::Sink;
+use self::tweetr::ops::Config;
+use self::tweetr::Outcome;
+use std::env::{self, temp_dir};
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn nonexistant_default() {
+    let td = temp_dir().join("tweetr-test").join("ops-config-nonexistant_default");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("config.toml");
+    let _ = fs::remove_file(&tf);
+
+    assert_eq!(Config::read(&tf), Ok(Config::default()));
+}
+
+#[test]
+fn empty_trans_eq() {
+    trans_scaffold("empty_trans_eq", Config::default());
+}
+
+#[test]
+fn full_trans_eq() {
+    trans_scaffold("full_trans_eq",
+                   Config {
+                       hooks_dir: Some("plugins".to_string()),
+                       dictionary: Some("/usr/share/dict/words".to_string()),
+                       thread_numbering: Some("{content} ({n}/{total})".to_string()),
+                       content_warning_format: Some("[{warning}] {content}".to_string()),
+                       quick_delimiter: Some(";;".to_string()),
+                       collision_window: Some(30),
+                       posting_slots: Some(vec!["Mon/Wed/Fri 09:00 17:00".to_string(), "Sat 12:00".to_string()]),
+                       refuse_insecure_users: Some(true),
+                       archive_size: Some(1000),
+                       archive_retention: Some(6),
+                       media_retention: Some(90),
+                       duplicate_window: Some(7),
+                       evergreen_cooldown: Some(60),
+                       horizon: Some(365),
+                       embargo: Some(vec![Embargo {
+                                              start: "2016-09-10 00:00".to_string(),
+                                              end: "2016-09-12 09:00".to_string(),
+                                          }]),
+                       embargo_policy: Some("spread".to_string()),
+                       recovery_policy: Some("retry".to_string()),
+                       missed_expiry: Some(120),
+                       latency_threshold: Some(600),
+                       sink: Some(vec![Sink {
+                                           name: "motd".to_string(),
+                                           file: Some("/etc/motd".to_string()),
+                                           command: None,
+                                           discord_webhook: None,
+                                       },
+                                       Sink {
+                                           name: "irc".to_string(),
+                                           file: None,
+                                           command: Some("ii-announce '#tweetr'".to_string()),
+                                           discord_webhook: None,
+                                       },
+                                       Sink {
+                                           name: "community".to_string(),
+                                           file: None,
+                                           command: None,
+                                           discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+                                       }]),
+                       connect_timeout: Some(5),
+                       read_timeout: Some(0),
+                       write_timeout: Some(60),
+                       bind_address: Some("127.0.0.1".to_string()),
+                       file_retries: Some(10),
+                       file_retry_delay: Some(500),
+                       datetime_format: Some("%d.%m.%Y %H:%M".to_string()),
+                       date_format: Some("%a %d.%m".to_string()),
+                       time_format: Some("%H:%M".to_string()),
+                       first_day_of_week: Some("Sun".to_string()),
+                       transform: Some(Transforms {
+                           collapse_spaces: Some(true),
+                           smart_quotes: Some(false),
+                           hashtags: Some("hashtags.txt".to_string()),
+                           campaign_tag: Some("#tweetr".to_string()),
+                       }),
+                       timezone: Some("+02:00".to_string()),
+                       quiet_hours: Some("23:00-07:00".to_string()),
+                       jitter: Some(5),
+                       tags: Some(vec!["tweetr".to_string()]),
+                       sensitive: Some(false),
+                   });
+}
+
+#[test]
+fn invalid_time_format() {
+    invalid_scaffold("invalid_time_format",
+                     "timezone = \"+02:00\"\ntime_format = \"%H:%Q\"\n",
+                     "error: 2:1: invalid strftime pattern \"%H:%Q\" for the key `time_format`");
+}
+
+#[test]
+fn invalid_datetime_format() {
+    invalid_scaffold("invalid_datetime_format",
+                     "datetime_format = \"%d.%m.%Y %\"\n",
+                     "error: 1:1: invalid strftime pattern \"%d.%m.%Y %\" for the key `datetime_format`");
+}
+
+#[test]
+fn invalid_first_day_of_week() {
+    invalid_scaffold("invalid_first_day_of_week",
+                     "first_day_of_week = \"Sunday\"\n",
+                     "error: 1:1: \"Sunday\" is not a weekday for the key `first_day_of_week`");
+}
+
+#[test]
+fn invalid_quick_delimiter() {
+    invalid_scaffold("invalid_quick_delimiter",
+                     "quick_delimiter = \"\"\n",
+                     "error: 1:1: the delimiter can't be empty for the key `quick_delimiter`");
+}
+
+#[test]
+fn invalid_embargo() {
+    invalid_scaffold("invalid_embargo",
+                     "[[embargo]]\nstart = \"2016-09-12 09:00\"\nend = \"2016-09-10\"\n",
+                     "error: invalid embargo: \"2016-09-10\" is not a date and time for the key `embargo`");
+}
+
+#[test]
+fn invalid_embargo_policy() {
+    invalid_scaffold("invalid_embargo_policy",
+                     "embargo_policy = \"later\"\n",
+                     "error: 1:1: \"later\" is not one of end, spread or slots for the key `embargo_policy`");
+}
+
+#[test]
+fn invalid_recovery_policy() {
+    invalid_scaffold("invalid_recovery_policy",
+                     "recovery_policy = \"ignore\"\n",
+                     "error: 1:1: \"ignore\" is not one of hold or retry for the key `recovery_policy`");
+}
+
+#[test]
+fn invalid_bind_address() {
+    invalid_scaffold("invalid_bind_address",
+                     "bind_address = \"eth-nonexistent\"\n",
+                     "error: 1:1: no such IP address or network interface \"eth-nonexistent\" for the key `bind_address`");
+}
+
+#[test]
+fn invalid_collision_window() {
+    invalid_scaffold("invalid_collision_window",
+                     "collision_window = -10\n",
+                     "error: 1:1: -10 minutes is not between 0 and 525600 (a year) for the key `collision_window`");
+}
+
+#[test]
+fn invalid_duplicate_window() {
+    invalid_scaffold("invalid_duplicate_window",
+                     "duplicate_window = 100000000000000\n",
+                     "error: 1:1: 100000000000000 days is not between 0 and 365 (a year) for the key `duplicate_window`");
+}
+
+#[test]
+fn invalid_after_multiline_string() {
+    invalid_scaffold("invalid_after_multiline_string",
+                     "dictionary = \"\"\"\ncollision_window = 10\n\"\"\"\ncollision_window = -10\n",
+                     "error: 4:1: -10 minutes is not between 0 and 525600 (a year) for the key `collision_window`");
+}
+
+#[test]
+fn invalid_quoted_key() {
+    invalid_scaffold("invalid_quoted_key",
+                     "\"collision_window\" = -10\n",
+                     "error: -10 minutes is not between 0 and 525600 (a year) for the key `collision_window`");
+}
+
+#[test]
+fn interpolated() {
+    let td = temp_dir().join("tweetr-test").join("ops-config-interpolated");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("config.toml");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"dictionary = \"${TWEETR_TEST_CONFIG_INTERPOLATED}/words\"\n\
+                     [[sink]]\n\
+                     name = \"announcements\"\n\
+                     discord_webhook = \"https://discordapp.com/api/webhooks/${TWEETR_TEST_CONFIG_INTERPOLATED_WEBHOOK}\"\n")
+        .unwrap();
+    env::set_var("TWEETR_TEST_CONFIG_INTERPOLATED", "/usr/share/dict");
+    env::set_var("TWEETR_TEST_CONFIG_INTERPOLATED_WEBHOOK", "2334/Hb8KDdv-DmkLz");
+
+    let config = Config::read(&tf).unwrap();
+    assert_eq!(config.dictionary, Some("/usr/share/dict/words".to_string()));
+    assert_eq!(config.sink.unwrap()[0].discord_webhook,
+               Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()));
+}
+
+#[test]
+fn interpolated_unset() {
+    env::remove_var("TWEETR_TEST_CONFIG_INTERPOLATED_UNSET");
+    invalid_scaffold("interpolated_unset",
+                     "hooks_dir = \"${TWEETR_TEST_CONFIG_INTERPOLATED_UNSET}\"\n",
+                     "error: 1:1: environment variable TWEETR_TEST_CONFIG_INTERPOLATED_UNSET isn't set for the key `hooks_dir`");
+}
+
+
+fn invalid_scaffold(name: &str, contents: &str, error: &str) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-config-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("config.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+
+    assert_eq!(Config::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "configuration",
+                   errors: vec![error.to_string()],
+               })));
+}
+
+fn trans_scaffold(name: &str, config: Config) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-config-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("config.toml");
+    let _ = fs::remove_file(&tf);
+
+    config.write(&tf).unwrap();
+    let read_config = Config::read(&tf).unwrap();
+
+    assert_eq!(config, read_config);
+}
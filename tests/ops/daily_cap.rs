@@ -0,0 +1,70 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, daily_cap};
+use self::chrono::{DateTime, FixedOffset};
+
+
+#[test]
+fn posted_on_ignores_queued() {
+    let tweets = [posted("nabijaczleweli", "2016-09-10T09:00:00+02:00", "2016-09-10T09:00:00+02:00"),
+                  tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                  posted("danerangLP", "2016-09-10T09:00:00+02:00", "2016-09-10T09:00:00+02:00")];
+
+    assert_eq!(daily_cap::posted_on(&tweets, "nabijaczleweli", time("2016-09-10T15:00:00+02:00"), tz()), 1);
+}
+
+#[test]
+fn carry_over_keeps_order() {
+    let now = time("2016-09-10T18:00:00+02:00");
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00"), tweet("nabijaczleweli", "2016-09-10T15:00:00+02:00")];
+    for t in &mut tweets {
+        daily_cap::carry_over(t, now, tz());
+    }
+
+    assert_eq!(tweets.iter().map(|t| t.time).collect::<Vec<_>>(),
+               vec![time("2016-09-11T12:00:00+02:00"), time("2016-09-11T15:00:00+02:00")]);
+}
+
+#[test]
+fn carry_over_in_timezone() {
+    let mut tweet = tweet("nabijaczleweli", "2016-09-10T23:30:00+02:00");
+    daily_cap::carry_over(&mut tweet, time("2016-09-10T23:45:00+02:00"), FixedOffset::east(0));
+
+    assert_eq!(tweet.time, time("2016-09-11T23:30:00+02:00"));
+}
+
+#[test]
+fn check_counts_posted() {
+    let queue = [posted("nabijaczleweli", "2016-09-09T09:00:00+02:00", "2016-09-10T09:00:00+02:00"),
+                 tweet("nabijaczleweli", "2016-09-09T12:00:00+02:00")];
+
+    assert!(daily_cap::check(&tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00"), &queue, 1, tz()).is_some());
+    assert_eq!(daily_cap::check(&tweet("danerangLP", "2016-09-10T12:00:00+02:00"), &queue, 1, tz()), None);
+}
+
+
+fn tz() -> FixedOffset {
+    FixedOffset::east(2 * 60 * 60)
+}
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn posted(author: &str, at: &str, posted_at: &str) -> QueuedTweet {
+    QueuedTweet {
+        time_posted: Some(time(posted_at)),
+        id: Some(774560457755590656),
+        ..tweet(author, at)
+    }
+}
+
+fn tweet(author: &str, at: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: time(at),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
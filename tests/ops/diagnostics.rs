@@ -0,0 +1,32 @@
+extern crate tweetr;
+
+use self::tweetr::ops::diagnostics::{self, Diagnostics, Verbosity};
+use std::io::Write;
+
+
+#[test]
+fn verbosity_new() {
+    assert_eq!(Verbosity::new(false, false), Verbosity::Normal);
+    assert_eq!(Verbosity::new(true, false), Verbosity::Quiet);
+    assert_eq!(Verbosity::new(false, true), Verbosity::Verbose);
+    assert_eq!(Verbosity::new(true, true), Verbosity::Quiet);
+}
+
+#[test]
+fn quiet_drops() {
+    let mut out = Vec::new();
+
+    diagnostics::use_verbosity(Verbosity::Quiet);
+    writeln!(Diagnostics::new(&mut out), "Removed 2 tweets.").unwrap();
+    assert!(!diagnostics::verbose());
+    assert!(out.is_empty());
+
+    diagnostics::use_verbosity(Verbosity::Normal);
+    writeln!(Diagnostics::new(&mut out), "Removed 2 tweets.").unwrap();
+    assert!(!diagnostics::verbose());
+    assert_eq!(out, b"Removed 2 tweets.\n".to_vec());
+
+    diagnostics::use_verbosity(Verbosity::Verbose);
+    assert!(diagnostics::verbose());
+    diagnostics::use_verbosity(Verbosity::Normal);
+}
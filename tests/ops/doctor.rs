@@ -0,0 +1,132 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::doctor::{self, Check};
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, Duration};
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+#[test]
+fn versions_empty_dir() {
+    assert_eq!(doctor::check_versions(&config_dir("versions_empty_dir")).outcome, Outcome::NoError);
+}
+
+#[test]
+fn versions_sink_keys() {
+    let td = config_dir("versions_sink_keys");
+    File::create(td.join("config.toml"))
+        .unwrap()
+        .write_all(b"[[sink]]\nname = \"motd\"\nfile = \"/etc/motd\"\n\n[[sink]]\nname = \"irc\"\ncommand = \"ii-announce\"\nchannel = \"#tweetr\"\n")
+        .unwrap();
+
+    assert_eq!(doctor::check_versions(&td).outcome,
+               Outcome::FileParsingFailed {
+                   desc: "configuration directory",
+                   errors: vec!["config.toml: unknown key `sink.1.channel`".to_string()],
+               });
+}
+
+#[test]
+fn versions_named_anchored() {
+    let td = config_dir("versions_named_anchored");
+    File::create(td.join("tweets.toml"))
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nname = \"launch\"\n\n\
+                     [[tweet]]\nauthor = \"a\"\ntime = \"15 minutes after launch\"\ncontent = \"c\"\n")
+        .unwrap();
+
+    assert_eq!(doctor::check_versions(&td).outcome, Outcome::NoError);
+}
+
+#[test]
+fn versions_embargo_keys() {
+    let td = config_dir("versions_embargo_keys");
+    File::create(td.join("config.toml"))
+        .unwrap()
+        .write_all(b"[[embargo]]\nstart = \"2016-09-10T00:00:00+02:00\"\nend = \"2016-09-11T00:00:00+02:00\"\nreason = \"election\"\n")
+        .unwrap();
+
+    assert_eq!(doctor::check_versions(&td).outcome,
+               Outcome::FileParsingFailed {
+                   desc: "configuration directory",
+                   errors: vec!["config.toml: unknown key `embargo.0.reason`".to_string()],
+               });
+}
+
+#[test]
+fn versions_invalid_skipped() {
+    let td = config_dir("versions_invalid_skipped");
+    File::create(td.join("app.toml")).unwrap().write_all(b"key = \n").unwrap();
+
+    assert_eq!(doctor::check_versions(&td).outcome, Outcome::NoError);
+}
+
+#[cfg(unix)]
+#[test]
+fn permissions_insecure_users() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let td = config_dir("permissions_insecure_users");
+    File::create(td.join("users.toml")).unwrap();
+    fs::set_permissions(td.join("users.toml"), fs::Permissions::from_mode(0o644)).unwrap();
+
+    assert_eq!(doctor::check_permissions(&td).outcome,
+               Outcome::PreflightFailed(vec![format!("{} is readable by everyone (mode 644), exposing the users' access tokens",
+                                                     td.join("users.toml").display())]));
+}
+
+#[test]
+fn clock_ahead() {
+    let server_time = DateTime::parse_from_rfc2822("Fri, 09 Sep 2016 12:00:00 GMT").unwrap();
+
+    assert_eq!(doctor::check_clock(server_time, server_time + Duration::seconds(61)).outcome,
+               Outcome::PreflightFailed(vec!["the system clock is 61 seconds ahead of the Twitter API's".to_string()]));
+    assert_eq!(doctor::check_clock(server_time, server_time + Duration::seconds(60)).outcome, Outcome::NoError);
+}
+
+#[test]
+fn summarise_multiple() {
+    let checks = vec![check(Outcome::NoError),
+                      check(Outcome::TwitterAPIError("Invalid or expired token.".to_string())),
+                      check(Outcome::PreflightFailed(vec!["the system clock is 300 seconds behind the Twitter API's".to_string()]))];
+
+    assert_eq!(doctor::summarise(&checks),
+               Outcome::Multiple(vec![Outcome::TwitterAPIError("Invalid or expired token.".to_string()),
+                                      Outcome::PreflightFailed(vec!["the system clock is 300 seconds behind the Twitter API's"
+                                                                        .to_string()])]));
+}
+
+#[test]
+fn print_multiline_failure() {
+    let mut out = Vec::new();
+    doctor::print_results(&mut out,
+                          &[check(Outcome::PreflightFailed(vec!["tweets.toml can't be written".to_string(),
+                                                                "users.toml is readable by everyone".to_string()]))]);
+
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "[FAIL] file permissions\n  \
+                  Can't start:\n    \
+                    tweets.toml can't be written\n    \
+                    users.toml is readable by everyone\n  \
+                  Hint: chmod\n");
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-doctor-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
+
+fn check(outcome: Outcome) -> Check {
+    Check {
+        name: "file permissions".to_string(),
+        outcome: outcome,
+        hint: "chmod",
+    }
+}
@@ -0,0 +1,111 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::embargo::{self, Policy, Window};
+use self::tweetr::ops::suggest_time::Slot;
+use self::tweetr::ops::QueuedTweet;
+use self::chrono::{Datelike, DateTime, Duration, FixedOffset, Local, Timelike};
+
+
+#[test]
+fn lifted_overlapping() {
+    let embargoes = [window("2016-09-10T00:00:00+02:00", "2016-09-12T09:00:00+02:00"),
+                     window("2016-09-11T00:00:00+02:00", "2016-09-11T12:00:00+02:00"),
+                     window("2016-09-12T08:00:00+02:00", "2016-09-13T00:00:00+02:00"),
+                     window("2016-09-14T00:00:00+02:00", "2016-09-15T00:00:00+02:00")];
+
+    assert_eq!(embargo::lifted(&embargoes, time("2016-09-11T06:00:00+02:00")), Some(time("2016-09-13T00:00:00+02:00")));
+    assert_eq!(embargo::lifted(&embargoes, time("2016-09-13T00:00:00+02:00")), None);
+    assert_eq!(embargo::lifted(&embargoes, time("2016-09-14T12:00:00+02:00")), Some(time("2016-09-15T00:00:00+02:00")));
+}
+
+#[test]
+fn lifted_in_time_offset() {
+    let embargoes = [window("2016-09-10T00:00:00+02:00", "2016-09-12T09:00:00+02:00")];
+
+    let lifted = embargo::lifted(&embargoes, time("2016-09-11T06:00:00-05:00")).unwrap();
+    assert_eq!(lifted, time("2016-09-12T02:00:00-05:00"));
+    assert_eq!(lifted.offset(), &FixedOffset::west(5 * 60 * 60));
+}
+
+#[test]
+fn reschedule_spread_per_author() {
+    let embargoes = [window("2016-09-10T00:00:00+02:00", "2016-09-12T09:00:00+02:00")];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-11T12:00:00+02:00"),
+                          tweet("danerangLP", "2016-09-11T12:00:00-05:00"),
+                          tweet("nabijaczleweli", "2016-09-11T13:00:00+02:00"),
+                          tweet("nabijaczleweli", "2016-09-11T14:00:00+02:00")];
+
+    embargo::reschedule(&mut tweets, &[0, 1, 2], &embargoes, embargoes[0].end, Policy::Spread, Duration::minutes(15), &[]);
+    assert_eq!(tweets[0].time, time("2016-09-12T09:00:00+02:00"));
+    assert_eq!(tweets[1].time, time("2016-09-12T02:00:00-05:00"));
+    assert_eq!(tweets[1].time.offset(), &FixedOffset::west(5 * 60 * 60));
+    assert_eq!(tweets[2].time, time("2016-09-12T09:15:00+02:00"));
+    assert_eq!(tweets[3].time, time("2016-09-11T14:00:00+02:00"));
+}
+
+#[test]
+fn reschedule_spread_past_next_embargo() {
+    let embargoes = [window("2016-09-10T00:00:00+02:00", "2016-09-12T09:00:00+02:00"),
+                     window("2016-09-12T09:10:00+02:00", "2016-09-12T10:00:00+02:00")];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-11T12:00:00+02:00"), tweet("nabijaczleweli", "2016-09-11T13:00:00+02:00")];
+
+    embargo::reschedule(&mut tweets, &[0, 1], &embargoes, embargoes[0].end, Policy::Spread, Duration::minutes(15), &[]);
+    assert_eq!(tweets[0].time, time("2016-09-12T09:00:00+02:00"));
+    assert_eq!(tweets[1].time, time("2016-09-12T10:00:00+02:00"));
+}
+
+#[test]
+fn reschedule_slots_no_grid() {
+    let embargoes = [window("2016-09-10T00:00:00+02:00", "2016-09-12T09:00:00+02:00")];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-11T12:00:00+02:00")];
+
+    embargo::reschedule(&mut tweets, &[0], &embargoes, embargoes[0].end, Policy::Slots, Duration::minutes(15), &[]);
+    assert_eq!(tweets[0].time, time("2016-09-12T09:00:00+02:00"));
+}
+
+#[test]
+fn reschedule_slots_free() {
+    let lifted = time("2016-09-12T09:00:00+02:00");
+    let embargoes = [Window {
+                         start: lifted - Duration::days(2),
+                         end: lifted,
+                     }];
+    let local = lifted.with_timezone(&Local);
+    let grid = [Slot {
+                    weekday: local.weekday(),
+                    hour: local.hour(),
+                    minute: local.minute(),
+                },
+                Slot {
+                    weekday: local.weekday().succ(),
+                    hour: local.hour(),
+                    minute: local.minute(),
+                }];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-11T12:00:00+02:00"), tweet("nabijaczleweli", "2016-09-11T13:00:00+02:00")];
+
+    embargo::reschedule(&mut tweets, &[0, 1], &embargoes, lifted, Policy::Slots, Duration::minutes(15), &grid);
+    assert_eq!(tweets[0].time, lifted);
+    assert_eq!(tweets[1].time, lifted + Duration::days(1));
+}
+
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn window(start: &str, end: &str) -> Window {
+    Window {
+        start: time(start),
+        end: time(end),
+    }
+}
+
+fn tweet(author: &str, at: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: time(at),
+        content: "Q3 results are in".to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,147 @@
+extern crate rustc_serialize;
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::events::{self, Event, EventLog, Tracker};
+use self::tweetr::ops::QueuedTweet;
+use self::rustc_serialize::json::Json;
+use self::chrono::DateTime;
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Read;
+
+
+#[test]
+fn tweet_json() {
+    let posted = QueuedTweet {
+        thread: Some("release".to_string()),
+        time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:05+02:00").unwrap()),
+        id: Some(774560457755590656),
+        ..tweet("Abolish the bourgeoisie")
+    };
+
+    let json = Event::Posted(&posted).to_json(DateTime::parse_from_rfc3339("2016-09-10T12:00:06+02:00").unwrap());
+    assert_eq!(json.find("event").and_then(Json::as_string), Some("posted"));
+    assert_eq!(json.find("time").and_then(Json::as_string), Some("2016-09-10T12:00:06+02:00"));
+    assert_eq!(json.find_path(&["tweet", "author"]).and_then(Json::as_string), Some("nabijaczleweli"));
+    assert_eq!(json.find_path(&["tweet", "content"]).and_then(Json::as_string), Some("Abolish the bourgeoisie"));
+    assert_eq!(json.find_path(&["tweet", "scheduled"]).and_then(Json::as_string), Some("2016-09-10T12:00:00+02:00"));
+    assert_eq!(json.find_path(&["tweet", "thread"]).and_then(Json::as_string), Some("release"));
+    assert_eq!(json.find_path(&["tweet", "id"]).and_then(Json::as_i64), Some(774560457755590656));
+    assert_eq!(json.find_path(&["tweet", "time_posted"]).and_then(Json::as_string), Some("2016-09-10T12:00:05+02:00"));
+}
+
+#[test]
+fn unposted_tweet_json() {
+    let queued = tweet("Abolish the bourgeoisie");
+    let json = Event::Queued(&queued).to_json(DateTime::parse_from_rfc3339("2016-09-10T11:00:00+02:00").unwrap());
+    assert_eq!(json.find("tweet").and_then(Json::as_object).map(|t| t.keys().cloned().collect::<Vec<_>>()),
+               Some(vec!["author".to_string(), "content".to_string(), "scheduled".to_string()]));
+    assert_eq!(json.find("error"), None);
+}
+
+#[test]
+fn failure_json() {
+    let queued = tweet("Abolish the bourgeoisie");
+    let json = Event::failure(&queued, &Outcome::TwitterAPIError("Status is a duplicate. (code 187)".to_string()))
+        .to_json(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+    assert_eq!(json.find("event").and_then(Json::as_string), Some("failed"));
+    assert_eq!(json.find("error").and_then(Json::as_string), Some("Twitter API error: Status is a duplicate. (code 187)"));
+}
+
+#[test]
+fn rate_limits() {
+    let queued = tweet("Abolish the bourgeoisie");
+    for e in &["Rate limit reached, hold until 1473505200", "Rate limit exceeded (code 88)", "User is over daily status update limit. (code 185)"] {
+        assert_eq!(Event::failure(&queued, &Outcome::TwitterAPIError(e.to_string())).name(), "rate_limited");
+    }
+    assert_eq!(Event::failure(&queued, &Outcome::NetworkTimeout { desc: "posting tweet".to_string() }).name(), "failed");
+}
+
+#[test]
+fn suspensions() {
+    let queued = tweet("Abolish the bourgeoisie");
+    for e in &["Your account is suspended and is not permitted to access this feature. (code 64)",
+               "To protect our users from spam and other malicious activity, this account is temporarily locked. (code 326)"] {
+        assert_eq!(Event::failure(&queued, &Outcome::TwitterAPIError(e.to_string())).name(), "suspended");
+    }
+    assert_eq!(Event::failure(&queued, &Outcome::TwitterAPIError("Invalid or expired token. (code 89)".to_string())).name(), "failed");
+}
+
+#[test]
+fn permanent_failures() {
+    for e in &["No status found with that ID. (code 144)", "Status is over 140 characters. (code 186)", "Status is a duplicate. (code 187)"] {
+        assert!(events::permanent(&Outcome::TwitterAPIError(e.to_string())));
+    }
+    for e in &["Over capacity (code 130)", "Internal error (code 131)", "Rate limit exceeded (code 88)", "Invalid or expired token. (code 89)"] {
+        assert!(!events::permanent(&Outcome::TwitterAPIError(e.to_string())));
+    }
+    assert!(!events::permanent(&Outcome::NetworkTimeout { desc: "posting tweet".to_string() }));
+}
+
+#[test]
+fn tracker_forgets_removed() {
+    let queued = tweet("Abolish the bourgeoisie");
+    let mut tracker = Tracker::default();
+    assert_eq!(tracker.queued(&[queued.clone()]), vec![0]);
+    assert!(tracker.due(&queued));
+
+    assert_eq!(tracker.queued(&[]), Vec::<usize>::new());
+    assert_eq!(tracker.queued(&[queued.clone()]), vec![0]);
+    assert!(tracker.due(&queued));
+}
+
+#[test]
+fn tracker_changed() {
+    let queued = tweet("Abolish the bourgeoisie");
+    let mut tracker = Tracker::default();
+    tracker.queued(&[queued.clone()]);
+
+    let rescheduled = QueuedTweet { time: DateTime::parse_from_rfc3339("2016-09-11T12:00:00+02:00").unwrap(), ..queued.clone() };
+    assert_eq!(tracker.queued(&[rescheduled]), vec![0]);
+}
+
+#[test]
+fn tracker_skips_posted() {
+    let posted = QueuedTweet { id: Some(774560457755590656), ..tweet("Abolish the bourgeoisie") };
+    let mut tracker = Tracker::default();
+    assert_eq!(tracker.queued(&[posted, tweet("Workers of the world, unite!")]), vec![1]);
+}
+
+#[test]
+fn log_appends() {
+    let td = temp_dir().join("tweetr-test").join("ops-events-log_appends");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("events.ndjson");
+    let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+    let queued = tweet("Abolish the bourgeoisie");
+
+    EventLog::open(&tf).unwrap().emit(Event::Queued(&queued), now).unwrap();
+    let mut log = EventLog::open(&tf).unwrap();
+    log.emit(Event::Posting(&queued), now).unwrap();
+    log.emit(Event::Sleeping(now), now).unwrap();
+
+    let mut written = String::new();
+    File::open(&tf).unwrap().read_to_string(&mut written).unwrap();
+    let events: Vec<_> = written.lines().map(|l| Json::from_str(l).unwrap().find("event").and_then(Json::as_string).unwrap().to_string()).collect();
+    assert_eq!(events, vec!["queued", "posting", "sleeping"]);
+}
+
+#[test]
+fn log_none() {
+    assert_eq!(EventLog::none().emit(Event::Sleeping(DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()),
+                                     DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()),
+               Ok(()));
+}
+
+
+fn tweet(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
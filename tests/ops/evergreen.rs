@@ -0,0 +1,153 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::evergreen::{self, EvergreenGroup, EvergreenTweet};
+use self::tweetr::ops::suggest_time;
+use self::chrono::{DateTime, Duration, FixedOffset, Local, TimeZone};
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn read_weight() {
+    let td = temp_dir().join("tweetr-test").join("ops-evergreen-read_weight");
+    fs::create_dir_all(&td).unwrap();
+    let tf = EvergreenTweet::path(&td);
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish the bourgeoisie\"\nweight = 0\n\n\
+                     [[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish the bourgeoisie\"\n\n\
+                     [[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish the bourgeoisie\"\nweight = \"3\"\n")
+        .unwrap();
+
+    assert_eq!(EvergreenTweet::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "evergreen tweets",
+                   errors: vec!["error: 13:1: expected a value of type `integer`, but found a value of type `string` for the key \
+                                  `tweet.2.weight`".to_string()],
+               })));
+
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish the bourgeoisie\"\nweight = 0\n\n\
+                     [[tweet]]\nauthor = \"nabijaczleweli\"\ncontent = \"Abolish the bourgeoisie\"\n")
+        .unwrap();
+    assert_eq!(EvergreenTweet::read(&tf), Ok(vec![tweet(0, None), tweet(1, None)]));
+}
+
+#[test]
+fn trans_eq() {
+    let td = temp_dir().join("tweetr-test").join("ops-evergreen-trans_eq");
+    fs::create_dir_all(&td).unwrap();
+    let tf = EvergreenTweet::path(&td);
+    let _ = fs::remove_file(&tf);
+
+    let pool = vec![tweet(1, None), tweet(0, Some(now())), tweet(5, Some(DateTime::parse_from_rfc3339("2016-09-12T09:00:00+02:00").unwrap()))];
+    EvergreenTweet::write(pool.clone(), vec![], &tf).unwrap();
+    assert_eq!(EvergreenTweet::read(&tf), Ok(pool));
+}
+
+#[test]
+fn trans_eq_groups() {
+    let td = temp_dir().join("tweetr-test").join("ops-evergreen-trans_eq_groups");
+    fs::create_dir_all(&td).unwrap();
+    let tf = EvergreenTweet::path(&td);
+    let _ = fs::remove_file(&tf);
+
+    let groups = vec![group(&[("tweetr", 70), ("tweetr_de", 15), ("tweetr_pl", 15)]),
+                      EvergreenGroup { queued: vec![("nabijaczleweli".to_string(), 3)].into_iter().collect(), ..group(&[("nabijaczleweli", 1)]) }];
+    EvergreenTweet::write(vec![tweet(1, None)], groups.clone(), &tf).unwrap();
+    assert_eq!(EvergreenTweet::read(&tf), Ok(vec![tweet(1, None)]));
+    assert_eq!(EvergreenGroup::read(&tf), Ok(groups));
+}
+
+#[test]
+fn group_ratios_converge() {
+    let mut pool: Vec<_> = ["tweetr", "tweetr_de", "tweetr_pl"].iter().map(|a| EvergreenTweet { author: a.to_string(), ..tweet(1, None) }).collect();
+    let mut groups = vec![group(&[("tweetr", 70), ("tweetr_de", 15), ("tweetr_pl", 15)])];
+
+    let slot = now();
+    for i in 0..100 {
+        let queued = evergreen::fill(&mut pool, &mut groups, slot + Duration::days(i), &[], Duration::minutes(10), Duration::days(0), now());
+        assert_eq!(queued.len(), 1);
+
+        let total = (i + 1) as f64;
+        for (account, &weight) in &groups[0].weights {
+            let queued = groups[0].queued.get(account).cloned().unwrap_or(0) as f64;
+            assert!((queued - total * weight as f64 / 100.0).abs() <= 1.0, "{}: {} after {}", account, queued, total);
+        }
+    }
+    assert_eq!(groups[0].queued,
+               vec![("tweetr".to_string(), 70), ("tweetr_de".to_string(), 15), ("tweetr_pl".to_string(), 15)].into_iter().collect());
+}
+
+#[test]
+fn group_slot_taken() {
+    let mut pool: Vec<_> = ["tweetr", "tweetr_pl"].iter().map(|a| EvergreenTweet { author: a.to_string(), ..tweet(1, None) }).collect();
+    let mut groups = vec![group(&[("tweetr", 1), ("tweetr_pl", 1)])];
+
+    let slot = now();
+    let fresh = EvergreenTweet { author: "tweetr_pl".to_string(), ..tweet(1, None) }.queued(slot);
+    assert_eq!(evergreen::fill(&mut pool, &mut groups, slot, &[fresh], Duration::minutes(10), Duration::days(0), now()), vec![]);
+    assert!(groups[0].queued.is_empty());
+
+    pool[0].weight = 0;
+    assert_eq!(evergreen::fill(&mut pool, &mut groups, slot, &[], Duration::minutes(10), Duration::days(0), now()),
+               vec![pool[1].queued(slot)]);
+}
+
+#[test]
+fn pick_weighted() {
+    let pool = vec![tweet(1, None), tweet(0, None), tweet(3, None)];
+
+    let picks: Vec<_> = (0..8).map(|r| evergreen::pick(&pool, "nabijaczleweli", now(), Duration::days(30), r)).collect();
+    assert_eq!(picks, vec![Some(0), Some(2), Some(2), Some(2), Some(0), Some(2), Some(2), Some(2)]);
+}
+
+#[test]
+fn pick_cooldown() {
+    let now = now();
+    let pool = vec![tweet(1, Some(now - Duration::days(29))), tweet(1, Some(now - Duration::days(30)))];
+
+    assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(30), 0), Some(1));
+    assert_eq!(evergreen::pick(&pool, "nabijaczleweli", now, Duration::days(31), 0), None);
+    assert_eq!(evergreen::pick(&[tweet(0, None)], "nabijaczleweli", now, Duration::days(30), 0), None);
+}
+
+#[test]
+fn due_slot_latest() {
+    let grid = suggest_time::parse_slots("Mon/Wed 09:00 17:00").unwrap();
+    let since = fixed(Local.ymd(2016, 9, 12).and_hms(9, 0, 0));
+
+    assert_eq!(evergreen::due_slot(&grid, since, since + Duration::hours(7)), None);
+    assert_eq!(evergreen::due_slot(&grid, since, since + Duration::hours(8)), Some(since + Duration::hours(8)));
+    assert_eq!(evergreen::due_slot(&grid, since, since + Duration::weeks(1)), Some(since + Duration::weeks(1)));
+    assert_eq!(evergreen::due_slot(&grid, since, since - Duration::hours(1)), None);
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    fixed(Local::now())
+}
+
+fn fixed(time: DateTime<Local>) -> DateTime<FixedOffset> {
+    time.with_timezone(time.offset())
+}
+
+fn group(weights: &[(&str, u32)]) -> EvergreenGroup {
+    EvergreenGroup {
+        weights: weights.iter().map(|&(a, w)| (a.to_string(), w)).collect(),
+        queued: Default::default(),
+    }
+}
+
+fn tweet(weight: u32, last_queued: Option<DateTime<FixedOffset>>) -> EvergreenTweet {
+    EvergreenTweet {
+        author: "nabijaczleweli".to_string(),
+        content: "Abolish the bourgeoisie".to_string(),
+        weight: weight,
+        last_queued: last_queued,
+    }
+}
@@ -0,0 +1,124 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::chrono::{DateTime, FixedOffset};
+use self::tweetr::ops::{archive, export_posted, QueuedTweet, User};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn posted_archived_and_queued() {
+    let td = config_dir("posted_archived_and_queued");
+    let mut archived = vec![posted("Abolish", "2016-09-10T12:00:00+02:00"), posted("the", "2016-09-12T12:00:00+02:00")];
+    archive::rotate(&mut archived, &td, None).unwrap();
+    let queue = vec![unposted("Workers of the world"), posted("bourgeoisie", "2016-09-11T12:00:00+02:00")];
+
+    assert_eq!(export_posted::posted(&td, &queue, None),
+               Ok(vec![posted("Abolish", "2016-09-10T12:00:00+02:00"),
+                       posted("bourgeoisie", "2016-09-11T12:00:00+02:00"),
+                       posted("the", "2016-09-12T12:00:00+02:00")]));
+    assert_eq!(export_posted::posted(&td, &queue, Some(time("2016-09-11T00:00:00+02:00"))),
+               Ok(vec![posted("bourgeoisie", "2016-09-11T12:00:00+02:00"), posted("the", "2016-09-12T12:00:00+02:00")]));
+}
+
+#[test]
+fn csv_quoted() {
+    let tweet = QueuedTweet {
+        content: "Abolish, \"the\"\nbourgeoisie".to_string(),
+        app: None,
+        retries: 2,
+        ..posted("", "2016-09-10T12:00:05+02:00")
+    };
+
+    let mut out = Vec::new();
+    export_posted::write_csv(&mut out, &[tweet], &[user("nabijaczleweli")]);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "id,account,text,scheduled,posted,retries,permalink\n\
+                774541307568103424,nabijaczleweli,\"Abolish, \"\"the\"\"\nbourgeoisie\",2016-09-10T12:00:00+02:00,2016-09-10T12:00:05+02:00,2,\
+                https://twitter.com/nabijaczleweli/status/774541307568103424\n");
+}
+
+#[test]
+fn csv_no_permalink_for_sinks() {
+    let tweet = QueuedTweet { author: "motd".to_string(), ..posted("Abolish", "2016-09-10T12:00:05+02:00") };
+
+    let mut out = Vec::new();
+    export_posted::write_csv(&mut out, &[tweet], &[user("nabijaczleweli")]);
+    assert_eq!(String::from_utf8(out).unwrap().lines().last(),
+               Some("774541307568103424,motd,Abolish,2016-09-10T12:00:00+02:00,2016-09-10T12:00:05+02:00,0,"));
+}
+
+#[test]
+fn json_ids_strings() {
+    let tweets = [posted("Abolish", "2016-09-10T12:00:05+02:00"), QueuedTweet { author: "motd".to_string(), ..posted("the", "2016-09-10T12:00:05+02:00") }];
+
+    let mut out = Vec::new();
+    export_posted::write_json(&mut out, &tweets, &[user("nabijaczleweli")]);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains(r#""id": "774541307568103424""#), "{}", out);
+    assert!(out.contains(r#""permalink": "https://twitter.com/nabijaczleweli/status/774541307568103424""#), "{}", out);
+    assert!(out.contains(r#""permalink": null"#), "{}", out);
+    assert!(out.contains(r#""retries": 0"#), "{}", out);
+}
+
+#[test]
+fn json_empty() {
+    let mut out = Vec::new();
+    export_posted::write_json(&mut out, &[], &[]);
+    assert_eq!(String::from_utf8(out).unwrap(), "[]\n");
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-export_posted-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn unposted(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time("2016-09-10T12:00:00+02:00"),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
+
+fn posted(content: &str, time_posted: &str) -> QueuedTweet {
+    QueuedTweet {
+        time_posted: Some(time(time_posted)),
+        id: Some(774541307568103424),
+        ..unposted(content)
+    }
+}
+
+fn user(name: &str) -> User {
+    User {
+        name: name.to_string(),
+        id: 481,
+        access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
@@ -0,0 +1,89 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, fmt_queue};
+use self::chrono::DateTime;
+
+
+#[test]
+fn sort_stable() {
+    let mut tweets = vec![tweet("Abolish", "2016-09-10T12:00:00+02:00", None),
+                          tweet("the bourgeoisie", "2016-09-10T10:00:00Z", None),
+                          tweet("Workers", "2016-09-10T11:00:00+01:00", None)];
+    fmt_queue::sort(&mut tweets);
+
+    assert_eq!(contents(&tweets), vec!["Abolish", "the bourgeoisie", "Workers"]);
+}
+
+#[test]
+fn sort_by_instant() {
+    let mut tweets = vec![tweet("Abolish", "2016-09-10T12:00:00+02:00", None),
+                          tweet("the bourgeoisie", "2016-09-10T11:00:00+02:00", None),
+                          tweet("Workers", "2016-09-10T09:30:00Z", None)];
+    fmt_queue::sort(&mut tweets);
+
+    assert_eq!(contents(&tweets), vec!["the bourgeoisie", "Workers", "Abolish"]);
+}
+
+#[test]
+fn sort_interleaved_threads() {
+    let mut tweets = vec![tweet("a1", "2016-09-10T15:00:00Z", Some("a")),
+                          tweet("b1", "2016-09-10T14:00:00Z", Some("b")),
+                          tweet("a2", "2016-09-10T12:00:00Z", Some("a")),
+                          tweet("solo", "2016-09-10T13:00:00Z", None),
+                          tweet("b2", "2016-09-10T11:00:00Z", Some("b"))];
+    fmt_queue::sort(&mut tweets);
+
+    assert_eq!(contents(&tweets), vec!["b1", "a1", "solo", "b2", "a2"]);
+}
+
+#[test]
+fn sort_idempotent() {
+    let mut tweets = vec![tweet("a1", "2016-09-10T15:00:00Z", Some("a")),
+                          tweet("solo", "2016-09-10T13:00:00Z", None),
+                          tweet("a2", "2016-09-10T12:00:00Z", Some("a"))];
+    fmt_queue::sort(&mut tweets);
+    let sorted = tweets.clone();
+    fmt_queue::sort(&mut tweets);
+
+    assert_eq!(tweets, sorted);
+}
+
+#[test]
+fn dedup_different_time() {
+    let mut tweets = vec![tweet("Abolish", "2016-09-10T12:00:00+02:00", None), tweet("Abolish", "2016-09-10T12:00:01+02:00", None)];
+
+    assert_eq!(fmt_queue::dedup(&mut tweets), 0);
+    assert_eq!(tweets.len(), 2);
+}
+
+#[test]
+fn remnants_kept() {
+    let mut first = tweet("a1", "2016-09-10T12:00:00Z", Some("a"));
+    first.in_reply_to = Some(420);
+    let mut solo = tweet("solo", "2016-09-10T12:00:00Z", None);
+    solo.in_reply_to = Some(421);
+    let mut posted = tweet("a2", "2016-09-10T12:00:00Z", Some("a"));
+    posted.in_reply_to = Some(422);
+    posted.id = Some(423);
+
+    let mut tweets = vec![first, solo, posted];
+    let before = tweets.clone();
+    assert_eq!(fmt_queue::clear_remnants(&mut tweets), 0);
+    assert_eq!(tweets, before);
+}
+
+
+fn tweet(content: &str, time: &str, thread: Option<&str>) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339(time).unwrap(),
+        content: content.to_string(),
+        thread: thread.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+fn contents(tweets: &[QueuedTweet]) -> Vec<&str> {
+    tweets.iter().map(|t| &t.content[..]).collect()
+}
@@ -0,0 +1,89 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::hooks::{self, Hook};
+use self::tweetr::ops::QueuedTweet;
+use self::tweetr::Outcome;
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn nonexistant() {
+    let td = hooks_dir("nonexistant");
+    assert_eq!(hooks::run(&td, Hook::PrePost, tweet()), Ok(tweet()));
+}
+
+#[cfg(unix)]
+#[test]
+fn accept() {
+    let td = hooks_dir("accept");
+    make_hook(&td, Hook::PrePost, "#!/bin/sh\ncat > /dev/null\n");
+
+    assert_eq!(hooks::run(&td, Hook::PrePost, tweet()), Ok(tweet()));
+}
+
+#[cfg(unix)]
+#[test]
+fn mutate() {
+    let td = hooks_dir("mutate");
+    make_hook(&td, Hook::PreQueue, "#!/bin/sh\nsed 's/Capitalism/Abolish capitalism/'\n");
+
+    let mut expected = tweet();
+    expected.content = "Abolish capitalism".to_string();
+    assert_eq!(hooks::run(&td, Hook::PreQueue, tweet()), Ok(expected));
+}
+
+#[cfg(unix)]
+#[test]
+fn veto() {
+    let td = hooks_dir("veto");
+    make_hook(&td, Hook::PostPost, "#!/bin/sh\necho \"Not on my watch\" >&2\nexit 1\n");
+
+    assert_eq!(hooks::run(&td, Hook::PostPost, tweet()),
+               Err(Outcome::HookVetoed {
+                   hook: "post-post",
+                   reason: "Not on my watch".to_string(),
+               }));
+}
+
+#[cfg(unix)]
+#[test]
+fn invalid_output() {
+    let td = hooks_dir("invalid_output");
+    make_hook(&td, Hook::PreQueue, "#!/bin/sh\necho '{\"author\": 1}'\n");
+
+    match hooks::run(&td, Hook::PreQueue, tweet()) {
+        Err(Outcome::HookVetoed { hook, .. }) => assert_eq!(hook, "pre-queue"),
+        other => panic!("{:?}", other),
+    }
+}
+
+
+fn hooks_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-hooks-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
+
+#[cfg(unix)]
+fn make_hook(hooks_dir: &PathBuf, hook: Hook, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    use std::io::Write;
+
+    let hf = hooks_dir.join(hook.name());
+    fs::File::create(&hf).unwrap().write_all(script.as_bytes()).unwrap();
+    fs::set_permissions(&hf, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn tweet() -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+        content: "Capitalism".to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,42 @@
+extern crate rustc_serialize;
+extern crate tweetr;
+
+use self::tweetr::ops::http_cache::{self, Entry};
+use self::rustc_serialize::json::Json;
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Write;
+
+
+#[test]
+fn key_per_query() {
+    let uri = "https://api.twitter.com/1.1/statuses/user_timeline.json";
+    assert_eq!(http_cache::key(uri, "count=200&user_id=481", "6908265-abcd"),
+               http_cache::key(uri, "count=200&user_id=481", "6908265-abcd"));
+    assert!(http_cache::key(uri, "count=200&user_id=481", "6908265-abcd") != http_cache::key(uri, "count=200&user_id=482", "6908265-abcd"));
+}
+
+#[test]
+fn entry_without_validators() {
+    assert_eq!(Entry::from_json(&Json::from_str("{\"etag\": null, \"last_modified\": null, \"body\": \"{}\"}").unwrap()), None);
+    assert_eq!(Entry::from_json(&Json::from_str("{\"etag\": \"\\\"6908265\\\"\"}").unwrap()), None);
+}
+
+#[test]
+fn write_replaces() {
+    let dir = temp_dir().join("tweetr-test").join("ops-http-cache-write-replaces");
+    let _ = fs::remove_dir_all(&dir);
+
+    http_cache::write(&dir, "timeline", &Entry::new(Some("\"1\""), None, "[1]").unwrap()).unwrap();
+    http_cache::write(&dir, "timeline", &Entry::new(Some("\"2\""), None, "[2, 1]").unwrap()).unwrap();
+    assert_eq!(http_cache::read(&dir, "timeline"), Entry::new(Some("\"2\""), None, "[2, 1]"));
+}
+
+#[test]
+fn read_corrupt() {
+    let dir = temp_dir().join("tweetr-test").join("ops-http-cache-read-corrupt");
+    fs::create_dir_all(&dir).unwrap();
+    File::create(dir.join("timeline")).unwrap().write_all(b"{\"etag\": \"\\\"1\\\"\", \"bo").unwrap();
+
+    assert_eq!(http_cache::read(&dir, "timeline"), None);
+}
@@ -0,0 +1,110 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::journal::{self, Action};
+use self::tweetr::ops::QueuedTweet;
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn diff_posted() {
+    let queued = tweet("Abolish", "2016-09-10T12:00:00+02:00");
+    let mut posted = queued.clone();
+    posted.id = Some(774541307568103424);
+    posted.time_posted = Some(posted.time);
+
+    assert_eq!(actions(&journal::diff(&[queued.clone()], &[posted.clone()], &[])), vec![Action::Posted]);
+    assert_eq!(actions(&journal::diff(&[queued.clone()], &[], &[posted.clone()])), vec![Action::Posted, Action::Archived]);
+    assert_eq!(actions(&journal::diff(&[posted.clone()], &[], &[posted.clone()])), vec![Action::Archived]);
+}
+
+#[test]
+fn diff_reworded() {
+    let before = tweet("Abolish", "2016-09-10T12:00:00+02:00");
+    let after = QueuedTweet { content: "Abolish the bourgeoisie".to_string(), ..before.clone() };
+
+    assert_eq!(actions(&journal::diff(&[before], &[after], &[])), vec![Action::Edited(vec!["content".to_string()])]);
+}
+
+#[test]
+fn diff_deleted() {
+    let tweets = vec![tweet("Abolish", "2016-09-10T12:00:00+02:00"), tweet("the bourgeoisie", "2016-09-11T12:00:00+02:00")];
+
+    let changes = journal::diff(&tweets, &tweets[1..], &[]);
+    assert_eq!(actions(&changes), vec![Action::Deleted]);
+    assert_eq!(changes[0].tweet.content, "Abolish");
+}
+
+#[test]
+fn lifecycle() {
+    let p = journal_path("lifecycle");
+    let queued = tweet("Abolish", "2016-09-10T12:00:00+02:00");
+    let rescheduled = QueuedTweet { time: DateTime::parse_from_rfc3339("2016-09-10T15:00:00+02:00").unwrap(), ..queued.clone() };
+    let mut posted = rescheduled.clone();
+    posted.id = Some(774541307568103424);
+    posted.time_posted = Some(posted.time);
+    let other = tweet("the bourgeoisie", "2016-09-11T12:00:00+02:00");
+
+    journal::record(&p, "queue-tweet", &journal::diff(&[], &[queued.clone()], &[]), now()).unwrap();
+    journal::record(&p, "quick-queue", &journal::diff(&[queued.clone()], &[queued.clone(), other.clone()], &[]), now()).unwrap();
+    journal::record(&p, "shift-queue", &journal::diff(&[queued.clone(), other.clone()], &[rescheduled.clone(), other.clone()], &[]), now()).unwrap();
+    journal::record(&p, "start-daemon", &journal::diff(&[rescheduled.clone(), other.clone()], &[other.clone()], &[posted.clone()]), now()).unwrap();
+
+    let records = journal::read(&p).unwrap();
+    assert_eq!(records.len(), 5);
+
+    let history = journal::history(&records, &records[0].entry);
+    assert_eq!(history.iter().map(|r| (r.actor.as_str(), r.action.clone())).collect::<Vec<_>>(),
+               vec![("queue-tweet", Action::Queued),
+                    ("shift-queue", Action::Edited(vec!["time".to_string()])),
+                    ("start-daemon", Action::Posted),
+                    ("start-daemon", Action::Archived)]);
+    assert_eq!(journal::history(&records, "774541307568103424"), history);
+    assert_eq!(history[3].tweet.id, Some(774541307568103424));
+
+    let other_history = journal::history(&records, &records[1].entry);
+    assert_eq!(other_history.len(), 1);
+    assert!(records[1].entry != records[0].entry);
+}
+
+#[test]
+fn read_nonexistant() {
+    assert_eq!(journal::read(&journal_path("read_nonexistant")), Ok(vec![]));
+}
+
+#[test]
+fn read_invalid() {
+    let p = journal_path("read_invalid");
+    File::create(&p).unwrap().write_all(b"{\"action\": \"queued\"}\n").unwrap();
+
+    assert!(journal::read(&p).is_err());
+}
+
+
+fn actions(changes: &[journal::Change]) -> Vec<Action> {
+    changes.iter().map(|c| c.action.clone()).collect()
+}
+
+fn now() -> DateTime<chrono::FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-09T10:00:00+02:00").unwrap()
+}
+
+fn journal_path(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-journal-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    journal::path(&td)
+}
+
+fn tweet(content: &str, time: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339(time).unwrap(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,118 @@
+extern crate tweetr;
+
+use self::tweetr::ops::l10n::{self, BUILT_IN, ENGLISH};
+use std::collections::BTreeMap;
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn built_in_complete() {
+    for &(locale, _) in BUILT_IN {
+        let catalog = l10n::read_catalog(&config_dir("built_in_complete"), locale).unwrap().unwrap();
+
+        for &(id, english) in ENGLISH {
+            let translated = &catalog[id];
+            assert_eq!(placeholders(translated), placeholders(english), "{} in {}", id, locale);
+        }
+    }
+}
+
+#[test]
+fn config_dir_overrides_built_in() {
+    let td = config_dir("config_dir_overrides_built_in");
+    File::create(td.join("locale").join("pl_PL.toml")).unwrap().write_all(b"prompt_pin = \"PIN\"\n").unwrap();
+
+    let mut expected = BTreeMap::new();
+    expected.insert("prompt_pin".to_string(), "PIN".to_string());
+    assert_eq!(l10n::read_catalog(&td, "pl_PL"), Ok(Some(expected)));
+}
+
+#[test]
+fn language_fallback() {
+    let td = config_dir("language_fallback");
+    File::create(td.join("locale").join("de.toml")).unwrap().write_all(b"prompt_pin = \"PIN von dieser Seite\"\n").unwrap();
+
+    assert_eq!(l10n::read_catalog(&td, "de_AT").unwrap().unwrap()["prompt_pin"], "PIN von dieser Seite");
+    assert_eq!(l10n::read_catalog(&td, "pl_PL").unwrap().unwrap()["prompt_pin"], "Wpisz PIN z tej strony");
+}
+
+#[test]
+fn unknown_message() {
+    let td = config_dir("unknown_message");
+    File::create(td.join("locale").join("de.toml")).unwrap().write_all(b"prompt_pin = \"PIN\"\nprompt_pim = \"PIM\"\n").unwrap();
+
+    assert_eq!(l10n::read_catalog(&td, "de"),
+               Err(Outcome::FileParsingFailed {
+                   desc: "message catalog",
+                   errors: vec!["error: 2:1: unknown message for the key `prompt_pim`".to_string()],
+               }));
+}
+
+#[test]
+fn non_string_message() {
+    let td = config_dir("non_string_message");
+    File::create(td.join("locale").join("de.toml")).unwrap().write_all(b"prompt_pin = 1234\n").unwrap();
+
+    assert_eq!(l10n::read_catalog(&td, "de"),
+               Err(Outcome::FileParsingFailed {
+                   desc: "message catalog",
+                   errors: vec!["error: 1:1: expected a value of type `string` for the key `prompt_pin`".to_string()],
+               }));
+}
+
+#[test]
+fn values_not_refilled() {
+    assert_eq!(l10n::tr("thread_blocked",
+                        &[("content", &"Fill in {thread}, {count} times"),
+                          ("count", &3),
+                          ("thread", &"launch")]),
+               "\"Fill in {thread}, {count} times\" failed permanently, holding back 3 tweet(s) of thread launch until requeued with \
+                `tweetr requeue-thread launch`.");
+}
+
+#[test]
+fn unknown_placeholders_kept() {
+    assert_eq!(l10n::tr("hook_vetoed", &[("hook", &"pre-post"), ("reason", &"{")]), "The pre-post hook rejected the tweet: {");
+    assert_eq!(l10n::tr("prompt_resolve {a} {", &[("a", &"b")]), "prompt_resolve b {");
+}
+
+#[test]
+fn english_by_default() {
+    let mut out = Vec::new();
+    Outcome::OverrideNoForce("$HOME/.tweetr/app.toml".to_string()).print_error(&mut out);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "File \"$HOME/.tweetr/app.toml\" was not overriden to prevent data loss.\nPass --force to override it.\n");
+}
+
+#[test]
+fn errors_translated() {
+    l10n::use_catalog(l10n::read_catalog(&config_dir("errors_translated"), "pl").unwrap().unwrap());
+
+    let mut out = Vec::new();
+    Outcome::Multiple(vec![Outcome::HookVetoed {
+                               hook: "pre-post",
+                               reason: "too spicy".to_string(),
+                           },
+                           Outcome::PreflightFailed(vec!["no".to_string()])])
+        .print_error(&mut out);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Wystąpiły błędy (2):\n  1. Hook pre-post odrzucił tweeta: too spicy\n  2. Nie można uruchomić:\n       no\n");
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-l10n-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(td.join("locale")).unwrap();
+    td
+}
+
+fn placeholders(message: &str) -> Vec<&str> {
+    let mut placeholders: Vec<_> = message.split('{').skip(1).map(|s| &s[..s.find('}').unwrap()]).collect();
+    placeholders.sort();
+    placeholders
+}
@@ -0,0 +1,77 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::latency::Latency;
+use self::chrono::{DateTime, Duration, FixedOffset};
+
+
+#[test]
+fn on_time_stays_zero() {
+    let mut latency = Latency::default();
+    for _ in 0..10 {
+        assert!(!latency.record(due(), due(), threshold()));
+    }
+
+    assert_eq!(latency.average, 0.0);
+}
+
+#[test]
+fn early_counts_as_on_time() {
+    let mut latency = Latency::default();
+    latency.record(due(), due() - Duration::minutes(10), threshold());
+
+    assert_eq!(latency.average, 0.0);
+}
+
+#[test]
+fn single_slow_doesnt_alert() {
+    let mut latency = Latency::default();
+    assert!(!latency.record(due(), due() + Duration::minutes(20), threshold()));
+    for _ in 0..5 {
+        assert!(!latency.record(due(), due() + Duration::seconds(1), threshold()));
+    }
+
+    assert!(latency.average < 120.0);
+}
+
+#[test]
+fn sustained_alerts_once() {
+    let mut latency = Latency::default();
+    let alerts: Vec<_> = (0..10).map(|_| latency.record(due(), due() + Duration::minutes(15), threshold())).collect();
+
+    assert_eq!(alerts.iter().filter(|&&a| a).count(), 1);
+    assert!(latency.exceeded);
+}
+
+#[test]
+fn realerts_after_recovering() {
+    let mut latency = Latency::default();
+    let mut alerts = 0;
+    for &late in &[15, 15, 15, 15, 0, 0, 0, 0, 0, 0, 0, 0, 15, 15, 15, 15] {
+        if latency.record(due(), due() + Duration::minutes(late), threshold()) {
+            alerts += 1;
+        }
+    }
+
+    assert_eq!(alerts, 2);
+}
+
+#[test]
+fn no_threshold_never_alerts() {
+    let mut latency = Latency::default();
+    for _ in 0..10 {
+        assert!(!latency.record(due(), due() + Duration::hours(1), None));
+    }
+
+    assert!(!latency.exceeded);
+    assert!(latency.average > 3000.0);
+}
+
+
+fn due() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()
+}
+
+fn threshold() -> Option<Duration> {
+    Some(Duration::minutes(5))
+}
@@ -0,0 +1,36 @@
+extern crate tweetr;
+
+use self::tweetr::ops::lint::Lint;
+
+
+#[test]
+fn no_rules() {
+    assert!(Lint::default().check("FREE #TWEETR for @ALL of @you, @NOW! #release").is_empty());
+}
+
+#[test]
+fn trailing_punctuation() {
+    let lint = Lint {
+        max_hashtags: Some(0),
+        max_mentions: Some(0),
+        ..Lint::default()
+    };
+    assert_eq!(lint.check("Thanks, @nabijaczleweli! (#tweetr)"),
+               vec!["1 hashtag, more than the 0 allowed".to_string(), "1 mention, more than the 0 allowed".to_string()]);
+    assert!(lint.check("# @ alone aren't anything").is_empty());
+}
+
+#[test]
+fn caps_skip_tags_mentions_and_links() {
+    let lint = Lint { max_caps: Some(3), ..Lint::default() };
+    assert!(lint.check("#NASA @NASA https://example.com/NASA OK (HTTP://EXAMPLE.COM)").is_empty());
+    assert_eq!(lint.check("NASA's rocket"), vec!["\"NASA\" is in capitals, longer than the 3 letters allowed".to_string()]);
+    assert_eq!(lint.check("Ünïcödé ÜNÏCÖDÉ"), vec!["\"ÜNÏCÖDÉ\" is in capitals, longer than the 3 letters allowed".to_string()]);
+}
+
+#[test]
+fn link_patterns() {
+    let lint = Lint { link_required_for: Some(vec!["launch".to_string(), "#v*.*".to_string()]), ..Lint::default() };
+    assert_eq!(lint.check("#Launch day! #launchday #v1.4 #v14"), vec!["#Launch without a link".to_string(), "#v1.4 without a link".to_string()]);
+    assert!(lint.check("#Launch day! https://github.com/nabijaczleweli/tweetr").is_empty());
+}
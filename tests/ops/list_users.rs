@@ -0,0 +1,101 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{list_users, User};
+use self::tweetr::Outcome;
+
+
+#[test]
+fn no_secrets() {
+    let user = User {
+        telegram_bot_token: Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string()),
+        telegram_chat_id: Some("@tweetr_announcements".to_string()),
+        discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+        ..user("nabijaczleweli", 481)
+    };
+
+    let mut out = Vec::new();
+    list_users::print_users(&mut out, &[(&user, Some(Outcome::NoError))], None);
+    let out = String::from_utf8(out).unwrap();
+
+    for secret in &["FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5", "AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw",
+                    "Hb8KDdv-DmkLz"] {
+        assert!(!out.contains(secret), "{} in {:?}", secret, out);
+    }
+    assert!(out.contains("Telegram chat @tweetr_announcements"), "{:?}", out);
+}
+
+#[test]
+fn invalid_tokens() {
+    let mut out = Vec::new();
+    list_users::print_users(&mut out,
+                            &[(&user("nabijaczleweli", 481), Some(Outcome::TwitterAPIError("Invalid or expired token. (code 89)".to_string())))],
+                            None);
+    assert!(String::from_utf8(out).unwrap().lines().last().unwrap().ends_with("  invalid: Twitter API error: Invalid or expired token. (code 89)"));
+}
+
+#[test]
+fn aligned() {
+    let users = [user("nabijaczleweli", 1246428073), User { app: Some("agency".to_string()), ..user("tweetr_test", 4169) }];
+
+    let mut out = Vec::new();
+    list_users::print_users(&mut out, &[(&users[0], None), (&users[1], None)], None);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "User                       App      Backends  Added    Tokens\n\
+                nabijaczleweli#1246428073  default  Twitter   unknown  not checked\n\
+                tweetr_test#4169           agency   Twitter   unknown  not checked\n");
+}
+
+#[test]
+fn long_errors_truncated() {
+    let mut out = Vec::new();
+    list_users::print_users(&mut out,
+                            &[(&user("nabijaczleweli", 481), Some(Outcome::TwitterAPIError("Invalid or expired token. (code 89)".to_string())))],
+                            Some(60));
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.lines().all(|l| l.chars().count() <= 60), "{:?}", out);
+    assert!(out.lines().last().unwrap().ends_with("  invalid: Tw…"), "{:?}", out);
+}
+
+#[test]
+fn unparseable_added() {
+    let mut out = Vec::new();
+    list_users::print_users(&mut out, &[(&User { added: Some("yesterday".to_string()), ..user("nabijaczleweli", 481) }, None)], None);
+    assert!(String::from_utf8(out).unwrap().contains("  yesterday  "));
+}
+
+#[test]
+fn suspended_posting_column() {
+    let users = [user("nabijaczleweli", 481), User { suspended: Some("2016-09-10T12:00:00+02:00".to_string()), ..user("tweetr_test", 4169) }];
+
+    let mut out = Vec::new();
+    list_users::print_users(&mut out, &[(&users[0], None), (&users[1], None)], None);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "User                App      Backends  Added    Posting                                                      Tokens\n\
+                nabijaczleweli#481  default  Twitter   unknown  active                                                       not checked\n\
+                tweetr_test#4169    default  Twitter   unknown  paused since 2016-09-10T12:00:00+02:00, suspended or locked  not checked\n");
+}
+
+
+fn user(name: &str, id: i64) -> User {
+    User {
+        name: name.to_string(),
+        id: id,
+        access_token_key: format!("{}-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", id),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
@@ -0,0 +1,193 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::media::{self, Upload};
+use self::tweetr::ops::QueuedTweet;
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use self::chrono::{DateTime, Duration, FixedOffset, Local};
+use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::Write;
+
+
+#[test]
+fn extension_from_url() {
+    assert_eq!(media::extension("https://example.com/chart.png", None), Some("png".to_string()));
+    assert_eq!(media::extension("https://example.com/charts/weekly.JPEG#top", Some("text/html")), Some("jpeg".to_string()));
+    assert_eq!(media::extension("http://example.com/clip.mp4?t=3", None), Some("mp4".to_string()));
+}
+
+#[test]
+fn extension_from_content_type() {
+    assert_eq!(media::extension("https://example.com/chart.php", Some("image/png; charset=binary")), Some("png".to_string()));
+    assert_eq!(media::extension("https://example.com/v1.2/render", Some("image/gif")), Some("gif".to_string()));
+    assert_eq!(media::extension("https://example.com/chart", None), None);
+    assert_eq!(media::extension("https://example.com/index.html", Some("text/html")), None);
+}
+
+#[test]
+fn file_name_by_contents() {
+    assert_eq!(media::file_name(b"abc", "gif"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad.gif");
+    assert_eq!(media::file_name(b"abc", "png")[..64], media::file_name(b"abc", "gif")[..64]);
+    assert!(media::file_name(b"abd", "gif") != media::file_name(b"abc", "gif"));
+}
+
+#[cfg(feature = "network")]
+#[test]
+fn localise_paths_untouched() {
+    let td = temp_dir().join("tweetr-test").join("ops-media-localise_paths_untouched");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    let mut tweet = tweet(vec!["/srv/charts/legend.png".to_string(), "chart.png".to_string(), "httpd.png".to_string()]);
+    let original = tweet.clone();
+    assert_eq!(media::localise(&mut tweet, &td), Ok(()));
+    assert_eq!(tweet, original);
+    assert!(!media::dir(&td).exists());
+}
+
+#[test]
+fn unused_no_media_dir() {
+    let td = temp_dir().join("tweetr-test").join("ops-media-unused_no_media_dir");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    assert!(media::unused(&td, &[], Some(0), now() + Duration::days(1)).is_empty());
+}
+
+#[test]
+fn unused_unposted_kept() {
+    let td = media_dir("unused_unposted_kept", &["chart.png"]);
+    let tweet = tweet(vec![media::dir(&td).join("chart.png").display().to_string()]);
+
+    assert!(media::unused(&td, &[tweet], Some(0), now() + Duration::days(3650)).is_empty());
+}
+
+#[test]
+fn unused_matched_by_file_name() {
+    let td = media_dir("unused_matched_by_file_name", &["chart.png", "legend.png"]);
+    let tweet = tweet(vec!["../elsewhere/.tweetr/media/chart.png".to_string(), "/srv/charts/legend.png.bak".to_string()]);
+
+    assert_eq!(media::unused(&td, &[tweet], None, now() + Duration::days(1)),
+               vec![(media::dir(&td).join("legend.png"), 4)]);
+}
+
+#[test]
+fn unused_posted_within_retention() {
+    let td = media_dir("unused_posted_within_retention", &["chart.png", "legend.png"]);
+    let now = now() + Duration::days(1);
+    let recent = QueuedTweet {
+        time_posted: Some(now - Duration::days(29)),
+        id: Some(774541307568103424),
+        ..tweet(vec![media::dir(&td).join("chart.png").display().to_string()])
+    };
+    let old = QueuedTweet {
+        time_posted: Some(now - Duration::days(31)),
+        id: Some(774541307568103425),
+        ..tweet(vec![media::dir(&td).join("chart.png").display().to_string(), media::dir(&td).join("legend.png").display().to_string()])
+    };
+
+    assert_eq!(media::unused(&td, &[recent.clone(), old.clone()], Some(30), now),
+               vec![(media::dir(&td).join("legend.png"), 4)]);
+    assert!(media::unused(&td, &[recent, old], None, now).is_empty());
+}
+
+#[test]
+fn unused_grace_period() {
+    let td = media_dir("unused_grace_period", &["chart.png"]);
+
+    assert!(media::unused(&td, &[], None, now()).is_empty());
+    assert!(media::unused(&td, &[], None, now() + Duration::seconds(media::GRACE_PERIOD - 60)).is_empty());
+    assert_eq!(media::unused(&td, &[], None, now() + Duration::seconds(media::GRACE_PERIOD + 60)),
+               vec![(media::dir(&td).join("chart.png"), 4)]);
+}
+
+#[test]
+fn chunked_by_type_and_size() {
+    assert!(!media::chunked("chart.gif", media::MAX_SIMPLE_UPLOAD_SIZE));
+    assert!(media::chunked("chart.gif", media::MAX_SIMPLE_UPLOAD_SIZE + 1));
+    assert!(media::chunked("/srv/videos/weekly.MOV", 0));
+    assert!(!media::chunked("/srv/videos/weekly", 1024));
+}
+
+#[test]
+fn uploads_written_whole() {
+    let td = temp_dir().join("tweetr-test").join("ops-media-uploads_written_whole");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let tf = media::uploads_path(&td);
+
+    let uploads = vec![upload("/srv/videos/weekly.mp4", 481), upload("/srv/videos/monthly.mp4", 482)];
+    media::write_uploads(&uploads, &tf).unwrap();
+    assert_eq!(media::read_uploads(&tf), Ok(uploads));
+    assert!(!td.join(".uploads.toml.new").exists());
+
+    media::write_uploads(&[], &tf).unwrap();
+    assert_eq!(media::read_uploads(&tf), Ok(vec![]));
+}
+
+#[test]
+fn uploads_invalid() {
+    let td = temp_dir().join("tweetr-test").join("ops-media-uploads_invalid");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let tf = media::uploads_path(&td);
+    File::create(&tf).unwrap().write_all(b"[[upload]]\npath = \"/srv/videos/weekly.mp4\"\n").unwrap();
+
+    match media::read_uploads(&tf) {
+        Err(Outcome::FileParsingFailed { desc, .. }) => assert_eq!(desc, "media uploads"),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn resumable_matching() {
+    let uploads = vec![upload("/srv/videos/weekly.mp4", 481),
+                       upload("/srv/videos/monthly.mp4", 481),
+                       upload("/srv/videos/monthly.mp4", 482),
+                       Upload { expires: 1473505200, ..upload("/srv/videos/yearly.mp4", 482) }];
+
+    assert_eq!(media::resumable(&uploads, "/srv/videos/monthly.mp4", 482, 52428800, 1473505200), Some(2));
+    assert_eq!(media::resumable(&uploads, "/srv/videos/yearly.mp4", 482, 52428800, 1473505199), Some(3));
+    assert_eq!(media::resumable(&uploads, "/srv/videos/yearly.mp4", 482, 52428800, 1473505200), None);
+    assert_eq!(media::resumable(&uploads, "videos/weekly.mp4", 481, 52428800, 1473505200), None);
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    let now = Local::now();
+    now.with_timezone(now.offset())
+}
+
+fn media_dir(name: &str, files: &[&str]) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-media-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(media::dir(&td)).unwrap();
+    for fname in files {
+        File::create(media::dir(&td).join(fname)).unwrap().write_all(b"\x89PNG").unwrap();
+    }
+    td
+}
+
+fn tweet(media: Vec<String>) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+        content: "This week's numbers".to_string(),
+        media: media,
+        ..Default::default()
+    }
+}
+
+fn upload(path: &str, user_id: i64) -> Upload {
+    Upload {
+        path: path.to_string(),
+        user_id: user_id,
+        size: 52428800,
+        media_id: 710511363345354753,
+        segments: 17,
+        expires: 1473591600,
+        finalised: false,
+    }
+}
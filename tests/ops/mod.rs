@@ -1,4 +1,58 @@
+mod hooks;
+mod archive;
+mod backend;
+mod calendar;
+mod config;
+mod daily_cap;
+mod diagnostics;
+#[cfg(feature = "network")]
+mod doctor;
+mod events;
+mod evergreen;
+mod embargo;
+mod export_posted;
+mod fmt_queue;
+mod journal;
+mod l10n;
+mod lint;
+mod list_users;
+mod media;
+mod network;
+mod http_cache;
+mod notify;
+#[cfg(feature = "network")]
+mod oauth;
+mod post_all;
+mod post_now;
+mod preflight;
+mod prune_posted;
+mod rotate_token;
+mod setup_state;
+mod reconcile;
+mod recovery;
+mod latency;
 mod queued_tweet;
 mod queue_tweet;
+mod quick_queue;
+mod release_announce;
+mod remove_user;
+mod report;
+mod results;
+mod retry;
+mod schedule;
+mod schema;
+mod settings;
+mod shift_queue;
+mod simulate;
+mod requeue_thread;
+mod start_daemon;
+mod template;
+#[cfg(feature = "testing")]
+mod testing;
 mod token;
+mod transform;
+mod transcript;
+mod tui;
+#[cfg(feature = "network")]
+mod update;
 mod user;
@@ -0,0 +1,234 @@
+extern crate tweetr;
+#[cfg(feature = "network")]
+extern crate hyper;
+
+use self::tweetr::ops::network::{self, Timeouts};
+use self::tweetr::ops::Config;
+use self::tweetr::Outcome;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use std::sync::Arc;
+use std::env::temp_dir;
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpListener};
+use std::str::FromStr;
+use std::fs::{self, File};
+use std::thread;
+
+
+#[test]
+fn configured() {
+    let td = temp_dir().join("tweetr-test").join("ops-network-configured");
+    fs::create_dir_all(&td).unwrap();
+    File::create(Config::path(&td)).unwrap().write_all(b"connect_timeout = 5\nread_timeout = 0\n").unwrap();
+
+    assert_eq!(Config::read(&Config::path(&td)).unwrap().timeouts(),
+               Timeouts {
+                   connect: Some(Duration::from_secs(5)),
+                   read: None,
+                   write: Some(Duration::from_secs(30)),
+               });
+}
+
+#[test]
+fn overridden_by_flags() {
+    let config = Config { connect_timeout: Some(5), ..Config::default() };
+
+    assert_eq!(config.timeouts().overridden(Some(0), None, Some(1)),
+               Timeouts {
+                   connect: None,
+                   read: Some(Duration::from_secs(30)),
+                   write: Some(Duration::from_secs(1)),
+               });
+}
+
+#[test]
+fn run_carries_timeouts() {
+    let timeouts = Timeouts { write: None, ..Timeouts::default() };
+    network::use_timeouts(timeouts);
+
+    assert_eq!(network::run("checking", || Ok(network::timeouts())), Ok(timeouts));
+}
+
+#[test]
+fn run_error() {
+    assert_eq!(network::run("failing", || Err::<(), _>(Outcome::TwitterAPIError("Status is a duplicate.".to_string()))),
+               Err(Outcome::TwitterAPIError("Status is a duplicate.".to_string())));
+}
+
+#[test]
+fn run_timeout() {
+    network::use_timeouts(Timeouts {
+        connect: Some(Duration::from_millis(5)),
+        read: Some(Duration::from_millis(5)),
+        write: Some(Duration::from_millis(5)),
+    });
+
+    assert_eq!(network::run("posting to Telegram chat 1234", || Ok(thread::sleep(Duration::from_millis(500)))),
+               Err(Outcome::NetworkTimeout { desc: "posting to Telegram chat 1234".to_string() }));
+}
+
+#[test]
+fn configured_bind_address() {
+    let td = temp_dir().join("tweetr-test").join("ops-network-configured_bind_address");
+    fs::create_dir_all(&td).unwrap();
+    File::create(Config::path(&td)).unwrap().write_all(b"bind_address = \"::1\"\n").unwrap();
+
+    assert_eq!(Config::read(&Config::path(&td)).unwrap().bind_address(), Some(IpAddr::from_str("::1").unwrap()));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn bind_address_interface() {
+    assert_eq!(network::resolve_bind_address("lo"), Ok(IpAddr::from_str("127.0.0.1").unwrap()));
+}
+
+#[test]
+fn run_carries_bind_address() {
+    let addr = Some(IpAddr::from_str("192.0.2.7").unwrap());
+    network::use_bind_address(addr);
+
+    assert_eq!(network::run("checking", || Ok(network::bind_address())), Ok(addr));
+}
+
+// All of 127.0.0.0/8 is the loopback on Linux, so connecting from another address than the default 127.0.0.1 is observable
+#[cfg(all(target_os = "linux", feature = "network"))]
+#[test]
+fn client_binds() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (mut stream, peer) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf).unwrap();
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        peer.ip()
+    });
+
+    network::use_bind_address(Some(IpAddr::from_str("127.0.0.2").unwrap()));
+    let resp = network::client().get(&format!("http://127.0.0.1:{}/", port)).send().unwrap();
+    network::use_bind_address(None);
+
+    assert!(resp.status.is_success());
+    assert_eq!(server.join().unwrap(), IpAddr::from_str("127.0.0.2").unwrap());
+}
+
+#[cfg(all(target_os = "linux", feature = "network"))]
+#[test]
+fn client_bound_other_version() {
+    network::use_bind_address(Some(IpAddr::from_str("::1").unwrap()));
+    let resp = network::client().get("http://127.0.0.1:1/").send();
+    network::use_bind_address(None);
+
+    assert_eq!(resp.unwrap_err().to_string(), "127.0.0.1 has no address reachable from ::1");
+}
+
+// The server never responds, so the request gives up reading, without being carried on anywhere afterwards
+#[cfg(feature = "network")]
+#[test]
+fn client_read_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf).unwrap();
+        let _ = stream.read(&mut buf);
+    });
+
+    network::use_timeouts(Timeouts { read: Some(Duration::from_millis(100)), ..Timeouts::default() });
+    let resp = network::request_blocking(|| {
+        network::client()
+            .post(&format!("http://127.0.0.1:{}/", port))
+            .body("status=Abolish+the+bourgeoisie")
+            .send()
+            .map_err(|e| network::request_error("posting to Telegram chat 1234", e, Outcome::TwitterAPIError))
+    });
+    network::use_timeouts(Timeouts::default());
+
+    assert_eq!(resp.map(|_| ()), Err(Outcome::NetworkTimeout { desc: "posting to Telegram chat 1234".to_string() }));
+    server.join().unwrap();
+}
+
+#[cfg(feature = "network")]
+#[test]
+fn request_error_other() {
+    assert_eq!(network::request_error("posting to Discord webhook",
+                                      hyper::Error::TooLarge,
+                                      |e| Outcome::BackendError { backend: "Discord".to_string(), error: e }),
+               Outcome::BackendError { backend: "Discord".to_string(), error: hyper::Error::TooLarge.to_string() });
+}
+
+#[test]
+fn exit_value() {
+    assert_eq!(Outcome::NetworkTimeout { desc: "posting".to_string() }.exit_value(), 9);
+}
+
+#[test]
+fn request_retries_rate_limited() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    assert_eq!(network::request("requesting a request token", move || {
+                   counter.fetch_add(1, Ordering::SeqCst);
+                   Err::<(), _>(Outcome::RateLimited {
+                       desc: "requesting a request token".to_string(),
+                       reset: Some(0),
+                   })
+               }),
+               Err(Outcome::RateLimited {
+                   desc: "requesting a request token".to_string(),
+                   reset: Some(0),
+               }));
+    assert_eq!(attempts.load(Ordering::SeqCst), network::RATE_LIMIT_RETRIES as usize + 1);
+}
+
+#[test]
+fn request_blocking_retries_rate_limited() {
+    let attempts = AtomicUsize::new(0);
+    assert_eq!(network::request_blocking(|| {
+                   attempts.fetch_add(1, Ordering::SeqCst);
+                   Err::<(), _>(Outcome::RateLimited {
+                       desc: "posting to Discord webhook".to_string(),
+                       reset: Some(0),
+                   })
+               }),
+               Err(Outcome::RateLimited {
+                   desc: "posting to Discord webhook".to_string(),
+                   reset: Some(0),
+               }));
+    assert_eq!(attempts.load(Ordering::SeqCst), network::RATE_LIMIT_RETRIES as usize + 1);
+}
+
+#[test]
+fn request_rate_limited_unknown_reset() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    assert_eq!(network::request("posting to Telegram chat 1234", move || {
+                   counter.fetch_add(1, Ordering::SeqCst);
+                   Err::<(), _>(Outcome::RateLimited {
+                       desc: "posting to Telegram chat 1234".to_string(),
+                       reset: None,
+                   })
+               }),
+               Err(Outcome::RateLimited {
+                   desc: "posting to Telegram chat 1234".to_string(),
+                   reset: None,
+               }));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn rate_limited_message() {
+    let mut out = Vec::new();
+    Outcome::RateLimited {
+            desc: "requesting an access token".to_string(),
+            reset: None,
+        }
+        .print_error(&mut out);
+    assert_eq!(String::from_utf8(out).unwrap(), "Rate limited requesting an access token, try again later.\n");
+}
+
+#[test]
+fn rate_limited_exit_value() {
+    assert_eq!(Outcome::RateLimited { desc: "posting".to_string(), reset: None }.exit_value(), 11);
+}
@@ -0,0 +1,44 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::notify::{Notification, Notifier};
+use self::tweetr::ops::QueuedTweet;
+use self::chrono::DateTime;
+use self::tweetr::Outcome;
+
+
+#[test]
+fn failed_rate_limited() {
+    assert_eq!(Notification::failed(&tweet(),
+                                    &Outcome::RateLimited {
+                                        desc: "posting to Twitter as @nabijaczleweli".to_string(),
+                                        reset: None,
+                                    })
+                   .title,
+               "Rate limited posting tweet by nabijaczleweli");
+}
+
+#[test]
+fn failed_multiline_error_trimmed() {
+    let body = Notification::failed(&tweet(), &Outcome::PreflightFailed(vec!["one".to_string(), "two".to_string()])).body;
+    assert!(body.starts_with("\"Abolish the bourgeoisie\": "));
+    assert!(!body.ends_with('\n'));
+}
+
+#[test]
+fn disabled_never_shows() {
+    let mut notifier = Notifier::new(false);
+    for _ in 0..3 {
+        assert_eq!(notifier.notify(&Notification::posted(&tweet())), Ok(()));
+    }
+}
+
+
+fn tweet() -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
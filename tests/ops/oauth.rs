@@ -0,0 +1,104 @@
+extern crate tweetr;
+extern crate egg_mode;
+extern crate hyper;
+
+use self::tweetr::ops::oauth;
+use self::egg_mode::Token;
+use self::hyper::header::Headers;
+use std::collections::BTreeMap;
+
+
+// The examples from RFC 5849, section 1.2, in order
+
+#[test]
+fn rfc5849_temporary_credentials() {
+    let mut oauth_params = BTreeMap::new();
+    oauth_params.insert("oauth_consumer_key", "dpf43f3p2l4k3l03".to_string());
+    oauth_params.insert("oauth_signature_method", "HMAC-SHA1".to_string());
+    oauth_params.insert("oauth_timestamp", "137131200".to_string());
+    oauth_params.insert("oauth_nonce", "wIjqoS".to_string());
+    oauth_params.insert("oauth_callback", "http://printer.example.com/ready".to_string());
+
+    assert_eq!(oauth::hmac_sha1(&oauth::signing_key("kd94hf93k423kf44", ""),
+                                &oauth::signature_base_string("POST", "https://photos.example.net/initiate", &[], &oauth_params)),
+               "74KNZJeDHnMBp0EMJ9ZHt/XKycU=");
+}
+
+#[test]
+fn rfc5849_token_credentials() {
+    let mut oauth_params = BTreeMap::new();
+    oauth_params.insert("oauth_consumer_key", "dpf43f3p2l4k3l03".to_string());
+    oauth_params.insert("oauth_token", "hh5s93j4hdidpola".to_string());
+    oauth_params.insert("oauth_signature_method", "HMAC-SHA1".to_string());
+    oauth_params.insert("oauth_timestamp", "137131201".to_string());
+    oauth_params.insert("oauth_nonce", "walatlh".to_string());
+    oauth_params.insert("oauth_verifier", "hfdp7dh39dks9884".to_string());
+
+    assert_eq!(oauth::sign("POST",
+                           "https://photos.example.net/token",
+                           &[],
+                           &oauth_params,
+                           &Token::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44"),
+                           &Token::new("hh5s93j4hdidpola", "hdhd0244k9j7ao03")),
+               "gKgrFCywp7rO0OXSjdot/IHF7IU=");
+}
+
+#[test]
+fn rfc5849_resource() {
+    let app = Token::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44");
+    let access = Token::new("nnch734d00sl2jdk", "pfkkdhi9sl3r4s00");
+    let mut oauth_params = oauth::protocol_params(&app, &access, "137131202", "chapoH");
+    oauth_params.remove("oauth_version");
+
+    let params = [("file", "vacation.jpg"), ("size", "original")];
+    assert_eq!(oauth::signature_base_string("GET", "http://photos.example.net/photos", &params, &oauth_params),
+               "GET&http%3A%2F%2Fphotos.example.net%2Fphotos&file%3Dvacation.jpg%26oauth_consumer_key%3Ddpf43f3p2l4k3l03%26\
+                oauth_nonce%3DchapoH%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D137131202%26\
+                oauth_token%3Dnnch734d00sl2jdk%26size%3Doriginal");
+
+    let signature = oauth::sign("GET", "http://photos.example.net/photos", &params, &oauth_params, &app, &access);
+    assert_eq!(signature, "MdpQcU8iPSUjWoN/UDMsK2sui9I=");
+
+    oauth_params.insert("oauth_signature", signature);
+    assert_eq!(oauth::authorization_header(&oauth_params),
+               "OAuth oauth_consumer_key=\"dpf43f3p2l4k3l03\", oauth_nonce=\"chapoH\", oauth_signature=\"MdpQcU8iPSUjWoN%2FUDMsK2sui9I%3D\", \
+                oauth_signature_method=\"HMAC-SHA1\", oauth_timestamp=\"137131202\", oauth_token=\"nnch734d00sl2jdk\"");
+}
+
+#[test]
+fn method_case_insensitive() {
+    let oauth_params = oauth::protocol_params(&Token::new("a", "b"), &Token::new("c", "d"), "137131202", "chapoH");
+
+    assert_eq!(oauth::signature_base_string("get", "https://api.twitter.com/1.1/account/settings.json", &[], &oauth_params),
+               oauth::signature_base_string("GET", "https://api.twitter.com/1.1/account/settings.json", &[], &oauth_params));
+}
+
+#[test]
+fn authorization_fresh() {
+    let app = Token::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44");
+    let access = Token::new("nnch734d00sl2jdk", "pfkkdhi9sl3r4s00");
+
+    assert!(oauth::authorization("GET", "http://photos.example.net/photos", &[], &app, &access) !=
+            oauth::authorization("GET", "http://photos.example.net/photos", &[], &app, &access));
+}
+
+#[test]
+fn clock_skew_unparseable_date() {
+    let mut headers = Headers::new();
+    headers.set_raw("Date", vec![b"yesterday".to_vec()]);
+    assert_eq!(oauth::clock_skew(&headers, 1473505200), None);
+}
+
+#[test]
+fn clock_skew_padded_date() {
+    let mut headers = Headers::new();
+    headers.set_raw("Date", vec![b"  Sat, 10 Sep 2016 11:00:00 GMT ".to_vec()]);
+    assert_eq!(oauth::clock_skew(&headers, 1473505200), Some(0));
+}
+
+#[test]
+fn describe_clock_correction_threshold() {
+    assert!(oauth::describe_clock_correction(oauth::MAX_CLOCK_SKEW).contains("in sync"));
+    assert!(oauth::describe_clock_correction(-oauth::MAX_CLOCK_SKEW).contains("in sync"));
+    assert!(oauth::describe_clock_correction(oauth::MAX_CLOCK_SKEW + 1).starts_with("the system clock is 31 seconds behind the Twitter API's"));
+}
@@ -0,0 +1,108 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{post_all, Anchor, QueuedTweet};
+use self::chrono::{DateTime, Duration, FixedOffset};
+use self::tweetr::util::Confirmation;
+use std::io::BufReader;
+
+
+#[test]
+fn skips_posted_and_paused() {
+    let tweets = vec![QueuedTweet { id: Some(774560457755590656), ..tweet("Capitalism", 0) },
+                      QueuedTweet { paused: true, ..tweet("Abolish the bourgeoisie", 1) },
+                      tweet("Workers of the world, unite!", 2)];
+    assert_eq!(post_all::order(&tweets, time(2)), vec![2]);
+}
+
+#[test]
+fn skips_blocked() {
+    let tweets = vec![QueuedTweet { blocked: Some("Twitter API error: Status is a duplicate. (code 187)".to_string()), ..tweet("Capitalism", 0) },
+                      tweet("Abolish the bourgeoisie", 1)];
+    assert_eq!(post_all::order(&tweets, time(1)), vec![1]);
+}
+
+#[test]
+fn up_to_inclusive() {
+    let tweets = vec![tweet("Capitalism", 1), tweet("Abolish the bourgeoisie", 2)];
+    assert_eq!(post_all::order(&tweets, time(1)), vec![0]);
+    assert_eq!(post_all::order(&tweets, time(0)), Vec::<usize>::new());
+}
+
+#[test]
+fn ties_in_queue_order() {
+    let tweets = vec![tweet("Capitalism", 1), tweet("Abolish the bourgeoisie", 0), tweet("Workers of the world, unite!", 1)];
+    assert_eq!(post_all::order(&tweets, time(1)), vec![1, 0, 2]);
+}
+
+#[test]
+fn thread_order() {
+    let tweets = vec![QueuedTweet { thread: Some("manifesto".to_string()), ..tweet("A spectre is haunting Europe", 2) },
+                      QueuedTweet { thread: Some("manifesto".to_string()), ..tweet("the spectre of communism", 0) },
+                      tweet("Capitalism", 1)];
+    assert_eq!(post_all::order(&tweets, time(2)), vec![2, 0, 1]);
+}
+
+#[test]
+fn thread_waits_for_unposted() {
+    let tweets = vec![QueuedTweet { thread: Some("manifesto".to_string()), ..tweet("A spectre is haunting Europe", 3) },
+                      QueuedTweet { thread: Some("manifesto".to_string()), ..tweet("the spectre of communism", 0) }];
+    assert_eq!(post_all::order(&tweets, time(2)), Vec::<usize>::new());
+}
+
+#[test]
+fn thread_after_posted() {
+    let tweets = vec![QueuedTweet { thread: Some("manifesto".to_string()), id: Some(774560457755590656), ..tweet("A spectre is haunting Europe", 3) },
+                      QueuedTweet { thread: Some("manifesto".to_string()), ..tweet("the spectre of communism", 0) }];
+    assert_eq!(post_all::order(&tweets, time(2)), vec![1]);
+}
+
+#[test]
+fn anchor_order() {
+    let tweets = vec![QueuedTweet { after: Some(anchor("launch")), ..tweet("In case you missed it", 1) },
+                      QueuedTweet { name: Some("launch".to_string()), ..tweet("tweetr v1.4.0 is out!", 1) }];
+    assert_eq!(post_all::order(&tweets, time(1)), vec![1, 0]);
+}
+
+#[test]
+fn anchor_waits_for_unposted() {
+    let tweets = vec![QueuedTweet { after: Some(anchor("launch")), ..tweet("In case you missed it", 1) },
+                      QueuedTweet { name: Some("launch".to_string()), paused: true, ..tweet("tweetr v1.4.0 is out!", 1) }];
+    assert_eq!(post_all::order(&tweets, time(1)), Vec::<usize>::new());
+}
+
+#[test]
+fn confirm() {
+    assert!(post_all::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"Y\n" as &[u8]), &mut Vec::new()));
+    assert!(!post_all::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"n\n" as &[u8]), &mut Vec::new()));
+    assert!(!post_all::prompt_confirm(Confirmation::Ask, &mut BufReader::new(b"" as &[u8]), &mut Vec::new()));
+    assert!(!post_all::prompt_confirm(Confirmation::Refuse, &mut BufReader::new(b"Y\n" as &[u8]), &mut Vec::new()));
+}
+
+#[test]
+fn summary() {
+    let mut out = Vec::new();
+    post_all::print_summary(&mut out, 1, 1, false);
+    assert_eq!(String::from_utf8(out).unwrap(), "Posted 1 of 1 tweet.\n");
+}
+
+
+fn time(days: i64) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap() + Duration::days(days)
+}
+
+fn anchor(name: &str) -> Anchor {
+    Anchor {
+        name: name.to_string(),
+        delay: 0,
+    }
+}
+
+fn tweet(content: &str, days: i64) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time(days),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,78 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::chrono::{DateTime, Duration, FixedOffset};
+use self::tweetr::ops::{QueuedTweet, Anchor, post_now};
+
+
+#[test]
+fn overdue_pushed_back() {
+    let now = now();
+    let mut tweets = vec![scheduled("brandacct", -30), scheduled("brandacct", 30)];
+
+    assert_eq!(post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10)),
+               vec![(0, now - Duration::minutes(30))]);
+    assert_eq!(tweets[0].time, now + Duration::minutes(10));
+    assert_eq!(tweets[1].time, now + Duration::minutes(30));
+}
+
+#[test]
+fn cascade_keeps_order() {
+    let now = now();
+    let mut tweets = vec![scheduled("brandacct", 12), scheduled("brandacct", 1), scheduled("brandacct", 25)];
+
+    post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10));
+    assert_eq!(tweets[1].time, now + Duration::minutes(10));
+    assert_eq!(tweets[0].time, now + Duration::minutes(20));
+    assert_eq!(tweets[2].time, now + Duration::minutes(30));
+}
+
+#[test]
+fn others_untouched() {
+    let now = now();
+    let mut tweets = vec![scheduled("other", 1),
+                          QueuedTweet { paused: true, ..scheduled("brandacct", 2) },
+                          QueuedTweet { blocked: Some("Twitter API error: Status is a duplicate. (code 187)".to_string()), ..scheduled("brandacct", 3) },
+                          QueuedTweet { id: Some(6908265), ..scheduled("brandacct", -1) }];
+    let original = tweets.clone();
+
+    assert_eq!(post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10)), vec![]);
+    assert_eq!(tweets, original);
+}
+
+#[test]
+fn dependents_moved_along() {
+    let now = now();
+    let mut tweets = vec![QueuedTweet { name: Some("launch".to_string()), ..scheduled("brandacct", 5) },
+                          QueuedTweet {
+                              after: Some(Anchor {
+                                  name: "launch".to_string(),
+                                  delay: 60 * 60,
+                              }),
+                              ..scheduled("other", 65)
+                          }];
+
+    assert_eq!(post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::minutes(10)),
+               vec![(0, now + Duration::minutes(5)), (1, now + Duration::minutes(65))]);
+    assert_eq!(tweets[1].time, now + Duration::minutes(70));
+}
+
+#[test]
+fn zero_interval() {
+    let now = now();
+    let mut tweets = vec![scheduled("brandacct", 0)];
+
+    post_now::preempt(&mut tweets, &post_now::breaking("brandacct", "Breaking", now), Duration::zero());
+    assert_eq!(tweets[0].time, now + Duration::seconds(1));
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap()
+}
+
+fn scheduled(author: &str, minutes: i64) -> QueuedTweet {
+    let mut tweet = post_now::breaking(author, &format!("Scheduled {}", minutes), now() + Duration::minutes(minutes));
+    tweet.settings.jitter = None;
+    tweet
+}
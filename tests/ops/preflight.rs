@@ -0,0 +1,70 @@
+extern crate tweetr;
+
+use self::tweetr::ops::preflight::{self, Access};
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn nonexistant() {
+    let td = config_dir("nonexistant");
+    assert_eq!(preflight::run(&td, &[("users.toml", Access::Read), ("tweets.toml", Access::ReadWrite)], true), Ok(vec![]));
+}
+
+#[cfg(unix)]
+#[test]
+fn secure_users() {
+    let td = config_dir("secure_users");
+    make_users(&td, 0o600);
+
+    assert_eq!(preflight::run(&td, &[("users.toml", Access::Read)], true), Ok(vec![]));
+}
+
+#[cfg(unix)]
+#[test]
+fn insecure_users_warn() {
+    let td = config_dir("insecure_users_warn");
+    make_users(&td, 0o644);
+
+    assert_eq!(preflight::run(&td, &[("users.toml", Access::Read)], false),
+               Ok(vec![format!("{} is readable by everyone (mode 644), exposing the users' access tokens", td.join("users.toml").display())]));
+}
+
+#[cfg(unix)]
+#[test]
+fn insecure_users_refuse() {
+    let td = config_dir("insecure_users_refuse");
+    make_users(&td, 0o604);
+
+    assert_eq!(preflight::run(&td, &[("users.toml", Access::Read)], true),
+               Err(Outcome::PreflightFailed(vec![format!("{} is readable by everyone (mode 604), exposing the users' access tokens",
+                                                         td.join("users.toml").display())])));
+}
+
+#[cfg(unix)]
+#[test]
+fn insecure_users_unchecked() {
+    let td = config_dir("insecure_users_unchecked");
+    make_users(&td, 0o644);
+
+    assert_eq!(preflight::run(&td, &[("tweets.toml", Access::ReadWrite)], true), Ok(vec![]));
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-preflight-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
+
+#[cfg(unix)]
+fn make_users(td: &PathBuf, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = td.join("users.toml");
+    fs::File::create(&path).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+}
@@ -0,0 +1,167 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{prune_posted, QueuedTweet, User};
+use self::tweetr::util::encoding::Encoding;
+use std::collections::BTreeSet;
+use self::chrono::{DateTime, Duration, FixedOffset};
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Write;
+use self::tweetr::Outcome;
+
+
+#[test]
+fn candidates_oldest_first() {
+    let tweets = vec![tweet("nabijaczleweli", 3, 774560460511248384),
+                      tweet("nabijaczleweli", 1, 774560457755590656),
+                      tweet("nabijaczleweli", 20, 774560463040413696)];
+
+    assert_eq!(prune_posted::candidates(&tweets, &[user("nabijaczleweli")], &BTreeSet::new(), at(10), None, None),
+               vec![1, 0]);
+}
+
+#[test]
+fn candidates_skip_sinks_and_unposted() {
+    let tweets = vec![tweet("motd", 1, 1473598801000),
+                      QueuedTweet { id: None, ..tweet("nabijaczleweli", 1, 0) },
+                      QueuedTweet { time_posted: None, ..tweet("nabijaczleweli", 1, 774560457755590656) }];
+
+    assert_eq!(prune_posted::candidates(&tweets, &[user("nabijaczleweli")], &BTreeSet::new(), at(10), None, None),
+               Vec::<usize>::new());
+}
+
+#[test]
+fn candidates_account() {
+    let tweets = vec![tweet("nabijaczleweli", 1, 774560457755590656), tweet("tweetr_test", 2, 774560460511248384)];
+    let users = [user("nabijaczleweli"), user("tweetr_test")];
+
+    assert_eq!(prune_posted::candidates(&tweets, &users, &BTreeSet::new(), at(10), Some("tweetr_test"), None), vec![1]);
+    assert_eq!(prune_posted::candidates(&tweets, &users, &BTreeSet::new(), at(10), Some("motd"), None), Vec::<usize>::new());
+}
+
+#[test]
+fn candidates_min_engagement() {
+    let tweets = vec![tweet("nabijaczleweli", 1, 774560457755590656),
+                      tweet("nabijaczleweli", 2, 774560460511248384),
+                      tweet("nabijaczleweli", 3, 774560463040413696)];
+    let metrics = vec![(774560457755590656, 3), (774560460511248384, 30)].into_iter().collect();
+
+    assert_eq!(prune_posted::candidates(&tweets, &[user("nabijaczleweli")], &BTreeSet::new(), at(10), None, Some((&metrics, 10))),
+               vec![0]);
+}
+
+#[test]
+fn pruned_roundtrip() {
+    let td = temp_dir().join("tweetr-test").join("ops-prune_posted-pruned_roundtrip");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let p = prune_posted::pruned_path(&td);
+
+    let ids: BTreeSet<_> = vec![774560457755590656, 774560460511248384].into_iter().collect();
+    prune_posted::write_pruned(&ids, &p).unwrap();
+    assert_eq!(prune_posted::read_pruned(&p), Ok(ids));
+    assert!(!td.join(".pruned.toml.new").exists());
+}
+
+#[test]
+fn read_metrics_quoted() {
+    let td = temp_dir().join("tweetr-test").join("ops-prune_posted-read_metrics_quoted");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    File::create(td.join("metrics.csv"))
+        .unwrap()
+        .write_all(b"id,account,text,Engagements\r\n774560457755590656,nabijaczleweli,\"Abolish, \"\"the\"\"\r\nbourgeoisie\",12\r\n\r\n")
+        .unwrap();
+
+    assert_eq!(prune_posted::read_metrics(&td.join("metrics.csv"), None),
+               Ok(vec![(774560457755590656, 12)].into_iter().collect()));
+}
+
+#[test]
+fn read_metrics_utf16() {
+    let td = temp_dir().join("tweetr-test").join("ops-prune_posted-read_metrics_utf16");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let csv: Vec<u8> = "\u{FEFF}\"Tweet id\",\"Tweet text\",\"engagements\"\n\"774560457755590656\",\"Zażółć\",\"3.0\"\n"
+        .encode_utf16()
+        .flat_map(|u| vec![u as u8, (u >> 8) as u8])
+        .collect();
+    File::create(td.join("metrics.csv")).unwrap().write_all(&csv).unwrap();
+
+    assert_eq!(prune_posted::read_metrics(&td.join("metrics.csv"), None),
+               Ok(vec![(774560457755590656, 3)].into_iter().collect()));
+}
+
+#[test]
+fn read_metrics_invalid_sequences() {
+    let td = temp_dir().join("tweetr-test").join("ops-prune_posted-read_metrics_invalid_sequences");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    File::create(td.join("metrics.csv"))
+        .unwrap()
+        .write_all(b"id,text,engagements
+774560457755590656,\"Abolish\nthe\xE9\",12\n774560460511248384,\xC3\x28,3\n")
+        .unwrap();
+
+    assert_eq!(prune_posted::read_metrics(&td.join("metrics.csv"), Some(Encoding::Utf8)),
+               Err(Outcome::FileParsingFailed {
+                   desc: "metrics",
+                   errors: vec!["error: row 2: invalid UTF-8 sequence 0xE9 at byte offset 51".to_string(),
+                                "error: row 3: invalid UTF-8 sequence 0xC3 at byte offset 76".to_string()],
+               }));
+}
+
+#[test]
+fn read_metrics_invalid() {
+    let td = temp_dir().join("tweetr-test").join("ops-prune_posted-read_metrics_invalid");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    File::create(td.join("metrics.csv")).unwrap().write_all(b"Tweet id,engagements\nabc,12\n").unwrap();
+
+    assert_eq!(prune_posted::read_metrics(&td.join("metrics.csv"), None),
+               Err(Outcome::FileParsingFailed {
+                   desc: "metrics",
+                   errors: vec!["error: row 2: \"abc\" is not a valid tweet ID".to_string()],
+               }));
+}
+
+
+fn at(day: i64) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-01T12:00:00+02:00").unwrap() + Duration::days(day)
+}
+
+fn tweet(author: &str, day: i64, id: i64) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: at(day),
+        content: "Abolish the bourgeoisie".to_string(),
+        time_posted: Some(at(day) + Duration::seconds(5)),
+        id: Some(id),
+        ..Default::default()
+    }
+}
+
+fn user(name: &str) -> User {
+    User {
+        name: name.to_string(),
+        id: 481,
+        access_token_key: "key".to_string(),
+        access_token_secret: "secret".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
@@ -3,7 +3,7 @@ mod get_tweet {
     extern crate chrono;
 
     use self::tweetr::ops::{QueuedTweet, queue_tweet};
-    use self::chrono::DateTime;
+    use self::chrono::{DateTime, Local};
     use std::io::BufReader;
 
 
@@ -11,14 +11,14 @@ mod get_tweet {
     fn rfc2822() {
         assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
                                                                 Test tweet\n\
-                                                                Fri, 9 Sep 2016 00:33:30 +0200\n" as &[u8]),
+                                                                Fri, 9 Sep 2016 00:33:30 +0200\n\
+                                                                y\n" as &[u8]),
                                           &mut Vec::new()),
                    Some(QueuedTweet {
                        author: "tweetr_test".to_string(),
                        time: DateTime::parse_from_rfc2822("Fri, 9 Sep 2016 00:33:30 +0200").unwrap(),
                        content: "Test tweet".to_string(),
-                       time_posted: None,
-                       id: None,
+                       ..Default::default()
                    }));
     }
 
@@ -26,4 +26,262 @@ mod get_tweet {
     fn empty() {
         assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"" as &[u8]), &mut Vec::new()), None);
     }
+
+    #[test]
+    fn invalid_time_explained() {
+        let mut out = Vec::new();
+        let tweet = queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
+                                                                 Test tweet\n\
+                                                                 in 5 fortnights\n\
+                                                                 Fri, 9 Sep 2016 00:33:30 +0200\n\
+                                                                 y\n" as &[u8]),
+                                           &mut out)
+            .unwrap();
+
+        assert_eq!(tweet.time, DateTime::parse_from_rfc2822("Fri, 9 Sep 2016 00:33:30 +0200").unwrap());
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("\"in 5 fortnights\" is not a valid relative time: \
+                       unknown unit \"fortnights\", expected one of seconds, minutes, hours, days or weeks\n"));
+    }
+
+    #[test]
+    fn unix_time() {
+        let tweet = queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
+                                                                 Test tweet\n\
+                                                                 @9223372036854775807\n\
+                                                                 @1473374010\n\
+                                                                 y\n" as &[u8]),
+                                           &mut Vec::new())
+            .unwrap();
+
+        assert_eq!(tweet.time.timestamp(), 1473374010);
+    }
+
+    #[test]
+    fn past_time_reentered() {
+        let mut out = Vec::new();
+        let tweet = queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
+                                                                 Test tweet\n\
+                                                                 2016-09-09T00:33:30+02:00\n\
+                                                                 n\n\
+                                                                 in 2 hours\n" as &[u8]),
+                                           &mut out)
+            .unwrap();
+
+        assert!(tweet.time.with_timezone(&Local) > Local::now());
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("2016-09-09T00:33:30+02:00 is in the past, that was "), "{:?}", out);
+        assert!(out.contains(" ago.\nQueue it anyway? [y/N]: "), "{:?}", out);
+    }
+}
+
+#[cfg(not(windows))]
+mod get_edited_tweet {
+    extern crate tweetr;
+
+    use self::tweetr::ops::queue_tweet;
+    use std::fs::{self, File};
+    use std::env::temp_dir;
+    use std::path::PathBuf;
+    use std::io::{BufReader, Write};
+    use std::iter;
+
+
+    #[test]
+    fn comments_stripped() {
+        let editor = editor("comments_stripped", &["# Leading comment\nAbolish\n\n  the bourgeoisie\n# 0/280 characters.\n"]);
+
+        let tweet = queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\n" as &[u8]), &mut Vec::new(), &editor, false)
+            .unwrap();
+        assert_eq!(tweet.author, "tweetr_test");
+        assert_eq!(tweet.content, "Abolish\n\n  the bourgeoisie");
+    }
+
+    #[test]
+    fn timed() {
+        let editor = editor("timed", &["Abolish the bourgeoisie\n"]);
+
+        let tweet = queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\n2016-09-09T00:33:30+02:00\ny\n" as &[u8]),
+                                                  &mut Vec::new(),
+                                                  &editor,
+                                                  true)
+            .unwrap();
+        assert_eq!(tweet.time.to_rfc3339(), "2016-09-09T00:33:30+02:00");
+    }
+
+    #[test]
+    fn too_long_reedited() {
+        let editor = editor("too_long_reedited", &[&long(), "Abolish the bourgeoisie\n"]);
+
+        let mut out = Vec::new();
+        let tweet = queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\n\n" as &[u8]), &mut out, &editor, false).unwrap();
+        assert_eq!(tweet.content, "Abolish the bourgeoisie");
+        assert!(String::from_utf8(out).unwrap().contains("The tweet is 281 characters long, only 280 fit."));
+    }
+
+    #[test]
+    fn too_long_kept() {
+        let editor = editor("too_long_kept", &[&long()]);
+
+        let tweet = queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\nn\n" as &[u8]), &mut Vec::new(), &editor, false)
+            .unwrap();
+        assert_eq!(tweet.content, long());
+    }
+
+    #[test]
+    fn editor_failed() {
+        let mut out = Vec::new();
+        assert_eq!(queue_tweet::get_edited_tweet(&mut BufReader::new(b"tweetr_test\n" as &[u8]), &mut out, "false", false),
+                   None);
+        assert!(String::from_utf8(out).unwrap().contains("Couldn't edit the tweet: false exited with"));
+    }
+
+    #[test]
+    fn temp_file_private() {
+        assert_eq!(queue_tweet::edit_content("sh -c 'ls -l \"$0\" | cut -c 1-10 > \"$0\"'", "Abolish the bourgeoisie").unwrap(),
+                   "-rw-------");
+    }
+
+
+    fn long() -> String {
+        iter::repeat('A').take(281).collect()
+    }
+
+    /// Make an editor replacing the file with the specified contents, in order, one each time it's run.
+    fn editor(name: &str, contents: &[&str]) -> String {
+        let td = temp_dir().join("tweetr-test").join(format!("ops-queue_tweet-get_edited_tweet-{}", name));
+        let _ = fs::remove_dir_all(&td);
+        fs::create_dir_all(&td).unwrap();
+
+        for (i, content) in contents.iter().enumerate() {
+            File::create(td.join(i.to_string())).unwrap().write_all(content.as_bytes()).unwrap();
+        }
+        let counter: PathBuf = td.join("counter");
+        format!("sh -c 'n=$(cat \"{0}\" 2>/dev/null || echo 0); cp \"{1}/$n\" \"$0\"; echo $((n + 1)) > \"{0}\"'",
+                counter.display(),
+                td.display())
+    }
+}
+
+mod print_import_preview {
+    extern crate tweetr;
+    extern crate chrono;
+
+    use self::tweetr::ops::queue_tweet::{self, Import};
+    use self::tweetr::ops::QueuedTweet;
+    use self::chrono::{DateTime, Duration};
+
+
+    #[test]
+    fn empty() {
+        let mut out = Vec::new();
+        queue_tweet::print_import_preview(&mut out, &[]);
+        assert_eq!(String::from_utf8(out).unwrap(), "0 to add, 0 duplicates, 0 failing validation; nothing was queued.\n");
+    }
+
+    #[test]
+    fn duplicates_and_reasons_listed() {
+        let tweet = QueuedTweet {
+            author: "nabijaczleweli".to_string(),
+            time: DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap(),
+            content: "Abolish the bourgeoisie".to_string(),
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        queue_tweet::print_import_preview(&mut out,
+                                          &[(tweet.clone(),
+                                             Import::Duplicate(vec![QueuedTweet { time: tweet.time - Duration::days(1), ..tweet.clone() },
+                                                                    QueuedTweet { time: tweet.time + Duration::days(1), ..tweet.clone() }])),
+                                            (tweet.clone(),
+                                             Import::Invalid(vec!["Link https://example.com/ returned 404 Not Found".to_string(),
+                                                                  "Possibly misspelt words: bourgeoisie".to_string()]))]);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "= 2016-09-09T12:00:00+02:00 by nabijaczleweli: \"Abolish the bourgeoisie\"\n    \
+                        duplicates the tweet scheduled for 2016-09-08T12:00:00+02:00\n    \
+                        duplicates the tweet scheduled for 2016-09-10T12:00:00+02:00\n\
+                    ! 2016-09-09T12:00:00+02:00 by nabijaczleweli: \"Abolish the bourgeoisie\"\n    \
+                        Link https://example.com/ returned 404 Not Found\n    \
+                        Possibly misspelt words: bourgeoisie\n\
+                    0 to add, 1 duplicate, 1 failing validation; nothing was queued.\n");
+    }
+}
+
+mod invisible_chars {
+    extern crate tweetr;
+
+    use self::tweetr::ops::queue_tweet;
+
+
+    #[test]
+    fn none() {
+        assert!(queue_tweet::invisible_chars("Zażółć gęślą jaźń\n\tAbolish the bourgeoisie!").is_empty());
+    }
+
+    #[test]
+    fn bidi_and_controls() {
+        assert_eq!(queue_tweet::invisible_chars("\u{202e}Abolish\u{202c} the\u{7}\u{200f} bourgeoisie\u{202e}!"),
+                   vec!["U+202E RIGHT-TO-LEFT OVERRIDE (2 times)",
+                        "U+202C POP DIRECTIONAL FORMATTING",
+                        "U+0007 CONTROL CHARACTER",
+                        "U+200F RIGHT-TO-LEFT MARK"]);
+    }
+
+    #[test]
+    fn zero_width_joiner() {
+        assert!(queue_tweet::invisible_chars("👨\u{200d}👩\u{200d}👧").is_empty());
+        assert_eq!(queue_tweet::invisible_chars("\u{200d}Abolish\u{200d}👩 the bourgeoisie👩\u{200d}"),
+                   vec!["U+200D ZERO WIDTH JOINER (3 times)"]);
+    }
+}
+
+mod strip_invisible {
+    extern crate tweetr;
+
+    use self::tweetr::ops::queue_tweet;
+
+
+    #[test]
+    fn nothing_to_strip() {
+        assert_eq!(queue_tweet::strip_invisible("Zażółć gęślą jaźń\n👨\u{200d}👩\u{200d}👧"), "Zażółć gęślą jaźń\n👨\u{200d}👩\u{200d}👧");
+    }
+
+    #[test]
+    fn everything_found_stripped() {
+        let content = "\u{feff}\u{202e}Abol\u{ad}ish\u{202c} the\u{7}\u{200f} bour\u{200b}geoisie\u{200d}!";
+        let stripped = queue_tweet::strip_invisible(content);
+        assert_eq!(stripped, "Abolish the bourgeoisie!");
+        assert!(queue_tweet::invisible_chars(&stripped).is_empty());
+    }
+}
+
+mod find_mentions {
+    extern crate tweetr;
+
+    use self::tweetr::ops::queue_tweet;
+
+
+    #[test]
+    fn none() {
+        assert!(queue_tweet::find_mentions("Abolish the bourgeoisie!").is_empty());
+        assert!(queue_tweet::find_mentions("Write to tweetr@example.com or ping tweetr_test@@").is_empty());
+    }
+
+    #[test]
+    fn deduplicated_case_insensitively() {
+        assert_eq!(queue_tweet::find_mentions("@tweetr_test @Tweetr_Test @TWEETR_TEST, cc @nabijaczleweli"),
+                   vec!["tweetr_test", "nabijaczleweli"]);
+    }
+
+    #[test]
+    fn punctuation_around() {
+        assert_eq!(queue_tweet::find_mentions("(@tweetr_test), \"@nabijaczleweli's\" and\n@abc…"),
+                   vec!["tweetr_test", "nabijaczleweli", "abc"]);
+    }
+
+    #[test]
+    fn too_long() {
+        assert_eq!(queue_tweet::find_mentions("@a23456789012345 @a234567890123456"), vec!["a23456789012345"]);
+    }
 }
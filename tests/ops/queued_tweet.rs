@@ -1,10 +1,14 @@
 extern crate tweetr;
 extern crate chrono;
 
-use self::chrono::{DateTime, Local};
-use self::tweetr::ops::QueuedTweet;
+use self::chrono::{DateTime, Duration, Local};
+use self::tweetr::ops::{QueuedTweet, Anchor, Coordinates, ReplySettings};
+use self::tweetr::ops::settings::Settings;
+use self::tweetr::util::encoding::Encoding;
+use self::tweetr::Outcome;
 use std::env::temp_dir;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{Read, Write};
 
 
 #[test]
@@ -22,11 +26,408 @@ fn posted_trans_eq() {
     trans_scaffold("posted_trans_eq", vec![posted()]);
 }
 
+#[test]
+fn default_trans_eq() {
+    trans_scaffold("default_trans_eq", vec![QueuedTweet::default()]);
+}
+
 #[test]
 fn mixed_trans_eq() {
     trans_scaffold("mixed_trans_eq", vec![unposted(), posted()]);
 }
 
+#[test]
+fn threaded_trans_eq() {
+    let mut first = posted();
+    first.thread = Some("manifesto".to_string());
+    let mut second = posted();
+    second.thread = Some("manifesto".to_string());
+    second.in_reply_to = first.id;
+    second.id = Some(421);
+
+    trans_scaffold("threaded_trans_eq", vec![first, second]);
+}
+
+#[test]
+fn extra_params_trans_eq() {
+    let mut tweet = unposted();
+    tweet.extra_params.insert("card_uri".to_string(), "tombstone://card".to_string());
+    tweet.extra_params.insert("possibly_sensitive".to_string(), "true".to_string());
+
+    trans_scaffold("extra_params_trans_eq", vec![tweet, posted()]);
+}
+
+#[test]
+fn geo_trans_eq() {
+    let mut tweet = unposted();
+    tweet.coordinates = Coordinates::new(52.2297, 21.0122);
+    tweet.place_id = Some("5a110d312052166f".to_string());
+    let mut place_only = posted();
+    place_only.place_id = Some("5a110d312052166f".to_string());
+
+    trans_scaffold("geo_trans_eq", vec![tweet, place_only]);
+}
+
+#[test]
+fn paused_trans_eq() {
+    let mut tweet = unposted();
+    tweet.paused = true;
+
+    trans_scaffold("paused_trans_eq", vec![tweet, unposted()]);
+}
+
+#[test]
+fn settings_trans_eq() {
+    let mut tweet = unposted();
+    tweet.settings = Settings {
+        timezone: Some("-05:00".to_string()),
+        quiet_hours: Some("22:00-06:00".to_string()),
+        jitter: Some(15),
+        tags: Some(vec![]),
+        sensitive: Some(false),
+    };
+
+    trans_scaffold("settings_trans_eq", vec![tweet, unposted()]);
+}
+
+#[test]
+fn reply_settings_trans_eq() {
+    let mut tweet = unposted();
+    tweet.reply_settings = Some(ReplySettings::MentionedUsers);
+    let mut following = posted();
+    following.reply_settings = Some(ReplySettings::Following);
+
+    trans_scaffold("reply_settings_trans_eq", vec![tweet, following, unposted()]);
+}
+
+#[test]
+fn media_trans_eq() {
+    let mut tweet = unposted();
+    tweet.media = vec!["/srv/charts/legend.png".to_string(), "media/chart.png".to_string()];
+
+    trans_scaffold("media_trans_eq", vec![tweet, posted()]);
+}
+
+#[test]
+fn retries_trans_eq() {
+    let mut tweet = posted();
+    tweet.retries = 3;
+
+    trans_scaffold("retries_trans_eq", vec![tweet, unposted()]);
+}
+
+#[test]
+fn subsecond_trans_eq() {
+    let mut tweet = posted();
+    tweet.time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30.125+02:00").unwrap();
+    tweet.time_posted = Some(DateTime::parse_from_rfc3339("2016-09-09T00:33:30.973+02:00").unwrap());
+
+    trans_scaffold("subsecond_trans_eq", vec![tweet, unposted()]);
+}
+
+#[test]
+fn control_characters_trans_eq() {
+    let mut tweet = unposted();
+    tweet.content = "Abolish\u{0}the\u{1b}[1mbourgeoisie\u{1f}\t\r\n".to_string();
+    tweet.extra_params.insert("card\u{7}".to_string(), "\u{2}".to_string());
+
+    trans_scaffold("control_characters_trans_eq", vec![tweet, posted()]);
+}
+
+#[test]
+fn anchored_trans_eq() {
+    let mut launch = unposted();
+    launch.name = Some("launch".to_string());
+    let mut follow_up = unposted();
+    follow_up.time = launch.time + Duration::minutes(15);
+    follow_up.after = Some(Anchor {
+        name: "launch".to_string(),
+        delay: 15 * 60,
+    });
+
+    trans_scaffold("anchored_trans_eq", vec![follow_up, launch]);
+}
+
+#[test]
+fn anchored_written_relative() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-anchored_written_relative");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("tweets.toml");
+
+    let mut tweet = unposted();
+    tweet.after = Some(Anchor {
+        name: "launch".to_string(),
+        delay: 2 * 60 * 60,
+    });
+    QueuedTweet::write(vec![tweet], &tf).unwrap();
+
+    let mut written = String::new();
+    File::open(&tf).unwrap().read_to_string(&mut written).unwrap();
+    assert!(written.contains("time = \"2 hours after launch\"\n"));
+}
+
+#[test]
+fn read_missing() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-read_missing");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    assert_eq!(QueuedTweet::read(&td.join("tweets.toml")), Err(None));
+}
+
+#[test]
+fn read_invalid_utf8() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-read_invalid_utf8");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"\xff\"\n").unwrap();
+
+    match QueuedTweet::read(&tf) {
+        Err(Some(Outcome::IoError { path, op: "read", .. })) => assert_eq!(path, tf.display().to_string()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn write_missing_directory() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-write_missing_directory");
+    let _ = fs::remove_dir_all(&td);
+    let tf = td.join("tweets.toml");
+
+    match QueuedTweet::write(vec![unposted()], &tf) {
+        Err(Outcome::IoError { path, op: "create", .. }) => assert_eq!(path, tf.display().to_string()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn anchored_read() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-anchored_read");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"1 day after teaser\"\ncontent = \"launch\"\nname = \"launch\"\n\n\
+                     [[tweet]]\nauthor = \"a\"\ntime = \"90 minutes after launch\"\ncontent = \"follow-up\"\n\n\
+                     [[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T12:00:00+02:00\"\ncontent = \"teaser\"\nname = \"teaser\"\n")
+        .unwrap();
+
+    let tweets = QueuedTweet::read(&tf).unwrap();
+    assert_eq!(tweets[0].time, DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap());
+    assert_eq!(tweets[1].time, DateTime::parse_from_rfc3339("2016-09-10T13:30:00+02:00").unwrap());
+}
+
+#[test]
+fn anchored_to_posted() {
+    let mut launch = posted();
+    launch.name = Some("launch".to_string());
+    let mut follow_up = unposted();
+    follow_up.after = Some(Anchor {
+        name: "launch".to_string(),
+        delay: 60,
+    });
+
+    let mut tweets = vec![launch.clone(), follow_up];
+    assert!(QueuedTweet::schedule(&mut tweets, &[]).is_empty());
+    assert_eq!(tweets[1].time, launch.time_posted.unwrap() + Duration::minutes(1));
+}
+
+#[test]
+fn anchored_posted_without_anchor() {
+    let mut tweet = posted();
+    tweet.after = Some(Anchor {
+        name: "archived".to_string(),
+        delay: 60,
+    });
+
+    let mut tweets = vec![tweet.clone()];
+    assert!(QueuedTweet::schedule(&mut tweets, &[]).is_empty());
+    assert_eq!(tweets[0].time, tweet.time_posted.unwrap());
+}
+
+#[test]
+fn read_relative_to() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-read_relative_to");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"after launch\"\ncontent = \"b\"\n").unwrap();
+
+    let mut launch = unposted();
+    launch.name = Some("launch".to_string());
+    assert_eq!(QueuedTweet::read_relative_to(&tf, &[launch.clone()], None).unwrap()[0].time, launch.time);
+}
+
+#[test]
+fn read_relative_to_windows1252() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-read_relative_to_windows1252");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"Caf\xE9 \x80\"\n").unwrap();
+    assert_eq!(QueuedTweet::read_relative_to(&tf, &[], None).unwrap()[0].content, "Café €");
+}
+
+#[test]
+fn read_relative_to_invalid_sequence() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-read_relative_to_invalid_sequence");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"Caf\xE9\"\n").unwrap();
+    assert_eq!(QueuedTweet::read_relative_to(&tf, &[], Some(Encoding::Utf8)),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "queued tweets",
+                   errors: vec!["error: 4:15: invalid UTF-8 sequence 0xE9 at byte offset 72".to_string()],
+               })));
+}
+
+#[test]
+fn anchor_missing_error() {
+    error_scaffold("anchor_missing_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"15 minutes after launch\"\ncontent = \"b\"\n",
+                   "error: 3:1: no tweet named \"launch\" for the key `tweet.0.time`");
+}
+
+#[test]
+fn anchor_cycle_error() {
+    error_scaffold("anchor_cycle_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"after b\"\ncontent = \"b\"\nname = \"a\"\n\n\
+                    [[tweet]]\nauthor = \"a\"\ntime = \"after a\"\ncontent = \"b\"\nname = \"b\"\n",
+                   "error: 3:1: scheduled relative to \"b\", which can't be scheduled for the key `tweet.0.time`");
+}
+
+#[test]
+fn duplicate_name_error() {
+    error_scaffold("duplicate_name_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nname = \"launch\"\n\n\
+                    [[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nname = \"launch\"\n",
+                   "error: 11:1: duplicate name \"launch\" for the key `tweet.1.name`");
+}
+
+#[test]
+fn invalid_anchored_time_error() {
+    error_scaffold("invalid_anchored_time_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"15 fortnights after launch\"\ncontent = \"b\"\n",
+                   "error: 3:1: invalid relative time (unknown unit \"fortnights\", expected one of seconds, minutes, hours, days or weeks) \
+                    for the key `tweet.0.time`");
+}
+
+#[test]
+fn missing_key_error() {
+    error_scaffold("missing_key_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\n\n\
+                    [[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\n",
+                   "error: 6:1: expected a value of type `string` for the key `tweet.1.content`");
+}
+
+#[test]
+fn wrong_type_error() {
+    error_scaffold("wrong_type_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\n  id = \"123\"\n",
+                   "error: 5:3: expected a value of type `integer`, but found a value of type `string` for the key `tweet.0.id`");
+}
+
+#[test]
+fn invalid_datetime_error() {
+    error_scaffold("invalid_datetime_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\n\n\
+                    [[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\ntime_posted = \"yesterday\"\n",
+                   "error: 10:1: invalid RFC3339 datetime (input contains invalid characters) for the key `tweet.1.time_posted`");
+}
+
+#[test]
+fn lat_out_of_range_error() {
+    error_scaffold("lat_out_of_range_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nlat = 91.5\nlong = 21.0122\n",
+                   "error: 5:1: latitude out of range [-90, 90] for the key `tweet.0.lat`");
+}
+
+#[test]
+fn long_without_lat_error() {
+    error_scaffold("long_without_lat_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nlong = 21.0122\n",
+                   "error: 5:1: longitude without latitude for the key `tweet.0.long`");
+}
+
+#[test]
+fn invalid_timezone_error() {
+    error_scaffold("invalid_timezone_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\ntimezone = \"CEST\"\n",
+                   "error: 5:1: invalid UTC offset for the key `tweet.0.timezone`");
+}
+
+#[test]
+fn invalid_quiet_hours_error() {
+    error_scaffold("invalid_quiet_hours_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nquiet_hours = \"night\"\n",
+                   "error: 5:1: invalid span of time for the key `tweet.0.quiet_hours`");
+}
+
+#[test]
+fn invalid_reply_settings_error() {
+    error_scaffold("invalid_reply_settings_error",
+                   "[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\nreply_settings = \"nobody\"\n",
+                   "error: 5:1: invalid reply settings, expected \"everyone\", \"following\" or \"mentionedUsers\" for the key \
+                    `tweet.0.reply_settings`");
+}
+
+#[test]
+fn recovering_keeps_broken() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-recovering_keeps_broken");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"b\"\n\n\
+                     [[tweet]]\nauthor = \"a\"\ncontent = \"missing time\"\n")
+        .unwrap();
+
+    let queue = QueuedTweet::read_recovering(&tf).unwrap();
+    assert_eq!(queue.tweets.len(), 1);
+    assert_eq!(queue.broken.len(), 1);
+    assert_eq!(queue.errors,
+               Some(Outcome::FileParsingFailed {
+                   desc: "queued tweets",
+                   errors: vec!["error: 6:1: expected a value of type `string` for the key `tweet.1.time`".to_string()],
+               }));
+
+    let mut tweets = queue.tweets.clone();
+    tweets.push(posted());
+    QueuedTweet::write_with_broken(tweets.clone(), &queue.broken, &tf).unwrap();
+
+    let requeue = QueuedTweet::read_recovering(&tf).unwrap();
+    assert_eq!(requeue.tweets, tweets);
+    assert_eq!(requeue.broken, queue.broken);
+    assert!(requeue.errors.is_some());
+}
+
+
+#[test]
+fn recovering_sets_aside_unschedulable() {
+    let td = temp_dir().join("tweetr-test").join("ops-queued_tweet-recovering_sets_aside_unschedulable");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[tweet]]\nauthor = \"a\"\ntime = \"after launch\"\ncontent = \"b\"\n\n\
+                     [[tweet]]\nauthor = \"a\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"c\"\n")
+        .unwrap();
+
+    let queue = QueuedTweet::read_recovering(&tf).unwrap();
+    assert_eq!(queue.tweets.len(), 1);
+    assert_eq!(queue.tweets[0].content, "c");
+    assert_eq!(queue.broken.len(), 1);
+    assert_eq!(queue.errors,
+               Some(Outcome::FileParsingFailed {
+                   desc: "queued tweets",
+                   errors: vec!["error: 3:1: no tweet named \"launch\" for the key `tweet.0.time`".to_string()],
+               }));
+}
+
 
 fn trans_scaffold(name: &str, tweets: Vec<QueuedTweet>) {
     let td = temp_dir().join("tweetr-test").join(format!("ops-queued_tweet-{}", name));
@@ -35,7 +436,7 @@ fn trans_scaffold(name: &str, tweets: Vec<QueuedTweet>) {
     let tf = td.join("tweets.toml");
     let _ = fs::remove_file(&tf);
 
-    QueuedTweet::write(tweets.clone(), &tf);
+    QueuedTweet::write(tweets.clone(), &tf).unwrap();
     let read_tweets = QueuedTweet::read(&tf).unwrap();
 
     assert_eq!(tweets, read_tweets);
@@ -46,8 +447,7 @@ fn unposted() -> QueuedTweet {
         author: "nabijaczleweli".to_string(),
         time: DateTime::parse_from_rfc2822("Tue, 1 Jul 2098 10:52:37 +0200").unwrap(),
         content: "This tweet was not posted yet, so das good".to_string(),
-        time_posted: None,
-        id: None,
+        ..Default::default()
     }
 }
 
@@ -59,5 +459,20 @@ fn posted() -> QueuedTweet {
         content: "This tweet got posted just now, aww yeah, boii".to_string(),
         time_posted: Some(now.with_timezone(now.offset())),
         id: Some(420),
+        ..Default::default()
     }
 }
+
+fn error_scaffold(name: &str, contents: &str, error: &str) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-queued_tweet-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+
+    assert_eq!(QueuedTweet::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "queued tweets",
+                   errors: vec![error.to_string()],
+               })));
+}
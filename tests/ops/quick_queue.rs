@@ -0,0 +1,60 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::quick_queue;
+use self::chrono::DateTime;
+
+
+#[test]
+fn escapes() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    let tweet = quick_queue::parse(r"C:\\tweetr \|\| D:\\ | in 1 hour | nabijaczleweli", "|", now).unwrap();
+    assert_eq!(tweet.content, r"C:\tweetr || D:\");
+
+    let tweet = quick_queue::parse(r"a\;;b ;; in 1 hour ;; nabijaczleweli", ";;", now).unwrap();
+    assert_eq!(tweet.content, "a;;b");
+}
+
+#[test]
+fn unknown_escape() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    assert_eq!(quick_queue::parse(r"Ship day!\t | in 1 hour | nabijaczleweli", "|", now),
+               Err(vec![r#"content segment: unknown escape "\t", expected "\|", "\\" or "\n""#.to_string()]));
+}
+
+#[test]
+fn unfinished_escape() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    assert_eq!(quick_queue::parse(r"Ship day! | in 1 hour | nabijaczleweli\", "|", now),
+               Err(vec!["account segment: unfinished escape at the end".to_string()]));
+}
+
+#[test]
+fn too_many_segments() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    assert_eq!(quick_queue::parse("Cats | dogs | in 1 hour | nabijaczleweli", "|", now),
+               Err(vec!["expected the content, time and account separated by \"|\", got 4 segments".to_string()]));
+}
+
+#[test]
+fn bad_account() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    assert_eq!(quick_queue::parse("Ship day! | in 1 hour | @", "|", now),
+               Err(vec!["account segment: \"@\" isn't an account name".to_string()]));
+    assert_eq!(quick_queue::parse("Ship day! | tomorrow | @brand acct", "|", now),
+               Err(vec!["time segment: \"tomorrow\" is not an RFC2822, RFC3339, Unix or relative time".to_string(),
+                        "account segment: \"@brand acct\" isn't an account name".to_string()]));
+}
+
+#[test]
+fn past_horizon() {
+    let now = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+
+    assert_eq!(quick_queue::parse("Ship day! | in 5000 days | brandacct", "|", now),
+               Err(vec!["time segment: \"in 5000 days\" is more than 3650 days in the future, past the horizon".to_string()]));
+}
@@ -0,0 +1,97 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::chrono::DateTime;
+use self::tweetr::ops::reconcile::{self, Discrepancy, Posted};
+use self::tweetr::ops::QueuedTweet;
+use std::collections::BTreeSet;
+
+
+#[test]
+fn nothing_on_timeline() {
+    let recorded = vec![tweet("nabijaczleweli", "Abolish", "2016-09-10T12:00:00+02:00", Some(1)),
+                        tweet("nabijaczleweli", "the", "2016-09-11T12:00:00+02:00", None)];
+    assert!(reconcile::discrepancies("nabijaczleweli", &[], &recorded, &BTreeSet::new()).is_empty());
+}
+
+#[test]
+fn all_recorded() {
+    let recorded = vec![tweet("nabijaczleweli", "Abolish", "2016-09-10T12:00:00+02:00", Some(1)),
+                        tweet("nabijaczleweli", "the", "2016-09-11T12:00:00+02:00", Some(2)),
+                        tweet("nabijaczleweli", "bourgeoisie", "2016-09-12T12:00:00+02:00", None)];
+    let timeline = vec![posted(2, "2016-09-11T12:00:05+02:00", "the"), posted(1, "2016-09-10T12:00:05+02:00", "Abolish")];
+    assert!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()).is_empty());
+}
+
+#[test]
+fn posted_before_scheduled_not_matched() {
+    let recorded = vec![tweet("nabijaczleweli", "Abolish", "2016-09-10T12:00:00+02:00", None)];
+    let timeline = vec![posted(1, "2016-09-10T11:58:00+02:00", "Abolish"), posted(2, "2016-09-10T11:59:30+02:00", "Abolish")];
+    assert_eq!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()),
+               vec![Discrepancy::Unrecorded(&recorded[0], &timeline[1])]);
+}
+
+#[test]
+fn each_post_matched_once() {
+    let recorded = vec![tweet("nabijaczleweli", "Good morning", "2016-09-10T08:00:00+02:00", None),
+                        tweet("nabijaczleweli", "Good morning", "2016-09-11T08:00:00+02:00", None),
+                        tweet("nabijaczleweli", "Good morning", "2016-09-12T08:00:00+02:00", None)];
+    let timeline = vec![posted(2, "2016-09-11T08:00:05+02:00", "Good morning"), posted(1, "2016-09-10T08:00:05+02:00", "Good morning")];
+    assert_eq!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()),
+               vec![Discrepancy::Unrecorded(&recorded[0], &timeline[1]), Discrepancy::Unrecorded(&recorded[1], &timeline[0])]);
+}
+
+#[test]
+fn other_authors_skipped() {
+    let recorded = vec![tweet("tweetr_test", "Abolish", "2016-09-10T12:00:00+02:00", None),
+                        tweet("tweetr_test", "the", "2016-09-11T12:00:00+02:00", Some(5))];
+    let timeline = vec![posted(2, "2016-09-11T12:00:05+02:00", "the"), posted(1, "2016-09-10T12:00:05+02:00", "Abolish")];
+    assert!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()).is_empty());
+}
+
+#[test]
+fn deleted_only_since_oldest() {
+    let recorded = vec![tweet("nabijaczleweli", "Abolish", "2016-09-09T12:00:00+02:00", Some(1)),
+                        tweet("nabijaczleweli", "the", "2016-09-11T12:00:00+02:00", Some(2)),
+                        tweet("nabijaczleweli", "bourgeoisie", "2016-09-12T12:00:00+02:00", Some(3))];
+    let timeline = vec![posted(3, "2016-09-12T12:00:05+02:00", "bourgeoisie"), posted(9, "2016-09-10T12:00:00+02:00", "Hello")];
+    assert_eq!(reconcile::discrepancies("nabijaczleweli", &timeline, &recorded, &BTreeSet::new()),
+               vec![Discrepancy::Deleted(&recorded[1])]);
+}
+
+#[test]
+fn print() {
+    let recorded = vec![tweet("nabijaczleweli", "Abolish", "2016-09-10T12:00:00+02:00", None),
+                        tweet("nabijaczleweli", "the", "2016-09-11T12:00:00+02:00", Some(2))];
+    let timeline = vec![posted(1, "2016-09-10T12:00:05+02:00", "Abolish")];
+
+    let mut out = Vec::new();
+    reconcile::print_discrepancies(&mut out,
+                                   "nabijaczleweli",
+                                   &[Discrepancy::Unrecorded(&recorded[0], &timeline[0]), Discrepancy::Deleted(&recorded[1])]);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "@nabijaczleweli: 2 discrepancies:\n  \
+                  Tweet \"Abolish\" scheduled for 2016-09-10T12:00:00+02:00 was posted at 2016-09-10T12:00:05+02:00 with ID 1, but isn't \
+                recorded as posted\n  \
+                  Tweet \"the\" recorded as posted at 2016-09-11T12:00:00+02:00 with ID 2 isn't on the timeline, it might've been deleted\n");
+}
+
+
+fn tweet(author: &str, content: &str, time: &str, id: Option<i64>) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: DateTime::parse_from_rfc3339(time).unwrap(),
+        content: content.to_string(),
+        time_posted: id.map(|_| DateTime::parse_from_rfc3339(time).unwrap()),
+        id: id,
+        ..Default::default()
+    }
+}
+
+fn posted(id: i64, time: &str, text: &str) -> Posted {
+    Posted {
+        id: id,
+        time: DateTime::parse_from_rfc3339(time).unwrap(),
+        text: text.to_string(),
+    }
+}
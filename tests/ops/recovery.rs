@@ -0,0 +1,178 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::recovery::{self, Checkpoint, InFlight, Kind, Policy, Resolution};
+use self::tweetr::ops::journal::{Action, Record, Snapshot};
+use self::tweetr::ops::{QueuedTweet, Anchor};
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, Duration, FixedOffset};
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn unarchived() {
+    let tweets = vec![posted("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00"), posted("Seize the means", "2016-09-10T13:00:00+02:00")];
+    let archived = [tweets[1].clone()];
+
+    assert_eq!(resolutions(&recovery::check(None, &tweets, &archived, &[], Policy::Hold, None, time("2016-09-10T15:00:00+02:00"))),
+               vec![(Kind::Unarchived, Some(0), Resolution::Archive), (Kind::Unarchived, Some(1), Resolution::Drop)]);
+}
+
+#[test]
+fn unarchived_journalled() {
+    let tweets = vec![posted("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00")];
+    let journal = [record(Action::Archived, &tweets[0], "2016-09-10T12:00:05+02:00")];
+
+    assert_eq!(resolutions(&recovery::check(None, &tweets, &[], &journal, Policy::Hold, None, time("2016-09-10T15:00:00+02:00"))),
+               vec![(Kind::Unarchived, Some(0), Resolution::Drop)]);
+}
+
+#[test]
+fn unarchived_kept_for_anchored() {
+    let tweets = vec![QueuedTweet { name: Some("launch".to_string()), ..posted("tweetr v1.4.0 is out!", "2016-09-10T12:00:00+02:00") },
+                      QueuedTweet {
+                          after: Some(Anchor {
+                              name: "launch".to_string(),
+                              delay: 60 * 60,
+                          }),
+                          ..tweet("In case you missed it", "2016-09-10T13:00:00+02:00")
+                      }];
+
+    assert!(recovery::check(None, &tweets, &[], &[], Policy::Hold, None, time("2016-09-10T15:00:00+02:00")).is_empty());
+}
+
+#[test]
+fn in_flight() {
+    let tweets = vec![tweet("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00"), posted("Seize the means", "2016-09-10T12:00:00+02:00")];
+    let checkpoint = Checkpoint {
+        time: time("2016-09-10T12:00:00+02:00").timestamp(),
+        in_flight: vec![InFlight::of(&tweets[0], time("2016-09-10T12:00:00+02:00")),
+                        InFlight::of(&tweet("Seize the means", "2016-09-10T12:00:00+02:00"), time("2016-09-10T12:00:00+02:00")),
+                        InFlight::of(&tweet("Workers of the world, unite!", "2016-09-10T12:00:00+02:00"), time("2016-09-10T12:00:00+02:00"))],
+    };
+    let now = time("2016-09-10T12:30:00+02:00");
+
+    assert_eq!(resolutions(&recovery::check(Some(&checkpoint), &tweets, &[tweets[1].clone()], &[], Policy::Hold, None, now)),
+               vec![(Kind::Unarchived, Some(1), Resolution::Drop),
+                    (Kind::InFlight, Some(0), Resolution::Pause),
+                    (Kind::InFlight, Some(1), Resolution::Leave),
+                    (Kind::InFlight, None, Resolution::Leave)]);
+    assert_eq!(recovery::check(Some(&checkpoint), &tweets, &[tweets[1].clone()], &[], Policy::Retry, None, now)[1].resolution,
+               Resolution::Post);
+}
+
+#[test]
+fn in_flight_journalled() {
+    let tweets = vec![tweet("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00")];
+    let checkpoint = Checkpoint {
+        time: time("2016-09-10T12:00:00+02:00").timestamp(),
+        in_flight: vec![InFlight::of(&tweets[0], time("2016-09-10T12:00:00+02:00"))],
+    };
+    let now = time("2016-09-10T12:30:00+02:00");
+
+    let journal = [record(Action::Posted, &tweets[0], "2016-09-10T12:00:05+02:00")];
+    assert_eq!(resolutions(&recovery::check(Some(&checkpoint), &tweets, &[], &journal, Policy::Hold, None, now)),
+               vec![(Kind::InFlight, Some(0), Resolution::Leave)]);
+
+    // Posted before the daemon started posting it this time
+    let journal = [record(Action::Posted, &tweets[0], "2016-09-10T11:00:00+02:00")];
+    assert_eq!(resolutions(&recovery::check(Some(&checkpoint), &tweets, &[], &journal, Policy::Hold, None, now)),
+               vec![(Kind::InFlight, Some(0), Resolution::Pause)]);
+}
+
+#[test]
+fn missed() {
+    let tweets = vec![tweet("Before the daemon stopped", "2016-09-10T09:00:00+02:00"),
+                      tweet("Long overdue", "2016-09-10T11:00:00+02:00"),
+                      tweet("Slightly late", "2016-09-10T14:30:00+02:00"),
+                      QueuedTweet { paused: true, ..tweet("Paused", "2016-09-10T14:00:00+02:00") },
+                      tweet("Not yet due", "2016-09-10T16:00:00+02:00")];
+    let checkpoint = Checkpoint::at(time("2016-09-10T10:00:00+02:00"));
+    let now = time("2016-09-10T15:00:00+02:00");
+
+    assert_eq!(resolutions(&recovery::check(Some(&checkpoint), &tweets, &[], &[], Policy::Hold, Some(Duration::hours(1)), now)),
+               vec![(Kind::Missed, Some(1), Resolution::Pause), (Kind::Missed, Some(2), Resolution::Post)]);
+    assert_eq!(resolutions(&recovery::check(Some(&checkpoint), &tweets, &[], &[], Policy::Hold, None, now)),
+               vec![(Kind::Missed, Some(1), Resolution::Post), (Kind::Missed, Some(2), Resolution::Post)]);
+}
+
+#[test]
+fn apply_keeps_order() {
+    let mut tweets = vec![posted("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00"),
+                          tweet("Long overdue", "2016-09-10T11:00:00+02:00"),
+                          posted("Seize the means", "2016-09-10T13:00:00+02:00")];
+    let checkpoint = Checkpoint::at(time("2016-09-10T10:00:00+02:00"));
+    let archived = tweets.clone();
+    let recoveries =
+        recovery::check(Some(&checkpoint), &tweets, &archived, &[], Policy::Hold, Some(Duration::hours(1)), time("2016-09-10T15:00:00+02:00"));
+
+    assert_eq!(recovery::apply(&mut tweets, &recoveries), vec![archived[0].clone(), archived[2].clone()]);
+    assert_eq!(tweets, vec![QueuedTweet { paused: true, ..archived[1].clone() }]);
+}
+
+#[test]
+fn hold_timed_out_rate_limited() {
+    let mut tweet = tweet("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00");
+    let rate_limited = Outcome::RateLimited {
+        desc: "posting to Twitter as @nabijaczleweli".to_string(),
+        reset: None,
+    };
+
+    assert!(!recovery::hold_timed_out(&mut tweet, &rate_limited, Policy::Hold));
+    assert!(!tweet.paused);
+}
+
+#[test]
+fn checkpoint_trans_eq() {
+    let td = temp_dir().join("tweetr-test").join("ops-recovery-checkpoint_trans_eq");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let tf = Checkpoint::path(&td);
+
+    let checkpoint = Checkpoint {
+        time: time("2016-09-10T12:00:00+02:00").timestamp(),
+        in_flight: vec![InFlight::of(&tweet("Abolish the bourgeoisie", "2016-09-10T12:00:00+02:00"), time("2016-09-10T12:00:00+02:00")),
+                        InFlight::of(&tweet("Seize the means", "2016-09-10T11:00:00+02:00"), time("2016-09-10T12:00:01+02:00"))],
+    };
+    checkpoint.write(&tf).unwrap();
+    assert_eq!(Checkpoint::read(&tf), Ok(Some(checkpoint)));
+    assert!(!td.join(".checkpoint.toml.new").exists());
+}
+
+
+fn resolutions(recoveries: &[recovery::Recovery]) -> Vec<(Kind, Option<usize>, Resolution)> {
+    recoveries.iter().map(|r| (r.kind, r.index, r.resolution)).collect()
+}
+
+fn record(action: Action, tweet: &QueuedTweet, at: &str) -> Record {
+    Record {
+        time: time(at),
+        actor: "start-daemon".to_string(),
+        user: None,
+        action: action,
+        entry: "4b6e3a1f".to_string(),
+        tweet: Snapshot { id: Some(774560457755590656), ..Snapshot::of(tweet) },
+    }
+}
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn posted(content: &str, at: &str) -> QueuedTweet {
+    QueuedTweet {
+        time_posted: Some(time(at)),
+        id: Some(774560457755590656),
+        ..tweet(content, at)
+    }
+}
+
+fn tweet(content: &str, at: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time(at),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
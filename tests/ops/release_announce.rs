@@ -0,0 +1,91 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::release_announce::{self, Release};
+use self::tweetr::ops::Template;
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::fs::{self, File};
+
+
+#[test]
+fn changelog_path_order() {
+    let td = temp_dir().join("tweetr-test").join("ops-release-announce-changelog_path_order");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    File::create(td.join("NEWS")).unwrap();
+    assert_eq!(release_announce::changelog_path(&td), Some(td.join("NEWS")));
+    File::create(td.join("CHANGES.md")).unwrap();
+    assert_eq!(release_announce::changelog_path(&td), Some(td.join("CHANGES.md")));
+}
+
+#[test]
+fn parse_changelog_no_releases() {
+    assert_eq!(release_announce::parse_changelog("# Changelog\n\n## Unreleased\n- Release announcements\n", None), None);
+}
+
+#[test]
+fn parse_changelog_nested_items() {
+    let changelog = "# v1.4.0\n\
+                     - Release announcements\n  \
+                       - from CI\n\
+                     Some prose - not an item\n";
+
+    assert_eq!(release_announce::parse_changelog(changelog, Some("1.4.0")),
+               Some(Release {
+                   version: "1.4.0".to_string(),
+                   highlights: vec!["Release announcements".to_string(), "from CI".to_string()],
+               }));
+}
+
+#[test]
+fn latest_tag_not_repo() {
+    let td = temp_dir().join("tweetr-test").join("ops-release-announce-latest_tag_not_repo");
+    let _ = fs::remove_dir_all(&td);
+
+    assert_eq!(release_announce::latest_tag(&td), None);
+    assert_eq!(release_announce::remote_url(&td), None);
+}
+
+#[test]
+fn values_unused_placeholders() {
+    let template = Template {
+        name: "release".to_string(),
+        content: "tweetr {version} is out!".to_string(),
+    };
+    let release = Release {
+        version: "v1.4.0".to_string(),
+        highlights: vec!["Release announcements".to_string()],
+    };
+
+    let values = release_announce::values(&template, &release, None, 3);
+    assert_eq!(values.keys().collect::<Vec<_>>(), vec!["version"]);
+    assert_eq!(template.fill(&values), Ok("tweetr v1.4.0 is out!".to_string()));
+}
+
+#[test]
+fn values_no_url() {
+    let template = Template {
+        name: "release".to_string(),
+        content: "tweetr {version} is out! {url}".to_string(),
+    };
+    let release = Release {
+        version: "v1.4.0".to_string(),
+        highlights: vec![],
+    };
+
+    assert_eq!(template.fill(&release_announce::values(&template, &release, None, 3)),
+               Err(vec!["no value for the placeholder {url}".to_string()]));
+}
+
+#[test]
+fn announcement_thread() {
+    let time = DateTime::parse_from_rfc3339("2017-01-29T12:00:00+01:00").unwrap();
+    let content = format!("tweetr v1.4.0 is out!\n{}", vec!["- Release announcements"; 20].join("\n"));
+
+    let tweets = release_announce::announcement(&content, "v1.4.0", "nabijaczleweli", time, "{n}/{total} {content}");
+    assert!(tweets.len() > 1);
+    assert!(tweets.iter().all(|t| t.thread.is_some() && t.thread == tweets[0].thread && t.time == time && t.author == "nabijaczleweli"));
+    assert_eq!(tweets.iter().filter(|t| t.name.is_some()).count(), 1);
+}
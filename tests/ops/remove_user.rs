@@ -0,0 +1,105 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{remove_user, QueuedTweet, User};
+use self::tweetr::Outcome;
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn other_users_untouched() {
+    let tf = users_file("other_users_untouched");
+    let mut kept = user("tweetr_test", 482);
+    kept.app = Some("agency".to_string());
+    kept.added = Some("2016-09-10T12:00:00+02:00".to_string());
+    User::write(vec![user("nabijaczleweli", 481), kept.clone(), user("not-nabijaczleweli", 483)], &tf).unwrap();
+
+    assert_eq!(remove_user::remove_user(&tf, "nabijaczleweli"), Ok(user("nabijaczleweli", 481)));
+    assert_eq!(User::read(&tf), Ok(vec![kept, user("not-nabijaczleweli", 483)]));
+}
+
+#[test]
+fn nonexistant_account() {
+    let tf = users_file("nonexistant_account");
+    User::write(vec![user("nabijaczleweli", 481)], &tf).unwrap();
+
+    assert_eq!(remove_user::remove_user(&tf, "not-nabijaczleweli"),
+               Err(Outcome::RequiredDataFromSubsystemNonexistant {
+                   subsys: "add-user",
+                   desc: "add and authorise user with name \"not-nabijaczleweli\"".to_string(),
+               }));
+    assert_eq!(User::read(&tf), Ok(vec![user("nabijaczleweli", 481)]));
+}
+
+#[test]
+fn posted_tweets_not_orphaned() {
+    let tweets = vec![QueuedTweet { time_posted: Some(DateTime::parse_from_rfc3339("2016-09-10T12:00:05+02:00").unwrap()), id: Some(481), ..tweet() },
+                      QueuedTweet { author: "tweetr_test".to_string(), ..tweet() }];
+
+    let queued = remove_user::queued_by(&tweets, "nabijaczleweli");
+    assert!(queued.is_empty());
+    assert_eq!(remove_user::check_orphans("nabijaczleweli", &queued), Ok(()));
+}
+
+#[test]
+fn all_orphans_listed() {
+    let tweets = vec![tweet(), QueuedTweet { content: "Seize the means of production".to_string(), ..tweet() }];
+
+    match remove_user::check_orphans("nabijaczleweli", &remove_user::queued_by(&tweets, "nabijaczleweli")) {
+        Err(Outcome::PreflightFailed(problems)) => {
+            assert_eq!(problems.len(), 3);
+            assert_eq!(problems[2], "pass --orphan to remove nabijaczleweli anyway, leaving 2 tweets unpostable");
+        }
+        out => panic!("{:?}", out),
+    }
+}
+
+#[test]
+fn one_orphan_message() {
+    let mut out = Vec::new();
+    remove_user::print_success_message(&mut out, &user("nabijaczleweli", 481), 1);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Removed user nabijaczleweli#481\n1 queued tweet by nabijaczleweli was left in the queue, change its author or remove it.\n");
+}
+
+
+fn users_file(name: &str) -> ::std::path::PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-remove_user-{}", name));
+    fs::create_dir_all(&td).unwrap();
+    td.join("users.toml")
+}
+
+fn tweet() -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
+
+fn user(name: &str, id: i64) -> User {
+    User {
+        name: name.to_string(),
+        id: id,
+        access_token_key: format!("{}-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", id),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
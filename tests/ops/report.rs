@@ -0,0 +1,101 @@
+extern crate rustc_serialize;
+extern crate tweetr;
+extern crate chrono;
+
+use self::rustc_serialize::json::Json;
+use self::tweetr::ops::report::{self, Report};
+use self::tweetr::ops::QueuedTweet;
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, FixedOffset};
+use std::env::temp_dir;
+use std::time::Duration;
+use std::fs::{self, File};
+use std::io::Read;
+
+
+#[test]
+fn new_posted_and_archived() {
+    let before = [tweet("Abolish the bourgeoisie"), tweet("Workers of the world, unite!"), tweet("Seize the means of production")];
+    let posted = QueuedTweet {
+        time_posted: Some(time("2016-09-10T12:00:05+02:00")),
+        id: Some(774560457755590656),
+        ..before[0].clone()
+    };
+    let archived = QueuedTweet {
+        time_posted: Some(time("2016-09-10T12:00:10+02:00")),
+        id: Some(774560457755590657),
+        ..before[1].clone()
+    };
+
+    let report = Report::new("post-all",
+                             time("2016-09-10T12:00:00+02:00"),
+                             Duration::from_secs(10),
+                             &Outcome::NoError,
+                             &before,
+                             &[posted, before[2].clone()],
+                             &[archived],
+                             vec![]);
+    assert_eq!((report.added, report.edited, report.removed, report.posted, report.archived), (0, 0, 0, 2, 1));
+}
+
+#[test]
+fn new_error() {
+    let report = Report::new("queue-tweet",
+                             time("2016-09-10T12:00:00+02:00"),
+                             Duration::from_secs(0),
+                             &Outcome::PreflightFailed(vec!["tweets.toml is world-writable".to_string()]),
+                             &[],
+                             &[],
+                             &[],
+                             vec![]);
+    assert!(report.exit_code != 0);
+    assert!(report.error.unwrap().contains("tweets.toml is world-writable"));
+}
+
+#[test]
+fn write_parses() {
+    let tf = temp_dir().join("tweetr-test").join("ops-report-write_parses");
+    fs::create_dir_all(&tf).unwrap();
+    let tf = tf.join("report.json");
+
+    let report = Report::new("queue-tweet",
+                             time("2016-09-10T12:00:00+02:00"),
+                             Duration::from_millis(250),
+                             &Outcome::NoError,
+                             &[],
+                             &[tweet("Abolish the bourgeoisie")],
+                             &[],
+                             vec!["Invisible characters: U+200B".to_string()]);
+    report.write(&tf).unwrap();
+
+    let mut buf = String::new();
+    File::open(&tf).unwrap().read_to_string(&mut buf).unwrap();
+    let json = Json::from_str(&buf).unwrap();
+    assert_eq!(json.find("added"), Some(&Json::U64(1)));
+    assert_eq!(json.find("duration"), Some(&Json::F64(0.25)));
+    assert_eq!(json.find("warnings"), Some(&Json::Array(vec![Json::String("Invisible characters: U+200B".to_string())])));
+}
+
+#[test]
+fn take_warnings_stops_collecting() {
+    report::collect_warnings();
+    report::warn("Possibly misspelt words: Wrokers");
+    assert_eq!(report::take_warnings(), vec!["Possibly misspelt words: Wrokers".to_string()]);
+
+    report::warn("Invisible characters: U+200B");
+    assert_eq!(report::take_warnings(), Vec::<String>::new());
+}
+
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn tweet(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time("2016-09-10T12:00:00+02:00"),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,49 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, requeue_thread, start_daemon};
+use self::chrono::DateTime;
+
+
+#[test]
+fn undoes_block() {
+    let mut tweets = vec![tweet("1/2 Abolish", Some("manifesto")), tweet("2/2 the bourgeoisie", Some("manifesto"))];
+    let original = tweets.clone();
+
+    start_daemon::block_thread(&mut tweets, 0, "Twitter API error: Status is a duplicate. (code 187)");
+    tweets[0].retries += 1;
+    assert_eq!(requeue_thread::requeue(&mut tweets, "manifesto"), 2);
+    assert_eq!(tweets, original);
+}
+
+#[test]
+fn other_threads_untouched() {
+    let mut tweets = vec![tweet("1/2 Abolish", Some("manifesto")), tweet("Unrelated", None), tweet("Workers of the world", Some("unite"))];
+    for i in 0..tweets.len() {
+        tweets[i].blocked = Some("Twitter API error: Status is a duplicate. (code 187)".to_string());
+    }
+    let original = tweets.clone();
+
+    assert_eq!(requeue_thread::requeue(&mut tweets, "manifesto"), 1);
+    assert_eq!(tweets[1], original[1]);
+    assert_eq!(tweets[2], original[2]);
+}
+
+#[test]
+fn unblocked_retries_kept() {
+    let mut tweets = vec![QueuedTweet { retries: 3, ..tweet("1/2 Abolish", Some("manifesto")) }];
+
+    assert_eq!(requeue_thread::requeue(&mut tweets, "manifesto"), 0);
+    assert_eq!(tweets[0].retries, 3);
+}
+
+
+fn tweet(content: &str, thread: Option<&str>) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+        content: content.to_string(),
+        thread: thread.map(str::to_string),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,110 @@
+extern crate rustc_serialize;
+extern crate tweetr;
+extern crate chrono;
+
+use self::rustc_serialize::json::Json;
+use self::tweetr::ops::results::{self, Output, Recorded, Results};
+use self::tweetr::ops::{http_cache, QueuedTweet};
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, FixedOffset};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn new_queued_and_posted() {
+    let before = [tweet("Abolish the bourgeoisie")];
+    let posted = QueuedTweet {
+        time_posted: Some(time("2016-09-10T12:00:05+02:00")),
+        id: Some(774560457755590656),
+        ..before[0].clone()
+    };
+
+    let results = Results::new("post-all",
+                               &Outcome::NoError,
+                               &before,
+                               &[posted, tweet("Workers of the world, unite!")],
+                               &[],
+                               Recorded::default());
+    let json = results.to_json();
+    let changes = json.find("changes").unwrap().as_array().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].find("action"), Some(&Json::String("posted".to_string())));
+    assert_eq!(changes[0].find_path(&["tweet", "id"]), Some(&Json::I64(774560457755590656)));
+    assert_eq!(changes[1].find("action"), Some(&Json::String("queued".to_string())));
+    assert_eq!(changes[1].find_path(&["tweet", "id"]), None);
+}
+
+#[test]
+fn new_error() {
+    let results = Results::new("add-user",
+                               &Outcome::PreflightFailed(vec!["users.toml is world-writable".to_string()]),
+                               &[],
+                               &[],
+                               &[],
+                               Recorded::default());
+    assert!(results.exit_code != 0);
+    assert!(results.error.unwrap().contains("users.toml is world-writable"));
+}
+
+#[test]
+fn empty_lists_present() {
+    let json = Results::new("validate", &Outcome::NoError, &[], &[], &[], Recorded::default()).to_json();
+    for key in &["written", "created", "tokens", "changes"] {
+        assert_eq!(json.find(key), Some(&Json::Array(vec![])));
+    }
+}
+
+#[test]
+fn http_cache_counted() {
+    http_cache::hit();
+    http_cache::miss();
+    let json = Results::new("doctor", &Outcome::NoError, &[], &[], &[], Recorded::default()).to_json();
+    assert!(json.find_path(&["http_cache", "hits"]).and_then(Json::as_u64).unwrap() >= 1);
+    assert!(json.find_path(&["http_cache", "misses"]).and_then(Json::as_u64).unwrap() >= 1);
+}
+
+#[test]
+fn queue_write_recorded() {
+    let td = temp_dir().join("tweetr-test").join("ops-results-queue_write_recorded");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("tweets.toml");
+
+    results::collect();
+    QueuedTweet::write(vec![tweet("Abolish the bourgeoisie")], &tf).unwrap();
+    QueuedTweet::write(vec![], &tf).unwrap();
+    assert_eq!(results::take().written, vec![tf]);
+
+    QueuedTweet::write(vec![], &td.join("tweets.toml")).unwrap();
+    assert_eq!(results::take().written, Vec::<PathBuf>::new());
+}
+
+#[test]
+fn output_stderr_while_collecting() {
+    results::collect();
+    match results::output() {
+        Output::Stderr(_) => (),
+        Output::Stdout(_) => panic!("output to stdout while collecting"),
+    }
+
+    results::take();
+    match results::output() {
+        Output::Stdout(_) => (),
+        Output::Stderr(_) => panic!("output to stderr while not collecting"),
+    }
+}
+
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn tweet(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time("2016-09-10T12:00:00+02:00"),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
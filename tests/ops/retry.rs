@@ -0,0 +1,82 @@
+extern crate tweetr;
+
+use self::tweetr::ops::retry::{self, RetryPolicy};
+use self::tweetr::ops::Config;
+use self::tweetr::Outcome;
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, Instant};
+use std::env::temp_dir;
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn configured() {
+    let td = temp_dir().join("tweetr-test").join("ops-retry-configured");
+    fs::create_dir_all(&td).unwrap();
+    File::create(Config::path(&td)).unwrap().write_all(b"file_retries = 5\nfile_retry_delay = 200\n").unwrap();
+
+    assert_eq!(Config::read(&Config::path(&td)).unwrap().file_retries(),
+               RetryPolicy {
+                   retries: 5,
+                   delay: Duration::from_millis(200),
+               });
+}
+
+#[test]
+fn waits_between_attempts() {
+    retry::use_policy(RetryPolicy {
+        retries: 2,
+        delay: Duration::from_millis(50),
+    });
+
+    let start = Instant::now();
+    let mut attempts = 0;
+    retry::run(|| {
+            attempts += 1;
+            Err::<(), _>(Error::new(ErrorKind::TimedOut, "timed out"))
+        })
+        .unwrap_err();
+    assert_eq!(attempts, 3);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[test]
+fn disabled() {
+    retry::use_policy(RetryPolicy { retries: 0, ..RetryPolicy::default() });
+
+    let mut attempts = 0;
+    let error = retry::run(|| {
+            attempts += 1;
+            Err::<(), _>(Error::new(ErrorKind::WouldBlock, "would block"))
+        })
+        .unwrap_err();
+    assert_eq!(attempts, 1);
+    assert_eq!(error.to_string(), "would block");
+}
+
+#[test]
+fn persistent_failure_reported() {
+    retry::use_policy(RetryPolicy {
+        retries: 1,
+        delay: Duration::from_millis(1),
+    });
+
+    let td = temp_dir().join("tweetr-test").join("ops-retry-persistent_failure_reported");
+    assert_eq!(Outcome::from_io("read",
+                                &td.join("config.toml"),
+                                retry::run(|| Err::<(), _>(Error::new(ErrorKind::Interrupted, "interrupted"))).unwrap_err()),
+               Outcome::IoError {
+                   path: td.join("config.toml").display().to_string(),
+                   op: "read",
+                   cause: "interrupted, still after 2 attempts".to_string(),
+               });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn stale_nfs_handle() {
+    assert!(retry::transient(&Error::from_raw_os_error(116)));
+    assert!(retry::transient(&Error::from_raw_os_error(37)));
+    assert!(!retry::transient(&Error::from_raw_os_error(2)));
+}
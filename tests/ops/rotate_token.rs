@@ -0,0 +1,112 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{rotate_token, User};
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn keeps_configuration() {
+    let tf = users_file("keeps_configuration");
+    let existing = User {
+        app: Some("agency".to_string()),
+        telegram_bot_token: Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string()),
+        telegram_chat_id: Some("@tweetr_announcements".to_string()),
+        discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+        timezone: Some("+02:00".to_string()),
+        quiet_hours: Some("23:00-07:00".to_string()),
+        jitter: Some(5),
+        tags: Some(vec!["tweetr".to_string()]),
+        sensitive: Some(true),
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+        ..user("nabijaczleweli", 481)
+    };
+    User::write(vec![existing.clone()], &tf).unwrap();
+
+    let authorised = User { access_token_key: "481-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(), ..user("nabijaczleweli", 481) };
+    let rotated = User { access_token_key: authorised.access_token_key.clone(), ..existing };
+    assert_eq!(rotate_token::replace_tokens(&tf, "nabijaczleweli", &authorised), Ok(rotated.clone()));
+    assert_eq!(User::read(&tf), Ok(vec![rotated]));
+}
+
+#[test]
+fn other_users_untouched() {
+    let tf = users_file("other_users_untouched");
+    let users = vec![user("nabijaczleweli", 481), user("not-nabijaczleweli", 482)];
+    User::write(users.clone(), &tf).unwrap();
+
+    let authorised = User { access_token_secret: "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx".to_string(), ..user("not-nabijaczleweli", 482) };
+    assert_eq!(rotate_token::replace_tokens(&tf, "not-nabijaczleweli", &authorised), Ok(authorised.clone()));
+    assert_eq!(User::read(&tf), Ok(vec![users[0].clone(), authorised]));
+}
+
+#[test]
+fn renamed_account() {
+    let tf = users_file("renamed_account");
+    User::write(vec![user("nabijaczleweli", 481)], &tf).unwrap();
+
+    let authorised = User { access_token_key: "481-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(), ..user("nabijaczleweli2", 481) };
+    assert_eq!(rotate_token::replace_tokens(&tf, "nabijaczleweli", &authorised).map(|u| (u.name, u.access_token_key)),
+               Ok(("nabijaczleweli".to_string(), authorised.access_token_key)));
+}
+
+#[test]
+fn wrong_account() {
+    let tf = users_file("wrong_account");
+    User::write(vec![user("nabijaczleweli", 481)], &tf).unwrap();
+
+    assert_eq!(rotate_token::replace_tokens(&tf, "nabijaczleweli", &user("not-nabijaczleweli", 482)),
+               Err(Outcome::PreflightFailed(vec!["the PIN authorises user not-nabijaczleweli#482 instead of nabijaczleweli#481, sign into \
+                                                  nabijaczleweli on Twitter before authorising"
+                                                     .to_string()])));
+    assert_eq!(User::read(&tf), Ok(vec![user("nabijaczleweli", 481)]));
+}
+
+#[test]
+fn nonexistant_account() {
+    let tf = users_file("nonexistant_account");
+    User::write(vec![user("nabijaczleweli", 481)], &tf).unwrap();
+
+    assert_eq!(rotate_token::replace_tokens(&tf, "not-nabijaczleweli", &user("not-nabijaczleweli", 482)),
+               Err(Outcome::RequiredDataFromSubsystemNonexistant {
+                   subsys: "add-user",
+                   desc: "add and authorise user with name \"not-nabijaczleweli\"".to_string(),
+               }));
+}
+
+
+fn users_file(name: &str) -> ::std::path::PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-rotate_token-{}", name));
+    fs::create_dir_all(&td).unwrap();
+    td.join("users.toml")
+}
+
+fn user(name: &str, id: i64) -> User {
+    User {
+        name: name.to_string(),
+        id: id,
+        access_token_key: format!("{}-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", id),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
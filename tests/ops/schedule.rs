@@ -0,0 +1,52 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::schedule::{self, ScheduleError};
+use self::chrono::{DateTime, Duration};
+
+
+#[test]
+fn later_overflow() {
+    let time = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+
+    assert_eq!(schedule::later(time, 9999999999999 * 7 * 24 * 60 * 60), Err(ScheduleError::Overflow));
+    assert_eq!(schedule::later(time, u64::max_value()), Err(ScheduleError::Overflow));
+    assert_eq!(schedule::later(time, i64::max_value() as u64 / 1000), Err(ScheduleError::Overflow));
+}
+
+#[test]
+fn shifted_overflow() {
+    let time = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+
+    assert_eq!(schedule::shifted(time, i64::max_value()), Err(ScheduleError::Overflow));
+    assert_eq!(schedule::shifted(time, -i64::max_value()), Err(ScheduleError::Overflow));
+    assert_eq!(schedule::shifted(time, i64::min_value()), Err(ScheduleError::Overflow));
+    assert_eq!(schedule::shifted(time, -9999999999999 * 7 * 24 * 60 * 60), Err(ScheduleError::Overflow));
+}
+
+#[test]
+fn horizon_boundary() {
+    let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+    schedule::use_horizon(Some(Duration::days(365)));
+
+    assert_eq!(schedule::within_horizon(now + Duration::days(365), now), Ok(now + Duration::days(365)));
+    assert_eq!(schedule::within_horizon(now + Duration::days(365) + Duration::seconds(1), now),
+               Err(ScheduleError::PastHorizon { horizon: Duration::days(365) }));
+}
+
+#[test]
+fn horizon_unrepresentable() {
+    let now = DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap();
+    schedule::use_horizon(Some(Duration::max_value()));
+
+    assert_eq!(schedule::within_horizon(now + Duration::weeks(1000), now), Ok(now + Duration::weeks(1000)));
+}
+
+#[test]
+fn past_horizon_display() {
+    assert_eq!(ScheduleError::PastHorizon { horizon: Duration::days(3650) }.to_string(),
+               "more than 3650 days in the future, past the horizon");
+    assert_eq!(ScheduleError::PastHorizon { horizon: Duration::weeks(52) }.to_string(),
+               "more than 52 weeks in the future, past the horizon");
+    assert_eq!(ScheduleError::Overflow.to_string(), "too far away");
+}
@@ -0,0 +1,154 @@
+extern crate tweetr;
+extern crate toml;
+
+use self::tweetr::ops::schema::{self, Format, Kind};
+use self::tweetr::ops::{Config, QueuedTweet, User};
+use std::collections::BTreeSet;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+
+
+#[test]
+fn parse_all() {
+    for format in Format::all() {
+        assert_eq!(Format::parse(format.name()), Some(*format));
+    }
+}
+
+#[test]
+fn config_example_read() {
+    let tf = example_file("config_example_read", Format::Config, false);
+    assert_eq!(Config::read(&tf), Ok(Config::default()));
+}
+
+#[test]
+fn users_example_read() {
+    let tf = example_file("users_example_read", Format::Users, false);
+    let users = User::read(&tf).unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].name, "nabijaczleweli");
+    assert_eq!(users[0].app, None);
+}
+
+#[test]
+fn queue_example_read() {
+    let tf = example_file("queue_example_read", Format::Queue, false);
+    let tweets = QueuedTweet::read(&tf).unwrap();
+    assert_eq!(tweets.len(), 1);
+    assert_eq!(tweets[0].content, "Abolish the bourgeoisie");
+    assert_eq!(tweets[0].time_posted, None);
+}
+
+#[test]
+fn archive_example_read() {
+    let tf = example_file("archive_example_read", Format::Archive, false);
+    let tweets = QueuedTweet::read(&tf).unwrap();
+    assert_eq!(tweets.len(), 1);
+    assert_eq!(tweets[0].id, Some(774541307568103424));
+}
+
+#[test]
+fn config_all_keys() {
+    let tf = example_file("config_all_keys", Format::Config, true);
+    let config = Config::read(&tf).unwrap();
+    config.write(&tf).unwrap();
+    assert_eq!(written_keys(&tf, Format::Config), schema_keys(Format::Config));
+}
+
+#[test]
+fn users_all_keys() {
+    let tf = example_file("users_all_keys", Format::Users, true);
+    let users = User::read(&tf).unwrap();
+    User::write(users, &tf).unwrap();
+    assert_eq!(written_keys(&tf, Format::Users), schema_keys(Format::Users));
+}
+
+#[test]
+fn queue_all_keys() {
+    let tf = example_file("queue_all_keys", Format::Queue, true);
+    let tweets = QueuedTweet::read(&tf).unwrap();
+    QueuedTweet::write(tweets, &tf).unwrap();
+    assert_eq!(written_keys(&tf, Format::Queue), schema_keys(Format::Queue));
+}
+
+#[test]
+fn json_schema_required() {
+    let queue = schema::json_schema(Format::Queue);
+    let tweet = queue.find_path(&["properties", "tweet", "items"]).unwrap();
+    assert_eq!(tweet.find("required").unwrap().as_array().unwrap().iter().map(|k| k.as_string().unwrap()).collect::<Vec<_>>(),
+               vec!["author", "time", "content"]);
+    assert_eq!(tweet.find_path(&["properties", "reply_settings", "enum"]).unwrap().as_array().unwrap().len(), 3);
+
+    let config = schema::json_schema(Format::Config);
+    assert_eq!(config.find("required"), None);
+    assert_eq!(config.find_path(&["properties", "embargo", "items", "required"]).unwrap().as_array().unwrap().len(), 2);
+    assert_eq!(config.find_path(&["properties", "transform", "type"]).and_then(|t| t.as_string()), Some("object"));
+}
+
+
+fn example_file(name: &str, format: Format, uncomment: bool) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-schema-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join(format.file());
+
+    let example = schema::example(format);
+    let example = if uncomment {
+        example.lines()
+            .map(|l| if l.starts_with("# [") || (l.starts_with("# ") && l.contains(" = ") && l[2..l.find(" = ").unwrap()].chars().all(|c| c == '_' || c.is_lowercase())) {
+                &l[2..]
+            } else {
+                l
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        example
+    };
+    File::create(&tf).unwrap().write_all(example.as_bytes()).unwrap();
+    tf
+}
+
+/// All keys in the file, as `table.key`, with the arrays of tables' entries merged.
+fn written_keys(p: &PathBuf, format: Format) -> BTreeSet<String> {
+    let mut toml = String::new();
+    File::open(p).unwrap().read_to_string(&mut toml).unwrap();
+    let table = toml::Parser::new(&toml).parse().unwrap();
+
+    let mut keys = BTreeSet::new();
+    for (key, value) in table {
+        let tables = match value {
+            toml::Value::Table(t) => vec![t],
+            toml::Value::Array(ref a) if format.fields().iter().any(|f| f.key == key && is_tables(f.kind)) => {
+                a.iter().map(|t| t.as_table().unwrap().clone()).collect()
+            }
+            _ => vec![],
+        };
+        for t in tables {
+            keys.extend(t.keys().map(|k| format!("{}.{}", key, k)));
+        }
+        keys.insert(key);
+    }
+    keys
+}
+
+fn schema_keys(format: Format) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    for field in format.fields() {
+        match field.kind {
+            Kind::Table(fields) | Kind::Tables(fields) => keys.extend(fields.iter().map(|f| format!("{}.{}", field.key, f.key))),
+            _ => {}
+        }
+        keys.insert(field.key.to_string());
+    }
+    keys
+}
+
+fn is_tables(kind: Kind) -> bool {
+    match kind {
+        Kind::Tables(_) => true,
+        _ => false,
+    }
+}
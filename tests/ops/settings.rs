@@ -0,0 +1,152 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::settings::{Effective, Settings, Source};
+use self::tweetr::ops::QueuedTweet;
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, Duration, FixedOffset};
+
+
+#[test]
+fn tweet_overrides_account() {
+    let effective = Effective::resolve(Some(&Settings { jitter: Some(1), ..Settings::default() }),
+                                       Some(&Settings {
+                                           jitter: Some(2),
+                                           sensitive: Some(true),
+                                           ..Settings::default()
+                                       }),
+                                       &Settings {
+                                           jitter: Some(3),
+                                           sensitive: Some(false),
+                                           timezone: Some("UTC".to_string()),
+                                           ..Settings::default()
+                                       })
+        .unwrap();
+
+    assert_eq!(effective.jitter, (1, Source::Tweet));
+    assert_eq!(effective.sensitive, (true, Source::Account));
+    assert_eq!(effective.timezone.1, Source::Config);
+    assert_eq!(effective.tags, (vec![], Source::Default));
+}
+
+#[test]
+fn empty_tags_override() {
+    let effective = Effective::resolve(Some(&Settings { tags: Some(vec![]), ..Settings::default() }),
+                                       None,
+                                       &Settings { tags: Some(vec!["tweetr".to_string()]), ..Settings::default() })
+        .unwrap();
+
+    assert_eq!(effective.tags, (vec![], Source::Tweet));
+}
+
+#[test]
+fn invalid_config_quiet_hours() {
+    assert_eq!(Effective::resolve(None, None, &Settings { quiet_hours: Some("22:00".to_string()), ..Settings::default() }),
+               Err(Outcome::FileParsingFailed {
+                   desc: "configuration",
+                   errors: vec!["error: invalid span of time \"22:00\" for the key `quiet_hours`".to_string()],
+               }));
+}
+
+#[test]
+fn invalid_overridden_ignored() {
+    assert!(Effective::resolve(None,
+                               Some(&Settings { timezone: Some("+01:00".to_string()), ..Settings::default() }),
+                               &Settings { timezone: Some("CET".to_string()), ..Settings::default() })
+        .is_ok());
+}
+
+#[test]
+fn quiet_hours_same_day() {
+    let effective = effective("+02:00", "12:00-14:00", 0);
+
+    assert_eq!(effective.posting_time(&tweet("2016-09-10T13:00:00+02:00")), datetime("2016-09-10T14:00:00+02:00"));
+    assert_eq!(effective.posting_time(&tweet("2016-09-10T14:00:00+02:00")), datetime("2016-09-10T14:00:00+02:00"));
+    assert_eq!(effective.posting_time(&tweet("2016-09-10T11:59:00+02:00")), datetime("2016-09-10T11:59:00+02:00"));
+}
+
+#[test]
+fn quiet_hours_in_timezone() {
+    let effective = effective("+02:00", "23:00-07:00", 0);
+
+    let posting_time = effective.posting_time(&tweet("2016-09-10T21:30:00Z"));
+    assert_eq!(posting_time, datetime("2016-09-11T05:00:00Z"));
+    assert_eq!(posting_time.to_rfc3339(), "2016-09-11T05:00:00+00:00");
+
+    assert_eq!(effective.posting_time(&tweet("2016-09-10T20:30:00Z")), datetime("2016-09-10T20:30:00Z"));
+}
+
+#[test]
+fn jitter_consistent_and_bounded() {
+    let effective = effective("UTC", "", 30);
+
+    for hour in 0..24 {
+        let tweet = tweet(&format!("2016-09-10T{:02}:00:00Z", hour));
+        let posting_time = effective.posting_time(&tweet);
+
+        assert!(tweet.time <= posting_time && posting_time <= tweet.time + Duration::minutes(30));
+        assert_eq!(effective.posting_time(&tweet), posting_time);
+    }
+}
+
+#[test]
+fn jitter_unaffected_by_applying() {
+    let effective = Effective::resolve(None,
+                                       None,
+                                       &Settings {
+                                           jitter: Some(30),
+                                           tags: Some(vec!["tweetr".to_string()]),
+                                           ..Settings::default()
+                                       })
+        .unwrap();
+    let tweet = tweet("2016-09-10T12:00:00Z");
+
+    assert_eq!(effective.posting_time(&effective.apply(&tweet)), effective.posting_time(&tweet));
+}
+
+#[test]
+fn apply_keeps_explicit_sensitivity() {
+    let effective = Effective::resolve(None, None, &Settings { sensitive: Some(true), ..Settings::default() }).unwrap();
+    let mut tweet = tweet("2016-09-10T12:00:00Z");
+    tweet.extra_params.insert("possibly_sensitive".to_string(), "false".to_string());
+
+    assert_eq!(effective.apply(&tweet), tweet);
+}
+
+#[test]
+fn apply_nothing() {
+    let effective = Effective::resolve(None, None, &Settings::default()).unwrap();
+    let tweet = tweet("2016-09-10T12:00:00Z");
+
+    assert_eq!(effective.apply(&tweet), tweet);
+}
+
+
+fn effective(timezone: &str, quiet_hours: &str, jitter: u32) -> Effective {
+    Effective::resolve(None,
+                       None,
+                       &Settings {
+                           timezone: Some(timezone.to_string()),
+                           quiet_hours: if quiet_hours.is_empty() {
+                               None
+                           } else {
+                               Some(quiet_hours.to_string())
+                           },
+                           jitter: Some(jitter),
+                           ..Settings::default()
+                       })
+        .unwrap()
+}
+
+fn tweet(time: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: datetime(time),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
+
+fn datetime(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
@@ -0,0 +1,60 @@
+extern crate tweetr;
+
+use self::tweetr::ops::setup_state::SetupState;
+use self::tweetr::ops::{fmt_queue, prune_posted};
+use self::tweetr::Outcome;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+
+
+#[test]
+fn detect_partial() {
+    for &(files, state) in &[(&["users.toml"][..], SetupState::NoApp),
+                             (&["tweets.toml"][..], SetupState::NoApp),
+                             (&["users.toml", "tweets.toml"][..], SetupState::NoApp),
+                             (&["app.toml", "tweets.toml"][..], SetupState::NoUsers)] {
+        let tf = config_dir("detect_partial");
+        for file in files {
+            File::create(tf.join(file)).unwrap();
+        }
+
+        assert_eq!(SetupState::detect(&tf), state, "files: {:?}", files);
+    }
+}
+
+#[test]
+fn verify_points_at_next_step() {
+    let tf = config_dir("verify_points_at_next_step");
+    File::create(tf.join("app.toml")).unwrap();
+    File::create(tf.join("users.toml")).unwrap();
+
+    assert_eq!(fmt_queue::verify(&("$TEMP/verify_points_at_next_step".to_string(), tf.clone())),
+               Err(Outcome::SetupIncomplete(SetupState::NoQueue)));
+    assert_eq!(prune_posted::verify(&("$TEMP/verify_points_at_next_step".to_string(), tf.clone())),
+               Ok((tf.join("app.toml"), tf.join("users.toml"))));
+}
+
+#[test]
+fn messages() {
+    for &(state, message) in &[(SetupState::FirstRun,
+                                "Nothing's configured yet.\nRun the setup subsystem to configure the app, authorise a user and queue tweets step by \
+                                 step, or start with the init subsystem.\n"),
+                               (SetupState::NoUsers,
+                                "The app is configured, but no users are authorised yet.\nNext, run the add-user subsystem to authorise an account \
+                                 to post as.\n")] {
+        let mut out = Vec::new();
+        Outcome::SetupIncomplete(state).print_error(&mut out);
+
+        assert_eq!(String::from_utf8(out).unwrap(), message);
+        assert_eq!(Outcome::SetupIncomplete(state).exit_value(), 2);
+    }
+}
+
+
+fn config_dir(name: &str) -> PathBuf {
+    let tf = temp_dir().join("tweetr-test").join(format!("ops-setup-state-{}", name));
+    let _ = fs::remove_dir_all(&tf);
+    fs::create_dir_all(&tf).unwrap();
+    tf
+}
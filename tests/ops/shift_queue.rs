@@ -0,0 +1,153 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::shift_queue::{self, Filter};
+use self::tweetr::ops::schedule;
+use self::tweetr::ops::{QueuedTweet, Anchor};
+use self::chrono::{DateTime, Duration};
+
+
+#[test]
+fn all() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "not-nabijaczleweli", "2016-09-11T12:00:00+02:00")];
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), 2 * 60 * 60), Ok((2, 0)));
+    assert_eq!(tweets[0].time, original[0].time + Duration::hours(2));
+    assert_eq!(tweets[1].time, original[1].time + Duration::hours(2));
+}
+
+#[test]
+fn earlier() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), -24 * 60 * 60), Ok((1, 0)));
+    assert_eq!(tweets[0].time, DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap());
+}
+
+#[test]
+fn posted_untouched() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+    tweets[0].id = Some(1234);
+    tweets[0].time_posted = Some(tweets[0].time);
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), 60), Ok((0, 0)));
+    assert_eq!(tweets, original);
+}
+
+#[test]
+fn from() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "nabijaczleweli", "2016-09-11T12:00:00+02:00")];
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets,
+                                  &Filter { from: Some(DateTime::parse_from_rfc3339("2016-09-11T12:00:00+02:00").unwrap()), ..everything() },
+                                  60 * 60),
+               Ok((1, 0)));
+    assert_eq!(tweets[0].time, original[0].time);
+    assert_eq!(tweets[1].time, original[1].time + Duration::hours(1));
+}
+
+#[test]
+fn author() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "not-nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &Filter { author: Some("not-nabijaczleweli".to_string()), ..everything() }, 60),
+               Ok((1, 0)));
+    assert_eq!(tweets[0].time, original[0].time);
+    assert_eq!(tweets[1].time, original[1].time + Duration::minutes(1));
+}
+
+#[test]
+fn tag() {
+    let mut tweets = vec![tweet("Abolish #Launch!", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("Workers", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("#launchparty", "nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+    tweets[1].settings.tags = Some(vec!["launch".to_string()]);
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &Filter { tag: Some("#launch".to_string()), ..everything() }, 60), Ok((2, 0)));
+    assert_eq!(tweets[0].time, original[0].time + Duration::minutes(1));
+    assert_eq!(tweets[1].time, original[1].time + Duration::minutes(1));
+    assert_eq!(tweets[2].time, original[2].time);
+    assert_eq!(tweets[3].time, original[3].time);
+}
+
+#[test]
+fn anchored_follow() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "not-nabijaczleweli", "2016-09-10T12:15:00+02:00"),
+                          tweet("Workers", "nabijaczleweli", "2016-09-10T12:30:00+02:00")];
+    tweets[0].name = Some("abolish".to_string());
+    tweets[1].after = Some(Anchor {
+        name: "abolish".to_string(),
+        delay: 15 * 60,
+    });
+    tweets[2].name = Some("workers".to_string());
+    tweets[2].after = Some(Anchor {
+        name: "abolish".to_string(),
+        delay: 30 * 60,
+    });
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &Filter { author: Some("nabijaczleweli".to_string()), ..everything() }, 60 * 60),
+               Ok((1, 2)));
+    assert_eq!(tweets[0].time, original[0].time + Duration::hours(1));
+    assert_eq!(tweets[1].time, original[1].time + Duration::hours(1));
+    assert_eq!(tweets[2].time, original[2].time + Duration::hours(1));
+}
+
+#[test]
+fn too_early() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+    let original = tweets.clone();
+
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), -40000000 * 7 * 24 * 60 * 60),
+               Err(vec!["moving \"Abolish\" 40000000 weeks earlier is too early".to_string(),
+                        "moving \"the bourgeoisie\" 40000000 weeks earlier is too early".to_string()]));
+    assert_eq!(tweets, original);
+}
+
+#[test]
+fn past_horizon() {
+    let mut tweets = vec![tweet("Abolish", "nabijaczleweli", "2016-09-10T12:00:00+02:00"),
+                          tweet("the bourgeoisie", "not-nabijaczleweli", "2016-09-10T12:15:00+02:00")];
+    tweets[0].name = Some("abolish".to_string());
+    tweets[1].after = Some(Anchor {
+        name: "abolish".to_string(),
+        delay: 15 * 60,
+    });
+    let original = tweets.clone();
+    schedule::use_horizon(Some(Duration::days(1)));
+
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), 1040 * 7 * 24 * 60 * 60),
+               Err(vec!["moving \"Abolish\" 1040 weeks later puts it more than 1 day in the future, past the horizon".to_string(),
+                        "moving \"the bourgeoisie\" 1040 weeks later puts it more than 1 day in the future, past the horizon".to_string()]));
+    assert_eq!(tweets, original);
+    assert_eq!(shift_queue::shift(&mut tweets, &everything(), 7 * 24 * 60 * 60), Ok((1, 1)));
+}
+
+
+fn everything() -> Filter {
+    Filter {
+        from: None,
+        author: None,
+        tag: None,
+    }
+}
+
+fn tweet(content: &str, author: &str, time: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: DateTime::parse_from_rfc3339(time).unwrap(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,172 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::simulate::{self, Rules, Step};
+use self::tweetr::ops::embargo::{Policy, Window};
+use self::tweetr::ops::settings::Settings;
+use self::tweetr::ops::{QueuedTweet, Anchor, User};
+use self::chrono::{DateTime, Duration, FixedOffset};
+
+
+#[test]
+fn daily_cap_carries_over() {
+    let users = [User { max_posts_per_day: Some(1), ..user("nabijaczleweli") }];
+    let settings = settings();
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00"), tweet("nabijaczleweli", "2016-09-10T15:00:00+02:00")];
+
+    let timeline = simulate::run(&mut tweets,
+                                 &[],
+                                 &rules(&users, &settings, &[]),
+                                 time("2016-09-10T00:00:00+02:00"),
+                                 time("2016-09-12T00:00:00+02:00"))
+        .unwrap();
+    assert_eq!(timeline.iter().map(|e| (e.time, e.step)).collect::<Vec<_>>(),
+               vec![(time("2016-09-10T12:00:00+02:00"), Step::Posted),
+                    (time("2016-09-10T15:00:00+02:00"), Step::CarriedOver(time("2016-09-11T15:00:00+02:00"))),
+                    (time("2016-09-11T15:00:00+02:00"), Step::Posted)]);
+}
+
+#[test]
+fn daily_cap_counts_history() {
+    let users = [User { max_posts_per_day: Some(1), ..user("nabijaczleweli") }];
+    let settings = settings();
+    let history = [QueuedTweet {
+                       time_posted: Some(time("2016-09-10T09:00:00+02:00")),
+                       id: Some(774560457755590656),
+                       ..tweet("nabijaczleweli", "2016-09-10T09:00:00+02:00")
+                   }];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+
+    let timeline = simulate::run(&mut tweets,
+                                 &history,
+                                 &rules(&users, &settings, &[]),
+                                 time("2016-09-10T10:00:00+02:00"),
+                                 time("2016-09-10T18:00:00+02:00"))
+        .unwrap();
+    assert_eq!(timeline.iter().map(|e| e.step).collect::<Vec<_>>(),
+               vec![Step::CarriedOver(time("2016-09-11T12:00:00+02:00"))]);
+    assert_eq!(tweets[0].time_posted, None);
+}
+
+#[test]
+fn embargo_reschedules() {
+    let settings = settings();
+    let embargoes = [Window {
+                         start: time("2016-09-10T10:00:00+02:00"),
+                         end: time("2016-09-10T14:00:00+02:00"),
+                     }];
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00")];
+
+    let timeline = simulate::run(&mut tweets,
+                                 &[],
+                                 &rules(&[], &settings, &embargoes),
+                                 time("2016-09-10T00:00:00+02:00"),
+                                 time("2016-09-11T00:00:00+02:00"))
+        .unwrap();
+    assert_eq!(timeline.iter().map(|e| (e.time, e.step)).collect::<Vec<_>>(),
+               vec![(time("2016-09-10T12:00:00+02:00"), Step::Embargoed(time("2016-09-10T14:00:00+02:00"))),
+                    (time("2016-09-10T14:00:00+02:00"), Step::Posted)]);
+}
+
+#[test]
+fn anchored_after_anchor() {
+    let settings = settings();
+    let mut tweets = vec![QueuedTweet {
+                              after: Some(Anchor {
+                                  name: "launch".to_string(),
+                                  delay: 60 * 60,
+                              }),
+                              ..tweet("nabijaczleweli", "2016-09-10T09:00:00+02:00")
+                          },
+                          QueuedTweet { name: Some("launch".to_string()), ..tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00") }];
+
+    let timeline = simulate::run(&mut tweets,
+                                 &[],
+                                 &rules(&[], &settings, &[]),
+                                 time("2016-09-10T00:00:00+02:00"),
+                                 time("2016-09-11T00:00:00+02:00"))
+        .unwrap();
+    assert_eq!(timeline.iter().map(|e| e.time).collect::<Vec<_>>(),
+               vec![time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00")]);
+}
+
+#[test]
+fn paused_not_posted() {
+    let settings = settings();
+    let mut tweets = vec![QueuedTweet { paused: true, ..tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00") }];
+
+    assert_eq!(simulate::run(&mut tweets,
+                             &[],
+                             &rules(&[], &settings, &[]),
+                             time("2016-09-10T00:00:00+02:00"),
+                             time("2016-09-11T00:00:00+02:00")),
+               Ok(vec![]));
+}
+
+#[test]
+fn no_side_effects_past_to() {
+    let settings = settings();
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-10T12:00:00+02:00"), tweet("nabijaczleweli", "2016-09-11T12:00:00+02:00")];
+
+    let timeline = simulate::run(&mut tweets,
+                                 &[],
+                                 &rules(&[], &settings, &[]),
+                                 time("2016-09-10T00:00:00+02:00"),
+                                 time("2016-09-11T00:00:00+02:00"))
+        .unwrap();
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(tweets[1].time_posted, None);
+}
+
+
+fn rules<'a>(users: &'a [User], settings: &'a Settings, embargoes: &'a [Window]) -> Rules<'a> {
+    Rules {
+        users: users,
+        settings: settings,
+        embargoes: embargoes,
+        embargo_policy: Policy::End,
+        collision_window: Duration::minutes(10),
+        slots: &[],
+    }
+}
+
+fn settings() -> Settings {
+    Settings { timezone: Some("+02:00".to_string()), ..Settings::default() }
+}
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn user(name: &str) -> User {
+    User {
+        name: name.to_string(),
+        id: 481,
+        access_token_key: "key".to_string(),
+        access_token_secret: "secret".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
+
+fn tweet(author: &str, at: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: author.to_string(),
+        time: time(at),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
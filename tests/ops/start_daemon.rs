@@ -0,0 +1,455 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::clock::{self, Clock, ManualClock};
+use self::tweetr::ops::start_daemon::{Cached, DaemonLock, Due, Summary, TestMode};
+use self::tweetr::ops::{QueuedTweet, User, start_daemon};
+use self::tweetr::ops::backend::{PostingBackend, Sink};
+use self::chrono::{DateTime, Duration, FixedOffset};
+use std::time::{Duration as StdDuration, Instant};
+use self::tweetr::Outcome;
+use std::path::Path;
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::thread;
+
+
+#[test]
+fn dst_fall_back() {
+    // 02:30 CEST is before 02:10 CET, the clock having gone back an hour at 03:00 CEST
+    let tweets = vec![tweet("2016-10-30T02:30:00+02:00")];
+    let clock = ManualClock::new(time("2016-10-30T02:20:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.set(time("2016-10-30T02:10:00+01:00"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+}
+
+#[test]
+fn dst_fall_back_repeated_hour() {
+    // 02:30 CET is an hour after 02:30 CEST, even though they look the same on the wall clock
+    let tweets = vec![tweet("2016-10-30T02:30:00+01:00")];
+    let clock = ManualClock::new(time("2016-10-30T02:45:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.advance(Duration::minutes(45));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+}
+
+#[test]
+fn dst_spring_forward() {
+    // 02:30 CET doesn't exist on the wall clock that day, but is still 03:30 CEST
+    let tweets = vec![tweet("2016-03-27T02:30:00+01:00")];
+    let clock = ManualClock::new(time("2016-03-27T03:29:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.set(time("2016-03-27T03:30:00+02:00"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+}
+
+#[test]
+fn leap_second() {
+    let tweets = vec![tweet("2016-12-31T23:59:60Z"), tweet("2017-01-01T00:00:00Z")];
+    let clock = ManualClock::new(time("2016-12-31T23:59:59Z"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.set(time("2016-12-31T23:59:60Z"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+
+    clock.set(time("2017-01-01T00:00:00Z"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0, 1]);
+}
+
+#[test]
+fn backwards_jump_no_double_post() {
+    let mut tweets = vec![tweet("2016-09-09T12:00:00+02:00")];
+    let clock = ManualClock::new(time("2016-09-09T12:01:00+02:00"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+    post(&mut tweets[0], &clock);
+
+    clock.advance(-Duration::hours(1));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+    clock.advance(Duration::hours(2));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+}
+
+#[test]
+fn backwards_jump_no_vanish() {
+    let tweets = vec![tweet("2016-09-09T12:00:00+02:00")];
+    let clock = ManualClock::new(time("2016-09-09T11:59:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    // An NTP correction moving the clock back across the tweet's time before it was ever checked
+    clock.set(time("2016-09-09T11:30:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.set(time("2016-09-09T12:00:00+02:00"));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0]);
+}
+
+#[test]
+fn forwards_jump_posts_all_skipped() {
+    let tweets = vec![tweet("2016-09-09T12:00:00+02:00"), tweet("2016-09-09T13:00:00+02:00"), tweet("2016-09-09T18:00:00+02:00")];
+    let clock = ManualClock::new(time("2016-09-09T11:00:00+02:00"));
+    assert!(start_daemon::tweet_indices_to_post(&tweets, &clock).is_empty());
+
+    clock.advance(Duration::hours(3));
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![0, 1]);
+}
+
+#[test]
+fn due_clock_jumps() {
+    let mut tweets = vec![tweet("2016-09-09T13:00:00+02:00"), tweet("2016-09-09T12:00:00+02:00"), tweet("2016-09-09T18:00:00+02:00")];
+    tweets[2].paused = true;
+    let clock = ManualClock::new(time("2016-09-09T11:00:00+02:00"));
+    let mut due = Due::new(&tweets);
+
+    for &(by, ref expected) in &[(0, vec![]), (2, vec![0, 1]), (-1, vec![1]), (-1, vec![]), (8, vec![0, 1])] {
+        clock.advance(Duration::hours(by));
+        assert_eq!(&due.due(&clock), expected);
+        assert_eq!(&start_daemon::tweet_indices_to_post(&tweets, &clock), expected);
+        assert_eq!(due.next_wake(&clock, Duration::hours(24)), start_daemon::next_wake(&tweets, &clock, Duration::hours(24)));
+    }
+}
+
+#[test]
+fn cached_rereads_changes_only() {
+    let td = temp_dir().join("tweetr-test").join("ops-start-daemon-cached_rereads_changes_only");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("tweets.toml");
+    QueuedTweet::write(vec![tweet("2016-09-09T12:00:00+02:00")], &tf).unwrap();
+
+    let mut cached = Cached::new(tf.clone());
+    assert_eq!(cached.get(QueuedTweet::read).unwrap().1, true);
+    assert_eq!(cached.get(QueuedTweet::read).unwrap().1, false);
+
+    // Written by the daemon itself
+    {
+        let (tweets, _) = cached.get(QueuedTweet::read).unwrap();
+        tweets[0].retries += 1;
+        QueuedTweet::write(tweets.clone(), &tf).unwrap();
+    }
+    cached.written();
+    assert_eq!(cached.get(QueuedTweet::read).map(|(t, r)| (t[0].retries, r)).unwrap(), (1, false));
+
+    // Rewritten unchanged, so only the modification time differs
+    let mut contents = vec![];
+    File::open(&tf).unwrap().read_to_end(&mut contents).unwrap();
+    thread::sleep(StdDuration::from_millis(1100));
+    File::create(&tf).unwrap().write_all(&contents).unwrap();
+    assert_eq!(cached.get(QueuedTweet::read).map(|(t, r)| (t[0].retries, r)).unwrap(), (1, false));
+
+    // Written by someone else, like queue-tweet
+    File::create(&tf).unwrap().write_all(b"").unwrap();
+    assert_eq!(cached.get(QueuedTweet::read).map(|(t, r)| (t.len(), r)).unwrap(), (0, true));
+
+    fs::remove_file(&tf).unwrap();
+    assert!(cached.get(QueuedTweet::read).is_err());
+}
+
+#[test]
+fn next_wake_skips_posted_and_paused() {
+    let mut tweets = vec![tweet("2016-09-09T12:10:00+02:00"), tweet("2016-09-09T12:20:00+02:00"), tweet("2016-09-09T12:30:00+02:00")];
+    let clock = ManualClock::new(time("2016-09-09T12:00:00+02:00"));
+    post(&mut tweets[0], &clock);
+    tweets[1].paused = true;
+
+    assert_eq!(start_daemon::next_wake(&tweets, &clock, Duration::hours(1)), time("2016-09-09T12:30:00+02:00"));
+}
+
+#[test]
+fn blocked_not_due() {
+    let mut tweets = vec![tweet("2016-09-09T11:00:00+02:00"), tweet("2016-09-09T11:30:00+02:00")];
+    tweets[0].blocked = Some("Twitter API error: Status is a duplicate. (code 187)".to_string());
+    let clock = ManualClock::new(time("2016-09-09T12:00:00+02:00"));
+
+    assert_eq!(start_daemon::tweet_indices_to_post(&tweets, &clock), vec![1]);
+    assert_eq!(Due::new(&tweets).due(&clock), vec![1]);
+}
+
+#[test]
+fn block_thread_skips_posted() {
+    let mut tweets = vec![tweet("2016-09-09T11:00:00+02:00"), tweet("2016-09-09T11:10:00+02:00"), tweet("2016-09-09T11:20:00+02:00")];
+    for t in &mut tweets {
+        t.thread = Some("manifesto".to_string());
+    }
+    tweets[2].id = Some(774560457755590656);
+
+    assert_eq!(start_daemon::block_thread(&mut tweets, 0, "Twitter API error: Status is a duplicate. (code 187)"), vec![0, 1]);
+    assert_eq!(tweets[2].blocked, None);
+}
+
+#[test]
+fn next_wake_due_ignored() {
+    let tweets = vec![tweet("2016-09-09T11:00:00+02:00")];
+    let clock = ManualClock::new(time("2016-09-09T12:00:00+02:00"));
+
+    assert_eq!(start_daemon::next_wake(&tweets, &clock, Duration::minutes(1)), time("2016-09-09T12:01:00+02:00"));
+}
+
+#[test]
+fn deadline_subsecond() {
+    let clock = ManualClock::new(time("2016-09-09T12:00:00.750+02:00"));
+    let now = Instant::now();
+
+    assert_eq!(clock::deadline(&clock, time("2016-09-09T12:00:01.005+02:00"), now), now + StdDuration::from_millis(255));
+}
+
+#[test]
+fn deadline_fixed_across_jumps() {
+    let clock = ManualClock::new(time("2016-09-09T12:00:00+02:00"));
+    let now = Instant::now();
+    let deadline = clock::deadline(&clock, time("2016-09-09T12:00:30+02:00"), now);
+
+    // The wall clock moving during the wait doesn't move the deadline, the next one is mapped from the corrected clock
+    clock.advance(-Duration::hours(1));
+    assert_eq!(deadline, now + StdDuration::from_secs(30));
+    assert_eq!(clock::deadline(&clock, time("2016-09-09T12:00:30+02:00"), now),
+               now + StdDuration::from_secs(60 * 60 + 30));
+}
+
+#[test]
+fn posting_time_from_clock() {
+    let td = temp_dir().join("tweetr-test").join("ops-start-daemon-posting_time_from_clock");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    let sink = Sink {
+        name: "nabijaczleweli".to_string(),
+        file: Some(td.join("announcements.txt").to_str().unwrap().to_string()),
+        command: None,
+        discord_webhook: None,
+    };
+    let clock = ManualClock::new(time("2016-09-09T12:00:00.481+02:00"));
+    let mut tweet = tweet("2016-09-09T12:00:00+02:00");
+
+    assert_eq!(start_daemon::post_tweet(&mut tweet, &sink, &clock, false, &mut vec![]), Outcome::NoError);
+    assert_eq!(tweet.time_posted, Some(time("2016-09-09T12:00:00.481+02:00")));
+    assert_eq!(tweet.time_posted.unwrap() - tweet.time, Duration::milliseconds(481));
+    assert_eq!(tweet.app, None);
+}
+
+#[test]
+fn posting_records_app() {
+    struct NamedApp;
+    impl PostingBackend for NamedApp {
+        fn description(&self) -> String {
+            "named app".to_string()
+        }
+
+        fn post(&self, _: &QueuedTweet) -> Result<i64, Outcome> {
+            Ok(774541307568103424)
+        }
+
+        fn app(&self) -> Option<String> {
+            Some("work".to_string())
+        }
+    }
+
+    let clock = ManualClock::new(time("2016-09-09T12:00:00+02:00"));
+    let mut tweet = tweet("2016-09-09T12:00:00+02:00");
+
+    assert_eq!(start_daemon::post_tweet(&mut tweet, &NamedApp, &clock, false, &mut vec![]), Outcome::NoError);
+    assert_eq!(tweet.id, Some(774541307568103424));
+    assert_eq!(tweet.app, Some("work".to_string()));
+}
+
+#[test]
+fn test_mode_disabled() {
+    let users = vec![User { test_mode: Some(false), test_account: Some("tweetr_test".to_string()), ..user("nabijaczleweli") },
+                     user("tweetr_test")];
+    assert_eq!(start_daemon::test_mode(&users, 0), Ok(TestMode::Off));
+}
+
+#[test]
+fn test_mode_reroute_ignores_test_account_mode() {
+    let users = vec![User { test_mode: Some(true), test_account: Some("tweetr_test".to_string()), ..user("nabijaczleweli") },
+                     User { test_mode: Some(true), ..user("tweetr_test") }];
+    assert_eq!(start_daemon::test_mode(&users, 0), Ok(TestMode::Reroute(1)));
+    assert_eq!(start_daemon::test_mode(&users, 1), Ok(TestMode::Mark));
+}
+
+#[test]
+fn test_mode_mark_once() {
+    let marked = start_daemon::mark_test("Abolish the bourgeoisie");
+    assert_eq!(start_daemon::mark_test(&marked), marked);
+    assert_eq!(start_daemon::mark_test("[test] Abolish the bourgeoisie"), "[TEST] [test] Abolish the bourgeoisie");
+}
+
+#[test]
+fn fold_content_warning_once() {
+    let format = "{content}\n\n(CW: {warning})";
+    let folded = start_daemon::fold_content_warning("Abolish the bourgeoisie", Some("politics"), format);
+    assert_eq!(folded, "Abolish the bourgeoisie\n\n(CW: politics)");
+    assert_eq!(start_daemon::fold_content_warning(&folded, Some("politics"), format), folded);
+    assert_eq!(start_daemon::fold_content_warning(&folded, Some("war"), format),
+               "Abolish the bourgeoisie\n\n(CW: politics)\n\n(CW: war)");
+}
+
+#[test]
+fn suspend_keeps_others() {
+    let tf = temp_dir().join("tweetr-test").join("ops-start-daemon-suspend_keeps_others");
+    let _ = fs::remove_dir_all(&tf);
+    fs::create_dir_all(&tf).unwrap();
+    let tf = tf.join("users.toml");
+    let users = vec![user("nabijaczleweli"), user("tweetr_test")];
+    User::write(users.clone(), &tf).unwrap();
+
+    start_daemon::suspend(&tf, "tweetr_test", time("2016-09-10T12:00:00+02:00")).unwrap();
+    start_daemon::suspend(&tf, "not-nabijaczleweli", time("2016-09-10T12:00:00+02:00")).unwrap();
+    assert_eq!(User::read(&tf),
+               Ok(vec![users[0].clone(), User { suspended: Some("2016-09-10T12:00:00+02:00".to_string()), ..users[1].clone() }]));
+}
+
+#[test]
+fn summary_suspended() {
+    let users = vec![User { suspended: Some("2016-09-10T12:00:00+02:00".to_string()), ..user("nabijaczleweli") }];
+    let mut out = Vec::new();
+    start_daemon::print_summary(&mut out,
+                                &Summary {
+                                    config_dir: ".",
+                                    hooks_dir: Path::new("./hooks"),
+                                    events: None,
+                                    users: Some(&users),
+                                    sinks: &[],
+                                    tweets: None,
+                                    delay: Duration::seconds(60),
+                                });
+    assert!(String::from_utf8(out).unwrap().contains(", paused since 2016-09-10T12:00:00+02:00, suspended or locked\n"));
+}
+
+#[test]
+fn summary_unreadable() {
+    let mut out = Vec::new();
+    start_daemon::print_summary(&mut out,
+                                &Summary {
+                                    config_dir: ".",
+                                    hooks_dir: Path::new("./hooks"),
+                                    events: Some(Path::new("/dev/fd/3")),
+                                    users: None,
+                                    sinks: &[],
+                                    tweets: None,
+                                    delay: Duration::seconds(5),
+                                });
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Starting the daemon with:\n  \
+                  Configuration directory: .\n  \
+                  Hooks directory: ./hooks\n  \
+                  Events: appended to /dev/fd/3\n  \
+                  Accounts: couldn't be read\n  \
+                  0 sinks\n  \
+                  Queue: couldn't be read\n  \
+                  Checking for tweets to post every 5000ms, or when the next one is due\n");
+}
+
+#[test]
+fn summary_redacts_secrets() {
+    let users = vec![User {
+                         discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+                         test_mode: Some(true),
+                         ..user("nabijaczleweli")
+                     }];
+    let sinks = vec![Sink {
+                         name: "motd".to_string(),
+                         file: Some("/etc/motd".to_string()),
+                         command: None,
+                         discord_webhook: None,
+                     },
+                     Sink {
+                         name: "community".to_string(),
+                         file: None,
+                         command: None,
+                         discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+                     }];
+    let mut posted = tweet("2016-09-10T12:00:00+02:00");
+    post(&mut posted, &ManualClock::new(time("2016-09-10T12:00:00+02:00")));
+
+    let mut out = Vec::new();
+    start_daemon::print_summary(&mut out,
+                                &Summary {
+                                    config_dir: ".",
+                                    hooks_dir: Path::new("./hooks"),
+                                    events: None,
+                                    users: Some(&users),
+                                    sinks: &sinks,
+                                    tweets: Some(&[posted]),
+                                    delay: Duration::seconds(60),
+                                });
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.contains("\n    nabijaczleweli#481, access token ****ESD4, mirrored to Discord webhook, in test mode\n"));
+    assert!(out.contains("\n  2 sinks:\n    motd, to /etc/motd\n    community, to a Discord webhook\n"));
+    assert!(out.contains("\n  1 tweet queued, 0 to post\n"));
+    assert!(!out.contains("GVQDq88q"));
+    assert!(!out.contains("FNlJkpZ"));
+    assert!(!out.contains("Hb8KDdv"));
+}
+
+#[test]
+fn redact_multibyte() {
+    assert_eq!(start_daemon::redact("ąęśćżźńółĄĘŚĆŻŹŃÓŁ"), "****ŹŃÓŁ");
+    assert_eq!(start_daemon::redact(""), "****");
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_lock_stale_taken_over() {
+    use std::process;
+
+    let td = temp_dir().join("tweetr-test").join("ops-start_daemon-daemon_lock_stale_taken_over");
+    fs::create_dir_all(&td).unwrap();
+
+    let mut gone = process::Command::new("true").spawn().unwrap();
+    gone.wait().unwrap();
+    File::create(DaemonLock::path(&td)).unwrap().write_all(gone.id().to_string().as_bytes()).unwrap();
+    assert_eq!(DaemonLock::holder(&td), None);
+
+    let lock = DaemonLock::acquire(&td).unwrap();
+    assert_eq!(DaemonLock::holder(&td), Some(process::id()));
+    drop(lock);
+    assert!(!DaemonLock::path(&td).exists());
+}
+
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn tweet(time_s: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time(time_s),
+        content: "Abolish the bourgeoisie".to_string(),
+        ..Default::default()
+    }
+}
+
+fn user(name: &str) -> User {
+    User {
+        name: name.to_string(),
+        id: 481,
+        access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    }
+}
+
+fn post(tweet: &mut QueuedTweet, clock: &ManualClock) {
+    tweet.time_posted = Some(clock.now());
+    tweet.id = Some(420);
+}
@@ -0,0 +1,108 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{Template, queue_tweet};
+use std::collections::BTreeMap;
+use self::tweetr::Outcome;
+use std::path::PathBuf;
+use std::env::temp_dir;
+use std::io::Write;
+use std::fs::{self, File};
+
+
+#[test]
+fn read() {
+    let tf = templates_file("read",
+                            "[[template]]\nname = \"release\"\ncontent = \"tweetr {version} is out!\"\n\n\
+                             [[template]]\nname = \"hello\"\ncontent = \"Hello, world!\"\n");
+
+    assert_eq!(Template::read(&tf),
+               Ok(vec![Template {
+                           name: "release".to_string(),
+                           content: "tweetr {version} is out!".to_string(),
+                       },
+                       Template {
+                           name: "hello".to_string(),
+                           content: "Hello, world!".to_string(),
+                       }]));
+}
+
+#[test]
+fn duplicate() {
+    let tf = templates_file("duplicate",
+                            "[[template]]\nname = \"hello\"\ncontent = \"Hello\"\n\n\
+                             [[template]]\nname = \"hello\"\ncontent = \"Hello, world!\"\n");
+
+    assert_eq!(Template::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "templates",
+                   errors: vec!["error: 6:1: duplicate template \"hello\" for the key `template.1.name`".to_string()],
+               })));
+}
+
+#[test]
+fn malformed_placeholder() {
+    let tf = templates_file("malformed_placeholder",
+                            "[[template]]\nname = \"release\"\ncontent = \"tweetr {version is out!\"\n");
+
+    assert_eq!(Template::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "templates",
+                   errors: vec!["error: 3:1: invalid character ' ' in placeholder at character 15 for the key `template.0.content`".to_string()],
+               })));
+}
+
+#[test]
+fn escaped_braces() {
+    let template = Template {
+        name: "code".to_string(),
+        content: "fn {name}() {{}}".to_string(),
+    };
+    assert_eq!(template.placeholders(), Ok(vec!["name".to_string()]));
+    assert_eq!(template.fill(&values(&[("name", "main")])), Ok("fn main() {}".to_string()));
+
+    let template = Template { content: "fn main() {}".to_string(), ..template };
+    assert_eq!(template.placeholders(), Err("empty placeholder at character 10".to_string()));
+
+    let template = Template { content: "fn main() }".to_string(), ..template };
+    assert_eq!(template.placeholders(), Err("unmatched '}' at character 10".to_string()));
+}
+
+#[test]
+fn fill_all_problems() {
+    let template = Template {
+        name: "release".to_string(),
+        content: "tweetr {version} is out! {notes}".to_string(),
+    };
+
+    assert_eq!(queue_tweet::template(&[template], "release", &values(&[("verison", "v1.4.0")])),
+               Err(Outcome::TemplateError {
+                   name: "release".to_string(),
+                   errors: vec!["no value for the placeholder {version}".to_string(),
+                                "no value for the placeholder {notes}".to_string(),
+                                "no placeholder for the value of verison".to_string()],
+               }));
+}
+
+#[test]
+fn repeated_placeholder() {
+    let template = Template {
+        name: "release".to_string(),
+        content: "{version}: tweetr {version} is out!".to_string(),
+    };
+
+    assert_eq!(template.fill(&values(&[("version", "v1.4.0")])), Ok("v1.4.0: tweetr v1.4.0 is out!".to_string()));
+}
+
+
+fn templates_file(name: &str, contents: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-template-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("templates.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+    tf
+}
+
+fn values(values: &[(&str, &str)]) -> BTreeMap<String, String> {
+    values.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
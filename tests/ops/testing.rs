@@ -0,0 +1,97 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::testing::{ConfigDir, FakeBackend};
+use self::tweetr::ops::{AppTokens, Apps, QueuedTweet, start_daemon};
+use self::tweetr::ops::settings::Settings;
+use self::tweetr::ops::clock::ManualClock;
+use self::tweetr::Outcome;
+use self::chrono::{DateTime, FixedOffset};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+
+#[test]
+fn fake_backend_sequential_ids() {
+    let backend = FakeBackend::new("fake timeline");
+    let clock = ManualClock::new(time("2016-09-10T12:00:00+02:00"));
+
+    let mut first = tweet("Abolish the bourgeoisie");
+    let mut second = tweet("Seize the means of production");
+    assert_eq!(start_daemon::post_tweet(&mut first, &backend, &clock, false, &mut vec![]), Outcome::NoError);
+    assert_eq!(start_daemon::post_tweet(&mut second, &backend, &clock, false, &mut vec![]), Outcome::NoError);
+
+    assert_eq!((first.id, second.id), (Some(1), Some(2)));
+    assert_eq!(first.time_posted, Some(time("2016-09-10T12:00:00+02:00")));
+    assert_eq!(backend.posted().into_iter().map(|t| t.content).collect::<Vec<_>>(),
+               vec!["Abolish the bourgeoisie".to_string(), "Seize the means of production".to_string()]);
+}
+
+#[test]
+fn fake_backend_scripted_failure() {
+    let backend = FakeBackend::new("fake timeline");
+    backend.then(Err(Outcome::TwitterAPIError("Over capacity".to_string()))).then(Ok(774560457755590656));
+
+    let clock = ManualClock::new(time("2016-09-10T12:00:00+02:00"));
+    let mut tweet = tweet("Abolish the bourgeoisie");
+    assert_eq!(start_daemon::post_tweet(&mut tweet, &backend, &clock, false, &mut vec![]),
+               Outcome::TwitterAPIError("Over capacity".to_string()));
+    assert_eq!(tweet.id, None);
+    assert_eq!(start_daemon::post_tweet(&mut tweet, &backend, &clock, false, &mut vec![]), Outcome::NoError);
+    assert_eq!(tweet.id, Some(774560457755590656));
+
+    assert_eq!(backend.calls().into_iter().map(|(_, r)| r).collect::<Vec<_>>(),
+               vec![Err(Outcome::TwitterAPIError("Over capacity".to_string())), Ok(774560457755590656)]);
+    assert_eq!(backend.posted().len(), 1);
+}
+
+#[test]
+fn fake_backend_variant() {
+    let backend = FakeBackend::new("fake chat").with_variant("discord");
+
+    let mut tweet = tweet("Abolish the bourgeoisie");
+    tweet.variants.insert("discord".to_string(), "**Abolish** the bourgeoisie".to_string());
+    assert_eq!(start_daemon::mirror_tweet(&tweet, &backend, false, &mut vec![]), Outcome::NoError);
+
+    assert_eq!(backend.posted()[0].content, "**Abolish** the bourgeoisie");
+}
+
+#[test]
+fn fake_backend_latency() {
+    let backend = FakeBackend::new("fake timeline").with_latency(Duration::from_millis(50));
+
+    let start = Instant::now();
+    assert_eq!(start_daemon::mirror_tweet(&tweet("Abolish the bourgeoisie"), &backend, false, &mut vec![]), Outcome::NoError);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn config_dir_verifies() {
+    let config_dir = ConfigDir::new("ops-testing-config_dir_verifies")
+        .with_apps(&Apps {
+            default: AppTokens {
+                key: "qdPD5j8qkX2Bv7fB7Xv9mJE6r".to_string(),
+                secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+            },
+            named: BTreeMap::new(),
+        })
+        .with_users(vec![])
+        .with_tweets(vec![tweet("Abolish the bourgeoisie")]);
+
+    let (_, _, tweets) = start_daemon::verify(&config_dir.config_dir()).unwrap();
+    assert_eq!(QueuedTweet::read(&tweets).unwrap(), vec![tweet("Abolish the bourgeoisie")]);
+}
+
+
+fn time(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn tweet(content: &str) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: time("2016-09-10T12:00:00+02:00"),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
@@ -1,8 +1,12 @@
 extern crate tweetr;
 
-use self::tweetr::ops::AppTokens;
-use std::env::temp_dir;
-use std::fs;
+use self::tweetr::ops::{AppTokens, Apps};
+use std::collections::BTreeMap;
+use self::tweetr::Outcome;
+use std::path::PathBuf;
+use std::env::{self, temp_dir};
+use std::io::Write;
+use std::fs::{self, File};
 
 
 #[test]
@@ -17,8 +21,138 @@ fn trans_eq() {
         key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
         secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
     };
-    tokens.write(&tf);
+    tokens.write(&tf).unwrap();
     let read_tokens = AppTokens::read(&tf).unwrap();
 
     assert_eq!(tokens, read_tokens);
 }
+
+#[test]
+fn apps_trans_eq() {
+    let tf = app_file("apps_trans_eq");
+
+    let mut named = BTreeMap::new();
+    named.insert("client".to_string(),
+                 AppTokens {
+                     key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+                     secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+                 });
+    named.insert("agency".to_string(),
+                 AppTokens {
+                     key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+                     secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+                 });
+    let apps = Apps {
+        default: AppTokens {
+            key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+            secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+        },
+        named: named,
+    };
+    apps.write(&tf).unwrap();
+
+    assert_eq!(Apps::read(&tf).unwrap(), apps);
+    assert_eq!(AppTokens::read(&tf).unwrap(), apps.default);
+}
+
+#[test]
+fn apps_default_only() {
+    let tf = app_file("apps_default_only");
+
+    let tokens = AppTokens {
+        key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+        secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+    };
+    tokens.write(&tf).unwrap();
+
+    assert_eq!(Apps::read(&tf).unwrap(),
+               Apps {
+                   default: tokens,
+                   named: BTreeMap::new(),
+               });
+}
+
+#[test]
+fn apps_duplicate() {
+    let tf = app_file("apps_duplicate");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"key = \"qzuqpwr101q4RtK9mDorI9ndm\"\n\
+                     secret = \"HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5\"\n\
+                     \n\
+                     [[app]]\n\
+                     name = \"client\"\n\
+                     key = \"GeVFiYk7q8DhUmgMXE0iODrFa\"\n\
+                     secret = \"bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe\"\n\
+                     \n\
+                     [[app]]\n\
+                     name = \"client\"\n\
+                     key = \"qdPD7N8CcPYDKiNv81QWNWaHK\"\n\
+                     secret = \"U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p\"\n")
+        .unwrap();
+
+    assert_eq!(Apps::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "application tokens",
+                   errors: vec!["error: 10:1: duplicate app \"client\" for the key `app.1.name`".to_string()],
+               })));
+}
+
+#[test]
+fn apps_interpolated() {
+    let tf = app_file("apps_interpolated");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"key = \"qzuqpwr101q4RtK9mDorI9ndm\"\n\
+                     secret = \"${TWEETR_TEST_APPS_INTERPOLATED_SECRET}\"\n\
+                     \n\
+                     [[app]]\n\
+                     name = \"client\"\n\
+                     key = \"GeVFiYk7q8DhUmgMXE0iODrFa\"\n\
+                     secret = \"bH3VIvYEwwVmMXk$${TnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe\"\n")
+        .unwrap();
+    env::set_var("TWEETR_TEST_APPS_INTERPOLATED_SECRET", "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+
+    let apps = Apps::read(&tf).unwrap();
+    assert_eq!(apps.default.secret, "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+    assert_eq!(apps.named["client"].secret, "bH3VIvYEwwVmMXk${TnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe");
+    assert_eq!(AppTokens::read(&tf).unwrap().secret, "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+
+    let verbatim = Apps::read_verbatim(&tf).unwrap();
+    assert_eq!(verbatim.default.secret, "${TWEETR_TEST_APPS_INTERPOLATED_SECRET}");
+    assert_eq!(verbatim.named["client"].secret, "bH3VIvYEwwVmMXk$${TnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe");
+}
+
+#[test]
+fn apps_interpolated_unset() {
+    let tf = app_file("apps_interpolated_unset");
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"key = \"qzuqpwr101q4RtK9mDorI9ndm\"\n\
+                     secret = \"HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5\"\n\
+                     \n\
+                     [[app]]\n\
+                     name = \"client\"\n\
+                     key = \"GeVFiYk7q8DhUmgMXE0iODrFa\"\n\
+                     secret = \"${TWEETR_TEST_APPS_INTERPOLATED_UNSET}\"\n")
+        .unwrap();
+    env::remove_var("TWEETR_TEST_APPS_INTERPOLATED_UNSET");
+
+    assert_eq!(Apps::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "application tokens",
+                   errors: vec!["error: 7:1: environment variable TWEETR_TEST_APPS_INTERPOLATED_UNSET isn't set for the key `app.0.secret`"
+                                    .to_string()],
+               })));
+    assert!(Apps::read_verbatim(&tf).is_ok());
+}
+
+
+fn app_file(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-token-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("app.toml");
+    let _ = fs::remove_file(&tf);
+    tf
+}
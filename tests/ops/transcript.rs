@@ -0,0 +1,155 @@
+extern crate tweetr;
+
+use self::tweetr::ops::transcript::{self, Transcribed};
+use self::tweetr::ops::{User, add_user};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+
+#[test]
+fn prompts_and_answers() {
+    let tf = transcript_file("prompts_and_answers");
+    let mut input = Transcribed::new(BufReader::new(b"nabijaczleweli\nAbolish the bourgeoisie\n" as &[u8]));
+    let mut output = Transcribed::new(Vec::new());
+
+    transcript::start(&tf).unwrap();
+    for prompt in &["Author (or empty to finish): ", "Tweet content: "] {
+        write!(output, "{}", prompt).unwrap();
+        input.read_line(&mut String::new()).unwrap();
+    }
+    writeln!(output, "Scheduled for 2016-09-10T12:00:00+02:00.").unwrap();
+    transcript::stop();
+
+    assert_eq!(written(&tf),
+               "Author (or empty to finish): nabijaczleweli\nTweet content: Abolish the bourgeoisie\nScheduled for 2016-09-10T12:00:00+02:00.\n");
+    assert_eq!(output.into_inner(),
+               b"Author (or empty to finish): Tweet content: Scheduled for 2016-09-10T12:00:00+02:00.\n".to_vec());
+}
+
+#[test]
+fn secrets_redacted_until_dropped() {
+    let tf = transcript_file("secrets_redacted_until_dropped");
+    let mut input = Transcribed::new(BufReader::new(b"12345\r\n1234567\nnabijaczleweli\n" as &[u8]));
+
+    transcript::start(&tf).unwrap();
+    {
+        let _secret = transcript::secret();
+        input.read_line(&mut String::new()).unwrap();
+        input.read_line(&mut String::new()).unwrap();
+    }
+    input.read_line(&mut String::new()).unwrap();
+    transcript::stop();
+
+    assert_eq!(written(&tf), "<redacted, 5 characters>\n<redacted, 7 characters>\nnabijaczleweli\n");
+}
+
+#[test]
+fn secret_answers_hidden_when_printed() {
+    let tf = transcript_file("secret_answers_hidden_when_printed");
+    let mut input = Transcribed::new(BufReader::new(b"qdPD7N8CcPYDKiNv81QWNWaHK\n" as &[u8]));
+    let mut output = Transcribed::new(Vec::new());
+
+    transcript::start(&tf).unwrap();
+    {
+        let _secret = transcript::secret();
+        write!(output, "App key: ").unwrap();
+        input.read_line(&mut String::new()).unwrap();
+    }
+    writeln!(output, "  ~ default app (key {} -> {})", "qzuqpwr101q4RtK9mDorI9ndm", "qdPD7N8CcPYDKiNv81QWNWaHK").unwrap();
+    transcript::stop();
+
+    assert_eq!(written(&tf),
+               "App key: <redacted, 25 characters>\n  ~ default app (key qzuqpwr101q4RtK9mDorI9ndm -> <redacted, 25 characters>)\n");
+}
+
+#[test]
+fn access_tokens_hidden() {
+    let tf = transcript_file("access_tokens_hidden");
+    let mut output = Transcribed::new(Vec::new());
+    let user = User {
+        name: "nabijaczleweli".to_string(),
+        id: 481,
+        access_token_key: "1234567890-Qx8nLbUqJwXr3v5Ug9S2cTtH7oEaZmYdKf4pN".to_string(),
+        access_token_secret: "8fEBRyQ4NzcqXoS5kvNe9ihhZSWfxq6LRxr1Wm8rZrnLDOELTL".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
+    };
+
+    transcript::start(&tf).unwrap();
+    add_user::print_success_message(&mut output, &mut Vec::new(), &user, true);
+    transcript::stop();
+
+    assert!(String::from_utf8(output.into_inner()).unwrap().contains(&user.access_token_secret));
+    assert_eq!(written(&tf),
+               "Access tokens:\n  Key   : <redacted, 48 characters>\n  Secret: <redacted, 50 characters>\n");
+}
+
+#[test]
+fn unfinished_answer_recorded() {
+    let tf = transcript_file("unfinished_answer_recorded");
+    let mut input = Transcribed::new(b"nabijacz" as &[u8]);
+
+    transcript::start(&tf).unwrap();
+    input.read_to_string(&mut String::new()).unwrap();
+    transcript::stop();
+
+    assert_eq!(written(&tf), "nabijacz\n");
+}
+
+#[test]
+fn nothing_without_transcript() {
+    let tf = transcript_file("nothing_without_transcript");
+    transcript::start(&tf).unwrap();
+    transcript::stop();
+
+    let mut input = Transcribed::new(b"nabijaczleweli\n" as &[u8]);
+    let mut line = String::new();
+    input.read_line(&mut line).unwrap();
+    write!(Transcribed::new(Vec::new()), "Author (or empty to finish): ").unwrap();
+
+    assert_eq!(line, "nabijaczleweli\n");
+    assert_eq!(written(&tf), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tf = transcript_file("private");
+    File::create(&tf).unwrap();
+    fs::set_permissions(&tf, fs::Permissions::from_mode(0o644)).unwrap();
+
+    transcript::start(&tf).unwrap();
+    transcript::stop();
+    assert_eq!(fs::metadata(&tf).unwrap().permissions().mode() & 0o777, 0o600);
+}
+
+
+fn transcript_file(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-transcript-{}", name));
+    fs::create_dir_all(&td).unwrap();
+    td.join("transcript.txt")
+}
+
+/// The transcript, without the header.
+fn written(tf: &PathBuf) -> String {
+    let mut written = String::new();
+    File::open(tf).unwrap().read_to_string(&mut written).unwrap();
+    written[written.find('\n').unwrap() + 1..].to_string()
+}
@@ -0,0 +1,105 @@
+extern crate tweetr;
+
+use self::tweetr::ops::transform::{self, Pipeline, Step, Transforms};
+use self::tweetr::ops::Config;
+use std::collections::BTreeMap;
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Write;
+use self::tweetr::Outcome;
+
+
+#[test]
+fn collapse_spaces_keeps_newlines() {
+    assert_eq!(Step::CollapseSpaces.apply("  Abolish \t the bourgeoisie\n\n\tWorkers  unite  "),
+               "Abolish the bourgeoisie\n\nWorkers unite");
+}
+
+#[test]
+fn smart_quotes_all() {
+    assert_eq!(Step::SmartQuotes.apply("„Abolish” «the» ‚bourgeoisie‘ ‹now›"), "\"Abolish\" \"the\" 'bourgeoisie' 'now'");
+    assert_eq!(Step::SmartQuotes.apply("Abolish the bourgeoisie"), "Abolish the bourgeoisie");
+}
+
+#[test]
+fn hashtag_case_word_boundary() {
+    let mut hashtags = BTreeMap::new();
+    hashtags.insert("tweetr".to_string(), "Tweetr".to_string());
+    let step = Step::HashtagCase(hashtags);
+
+    assert_eq!(step.apply("#tweetrrelease #TWEETR. #tweetr_ #"), "#tweetrrelease #Tweetr. #tweetr_ #");
+    assert_eq!(step.apply("##tweetr"), "##Tweetr");
+}
+
+#[test]
+fn campaign_tag_word_boundary() {
+    let step = Step::CampaignTag("tweetr".to_string());
+    assert_eq!(step.apply("#tweetrrelease"), "#tweetrrelease #tweetr");
+    assert_eq!(step.apply("Out now! #Tweetr!"), "Out now! #Tweetr!");
+}
+
+#[test]
+fn pipeline_order() {
+    let td = temp_dir().join("tweetr-test").join("ops-transform-pipeline_order");
+    fs::create_dir_all(&td).unwrap();
+    File::create(td.join("hashtags")).unwrap().write_all(b"TweetrRelease\n").unwrap();
+
+    let config = Config {
+        transform: Some(Transforms {
+            collapse_spaces: Some(true),
+            smart_quotes: Some(true),
+            hashtags: Some("hashtags".to_string()),
+            campaign_tag: Some("tweetrrelease".to_string()),
+        }),
+        ..Config::default()
+    };
+    let pipeline = transform::pipeline(&config, &td).unwrap();
+    assert_eq!(pipeline.steps.iter().map(Step::name).collect::<Vec<_>>(),
+               vec!["collapse_spaces", "smart_quotes", "hashtags", "campaign_tag"]);
+    assert_eq!(pipeline.apply("It’s  out! "), "It's out! #tweetrrelease");
+    assert_eq!(pipeline.apply("It’s  out! #TWEETRRELEASE"), "It's out! #TweetrRelease");
+}
+
+#[test]
+fn disabled_steps() {
+    let config = Config {
+        transform: Some(Transforms {
+            collapse_spaces: Some(false),
+            smart_quotes: None,
+            hashtags: None,
+            campaign_tag: None,
+        }),
+        ..Config::default()
+    };
+    assert_eq!(transform::pipeline(&config, &temp_dir()), Ok(Pipeline::default()));
+}
+
+#[test]
+fn missing_hashtags() {
+    let td = temp_dir().join("tweetr-test").join("ops-transform-missing_hashtags");
+    fs::create_dir_all(&td).unwrap();
+    let _ = fs::remove_file(td.join("hashtags"));
+
+    let config = Config {
+        transform: Some(Transforms {
+            collapse_spaces: None,
+            smart_quotes: None,
+            hashtags: Some("hashtags".to_string()),
+            campaign_tag: None,
+        }),
+        ..Config::default()
+    };
+    match transform::pipeline(&config, &td) {
+        Err(Outcome::PreflightFailed(errors)) => {
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].starts_with(&format!("couldn't read the hashtags from {}: ", td.join("hashtags").display())));
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn preview_unchanged_steps_skipped() {
+    let pipeline = Pipeline { steps: vec![Step::SmartQuotes, Step::CollapseSpaces, Step::SmartQuotes] };
+    assert_eq!(pipeline.preview("“Abolish”"), vec![(&Step::SmartQuotes, "\"Abolish\"".to_string())]);
+}
@@ -0,0 +1,184 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::tui::{State, Pane, Field};
+use self::tweetr::ops::QueuedTweet;
+use self::chrono::{DateTime, FixedOffset, Duration, Local};
+
+
+#[test]
+fn listed_panes() {
+    let state = State::new(vec![tweet("Abolish", false), tweet("the bourgeoisie", true), tweet("now", false)], vec![]);
+
+    assert_eq!(state.listed(Pane::Queue), vec![0, 2]);
+    assert_eq!(state.listed(Pane::Archive), vec![0]);
+    assert_eq!(state.posted[0].content, "the bourgeoisie");
+    assert_eq!(state.listed(Pane::Compose), Vec::<usize>::new());
+}
+
+#[test]
+fn listed_archived() {
+    let mut archived = tweet("Workers of the world", true);
+    archived.time_posted = Some(now() - Duration::days(40));
+    let state = State::new(vec![tweet("Abolish", false), tweet("the bourgeoisie", true)], vec![archived]);
+
+    assert_eq!(state.listed(Pane::Queue), vec![0]);
+    assert_eq!(state.listed(Pane::Archive), vec![0, 1]);
+    assert_eq!(state.posted.iter().map(|t| &t.content[..]).collect::<Vec<_>>(), vec!["Workers of the world", "the bourgeoisie"]);
+    assert_eq!(state.tweets.len(), 2);
+}
+
+#[test]
+fn navigation() {
+    let mut state = State::new(vec![tweet("Abolish", false), tweet("the bourgeoisie", true), tweet("now", false)], vec![]);
+
+    state.select_prev();
+    assert_eq!(state.selected_index(), Some(0));
+    state.select_next();
+    state.select_next();
+    assert_eq!(state.selected_index(), Some(2));
+
+    state.next_pane();
+    assert_eq!(state.pane, Pane::Archive);
+    assert_eq!(state.selected_index(), Some(0));
+    state.next_pane();
+    assert_eq!(state.selected_index(), None);
+}
+
+#[test]
+fn delete() {
+    let mut state = State::new(vec![tweet("Abolish", false), tweet("the bourgeoisie", true), tweet("now", false)], vec![]);
+    state.select_next();
+
+    assert_eq!(state.delete_selected().map(|t| t.content), Some("now".to_string()));
+    assert!(state.dirty);
+    assert_eq!(state.selected_index(), Some(0));
+    assert_eq!(state.tweets.len(), 2);
+}
+
+#[test]
+fn delete_posted() {
+    let mut state = State::new(vec![tweet("Abolish", true)], vec![]);
+    state.next_pane();
+
+    assert_eq!(state.delete_selected(), None);
+    assert!(!state.dirty);
+    assert_eq!(state.tweets.len(), 1);
+}
+
+#[test]
+fn toggle_pause() {
+    let mut state = State::new(vec![tweet("Abolish", false)], vec![]);
+
+    state.toggle_pause_selected();
+    assert!(state.tweets[0].paused);
+    assert!(state.dirty);
+    state.toggle_pause_selected();
+    assert!(!state.tweets[0].paused);
+}
+
+#[test]
+fn edit() {
+    let mut state = State::new(vec![tweet("Abolish", false)], vec![]);
+    state.tweets[0].thread = Some("manifesto".to_string());
+
+    state.edit_selected();
+    assert_eq!(state.pane, Pane::Compose);
+    assert_eq!(state.compose.field, Field::Content);
+    state.compose.content = "Abolish the bourgeoisie".to_string();
+
+    let edited = state.composed(now()).unwrap();
+    assert_eq!(edited.content, "Abolish the bourgeoisie");
+    assert_eq!(edited.time, state.tweets[0].time);
+    assert_eq!(edited.thread, Some("manifesto".to_string()));
+
+    state.submit(edited);
+    assert_eq!(state.tweets.len(), 1);
+    assert_eq!(state.tweets[0].content, "Abolish the bourgeoisie");
+    assert_eq!(state.compose.editing, None);
+}
+
+#[test]
+fn compose_new() {
+    let mut state = State::new(vec![], vec![]);
+    state.compose.author = "nabijaczleweli".to_string();
+    state.compose.time = "in 2 hours".to_string();
+    state.compose.content = "Abolish the bourgeoisie".to_string();
+
+    let now = now();
+    let composed = state.composed(now).unwrap();
+    assert_eq!(composed.time, now + Duration::hours(2));
+
+    state.submit(composed);
+    assert_eq!(state.listed(Pane::Queue), vec![0]);
+    assert!(state.dirty);
+}
+
+#[test]
+fn compose_anchored() {
+    let mut state = State::new(vec![tweet("Abolish", false)], vec![]);
+    state.tweets[0].name = Some("abolish".to_string());
+    state.compose.author = "nabijaczleweli".to_string();
+    state.compose.time = "15 minutes after abolish".to_string();
+    state.compose.content = "the bourgeoisie".to_string();
+
+    let composed = state.composed(now()).unwrap();
+    assert_eq!(composed.time, state.tweets[0].time + Duration::minutes(15));
+    state.submit(composed);
+
+    state.tweets[0].time = state.tweets[0].time + Duration::hours(1);
+    state.pane = Pane::Queue;
+    state.selected = 0;
+    state.edit_selected();
+    assert_eq!(state.compose.time, state.tweets[0].time.to_rfc3339());
+    let edited = state.composed(now()).unwrap();
+    state.submit(edited);
+    assert_eq!(state.tweets[1].time, state.tweets[0].time + Duration::minutes(15));
+
+    state.pane = Pane::Queue;
+    state.selected = 1;
+    state.edit_selected();
+    assert_eq!(state.compose.time, "15 minutes after abolish");
+}
+
+#[test]
+fn compose_unschedulable() {
+    let mut state = State::new(vec![], vec![]);
+    state.compose.author = "nabijaczleweli".to_string();
+    state.compose.time = "15 minutes after abolish".to_string();
+    state.compose.content = "the bourgeoisie".to_string();
+    assert_eq!(state.composed(now()), Err("Can't schedule the tweet: no tweet named \"abolish\"".to_string()));
+
+    state.compose.time = "15 fortnights after abolish".to_string();
+    assert!(state.composed(now()).is_err());
+}
+
+#[test]
+fn compose_invalid() {
+    let mut state = State::new(vec![], vec![]);
+    assert_eq!(state.composed(now()), Err("No author".to_string()));
+
+    state.compose.author = "nabijaczleweli".to_string();
+    assert_eq!(state.composed(now()), Err("No content".to_string()));
+
+    state.compose.content = "Abolish the bourgeoisie".to_string();
+    state.compose.time = "eventually".to_string();
+    assert!(state.composed(now()).is_err());
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    let now = Local::now();
+    now.with_timezone(now.offset())
+}
+
+fn tweet(content: &str, posted: bool) -> QueuedTweet {
+    QueuedTweet {
+        author: "nabijaczleweli".to_string(),
+        time: now(),
+        content: content.to_string(),
+        time_posted: if posted { Some(now()) } else { None },
+        id: if posted { Some(420) } else { None },
+        ..Default::default()
+    }
+}
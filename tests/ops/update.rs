@@ -0,0 +1,115 @@
+extern crate rustc_serialize;
+extern crate tweetr;
+extern crate crypto;
+
+use self::tweetr::ops::update::{self, Release};
+use self::rustc_serialize::base64::{STANDARD, ToBase64};
+use self::crypto::ed25519;
+use self::rustc_serialize::json::Json;
+use std::collections::BTreeMap;
+use std::env::temp_dir;
+use std::io::{Read, Write};
+use std::fs::{self, File};
+
+
+#[test]
+fn from_json() {
+    let release = Release::from_json(&Json::from_str(r#"{
+        "tag_name": "v0.2.2",
+        "name": "v0.2.2",
+        "assets": [
+            {"name": "tweetr-v0.2.2.exe", "browser_download_url": "https://example.com/tweetr-v0.2.2.exe"},
+            {"name": "tweetr-v0.2.2.exe.sig", "browser_download_url": "https://example.com/tweetr-v0.2.2.exe.sig"}
+        ]
+    }"#)
+            .unwrap())
+        .unwrap();
+
+    let mut assets = BTreeMap::new();
+    assets.insert("tweetr-v0.2.2.exe".to_string(), "https://example.com/tweetr-v0.2.2.exe".to_string());
+    assets.insert("tweetr-v0.2.2.exe.sig".to_string(), "https://example.com/tweetr-v0.2.2.exe.sig".to_string());
+    assert_eq!(release,
+               Release {
+                   version: "v0.2.2".to_string(),
+                   assets: assets,
+               });
+}
+
+#[test]
+fn from_json_invalid_asset() {
+    assert_eq!(Release::from_json(&Json::from_str(r#"{"tag_name": "v0.2.2", "assets": [{"name": "tweetr-v0.2.2.exe"}]}"#).unwrap()),
+               Err("invalid asset {\"name\":\"tweetr-v0.2.2.exe\"}".to_string()));
+}
+
+#[test]
+fn binary_missing() {
+    let release = Release {
+        version: "v0.2.2".to_string(),
+        assets: BTreeMap::new(),
+    };
+
+    assert_eq!(release.binary(),
+               Err(format!("v0.2.2 has no {} for this platform", update::asset_name("v0.2.2"))));
+}
+
+#[test]
+fn is_newer() {
+    assert!(update::is_newer("v0.2.2", "0.2.1"));
+    assert!(update::is_newer("v1.0.0", "0.2.1"));
+    assert!(!update::is_newer("v0.2.0", "0.2.1"));
+    assert!(!update::is_newer("v0.2.2-rc1", "0.2.1"));
+}
+
+#[test]
+fn verify_signature() {
+    let (secret, public) = ed25519::keypair(&[7; 32]);
+    let signature = ed25519::signature(b"tweetr v0.2.2", &secret).to_base64(STANDARD);
+
+    assert_eq!(update::verify_signature(b"tweetr v0.2.2", &signature, &public.to_base64(STANDARD)), Ok(()));
+    assert_eq!(update::verify_signature(b"tweetr v0.2.3", &signature, &public.to_base64(STANDARD)),
+               Err("signature mismatch: not signed with the release key".to_string()));
+}
+
+#[test]
+fn verify_signature_other_key() {
+    let (secret, _) = ed25519::keypair(&[7; 32]);
+    let (_, public) = ed25519::keypair(&[8; 32]);
+    let signature = ed25519::signature(b"tweetr v0.2.2", &secret).to_base64(STANDARD);
+
+    assert_eq!(update::verify_signature(b"tweetr v0.2.2", &signature, &public.to_base64(STANDARD)),
+               Err("signature mismatch: not signed with the release key".to_string()));
+}
+
+#[test]
+fn verify_signature_truncated() {
+    let (secret, public) = ed25519::keypair(&[7; 32]);
+    let signature = ed25519::signature(b"tweetr v0.2.2", &secret);
+
+    assert_eq!(update::verify_signature(b"tweetr v0.2.2", &signature[..32].to_base64(STANDARD), &public.to_base64(STANDARD)),
+               Err("invalid signature: 32 bytes long, not 64".to_string()));
+}
+
+#[test]
+fn replace_executable() {
+    let td = temp_dir().join("tweetr-test").join("ops-update-replace_executable");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    File::create(td.join("tweetr")).unwrap().write_all(b"v0.2.1").unwrap();
+
+    update::replace_executable(&td.join("tweetr"), b"v0.2.2").unwrap();
+
+    let mut data = String::new();
+    File::open(td.join("tweetr")).unwrap().read_to_string(&mut data).unwrap();
+    assert_eq!(data, "v0.2.2");
+    assert!(!td.join(".tweetr.new").exists());
+}
+
+#[test]
+fn replace_executable_nonexistant() {
+    let td = temp_dir().join("tweetr-test").join("ops-update-replace_executable_nonexistant");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    assert!(update::replace_executable(&td.join("tweetr"), b"v0.2.2").unwrap_err().starts_with("couldn't read "));
+    assert!(!td.join(".tweetr.new").exists());
+}
@@ -1,12 +1,42 @@
+#[cfg(feature = "network")]
 extern crate egg_mode;
 extern crate tweetr;
 
 use self::tweetr::ops::User;
+use self::tweetr::Outcome;
+#[cfg(feature = "network")]
 use self::egg_mode::Token;
+use std::env::temp_dir;
+use std::fs::{self, File};
+use std::io::Write;
 
 mod trans;
 
 
+#[test]
+fn telegram_half_configured() {
+    let td = temp_dir().join("tweetr-test").join("ops-user-telegram_half_configured");
+    fs::create_dir_all(&td).unwrap();
+    let tf = td.join("users.toml");
+
+    File::create(&tf)
+        .unwrap()
+        .write_all(b"[[user]]\n\
+                     name = \"nabijaczleweli\"\n\
+                     id = 481\n\
+                     access_token_key = \"481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4\"\n\
+                     access_token_secret = \"GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5\"\n\
+                     telegram_chat_id = \"@tweetr_announcements\"\n")
+        .unwrap();
+    assert_eq!(User::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "users",
+                   errors: vec!["error: 6:1: no telegram_bot_token to go with it for the key `user.0.telegram_chat_id`".to_string()],
+               })));
+}
+
+
+#[cfg(feature = "network")]
 #[test]
 fn convert_from_raw_token() {
     let user = User {
@@ -14,6 +44,21 @@ fn convert_from_raw_token() {
         id: 481,
         access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
         access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: None,
     };
     assert_eq!(User::from_raw_access_token((Token::new(&user.access_token_key[..], &user.access_token_secret[..]), user.id, user.name.clone())),
                user);
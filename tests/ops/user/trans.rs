@@ -1,5 +1,6 @@
 extern crate tweetr;
 
+use self::tweetr::ops::lint::Lint;
 use self::tweetr::ops::User;
 use std::env::temp_dir;
 use std::fs;
@@ -18,6 +19,21 @@ fn single_eq() {
                             id: 481,
                             access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
                             access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
                         }]);
 }
 
@@ -29,18 +45,210 @@ fn multi_eq() {
                             id: 481,
                             access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
                             access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
                         },
                         User {
                             name: "danerangLP".to_string(),
                             id: 334776,
                             access_token_key: "334776-WTRvsJI4DQgvzYwDYT8YYdEBxnpCQpQB2t4SrEK".to_string(),
                             access_token_secret: "qbOzYrP9bwcOEGAUnuiPVfA7JqAAsWhrd8DcbwDH5RwTA".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
                         },
                         User {
                             name: "LinesFromNLSS".to_string(),
                             id: 6695520,
                             access_token_key: "6695520-KhiuVzAS41GS0V3hCBA7VFnHHNdwQpUDCaNfiOn".to_string(),
                             access_token_secret: "3cx12ULmXYkhcnEiPXBbpoilLPdQOVd8KigUoPQmaw8f5".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
+                        }]);
+}
+
+#[test]
+fn mirrors_eq() {
+    trans_scaffold("mirrors_trans_eq",
+                   vec![User {
+                            name: "nabijaczleweli".to_string(),
+                            id: 481,
+                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: None,
+                            telegram_bot_token: Some("110201543:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw".to_string()),
+                            telegram_chat_id: Some("@tweetr_announcements".to_string()),
+                            discord_webhook: Some("https://discordapp.com/api/webhooks/2334/Hb8KDdv-DmkLz".to_string()),
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
+                        }]);
+}
+
+#[test]
+fn defaults_eq() {
+    trans_scaffold("defaults_trans_eq",
+                   vec![User {
+                            name: "nabijaczleweli".to_string(),
+                            id: 481,
+                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: Some("+02:00".to_string()),
+                            quiet_hours: Some("23:00-07:00".to_string()),
+                            jitter: Some(5),
+                            tags: Some(vec!["tweetr".to_string(), "#communism".to_string()]),
+                            sensitive: Some(true),
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
+                        }]);
+}
+
+#[test]
+fn app_eq() {
+    trans_scaffold("app_trans_eq",
+                   vec![User {
+                            name: "nabijaczleweli".to_string(),
+                            id: 481,
+                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: Some("client".to_string()),
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
+                        }]);
+}
+
+#[test]
+fn overwrite_leaves_nothing_behind() {
+    trans_scaffold("overwrite_leaves_nothing_behind", vec![]);
+    trans_scaffold("overwrite_leaves_nothing_behind",
+                   vec![User {
+                            name: "nabijaczleweli".to_string(),
+                            id: 481,
+                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+                            app: None,
+                            telegram_bot_token: None,
+                            telegram_chat_id: None,
+                            discord_webhook: None,
+                            timezone: None,
+                            quiet_hours: None,
+                            jitter: None,
+                            tags: None,
+                            sensitive: None,
+                            test_mode: None,
+                            test_account: None,
+                            added: None,
+                            suspended: None,
+                            max_posts_per_day: None,
+                            lint: None,
+                        }]);
+
+    let td = temp_dir().join("tweetr-test").join("ops-user-overwrite_leaves_nothing_behind");
+    assert_eq!(fs::read_dir(&td).unwrap().map(|f| f.unwrap().file_name().into_string().unwrap()).collect::<Vec<_>>(),
+               vec!["users.toml"]);
+}
+
+#[test]
+fn lint_eq() {
+    let user = User {
+        name: "brandacct".to_string(),
+        id: 481,
+        access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
+        access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
+        app: None,
+        telegram_bot_token: None,
+        telegram_chat_id: None,
+        discord_webhook: None,
+        timezone: None,
+        quiet_hours: None,
+        jitter: None,
+        tags: None,
+        sensitive: None,
+        test_mode: None,
+        test_account: None,
+        added: None,
+        suspended: None,
+        max_posts_per_day: None,
+        lint: Some(Lint {
+            max_hashtags: Some(2),
+            max_mentions: None,
+            max_caps: Some(4),
+            link_required_for: Some(vec!["#release*".to_string()]),
+            block: Some(true),
+        }),
+    };
+    trans_scaffold("lint_trans_eq",
+                   vec![user.clone(),
+                        User {
+                            name: "nabijaczleweli".to_string(),
+                            lint: None,
+                            ..user
                         }]);
 }
 
@@ -52,7 +260,7 @@ fn trans_scaffold(name: &str, users: Vec<User>) {
     let tf = td.join("users.toml");
     let _ = fs::remove_file(&tf);
 
-    User::write(users.clone(), &tf);
+    User::write(users.clone(), &tf).unwrap();
     let read_users = User::read(&tf).unwrap();
 
     assert_eq!(users, read_users);
@@ -0,0 +1,222 @@
+extern crate tweetr;
+extern crate clap;
+
+use self::tweetr::options::{Options, Subsystem};
+use self::tweetr::ops::diagnostics::Verbosity;
+use self::tweetr::util::encoding::Encoding;
+use self::tweetr::util::Confirmation;
+use std::env::temp_dir;
+use std::time::Duration;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn parse_from_config_dir_after_subsystem() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_config_dir_after_subsystem");
+    fs::create_dir_all(&td).unwrap();
+
+    let options = Options::parse_from(vec!["tweetr".to_string(), "list-users".to_string(), "-c".to_string(), td.display().to_string()]).unwrap();
+    assert_eq!(options.config_dir, (td.display().to_string(), fs::canonicalize(&td).unwrap()));
+    assert_eq!(options.subsystem, Subsystem::ListUsers { check: false });
+}
+
+#[test]
+fn parse_from_yes() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_yes");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    let options = Options::parse_from(&["tweetr", "-c", &td, "fmt-queue", "--yes"]).unwrap();
+    assert_eq!(options.confirmation, Confirmation::Yes);
+    assert_eq!(options.subsystem, Subsystem::FmtQueue);
+}
+
+#[test]
+fn parse_from_help() {
+    assert_eq!(Options::parse_from(&["tweetr", "--help"]).unwrap_err().kind, clap::ErrorKind::HelpDisplayed);
+    assert_eq!(Options::parse_from(&["tweetr", "list-users", "--colour"]).unwrap_err().kind, clap::ErrorKind::UnknownArgument);
+}
+
+#[test]
+fn parse_from_nonexistant_config_dir() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_nonexistant_config_dir");
+    let _ = fs::remove_dir_all(&td);
+
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td.display().to_string(), "validate"]).unwrap_err().kind,
+               clap::ErrorKind::ValueValidation);
+}
+
+#[test]
+fn builder() {
+    let td = temp_dir().join("tweetr-test").join("options-builder");
+
+    let options = Options::default()
+        .with_config_dir(&td)
+        .with_read_only(true)
+        .with_confirmation(Confirmation::Refuse)
+        .with_locale("pl_PL")
+        .with_subsystem(Subsystem::ShowEffectiveConfig { author: Some("nabijaczleweli".to_string()) });
+    assert_eq!(options,
+               Options {
+                   config_dir: (td.display().to_string(), td.clone()),
+                   read_only: true,
+                   confirmation: Confirmation::Refuse,
+                   locale: Some("pl_PL".to_string()),
+                   connect_timeout: None,
+                   read_timeout: None,
+                   write_timeout: None,
+                   report: None,
+                   json: false,
+                   transcript: None,
+                   verbosity: Verbosity::Normal,
+                   subsystem: Subsystem::ShowEffectiveConfig { author: Some("nabijaczleweli".to_string()) },
+               });
+    assert!(!td.exists());
+}
+
+#[test]
+fn parse_from_report_after_subsystem() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_report_after_subsystem");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    let options = Options::parse_from(&["tweetr", "-c", &td, "validate", "--report", "report.json"]).unwrap();
+    assert_eq!(options.report, Some(PathBuf::from("report.json")));
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "validate"]).unwrap().report, None);
+}
+
+#[test]
+fn parse_from_transcript() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_transcript");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    let options = Options::parse_from(&["tweetr", "-c", &td, "--transcript", "transcript.txt", "init"]).unwrap();
+    assert_eq!(options.transcript, Some(PathBuf::from("transcript.txt")));
+    let options = Options::parse_from(&["tweetr", "-c", &td, "add-user", "--transcript", "transcript.txt"]).unwrap();
+    assert_eq!(options.transcript, Some(PathBuf::from("transcript.txt")));
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "validate"]).unwrap().transcript, None);
+}
+
+#[test]
+fn parse_from_verbosity() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_verbosity");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "export-posted"]).unwrap().verbosity, Verbosity::Normal);
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "--quiet", "export-posted"]).unwrap().verbosity, Verbosity::Quiet);
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "fmt-queue", "-q"]).unwrap().verbosity, Verbosity::Quiet);
+
+    let options = Options::parse_from(&["tweetr", "-c", &td, "start-daemon", "-v"]).unwrap();
+    assert_eq!(options.verbosity, Verbosity::Verbose);
+    match options.subsystem {
+        Subsystem::StartDaemon { events: None, .. } => (),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "-v", "add-user", "--quiet"]).unwrap().verbosity, Verbosity::Quiet);
+}
+
+#[test]
+fn parse_from_delay() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_delay");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "start-daemon", "--delay", "604800000"]).unwrap().subsystem {
+        Subsystem::StartDaemon { delay, .. } => assert_eq!(delay, Duration::from_secs(7 * 24 * 60 * 60)),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "start-daemon", "--delay", "604800001"]).unwrap_err().kind,
+               clap::ErrorKind::ValueValidation);
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "start-daemon", "--delay", "18446744073709551615"]).unwrap_err().kind,
+               clap::ErrorKind::ValueValidation);
+}
+
+#[test]
+fn parse_from_unix_time() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_unix_time");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "shift-queue", "--by", "1 hour", "--from", "@1473501600"]).unwrap().subsystem {
+        Subsystem::ShiftQueue { from: Some(from), .. } => assert_eq!(from.timestamp(), 1473501600),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "shift-queue", "--by", "1 hour", "--from", "@soon"]).unwrap_err().kind,
+               clap::ErrorKind::ValueValidation);
+}
+
+#[test]
+fn parse_from_simulate() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_simulate");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "simulate", "--from", "@1473501600", "--to", "@1473674400"]).unwrap().subsystem {
+        Subsystem::Simulate { from, to } => assert_eq!((from.timestamp(), to.timestamp()), (1473501600, 1473674400)),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "simulate"]).unwrap_err().kind, clap::ErrorKind::MissingRequiredArgument);
+}
+
+#[test]
+fn parse_from_encoding() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_encoding");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "prune-posted", "--older-than", "90 days", "--metrics", "m.csv", "--encoding", "CP1252"])
+        .unwrap()
+        .subsystem {
+        Subsystem::PrunePosted { encoding, .. } => assert_eq!(encoding, Some(Encoding::Windows1252)),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "prune-posted", "--older-than", "90 days", "--encoding", "utf-8"]).unwrap_err().kind,
+               clap::ErrorKind::MissingRequiredArgument);
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "prune-posted", "--older-than", "90 days", "--metrics", "m.csv", "--encoding", "latin-2"])
+                   .unwrap_err()
+                   .kind,
+               clap::ErrorKind::ValueValidation);
+}
+
+#[test]
+fn parse_from_older_than() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_older_than");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "prune-posted", "--older-than", "2 weeks"]).unwrap().subsystem {
+        Subsystem::PrunePosted { older_than, .. } => assert_eq!(older_than, 2 * 7 * 24 * 60 * 60),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "prune-posted", "--older-than", "1000000000000 weeks"]).unwrap_err().kind,
+               clap::ErrorKind::ValueValidation);
+}
+
+#[test]
+fn parse_from_prune_keep() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_prune_keep");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    match Options::parse_from(&["tweetr", "-c", &td, "prune", "--keep", "120000"]).unwrap().subsystem {
+        Subsystem::Prune { keep, .. } => assert_eq!(keep, Some(120000)),
+        subsystem => panic!("{:?}", subsystem),
+    }
+    for keep in &["100000000", "4294967295", "4294967296"] {
+        assert_eq!(Options::parse_from(&["tweetr", "-c", &td, "prune", "--keep", keep]).unwrap_err().kind, clap::ErrorKind::ValueValidation);
+    }
+}
+
+#[test]
+fn parse_from_json() {
+    let td = temp_dir().join("tweetr-test").join("options-parse_from_json");
+    fs::create_dir_all(&td).unwrap();
+    let td = td.display().to_string();
+
+    assert!(Options::parse_from(&["tweetr", "-c", &td, "--json", "validate"]).unwrap().json);
+    assert!(Options::parse_from(&["tweetr", "-c", &td, "validate", "--json"]).unwrap().json);
+    assert!(!Options::parse_from(&["tweetr", "-c", &td, "validate"]).unwrap().json);
+}
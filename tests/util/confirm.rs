@@ -0,0 +1,47 @@
+extern crate tweetr;
+
+use self::tweetr::util::{Confirmation, confirm, confirm_or};
+use std::io::Cursor;
+
+
+#[test]
+fn ask() {
+    let mut out = Vec::new();
+    assert!(confirm(Confirmation::Ask, &mut Cursor::new(b"Y\n"), &mut out, "Post them now? [y/N]"));
+    assert_eq!(String::from_utf8(out).unwrap(), "Post them now? [y/N]: \n");
+
+    assert!(!confirm(Confirmation::Ask, &mut Cursor::new(b"n\n"), &mut Vec::new(), "Post them now? [y/N]"));
+    assert!(!confirm(Confirmation::Ask, &mut Cursor::new(b"yes\n"), &mut Vec::new(), "Post them now? [y/N]"));
+    assert!(!confirm(Confirmation::Ask, &mut Cursor::new(b""), &mut Vec::new(), "Post them now? [y/N]"));
+}
+
+#[test]
+fn yes_doesnt_read() {
+    let mut input = Cursor::new(b"n\n");
+    assert!(confirm(Confirmation::Yes, &mut input, &mut Vec::new(), "Post them now? [y/N]"));
+    assert_eq!(input.position(), 0);
+}
+
+#[test]
+fn refuse_doesnt_read() {
+    let mut input = Cursor::new(b"y\n");
+    assert!(!confirm(Confirmation::Refuse, &mut input, &mut Vec::new(), "Post them now? [y/N]"));
+    assert_eq!(input.position(), 0);
+}
+
+#[test]
+fn not_terminal_doesnt_read() {
+    let mut input = Cursor::new(b"y\n");
+    assert!(!confirm(Confirmation::NotTerminal, &mut input, &mut Vec::new(), "Post them now? [y/N]"));
+    assert!(confirm_or(Confirmation::NotTerminal, true, &mut input, &mut Vec::new(), "Queue it anyway? [y/N]"));
+    assert_eq!(input.position(), 0);
+}
+
+#[test]
+fn policy() {
+    assert_eq!(Confirmation::new(false, false, true), Confirmation::Ask);
+    assert_eq!(Confirmation::new(true, false, true), Confirmation::Yes);
+    assert_eq!(Confirmation::new(true, false, false), Confirmation::Yes);
+    assert_eq!(Confirmation::new(false, true, true), Confirmation::Refuse);
+    assert_eq!(Confirmation::new(false, false, false), Confirmation::NotTerminal);
+}
@@ -0,0 +1,55 @@
+extern crate tweetr;
+
+use self::tweetr::util::encoding::{self, Encoding, Invalid};
+
+
+#[test]
+fn parse_aliases() {
+    assert_eq!(Encoding::parse("UTF8"), Ok(Encoding::Utf8));
+    assert_eq!(Encoding::parse("utf-16le"), Ok(Encoding::Utf16Le));
+    assert_eq!(Encoding::parse("UTF-16BE"), Ok(Encoding::Utf16Be));
+    assert_eq!(Encoding::parse("cp1252"), Ok(Encoding::Windows1252));
+    assert_eq!(Encoding::parse("latin-2"),
+               Err("\"latin-2\" is not one of utf-8, utf-16le, utf-16be or windows-1252".to_string()));
+}
+
+#[test]
+fn detect_bomless_utf16() {
+    assert_eq!(Encoding::detect(b"i\x00d\x00,\x00e\x00"), Encoding::Utf16Le);
+    assert_eq!(Encoding::detect(b"\x00i\x00d\x00,\x00e"), Encoding::Utf16Be);
+    assert_eq!(Encoding::detect(b"id,e"), Encoding::Utf8);
+}
+
+#[test]
+fn override_detection() {
+    assert_eq!(encoding::decode("Zażółć".as_bytes(), Some(Encoding::Windows1252)),
+               Ok(("ZaÅ¼Ã³Å‚Ä‡".to_string(), Encoding::Windows1252)));
+}
+
+#[test]
+fn windows1252_undefined() {
+    assert_eq!(encoding::decode(b"a\r\nb\x81", Some(Encoding::Windows1252)),
+               Err(vec![Invalid {
+                            offset: 4,
+                            line: 2,
+                            column: 2,
+                            bytes: vec![0x81],
+                            encoding: Encoding::Windows1252,
+                        }]));
+}
+
+#[test]
+fn utf16_unpaired_surrogate_offset_counts_bom() {
+    let (decoded, encoding, invalid) = encoding::decode_lossy(b"\xFF\xFEa\x00\n\x00\x00\xDCb\x00", None);
+    assert_eq!(decoded, "a\n\u{FFFD}b");
+    assert_eq!(encoding, Encoding::Utf16Le);
+    assert_eq!(invalid,
+               vec![Invalid {
+                        offset: 6,
+                        line: 2,
+                        column: 1,
+                        bytes: vec![0x00, 0xDC],
+                        encoding: Encoding::Utf16Le,
+                    }]);
+    assert_eq!(invalid[0].to_string(), "error: 2:1: invalid UTF-16LE sequence 0x00 0xDC at byte offset 6");
+}
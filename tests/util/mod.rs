@@ -2,3 +2,11 @@ mod prompt_nonzero_len;
 mod prompt_exact_len;
 mod prompt_multiline;
 mod prompt_any_len;
+mod parse_relative_time;
+mod parse_anchored_time;
+mod parse_time_shift;
+mod parse_unix_time;
+mod confirm;
+mod encoding;
+mod table;
+mod terminal;
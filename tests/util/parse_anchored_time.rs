@@ -0,0 +1,58 @@
+extern crate tweetr;
+
+use self::tweetr::util::{RelativeTimeError, parse_anchored_time};
+
+
+#[test]
+fn not_anchored() {
+    assert_eq!(parse_anchored_time("now"), None);
+    assert_eq!(parse_anchored_time("in 5 minutes"), None);
+    assert_eq!(parse_anchored_time("2016-09-10T12:00:00+02:00"), None);
+    assert_eq!(parse_anchored_time(""), None);
+}
+
+#[test]
+fn no_name() {
+    assert_eq!(parse_anchored_time("after"),
+               Some(Err(RelativeTimeError::BadSyntax { expected: "a tweet name after \"after\"" })));
+    assert_eq!(parse_anchored_time("5 minutes after"),
+               Some(Err(RelativeTimeError::BadSyntax { expected: "a tweet name after \"after\"" })));
+}
+
+#[test]
+fn trailing() {
+    assert_eq!(parse_anchored_time("after the launch"),
+               Some(Err(RelativeTimeError::BadSyntax { expected: "nothing after the tweet name" })));
+}
+
+#[test]
+fn no_number() {
+    assert_eq!(parse_anchored_time("five minutes after launch"),
+               Some(Err(RelativeTimeError::BadSyntax { expected: "a whole number before the unit of time" })));
+}
+
+#[test]
+fn unknown_unit() {
+    assert_eq!(parse_anchored_time("5 moments after launch"),
+               Some(Err(RelativeTimeError::UnknownUnit { got: "moments".to_string() })));
+}
+
+#[test]
+fn zero() {
+    assert_eq!(parse_anchored_time("after launch"), Some(Ok((0, "launch"))));
+    assert_eq!(parse_anchored_time("0 seconds after launch"), Some(Ok((0, "launch"))));
+}
+
+#[test]
+fn units() {
+    assert_eq!(parse_anchored_time("1 second after a"), Some(Ok((1, "a"))));
+    assert_eq!(parse_anchored_time("2 Minutes after a"), Some(Ok((2 * 60, "a"))));
+    assert_eq!(parse_anchored_time("3 hours AFTER a"), Some(Ok((3 * 60 * 60, "a"))));
+    assert_eq!(parse_anchored_time("1 day after a"), Some(Ok((60 * 60 * 24, "a"))));
+    assert_eq!(parse_anchored_time("2 weeks after a"), Some(Ok((2 * 60 * 60 * 24 * 7, "a"))));
+}
+
+#[test]
+fn name_kept_verbatim() {
+    assert_eq!(parse_anchored_time("after Launch-Day"), Some(Ok((0, "Launch-Day"))));
+}
@@ -0,0 +1,148 @@
+extern crate tweetr;
+
+use self::tweetr::util::{RelativeTimeError, parse_relative_time};
+use std::time::Duration;
+
+
+#[test]
+fn now_trailing() {
+    assert_eq!(parse_relative_time("now please"), Err(RelativeTimeError::BadSyntax { expected: "nothing after \"now\"" }));
+}
+
+#[test]
+fn not_relative() {
+    assert_eq!(parse_relative_time("tomorrow"), Err(RelativeTimeError::BadSyntax { expected: "\"now\" or \"in\"" }));
+    assert_eq!(parse_relative_time(""), Err(RelativeTimeError::BadSyntax { expected: "\"now\" or \"in\"" }));
+}
+
+#[test]
+fn no_number() {
+    assert_eq!(parse_relative_time("in"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"in\"" }));
+    assert_eq!(parse_relative_time("in -5 days"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"in\"" }));
+    assert_eq!(parse_relative_time("in 1.5 days"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"in\"" }));
+}
+
+#[test]
+fn seconds() {
+    assert_eq!(parse_relative_time("+0"), Ok(Duration::from_secs(0)));
+    assert_eq!(parse_relative_time("  +90 "), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_relative_time("+"), Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"+\"" }));
+    assert_eq!(parse_relative_time("+-5"), Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"+\"" }));
+    assert_eq!(parse_relative_time("+ 5"), Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"+\"" }));
+    assert_eq!(parse_relative_time("+5 seconds"), Err(RelativeTimeError::BadSyntax { expected: "nothing after the amount of seconds" }));
+    assert_eq!(parse_relative_time("+18446744073709551616"), Err(RelativeTimeError::NumberOverflow));
+}
+
+#[test]
+fn no_unit() {
+    assert_eq!(parse_relative_time("in 5"), Err(RelativeTimeError::BadSyntax { expected: "a unit of time after the number" }));
+}
+
+#[test]
+fn trailing() {
+    assert_eq!(parse_relative_time("in 5 days or so"), Err(RelativeTimeError::BadSyntax { expected: "nothing after the unit of time" }));
+}
+
+#[test]
+fn unknown_unit() {
+    assert_eq!(parse_relative_time("in 5 fortnights"), Err(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() }));
+    assert_eq!(parse_relative_time("in 5 MONTHS"), Err(RelativeTimeError::UnknownUnit { got: "MONTHS".to_string() }));
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(parse_relative_time("in 18446744073709551616 seconds"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_relative_time("in 18446744073709551615 weeks"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_relative_time("in 18446744073709551615 seconds"), Ok(Duration::from_secs(18446744073709551615)));
+}
+
+#[test]
+fn whitespace() {
+    assert_eq!(parse_relative_time("  in\t2   weeks "), Ok(Duration::from_secs(60 * 60 * 24 * 7 * 2)));
+}
+
+#[test]
+fn messages() {
+    assert_eq!(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() }.to_string(),
+               "unknown unit \"fortnights\", expected one of seconds, minutes, hours, days or weeks");
+    assert_eq!(RelativeTimeError::NumberOverflow.to_string(), "amount of time too large");
+    assert_eq!(RelativeTimeError::BadSyntax { expected: "a unit of time after the number" }.to_string(),
+               "expected a unit of time after the number");
+}
+
+#[test]
+fn iso8601() {
+    assert_eq!(parse_relative_time("in PT2H30M"), Ok(Duration::from_secs(60 * 60 * 2 + 60 * 30)));
+    assert_eq!(parse_relative_time("PT45M"), Ok(Duration::from_secs(60 * 45)));
+    assert_eq!(parse_relative_time("pt90s"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_relative_time("P2W"), Ok(Duration::from_secs(60 * 60 * 24 * 7 * 2)));
+    assert_eq!(parse_relative_time("In P1DT1H1M1S"), Ok(Duration::from_secs(60 * 60 * 24 + 60 * 60 + 60 + 1)));
+    assert_eq!(parse_relative_time("PT0S"), Ok(Duration::from_secs(0)));
+}
+
+#[test]
+fn iso8601_malformed() {
+    assert_eq!(parse_relative_time("PT"),
+               Err(RelativeTimeError::BadSyntax { expected: "an amount of time after each of \"P\" and \"T\" in the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("P1DT"),
+               Err(RelativeTimeError::BadSyntax { expected: "an amount of time after each of \"P\" and \"T\" in the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("PT45"),
+               Err(RelativeTimeError::BadSyntax { expected: "a designator after each number of the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("PT1.5H"),
+               Err(RelativeTimeError::BadSyntax { expected: "a whole number before each designator of the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("PT30M2H"),
+               Err(RelativeTimeError::BadSyntax { expected: "the designators of the ISO 8601 duration in order, each once" }));
+    assert_eq!(parse_relative_time("PT1H1H"),
+               Err(RelativeTimeError::BadSyntax { expected: "the designators of the ISO 8601 duration in order, each once" }));
+    assert_eq!(parse_relative_time("in PT45M please"),
+               Err(RelativeTimeError::BadSyntax { expected: "nothing after the ISO 8601 duration" }));
+}
+
+#[test]
+fn iso8601_unsupported() {
+    assert_eq!(parse_relative_time("P1Y"),
+               Err(RelativeTimeError::BadSyntax { expected: "only weeks and days before \"T\" in the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("P3M"),
+               Err(RelativeTimeError::BadSyntax { expected: "only weeks and days before \"T\" in the ISO 8601 duration" }));
+    assert_eq!(parse_relative_time("P1H"), Err(RelativeTimeError::UnknownUnit { got: "H".to_string() }));
+    assert_eq!(parse_relative_time("PT1D"), Err(RelativeTimeError::UnknownUnit { got: "D".to_string() }));
+}
+
+#[test]
+fn iso8601_overflow() {
+    assert_eq!(parse_relative_time("PT18446744073709551616S"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_relative_time("P31000000000000W"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_relative_time("PT18446744073709551615S"), Ok(Duration::from_secs(18446744073709551615)));
+    assert_eq!(parse_relative_time("PT1M18446744073709551615S"), Err(RelativeTimeError::NumberOverflow));
+}
+
+#[test]
+fn combined() {
+    assert_eq!(parse_relative_time("in 1 hour 30 minutes"), Ok(Duration::from_secs(60 * 90)));
+    assert_eq!(parse_relative_time("In 2 days 4 HOURS 1 second"), Ok(Duration::from_secs(60 * 60 * 52 + 1)));
+    assert_eq!(parse_relative_time("in 1 week and 1 day"), Ok(Duration::from_secs(60 * 60 * 24 * 8)));
+    assert_eq!(parse_relative_time("in 1 hour 30 minutes and 15 seconds"), Ok(Duration::from_secs(60 * 90 + 15)));
+}
+
+#[test]
+fn combined_any_order() {
+    assert_eq!(parse_relative_time("in 30 minutes 1 hour"), parse_relative_time("in 1 hour 30 minutes"));
+    assert_eq!(parse_relative_time("in 4 hours and 2 days"), parse_relative_time("in 2 days and 4 hours"));
+}
+
+#[test]
+fn combined_repeated_units() {
+    assert_eq!(parse_relative_time("in 1 hour 1 hour"), Ok(Duration::from_secs(60 * 60 * 2)));
+    assert_eq!(parse_relative_time("in 45 minutes and 45 minutes"), Ok(Duration::from_secs(60 * 90)));
+}
+
+#[test]
+fn combined_errors() {
+    assert_eq!(parse_relative_time("in 1 hour 30"), Err(RelativeTimeError::BadSyntax { expected: "a unit of time after the number" }));
+    assert_eq!(parse_relative_time("in 1 hour and"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"and\"" }));
+    assert_eq!(parse_relative_time("in 1 hour and half"), Err(RelativeTimeError::BadSyntax { expected: "a whole number after \"and\"" }));
+    assert_eq!(parse_relative_time("in 1 hour 1.5 minutes"),
+               Err(RelativeTimeError::BadSyntax { expected: "a whole number after the unit of time" }));
+    assert_eq!(parse_relative_time("in 1 hour 30 fortnights"), Err(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() }));
+    assert_eq!(parse_relative_time("in 18446744073709551615 seconds 1 second"), Err(RelativeTimeError::NumberOverflow));
+}
@@ -0,0 +1,43 @@
+extern crate tweetr;
+
+use self::tweetr::util::{RelativeTimeError, parse_time_shift};
+
+
+#[test]
+fn later() {
+    assert_eq!(parse_time_shift("90 seconds"), Ok(90));
+    assert_eq!(parse_time_shift("1 Week"), Ok(60 * 60 * 24 * 7));
+    assert_eq!(parse_time_shift("  2 hours  "), Ok(2 * 60 * 60));
+}
+
+#[test]
+fn earlier() {
+    assert_eq!(parse_time_shift("-90 seconds"), Ok(-90));
+    assert_eq!(parse_time_shift("- 2 days"), Ok(-2 * 60 * 60 * 24));
+}
+
+#[test]
+fn no_unit() {
+    assert_eq!(parse_time_shift("5"), Err(RelativeTimeError::BadSyntax { expected: "a unit of time after the number" }));
+}
+
+#[test]
+fn unknown_unit() {
+    assert_eq!(parse_time_shift("5 fortnights"), Err(RelativeTimeError::UnknownUnit { got: "fortnights".to_string() }));
+}
+
+#[test]
+fn bad_number() {
+    assert_eq!(parse_time_shift("+5 hours"),
+               Err(RelativeTimeError::BadSyntax { expected: "a whole number, optionally preceded by \"-\"" }));
+    assert_eq!(parse_time_shift("--5 hours"),
+               Err(RelativeTimeError::BadSyntax { expected: "a whole number, optionally preceded by \"-\"" }));
+    assert_eq!(parse_time_shift(""), Err(RelativeTimeError::BadSyntax { expected: "a whole number, optionally preceded by \"-\"" }));
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(parse_time_shift("99999999999999999999 seconds"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_time_shift("9223372036854775808 seconds"), Err(RelativeTimeError::NumberOverflow));
+    assert_eq!(parse_time_shift("-9223372036854775807 seconds"), Ok(-9223372036854775807));
+}
@@ -0,0 +1,33 @@
+extern crate tweetr;
+
+use self::tweetr::util::{RelativeTimeError, parse_unix_time};
+
+
+#[test]
+fn not_unix() {
+    assert_eq!(parse_unix_time("2016-09-10T12:00:00+02:00"), None);
+    assert_eq!(parse_unix_time("1473501600"), None);
+    assert_eq!(parse_unix_time(""), None);
+}
+
+#[test]
+fn negative() {
+    assert_eq!(parse_unix_time("@-1"), Some(Ok(-1)));
+    assert_eq!(parse_unix_time("@-9223372036854775807"), Some(Ok(-9223372036854775807)));
+}
+
+#[test]
+fn bad_number() {
+    for time in &["@", "@-", "@+5", "@1.5", "@ 1473501600", "@1473501600 UTC"] {
+        assert_eq!(parse_unix_time(time),
+                   Some(Err(RelativeTimeError::BadSyntax { expected: "a whole number of seconds after \"@\"" })),
+                   "input: {:?}",
+                   time);
+    }
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(parse_unix_time("@9223372036854775808"), Some(Err(RelativeTimeError::NumberOverflow)));
+    assert_eq!(parse_unix_time("@9223372036854775807"), Some(Ok(9223372036854775807)));
+}
@@ -1,2 +1,3 @@
 mod accept_all;
+mod options;
 mod verifier;
@@ -0,0 +1,57 @@
+extern crate tweetr;
+
+use self::tweetr::util::{MultilineOptions, prompt_multiline};
+use std::io::Cursor;
+
+
+#[test]
+fn line_numbers() {
+    let mut out = Vec::new();
+    let result = prompt_multiline(&mut Cursor::new(b"0123456789\\\n9876543210\\\n!\n"),
+                                  &mut out,
+                                  "Allowed chars",
+                                  |_| true,
+                                  MultilineOptions {
+                                      line_numbers: true,
+                                      char_count: false,
+                                  })
+    .unwrap();
+
+    assert_eq!(result, "0123456789\n9876543210\n!".to_string());
+    assert_eq!(String::from_utf8(out).unwrap(), "Allowed chars:            2 |            3 | ".to_string());
+}
+
+#[test]
+fn char_count() {
+    let mut out = Vec::new();
+    let result = prompt_multiline(&mut Cursor::new(b"0123456789\\\n9876543210\\\n!\n"),
+                                  &mut out,
+                                  "Allowed chars",
+                                  |_| true,
+                                  MultilineOptions {
+                                      line_numbers: false,
+                                      char_count: true,
+                                  })
+    .unwrap();
+
+    assert_eq!(result, "0123456789\n9876543210\n!".to_string());
+    assert_eq!(String::from_utf8(out).unwrap(), "Allowed chars:           [11]           [22] ".to_string());
+}
+
+#[test]
+fn line_numbers_and_char_count_restart() {
+    let mut out = Vec::new();
+    let result = prompt_multiline(&mut Cursor::new(b"0\\\n1\n0123456789\\\n!\n"),
+                                  &mut out,
+                                  "Allowed chars",
+                                  |s| s.contains('!'),
+                                  MultilineOptions {
+                                      line_numbers: true,
+                                      char_count: true,
+                                  })
+    .unwrap();
+
+    assert_eq!(result, "0123456789\n!".to_string());
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Allowed chars:        [2] 2 | Allowed chars:       [11] 2 | ".to_string());
+}
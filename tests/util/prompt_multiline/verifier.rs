@@ -1,7 +1,7 @@
 mod single_line {
     extern crate tweetr;
 
-    use self::tweetr::util::prompt_multiline;
+    use self::tweetr::util::{MultilineOptions, prompt_multiline};
     use std::iter::FromIterator;
     use std::io::Cursor;
 
@@ -9,7 +9,7 @@ mod single_line {
     #[test]
     fn optimistic() {
         let mut out = Vec::new();
-        let result = prompt_multiline(&mut Cursor::new(b"0!1\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap();
+        let result = prompt_multiline(&mut Cursor::new(b"0!1\n"), &mut out, "Allowed chars", |s| s.contains('!'), MultilineOptions::default()).unwrap();
 
         assert_eq!(result, "0!1".to_string());
         assert_eq!(out, Vec::from_iter(b"Allowed chars: ".iter().cloned()));
@@ -18,14 +18,14 @@ mod single_line {
     #[test]
     fn bad() {
         let mut out = Vec::new();
-        prompt_multiline(&mut Cursor::new(b"01\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap_err();
+        prompt_multiline(&mut Cursor::new(b"01\n"), &mut out, "Allowed chars", |s| s.contains('!'), MultilineOptions::default()).unwrap_err();
         assert_eq!(String::from_iter(out.iter().map(|&i| i as char)), "Allowed chars: Allowed chars: ".to_string());
     }
 
     #[test]
     fn bad_but_then_ok() {
         let mut out = Vec::new();
-        let result = prompt_multiline(&mut Cursor::new(b"01\n0!1\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap();
+        let result = prompt_multiline(&mut Cursor::new(b"01\n0!1\n"), &mut out, "Allowed chars", |s| s.contains('!'), MultilineOptions::default()).unwrap();
 
         assert_eq!(result, "0!1".to_string());
         assert_eq!(String::from_iter(out.iter().map(|&i| i as char)), "Allowed chars: Allowed chars: ".to_string());
@@ -35,7 +35,7 @@ mod single_line {
 mod multi_line {
     extern crate tweetr;
 
-    use self::tweetr::util::prompt_multiline;
+    use self::tweetr::util::{MultilineOptions, prompt_multiline};
     use std::iter::FromIterator;
     use std::io::Cursor;
 
@@ -43,7 +43,7 @@ mod multi_line {
     #[test]
     fn optimistic() {
         let mut out = Vec::new();
-        let result = prompt_multiline(&mut Cursor::new(b"0\\\n!\\\n1\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap();
+        let result = prompt_multiline(&mut Cursor::new(b"0\\\n!\\\n1\n"), &mut out, "Allowed chars", |s| s.contains('!'), MultilineOptions::default()).unwrap();
 
         assert_eq!(result, "0\n!\n1".to_string());
         assert_eq!(out, Vec::from_iter(b"Allowed chars:                               ".iter().cloned()));
@@ -52,7 +52,7 @@ mod multi_line {
     #[test]
     fn bad() {
         let mut out = Vec::new();
-        prompt_multiline(&mut Cursor::new(b"0\\\n1\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap_err();
+        prompt_multiline(&mut Cursor::new(b"0\\\n1\n"), &mut out, "Allowed chars", |s| s.contains('!'), MultilineOptions::default()).unwrap_err();
         assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
                    "Allowed chars:                Allowed chars: ".to_string());
     }
@@ -60,7 +60,12 @@ mod multi_line {
     #[test]
     fn bad_but_then_ok() {
         let mut out = Vec::new();
-        let result = prompt_multiline(&mut Cursor::new(b"0\\\n1\n0\\\n!\\\n1\\\\\n"), &mut out, "Allowed chars", |s| s.contains('!')).unwrap();
+        let result = prompt_multiline(&mut Cursor::new(b"0\\\n1\n0\\\n!\\\n1\\\\\n"),
+                                      &mut out,
+                                      "Allowed chars",
+                                      |s| s.contains('!'),
+                                      MultilineOptions::default())
+            .unwrap();
 
         assert_eq!(result, "0\n!\n1\\".to_string());
         assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
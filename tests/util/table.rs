@@ -0,0 +1,56 @@
+extern crate tweetr;
+
+use self::tweetr::util::table::{Column, Table, truncate};
+
+
+#[test]
+fn truncate_ellipsis_counted() {
+    assert_eq!(truncate("Abolish", 7), "Abolish");
+    assert_eq!(truncate("Abolish", 6), "Aboli…");
+    assert_eq!(truncate("Abolish", 1), "…");
+}
+
+#[test]
+fn missing_cells_empty() {
+    let mut table = Table::new(vec![Column::new("Author"), Column::new("ID").right(), Column::new("Content")]);
+    table.row(vec!["nabijaczleweli".to_string()]);
+    assert_eq!(table.rows, vec![vec!["nabijaczleweli".to_string(), String::new(), String::new()]]);
+
+    let mut out = Vec::new();
+    table.write(&mut out);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "Author          ID  Content\n\
+                nabijaczleweli\n");
+}
+
+#[test]
+fn unshrinkable_overflow() {
+    let mut table = Table::new(vec![Column::new("Author"), Column::new("Content")]);
+    table.row(vec!["nabijaczleweli".to_string(), "Abolish the bourgeoisie".to_string()]);
+    table.max_width = Some(20);
+
+    assert_eq!(table.widths(), vec![14, 23]);
+}
+
+#[test]
+fn shrunk_widest_first() {
+    let mut table = Table::new(vec![Column::new("Backends").shrinkable(), Column::new("Tokens").shrinkable()]);
+    table.row(vec!["Twitter, Telegram chat @tweetr_announcements".to_string(), "valid".to_string()]);
+    table.row(vec!["Twitter".to_string(), "invalid: Twitter API error: Invalid or expired token. (code 89)".to_string()]);
+    table.max_width = Some(42);
+
+    assert_eq!(table.widths(), vec![20, 20]);
+}
+
+#[test]
+fn borders_empty() {
+    let mut table = Table::new(vec![Column::new("Author"), Column::new("Content")]);
+    table.borders = true;
+
+    let mut out = Vec::new();
+    table.write(&mut out);
+    assert_eq!(String::from_utf8(out).unwrap(),
+               "+--------+---------+\n\
+                | Author | Content |\n\
+                +--------+---------+\n");
+}
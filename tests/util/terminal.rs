@@ -0,0 +1,33 @@
+extern crate tweetr;
+
+use self::tweetr::util::terminal::wrap;
+use self::tweetr::util::MultilineOptions;
+
+
+#[test]
+fn wrap_exact_fit() {
+    assert_eq!(wrap("Abolish the", 11), vec!["Abolish the"]);
+    assert_eq!(wrap("Abolish the", 10), vec!["Abolish", "the"]);
+}
+
+#[test]
+fn wrap_collapses_spaces() {
+    assert_eq!(wrap("Abolish   the  bourgeoisie", 12), vec!["Abolish the", "bourgeoisie"]);
+}
+
+#[test]
+fn wrap_zero_width() {
+    assert_eq!(wrap("Abolish the\nbourgeoisie", 0), vec!["Abolish the", "bourgeoisie"]);
+}
+
+#[test]
+fn reprompt_aligned_with_last_line() {
+    let options = MultilineOptions {
+        line_numbers: true,
+        char_count: true,
+    };
+    assert_eq!(options.reprompt_wrapped("Tweet content to queue", 2, "Abolish\n", Some(18)),
+               "[8] 2 | ");
+    assert_eq!(options.reprompt_wrapped("Tweet content to queue", 2, "Abolish\n", Some(80)),
+               options.reprompt("Tweet content to queue", 2, "Abolish\n"));
+}